@@ -0,0 +1,112 @@
+//! Benchmarks for the renderer and simulation hot paths.
+//!
+//! Run with `cargo bench`. The runtime's static storage can be claimed once
+//! per process, so every benchmark shares one `Runtime`.
+
+use std::hint::black_box;
+
+use bit_bound::game::assets::SpriteId;
+use bit_bound::game::enemy::Enemy;
+use bit_bound::game::entities::EnemyKind;
+use bit_bound::game::fixed_pool::FixedPool;
+use bit_bound::game::prefab::PrefabId;
+use bit_bound::game::{GameState, render, update};
+use bit_bound::input::Input;
+use bit_bound::math::geom::{FIXED_ONE, Vec2i};
+use bit_bound::renderer::framebuffer::{HEIGHT, WIDTH};
+use bit_bound::runtime::Runtime;
+use criterion::{Criterion, criterion_group, criterion_main};
+
+/// A state with every enemy slot filled, spread over the screen.
+fn crowded_state() -> GameState {
+    let mut state = GameState::new();
+    let capacity = state.sim.enemies.capacity();
+
+    for i in 0..capacity {
+        let _ = state.sim.spawn_prefab(
+            PrefabId::Enemy(EnemyKind::DRONE),
+            Vec2i::new((WIDTH - 1 - i * 4) as i32, (i * HEIGHT / capacity) as i32),
+        );
+    }
+
+    state
+}
+
+fn benches(c: &mut Criterion) {
+    let Runtime {
+        memory,
+        framebuffer,
+    } = Runtime::take().expect("runtime already claimed");
+
+    let mut rgba = vec![0u32; WIDTH * HEIGHT];
+
+    c.bench_function("to_rgba_buffer", |b| {
+        b.iter(|| framebuffer.to_rgba_buffer(black_box(&mut rgba)))
+    });
+
+    c.bench_function("draw_sprite", |b| {
+        b.iter(|| {
+            framebuffer.draw_sprite(
+                black_box(40),
+                black_box(60),
+                SpriteId::PLAYER_FRAME_1.sprite(),
+            )
+        })
+    });
+
+    c.bench_function("draw_sprite_clipped", |b| {
+        b.iter(|| {
+            framebuffer.draw_sprite(
+                black_box(-20),
+                black_box(-8),
+                SpriteId::PLAYER_FRAME_1.sprite(),
+            )
+        })
+    });
+
+    c.bench_function("draw_text", |b| {
+        b.iter(|| framebuffer.draw_text(2, 2, black_box("THE QUICK BROWN FOX 0123456789"), 3))
+    });
+
+    c.bench_function("update_max_entities", |b| {
+        b.iter_batched_ref(
+            crowded_state,
+            |state| {
+                update(state, Input::empty(), memory);
+                memory.frame.reset();
+            },
+            criterion::BatchSize::SmallInput,
+        )
+    });
+
+    c.bench_function("render_max_entities", |b| {
+        let state = crowded_state();
+        b.iter(|| render(black_box(&state), framebuffer))
+    });
+
+    // A stress-level pool; parallel with `--features threads`.
+    c.bench_function("pool_update_4096", |b| {
+        let mut pool = Box::new(FixedPool::<Enemy, 4096>::new(Enemy::new(
+            EnemyKind::DRONE,
+            Vec2i::ZERO,
+        )));
+
+        for i in 0..pool.capacity() {
+            let mut enemy = Enemy::new(EnemyKind::DRONE, Vec2i::new(i as i32, (i % HEIGHT) as i32));
+            enemy.body.set_velocity(Vec2i::new(1 - (i % 3) as i32, 0));
+
+            let _ = pool.spawn(enemy);
+        }
+
+        b.iter(|| {
+            pool.update_each(|enemy| {
+                enemy.body.integrate();
+                enemy.body.pos.y = black_box(enemy.body.pos.y * 31 + enemy.body.pos.x)
+                    .rem_euclid(HEIGHT as i32 * FIXED_ONE);
+            })
+        })
+    });
+}
+
+criterion_group!(hot_paths, benches);
+criterion_main!(hot_paths);
@@ -0,0 +1,133 @@
+//! The game on a Raspberry Pi Pico with a 240x240 ST7789 on SPI0 and eight
+//! buttons to ground.
+//!
+//! | Signal                | Pin              |
+//! |-----------------------|------------------|
+//! | LCD SCK, MOSI         | GP18, GP19       |
+//! | LCD CS, DC, RST       | GP17, GP16, GP20 |
+//! | Up, Down, Left, Right | GP2 – GP5        |
+//! | A, B, Start, Select   | GP6 – GP9        |
+//!
+//! ```text
+//! rustup target add thumbv6m-none-eabi
+//! cargo install elf2uf2-rs
+//! cd boards/rp2040 && cargo run --release
+//! ```
+//!
+//! Memory is the `profile_tiny` envelope; there is no sound.
+
+#![no_std]
+#![no_main]
+
+use bit_bound::game::{GameState, render, update};
+use bit_bound::input::Input;
+use bit_bound::platform::lcd::{LcdConfig, SpiLcd};
+use bit_bound::runtime::Runtime;
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::{InputPin, OutputPin};
+use embedded_hal_bus::spi::ExclusiveDevice;
+use panic_halt as _;
+use rp2040_hal::fugit::RateExtU32;
+use rp2040_hal::{self as hal, Clock, Sio, Spi, Timer, Watchdog, gpio, pac};
+
+#[unsafe(link_section = ".boot2")]
+#[used]
+static BOOT2: [u8; 256] = rp2040_boot2::BOOT_LOADER_W25Q080;
+
+const XTAL_FREQ_HZ: u32 = 12_000_000;
+
+/// Microseconds per game frame.
+const FRAME_US: u64 = 16_667;
+
+#[hal::entry]
+fn main() -> ! {
+    let mut pac = pac::Peripherals::take().unwrap();
+    let mut watchdog = Watchdog::new(pac.WATCHDOG);
+    let clocks = hal::clocks::init_clocks_and_plls(
+        XTAL_FREQ_HZ,
+        pac.XOSC,
+        pac.CLOCKS,
+        pac.PLL_SYS,
+        pac.PLL_USB,
+        &mut pac.RESETS,
+        &mut watchdog,
+    )
+    .ok()
+    .unwrap();
+
+    let sio = Sio::new(pac.SIO);
+    let pins = gpio::Pins::new(
+        pac.IO_BANK0,
+        pac.PADS_BANK0,
+        sio.gpio_bank0,
+        &mut pac.RESETS,
+    );
+    let mut timer = Timer::new(pac.TIMER, &mut pac.RESETS, &clocks);
+
+    // Claim before anything else could: see `StaticCell::take`.
+    let Runtime {
+        memory,
+        framebuffer,
+    } = Runtime::take().unwrap();
+
+    let sck = pins.gpio18.into_function::<gpio::FunctionSpi>();
+    let mosi = pins.gpio19.into_function::<gpio::FunctionSpi>();
+    let bus = Spi::<_, _, _, 8>::new(pac.SPI0, (mosi, sck)).init(
+        &mut pac.RESETS,
+        clocks.peripheral_clock.freq(),
+        62_500_000u32.Hz(),
+        embedded_hal::spi::MODE_3,
+    );
+    let spi = ExclusiveDevice::new(bus, pins.gpio17.into_push_pull_output(), timer).unwrap();
+    let dc = pins.gpio16.into_push_pull_output();
+
+    let mut reset = pins.gpio20.into_push_pull_output();
+    reset.set_low().unwrap();
+    timer.delay_ms(10);
+    reset.set_high().unwrap();
+    timer.delay_ms(120);
+
+    let mut lcd = SpiLcd::new(spi, dc, LcdConfig::ST7789_240X240);
+    lcd.init(&mut timer).unwrap();
+
+    let mut buttons = [
+        (pins.gpio2.into_pull_up_input().into_dyn_pin(), Input::UP),
+        (pins.gpio3.into_pull_up_input().into_dyn_pin(), Input::DOWN),
+        (pins.gpio4.into_pull_up_input().into_dyn_pin(), Input::LEFT),
+        (pins.gpio5.into_pull_up_input().into_dyn_pin(), Input::RIGHT),
+        (pins.gpio6.into_pull_up_input().into_dyn_pin(), Input::A),
+        (pins.gpio7.into_pull_up_input().into_dyn_pin(), Input::B),
+        (pins.gpio8.into_pull_up_input().into_dyn_pin(), Input::START),
+        (
+            pins.gpio9.into_pull_up_input().into_dyn_pin(),
+            Input::SELECT,
+        ),
+    ];
+
+    let mut state = GameState::new();
+
+    loop {
+        let start = timer.get_counter();
+        let mut input = Input::empty();
+
+        for (pin, button) in &mut buttons {
+            if pin.is_low().unwrap() {
+                input.insert(*button);
+            }
+        }
+
+        update(&mut state, input, memory);
+        memory.frame.reset();
+
+        render(&state, framebuffer);
+
+        // A failed transfer only loses this frame.
+        let _ = lcd.present(framebuffer);
+
+        let elapsed = (timer.get_counter() - start).to_micros();
+
+        if elapsed < FRAME_US {
+            timer.delay_us((FRAME_US - elapsed) as u32);
+        }
+    }
+}
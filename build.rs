@@ -0,0 +1,859 @@
+//! Generates `$OUT_DIR/assets.rs` from `assets/assets.manifest`, which
+//! `game::assets` includes, and `$OUT_DIR/entities.rs` from
+//! `assets/entities.toml`, which `game::entities` includes.
+//!
+//! Only converted files from `assets/processed/` are read: conversion stays
+//! in `spritec` (ADR 0011), and this script has no dependencies (ADR 0019).
+//! Every listed file is checked here, so a missing or stale asset fails the
+//! build with the manifest line that names it. The entity definitions are
+//! text in a small subset of TOML, parsed here the same way (ADR 0026).
+
+use std::collections::HashSet;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const MANIFEST: &str = "assets/assets.manifest";
+const PROCESSED: &str = "assets/processed";
+const ENTITIES: &str = "assets/entities.toml";
+
+/// Bytes per 8x8 tile of 2bpp data.
+const TILE_BYTES: u64 = 16;
+
+enum Kind {
+    Sprite {
+        path: PathBuf,
+        width: usize,
+        height: usize,
+    },
+    Data {
+        path: PathBuf,
+    },
+    /// Frames as sprite names and how many ticks each is shown.
+    Animation {
+        frames: Vec<(String, u32)>,
+    },
+    /// The `.tiles` and `.map` of a `spritec --tilemap` conversion.
+    TileMap {
+        tiles: PathBuf,
+        map: PathBuf,
+        width: usize,
+        height: usize,
+    },
+}
+
+struct Asset {
+    kind: Kind,
+    name: String,
+    line: usize,
+}
+
+impl Asset {
+    /// The converted files this asset embeds.
+    fn paths(&self) -> Vec<&Path> {
+        match &self.kind {
+            Kind::Sprite { path, .. } | Kind::Data { path } => vec![path],
+            Kind::TileMap { tiles, map, .. } => vec![tiles, map],
+            Kind::Animation { .. } => Vec::new(),
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let root = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").expect("set by cargo"));
+    let out = PathBuf::from(env::var_os("OUT_DIR").expect("set by cargo"));
+
+    println!("cargo:rerun-if-changed={MANIFEST}");
+
+    let assets = match parse(&root) {
+        Ok(assets) => assets,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for path in assets.iter().flat_map(Asset::paths) {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+
+    println!("cargo:rerun-if-changed={ENTITIES}");
+
+    let entities = match parse_entities(&root, &assets) {
+        Ok(entities) => entities,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    for (file, code) in [
+        ("assets.rs", generate(&assets)),
+        ("entities.rs", generate_entities(&entities)),
+    ] {
+        let path = out.join(file);
+
+        if let Err(err) = fs::write(&path, code) {
+            eprintln!("error: {}: {err}", path.display());
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
+
+/// Read and validate the manifest. Errors name the offending line.
+fn parse(root: &Path) -> Result<Vec<Asset>, String> {
+    let text =
+        fs::read_to_string(root.join(MANIFEST)).map_err(|err| format!("{MANIFEST}: {err}"))?;
+    let mut assets = Vec::new();
+    let mut names = HashSet::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let at = |message: String| format!("{MANIFEST}:{}: {message}", number + 1);
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        let (kind, name) = match fields.as_slice() {
+            ["sprite", name, file, size] => {
+                let (width, height): (usize, usize) = size
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0)
+                    .ok_or_else(|| at(format!("invalid size `{size}`, expected WxH")))?;
+
+                let (path, len) = processed(root, file).map_err(at)?;
+                let expected = (width.div_ceil(8) * height.div_ceil(8)) as u64 * TILE_BYTES;
+
+                if len != expected {
+                    return Err(at(format!(
+                        "{file} has {len} bytes, but a {width}x{height} sprite needs {expected}"
+                    )));
+                }
+
+                (
+                    Kind::Sprite {
+                        path,
+                        width,
+                        height,
+                    },
+                    name,
+                )
+            }
+            ["data", name, file] => {
+                let (path, _) = processed(root, file).map_err(at)?;
+                (Kind::Data { path }, name)
+            }
+            ["tilemap", name, stem, size] => {
+                let (width, height): (usize, usize) = size
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0)
+                    .ok_or_else(|| at(format!("invalid size `{size}`, expected WxH in tiles")))?;
+
+                let (tiles, tiles_len) = processed(root, &format!("{stem}.tiles")).map_err(at)?;
+                let (map, _) = processed(root, &format!("{stem}.map")).map_err(at)?;
+                let cells =
+                    fs::read(&map).map_err(|err| at(format!("{}: {err}", map.display())))?;
+                let tile_count = tiles_len / TILE_BYTES;
+
+                if tiles_len % TILE_BYTES != 0 {
+                    return Err(at(format!("{stem}.tiles is not whole 8x8 tiles")));
+                }
+
+                if cells.len() != width * height * 2 {
+                    return Err(at(format!(
+                        "{stem}.map has {} bytes, but a {width}x{height} map needs {}",
+                        cells.len(),
+                        width * height * 2
+                    )));
+                }
+
+                if let Some(index) = cells
+                    .chunks(2)
+                    .map(|cell| cell[0] as u64)
+                    .find(|&i| i >= tile_count)
+                {
+                    return Err(at(format!(
+                        "{stem}.map uses tile {index}, but {stem}.tiles has {tile_count}"
+                    )));
+                }
+
+                (
+                    Kind::TileMap {
+                        tiles,
+                        map,
+                        width,
+                        height,
+                    },
+                    name,
+                )
+            }
+            ["anim", name, frames @ ..] if !frames.is_empty() => {
+                let frames = frames
+                    .iter()
+                    .map(|frame| {
+                        frame
+                            .split_once(':')
+                            .and_then(|(sprite, ticks)| {
+                                Some((sprite.to_string(), ticks.parse().ok()?))
+                            })
+                            .filter(|&(_, ticks)| ticks > 0)
+                            .ok_or_else(|| {
+                                at(format!("invalid frame `{frame}`, expected SPRITE:ticks"))
+                            })
+                    })
+                    .collect::<Result<_, _>>()?;
+
+                (Kind::Animation { frames }, name)
+            }
+            _ => {
+                return Err(at(
+                    "expected `sprite <NAME> <file> <WxH>`, `data <NAME> <file>`, \
+                     `anim <NAME> <SPRITE>:<ticks>...` or `tilemap <NAME> <name> <WxH>`"
+                        .to_string(),
+                ));
+            }
+        };
+
+        let valid_name = name.starts_with(|c: char| c.is_ascii_uppercase())
+            && name
+                .chars()
+                .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_');
+
+        if !valid_name {
+            return Err(at(format!("`{name}` is not an UPPER_SNAKE_CASE name")));
+        }
+
+        if !names.insert(name.to_string()) {
+            return Err(at(format!("`{name}` is listed twice")));
+        }
+
+        assets.push(Asset {
+            kind,
+            name: name.to_string(),
+            line: number + 1,
+        });
+    }
+
+    // Animations may name sprites listed after them.
+    for asset in &assets {
+        if let Kind::Animation { frames } = &asset.kind {
+            for (sprite, _) in frames {
+                let is_sprite = assets.iter().any(|other| {
+                    other.name == *sprite && matches!(other.kind, Kind::Sprite { .. })
+                });
+
+                if !is_sprite {
+                    return Err(format!(
+                        "{MANIFEST}:{}: `{sprite}` is not a sprite in the manifest",
+                        asset.line
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(assets)
+}
+
+/// The path and size of `file` in `assets/processed/`.
+fn processed(root: &Path, file: &str) -> Result<(PathBuf, u64), String> {
+    let path = root.join(PROCESSED).join(file);
+
+    match fs::metadata(&path) {
+        Ok(metadata) => Ok((path, metadata.len())),
+        Err(err) => Err(format!(
+            "{}: {err} (run `cargo run -p spritec` to convert the assets)",
+            path.display()
+        )),
+    }
+}
+
+fn generate(assets: &[Asset]) -> String {
+    let sprites: Vec<&Asset> = assets
+        .iter()
+        .filter(|asset| matches!(asset.kind, Kind::Sprite { .. }))
+        .collect();
+
+    let mut code = String::new();
+    let _ = writeln!(
+        code,
+        "// Generated by build.rs from {MANIFEST}. Do not edit.\n"
+    );
+
+    code.push_str(
+        "/// A sprite listed in `assets/assets.manifest`.\n\
+         #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n\
+         pub struct SpriteId(u16);\n\n\
+         impl SpriteId {\n",
+    );
+
+    for (index, sprite) in sprites.iter().enumerate() {
+        let _ = writeln!(code, "    pub const {}: Self = Self({index});", sprite.name);
+    }
+
+    let _ = writeln!(
+        code,
+        "\n    /// Every sprite, in manifest order.\n    \
+         pub const ALL: [Self; {}] = [{}];\n",
+        sprites.len(),
+        sprites
+            .iter()
+            .map(|sprite| format!("Self::{}", sprite.name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    code.push_str(
+        "    pub const fn sprite(self) -> &'static crate::renderer::sprite::Sprite {\n        \
+         &SPRITES[self.0 as usize]\n    }\n\n    \
+         /// The name this sprite has in the manifest.\n    \
+         pub const fn name(self) -> &'static str {\n        \
+         SPRITE_NAMES[self.0 as usize]\n    }\n}\n\n",
+    );
+
+    let _ = writeln!(
+        code,
+        "static SPRITES: [crate::renderer::sprite::Sprite; {}] = [",
+        sprites.len()
+    );
+
+    for sprite in &sprites {
+        if let Kind::Sprite {
+            path,
+            width,
+            height,
+        } = &sprite.kind
+        {
+            let _ = writeln!(
+                code,
+                "    crate::renderer::sprite::Sprite {{ width: {width}, height: {height}, tiles_x: {}, tiles_y: {}, data: include_bytes!({:?}) }},",
+                width.div_ceil(8),
+                height.div_ceil(8),
+                path.display().to_string()
+            );
+        }
+    }
+
+    let _ = writeln!(code, "];\n");
+    let _ = writeln!(code, "const SPRITE_NAMES: [&str; {}] = [", sprites.len());
+
+    for sprite in &sprites {
+        let _ = writeln!(code, "    {:?},", sprite.name);
+    }
+
+    let _ = writeln!(code, "];");
+
+    for asset in assets {
+        match &asset.kind {
+            Kind::Sprite { .. } => {}
+            Kind::TileMap {
+                tiles,
+                map,
+                width,
+                height,
+            } => {
+                let _ = writeln!(
+                    code,
+                    "\npub const {}: crate::renderer::tilemap::TileMap = crate::renderer::tilemap::TileMap {{ width: {width}, height: {height}, tiles: include_bytes!({:?}), map: include_bytes!({:?}) }};",
+                    asset.name,
+                    tiles.display().to_string(),
+                    map.display().to_string()
+                );
+            }
+            Kind::Data { path } => {
+                let _ = writeln!(
+                    code,
+                    "\npub const {}: &[u8] = include_bytes!({:?});",
+                    asset.name,
+                    path.display().to_string()
+                );
+            }
+            Kind::Animation { frames } => {
+                let _ = writeln!(
+                    code,
+                    "\npub const {}: crate::renderer::sprite::Animation = {{\n    \
+                     static FRAMES: [crate::renderer::sprite::AnimationFrame; {}] = [",
+                    asset.name,
+                    frames.len()
+                );
+
+                for (sprite, ticks) in frames {
+                    let _ = writeln!(
+                        code,
+                        "        crate::renderer::sprite::AnimationFrame {{ sprite: SpriteId::{sprite}.sprite(), ticks: {ticks} }},"
+                    );
+                }
+
+                code.push_str(
+                    "    ];\n\n    crate::renderer::sprite::Animation::new(&FRAMES)\n};\n",
+                );
+            }
+        }
+    }
+
+    code
+}
+
+/// A key an entity table may set.
+enum Field {
+    /// An integer in `min..=max`, `default` if the table leaves it out.
+    Int {
+        min: i64,
+        max: i64,
+        default: Option<i64>,
+    },
+    /// A sprite in the manifest; `None` if left out.
+    Sprite,
+    /// A name in `BEHAVIORS`; `straight` if left out.
+    Behavior,
+    /// A `data` asset in the manifest holding `.behavior` byte code;
+    /// `None` if left out.
+    Script,
+    /// `true` or `false`; `false` if left out.
+    Flag,
+    /// A quoted list of `<pickup>:<weight>`, with `none` for no drop;
+    /// drops nothing if left out.
+    Drops,
+}
+
+/// The `[<section>.<name>]` tables `ENTITIES` may hold, and what each
+/// becomes in `game::entities`.
+struct EntityKind {
+    section: &'static str,
+    /// The generated handle type.
+    handle: &'static str,
+    /// The hand-written definition struct in `game::entities`.
+    def: &'static str,
+    /// The generated static table.
+    table: &'static str,
+    fields: &'static [(&'static str, Field)],
+}
+
+const ENTITY_KINDS: [EntityKind; 3] = [
+    EntityKind {
+        section: "enemy",
+        handle: "EnemyKind",
+        def: "EnemyDef",
+        table: "ENEMIES",
+        fields: &[
+            (
+                "speed",
+                Field::Int {
+                    min: 0,
+                    max: 8,
+                    default: None,
+                },
+            ),
+            (
+                "drag",
+                Field::Int {
+                    min: 0,
+                    max: 256,
+                    default: Some(0),
+                },
+            ),
+            (
+                "max_speed",
+                Field::Int {
+                    min: 0,
+                    max: 8,
+                    default: Some(0),
+                },
+            ),
+            (
+                "hp",
+                Field::Int {
+                    min: 1,
+                    max: 255,
+                    default: Some(1),
+                },
+            ),
+            (
+                "armor",
+                Field::Int {
+                    min: 0,
+                    max: 255,
+                    default: Some(0),
+                },
+            ),
+            (
+                "score",
+                Field::Int {
+                    min: 0,
+                    max: u32::MAX as i64,
+                    default: Some(0),
+                },
+            ),
+            ("sprite", Field::Sprite),
+            ("behavior", Field::Behavior),
+            ("script", Field::Script),
+            ("boss", Field::Flag),
+            ("drops", Field::Drops),
+        ],
+    },
+    EntityKind {
+        section: "projectile",
+        handle: "ProjectileKind",
+        def: "ProjectileDef",
+        table: "PROJECTILES",
+        fields: &[
+            (
+                "speed",
+                Field::Int {
+                    min: 1,
+                    max: 8,
+                    default: None,
+                },
+            ),
+            (
+                "damage",
+                Field::Int {
+                    min: 1,
+                    max: 255,
+                    default: Some(1),
+                },
+            ),
+            ("sprite", Field::Sprite),
+        ],
+    },
+    EntityKind {
+        section: "pickup",
+        handle: "PickupKind",
+        def: "PickupDef",
+        table: "PICKUPS",
+        fields: &[
+            (
+                "score",
+                Field::Int {
+                    min: 0,
+                    max: u32::MAX as i64,
+                    default: Some(0),
+                },
+            ),
+            (
+                "coins",
+                Field::Int {
+                    min: 0,
+                    max: 255,
+                    default: Some(0),
+                },
+            ),
+            ("sprite", Field::Sprite),
+        ],
+    },
+];
+
+/// `behavior` values and the `game::entities::Behavior` variant of each.
+const BEHAVIORS: [(&str, &str); 2] = [("straight", "Straight"), ("home", "Home")];
+
+/// One table of `ENTITIES`.
+struct Entity {
+    /// Index into `ENTITY_KINDS`.
+    kind: usize,
+    name: String,
+    /// Each field of the kind's that the table sets, as a Rust expression.
+    values: Vec<(&'static str, String)>,
+    /// Pickups its `drops` names, with the line, checked once every table
+    /// is read.
+    drops: Vec<(String, usize)>,
+    line: usize,
+}
+
+/// Read and validate `ENTITIES`. Errors name the offending line.
+fn parse_entities(root: &Path, assets: &[Asset]) -> Result<Vec<Entity>, String> {
+    let text =
+        fs::read_to_string(root.join(ENTITIES)).map_err(|err| format!("{ENTITIES}: {err}"))?;
+    let mut entities: Vec<Entity> = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        let at = |message: String| format!("{ENTITIES}:{}: {message}", number + 1);
+
+        if let Some(header) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            let (section, name) = header
+                .split_once('.')
+                .ok_or_else(|| at(format!("expected `[<kind>.<name>]`, found `[{header}]`")))?;
+            let kind = ENTITY_KINDS
+                .iter()
+                .position(|kind| kind.section == section)
+                .ok_or_else(|| {
+                    at(format!(
+                        "unknown kind `{section}`; use enemy, projectile or pickup"
+                    ))
+                })?;
+
+            let valid_name = name.starts_with(|c: char| c.is_ascii_lowercase())
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+
+            if !valid_name {
+                return Err(at(format!("`{name}` is not a snake_case name")));
+            }
+
+            if entities
+                .iter()
+                .any(|other| other.kind == kind && other.name == name)
+            {
+                return Err(at(format!("`[{header}]` is defined twice")));
+            }
+
+            entities.push(Entity {
+                kind,
+                name: name.to_string(),
+                values: Vec::new(),
+                drops: Vec::new(),
+                line: number + 1,
+            });
+            continue;
+        }
+
+        let Some(entity) = entities.last_mut() else {
+            return Err(at("expected a `[<kind>.<name>]` table first".to_string()));
+        };
+        let (key, value) = line
+            .split_once('=')
+            .map(|(key, value)| (key.trim(), value.trim()))
+            .ok_or_else(|| at(format!("expected `key = value`, found `{line}`")))?;
+        let kind = &ENTITY_KINDS[entity.kind];
+        let (key, field) = kind
+            .fields
+            .iter()
+            .find(|(name, _)| *name == key)
+            .ok_or_else(|| at(format!("{} has no `{key}`", kind.section)))?;
+
+        if entity.values.iter().any(|(set, _)| set == key) {
+            return Err(at(format!("`{key}` is set twice")));
+        }
+
+        let string = value.strip_prefix('"').and_then(|v| v.strip_suffix('"'));
+
+        let expr = match (field, string) {
+            (Field::Int { min, max, .. }, None) => match value.parse::<i64>() {
+                Ok(n) if (*min..=*max).contains(&n) => n.to_string(),
+                _ => return Err(at(format!("`{key}` must be a number from {min} to {max}"))),
+            },
+            (Field::Sprite, Some(sprite)) => {
+                let is_sprite = assets
+                    .iter()
+                    .any(|asset| asset.name == sprite && matches!(asset.kind, Kind::Sprite { .. }));
+
+                if !is_sprite {
+                    return Err(at(format!("`{sprite}` is not a sprite in {MANIFEST}")));
+                }
+
+                format!("Some(SpriteId::{sprite})")
+            }
+            (Field::Behavior, Some(behavior)) => {
+                let (_, variant) = BEHAVIORS
+                    .iter()
+                    .find(|(name, _)| *name == behavior)
+                    .ok_or_else(|| {
+                        let names: Vec<_> = BEHAVIORS.iter().map(|(name, _)| *name).collect();
+                        at(format!(
+                            "unknown behavior `{behavior}`; use {}",
+                            names.join(", ")
+                        ))
+                    })?;
+
+                format!("Behavior::{variant}")
+            }
+            (Field::Script, Some(script)) => {
+                let is_data = assets
+                    .iter()
+                    .any(|asset| asset.name == script && matches!(asset.kind, Kind::Data { .. }));
+
+                if !is_data {
+                    return Err(at(format!("`{script}` is not a data asset in {MANIFEST}")));
+                }
+
+                // Checked when the tables are evaluated, so a bad script
+                // fails the build.
+                format!(
+                    "Some(BehaviorScript::new(crate::game::assets::{script}).expect(\"{script} is not a behavior script\"))"
+                )
+            }
+            (Field::Drops, Some(list)) => {
+                let mut entries = Vec::new();
+                let mut nothing = 0;
+
+                for entry in list.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                    let (pickup, weight) = entry
+                        .split_once(':')
+                        .map(|(pickup, weight)| (pickup.trim(), weight.trim()))
+                        .ok_or_else(|| {
+                            at(format!("expected `<pickup>:<weight>`, found `{entry}`"))
+                        })?;
+                    let weight = match weight.parse::<u16>() {
+                        Ok(weight) if weight > 0 => weight,
+                        _ => {
+                            return Err(at(format!(
+                                "the weight of `{pickup}` must be a number from 1 to {}",
+                                u16::MAX
+                            )));
+                        }
+                    };
+
+                    if pickup == "none" {
+                        nothing = weight;
+                    } else if entity.drops.iter().any(|(name, _)| name == pickup) {
+                        return Err(at(format!("`{pickup}` is listed twice")));
+                    } else {
+                        entity.drops.push((pickup.to_string(), number + 1));
+                        entries.push(format!(
+                            "(PickupKind::{}, {weight})",
+                            pickup.to_ascii_uppercase()
+                        ));
+                    }
+                }
+
+                format!("DropTable::new(&[{}], {nothing})", entries.join(", "))
+            }
+            (Field::Flag, None) if value == "true" || value == "false" => value.to_string(),
+            (Field::Flag, _) => return Err(at(format!("`{key}` must be true or false"))),
+            (Field::Int { .. }, Some(_)) => return Err(at(format!("`{key}` must be a number"))),
+            (Field::Drops, None) => {
+                return Err(at(format!(
+                    "`{key}` must be a quoted list like \"coin:3, none:7\""
+                )));
+            }
+            (Field::Sprite | Field::Behavior | Field::Script, None) => {
+                return Err(at(format!("`{key}` must be a quoted name")));
+            }
+        };
+
+        entity.values.push((key, expr));
+    }
+
+    for entity in &entities {
+        let kind = &ENTITY_KINDS[entity.kind];
+
+        for (key, field) in kind.fields {
+            if let Field::Int { default: None, .. } = field
+                && !entity.values.iter().any(|(set, _)| set == key)
+            {
+                return Err(format!(
+                    "{ENTITIES}:{}: [{}.{}] needs `{key}`",
+                    entity.line, kind.section, entity.name
+                ));
+            }
+        }
+    }
+
+    for entity in &entities {
+        for (pickup, line) in &entity.drops {
+            let is_pickup = entities
+                .iter()
+                .any(|other| ENTITY_KINDS[other.kind].section == "pickup" && other.name == *pickup);
+
+            if !is_pickup {
+                return Err(format!(
+                    "{ENTITIES}:{line}: `{pickup}` is not a pickup table"
+                ));
+            }
+        }
+    }
+
+    for kind in &ENTITY_KINDS {
+        if entities
+            .iter()
+            .filter(|entity| ENTITY_KINDS[entity.kind].section == kind.section)
+            .count()
+            > u8::MAX as usize
+        {
+            return Err(format!("{ENTITIES}: more than 255 {} tables", kind.section));
+        }
+    }
+
+    Ok(entities)
+}
+
+fn generate_entities(entities: &[Entity]) -> String {
+    let mut code = String::new();
+    let _ = writeln!(
+        code,
+        "// Generated by build.rs from {ENTITIES}. Do not edit."
+    );
+
+    for (index, kind) in ENTITY_KINDS.iter().enumerate() {
+        let defined: Vec<&Entity> = entities
+            .iter()
+            .filter(|entity| entity.kind == index)
+            .collect();
+
+        let _ = writeln!(
+            code,
+            "\n/// {a} {section} defined in `{ENTITIES}`.\n\
+             #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]\n\
+             pub struct {handle}(u8);\n\n\
+             impl {handle} {{",
+            a = if kind.section == "enemy" { "An" } else { "A" },
+            section = kind.section,
+            handle = kind.handle,
+        );
+
+        for (id, entity) in defined.iter().enumerate() {
+            let _ = writeln!(
+                code,
+                "    pub const {}: Self = Self({id});",
+                entity.name.to_ascii_uppercase()
+            );
+        }
+
+        let _ = writeln!(
+            code,
+            "\n    /// Every {section}, in file order.\n    \
+             pub const ALL: [Self; {len}] = [{all}];\n\n    \
+             pub const fn def(self) -> &'static {def} {{\n        \
+             &{table}[self.0 as usize]\n    }}\n}}\n\n\
+             static {table}: [{def}; {len}] = [",
+            section = kind.section,
+            len = defined.len(),
+            all = defined
+                .iter()
+                .map(|entity| format!("Self::{}", entity.name.to_ascii_uppercase()))
+                .collect::<Vec<_>>()
+                .join(", "),
+            def = kind.def,
+            table = kind.table,
+        );
+
+        for entity in defined {
+            let _ = write!(code, "    {} {{ name: {:?}", kind.def, entity.name);
+
+            for (key, field) in kind.fields {
+                let set = entity.values.iter().find(|(set, _)| set == key);
+                let value = match (set, field) {
+                    (Some((_, expr)), _) => expr.clone(),
+                    (None, Field::Int { default, .. }) => default.unwrap_or(0).to_string(),
+                    (None, Field::Sprite | Field::Script) => "None".to_string(),
+                    (None, Field::Behavior) => "Behavior::Straight".to_string(),
+                    (None, Field::Flag) => "false".to_string(),
+                    (None, Field::Drops) => "DropTable::NONE".to_string(),
+                };
+
+                let _ = write!(code, ", {key}: {value}");
+            }
+
+            let _ = writeln!(code, " }},");
+        }
+
+        let _ = writeln!(code, "];");
+    }
+
+    code
+}
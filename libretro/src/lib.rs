@@ -0,0 +1,370 @@
+//! libretro core: the game as a shared library for RetroArch and other
+//! frontends.
+//!
+//! ```text
+//! cargo build -p bit-bound-libretro --release
+//! retroarch -L target/release/libbit_bound_libretro.so
+//! ```
+//!
+//! The core needs no content file. Each `retro_run` is one tick: the
+//! retropad is read into `Input`, the game updates and renders, and the
+//! frame goes to the frontend as XRGB8888 with that tick's sound at
+//! 48 kHz. Savestates are `GameState::serialize` snapshots, so sound picks
+//! up from wherever it was when one is loaded.
+
+mod ffi;
+
+use std::ffi::{c_char, c_uint, c_void};
+use std::sync::Mutex;
+
+use bit_bound::audio::offline::{DEFAULT_SAMPLE_RATE, OfflineAudio};
+use bit_bound::game::{GameState, STATE_SNAPSHOT_MAX, render, update};
+use bit_bound::input::Input;
+use bit_bound::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+use bit_bound::runtime::Runtime;
+use bit_bound::runtime::memory::ActiveMemory;
+
+use ffi::*;
+
+/// Retropad bindings for each button.
+const PAD_MAP: [(c_uint, Input); 8] = [
+    (RETRO_DEVICE_ID_JOYPAD_UP, Input::UP),
+    (RETRO_DEVICE_ID_JOYPAD_DOWN, Input::DOWN),
+    (RETRO_DEVICE_ID_JOYPAD_LEFT, Input::LEFT),
+    (RETRO_DEVICE_ID_JOYPAD_RIGHT, Input::RIGHT),
+    (RETRO_DEVICE_ID_JOYPAD_A, Input::A),
+    (RETRO_DEVICE_ID_JOYPAD_B, Input::B),
+    (RETRO_DEVICE_ID_JOYPAD_START, Input::START),
+    (RETRO_DEVICE_ID_JOYPAD_SELECT, Input::SELECT),
+];
+
+/// The frontend's callbacks, as handed to the `retro_set_*` functions.
+#[derive(Clone, Copy)]
+struct Callbacks {
+    environment: Option<EnvironmentFn>,
+    video_refresh: Option<VideoRefreshFn>,
+    audio_batch: Option<AudioSampleBatchFn>,
+    input_poll: Option<InputPollFn>,
+    input_state: Option<InputStateFn>,
+}
+
+struct Core {
+    memory: &'static mut ActiveMemory,
+    framebuffer: &'static mut FrameBuffer,
+    state: GameState,
+    audio: OfflineAudio,
+    /// XRGB8888 copy of the framebuffer handed to the frontend.
+    pixels: Vec<u32>,
+    /// The tick's samples, interleaved stereo.
+    stereo: Vec<i16>,
+}
+
+static CALLBACKS: Mutex<Callbacks> = Mutex::new(Callbacks {
+    environment: None,
+    video_refresh: None,
+    audio_batch: None,
+    input_poll: None,
+    input_state: None,
+});
+
+/// Created by the first `retro_init`. The runtime's memory can only be
+/// claimed once per process, so the core outlives `retro_deinit`.
+static CORE: Mutex<Option<Core>> = Mutex::new(None);
+
+fn callbacks() -> Callbacks {
+    *CALLBACKS.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+/// Run `f` on the core, if `retro_init` has created it.
+fn with_core<R>(f: impl FnOnce(&mut Core) -> R) -> Option<R> {
+    CORE.lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .as_mut()
+        .map(f)
+}
+
+/// Ask the frontend for `value`; `false` if it refused or is not set up.
+fn environment<T>(cmd: c_uint, value: &mut T) -> bool {
+    callbacks().environment.is_some_and(|environment| {
+        // SAFETY: `value` is the argument type libretro defines for `cmd`.
+        unsafe { environment(cmd, (value as *mut T).cast()) }
+    })
+}
+
+impl Core {
+    fn run(&mut self, callbacks: Callbacks) {
+        let mut input = Input::empty();
+
+        if let (Some(poll), Some(state)) = (callbacks.input_poll, callbacks.input_state) {
+            // SAFETY: The frontend's callbacks are valid while the core runs.
+            unsafe { poll() };
+
+            for (id, button) in PAD_MAP {
+                // SAFETY: As above.
+                if unsafe { state(0, RETRO_DEVICE_JOYPAD, 0, id) } != 0 {
+                    input.insert(button);
+                }
+            }
+        }
+
+        update(&mut self.state, input, self.memory);
+        self.memory.frame.reset();
+
+        render(&self.state, self.framebuffer);
+        self.framebuffer.to_rgba_buffer(&mut self.pixels);
+
+        if let Some(video_refresh) = callbacks.video_refresh {
+            // SAFETY: `pixels` holds `WIDTH * HEIGHT` pixels of 4 bytes.
+            unsafe {
+                video_refresh(
+                    self.pixels.as_ptr().cast(),
+                    WIDTH as c_uint,
+                    HEIGHT as c_uint,
+                    WIDTH * 4,
+                )
+            };
+        }
+
+        self.audio.submit(self.state.apu.end_frame());
+        self.stereo.clear();
+        self.stereo.extend(
+            self.audio
+                .samples()
+                .iter()
+                .flat_map(|&sample| [sample, sample]),
+        );
+        self.audio.clear();
+
+        if let Some(audio_batch) = callbacks.audio_batch {
+            // SAFETY: `stereo` holds `len / 2` frames of two samples.
+            unsafe { audio_batch(self.stereo.as_ptr(), self.stereo.len() / 2) };
+        }
+    }
+
+    fn restart(&mut self) {
+        self.state = GameState::new();
+        self.memory.frame.reset();
+        self.audio.clear();
+    }
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_api_version() -> c_uint {
+    RETRO_API_VERSION
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_set_environment(callback: EnvironmentFn) {
+    CALLBACKS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .environment = Some(callback);
+
+    environment(RETRO_ENVIRONMENT_SET_SUPPORT_NO_GAME, &mut true);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_set_video_refresh(callback: VideoRefreshFn) {
+    CALLBACKS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .video_refresh = Some(callback);
+}
+
+/// Unused: sound goes through the batch callback.
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_set_audio_sample(_callback: AudioSampleFn) {}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_set_audio_sample_batch(callback: AudioSampleBatchFn) {
+    CALLBACKS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .audio_batch = Some(callback);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_set_input_poll(callback: InputPollFn) {
+    CALLBACKS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .input_poll = Some(callback);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_set_input_state(callback: InputStateFn) {
+    CALLBACKS
+        .lock()
+        .unwrap_or_else(|err| err.into_inner())
+        .input_state = Some(callback);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_init() {
+    let mut core = CORE.lock().unwrap_or_else(|err| err.into_inner());
+
+    if core.is_some() {
+        return;
+    }
+
+    let Some(Runtime {
+        memory,
+        framebuffer,
+    }) = Runtime::take()
+    else {
+        bit_bound::log_error!("runtime already claimed");
+        return;
+    };
+
+    *core = Some(Core {
+        memory,
+        framebuffer,
+        state: GameState::new(),
+        audio: OfflineAudio::new(DEFAULT_SAMPLE_RATE),
+        pixels: vec![0; WIDTH * HEIGHT],
+        stereo: Vec::new(),
+    });
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_deinit() {}
+
+/// # Safety
+///
+/// `info` must point to a `retro_system_info` the core may write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn retro_get_system_info(info: *mut SystemInfo) {
+    let system = SystemInfo {
+        library_name: c"bit-bound".as_ptr(),
+        library_version: concat!(env!("CARGO_PKG_VERSION"), "\0")
+            .as_ptr()
+            .cast::<c_char>(),
+        valid_extensions: c"".as_ptr(),
+        need_fullpath: false,
+        block_extract: false,
+    };
+
+    // SAFETY: The caller passes a valid, writable pointer.
+    unsafe { info.write(system) };
+}
+
+/// # Safety
+///
+/// `info` must point to a `retro_system_av_info` the core may write.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn retro_get_system_av_info(info: *mut SystemAvInfo) {
+    let av = SystemAvInfo {
+        geometry: GameGeometry {
+            base_width: WIDTH as c_uint,
+            base_height: HEIGHT as c_uint,
+            max_width: WIDTH as c_uint,
+            max_height: HEIGHT as c_uint,
+            aspect_ratio: WIDTH as f32 / HEIGHT as f32,
+        },
+        timing: SystemTiming {
+            fps: 60.0,
+            sample_rate: DEFAULT_SAMPLE_RATE as f64,
+        },
+    };
+
+    // SAFETY: The caller passes a valid, writable pointer.
+    unsafe { info.write(av) };
+}
+
+/// Every port is a retropad; only port 0 is read.
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_set_controller_port_device(_port: c_uint, _device: c_uint) {}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_reset() {
+    with_core(Core::restart);
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_run() {
+    let callbacks = callbacks();
+    with_core(|core| core.run(callbacks));
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_serialize_size() -> usize {
+    STATE_SNAPSHOT_MAX
+}
+
+/// # Safety
+///
+/// `data` must point to `size` writable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn retro_serialize(data: *mut c_void, size: usize) -> bool {
+    if data.is_null() {
+        return false;
+    }
+
+    // SAFETY: The caller passes `size` writable bytes at `data`.
+    let mut out = unsafe { std::slice::from_raw_parts_mut(data.cast::<u8>(), size) };
+
+    with_core(|core| core.state.serialize(&mut out).is_ok()).unwrap_or(false)
+}
+
+/// # Safety
+///
+/// `data` must point to `size` readable bytes.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn retro_unserialize(data: *const c_void, size: usize) -> bool {
+    if data.is_null() {
+        return false;
+    }
+
+    // SAFETY: The caller passes `size` readable bytes at `data`.
+    let mut snapshot = unsafe { std::slice::from_raw_parts(data.cast::<u8>(), size) };
+
+    with_core(|core| core.state.deserialize_into(&mut snapshot).is_ok()).unwrap_or(false)
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_cheat_reset() {}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_cheat_set(_index: c_uint, _enabled: bool, _code: *const c_char) {}
+
+/// Start a new game. The core has no content, so `game` is ignored and
+/// may be null.
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_load_game(_game: *const GameInfo) -> bool {
+    let mut format = RETRO_PIXEL_FORMAT_XRGB8888;
+
+    if !environment(RETRO_ENVIRONMENT_SET_PIXEL_FORMAT, &mut format) {
+        bit_bound::log_error!("frontend does not support XRGB8888");
+        return false;
+    }
+
+    with_core(Core::restart).is_some()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_load_game_special(
+    _game_type: c_uint,
+    _info: *const GameInfo,
+    _num_info: usize,
+) -> bool {
+    false
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_unload_game() {}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_get_region() -> c_uint {
+    RETRO_REGION_NTSC
+}
+
+/// No memory regions are exposed for achievements or cheats.
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_get_memory_data(_id: c_uint) -> *mut c_void {
+    std::ptr::null_mut()
+}
+
+#[unsafe(no_mangle)]
+pub extern "C" fn retro_get_memory_size(_id: c_uint) -> usize {
+    0
+}
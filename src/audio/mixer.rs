@@ -0,0 +1,194 @@
+//! Music fades, crossfades and ducking, as per-frame ramps on the channels'
+//! `GAIN` registers.
+//!
+//! The music level and the duck level are multiplied for every channel the
+//! song owns; channels playing a sound effect stay at full gain. Ducking
+//! follows the sound effects by itself: while one of at least the duck
+//! priority plays, the music drops to the duck level, and it comes back
+//! more slowly once they end. A change of danger level that changes the
+//! pattern playing dips the music, switches the song's layer at the bottom
+//! of the dip and brings it back, so the new layer eases in.
+
+use crate::audio::music::{Music, MusicPlayer};
+use crate::audio::sfx::SfxPlayer;
+use crate::audio::{Apu, CHANNELS, GAIN};
+
+const FULL: u16 = 255;
+
+/// Frames for the music to drop when a sound effect ducks it, and to come
+/// back afterwards.
+const DUCK_ATTACK: u16 = 4;
+const DUCK_RELEASE: u16 = 20;
+
+/// How low the music dips to change danger layer, and the frames down and
+/// back up.
+const LAYER_DIP: u16 = 128;
+const LAYER_FADE: u16 = 6;
+
+/// A level moving linearly towards a target, one step per frame.
+#[derive(Debug, Clone, Copy)]
+struct Ramp {
+    level: u16,
+    target: u16,
+    step: u16,
+}
+
+impl Ramp {
+    const fn at(level: u16) -> Self {
+        Self {
+            level,
+            target: level,
+            step: 0,
+        }
+    }
+
+    /// Move to `target` over `frames` frames; 0 jumps there.
+    fn to(&mut self, target: u16, frames: u16) {
+        self.target = target;
+        self.step = match frames {
+            0 => FULL,
+            _ => self.level.abs_diff(target).div_ceil(frames).max(1),
+        };
+    }
+
+    fn advance(&mut self) {
+        if self.level < self.target {
+            self.level = (self.level + self.step).min(self.target);
+        } else {
+            self.level = self.level.saturating_sub(self.step).max(self.target);
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.level == self.target
+    }
+}
+
+pub struct Mixer {
+    music: Ramp,
+    duck: Ramp,
+    /// The dip for a change of danger layer.
+    layer: Ramp,
+    /// Danger level the song should move to.
+    danger: u8,
+    /// Effects of at least this priority duck the music.
+    duck_priority: u8,
+    /// Music level while ducked, out of 255.
+    duck_level: u8,
+    /// Whether ducking is currently pulling the music down.
+    ducking: bool,
+    /// Song to start once the fade-out ends, and its fade-in length.
+    next: Option<(Music<'static>, u16)>,
+    /// Stop the song once the fade-out ends.
+    stop_at_silence: bool,
+}
+
+impl Mixer {
+    pub const fn new(duck_priority: u8, duck_level: u8) -> Self {
+        Self {
+            music: Ramp::at(FULL),
+            duck: Ramp::at(FULL),
+            layer: Ramp::at(FULL),
+            danger: 0,
+            duck_priority,
+            duck_level,
+            ducking: false,
+            next: None,
+            stop_at_silence: false,
+        }
+    }
+
+    /// Fade the music out over `frames`, then stop it.
+    pub fn fade_out(&mut self, frames: u16) {
+        self.music.to(0, frames);
+        self.next = None;
+        self.stop_at_silence = true;
+    }
+
+    /// Fade the music (back) in over `frames`.
+    pub fn fade_in(&mut self, frames: u16) {
+        self.music.to(FULL, frames);
+        self.next = None;
+        self.stop_at_silence = false;
+    }
+
+    /// Fade the current song out over the first half of `frames` and
+    /// `song` in over the second. The channels cannot play both songs at
+    /// once, so the two meet in silence.
+    pub fn crossfade(&mut self, song: Music<'static>, frames: u16) {
+        let out = frames / 2;
+
+        self.music.to(0, out);
+        self.next = Some((song, frames - out));
+        self.stop_at_silence = false;
+    }
+
+    /// Move the song to the layers for `danger` (see `MusicPlayer::set_danger`),
+    /// through a dip if that changes the pattern playing.
+    pub fn set_danger(&mut self, danger: u8) {
+        self.danger = danger;
+    }
+
+    /// The music level, out of 255, before ducking.
+    pub fn music_level(&self) -> u8 {
+        self.music.level as u8
+    }
+
+    /// Advance the ramps by one frame and write the channel gains. Call
+    /// after `sfx` and `music` have updated.
+    pub fn update(&mut self, apu: &mut Apu, sfx: &SfxPlayer, music: &mut MusicPlayer) {
+        let ducking = sfx
+            .highest_priority()
+            .is_some_and(|priority| priority >= self.duck_priority);
+
+        if ducking != self.ducking {
+            self.ducking = ducking;
+
+            if ducking {
+                self.duck.to(self.duck_level as u16, DUCK_ATTACK);
+            } else {
+                self.duck.to(FULL, DUCK_RELEASE);
+            }
+        }
+
+        if self.danger != music.danger() && self.layer.target == FULL {
+            if music.danger_changes_pattern(self.danger) {
+                self.layer.to(LAYER_DIP, LAYER_FADE);
+            } else {
+                music.set_danger(self.danger);
+            }
+        }
+
+        self.music.advance();
+        self.duck.advance();
+        self.layer.advance();
+
+        if self.layer.done() && self.layer.target == LAYER_DIP {
+            music.set_danger(self.danger);
+            self.layer.to(FULL, LAYER_FADE);
+        }
+
+        if self.music.done() && self.music.level == 0 {
+            if let Some((song, frames)) = self.next.take() {
+                music.play(song);
+                self.music.to(FULL, frames);
+            } else if self.stop_at_silence {
+                self.stop_at_silence = false;
+                music.stop(apu, sfx.busy());
+            }
+        }
+
+        let music_gain =
+            (self.music.level * self.duck.level / FULL * self.layer.level / FULL) as u8;
+        let busy = sfx.busy();
+
+        for channel in 0..CHANNELS {
+            let gain = match busy & (1 << channel) {
+                0 => music_gain,
+                _ => FULL as u8,
+            };
+
+            apu.write(GAIN + channel as u8, gain);
+        }
+    }
+}
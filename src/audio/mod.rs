@@ -0,0 +1,176 @@
+//! GameBoy-style audio: two square channels, a wave channel and a noise
+//! channel, driven through a small register file.
+//!
+//! Game code writes registers on the `Apu` during `update()`. Once per frame
+//! the main loop takes the register file with `Apu::end_frame()` and hands
+//! it to the `Synth`, which applies it at the next frame boundary of the
+//! sample stream, so sound changes line up with the frame that made them.
+//! See ADR 0020 for the register map.
+
+pub mod mixer;
+pub mod music;
+#[cfg(feature = "std")]
+pub mod offline;
+#[cfg(feature = "audio")]
+pub mod output;
+pub mod sfx;
+pub mod synth;
+
+use crate::audio::synth::FRAME_RATE;
+
+/// A synth that an audio callback plays from while other threads queue
+/// register frames into it.
+#[cfg(feature = "std")]
+pub type SharedSynth = std::sync::Arc<std::sync::Mutex<synth::Synth>>;
+
+/// Register blocks of the four channels, `CHANNEL_REGISTERS` bytes each.
+pub const SQUARE1: u8 = 0x00;
+pub const SQUARE2: u8 = 0x04;
+pub const WAVE: u8 = 0x08;
+pub const NOISE: u8 = 0x0c;
+
+pub const CHANNELS: usize = 4;
+pub const CHANNEL_REGISTERS: u8 = 4;
+
+/// Channel register: bits 4-7 starting volume, bit 3 envelope direction (set
+/// is up), bits 0-2 envelope period in 1/64 s steps (0 holds the volume).
+/// The wave channel ignores the envelope.
+pub const CONTROL: u8 = 0;
+/// Channel register. Squares: bits 6-7 duty (12.5%, 25%, 50%, 75%). Noise:
+/// bits 4-7 clock shift, bit 3 short (7-bit) mode, bits 0-2 divisor.
+pub const TONE: u8 = 1;
+/// Channel register: low 8 bits of the 11-bit period.
+pub const PERIOD_LO: u8 = 2;
+/// Channel register: bits 0-2 high bits of the period, bit 7 trigger.
+pub const PERIOD_HI: u8 = 3;
+
+/// Bit of `PERIOD_HI` that (re)starts the channel. Reads back as 0.
+pub const TRIGGER: u8 = 0x80;
+
+/// Bits 0-2: master volume, 0-7.
+pub const MASTER: u8 = 0x10;
+/// Bits 0-3: which channels are switched on.
+pub const ENABLE: u8 = 0x11;
+/// One byte per channel from here: its output level, 0 (silent) to 255
+/// (full). The mixer ramps between the old and new value over a frame, so
+/// fades written once per frame do not click.
+pub const GAIN: u8 = 0x12;
+/// 32 four-bit samples played by the wave channel, high nibble first.
+pub const WAVE_RAM: u8 = 0x20;
+pub const WAVE_RAM_SIZE: usize = 16;
+
+pub const REGISTER_COUNT: usize = WAVE_RAM as usize + WAVE_RAM_SIZE;
+
+/// The square channels' period for `hz`: they play `131072 / (2048 -
+/// period)` Hz. The wave channel plays half that for the same period.
+pub const fn square_period(hz: u32) -> u16 {
+    let hz = if hz < 64 { 64 } else { hz };
+    2048 - (131_072 / hz) as u16
+}
+
+/// One frame's register writes, as handed from the game to the `Synth`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RegisterFrame {
+    pub registers: [u8; REGISTER_COUNT],
+    /// Bit `n` is set when channel `n` was triggered this frame.
+    pub triggers: u8,
+    /// Frames a second the game makes; the synth plays each for as long.
+    pub rate: u8,
+}
+
+/// The register file game code writes to.
+#[derive(Debug, Clone, Copy)]
+pub struct Apu {
+    registers: [u8; REGISTER_COUNT],
+    triggers: u8,
+    rate: u8,
+}
+
+impl Default for Apu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Apu {
+    /// Full master volume, every channel on and silent.
+    pub const fn new() -> Self {
+        let mut registers = [0; REGISTER_COUNT];
+        registers[MASTER as usize] = 7;
+        registers[ENABLE as usize] = 0x0f;
+
+        let mut channel = 0;
+
+        while channel < CHANNELS {
+            registers[GAIN as usize + channel] = 0xff;
+            channel += 1;
+        }
+
+        Self {
+            registers,
+            triggers: 0,
+            rate: FRAME_RATE as u8,
+        }
+    }
+
+    /// Set the frames a second `end_frame` is called, `FRAME_RATE` unless
+    /// the game ticks at another rate.
+    pub fn set_frame_rate(&mut self, rate: u8) {
+        self.rate = rate;
+    }
+
+    pub fn read(&self, address: u8) -> u8 {
+        self.registers[address as usize]
+    }
+
+    /// Write one register. Setting `TRIGGER` in a `PERIOD_HI` register
+    /// restarts that channel at the next frame boundary.
+    pub fn write(&mut self, address: u8, value: u8) {
+        let mut value = value;
+
+        if address < MASTER && address % CHANNEL_REGISTERS == PERIOD_HI {
+            if value & TRIGGER != 0 {
+                self.triggers |= 1 << (address / CHANNEL_REGISTERS);
+            }
+
+            value &= !TRIGGER;
+        }
+
+        self.registers[address as usize] = value;
+    }
+
+    /// Set a channel's `CONTROL`, `TONE` and period and trigger it.
+    pub fn play(&mut self, channel: u8, control: u8, tone: u8, period: u16) {
+        self.write(channel + CONTROL, control);
+        self.write(channel + TONE, tone);
+        self.write(channel + PERIOD_LO, period as u8);
+        self.write(channel + PERIOD_HI, TRIGGER | ((period >> 8) as u8 & 0x07));
+    }
+
+    /// Change a channel's period without restarting it, for sweeps.
+    pub fn set_period(&mut self, channel: u8, period: u16) {
+        self.write(channel + PERIOD_LO, period as u8);
+        self.write(channel + PERIOD_HI, (period >> 8) as u8 & 0x07);
+    }
+
+    /// Silence a channel by restarting it at volume 0.
+    pub fn stop(&mut self, channel: u8) {
+        self.write(channel + CONTROL, 0);
+        self.write(channel + PERIOD_HI, TRIGGER);
+    }
+
+    pub fn set_wave(&mut self, samples: &[u8; WAVE_RAM_SIZE]) {
+        let start = WAVE_RAM as usize;
+        self.registers[start..start + WAVE_RAM_SIZE].copy_from_slice(samples);
+    }
+
+    /// The frame's registers and triggers. Triggers are cleared; registers
+    /// keep their values.
+    pub fn end_frame(&mut self) -> RegisterFrame {
+        RegisterFrame {
+            registers: self.registers,
+            triggers: core::mem::take(&mut self.triggers),
+            rate: self.rate,
+        }
+    }
+}
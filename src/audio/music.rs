@@ -0,0 +1,517 @@
+//! Tracker-style music driver for `.music` streams compiled by
+//! `spritec --music` (layout in `docs/music_format.md`).
+//!
+//! `Music` reads the stream in place, so songs play straight from
+//! `include_bytes!` data. `MusicPlayer` steps through the orders, patterns
+//! and rows at the song's tempo, writing notes to the `Apu`. A song's tempo
+//! is in ticks at `TICK_RATE`; at another `TickRate` the player steps some
+//! ticks twice, or not at all, to keep it. Channels
+//! playing a sound effect are left alone: the song keeps time on them but
+//! writes nothing until the effect ends and the channel's next note comes.
+//!
+//! A song may carry danger layers: variants of its patterns for when the
+//! game gets tense. `MusicPlayer::set_danger` plays each pattern as its
+//! variant for that danger level from the next row on, at the same row, so
+//! the beat carries across; `Mixer::set_danger` eases the change in.
+
+use crate::audio::{Apu, CHANNEL_REGISTERS, CHANNELS, NOISE, WAVE};
+use crate::runtime::pacing::{TICK_RATE, TickRate};
+
+pub const MUSIC_MAGIC: [u8; 4] = *b"BBMU";
+pub const MUSIC_VERSION: u16 = 2;
+
+/// Highest danger level a layer can be for.
+pub const MAX_DANGER: u8 = 3;
+
+const HEADER_SIZE: usize = 12;
+const INSTRUMENT_SIZE: usize = 4;
+const LAYER_SIZE: usize = 3;
+const CELL_SIZE: usize = 4;
+
+const NOTE_NONE: u8 = 0;
+const NOTE_OFF: u8 = 0xff;
+const HIGHEST_NOTE: u8 = 72;
+const NO_INSTRUMENT: u8 = 0xff;
+const NO_DUTY: u8 = 0xff;
+const NO_LOOP: u8 = 0xff;
+
+/// C-2 to B-2 in 1/100 Hz. Note 1 is C-2; each octave up doubles.
+const OCTAVE_2: [u32; 12] = [
+    6541, 6930, 7342, 7778, 8241, 8731, 9250, 9800, 10383, 11000, 11654, 12347,
+];
+
+const fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Instrument {
+    pub volume: u8,
+    /// Envelope period of each step down; 0 holds the volume.
+    pub fade: u8,
+    pub duty: Option<u8>,
+}
+
+/// One channel's entry in a row.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cell {
+    /// 0 none, 1-72 for C-2 to B-7, `0xff` stop.
+    pub note: u8,
+    pub instrument: Option<u8>,
+    /// `(b'T' | b'V' | b'B', parameter)`.
+    pub effect: Option<(u8, u8)>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Music<'a> {
+    data: &'a [u8],
+    instrument_count: usize,
+    pattern_count: usize,
+    order_count: usize,
+    layer_count: usize,
+}
+
+impl<'a> Music<'a> {
+    /// Check the header and walk every pattern. `None` if `data` is not a
+    /// well-formed stream of this version, so playback never reads out of
+    /// bounds.
+    pub const fn new(data: &'a [u8]) -> Option<Self> {
+        if data.len() < HEADER_SIZE
+            || data[0] != MUSIC_MAGIC[0]
+            || data[1] != MUSIC_MAGIC[1]
+            || data[2] != MUSIC_MAGIC[2]
+            || data[3] != MUSIC_MAGIC[3]
+            || read_u16(data, 4) != MUSIC_VERSION
+            || data[6] == 0
+        {
+            return None;
+        }
+
+        let music = Self {
+            data,
+            instrument_count: data[8] as usize,
+            pattern_count: data[9] as usize,
+            order_count: data[10] as usize,
+            layer_count: data[11] as usize,
+        };
+
+        if music.order_count == 0
+            || data.len() < music.pattern_table() + music.pattern_count * 2
+            || (data[7] != NO_LOOP && data[7] as usize >= music.order_count)
+        {
+            return None;
+        }
+
+        let mut i = 0;
+
+        while i < music.order_count {
+            if data[music.orders() + i] as usize >= music.pattern_count {
+                return None;
+            }
+
+            i += 1;
+        }
+
+        let mut pattern = 0;
+
+        while pattern < music.pattern_count {
+            let start = read_u16(data, music.pattern_table() + pattern * 2) as usize;
+
+            if start >= data.len() || data[start] == 0 {
+                return None;
+            }
+
+            let mut at = start + 1;
+            let mut row = 0;
+
+            while row < data[start] {
+                if at >= data.len() {
+                    return None;
+                }
+
+                at += 1 + (data[at] & 0x0f).count_ones() as usize * CELL_SIZE;
+                row += 1;
+            }
+
+            if at > data.len() {
+                return None;
+            }
+
+            pattern += 1;
+        }
+
+        let mut layer = 0;
+
+        while layer < music.layer_count {
+            let at = music.layers() + layer * LAYER_SIZE;
+            let (danger, base, variant) = (data[at], data[at + 1], data[at + 2]);
+
+            if danger == 0
+                || danger > MAX_DANGER
+                || base as usize >= music.pattern_count
+                || variant as usize >= music.pattern_count
+                || music.pattern(base).1 != music.pattern(variant).1
+            {
+                return None;
+            }
+
+            layer += 1;
+        }
+
+        Some(music)
+    }
+
+    const fn orders(&self) -> usize {
+        HEADER_SIZE + self.instrument_count * INSTRUMENT_SIZE
+    }
+
+    const fn layers(&self) -> usize {
+        self.orders() + self.order_count
+    }
+
+    const fn pattern_table(&self) -> usize {
+        self.layers() + self.layer_count * LAYER_SIZE
+    }
+
+    /// Starting ticks per row.
+    pub const fn tempo(&self) -> u8 {
+        self.data[6]
+    }
+
+    /// Order to continue at after the last one; `None` stops the song.
+    pub const fn loop_order(&self) -> Option<usize> {
+        match self.data[7] {
+            NO_LOOP => None,
+            order => Some(order as usize),
+        }
+    }
+
+    pub const fn order_count(&self) -> usize {
+        self.order_count
+    }
+
+    /// The instrument with id `id`, if the song defines it.
+    pub const fn instrument(&self, id: u8) -> Option<Instrument> {
+        if id as usize >= self.instrument_count {
+            return None;
+        }
+
+        let at = HEADER_SIZE + id as usize * INSTRUMENT_SIZE;
+
+        Some(Instrument {
+            volume: self.data[at],
+            fade: self.data[at + 1],
+            duty: match self.data[at + 2] {
+                NO_DUTY => None,
+                duty => Some(duty),
+            },
+        })
+    }
+
+    /// Whether any pattern has a danger layer.
+    pub const fn has_layers(&self) -> bool {
+        self.layer_count > 0
+    }
+
+    /// The pattern at `order`, as it plays at `danger`: the variant of the
+    /// highest layer up to `danger`, or the pattern itself.
+    pub const fn pattern_for(&self, order: usize, danger: u8) -> u8 {
+        let base = self.data[self.orders() + order];
+        let mut pattern = base;
+        let mut best = 0;
+        let mut layer = 0;
+
+        while layer < self.layer_count {
+            let at = self.layers() + layer * LAYER_SIZE;
+            let level = self.data[at];
+
+            if self.data[at + 1] == base && level <= danger && level > best {
+                best = level;
+                pattern = self.data[at + 2];
+            }
+
+            layer += 1;
+        }
+
+        pattern
+    }
+
+    /// Offset of the first row of `pattern`, and its row count.
+    const fn pattern(&self, pattern: u8) -> (usize, u8) {
+        let start = read_u16(self.data, self.pattern_table() + pattern as usize * 2) as usize;
+
+        (start + 1, self.data[start])
+    }
+
+    /// The row at byte offset `at`, and the offset of the next row.
+    fn row(&self, at: usize) -> ([Option<Cell>; CHANNELS], usize) {
+        let mask = self.data[at];
+        let mut cells = [None; CHANNELS];
+        let mut next = at + 1;
+
+        for (channel, cell) in cells.iter_mut().enumerate() {
+            if mask & (1 << channel) == 0 {
+                continue;
+            }
+
+            let bytes = &self.data[next..next + CELL_SIZE];
+
+            *cell = Some(Cell {
+                note: bytes[0],
+                instrument: (bytes[1] != NO_INSTRUMENT).then_some(bytes[1]),
+                effect: (bytes[2] != 0).then_some((bytes[2], bytes[3])),
+            });
+            next += CELL_SIZE;
+        }
+
+        (cells, next)
+    }
+}
+
+/// The period that plays `note` (1 is C-2) on a square channel. The wave
+/// channel plays an octave lower for the same period, so it gets `note +
+/// 12`'s period.
+const fn note_period(note: u8) -> u16 {
+    let index = (note - 1) as usize;
+    let centihertz = OCTAVE_2[index % 12] << (index / 12);
+
+    (2048 - 131_072 * 100 / centihertz) as u16
+}
+
+/// Noise clock for `note`: one clock shift down every half octave.
+const fn noise_tone(note: u8) -> u8 {
+    let shift = (HIGHEST_NOTE - note) / 6;
+    (shift << 4) | 0x02
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ChannelState {
+    instrument: u8,
+    /// Set by a `V` effect; the instrument's volume otherwise.
+    volume: Option<u8>,
+}
+
+/// Plays one `Music` at a time.
+pub struct MusicPlayer {
+    song: Option<Music<'static>>,
+    tempo: u8,
+    /// Ticks into the current row.
+    tick: u8,
+    /// The rate `update` is called at.
+    rate: TickRate,
+    /// Song time owed: each update adds `TICK_RATE`, and each `rate.hz()`
+    /// of it steps one song tick.
+    owed: u64,
+    order: usize,
+    /// Byte offset of the next row, and rows left in its pattern.
+    cursor: usize,
+    rows_left: u8,
+    /// Danger level the patterns are played at.
+    danger: u8,
+    channels: [ChannelState; CHANNELS],
+}
+
+impl Default for MusicPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MusicPlayer {
+    pub const fn new() -> Self {
+        Self {
+            song: None,
+            tempo: 1,
+            tick: 0,
+            rate: TickRate::Hz60,
+            owed: 0,
+            order: 0,
+            cursor: 0,
+            rows_left: 0,
+            danger: 0,
+            channels: [ChannelState {
+                instrument: 0,
+                volume: None,
+            }; CHANNELS],
+        }
+    }
+
+    /// Set the rate `update` is called at.
+    pub fn set_rate(&mut self, rate: TickRate) {
+        self.rate = rate;
+        self.owed = 0;
+    }
+
+    /// Start `song` from its first order, at the danger level already set.
+    pub fn play(&mut self, song: Music<'static>) {
+        *self = Self {
+            rate: self.rate,
+            danger: self.danger,
+            ..Self::new()
+        };
+        self.tempo = song.tempo();
+        self.song = Some(song);
+        self.jump(0);
+    }
+
+    /// Stop the song and silence the channels it is not sharing.
+    pub fn stop(&mut self, apu: &mut Apu, busy: u8) {
+        self.song = None;
+
+        for channel in 0..CHANNELS {
+            if busy & (1 << channel) == 0 {
+                apu.stop(channel as u8 * CHANNEL_REGISTERS);
+            }
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.song.is_some()
+    }
+
+    pub fn danger(&self) -> u8 {
+        self.danger
+    }
+
+    /// Whether playing at `danger` would change the pattern playing now.
+    pub fn danger_changes_pattern(&self, danger: u8) -> bool {
+        self.song.is_some_and(|song| {
+            song.pattern_for(self.order, danger) != song.pattern_for(self.order, self.danger)
+        })
+    }
+
+    /// Play the patterns' variants for `danger`, held to `MAX_DANGER`. The
+    /// pattern playing now changes from its next row on.
+    pub fn set_danger(&mut self, danger: u8) {
+        let danger = danger.min(MAX_DANGER);
+
+        if danger == self.danger {
+            return;
+        }
+
+        self.danger = danger;
+
+        let Some(song) = self.song else {
+            return;
+        };
+
+        // Variants have the pattern's row count, so skip to the same row.
+        let (mut cursor, rows) = song.pattern(song.pattern_for(self.order, danger));
+
+        for _ in self.rows_left..rows {
+            cursor = song.row(cursor).1;
+        }
+
+        self.cursor = cursor;
+    }
+
+    fn jump(&mut self, order: usize) {
+        let Some(song) = &self.song else {
+            return;
+        };
+
+        (self.cursor, self.rows_left) = song.pattern(song.pattern_for(order, self.danger));
+        self.order = order;
+    }
+
+    /// Advance one tick at the player's rate, starting a row every `tempo`
+    /// ticks at `TICK_RATE`. Channels whose bit is set in `busy` (see
+    /// `SfxPlayer::busy`) are not written.
+    pub fn update(&mut self, apu: &mut Apu, busy: u8) {
+        self.owed += TICK_RATE;
+
+        while self.owed >= self.rate.hz() {
+            self.owed -= self.rate.hz();
+            self.step(apu, busy);
+        }
+    }
+
+    fn step(&mut self, apu: &mut Apu, busy: u8) {
+        let Some(song) = self.song else {
+            return;
+        };
+
+        if self.tick == 0 {
+            self.play_row(&song, apu, busy);
+        }
+
+        self.tick += 1;
+
+        if self.tick >= self.tempo {
+            self.tick = 0;
+        }
+    }
+
+    fn play_row(&mut self, song: &Music<'static>, apu: &mut Apu, busy: u8) {
+        let (cells, next) = song.row(self.cursor);
+        let mut jump = None;
+
+        self.cursor = next;
+        self.rows_left -= 1;
+
+        for (channel, cell) in cells.iter().enumerate() {
+            let Some(cell) = cell else {
+                continue;
+            };
+
+            let state = &mut self.channels[channel];
+
+            if let Some(instrument) = cell.instrument {
+                state.instrument = instrument;
+                state.volume = None;
+            }
+
+            match cell.effect {
+                Some((b'T', ticks)) if ticks > 0 => self.tempo = ticks,
+                Some((b'V', volume)) => state.volume = Some(volume & 0x0f),
+                Some((b'B', order)) if (order as usize) < song.order_count() => {
+                    jump = Some(order as usize);
+                }
+                _ => {}
+            }
+
+            if busy & (1 << channel) == 0 {
+                play_note(apu, song, channel, cell.note, state);
+            }
+        }
+
+        if let Some(order) = jump {
+            self.jump(order);
+        } else if self.rows_left == 0 {
+            match (self.order + 1 < song.order_count(), song.loop_order()) {
+                (true, _) => self.jump(self.order + 1),
+                (false, Some(order)) => self.jump(order),
+                (false, None) => self.song = None,
+            }
+        }
+    }
+}
+
+fn play_note(apu: &mut Apu, song: &Music, channel: usize, note: u8, state: &ChannelState) {
+    let base = channel as u8 * CHANNEL_REGISTERS;
+
+    match note {
+        NOTE_NONE => {}
+        NOTE_OFF => apu.stop(base),
+        note if note <= HIGHEST_NOTE => {
+            let Some(instrument) = song.instrument(state.instrument) else {
+                return;
+            };
+
+            let volume = state.volume.unwrap_or(instrument.volume);
+            let control = volume << 4 | (instrument.fade & 0x07);
+
+            match base {
+                WAVE => apu.play(base, volume << 4, 0, note_period(note + 12)),
+                NOISE => apu.play(base, control, noise_tone(note), 0),
+                _ => apu.play(
+                    base,
+                    control,
+                    instrument.duty.unwrap_or(2) << 6,
+                    note_period(note),
+                ),
+            }
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,109 @@
+//! Renders register frames to samples without an output device, for
+//! golden-audio tests and WAV export.
+//!
+//! Every frame comes out as exactly `Synth::samples_per_frame(frame.rate)`
+//! samples, so at a steady rate sample `n * frame_samples()` is where video
+//! frame `n` starts, and the audio lines up with frames captured from the
+//! same run.
+
+use std::io::{self, Write};
+
+use crate::audio::RegisterFrame;
+use crate::audio::synth::Synth;
+use crate::runtime::hash::Fnv1a;
+
+/// Sample rate used when nothing else asks for one.
+pub const DEFAULT_SAMPLE_RATE: u32 = 48_000;
+
+/// Collects the mixed output of every submitted frame as 16-bit samples.
+pub struct OfflineAudio {
+    synth: Synth,
+    mix: Vec<f32>,
+    samples: Vec<i16>,
+}
+
+impl OfflineAudio {
+    pub fn new(sample_rate: u32) -> Self {
+        let synth = Synth::new(sample_rate);
+        let mix = vec![0.0; synth.frame_samples() as usize];
+
+        Self {
+            synth,
+            mix,
+            samples: Vec::new(),
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.synth.sample_rate()
+    }
+
+    /// Render one frame's samples; call where the main loop would call
+    /// `AudioOutput::submit`.
+    pub fn submit(&mut self, frame: RegisterFrame) {
+        let samples = self.synth.samples_per_frame(frame.rate);
+
+        self.mix.resize(samples as usize, 0.0);
+        self.synth.push(frame);
+        self.synth.render(&mut self.mix);
+
+        self.samples
+            .extend(self.mix.iter().map(|&sample| to_i16(sample)));
+    }
+
+    /// Everything rendered so far, mono.
+    pub fn samples(&self) -> &[i16] {
+        &self.samples
+    }
+
+    /// Hash of the samples rendered so far.
+    pub fn hash(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+
+        for sample in &self.samples {
+            hash.write_bytes(&sample.to_le_bytes());
+        }
+
+        hash.finish()
+    }
+
+    /// Drop the rendered samples, keeping the synth's channel state.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    pub fn write_wav(&self, out: &mut impl Write) -> io::Result<()> {
+        write_wav(self.sample_rate(), &self.samples, out)
+    }
+}
+
+fn to_i16(sample: f32) -> i16 {
+    (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16
+}
+
+/// Write `samples` as a mono 16-bit PCM WAV file.
+pub fn write_wav(sample_rate: u32, samples: &[i16], out: &mut impl Write) -> io::Result<()> {
+    let data_len = (samples.len() * 2) as u32;
+
+    out.write_all(b"RIFF")?;
+    out.write_all(&(36 + data_len).to_le_bytes())?;
+    out.write_all(b"WAVE")?;
+
+    out.write_all(b"fmt ")?;
+    out.write_all(&16u32.to_le_bytes())?;
+    out.write_all(&1u16.to_le_bytes())?; // PCM
+    out.write_all(&1u16.to_le_bytes())?; // Mono
+    out.write_all(&sample_rate.to_le_bytes())?;
+    out.write_all(&(sample_rate * 2).to_le_bytes())?; // Bytes per second
+    out.write_all(&2u16.to_le_bytes())?; // Bytes per sample
+    out.write_all(&16u16.to_le_bytes())?; // Bits per sample
+
+    out.write_all(b"data")?;
+    out.write_all(&data_len.to_le_bytes())?;
+
+    for sample in samples {
+        out.write_all(&sample.to_le_bytes())?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,104 @@
+//! Plays the `Synth` on the default output device through cpal.
+
+use std::sync::{Arc, Mutex};
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use cpal::{SampleFormat, Stream};
+
+use crate::audio::synth::Synth;
+use crate::audio::{RegisterFrame, SharedSynth};
+
+/// An open output stream. Sound stops when it is dropped.
+pub struct AudioOutput {
+    synth: SharedSynth,
+    _stream: Stream,
+}
+
+impl AudioOutput {
+    /// Open the default output device at its default sample rate.
+    pub fn open() -> Result<Self, String> {
+        let device = cpal::default_host()
+            .default_output_device()
+            .ok_or("no audio output device")?;
+        let supported = device
+            .default_output_config()
+            .map_err(|err| err.to_string())?;
+
+        let format = supported.sample_format();
+        let config = supported.config();
+        let channels = config.channels as usize;
+        let synth = Arc::new(Mutex::new(Synth::new(config.sample_rate.0)));
+        let on_error = |err: cpal::StreamError| crate::log_warn!("audio stream error: {err}");
+
+        let stream = match format {
+            SampleFormat::F32 => {
+                let synth = Arc::clone(&synth);
+                let mut mono = Vec::new();
+
+                device.build_output_stream(
+                    &config,
+                    move |data: &mut [f32], _| {
+                        render(&synth, &mut mono, data, channels, |sample| sample);
+                    },
+                    on_error,
+                    None,
+                )
+            }
+            SampleFormat::I16 => {
+                let synth = Arc::clone(&synth);
+                let mut mono = Vec::new();
+
+                device.build_output_stream(
+                    &config,
+                    move |data: &mut [i16], _| {
+                        render(&synth, &mut mono, data, channels, |sample| {
+                            (sample * i16::MAX as f32) as i16
+                        });
+                    },
+                    on_error,
+                    None,
+                )
+            }
+            other => return Err(format!("unsupported sample format {other}")),
+        }
+        .map_err(|err| err.to_string())?;
+
+        stream.play().map_err(|err| err.to_string())?;
+
+        Ok(Self {
+            synth,
+            _stream: stream,
+        })
+    }
+
+    /// Queue one frame's registers; call once per rendered frame.
+    pub fn submit(&self, frame: RegisterFrame) {
+        if let Ok(mut synth) = self.synth.lock() {
+            synth.push(frame);
+        }
+    }
+
+    /// The synth the stream plays from.
+    pub fn synth(&self) -> SharedSynth {
+        Arc::clone(&self.synth)
+    }
+}
+
+/// Fill an interleaved device buffer with the synth's mono output.
+fn render<T: Copy>(
+    synth: &Mutex<Synth>,
+    mono: &mut Vec<f32>,
+    data: &mut [T],
+    channels: usize,
+    convert: impl Fn(f32) -> T,
+) {
+    mono.resize(data.len() / channels, 0.0);
+
+    if let Ok(mut synth) = synth.lock() {
+        synth.render(mono);
+    }
+
+    for (frame, &sample) in data.chunks_mut(channels).zip(mono.iter()) {
+        frame.fill(convert(sample));
+    }
+}
@@ -0,0 +1,176 @@
+//! Sound effects on top of the `Apu`.
+//!
+//! An effect is data: the kind of channel it needs, a priority, and a list
+//! of steps that each trigger the channel and optionally sweep its pitch.
+//! Game code calls `SfxPlayer::play` when something happens and
+//! `SfxPlayer::update` once per tick to write the registers.
+//!
+//! When every channel of the right kind is busy, the new effect steals the
+//! one playing the lowest-priority effect, oldest first, as long as that
+//! priority is not above its own; otherwise the new effect is dropped.
+
+use crate::audio::{Apu, CHANNEL_REGISTERS, CHANNELS};
+
+/// The channel an effect plays on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChannelKind {
+    /// Either square channel.
+    Square,
+    Wave,
+    Noise,
+}
+
+impl ChannelKind {
+    /// Channel indices of this kind.
+    fn channels(self) -> &'static [usize] {
+        match self {
+            Self::Square => &[0, 1],
+            Self::Wave => &[2],
+            Self::Noise => &[3],
+        }
+    }
+}
+
+/// One stretch of an effect. The channel is triggered with `control`,
+/// `tone` and `period` on the step's first frame, then `sweep` is added to
+/// the period on every later frame.
+#[derive(Debug, Clone, Copy)]
+pub struct SfxStep {
+    /// Length in frames; at least 1.
+    pub frames: u8,
+    pub control: u8,
+    pub tone: u8,
+    pub period: u16,
+    /// Period change per frame. Positive rises in pitch.
+    pub sweep: i16,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SfxDef {
+    pub kind: ChannelKind,
+    /// Higher priorities steal channels from lower ones.
+    pub priority: u8,
+    pub steps: &'static [SfxStep],
+}
+
+/// Index of an effect in the table the `SfxPlayer` was created with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SfxId(pub u8);
+
+#[derive(Debug, Clone, Copy)]
+struct Playing {
+    id: SfxId,
+    priority: u8,
+    /// `SfxPlayer::started` when the effect began, to steal the oldest.
+    started: u32,
+    step: usize,
+    /// Frames into the current step.
+    frame: u8,
+    period: u16,
+}
+
+/// Which effect each channel is playing.
+pub struct SfxPlayer {
+    table: &'static [SfxDef],
+    channels: [Option<Playing>; CHANNELS],
+    /// Effects started so far.
+    started: u32,
+}
+
+impl SfxPlayer {
+    pub const fn new(table: &'static [SfxDef]) -> Self {
+        Self {
+            table,
+            channels: [None; CHANNELS],
+            started: 0,
+        }
+    }
+
+    /// Start effect `id` on the next `update`. Returns `false` when every
+    /// channel it could use plays something of higher priority.
+    pub fn play(&mut self, id: SfxId) -> bool {
+        let def = &self.table[id.0 as usize];
+        let candidates = def.kind.channels();
+
+        let free = candidates
+            .iter()
+            .copied()
+            .find(|&channel| self.channels[channel].is_none());
+
+        let channel = free.or_else(|| {
+            candidates
+                .iter()
+                .copied()
+                .filter_map(|channel| Some((channel, self.channels[channel]?)))
+                .min_by_key(|(_, playing)| (playing.priority, playing.started))
+                .filter(|(_, playing)| playing.priority <= def.priority)
+                .map(|(channel, _)| channel)
+        });
+
+        let Some(channel) = channel else {
+            return false;
+        };
+
+        self.channels[channel] = Some(Playing {
+            id,
+            priority: def.priority,
+            started: self.started,
+            step: 0,
+            frame: 0,
+            period: 0,
+        });
+        self.started = self.started.wrapping_add(1);
+
+        true
+    }
+
+    /// Bit `n` is set while channel `n` plays an effect.
+    pub fn busy(&self) -> u8 {
+        (0..CHANNELS)
+            .filter(|&channel| self.channels[channel].is_some())
+            .fold(0, |mask, channel| mask | 1 << channel)
+    }
+
+    /// Priority of the most important effect playing, if any.
+    pub fn highest_priority(&self) -> Option<u8> {
+        self.channels
+            .iter()
+            .flatten()
+            .map(|playing| playing.priority)
+            .max()
+    }
+
+    /// Advance every effect by one frame, writing its registers.
+    pub fn update(&mut self, apu: &mut Apu) {
+        for (channel, slot) in self.channels.iter_mut().enumerate() {
+            let Some(playing) = slot else {
+                continue;
+            };
+
+            let base = channel as u8 * CHANNEL_REGISTERS;
+            let steps = self.table[playing.id.0 as usize].steps;
+
+            // Stopped a frame after the last step so that step is heard.
+            let Some(step) = steps.get(playing.step) else {
+                apu.stop(base);
+                *slot = None;
+                continue;
+            };
+
+            if playing.frame == 0 {
+                playing.period = step.period;
+                apu.play(base, step.control, step.tone, step.period);
+            } else if step.sweep != 0 {
+                playing.period = playing.period.saturating_add_signed(step.sweep).min(2047);
+                apu.set_period(base, playing.period);
+            }
+
+            playing.frame += 1;
+
+            if playing.frame >= step.frames {
+                playing.step += 1;
+                playing.frame = 0;
+            }
+        }
+    }
+}
@@ -0,0 +1,306 @@
+//! Software mixer for the four channels. Pure integer channel state, so the
+//! same register frames always produce the same samples.
+
+use crate::audio::{
+    CHANNEL_REGISTERS, CHANNELS, CONTROL, ENABLE, GAIN, MASTER, NOISE, PERIOD_HI, PERIOD_LO,
+    REGISTER_COUNT, RegisterFrame, TONE, WAVE, WAVE_RAM,
+};
+
+/// Register frames per second unless a frame gives its own `rate`; one is
+/// applied every `sample_rate / rate` samples.
+pub const FRAME_RATE: u32 = 60;
+
+/// Frames the synth holds before merging new ones into the newest, which
+/// bounds the latency when the game runs ahead of the audio device.
+const QUEUE_LEN: usize = 4;
+
+/// Duty patterns, one bit per eighth of the period, high bits first.
+const DUTY: [u8; 4] = [0b0000_0001, 0b1000_0001, 0b1000_0111, 0b0111_1110];
+
+/// Clock of the period counters, and of the noise channel's divider.
+const SQUARE_CLOCK: u64 = 131_072;
+const NOISE_CLOCK: u64 = 524_288;
+
+/// Envelope steps per second.
+const ENVELOPE_RATE: u32 = 64;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Voice {
+    active: bool,
+    volume: u8,
+    /// Samples until the next envelope step.
+    envelope_left: u32,
+    /// Position in the waveform as a fraction of 2^32; for noise, the
+    /// fraction of the next LFSR clock.
+    phase: u64,
+    lfsr: u16,
+}
+
+pub struct Synth {
+    sample_rate: u32,
+    registers: [u8; REGISTER_COUNT],
+    voices: [Voice; CHANNELS],
+    queue: [RegisterFrame; QUEUE_LEN],
+    queued: usize,
+    /// Samples left before the next frame is applied.
+    frame_left: u32,
+    /// The `rate` of the frame playing.
+    frame_rate: u8,
+    /// Each channel's gain at the start of the frame; it ramps linearly to
+    /// the `GAIN` register by the end.
+    gain_from: [u8; CHANNELS],
+}
+
+impl Synth {
+    pub fn new(sample_rate: u32) -> Self {
+        let silent = RegisterFrame {
+            registers: [0; REGISTER_COUNT],
+            triggers: 0,
+            rate: FRAME_RATE as u8,
+        };
+
+        Self {
+            sample_rate,
+            registers: [0; REGISTER_COUNT],
+            voices: [Voice::default(); CHANNELS],
+            queue: [silent; QUEUE_LEN],
+            queued: 0,
+            frame_left: 0,
+            frame_rate: FRAME_RATE as u8,
+            gain_from: [0; CHANNELS],
+        }
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Samples rendered per register frame at `rate` frames a second.
+    pub fn samples_per_frame(&self, rate: u8) -> u32 {
+        (self.sample_rate / rate.max(1) as u32).max(1)
+    }
+
+    /// Samples rendered per register frame, at the rate of the frame
+    /// playing.
+    pub fn frame_samples(&self) -> u32 {
+        self.samples_per_frame(self.frame_rate)
+    }
+
+    /// Queue a frame to apply at the next frame boundary. When the queue is
+    /// full the frame replaces the newest one, keeping both frames'
+    /// triggers.
+    pub fn push(&mut self, frame: RegisterFrame) {
+        if self.queued == QUEUE_LEN {
+            let newest = &mut self.queue[QUEUE_LEN - 1];
+            newest.registers = frame.registers;
+            newest.triggers |= frame.triggers;
+            newest.rate = frame.rate;
+        } else {
+            self.queue[self.queued] = frame;
+            self.queued += 1;
+        }
+    }
+
+    /// Mix mono samples in `-1.0..=1.0` into `out`. Without a new frame the
+    /// last registers keep playing.
+    pub fn render(&mut self, out: &mut [f32]) {
+        for sample in out {
+            if self.frame_left == 0 {
+                self.next_frame();
+            }
+
+            self.frame_left -= 1;
+            *sample = self.mix();
+        }
+    }
+
+    fn next_frame(&mut self) {
+        self.gain_from = self.gains();
+
+        if self.queued == 0 {
+            self.frame_left = self.frame_samples();
+            return;
+        }
+
+        let frame = self.queue[0];
+        self.queue.copy_within(1.., 0);
+        self.queued -= 1;
+        self.registers = frame.registers;
+        self.frame_rate = frame.rate;
+        self.frame_left = self.frame_samples();
+
+        for channel in 0..CHANNELS {
+            if frame.triggers & (1 << channel) != 0 {
+                self.trigger(channel);
+            }
+        }
+    }
+
+    fn gains(&self) -> [u8; CHANNELS] {
+        let start = GAIN as usize;
+        let mut gains = [0; CHANNELS];
+        gains.copy_from_slice(&self.registers[start..start + CHANNELS]);
+        gains
+    }
+
+    /// `channel`'s gain at this point of the frame ramp, out of 255.
+    fn gain(&self, channel: usize) -> i32 {
+        let from = self.gain_from[channel] as i32;
+        let to = self.registers[GAIN as usize + channel] as i32;
+        let total = self.frame_samples() as i32;
+        let done = total - self.frame_left as i32;
+
+        from + (to - from) * done / total
+    }
+
+    fn register(&self, channel: usize, offset: u8) -> u8 {
+        self.registers[(channel as u8 * CHANNEL_REGISTERS + offset) as usize]
+    }
+
+    fn period(&self, channel: usize) -> u64 {
+        let low = self.register(channel, PERIOD_LO) as u64;
+        let high = (self.register(channel, PERIOD_HI) & 0x07) as u64;
+
+        2048 - (high << 8 | low)
+    }
+
+    fn envelope_samples(&self, channel: usize) -> u32 {
+        (self.register(channel, CONTROL) & 0x07) as u32 * self.sample_rate / ENVELOPE_RATE
+    }
+
+    fn trigger(&mut self, channel: usize) {
+        self.voices[channel] = Voice {
+            active: true,
+            volume: self.register(channel, CONTROL) >> 4,
+            envelope_left: self.envelope_samples(channel),
+            phase: 0,
+            lfsr: 0x7fff,
+        };
+    }
+
+    fn mix(&mut self) -> f32 {
+        let enabled = self.registers[ENABLE as usize];
+        let mut sum = 0i32;
+
+        for channel in 0..CHANNELS {
+            if !self.voices[channel].active || enabled & (1 << channel) == 0 {
+                continue;
+            }
+
+            let level = match channel as u8 * CHANNEL_REGISTERS {
+                WAVE => self.wave_level(),
+                NOISE => self.noise_level(),
+                _ => self.square_level(channel),
+            };
+
+            sum += level * self.gain(channel);
+
+            if channel as u8 * CHANNEL_REGISTERS != WAVE {
+                self.step_envelope(channel);
+            }
+        }
+
+        let master = (self.registers[MASTER as usize] & 0x07) as f32 / 7.0;
+
+        sum as f32 / (15 * 255 * CHANNELS) as f32 * master
+    }
+
+    /// Phase increment per sample for a cycle of `clock / divider` Hz.
+    fn step(&self, clock: u64, divider: u64) -> u64 {
+        (clock << 32) / (divider * self.sample_rate as u64)
+    }
+
+    fn square_level(&mut self, channel: usize) -> i32 {
+        let step = self.step(SQUARE_CLOCK, self.period(channel));
+        let duty = DUTY[(self.register(channel, TONE) >> 6) as usize];
+        let voice = &mut self.voices[channel];
+
+        voice.phase = (voice.phase + step) & 0xffff_ffff;
+        let high = duty >> (7 - (voice.phase >> 29)) & 1 != 0;
+
+        if high {
+            voice.volume as i32
+        } else {
+            -(voice.volume as i32)
+        }
+    }
+
+    fn wave_level(&mut self) -> i32 {
+        let channel = (WAVE / CHANNEL_REGISTERS) as usize;
+        let step = self.step(SQUARE_CLOCK, 2 * self.period(channel));
+        let volume = self.register(channel, CONTROL) >> 4;
+        let voice = &mut self.voices[channel];
+
+        voice.phase = (voice.phase + step) & 0xffff_ffff;
+
+        let index = (voice.phase >> 27) as usize;
+        let byte = self.registers[WAVE_RAM as usize + index / 2];
+        let nibble = if index.is_multiple_of(2) {
+            byte >> 4
+        } else {
+            byte & 0x0f
+        };
+
+        // Center the 0-15 sample on zero, then scale by the volume.
+        (nibble as i32 * 2 - 15) * volume as i32 / 15
+    }
+
+    fn noise_level(&mut self) -> i32 {
+        let channel = (NOISE / CHANNEL_REGISTERS) as usize;
+        let tone = self.register(channel, TONE);
+        let shift = (tone >> 4) as u32 + 1;
+
+        // Clocks at `NOISE_CLOCK / divisor >> shift`; divisor 0 counts as
+        // 0.5.
+        let divisor = match tone & 0x07 {
+            0 => 1,
+            divisor => 2 * divisor as u64,
+        };
+
+        let step = self.step(NOISE_CLOCK * 2, divisor << shift);
+        let voice = &mut self.voices[channel];
+
+        voice.phase += step;
+
+        while voice.phase >= 1 << 32 {
+            voice.phase -= 1 << 32;
+
+            let bit = (voice.lfsr ^ (voice.lfsr >> 1)) & 1;
+            voice.lfsr = (voice.lfsr >> 1) | (bit << 14);
+
+            if tone & 0x08 != 0 {
+                voice.lfsr = (voice.lfsr & !(1 << 6)) | (bit << 6);
+            }
+        }
+
+        if voice.lfsr & 1 == 0 {
+            voice.volume as i32
+        } else {
+            -(voice.volume as i32)
+        }
+    }
+
+    fn step_envelope(&mut self, channel: usize) {
+        let control = self.register(channel, CONTROL);
+        let reload = self.envelope_samples(channel);
+        let voice = &mut self.voices[channel];
+
+        if reload == 0 {
+            return;
+        }
+
+        voice.envelope_left = voice.envelope_left.saturating_sub(1);
+
+        if voice.envelope_left > 0 {
+            return;
+        }
+
+        voice.envelope_left = reload;
+
+        if control & 0x08 != 0 {
+            voice.volume = (voice.volume + 1).min(15);
+        } else {
+            voice.volume = voice.volume.saturating_sub(1);
+        }
+    }
+}
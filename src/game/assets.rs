@@ -0,0 +1,9 @@
+//! Typed handles for the assets listed in `assets/assets.manifest`.
+//!
+//! `build.rs` generates this module's contents into `OUT_DIR`: a `SpriteId`
+//! constant per `sprite` line (resolved with `SpriteId::sprite`), a `&[u8]`
+//! constant per `data` line, an `Animation` constant per `anim` line and a
+//! `TileMap` constant per `tilemap` line.
+//! The data itself is `include_bytes!`d from `assets/processed/`.
+
+include!(concat!(env!("OUT_DIR"), "/assets.rs"));
@@ -0,0 +1,243 @@
+//! Interpreter for `.behavior` byte code compiled by `spritec --behavior`
+//! (layout in `docs/behavior_format.md`).
+//!
+//! `BehaviorScript` reads the code in place, so scripts run straight from
+//! `include_bytes!` data, and checks every instruction once up front.
+//! Each enemy with a script carries a `BehaviorVm`: a program counter, a
+//! wait timer and the counts of the open `repeat` loops, small enough to
+//! copy with the rest of the `SimState` and to hash and serialize.
+
+use crate::game::kinematics::Kinematics;
+use crate::math::geom::Vec2i;
+
+pub const BEHAVIOR_MAGIC: [u8; 4] = *b"BBBH";
+pub const BEHAVIOR_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 8;
+
+/// `repeat` loops a script may have open at once.
+pub const MAX_LOOP_DEPTH: usize = 2;
+
+/// Instructions run in one tick at most, so a loop without a `wait` still
+/// ends the tick.
+const MAX_STEPS_PER_TICK: u32 = 32;
+
+const OP_STOP: u8 = 0;
+const OP_MOVE: u8 = 1;
+const OP_WAIT: u8 = 2;
+const OP_FIRE: u8 = 3;
+const OP_JUMP: u8 = 4;
+const OP_REPEAT: u8 = 5;
+const OP_NEXT: u8 = 6;
+const OP_IF_HP_BELOW: u8 = 7;
+
+const fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+/// Bytes taken by the instruction with `opcode`, or `None` if there is no
+/// such instruction.
+const fn instruction_size(opcode: u8) -> Option<usize> {
+    match opcode {
+        OP_STOP => Some(1),
+        OP_FIRE | OP_REPEAT => Some(2),
+        OP_MOVE | OP_WAIT | OP_JUMP | OP_NEXT => Some(3),
+        OP_IF_HP_BELOW => Some(4),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct BehaviorScript {
+    code: &'static [u8],
+}
+
+impl BehaviorScript {
+    /// Check the header and every instruction. `None` if `data` is not a
+    /// well-formed script of this version, so running it never reads out
+    /// of bounds or lands inside an instruction.
+    pub const fn new(data: &'static [u8]) -> Option<Self> {
+        if data.len() < HEADER_SIZE
+            || data[0] != BEHAVIOR_MAGIC[0]
+            || data[1] != BEHAVIOR_MAGIC[1]
+            || data[2] != BEHAVIOR_MAGIC[2]
+            || data[3] != BEHAVIOR_MAGIC[3]
+            || read_u16(data, 4) != BEHAVIOR_VERSION
+            || data.len() != HEADER_SIZE + read_u16(data, 6) as usize
+        {
+            return None;
+        }
+
+        let (_, code) = data.split_at(HEADER_SIZE);
+        let mut at = 0;
+
+        while at < code.len() {
+            let Some(size) = instruction_size(code[at]) else {
+                return None;
+            };
+
+            if at + size > code.len() {
+                return None;
+            }
+
+            if code[at] == OP_WAIT && read_u16(code, at + 1) == 0 {
+                return None;
+            }
+
+            let target = match code[at] {
+                OP_JUMP | OP_NEXT => Some(read_u16(code, at + 1) as usize),
+                OP_IF_HP_BELOW => Some(read_u16(code, at + 2) as usize),
+                _ => None,
+            };
+
+            if let Some(target) = target
+                && !starts_instruction(code, target)
+            {
+                return None;
+            }
+
+            at += size;
+        }
+
+        // Running off the end would read past the code.
+        if code.is_empty() || code[code.len() - 1] != OP_STOP {
+            return None;
+        }
+
+        Some(Self { code })
+    }
+}
+
+/// Whether an instruction of `code` starts at `target`.
+const fn starts_instruction(code: &[u8], target: usize) -> bool {
+    let mut at = 0;
+
+    while at < target && at < code.len() {
+        match instruction_size(code[at]) {
+            Some(size) => at += size,
+            None => return false,
+        }
+    }
+
+    at == target && at < code.len()
+}
+
+/// Where an enemy is in its script.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BehaviorVm {
+    pc: u16,
+    /// Ticks left of the current `wait`.
+    wait: u16,
+    /// Iterations left of each open `repeat`, innermost last.
+    loops: [u8; MAX_LOOP_DEPTH],
+    depth: u8,
+}
+
+impl BehaviorVm {
+    /// At the start of a script.
+    pub const fn new() -> Self {
+        Self {
+            pc: 0,
+            wait: 0,
+            loops: [0; MAX_LOOP_DEPTH],
+            depth: 0,
+        }
+    }
+
+    /// Run `script` for one tick: until a `wait`, a `stop`, a second
+    /// `fire` or `MAX_STEPS_PER_TICK` instructions. `move` sets `body`'s
+    /// velocity; `hp` is what `if hp <` compares. Returns the pattern
+    /// fired this tick.
+    pub fn step(&mut self, script: &BehaviorScript, hp: u8, body: &mut Kinematics) -> Option<u8> {
+        if self.wait > 0 {
+            self.wait -= 1;
+            return None;
+        }
+
+        let code = script.code;
+        let mut fired = None;
+
+        for _ in 0..MAX_STEPS_PER_TICK {
+            let at = self.pc as usize;
+            let next = (at + instruction_size(code[at]).unwrap_or(1)) as u16;
+
+            self.pc = next;
+
+            match code[at] {
+                OP_MOVE => {
+                    body.set_velocity(Vec2i::new(
+                        code[at + 1] as i8 as i32,
+                        code[at + 2] as i8 as i32,
+                    ));
+                }
+                OP_WAIT => {
+                    // This tick is the first of the wait.
+                    self.wait = read_u16(code, at + 1) - 1;
+                    return fired;
+                }
+                OP_FIRE if fired.is_some() => {
+                    // One pattern a tick; this one starts the next.
+                    self.pc = at as u16;
+                    return fired;
+                }
+                OP_FIRE => fired = Some(code[at + 1]),
+                OP_JUMP => self.jump(read_u16(code, at + 1)),
+                OP_REPEAT => {
+                    // Only hand-made code nests deeper than `spritec`
+                    // allows; the innermost count is reused.
+                    let slot = (self.depth as usize).min(MAX_LOOP_DEPTH - 1);
+
+                    self.loops[slot] = code[at + 1];
+                    self.depth = slot as u8 + 1;
+                }
+                OP_NEXT if self.depth > 0 => {
+                    let slot = self.depth as usize - 1;
+
+                    self.loops[slot] = self.loops[slot].saturating_sub(1);
+
+                    if self.loops[slot] > 0 {
+                        self.pc = read_u16(code, at + 1);
+                    } else {
+                        self.depth -= 1;
+                    }
+                }
+                OP_IF_HP_BELOW if hp < code[at + 1] => self.jump(read_u16(code, at + 2)),
+                OP_STOP => {
+                    // Stay on the `stop`, keeping the last velocity.
+                    self.pc = at as u16;
+                    return fired;
+                }
+                _ => {}
+            }
+        }
+
+        fired
+    }
+
+    /// A jump leaves every open loop: labels are never inside one.
+    fn jump(&mut self, target: u16) {
+        self.pc = target;
+        self.depth = 0;
+    }
+
+    /// The state as two words, for `GameState::serialize`.
+    pub const fn to_words(self) -> [u32; 2] {
+        [
+            self.pc as u32 | (self.wait as u32) << 16,
+            self.loops[0] as u32 | (self.loops[1] as u32) << 8 | (self.depth as u32) << 16,
+        ]
+    }
+
+    /// The state `to_words` wrote, if it fits `script`.
+    pub fn from_words(words: [u32; 2], script: &BehaviorScript) -> Option<Self> {
+        let vm = Self {
+            pc: words[0] as u16,
+            wait: (words[0] >> 16) as u16,
+            loops: [words[1] as u8, (words[1] >> 8) as u8],
+            depth: (words[1] >> 16) as u8,
+        };
+
+        (starts_instruction(script.code, vm.pc as usize) && vm.depth as usize <= MAX_LOOP_DEPTH)
+            .then_some(vm)
+    }
+}
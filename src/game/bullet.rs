@@ -0,0 +1,47 @@
+use crate::game::entities::{EnemyKind, ProjectileKind};
+use crate::game::patterns::BulletSpawn;
+use crate::math::geom::{Rect, Vec2fx, Vec2i};
+
+/// Side of a bullet drawn without a sprite.
+pub const BULLET_BLOCK: i32 = 2;
+
+/// A projectile an enemy's pattern fired. It flies at a fixed velocity
+/// until it hits a ship or leaves the screen.
+#[derive(Clone, Copy)]
+pub struct Bullet {
+    pub kind: ProjectileKind,
+    /// The enemy that fired it, which its damage is put down to.
+    pub source: EnemyKind,
+    /// Centre, in sub-pixels.
+    pub pos: Vec2fx,
+    /// Per tick.
+    pub vel: Vec2fx,
+}
+
+impl Bullet {
+    /// A `kind` bullet where `spawn` puts it, its pattern speed taken in
+    /// units of the kind's `speed`.
+    pub const fn new(kind: ProjectileKind, source: EnemyKind, spawn: BulletSpawn) -> Self {
+        let speed = kind.def().speed;
+
+        Self {
+            kind,
+            source,
+            pos: spawn.pos,
+            vel: Vec2fx::new(spawn.vel.x * speed, spawn.vel.y * speed),
+        }
+    }
+
+    /// The sprite's bounds, or a `BULLET_BLOCK` square, around the centre.
+    pub fn hitbox(&self) -> Rect {
+        let size = match self.kind.def().sprite {
+            Some(sprite) => Vec2i::new(sprite.sprite().width as i32, sprite.sprite().height as i32),
+            None => Vec2i::new(BULLET_BLOCK, BULLET_BLOCK),
+        };
+
+        Rect::at(
+            self.pos.to_pixels() - Vec2i::new(size.x / 2, size.y / 2),
+            size,
+        )
+    }
+}
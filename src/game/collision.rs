@@ -0,0 +1,119 @@
+use crate::math::geom::Rect;
+use crate::renderer::framebuffer::{HEIGHT, WIDTH};
+
+/// The screen, in the coordinates hitboxes use.
+pub const SCREEN: Rect = Rect::new(0, 0, WIDTH as i32, HEIGHT as i32);
+
+pub const CELL_SIZE: usize = 16;
+pub const GRID_WIDTH: usize = WIDTH.div_ceil(CELL_SIZE);
+pub const GRID_HEIGHT: usize = HEIGHT.div_ceil(CELL_SIZE);
+
+/// Entries per cell. Inserts into a full cell are dropped, so the grid can
+/// miss collisions in extreme crowds but never grows.
+const CELL_CAPACITY: usize = 8;
+
+/// Uniform broadphase grid over the screen.
+///
+/// Entities are inserted by index into every cell their hitbox touches.
+/// Indices are limited to 0..64 so queries can de-duplicate with a bitmask.
+#[derive(Clone, Copy)]
+pub struct CollisionGrid {
+    cells: [[u8; CELL_CAPACITY]; GRID_WIDTH * GRID_HEIGHT],
+    counts: [u8; GRID_WIDTH * GRID_HEIGHT],
+}
+
+impl Default for CollisionGrid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CollisionGrid {
+    pub const fn new() -> Self {
+        Self {
+            cells: [[0; CELL_CAPACITY]; GRID_WIDTH * GRID_HEIGHT],
+            counts: [0; GRID_WIDTH * GRID_HEIGHT],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.counts = [0; GRID_WIDTH * GRID_HEIGHT];
+    }
+
+    /// Visit every on-screen cell `hitbox` touches.
+    fn for_each_cell(hitbox: &Rect, mut f: impl FnMut(usize)) {
+        let Some(visible) = hitbox.intersection(&SCREEN) else {
+            return;
+        };
+
+        let cell = |v: i32| v as usize / CELL_SIZE;
+
+        let x0 = cell(visible.x);
+        let x1 = cell(visible.right() - 1);
+        let y0 = cell(visible.y);
+        let y1 = cell(visible.bottom() - 1);
+
+        for cy in y0..=y1 {
+            for cx in x0..=x1 {
+                f(cy * GRID_WIDTH + cx);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, index: usize, hitbox: &Rect) {
+        debug_assert!(index < 64, "grid indices are limited to 0..64");
+
+        Self::for_each_cell(hitbox, |cell| {
+            let count = self.counts[cell] as usize;
+
+            if count < CELL_CAPACITY {
+                self.cells[cell][count] = index as u8;
+                self.counts[cell] += 1;
+            }
+        });
+    }
+
+    /// Bitmask of every index sharing at least one cell with `hitbox`.
+    pub fn query(&self, hitbox: &Rect) -> u64 {
+        let mut found = 0u64;
+
+        Self::for_each_cell(hitbox, |cell| {
+            for &index in &self.cells[cell][..self.counts[cell] as usize] {
+                found |= 1 << index;
+            }
+        });
+
+        found
+    }
+
+    /// Number of entries in cell (`cx`, `cy`).
+    pub fn occupancy(&self, cx: usize, cy: usize) -> usize {
+        self.counts[cy * GRID_WIDTH + cx] as usize
+    }
+}
+
+/// Collision results for the current tick.
+#[derive(Clone, Copy)]
+pub struct CollisionState {
+    pub grid: CollisionGrid,
+    /// Bitmask of enemy indices whose hitbox overlaps the player's.
+    pub player_hits: u64,
+    /// The same for the co-op partner's ship.
+    pub partner_hits: u64,
+}
+
+impl Default for CollisionState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CollisionState {
+    pub const fn new() -> Self {
+        Self {
+            grid: CollisionGrid::new(),
+            player_hits: 0,
+            partner_hits: 0,
+        }
+    }
+}
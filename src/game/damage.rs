@@ -0,0 +1,251 @@
+//! Damage: every hit the simulation lands goes through here.
+//!
+//! A `Hit` says who dealt it, the kind of damage, how much, its chance of
+//! a crit and any status effect it applies. `Health::take` applies one to a
+//! target: nothing during the target's invulnerability frames, less by its
+//! `Resistances`, `CRIT_MULTIPLIER` times as much on a crit, and the
+//! effect stacked on (`game::status`). `Health::tick` counts the
+//! invulnerability down and runs the effects, burns dealing their damage.
+//! Both return what was `Dealt`, which the tick gathers into the
+//! `DamageLog` of its `TickEvents` for whatever reacts to damage: the hit
+//! flash, stats and scoring, on-hit effects. The crit roll is handed in, so
+//! a hit lands the same way in every replay.
+
+use crate::game::entities::EnemyKind;
+use crate::game::status::{StatusEffect, StatusEffects};
+
+/// Damage kinds, for tables indexed by `DamageKind::index`.
+pub const DAMAGE_KINDS: usize = 3;
+
+/// A crit deals this many times the damage.
+pub const CRIT_MULTIPLIER: u8 = 2;
+
+/// Most damage a `DamageLog` keeps in a tick; the rest is not reported.
+pub const DAMAGE_LOG_CAPACITY: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageKind {
+    /// Running into something.
+    Contact,
+    Shot,
+    /// A burn status ticking; armour does not stop it.
+    Burn,
+}
+
+impl DamageKind {
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Who dealt the damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageSource {
+    /// The first ship is 0, the co-op partner's 1.
+    Ship(u8),
+    Enemy(EnemyKind),
+    /// A burn status, after the hit that set it.
+    Burning,
+}
+
+/// Who took the damage.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DamageTarget {
+    /// The first ship is 0, the co-op partner's 1.
+    Ship(u8),
+    /// By its slot in the enemy pool when it was hit; a despawn later in the
+    /// tick can move it.
+    Enemy(u8),
+}
+
+/// One blow about to land.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Hit {
+    pub source: DamageSource,
+    pub kind: DamageKind,
+    pub amount: u8,
+    /// In 256ths: 0 never crits.
+    pub crit_chance: u8,
+    /// Stacked on the target when the hit lands.
+    pub status: Option<StatusEffect>,
+}
+
+impl Hit {
+    /// `amount` of `kind` from `source`, without crits or a status effect.
+    pub const fn new(source: DamageSource, kind: DamageKind, amount: u8) -> Self {
+        Self {
+            source,
+            kind,
+            amount,
+            crit_chance: 0,
+            status: None,
+        }
+    }
+}
+
+/// Damage taken off every hit, by `DamageKind::index`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Resistances(pub [u8; DAMAGE_KINDS]);
+
+impl Resistances {
+    pub const NONE: Self = Self([0; DAMAGE_KINDS]);
+
+    /// `armor` off every contact and shot, none off burns.
+    pub const fn armor(armor: u8) -> Self {
+        let mut resist = [armor; DAMAGE_KINDS];
+        resist[DamageKind::Burn.index()] = 0;
+        Self(resist)
+    }
+
+    pub const fn reduce(&self, kind: DamageKind, amount: u8) -> u8 {
+        amount.saturating_sub(self.0[kind.index()])
+    }
+}
+
+/// What a hit or a burn did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dealt {
+    pub target: DamageTarget,
+    pub source: DamageSource,
+    pub kind: DamageKind,
+    /// Hit points lost; 0 if resistances took it all.
+    pub amount: u8,
+    pub crit: bool,
+    /// It took the last hit point.
+    pub fatal: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Health {
+    pub hp: u8,
+    /// Ticks left in which hits are ignored.
+    pub invulnerable: u8,
+    pub status: StatusEffects,
+}
+
+impl Health {
+    pub const fn new(hp: u8) -> Self {
+        Self {
+            hp,
+            invulnerable: 0,
+            status: StatusEffects::NONE,
+        }
+    }
+
+    /// Land `hit` on `target`, unless it is invulnerable or already down,
+    /// and make it invulnerable for `iframes` ticks. A `roll` below the
+    /// hit's `crit_chance` crits.
+    pub fn take(
+        &mut self,
+        target: DamageTarget,
+        hit: &Hit,
+        resist: &Resistances,
+        iframes: u8,
+        roll: u8,
+    ) -> Option<Dealt> {
+        if self.invulnerable > 0 || self.hp == 0 {
+            return None;
+        }
+
+        let crit = roll < hit.crit_chance;
+        let mut amount = resist.reduce(hit.kind, hit.amount);
+
+        if crit {
+            amount = amount.saturating_mul(CRIT_MULTIPLIER);
+        }
+
+        if let Some(effect) = hit.status {
+            self.status.apply(effect);
+        }
+
+        self.invulnerable = iframes;
+        Some(self.lose(target, hit.source, hit.kind, amount, crit))
+    }
+
+    /// Advance one tick: count the invulnerability down and run the status
+    /// effects, dealing any burn damage due.
+    pub fn tick(&mut self, target: DamageTarget, resist: &Resistances) -> Option<Dealt> {
+        self.invulnerable = self.invulnerable.saturating_sub(1);
+
+        let burn = self.status.tick().burn;
+
+        if burn == 0 || self.hp == 0 {
+            return None;
+        }
+
+        let amount = resist.reduce(DamageKind::Burn, burn);
+
+        Some(self.lose(
+            target,
+            DamageSource::Burning,
+            DamageKind::Burn,
+            amount,
+            false,
+        ))
+    }
+
+    fn lose(
+        &mut self,
+        target: DamageTarget,
+        source: DamageSource,
+        kind: DamageKind,
+        amount: u8,
+        crit: bool,
+    ) -> Dealt {
+        let amount = amount.min(self.hp);
+
+        self.hp -= amount;
+
+        Dealt {
+            target,
+            source,
+            kind,
+            amount,
+            crit,
+            fatal: self.hp == 0,
+        }
+    }
+}
+
+/// The damage dealt in a tick, in the order it landed.
+#[derive(Debug, Clone, Copy)]
+pub struct DamageLog {
+    dealt: [Dealt; DAMAGE_LOG_CAPACITY],
+    len: u8,
+}
+
+impl Default for DamageLog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DamageLog {
+    pub const fn new() -> Self {
+        let unused = Dealt {
+            target: DamageTarget::Ship(0),
+            source: DamageSource::Burning,
+            kind: DamageKind::Contact,
+            amount: 0,
+            crit: false,
+            fatal: false,
+        };
+
+        Self {
+            dealt: [unused; DAMAGE_LOG_CAPACITY],
+            len: 0,
+        }
+    }
+
+    /// Keep `dealt`, unless the log is full.
+    pub fn push(&mut self, dealt: Dealt) {
+        if let Some(slot) = self.dealt.get_mut(self.len as usize) {
+            *slot = dealt;
+            self.len += 1;
+        }
+    }
+
+    pub fn as_slice(&self) -> &[Dealt] {
+        &self.dealt[..self.len as usize]
+    }
+}
@@ -1,6 +1,62 @@
+use crate::game::behavior::BehaviorVm;
+use crate::game::damage::{Health, Resistances};
+use crate::game::entities::EnemyKind;
+use crate::game::kinematics::Kinematics;
+use crate::math::geom::{FIXED_ONE, Rect, Vec2i};
+
 #[derive(Clone, Copy)]
 pub struct Enemy {
-    pub x: i32,
-    pub y: i32,
-    pub vx: i32,
+    /// Top-left corner and motion. Only a script gives it vertical speed;
+    /// it starts out flying straight.
+    pub body: Kinematics,
+    pub kind: EnemyKind,
+    /// Starts at its kind's `hp`.
+    pub health: Health,
+    /// Where it is in its kind's script, if the kind has one.
+    pub vm: BehaviorVm,
+    /// The pattern its script fired this tick.
+    pub firing: Option<u8>,
+}
+
+impl Enemy {
+    /// A `kind` enemy at `pos`, flying left at its speed, with its kind's
+    /// drag and top speed.
+    pub const fn new(kind: EnemyKind, pos: Vec2i) -> Self {
+        let def = kind.def();
+        let mut body = Kinematics::at(pos);
+        body.vel = Vec2i::new(-def.speed, 0).to_fixed();
+        body.drag = def.drag;
+        body.max_speed = match def.max_speed {
+            0 => None,
+            max => Some(max * FIXED_ONE),
+        };
+
+        Self {
+            body,
+            kind,
+            health: Health::new(def.hp),
+            vm: BehaviorVm::new(),
+            firing: None,
+        }
+    }
+
+    /// The top-left corner's pixel.
+    pub const fn pos(&self) -> Vec2i {
+        self.body.position()
+    }
+
+    /// What its kind's armour takes off each hit.
+    pub const fn resistances(&self) -> Resistances {
+        Resistances::armor(self.kind.def().armor)
+    }
+
+    /// The sprite's bounds, or the one pixel of a dot.
+    pub fn hitbox(&self) -> Rect {
+        let size = match self.kind.def().sprite {
+            Some(sprite) => Vec2i::new(sprite.sprite().width as i32, sprite.sprite().height as i32),
+            None => Vec2i::new(1, 1),
+        };
+
+        Rect::at(self.pos(), size)
+    }
 }
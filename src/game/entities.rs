@@ -0,0 +1,138 @@
+//! Stats of everything the game spawns, from `assets/entities.toml`.
+//!
+//! `build.rs` generates this module's tables into `OUT_DIR`: for each
+//! `[enemy.<name>]`, `[projectile.<name>]` and `[pickup.<name>]` table a
+//! constant of `EnemyKind`, `ProjectileKind` or `PickupKind`, resolved with
+//! `def()`. Balancing is an edit to the data file; gameplay code names the
+//! kinds and reads their definitions (ADR 0026).
+
+use crate::game::assets::SpriteId;
+use crate::game::behavior::BehaviorScript;
+use crate::game::loot::DropTable;
+
+include!(concat!(env!("OUT_DIR"), "/entities.rs"));
+
+/// How an enemy moves, besides flying left at its speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Behavior {
+    Straight,
+    /// Closes in on the first ship's height by a pixel every other tick.
+    Home,
+}
+
+#[derive(Debug)]
+pub struct EnemyDef {
+    pub name: &'static str,
+    /// Pixels per tick, leftward.
+    pub speed: i32,
+    /// Share of its velocity lost every tick, in 256ths (`Kinematics::drag`).
+    pub drag: i32,
+    /// Fastest it moves along either axis, in pixels per tick; 0 for no
+    /// limit.
+    pub max_speed: i32,
+    /// Damage it takes to destroy.
+    pub hp: u8,
+    /// Damage taken off every contact and shot; burns ignore it.
+    pub armor: u8,
+    /// Points for destroying it.
+    pub score: u32,
+    /// Drawn as a dot without one.
+    pub sprite: Option<SpriteId>,
+    pub behavior: Behavior,
+    /// Byte code that steers it, from `spritec --behavior`; `speed` is
+    /// only where it starts.
+    pub script: Option<BehaviorScript>,
+    /// Marked on the radar so it stands out from the rest.
+    pub boss: bool,
+    /// What it leaves when destroyed.
+    pub drops: DropTable,
+}
+
+#[derive(Debug)]
+pub struct ProjectileDef {
+    pub name: &'static str,
+    /// Pixels per tick.
+    pub speed: i32,
+    pub damage: u8,
+    pub sprite: Option<SpriteId>,
+}
+
+#[derive(Debug)]
+pub struct PickupDef {
+    pub name: &'static str,
+    /// Points for collecting it.
+    pub score: u32,
+    /// Spent in the shop between stages (`game::shop`).
+    pub coins: u8,
+    pub sprite: Option<SpriteId>,
+}
+
+const _: () = assert!(
+    !EnemyKind::ALL.is_empty(),
+    "assets/entities.toml defines no enemy"
+);
+
+/// Enemies defined, for tables indexed by `EnemyKind::index`.
+pub const ENEMY_KINDS: usize = EnemyKind::ALL.len();
+
+const _: () = assert!(
+    !PickupKind::ALL.is_empty(),
+    "assets/entities.toml defines no pickup"
+);
+
+/// Pickups defined, for tables indexed by `PickupKind::index`.
+pub const PICKUP_KINDS: usize = PickupKind::ALL.len();
+
+impl EnemyKind {
+    /// Position in `assets/entities.toml`, for hashes and snapshots.
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    pub const fn from_index(index: u8) -> Option<Self> {
+        if (index as usize) < Self::ALL.len() {
+            Some(Self(index))
+        } else {
+            None
+        }
+    }
+
+    /// The enemy a level spawn marker of `kind` places: the `kind`th one
+    /// defined, or the first if there are fewer.
+    pub const fn for_marker(kind: u8) -> Self {
+        match Self::from_index(kind) {
+            Some(enemy) => enemy,
+            None => Self::ALL[0],
+        }
+    }
+}
+
+impl ProjectileKind {
+    /// Position in `assets/entities.toml`, for hashes and snapshots.
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    pub const fn from_index(index: u8) -> Option<Self> {
+        if (index as usize) < Self::ALL.len() {
+            Some(Self(index))
+        } else {
+            None
+        }
+    }
+}
+
+impl PickupKind {
+    /// Position in `assets/entities.toml`, for hashes and snapshots.
+    pub const fn index(self) -> u8 {
+        self.0
+    }
+
+    pub const fn from_index(index: u8) -> Option<Self> {
+        if (index as usize) < Self::ALL.len() {
+            Some(Self(index))
+        } else {
+            None
+        }
+    }
+}
@@ -1,3 +1,10 @@
+/// Pools at least this full are updated in parallel with the `threads`
+/// feature; below it, handing the work to other threads costs more than
+/// it saves.
+#[cfg(feature = "threads")]
+pub const PARALLEL_MIN: usize = 256;
+
+#[derive(Clone, Copy)]
 pub struct FixedPool<T, const N: usize> {
     items: [T; N],
     len: usize,
@@ -11,9 +18,10 @@ impl<T: Copy, const N: usize> FixedPool<T, N> {
         }
     }
 
-    pub fn spawn(&mut self, item: T) -> Result<(), ()> {
+    /// Add an item to the pool, handing it back if the pool is full.
+    pub fn spawn(&mut self, item: T) -> Result<(), T> {
         if self.len >= N {
-            return Err(());
+            return Err(item);
         }
 
         self.items[self.len] = item;
@@ -30,6 +38,51 @@ impl<T: Copy, const N: usize> FixedPool<T, N> {
         self.items[index] = self.items[self.len];
     }
 
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Remove every item `dead` picks, in the same order as calling
+    /// `despawn` on each while walking the pool: the last item takes the
+    /// removed one's place and is checked next.
+    pub fn despawn_where(&mut self, mut dead: impl FnMut(&T) -> bool) {
+        let mut i = 0;
+
+        while i < self.len {
+            if dead(&self.items[i]) {
+                self.despawn(i);
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    /// Run `update` on every item. It sees one item and nothing else, so
+    /// the order does not matter and the result is the same with or
+    /// without the `threads` feature.
+    #[cfg(not(feature = "threads"))]
+    pub fn update_each(&mut self, update: impl Fn(&mut T)) {
+        self.as_mut_slice().iter_mut().for_each(update);
+    }
+
+    /// Run `update` on every item, spread over rayon's threads once the
+    /// pool holds `PARALLEL_MIN` items. It sees one item and nothing else,
+    /// so the order does not matter and the result is the same on any
+    /// number of threads.
+    #[cfg(feature = "threads")]
+    pub fn update_each(&mut self, update: impl Fn(&mut T) + Sync + Send)
+    where
+        T: Send,
+    {
+        use rayon::prelude::*;
+
+        if self.len >= PARALLEL_MIN {
+            self.as_mut_slice().par_iter_mut().for_each(update);
+        } else {
+            self.as_mut_slice().iter_mut().for_each(update);
+        }
+    }
+
     pub fn as_slice(&self) -> &[T] {
         &self.items[..self.len]
     }
@@ -42,6 +95,10 @@ impl<T: Copy, const N: usize> FixedPool<T, N> {
         self.len
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
     pub fn capacity(&self) -> usize {
         N
     }
@@ -0,0 +1,200 @@
+//! Ghosts: the path another run's ship took, shown dithered beside the
+//! player for score attack.
+//!
+//! A ghost stores positions rather than inputs, so it plays back the same
+//! whatever the live game does and even after a change to the simulation
+//! would make the run's input replay differently. The ship moves at most a
+//! pixel per axis per tick, so each tick is one of nine steps; runs of the
+//! same step are packed into a byte each:
+//!
+//! | Offset | Size | Field                                          |
+//! |--------|------|------------------------------------------------|
+//! | 0      | 4    | Magic `BBGH`                                   |
+//! | 4      | 2    | Version                                        |
+//! | 6      | 2    | Ship x at frame 0                              |
+//! | 8      | 2    | Ship y at frame 0                              |
+//! | 10     | 4    | Frames                                         |
+//! | 14     | ...  | Runs: step `(dx + 1) * 3 + (dy + 1)` in the high nibble, length - 1 in the low |
+//!
+//! All fields are little-endian.
+
+#[cfg(feature = "std")]
+use std::{fs, io, path::Path};
+
+#[cfg(feature = "std")]
+use crate::game::GameState;
+use crate::game::assets::PLAYER_FLY;
+use crate::math::geom::Vec2i;
+use crate::renderer::framebuffer::FrameBuffer;
+
+pub const GHOST_MAGIC: [u8; 4] = *b"BBGH";
+pub const GHOST_VERSION: u16 = 1;
+
+pub const GHOST_HEADER_SIZE: usize = 14;
+
+/// Longest run one byte holds.
+#[cfg(feature = "std")]
+const MAX_RUN: u8 = 16;
+
+const fn step_delta(step: u8) -> Vec2i {
+    Vec2i::new((step / 3) as i32 - 1, (step % 3) as i32 - 1)
+}
+
+/// A ghost file, checked but not decoded.
+#[derive(Debug, Clone, Copy)]
+pub struct Ghost<'a> {
+    runs: &'a [u8],
+    start: Vec2i,
+    frames: u32,
+}
+
+impl<'a> Ghost<'a> {
+    /// Check the header and that the runs add up to its frame count.
+    /// `None` if `data` is not a ghost of this version.
+    pub fn new(data: &'a [u8]) -> Option<Self> {
+        if data.len() < GHOST_HEADER_SIZE
+            || data[..4] != GHOST_MAGIC
+            || u16::from_le_bytes([data[4], data[5]]) != GHOST_VERSION
+        {
+            return None;
+        }
+
+        let runs = &data[GHOST_HEADER_SIZE..];
+        let frames = u32::from_le_bytes([data[10], data[11], data[12], data[13]]);
+        let total: u32 = runs.iter().map(|run| (run & 0xF) as u32 + 1).sum();
+
+        if total != frames || runs.iter().any(|run| run >> 4 > 8) {
+            return None;
+        }
+
+        Some(Self {
+            runs,
+            start: Vec2i::new(
+                u16::from_le_bytes([data[6], data[7]]) as i32,
+                u16::from_le_bytes([data[8], data[9]]) as i32,
+            ),
+            frames,
+        })
+    }
+
+    /// Frames recorded; the ghost is gone after the last.
+    pub fn frames(&self) -> u32 {
+        self.frames
+    }
+
+    pub fn playback(&self) -> GhostPlayback<'a> {
+        GhostPlayback {
+            ghost: *self,
+            run: 0,
+            into_run: 0,
+            frame: 0,
+            position: self.start,
+        }
+    }
+}
+
+/// A cursor into a `Ghost`. Moving forward is cheap; moving back starts
+/// over from frame 0.
+#[derive(Debug, Clone, Copy)]
+pub struct GhostPlayback<'a> {
+    ghost: Ghost<'a>,
+    /// The run the next step comes from.
+    run: usize,
+    /// Steps of that run already taken.
+    into_run: u8,
+    frame: u32,
+    position: Vec2i,
+}
+
+impl GhostPlayback<'_> {
+    /// The ship's top-left corner after `frame` ticks, or `None` past the
+    /// end of the recording.
+    pub fn position_at(&mut self, frame: u32) -> Option<Vec2i> {
+        if frame > self.ghost.frames {
+            return None;
+        }
+
+        if frame < self.frame {
+            *self = self.ghost.playback();
+        }
+
+        while self.frame < frame {
+            let run = self.ghost.runs[self.run];
+            self.position += step_delta(run >> 4);
+            self.frame += 1;
+            self.into_run += 1;
+
+            if self.into_run > run & 0xF {
+                self.run += 1;
+                self.into_run = 0;
+            }
+        }
+
+        Some(self.position)
+    }
+}
+
+/// Draw the ghost ship at `position` over every other pixel, animated like
+/// the player's on `frame`, counted at `TICK_RATE`.
+pub fn render_ghost(framebuffer: &mut FrameBuffer, position: Vec2i, frame: u32) {
+    let sprite = PLAYER_FLY.sprite_at(frame % 60);
+    framebuffer.draw_sprite_dithered(position.x, position.y, sprite);
+}
+
+/// Builds a ghost from a run, one tick at a time.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct GhostRecorder {
+    start: (u16, u16),
+    last: Vec2i,
+    frames: u32,
+    runs: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl GhostRecorder {
+    /// Start recording from the ship position of `state`, which should be
+    /// a new game.
+    pub fn new(state: &GameState) -> Self {
+        let start = state.sim.player.pos;
+
+        Self {
+            start: (start.x as u16, start.y as u16),
+            last: start,
+            frames: 0,
+            runs: Vec::new(),
+        }
+    }
+
+    /// Add the tick `state` has just run. A jump of more than a pixel is
+    /// stored as a one-pixel step, so the ghost lags behind until the ship
+    /// stops.
+    pub fn record(&mut self, state: &GameState) {
+        let delta = (state.sim.player.pos - self.last).signum();
+        let step = ((delta.x + 1) * 3 + (delta.y + 1)) as u8;
+
+        self.last += delta;
+        self.frames += 1;
+
+        match self.runs.last_mut() {
+            Some(run) if *run >> 4 == step && (*run & 0xF) + 1 < MAX_RUN => *run += 1,
+            _ => self.runs.push(step << 4),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(GHOST_HEADER_SIZE + self.runs.len());
+
+        bytes.extend_from_slice(&GHOST_MAGIC);
+        bytes.extend_from_slice(&GHOST_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.start.0.to_le_bytes());
+        bytes.extend_from_slice(&self.start.1.to_le_bytes());
+        bytes.extend_from_slice(&self.frames.to_le_bytes());
+        bytes.extend_from_slice(&self.runs);
+        bytes
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+}
@@ -0,0 +1,194 @@
+//! Controller rumble: game events played as envelopes on the two motors.
+//!
+//! `Haptics::update` takes the `TickEvents` every live tick returns, like
+//! `stats::Stats`, and starts the `RumbleEvent` each calls for: a ship hit,
+//! or a boss running into one. Each event has an `Envelope` of intensity
+//! over ticks, and the motors run at the strongest envelope playing. The
+//! binary hands `Haptics::rumble` to `Platform::rumble` every frame. The
+//! ship has no weapons yet, so nothing starts `Charge`; a weapon can call
+//! `Haptics::play` with it.
+
+use crate::game::TickEvents;
+use crate::game::damage::{DamageSource, DamageTarget};
+use crate::platform::Rumble;
+
+/// Envelopes playing at once; a new one replaces the closest to done.
+const VOICES: usize = 4;
+
+/// Intensity over time: up to `peak` over `attack` ticks, held for `hold`,
+/// then down to nothing over `release`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Envelope {
+    pub peak: Rumble,
+    pub attack: u8,
+    pub hold: u8,
+    pub release: u8,
+}
+
+impl Envelope {
+    const fn len(&self) -> u16 {
+        self.attack as u16 + self.hold as u16 + self.release as u16
+    }
+
+    /// The motors `age` ticks in.
+    fn at(&self, age: u16) -> Rumble {
+        let (attack, hold) = (self.attack as u16, self.hold as u16);
+        // Out of 256.
+        let share = if age < attack {
+            (age + 1) * 256 / (attack + 1)
+        } else if age < attack + hold {
+            256
+        } else if age < self.len() {
+            (self.len() - age) * 256 / (self.release as u16 + 1)
+        } else {
+            0
+        } as u32;
+
+        Rumble {
+            low: (self.peak.low as u32 * share / 256) as u16,
+            high: (self.peak.high as u32 * share / 256) as u16,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RumbleEvent {
+    /// A ship was hit: a short, sharp buzz.
+    Hit,
+    /// A boss ran into a ship: a long, heavy thud.
+    BossStomp,
+    /// A weapon charging: a light swell.
+    Charge,
+}
+
+impl RumbleEvent {
+    pub const fn envelope(self) -> Envelope {
+        match self {
+            Self::Hit => Envelope {
+                peak: Rumble {
+                    low: 0x4000,
+                    high: 0xc000,
+                },
+                attack: 0,
+                hold: 6,
+                release: 6,
+            },
+            Self::BossStomp => Envelope {
+                peak: Rumble {
+                    low: 0xffff,
+                    high: 0x6000,
+                },
+                attack: 2,
+                hold: 12,
+                release: 24,
+            },
+            Self::Charge => Envelope {
+                peak: Rumble {
+                    low: 0,
+                    high: 0x5000,
+                },
+                attack: 30,
+                hold: 0,
+                release: 4,
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Haptics {
+    enabled: bool,
+    /// Each envelope playing and the ticks since it started.
+    voices: [Option<(Envelope, u16)>; VOICES],
+}
+
+impl Default for Haptics {
+    fn default() -> Self {
+        Self::new(true)
+    }
+}
+
+impl Haptics {
+    pub const fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            voices: [None; VOICES],
+        }
+    }
+
+    pub const fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turn rumble on or off; off stops what is playing.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+
+        if !enabled {
+            self.voices = [None; VOICES];
+        }
+    }
+
+    /// Start `event`'s envelope, unless rumble is off.
+    pub fn play(&mut self, event: RumbleEvent) {
+        if !self.enabled {
+            return;
+        }
+
+        let voice = match self.voices.iter().position(Option::is_none) {
+            Some(free) => free,
+            None => (0..VOICES)
+                .min_by_key(|&i| {
+                    self.voices[i].map_or(0, |(envelope, age)| envelope.len().saturating_sub(age))
+                })
+                .unwrap_or(0),
+        };
+
+        self.voices[voice] = Some((event.envelope(), 0));
+    }
+
+    /// Advance one tick, then start the events in `events`.
+    pub fn update(&mut self, events: &TickEvents) {
+        for voice in &mut self.voices {
+            if let Some((envelope, age)) = voice {
+                *age += 1;
+
+                if *age >= envelope.len() {
+                    *voice = None;
+                }
+            }
+        }
+
+        let mut hit = false;
+        let mut stomp = false;
+
+        for dealt in events.damage.as_slice() {
+            if !matches!(dealt.target, DamageTarget::Ship(_)) {
+                continue;
+            }
+
+            match dealt.source {
+                DamageSource::Enemy(kind) if kind.def().boss => stomp = true,
+                _ => hit = true,
+            }
+        }
+
+        if stomp {
+            self.play(RumbleEvent::BossStomp);
+        } else if hit {
+            self.play(RumbleEvent::Hit);
+        }
+    }
+
+    /// The motors now: each at the strongest envelope playing.
+    pub fn rumble(&self) -> Rumble {
+        self.voices
+            .iter()
+            .flatten()
+            .map(|(envelope, age)| envelope.at(*age))
+            .fold(Rumble::OFF, |a, b| Rumble {
+                low: a.low.max(b.low),
+                high: a.high.max(b.high),
+            })
+    }
+}
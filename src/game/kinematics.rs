@@ -0,0 +1,90 @@
+//! Movement for entities: a position, velocity and acceleration in
+//! sub-pixels (`Vec2fx`), with drag and a top speed, advanced a tick at a
+//! time by `integrate`.
+//!
+//! Everything that moves on its own steps through the same `integrate`,
+//! so tuning how a kind of entity handles is a matter of its `drag`,
+//! `max_speed` and the acceleration it is given, not of its update code.
+//! The player's ship moves a whole pixel per held direction and does not
+//! use it.
+
+use crate::math::geom::{FIXED_ONE, Vec2fx, Vec2i};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Kinematics {
+    pub pos: Vec2fx,
+    /// Per tick.
+    pub vel: Vec2fx,
+    /// Added to the velocity every tick.
+    pub accel: Vec2fx,
+    /// Share of the velocity lost every tick, in 1/`FIXED_ONE`ths: 0 keeps
+    /// all of it, `FIXED_ONE` stops dead.
+    pub drag: i32,
+    /// Fastest the body moves along either axis, per tick; `None` for no
+    /// limit.
+    pub max_speed: Option<i32>,
+}
+
+impl Kinematics {
+    /// A body at rest at pixel `pos`, without drag or a top speed.
+    pub const fn at(pos: Vec2i) -> Self {
+        Self {
+            pos: pos.to_fixed(),
+            vel: Vec2fx::ZERO,
+            accel: Vec2fx::ZERO,
+            drag: 0,
+            max_speed: None,
+        }
+    }
+
+    /// The whole pixel the body is in.
+    pub const fn position(&self) -> Vec2i {
+        self.pos.to_pixels()
+    }
+
+    /// The whole pixel the body was in before the last `integrate`, if
+    /// nothing else moved it since.
+    pub const fn previous_position(&self) -> Vec2i {
+        self.previous_position_scaled(FIXED_ONE)
+    }
+
+    /// `previous_position` after an `integrate_scaled` by `scale`.
+    pub const fn previous_position_scaled(&self, scale: i32) -> Vec2i {
+        Vec2fx::new(
+            self.pos.x - self.vel.x * scale / FIXED_ONE,
+            self.pos.y - self.vel.y * scale / FIXED_ONE,
+        )
+        .to_pixels()
+    }
+
+    /// Set the velocity to whole pixels per tick.
+    pub fn set_velocity(&mut self, vel: Vec2i) {
+        self.vel = vel.to_fixed();
+    }
+
+    /// Move the position by whole pixels, without touching the velocity.
+    pub fn nudge(&mut self, offset: Vec2i) {
+        self.pos += offset.to_fixed();
+    }
+
+    /// Advance one tick: accelerate, apply drag, hold to the top speed,
+    /// then move.
+    pub fn integrate(&mut self) {
+        self.integrate_scaled(FIXED_ONE);
+    }
+
+    /// `integrate`, moving only `scale`/`FIXED_ONE` of the new velocity,
+    /// for a slowed body.
+    pub fn integrate_scaled(&mut self, scale: i32) {
+        let mut vel = self.vel + self.accel;
+
+        vel -= Vec2fx::new(vel.x * self.drag / FIXED_ONE, vel.y * self.drag / FIXED_ONE);
+
+        if let Some(max) = self.max_speed {
+            vel = Vec2fx::new(vel.x.clamp(-max, max), vel.y.clamp(-max, max));
+        }
+
+        self.vel = vel;
+        self.pos += Vec2fx::new(vel.x * scale / FIXED_ONE, vel.y * scale / FIXED_ONE);
+    }
+}
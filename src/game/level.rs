@@ -0,0 +1,145 @@
+//! Reader for level files written by `tools/leveled`.
+//!
+//! A level is a tile map plus spawn markers and a waypoint path, in the
+//! little-endian layout described in `docs/level_format.md`. Like
+//! `AssetPack`, everything here is a `const fn`, so an `include_bytes!`d
+//! level can be checked in a `const` item.
+
+use crate::math::geom::Vec2i;
+
+pub const LEVEL_MAGIC: [u8; 4] = *b"BBLV";
+pub const LEVEL_VERSION: u16 = 1;
+
+pub const HEADER_SIZE: usize = 16;
+pub const SPAWN_SIZE: usize = 6;
+pub const WAYPOINT_SIZE: usize = 4;
+
+/// `Level::scroll_speed` is in 1/`SCROLL_SUBPIXELS` pixels per tick.
+pub const SCROLL_SUBPIXELS: u16 = 16;
+
+/// Pixels along each side of a tile.
+pub const TILE_SIZE: usize = 8;
+
+const fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+/// Where an enemy of `kind` enters, in level pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Spawn {
+    pub x: u16,
+    pub y: u16,
+    pub kind: u8,
+}
+
+impl Spawn {
+    pub const fn pos(&self) -> Vec2i {
+        Vec2i::new(self.x as i32, self.y as i32)
+    }
+}
+
+/// One point of the level's waypoint path, in level pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Waypoint {
+    pub x: u16,
+    pub y: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Level<'a> {
+    data: &'a [u8],
+    width: usize,
+    height: usize,
+    spawn_count: usize,
+    waypoint_count: usize,
+}
+
+impl<'a> Level<'a> {
+    /// Check the header and section sizes. `None` if `data` is not a level
+    /// of this version.
+    pub const fn new(data: &'a [u8]) -> Option<Self> {
+        if data.len() < HEADER_SIZE
+            || data[0] != LEVEL_MAGIC[0]
+            || data[1] != LEVEL_MAGIC[1]
+            || data[2] != LEVEL_MAGIC[2]
+            || data[3] != LEVEL_MAGIC[3]
+            || read_u16(data, 4) != LEVEL_VERSION
+        {
+            return None;
+        }
+
+        let level = Self {
+            data,
+            width: read_u16(data, 6) as usize,
+            height: read_u16(data, 8) as usize,
+            spawn_count: read_u16(data, 12) as usize,
+            waypoint_count: read_u16(data, 14) as usize,
+        };
+
+        if data.len() != level.waypoints_offset() + level.waypoint_count * WAYPOINT_SIZE {
+            return None;
+        }
+
+        Some(level)
+    }
+
+    /// Width in tiles.
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Height in tiles.
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Camera speed in 1/`SCROLL_SUBPIXELS` pixels per tick.
+    pub const fn scroll_speed(&self) -> u16 {
+        read_u16(self.data, 10)
+    }
+
+    /// Tileset index of the tile at (`x`, `y`), in tiles.
+    pub const fn tile(&self, x: usize, y: usize) -> u8 {
+        assert!(x < self.width && y < self.height, "tile outside the level");
+        self.data[HEADER_SIZE + y * self.width + x]
+    }
+
+    pub const fn spawn_count(&self) -> usize {
+        self.spawn_count
+    }
+
+    /// Spawn markers are stored in the order they were placed.
+    pub const fn spawn(&self, index: usize) -> Spawn {
+        assert!(index < self.spawn_count, "spawn index out of range");
+        let at = self.spawns_offset() + index * SPAWN_SIZE;
+
+        Spawn {
+            x: read_u16(self.data, at),
+            y: read_u16(self.data, at + 2),
+            kind: self.data[at + 4],
+        }
+    }
+
+    pub const fn waypoint_count(&self) -> usize {
+        self.waypoint_count
+    }
+
+    /// Waypoints in path order.
+    pub const fn waypoint(&self, index: usize) -> Waypoint {
+        assert!(index < self.waypoint_count, "waypoint index out of range");
+        let at = self.waypoints_offset() + index * WAYPOINT_SIZE;
+
+        Waypoint {
+            x: read_u16(self.data, at),
+            y: read_u16(self.data, at + 2),
+        }
+    }
+
+    const fn spawns_offset(&self) -> usize {
+        HEADER_SIZE + self.width * self.height
+    }
+
+    const fn waypoints_offset(&self) -> usize {
+        self.spawns_offset() + self.spawn_count * SPAWN_SIZE
+    }
+}
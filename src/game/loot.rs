@@ -0,0 +1,62 @@
+//! Drop tables: what an enemy leaves behind when it is destroyed.
+//!
+//! An enemy's `drops` in `assets/entities.toml` lists pickups with
+//! weights, plus `none` for the chance of nothing, and `build.rs` turns it
+//! into the `DropTable` of its `EnemyDef`. The tick rolls the table of
+//! every enemy destroyed with a generator seeded from the tick count and
+//! the enemy's slot, like the daily wave's spawns, and spawns the pickup
+//! with `SimState::spawn_prefab`, so drops replay exactly.
+
+use crate::game::entities::PickupKind;
+use crate::runtime::rng::Rng;
+
+#[derive(Debug, Clone, Copy)]
+pub struct DropTable {
+    /// Each pickup and its weight.
+    entries: &'static [(PickupKind, u16)],
+    /// Weight of dropping nothing.
+    nothing: u16,
+}
+
+impl DropTable {
+    /// Drops nothing.
+    pub const NONE: Self = Self::new(&[], 0);
+
+    pub const fn new(entries: &'static [(PickupKind, u16)], nothing: u16) -> Self {
+        Self { entries, nothing }
+    }
+
+    pub const fn entries(&self) -> &'static [(PickupKind, u16)] {
+        self.entries
+    }
+
+    /// Sum of every weight, `none`'s included.
+    pub fn total_weight(&self) -> u32 {
+        self.entries
+            .iter()
+            .map(|&(_, weight)| weight as u32)
+            .sum::<u32>()
+            + self.nothing as u32
+    }
+
+    /// Pick a pickup by weight, or `None` for nothing.
+    pub fn roll(&self, rng: &mut Rng) -> Option<PickupKind> {
+        let total = self.total_weight();
+
+        if self.entries.is_empty() || total == 0 {
+            return None;
+        }
+
+        let mut pick = rng.below(total);
+
+        for &(pickup, weight) in self.entries {
+            if pick < weight as u32 {
+                return Some(pickup);
+            }
+
+            pick -= weight as u32;
+        }
+
+        None
+    }
+}
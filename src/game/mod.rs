@@ -1,107 +1,1370 @@
+pub mod assets;
+pub mod behavior;
+pub mod bullet;
+pub mod collision;
+pub mod damage;
 pub mod enemy;
+pub mod entities;
 pub mod fixed_pool;
+pub mod ghost;
+pub mod haptics;
+pub mod kinematics;
 pub mod level;
+pub mod loot;
+pub mod patterns;
+pub mod photo;
+pub mod pickup;
 pub mod player;
+pub mod practice;
+pub mod prefab;
+pub mod radar;
+pub mod response;
+pub mod shop;
+pub mod sounds;
+pub mod speedrun;
+pub mod stage;
+pub mod stats;
+pub mod status;
+pub mod title;
+pub mod tutorial;
 
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use crate::audio::Apu;
+use crate::audio::mixer::Mixer;
+use crate::audio::music::MusicPlayer;
+use crate::audio::sfx::SfxPlayer;
+use crate::game::assets::PLAYER_FLY;
+use crate::game::bullet::{BULLET_BLOCK, Bullet};
+use crate::game::collision::{CollisionState, SCREEN};
+#[cfg(feature = "std")]
+use crate::game::damage::Health;
+use crate::game::damage::{
+    DamageKind, DamageLog, DamageSource, DamageTarget, Dealt, Hit, Resistances,
+};
 use crate::game::enemy::Enemy;
-use crate::game::fixed_pool::FixedPool;
-use crate::game::player::Player;
-use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
-use crate::renderer::sprite::Sprite;
-use crate::runtime::memory::RuntimeMemory;
-
-const PLAYER_FRAME_1: Sprite = Sprite {
-    width: 35,
-    height: 16,
-    tiles_x: 5,
-    tiles_y: 2,
-    data: include_bytes!("../../assets/processed/spaceship_0.2bpp"),
+use crate::game::entities::{
+    Behavior, ENEMY_KINDS, EnemyKind, PICKUP_KINDS, PickupKind, ProjectileKind,
 };
+use crate::game::fixed_pool::FixedPool;
+use crate::game::level::Level;
+use crate::game::patterns::{BulletSpawn, SpawnList};
+use crate::game::pickup::{PICKUP_BLOCK, PICKUP_DRIFT, Pickup};
+use crate::game::player::{PLAYER_HEIGHT, PLAYER_WIDTH, Player, SHIP_HP};
+use crate::game::prefab::PrefabId;
+use crate::game::radar::Radar;
+use crate::game::shop::{Upgrade, Upgrades};
+use crate::game::stage::Stage;
+use crate::game::status::{StatusEffect, StatusEffects, StatusKind};
+use crate::input::Input;
+use crate::math::geom::{Rect, Vec2fx, Vec2i};
+use crate::renderer::effects::ScreenEffects;
+use crate::renderer::framebuffer::{ColorRemap, FONT_HEIGHT, FrameBuffer, HEIGHT, NO_REMAP, WIDTH};
+use crate::renderer::transition::{Direction, Transition, TransitionKind};
+use crate::runtime::hash::Fnv1a;
+use crate::runtime::memory::ActiveMemory;
+use crate::runtime::pacing::TickRate;
+use crate::runtime::rng::Rng;
+
+/// Per-tick simulation counters, reset at the start of every `update()`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrameStats {
+    pub collision_pairs: u32,
+}
+
+/// First bytes of a `GameState` snapshot.
+const STATE_MAGIC: [u8; 4] = *b"BBGS";
+
+const MAX_ENEMIES: usize = 32;
+
+const MAX_PICKUPS: usize = 16;
+
+const MAX_BULLETS: usize = 64;
+
+/// Largest possible `GameState::serialize` output, in bytes.
+pub const STATE_SNAPSHOT_MAX: usize = STATE_MAGIC.len()
+    + 7 * 4
+    + MAX_ENEMIES * ENEMY_WORDS * 4
+    + 4
+    + MAX_PICKUPS * PICKUP_WORDS * 4
+    + 4
+    + MAX_BULLETS * BULLET_WORDS * 4
+    + EXTRA_WORDS * 4;
+
+/// Ticks of the iris `GameState::open_on_player` starts.
+const OPENING_TICKS: u16 = 30;
+
+/// Words `GameState::serialize` writes per enemy.
+const ENEMY_WORDS: usize = 13;
+
+/// The `max_speed_word` of a body without a top speed.
+const NO_MAX_SPEED: u32 = u32::MAX;
+
+/// Words `GameState::serialize` writes per pickup.
+const PICKUP_WORDS: usize = 3;
+
+/// Words `GameState::serialize` writes per bullet.
+const BULLET_WORDS: usize = 5;
+
+/// Words `GameState::serialize` writes last, for what only some games
+/// have: which of it there is, the partner's ship, the wave seed, the
+/// upgrades and the stage's cursor.
+const EXTRA_WORDS: usize = 9;
+
+/// Bits of the first extra word.
+#[cfg(feature = "std")]
+const HAS_PARTNER: u32 = 1 << 0;
+#[cfg(feature = "std")]
+const HAS_WAVE_SEED: u32 = 1 << 1;
+#[cfg(feature = "std")]
+const HAS_STAGE: u32 = 1 << 2;
+
+/// Mixed into the seed of each drop roll, so drops do not follow the
+/// daily wave's spawns.
+const LOOT_SALT: u64 = 0x4c4f_4f54;
+
+/// Mixed into the seed of each pattern an enemy fires.
+const FIRE_SALT: u64 = 0x4649_5245;
+
+/// What enemy scripts' patterns fire.
+const ENEMY_BULLET: ProjectileKind = ProjectileKind::PELLET;
+
+/// Damage an enemy and a ship that run into each other deal each other.
+const CONTACT_DAMAGE: u8 = 1;
 
-const PLAYER_FRAME_2: Sprite = Sprite {
-    width: 35,
-    height: 16,
-    tiles_x: 5,
-    tiles_y: 2,
-    data: include_bytes!("../../assets/processed/spaceship_1.2bpp"),
+/// What a ship ramming an enemy leaves it with: knocked about, it moves at
+/// half speed for half a second.
+const RAM_STATUS: StatusEffect = StatusEffect {
+    kind: StatusKind::Slow,
+    ticks: 30,
 };
 
-pub struct GameState {
+/// Ticks a ship ignores hits after its last contact: one, so it is hit
+/// once for running into enemies rather than every tick it overlaps them.
+const SHIP_GRACE_TICKS: u8 = 1;
+
+/// Ticks an enemy ignores hits after taking one.
+const ENEMY_IFRAMES: u8 = 4;
+
+/// Enemies on screen at once that count as danger level 1: more than the
+/// test wave keeps up.
+const DANGER_CROWD: usize = 8;
+
+/// The deterministic half of a `GameState`: everything `simulate` reads
+/// and writes. It is `Copy` and holds no references, so saving it for a
+/// rollback is a plain copy of a few kilobytes.
+#[derive(Clone, Copy)]
+pub struct SimState {
     pub player: Player,
-    pub enemies: FixedPool<Enemy, 32>,
+    /// The second ship in co-op, steered by the second input.
+    pub partner: Option<Player>,
+    pub enemies: FixedPool<Enemy, MAX_ENEMIES>,
+    /// What destroyed enemies dropped, until a ship collects it or it
+    /// drifts off screen.
+    pub pickups: FixedPool<Pickup, MAX_PICKUPS>,
+    /// What enemies fired, until it hits a ship or leaves the screen.
+    pub bullets: FixedPool<Bullet, MAX_BULLETS>,
     pub spawn_timer: u32,
     pub frame_counter: u32,
+    /// Rebuilt by every tick; kept for the debug overlay.
+    pub collisions: CollisionState,
+    /// The level being played; without one, an enemy spawns every 30 ticks.
+    /// Snapshots hold only how far it has got, so one is restored with
+    /// `GameState::deserialize_into` onto a state built for the same level.
+    pub stage: Option<Stage>,
+    /// Seed of the daily challenge's wave. Without a level, each spawn then
+    /// picks its kind and height from it instead of sending a drone down
+    /// the middle.
+    pub wave_seed: Option<u64>,
+    /// Shop upgrades the ships have (`game::shop`). Hashed once any is
+    /// bought.
+    pub upgrades: Upgrades,
 }
 
-impl GameState {
-    pub fn new() -> Self {
+impl Default for SimState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimState {
+    pub const fn new() -> Self {
         Self {
-            player: Player {
-                x: 2,
-                y: (HEIGHT / 2) - 8,
-                anim_timer: 0,
-            },
-            enemies: FixedPool::new(Enemy { x: 0, y: 0, vx: 0 }),
+            player: Player::new(Vec2i::new(2, HEIGHT as i32 / 2 - 8)),
+            partner: None,
+            enemies: FixedPool::new(Enemy::new(EnemyKind::ALL[0], Vec2i::ZERO)),
+            pickups: FixedPool::new(Pickup::new(PickupKind::ALL[0], Vec2i::ZERO)),
+            bullets: FixedPool::new(Bullet::new(
+                ENEMY_BULLET,
+                EnemyKind::ALL[0],
+                BulletSpawn {
+                    pos: Vec2fx::ZERO,
+                    vel: Vec2fx::ZERO,
+                },
+            )),
             spawn_timer: 0,
             frame_counter: 0,
+            collisions: CollisionState::new(),
+            stage: None,
+            wave_seed: None,
+            upgrades: Upgrades::NONE,
         }
     }
+
+    /// Put the co-op partner's ship below the player's.
+    pub fn add_partner(&mut self) {
+        let y = (self.player.pos.y + PLAYER_HEIGHT + 8).min(HEIGHT as i32 - PLAYER_HEIGHT);
+
+        self.partner = Some(Player {
+            anim_timer: self.player.anim_timer,
+            ..Player::new(Vec2i::new(self.player.pos.x, y))
+        });
+    }
+
+    /// Spawn `prefab` with its top-left at `pos`, handing it back if its
+    /// pool is full.
+    pub fn spawn_prefab(&mut self, prefab: PrefabId, pos: Vec2i) -> Result<(), PrefabId> {
+        match prefab {
+            PrefabId::Enemy(kind) => self
+                .enemies
+                .spawn(Enemy::new(kind, pos))
+                .map_err(|_| prefab),
+            PrefabId::Pickup(kind) => self
+                .pickups
+                .spawn(Pickup::new(kind, pos))
+                .map_err(|_| prefab),
+        }
+    }
+
+    /// Land `hit` on the enemy in pool slot `index`, logging what it
+    /// dealt. Ships ramming it come through here, and so should any other
+    /// damage, so all of it shares the invulnerability frames, armour and
+    /// crits; the crit roll comes from the tick count and slot.
+    pub fn damage_enemy(&mut self, index: usize, hit: &Hit, log: &mut DamageLog) -> Option<Dealt> {
+        let roll = Rng::new(((self.frame_counter as u64) << 32) | index as u64).below(256) as u8;
+        let enemy = self.enemies.as_mut_slice().get_mut(index)?;
+        let resist = enemy.resistances();
+        let dealt = enemy.health.take(
+            DamageTarget::Enemy(index as u8),
+            hit,
+            &resist,
+            ENEMY_IFRAMES,
+            roll,
+        )?;
+
+        log.push(dealt);
+        Some(dealt)
+    }
+
+    /// Whether the level is cleared: every marker has spawned and no enemy
+    /// is left. Always false on the test wave.
+    pub fn stage_cleared(&self) -> bool {
+        self.stage.as_ref().is_some_and(Stage::is_finished) && self.enemies.is_empty()
+    }
+
+    /// How tense the game is, for the music's danger layers: 0 calm, 1 a
+    /// crowd of enemies, 2 a boss, 3 a boss down to half its hit points.
+    pub fn danger(&self) -> u8 {
+        let boss = self
+            .enemies
+            .as_slice()
+            .iter()
+            .filter(|enemy| enemy.kind.def().boss)
+            .map(|enemy| (enemy.health.hp as u16 * 2 <= enemy.kind.def().hp as u16) as u8 + 2)
+            .max();
+
+        boss.unwrap_or((self.enemies.len() >= DANGER_CROWD) as u8)
+    }
+
+    /// FNV-1a hash of the state. Two runs with the same input must produce
+    /// the same hash on every frame.
+    pub fn state_hash(&self) -> u64 {
+        let mut hash = Fnv1a::new();
+
+        hash.write_u32(self.frame_counter);
+        hash.write_u32(self.spawn_timer);
+        hash.write_u32(self.player.pos.x as u32);
+        hash.write_u32(self.player.pos.y as u32);
+        hash.write_u32(self.player.anim_timer);
+        hash.write_u32(self.player.health.invulnerable as u32);
+        hash.write_u32(self.enemies.len() as u32);
+
+        for enemy in self.enemies.as_slice() {
+            hash.write_u32(enemy.body.pos.x as u32);
+            hash.write_u32(enemy.body.pos.y as u32);
+            hash.write_u32(enemy.body.vel.x as u32);
+            hash.write_u32(enemy.body.vel.y as u32);
+            hash.write_u32(enemy.body.accel.x as u32);
+            hash.write_u32(enemy.body.accel.y as u32);
+            hash.write_u32(enemy.body.drag as u32);
+            hash.write_u32(max_speed_word(enemy.body.max_speed));
+            hash.write_u32(enemy.kind.index() as u32);
+            hash.write_u32(enemy.health.hp as u32);
+            hash.write_u32(enemy.health.invulnerable as u32);
+
+            for word in enemy.health.status.to_words() {
+                hash.write_u32(word);
+            }
+
+            for word in enemy.vm.to_words() {
+                hash.write_u32(word);
+            }
+        }
+
+        hash.write_u32(self.pickups.len() as u32);
+
+        for pickup in self.pickups.as_slice() {
+            hash.write_u32(pickup.pos.x as u32);
+            hash.write_u32(pickup.pos.y as u32);
+            hash.write_u32(pickup.kind.index() as u32);
+        }
+
+        hash.write_u32(self.bullets.len() as u32);
+
+        for bullet in self.bullets.as_slice() {
+            for word in bullet_words(bullet) {
+                hash.write_u32(word);
+            }
+        }
+
+        if let Some(stage) = &self.stage {
+            hash.write_u32(stage.camera().x as u32);
+            hash.write_u32(stage.spawned() as u32);
+        }
+
+        if let Some(partner) = &self.partner {
+            hash.write_u32(partner.pos.x as u32);
+            hash.write_u32(partner.pos.y as u32);
+            hash.write_u32(partner.health.invulnerable as u32);
+        }
+
+        if let Some(seed) = self.wave_seed {
+            hash.write_u32(seed as u32);
+            hash.write_u32((seed >> 32) as u32);
+        }
+
+        if !self.upgrades.is_none() {
+            for upgrade in Upgrade::ALL {
+                hash.write_u32(self.upgrades.level(upgrade) as u32);
+            }
+        }
+
+        hash.finish()
+    }
 }
 
-pub fn update(state: &mut GameState, _memory: &mut RuntimeMemory) {
-    // Update player animation
-    state.player.anim_timer += 1;
-    state.frame_counter += 1;
+/// A bullet as the words hashes and snapshots hold: its position and
+/// velocity, then its kind and the kind of enemy that fired it.
+fn bullet_words(bullet: &Bullet) -> [u32; BULLET_WORDS] {
+    [
+        bullet.pos.x as u32,
+        bullet.pos.y as u32,
+        bullet.vel.x as u32,
+        bullet.vel.y as u32,
+        bullet.kind.index() as u32 | (bullet.source.index() as u32) << 8,
+    ]
+}
 
-    if state.player.anim_timer >= 60 {
-        state.player.anim_timer = 0;
+/// A body's top speed as the word hashes and snapshots hold.
+const fn max_speed_word(max_speed: Option<i32>) -> u32 {
+    match max_speed {
+        Some(max) => max as u32,
+        None => NO_MAX_SPEED,
     }
+}
 
-    // Spawn enemy every 30 frames
-    state.spawn_timer += 1;
+/// What a tick did that the presentation reacts to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TickEvents {
+    /// At least one enemy spawned.
+    pub spawned: bool,
+    /// A ship was hit.
+    pub hit: bool,
+    /// Every hit and burn that landed.
+    pub damage: DamageLog,
+    /// Enemies destroyed, by `EnemyKind::index`.
+    pub destroyed: [u8; ENEMY_KINDS],
+    /// Pickups collected, by `PickupKind::index`.
+    pub collected: [u8; PICKUP_KINDS],
+    pub stats: FrameStats,
+}
 
-    if state.spawn_timer >= 30 {
-        state.spawn_timer = 0;
+/// A running game: the simulation and the presentation driven by it.
+pub struct GameState {
+    /// Everything `state_hash` and snapshots cover.
+    pub sim: SimState,
+    /// Counters from the last tick.
+    pub stats: FrameStats,
+    /// Sound registers. Output, like `stats`: not hashed or serialized.
+    pub apu: Apu,
+    /// Sound effects playing on `apu`.
+    pub sfx: SfxPlayer,
+    /// The song playing on `apu`, on the channels `sfx` leaves free.
+    pub music: MusicPlayer,
+    /// Music fades and ducking.
+    pub mixer: Mixer,
+    /// Hit flash, shake and transitions, drawn by `render`.
+    pub effects: ScreenEffects,
+    /// The HUD radar, placed from `sim` after every tick.
+    pub radar: Radar,
+    /// The rate the game ticks at, for what keeps its speed by the clock.
+    pub tick_rate: TickRate,
+}
 
-        let _ = state.enemies.spawn(Enemy {
-            x: (WIDTH - 1) as i32,
-            y: (HEIGHT / 2) as i32,
-            vx: -1,
-        });
+impl Default for GameState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GameState {
+    pub fn new() -> Self {
+        let mut apu = Apu::new();
+        apu.set_wave(&sounds::TRIANGLE_WAVE);
+
+        let mut music = MusicPlayer::new();
+        music.play(sounds::STAGE1_SONG);
+
+        Self {
+            sim: SimState::new(),
+            stats: FrameStats::default(),
+            apu,
+            sfx: SfxPlayer::new(&sounds::EFFECTS),
+            music,
+            mixer: Mixer::new(sounds::DUCK_PRIORITY, sounds::DUCK_LEVEL),
+            effects: ScreenEffects::new(),
+            radar: Radar::new(),
+            tick_rate: TickRate::Hz60,
+        }
     }
 
+    /// Run at `rate`: music and animations keep their speed, the rest of
+    /// the game slows or speeds up with the ticks.
+    pub fn set_tick_rate(&mut self, rate: TickRate) {
+        self.tick_rate = rate;
+        self.apu.set_frame_rate(rate.hz() as u8);
+        self.music.set_rate(rate);
+    }
+
+    /// A new game on the test wave, its spawns drawn from `seed`.
+    pub fn with_seed(seed: u64) -> Self {
+        let mut state = Self::new();
+        state.sim.wave_seed = Some(seed);
+        state
+    }
+
+    /// A new game that spawns enemies from `level`'s markers.
+    pub fn with_level(level: &Level<'_>) -> Self {
+        let mut state = Self::new();
+        state.sim.stage = Some(Stage::new(level));
+        state
+    }
+
+    /// Open the game with an iris widening from the first ship.
+    pub fn open_on_player(&mut self) {
+        let center = self.sim.player.hitbox().center();
+        let iris = TransitionKind::Iris {
+            x: center.x,
+            y: center.y,
+        };
+
+        self.effects
+            .transition(Transition::new(iris, Direction::In, OPENING_TICKS));
+    }
+
+    /// `SimState::state_hash` of the simulation.
+    pub fn state_hash(&self) -> u64 {
+        self.sim.state_hash()
+    }
+
+    /// Write the simulation state (everything `state_hash` covers) as
+    /// little-endian words. Of the stage only its cursor is written; its
+    /// markers come from the level. Per-tick data such as `stats` and
+    /// `collisions` is rebuilt by the next `update()` and not stored.
+    #[cfg(feature = "std")]
+    pub fn serialize(&self, writer: &mut impl Write) -> io::Result<()> {
+        let sim = &self.sim;
+        let words = [
+            sim.frame_counter,
+            sim.spawn_timer,
+            sim.player.pos.x as u32,
+            sim.player.pos.y as u32,
+            sim.player.anim_timer,
+            sim.player.health.invulnerable as u32,
+            sim.enemies.len() as u32,
+        ];
+
+        writer.write_all(&STATE_MAGIC)?;
+
+        for word in words {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+
+        for enemy in sim.enemies.as_slice() {
+            let [vm_pc, vm_loops] = enemy.vm.to_words();
+            let status = enemy.health.status.to_words();
+            let words: [u32; ENEMY_WORDS] = [
+                enemy.body.pos.x as u32,
+                enemy.body.pos.y as u32,
+                enemy.body.vel.x as u32,
+                enemy.body.vel.y as u32,
+                enemy.body.accel.x as u32,
+                enemy.body.accel.y as u32,
+                enemy.body.drag as u32,
+                max_speed_word(enemy.body.max_speed),
+                enemy.kind.index() as u32
+                    | (enemy.health.hp as u32) << 8
+                    | (enemy.health.invulnerable as u32) << 16,
+                status[0],
+                status[1],
+                vm_pc,
+                vm_loops,
+            ];
+
+            for word in words {
+                writer.write_all(&word.to_le_bytes())?;
+            }
+        }
+
+        writer.write_all(&(sim.pickups.len() as u32).to_le_bytes())?;
+
+        for pickup in sim.pickups.as_slice() {
+            let words: [u32; PICKUP_WORDS] = [
+                pickup.pos.x as u32,
+                pickup.pos.y as u32,
+                pickup.kind.index() as u32,
+            ];
+
+            for word in words {
+                writer.write_all(&word.to_le_bytes())?;
+            }
+        }
+
+        writer.write_all(&(sim.bullets.len() as u32).to_le_bytes())?;
+
+        for bullet in sim.bullets.as_slice() {
+            for word in bullet_words(bullet) {
+                writer.write_all(&word.to_le_bytes())?;
+            }
+        }
+
+        let partner = sim.partner.unwrap_or(sim.player);
+        let seed = sim.wave_seed.unwrap_or(0);
+        let [next, scroll] = sim.stage.as_ref().map_or([0; 2], Stage::cursor_words);
+        let mut flags = 0;
+
+        for (present, bit) in [
+            (sim.partner.is_some(), HAS_PARTNER),
+            (sim.wave_seed.is_some(), HAS_WAVE_SEED),
+            (sim.stage.is_some(), HAS_STAGE),
+        ] {
+            if present {
+                flags |= bit;
+            }
+        }
+
+        let words: [u32; EXTRA_WORDS] = [
+            flags,
+            partner.pos.x as u32,
+            partner.pos.y as u32,
+            partner.health.invulnerable as u32,
+            seed as u32,
+            (seed >> 32) as u32,
+            sim.upgrades.to_word(),
+            next,
+            scroll,
+        ];
+
+        for word in words {
+            writer.write_all(&word.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+
+    /// A new game in a state written by `serialize`. A level's snapshot
+    /// is refused, as the new game has no level to put it back on; use
+    /// `deserialize_into` on a game built with `with_level`.
+    #[cfg(feature = "std")]
+    pub fn deserialize(reader: &mut impl Read) -> io::Result<Self> {
+        let mut state = Self::new();
+        state.deserialize_into(reader)?;
+        Ok(state)
+    }
+
+    /// Put this game's simulation back to a state written by `serialize`.
+    /// A level's snapshot needs this game to have the same level, and a
+    /// snapshot without one a game without a level. Sound and effects are
+    /// left as they are; on an error, so is the simulation.
+    #[cfg(feature = "std")]
+    pub fn deserialize_into(&mut self, reader: &mut impl Read) -> io::Result<()> {
+        let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic)?;
+
+        if magic != STATE_MAGIC {
+            return Err(invalid("not a game state snapshot"));
+        }
+
+        let mut word = || -> io::Result<u32> {
+            let mut bytes = [0u8; 4];
+            reader.read_exact(&mut bytes)?;
+            Ok(u32::from_le_bytes(bytes))
+        };
+
+        let mut sim = SimState {
+            stage: self.sim.stage,
+            ..SimState::new()
+        };
+        sim.frame_counter = word()?;
+        sim.spawn_timer = word()?;
+        sim.player.pos = Vec2i::new(word()? as i32, word()? as i32);
+        sim.player.anim_timer = word()?;
+        sim.player.health.invulnerable = word()? as u8;
+
+        let enemies = word()? as usize;
+
+        if enemies > MAX_ENEMIES {
+            return Err(invalid("too many enemies in snapshot"));
+        }
+
+        for _ in 0..enemies {
+            let pos = Vec2fx::new(word()? as i32, word()? as i32);
+            let vel = Vec2fx::new(word()? as i32, word()? as i32);
+            let accel = Vec2fx::new(word()? as i32, word()? as i32);
+            let drag = word()? as i32;
+            let max_speed = match word()? {
+                NO_MAX_SPEED => None,
+                max => Some(max as i32),
+            };
+            let kind_hp = word()?;
+            let status = StatusEffects::from_words([word()?, word()?]);
+            let vm_words = [word()?, word()?];
+            let kind = u8::try_from(kind_hp & 0xff)
+                .ok()
+                .and_then(EnemyKind::from_index)
+                .ok_or_else(|| invalid("unknown enemy kind in snapshot"))?;
+            let vm = match kind.def().script {
+                Some(script) => behavior::BehaviorVm::from_words(vm_words, &script)
+                    .ok_or_else(|| invalid("enemy script state out of range in snapshot"))?,
+                None => behavior::BehaviorVm::new(),
+            };
+
+            let mut enemy = Enemy {
+                health: Health {
+                    hp: (kind_hp >> 8) as u8,
+                    invulnerable: (kind_hp >> 16) as u8,
+                    status,
+                },
+                vm,
+                ..Enemy::new(kind, Vec2i::ZERO)
+            };
+            enemy.body.pos = pos;
+            enemy.body.vel = vel;
+            enemy.body.accel = accel;
+            enemy.body.drag = drag;
+            enemy.body.max_speed = max_speed;
+
+            let _ = sim.enemies.spawn(enemy);
+        }
+
+        let pickups = word()? as usize;
+
+        if pickups > MAX_PICKUPS {
+            return Err(invalid("too many pickups in snapshot"));
+        }
+
+        for _ in 0..pickups {
+            let pos = Vec2i::new(word()? as i32, word()? as i32);
+            let kind = u8::try_from(word()?)
+                .ok()
+                .and_then(PickupKind::from_index)
+                .ok_or_else(|| invalid("unknown pickup kind in snapshot"))?;
+
+            let _ = sim.pickups.spawn(Pickup::new(kind, pos));
+        }
+
+        let bullets = word()? as usize;
+
+        if bullets > MAX_BULLETS {
+            return Err(invalid("too many bullets in snapshot"));
+        }
+
+        for _ in 0..bullets {
+            let pos = Vec2fx::new(word()? as i32, word()? as i32);
+            let vel = Vec2fx::new(word()? as i32, word()? as i32);
+            let kinds = word()?;
+            let kind = ProjectileKind::from_index(kinds as u8)
+                .ok_or_else(|| invalid("unknown projectile kind in snapshot"))?;
+            let source = EnemyKind::from_index((kinds >> 8) as u8)
+                .ok_or_else(|| invalid("unknown enemy kind in snapshot"))?;
+
+            let _ = sim.bullets.spawn(Bullet {
+                kind,
+                source,
+                pos,
+                vel,
+            });
+        }
+
+        let flags = word()?;
+        let mut partner = Player {
+            anim_timer: sim.player.anim_timer,
+            ..Player::new(Vec2i::new(word()? as i32, word()? as i32))
+        };
+        partner.health.invulnerable = word()? as u8;
+        let seed = word()? as u64 | (word()? as u64) << 32;
+
+        sim.partner = (flags & HAS_PARTNER != 0).then_some(partner);
+        sim.wave_seed = (flags & HAS_WAVE_SEED != 0).then_some(seed);
+        sim.upgrades = Upgrades::from_word(word()?);
+
+        let cursor = [word()?, word()?];
+
+        match (&mut sim.stage, flags & HAS_STAGE != 0) {
+            (Some(stage), true) => {
+                if !stage.set_cursor(cursor) {
+                    return Err(invalid("snapshot is of another level"));
+                }
+            }
+            (None, false) => {}
+            (None, true) => return Err(invalid("snapshot is of a level this game does not have")),
+            (Some(_), false) => return Err(invalid("snapshot is of a game without a level")),
+        }
+
+        self.sim = sim;
+        Ok(())
+    }
+}
+
+/// Run one tick of the game: `simulate`, then the sound it calls for.
+pub fn update(state: &mut GameState, input: Input, memory: &mut ActiveMemory) -> TickEvents {
+    update_coop(state, [input, Input::empty()], memory)
+}
+
+/// `update` with the partner's input as well. Returns what the tick did,
+/// for listeners outside the game such as `stats::Stats`.
+pub fn update_coop(
+    state: &mut GameState,
+    inputs: [Input; 2],
+    _memory: &mut ActiveMemory,
+) -> TickEvents {
+    crate::profile_scope!("update");
+
+    let events = simulate_coop(&mut state.sim, inputs);
+    state.stats = events.stats;
+
+    if events.spawned {
+        state.sfx.play(sounds::SPAWN);
+    }
+
+    state.effects.tick();
+    state.radar.update(&state.sim);
+
+    if events.hit {
+        state.sfx.play(sounds::HIT);
+        state.effects.flash();
+        state.effects.shake();
+    }
+
+    state.mixer.set_danger(state.sim.danger());
+    state.sfx.update(&mut state.apu);
+    state.music.update(&mut state.apu, state.sfx.busy());
+    state
+        .mixer
+        .update(&mut state.apu, &state.sfx, &mut state.music);
+
+    events
+}
+
+/// Advance `sim` one tick. The outcome depends on `sim` and `input` alone:
+/// no clock, globals, arenas or sound are read, so a rollback can restore
+/// an older `SimState` and run it forward again with corrected input.
+/// Profiler scopes only measure.
+pub fn simulate(sim: &mut SimState, input: Input) -> TickEvents {
+    simulate_coop(sim, [input, Input::empty()])
+}
+
+/// `simulate` with the partner's input as well; it is ignored without a
+/// partner.
+pub fn simulate_coop(sim: &mut SimState, inputs: [Input; 2]) -> TickEvents {
+    let mut events = TickEvents::default();
+
+    let step = 1 + sim.upgrades.extra_step(sim.frame_counter);
+
+    move_player(&mut sim.player, inputs[0], step);
+
+    // Update player animation
+    sim.player.anim_timer += 1;
+    sim.frame_counter += 1;
+
+    if sim.player.anim_timer >= 60 {
+        sim.player.anim_timer = 0;
+    }
+
+    if let Some(partner) = &mut sim.partner {
+        move_player(partner, inputs[1], step);
+        partner.anim_timer = sim.player.anim_timer;
+    }
+
+    events.spawned = spawn_enemies(sim);
+
     // Update enemies
-    let mut i = 0;
+    events.destroyed = update_enemies(sim, &mut events.damage);
+    update_bullets(sim, &mut events.damage);
+    events.collected = update_pickups(sim);
+
+    events.stats.collision_pairs = update_collisions(sim);
+    land_contacts(sim, &mut events.damage);
+    events.hit = events
+        .damage
+        .as_slice()
+        .iter()
+        .any(|dealt| matches!(dealt.target, DamageTarget::Ship(_)));
+
+    events
+}
+
+/// Spawn at the right edge: at the level's markers as the camera reaches
+/// them, each the enemy its kind picks (`EnemyKind::for_marker`), or every
+/// 30 ticks without a level: a drone mid-screen, or with a `wave_seed` a
+/// random enemy at a random height. Returns whether
+/// anything spawned.
+fn spawn_enemies(sim: &mut SimState) -> bool {
+    crate::profile_scope!("spawn");
+
+    let mut spawned = false;
+
+    if let Some(stage) = &mut sim.stage {
+        stage.advance();
+
+        while let Some(spawn) = sim.stage.as_mut().and_then(Stage::next_due) {
+            let enemy = PrefabId::Enemy(EnemyKind::for_marker(spawn.kind));
+            let pos = Vec2i::new(WIDTH as i32 - 1, (spawn.y as i32).min(HEIGHT as i32 - 1));
+
+            spawned |= sim.spawn_prefab(enemy, pos).is_ok();
+        }
+    } else {
+        sim.spawn_timer += 1;
+
+        if sim.spawn_timer >= 30 {
+            sim.spawn_timer = 0;
+
+            let (kind, y) = match sim.wave_seed {
+                Some(seed) => {
+                    // A generator per spawn, so the wave keeps no state
+                    // beyond the tick count.
+                    let mut rng = Rng::new(seed ^ ((sim.frame_counter as u64) << 32));
+                    let kind = EnemyKind::ALL[rng.below(EnemyKind::ALL.len() as u32) as usize];
+
+                    (kind, 8 + rng.below(HEIGHT as u32 - 16) as i32)
+                }
+                None => (EnemyKind::DRONE, (HEIGHT / 2) as i32),
+            };
+            spawned = sim
+                .spawn_prefab(PrefabId::Enemy(kind), Vec2i::new(WIDTH as i32 - 1, y))
+                .is_ok();
+        }
+    }
+
+    spawned
+}
+
+/// Move `step` pixels per tick in each held direction, staying on screen.
+fn move_player(player: &mut Player, input: Input, step: i32) {
+    let pos = &mut player.pos;
+
+    if input.contains(Input::UP) {
+        pos.y = (pos.y - step).max(0);
+    }
+
+    if input.contains(Input::DOWN) {
+        pos.y = (pos.y + step).min(HEIGHT as i32 - PLAYER_HEIGHT);
+    }
+
+    if input.contains(Input::LEFT) {
+        pos.x = (pos.x - step).max(0);
+    }
+
+    if input.contains(Input::RIGHT) {
+        pos.x = (pos.x + step).min(WIDTH as i32 - PLAYER_WIDTH);
+    }
+}
+
+/// Run the enemies' status effects, move them as far as the effects let
+/// them, and remove those off screen or out of hit points, rolling the
+/// drops of the destroyed. Returns how many of each kind were destroyed.
+fn update_enemies(sim: &mut SimState, log: &mut DamageLog) -> [u8; ENEMY_KINDS] {
+    crate::profile_scope!("enemies");
+
+    let target_y = sim.player.hitbox().center().y;
+    let homing = sim.frame_counter.is_multiple_of(2);
+
+    for (i, enemy) in sim.enemies.as_mut_slice().iter_mut().enumerate() {
+        let resist = enemy.resistances();
+
+        if let Some(dealt) = enemy.health.tick(DamageTarget::Enemy(i as u8), &resist) {
+            log.push(dealt);
+        }
+    }
+
+    // Enemies do not interact, so moving them can be spread over threads;
+    // despawning reorders the pool and stays serial.
+    sim.enemies.update_each(|enemy| {
+        let time_scale = enemy.health.status.time_scale();
+
+        if time_scale == 0 {
+            enemy.firing = None;
+            return;
+        }
+
+        enemy.firing = match &enemy.kind.def().script {
+            Some(script) => enemy.vm.step(script, enemy.health.hp, &mut enemy.body),
+            None => None,
+        };
+        enemy.body.integrate_scaled(time_scale);
+
+        if homing && enemy.kind.def().behavior == Behavior::Home {
+            let dy = (target_y - enemy.pos().y).signum();
+            enemy.body.nudge(Vec2i::new(0, dy));
+        }
+    });
+
+    let mut destroyed = [0u8; ENEMY_KINDS];
+    let mut wrecks = [(EnemyKind::ALL[0], Vec2i::ZERO); MAX_ENEMIES];
+    let mut wreck_count = 0;
+
+    sim.enemies.despawn_where(|enemy| {
+        if enemy.health.hp == 0 {
+            let count = &mut destroyed[enemy.kind.index() as usize];
+            *count = count.saturating_add(1);
+            wrecks[wreck_count] = (enemy.kind, enemy.hitbox().center());
+            wreck_count += 1;
+            return true;
+        }
+
+        !SCREEN.contains(enemy.pos())
+    });
+
+    for (i, &(kind, center)) in wrecks[..wreck_count].iter().enumerate() {
+        let mut rng = Rng::new(LOOT_SALT ^ ((sim.frame_counter as u64) << 32) ^ i as u64);
+
+        if let Some(pickup) = kind.def().drops.roll(&mut rng) {
+            let pos = center - Vec2i::new(PICKUP_BLOCK / 2, PICKUP_BLOCK / 2);
+
+            // A full pool loses the drop rather than an older one.
+            let _ = sim.spawn_prefab(PrefabId::Pickup(pickup), pos);
+        }
+    }
+
+    fire_patterns(sim);
+
+    destroyed
+}
+
+/// Fire the patterns the enemies' scripts chose this tick at the first
+/// ship, each with its own roll from the tick count and slot.
+fn fire_patterns(sim: &mut SimState) {
+    let target = sim.player.hitbox().center();
+    let mut spawns = SpawnList::new(BulletSpawn::default());
+
+    for (i, enemy) in sim.enemies.as_slice().iter().enumerate() {
+        let Some(pattern) = enemy.firing.and_then(patterns::pattern) else {
+            continue;
+        };
+        let mut rng = Rng::new(FIRE_SALT ^ ((sim.frame_counter as u64) << 32) ^ i as u64);
+
+        spawns.clear();
+        pattern.spawn(
+            enemy.hitbox().center(),
+            target,
+            sim.frame_counter,
+            &mut rng,
+            &mut spawns,
+        );
+
+        for &spawn in spawns.as_slice() {
+            // A full pool loses the new bullets rather than older ones.
+            let _ = sim
+                .bullets
+                .spawn(Bullet::new(ENEMY_BULLET, enemy.kind, spawn));
+        }
+    }
+}
+
+/// Move the bullets, and remove those off screen or that hit a ship.
+fn update_bullets(sim: &mut SimState, log: &mut DamageLog) {
+    for bullet in sim.bullets.as_mut_slice() {
+        bullet.pos += bullet.vel;
+    }
+
+    let mut ships = [Some(&mut sim.player), sim.partner.as_mut()];
+
+    sim.bullets.despawn_where(|bullet| {
+        let hitbox = bullet.hitbox();
+
+        for (index, ship) in ships.iter_mut().enumerate() {
+            if let Some(ship) = ship
+                && ship.hitbox().overlaps(&hitbox)
+            {
+                let hit = Hit::new(
+                    DamageSource::Enemy(bullet.source),
+                    DamageKind::Shot,
+                    bullet.kind.def().damage,
+                );
+
+                hit_ship(ship, index, &hit, log);
+                return true;
+            }
+        }
+
+        !hitbox.overlaps(&SCREEN)
+    });
+}
+
+/// Drift the pickups left, and remove those off screen or under a ship.
+/// Returns how many of each kind were collected.
+fn update_pickups(sim: &mut SimState) -> [u8; PICKUP_KINDS] {
+    let ships = [Some(sim.player.hitbox()), sim.partner.map(|p| p.hitbox())];
+    let mut collected = [0u8; PICKUP_KINDS];
+
+    for pickup in sim.pickups.as_mut_slice() {
+        pickup.pos.x -= PICKUP_DRIFT;
+    }
+
+    sim.pickups.despawn_where(|pickup| {
+        let hitbox = pickup.hitbox();
+
+        if ships.iter().flatten().any(|ship| ship.overlaps(&hitbox)) {
+            let count = &mut collected[pickup.kind.index() as usize];
+            *count = count.saturating_add(1);
+            return true;
+        }
+
+        !hitbox.overlaps(&SCREEN)
+    });
+
+    collected
+}
 
-    while i < state.enemies.len() {
-        let enemy = &mut state.enemies.as_mut_slice()[i];
-        enemy.x += enemy.vx;
+/// Rebuild the broadphase grid and test the ships against nearby enemies.
+/// Returns the number of pairs tested.
+fn update_collisions(sim: &mut SimState) -> u32 {
+    crate::profile_scope!("collisions");
 
-        if enemy.x < 0 {
-            state.enemies.despawn(i);
-            // do NOT increment i
-            // swapped element now sits at i
-        } else {
-            i += 1;
+    let collisions = &mut sim.collisions;
+    let enemies = sim.enemies.as_slice();
+
+    collisions.grid.clear();
+
+    for (i, enemy) in enemies.iter().enumerate() {
+        collisions.grid.insert(i, &enemy.hitbox());
+    }
+
+    let mut pairs = 0;
+
+    collisions.player_hits = ship_hits(collisions, enemies, &sim.player, &mut pairs);
+    collisions.partner_hits = match &sim.partner {
+        Some(partner) => ship_hits(collisions, enemies, partner, &mut pairs),
+        None => 0,
+    };
+
+    pairs
+}
+
+/// Turn the tick's contacts into hits, both ways: each ship touching an
+/// enemy is hit by the first of them, unless it is still in its grace
+/// ticks, and rams every one of them. Enemies already down are despawned
+/// by the next tick and touch no one.
+fn land_contacts(sim: &mut SimState, log: &mut DamageLog) {
+    let live = sim
+        .enemies
+        .as_slice()
+        .iter()
+        .enumerate()
+        .filter(|(_, enemy)| enemy.health.hp > 0)
+        .fold(0u64, |live, (i, _)| live | 1 << i);
+    let contacts = [sim.collisions.player_hits, sim.collisions.partner_hits];
+
+    for (index, hits) in contacts.into_iter().enumerate() {
+        let hits = hits & live;
+        let enemies = sim.enemies.as_slice();
+        let ship = match index {
+            0 => &mut sim.player,
+            _ => match &mut sim.partner {
+                Some(partner) => partner,
+                None => continue,
+            },
+        };
+
+        if hits == 0 {
+            ship.health.invulnerable = ship.health.invulnerable.saturating_sub(1);
+            continue;
+        }
+
+        let enemy = &enemies[hits.trailing_zeros() as usize];
+        let hit = Hit::new(
+            DamageSource::Enemy(enemy.kind),
+            DamageKind::Contact,
+            CONTACT_DAMAGE,
+        );
+        hit_ship(ship, index, &hit, log);
+
+        // Held in grace for as long as the contact lasts.
+        ship.health.invulnerable = SHIP_GRACE_TICKS;
+
+        let ram = Hit {
+            status: Some(RAM_STATUS),
+            ..Hit::new(
+                DamageSource::Ship(index as u8),
+                DamageKind::Contact,
+                CONTACT_DAMAGE,
+            )
+        };
+        let mut rammed = hits;
+
+        while rammed != 0 {
+            let i = rammed.trailing_zeros() as usize;
+            rammed &= rammed - 1;
+
+            sim.damage_enemy(i, &ram, log);
+        }
+    }
+}
+
+/// Land `hit` on the ship in seat `index`, unless it is in its grace
+/// ticks, logging what it dealt, and top its hit points back up.
+fn hit_ship(ship: &mut Player, index: usize, hit: &Hit, log: &mut DamageLog) {
+    let target = DamageTarget::Ship(index as u8);
+
+    if let Some(dealt) = ship
+        .health
+        .take(target, hit, &Resistances::NONE, SHIP_GRACE_TICKS, 0)
+    {
+        log.push(dealt);
+    }
+
+    ship.health.hp = SHIP_HP;
+}
+
+/// Bitmask of the enemies overlapping `ship`, counting the pairs tested.
+fn ship_hits(
+    collisions: &CollisionState,
+    enemies: &[Enemy],
+    ship: &Player,
+    pairs: &mut u32,
+) -> u64 {
+    let ship_box = ship.hitbox();
+    let mut candidates = collisions.grid.query(&ship_box);
+    let mut hits = 0;
+
+    while candidates != 0 {
+        let i = candidates.trailing_zeros() as usize;
+        candidates &= candidates - 1;
+
+        *pairs += 1;
+
+        if enemies[i].hitbox().overlaps(&ship_box) {
+            hits |= 1 << i;
         }
     }
+
+    hits
 }
 
 pub fn render(state: &GameState, framebuffer: &mut FrameBuffer) {
-    framebuffer.clear(0);
+    render_interpolated(state, &state.sim, ALPHA_ONE, framebuffer);
+}
 
-    let frame = if state.player.anim_timer % 20 < 10 {
-        &PLAYER_FRAME_1
-    } else {
-        &PLAYER_FRAME_2
+/// `alpha` of `ALPHA_ONE` is the current state; less is part of the way
+/// back to the previous tick.
+pub const ALPHA_ONE: u32 = 256;
+
+/// Draw the frame between the previous tick's `previous` and `state`,
+/// `alpha` of the way to `state`, for displays refreshing faster than the
+/// tick rate. Only positions are blended. Enemies move by their velocity
+/// every tick, so their previous position comes from that rather than from
+/// `previous`, whose pool slots a despawn reorders.
+pub fn render_interpolated(
+    state: &GameState,
+    previous: &SimState,
+    alpha: u32,
+    framebuffer: &mut FrameBuffer,
+) {
+    crate::profile_scope!("render");
+
+    draw_world(state, previous, alpha, framebuffer);
+    state.radar.render(framebuffer);
+    state.effects.apply(framebuffer);
+}
+
+/// Draw the ships and enemies alone, without the radar or the screen
+/// effects, as photo mode shows them.
+pub fn render_world(state: &GameState, framebuffer: &mut FrameBuffer) {
+    draw_world(state, &state.sim, ALPHA_ONE, framebuffer);
+}
+
+fn draw_world(state: &GameState, previous: &SimState, alpha: u32, framebuffer: &mut FrameBuffer) {
+    let alpha = alpha.min(ALPHA_ONE) as i32;
+    let blend = |from: i32, to: i32| {
+        (from * (ALPHA_ONE as i32 - alpha) + to * alpha + ALPHA_ONE as i32 / 2)
+            .div_euclid(ALPHA_ONE as i32)
     };
+    let blend_position =
+        |from: Vec2i, to: Vec2i| Vec2i::new(blend(from.x, to.x), blend(from.y, to.y));
+    let ship_position = |from: &Player, to: &Player| blend_position(from.pos, to.pos);
+
+    framebuffer.clear(0);
 
-    framebuffer.draw_sprite(state.player.x as i32, state.player.y as i32, frame);
+    // By the tick count rather than `anim_timer`, which wraps every 60
+    // ticks whatever the rate.
+    let frame = PLAYER_FLY.sprite_at(state.tick_rate.design_ticks(state.sim.frame_counter));
+    let player = ship_position(&previous.player, &state.sim.player);
+
+    framebuffer.draw_sprite(player.x, player.y, frame);
+
+    let markers = framebuffer.accessibility().shape_markers;
+
+    if let Some(partner) = &state.sim.partner {
+        let partner = ship_position(previous.partner.as_ref().unwrap_or(partner), partner);
+
+        framebuffer.draw_sprite(partner.x, partner.y, frame);
+
+        // The ships look alike; tell them apart by number.
+        if markers {
+            for (ship, label) in [(player, "1"), (partner, "2")] {
+                framebuffer.draw_text(
+                    ship.x as usize + PLAYER_WIDTH as usize + 1,
+                    ship.y as usize + (PLAYER_HEIGHT as usize - FONT_HEIGHT) / 2,
+                    label,
+                    3,
+                );
+            }
+        }
+    }
+
+    // Pickups, which drift a fixed distance each tick.
+    for pickup in state.sim.pickups.as_slice() {
+        let Vec2i { x, y } = blend_position(pickup.pos + Vec2i::new(PICKUP_DRIFT, 0), pickup.pos);
+
+        if let Some(sprite) = pickup.kind.def().sprite {
+            framebuffer.draw_sprite(x, y, sprite.sprite());
+        } else if !framebuffer.cull(x, y, PICKUP_BLOCK as usize, PICKUP_BLOCK as usize) {
+            draw_pickup_block(framebuffer, x, y);
+        }
+    }
+
+    let tick = state.sim.frame_counter;
+    let reduce_flashing = framebuffer.accessibility().reduce_flashing;
 
     // Enemies
-    for enemy in state.enemies.as_slice() {
-        if enemy.x >= 0 && enemy.x < WIDTH as i32 {
-            framebuffer.set_pixel(enemy.x as usize, enemy.y as usize, 2);
+    for enemy in state.sim.enemies.as_slice() {
+        let status = &enemy.health.status;
+        let previous = enemy.body.previous_position_scaled(status.time_scale());
+        let position = blend_position(previous, enemy.pos());
+        let Vec2i { x, y } = position;
+        let remap = status_remap(status, tick, reduce_flashing);
+
+        if let Some(sprite) = enemy.kind.def().sprite {
+            framebuffer.draw_sprite_remapped(x, y, sprite.sprite(), &remap);
+        } else if markers {
+            if !framebuffer.cull(x - 1, y - 1, 3, 3) {
+                draw_enemy_marker(framebuffer, x as usize, y as usize, &remap);
+            }
+        } else if !framebuffer.cull(x, y, 1, 1) {
+            framebuffer.set_pixel(x as usize, y as usize, remap[2]);
+        }
+
+        let hitbox = Rect::at(position, enemy.hitbox().size());
+
+        draw_embers(framebuffer, &hitbox, status.stacks(StatusKind::Burn), tick);
+    }
+
+    // Bullets, drawn over what fired them.
+    for bullet in state.sim.bullets.as_slice() {
+        let previous = Bullet {
+            pos: bullet.pos - bullet.vel,
+            ..*bullet
+        };
+        let Vec2i { x, y } =
+            blend_position(previous.hitbox().position(), bullet.hitbox().position());
+
+        if let Some(sprite) = bullet.kind.def().sprite {
+            framebuffer.draw_sprite(x, y, sprite.sprite());
+        } else if !framebuffer.cull(x, y, BULLET_BLOCK as usize, BULLET_BLOCK as usize) {
+            draw_bullet_block(framebuffer, x, y);
+        }
+    }
+}
+
+/// An enemy as a plus with a bright centre, so it is told apart by shape
+/// and not only by shade. Only the centre is its hitbox.
+fn draw_enemy_marker(framebuffer: &mut FrameBuffer, x: usize, y: usize, remap: &ColorRemap) {
+    for (dx, dy) in [(-1, 0), (1, 0), (0, -1), (0, 1)] {
+        framebuffer.set_pixel(
+            x.wrapping_add_signed(dx),
+            y.wrapping_add_signed(dy),
+            remap[2],
+        );
+    }
+
+    framebuffer.set_pixel(x, y, remap[3]);
+}
+
+/// A pickup without a sprite, as a hollow square: a ring rather than an
+/// enemy's plus.
+fn draw_pickup_block(framebuffer: &mut FrameBuffer, x: i32, y: i32) {
+    for dy in 0..PICKUP_BLOCK {
+        for dx in 0..PICKUP_BLOCK {
+            let edge = dx == 0 || dy == 0 || dx == PICKUP_BLOCK - 1 || dy == PICKUP_BLOCK - 1;
+
+            if edge && x + dx >= 0 && y + dy >= 0 {
+                framebuffer.set_pixel((x + dx) as usize, (y + dy) as usize, 3);
+            }
+        }
+    }
+}
+
+/// A bullet without a sprite, as a solid square.
+fn draw_bullet_block(framebuffer: &mut FrameBuffer, x: i32, y: i32) {
+    for dy in 0..BULLET_BLOCK {
+        for dx in 0..BULLET_BLOCK {
+            if x + dx >= 0 && y + dy >= 0 {
+                framebuffer.set_pixel((x + dx) as usize, (y + dy) as usize, 3);
+            }
+        }
+    }
+}
+
+/// The shades an enemy is drawn in under its status effects: pale while
+/// frozen, flickering bright while burning (steady with reduced flashing),
+/// dim while slowed.
+fn status_remap(status: &StatusEffects, tick: u32, reduce_flashing: bool) -> ColorRemap {
+    let hot = reduce_flashing || (tick / 4).is_multiple_of(2);
+
+    if status.has(StatusKind::Freeze) {
+        [0, 2, 3, 3]
+    } else if status.has(StatusKind::Burn) && hot {
+        [0, 3, 3, 2]
+    } else if status.has(StatusKind::Slow) {
+        [0, 1, 1, 2]
+    } else {
+        NO_REMAP
+    }
+}
+
+/// A spark per burn stack rising off the top of a burning enemy's
+/// `hitbox`, each over four ticks from its own place along the top.
+fn draw_embers(framebuffer: &mut FrameBuffer, hitbox: &Rect, stacks: u8, tick: u32) {
+    for spark in 0..stacks as u32 {
+        let age = tick + spark * 3;
+        let x = hitbox.x + ((spark * 5 + age / 4 * 3) as i32).rem_euclid(hitbox.w.max(1));
+        let y = hitbox.y - 1 - (age % 4) as i32;
+
+        if !framebuffer.cull(x, y, 1, 1) {
+            framebuffer.set_pixel(x as usize, y as usize, 3);
         }
     }
 }
@@ -0,0 +1,211 @@
+//! Bullet patterns: spreads, rings, spirals and aimed bursts.
+//!
+//! A `Pattern` is a few parameters; `Pattern::spawn` turns it into the
+//! bullets to add this tick, as positions and velocities in sub-pixels
+//! (`Vec2fx`). Directions come from the quarter-wave sine table
+//! below and randomness from the caller's `Rng`, so a pattern fired from
+//! the same state always spawns the same bullets. `PATTERNS` is the shared
+//! library that enemy scripts' `fire <n>` and bosses pick from by number.
+
+use crate::game::fixed_pool::FixedPool;
+pub use crate::math::geom::FIXED_ONE;
+use crate::math::geom::{Vec2fx, Vec2i};
+use crate::runtime::rng::Rng;
+
+/// A direction in 256ths of a turn: 0 points right and 64 down, since
+/// screen y grows downward.
+pub type Angle = u8;
+
+pub const QUARTER_TURN: Angle = 64;
+pub const HALF_TURN: Angle = 128;
+
+/// `sin` of each angle in the first quarter turn, in `FIXED_ONE` units.
+static QUARTER_SINE: [i16; QUARTER_TURN as usize + 1] = quarter_sine();
+
+/// Computed by the compiler, so the table is the same on every target.
+const fn quarter_sine() -> [i16; QUARTER_TURN as usize + 1] {
+    let mut table = [0; QUARTER_TURN as usize + 1];
+    let mut i = 0;
+
+    while i < table.len() {
+        let x = i as f64 * core::f64::consts::FRAC_PI_2 / QUARTER_TURN as f64;
+        let x2 = x * x;
+        // Taylor series; well under half a unit off over a quarter turn.
+        let sin = x * (1.0 - x2 / 6.0 * (1.0 - x2 / 20.0 * (1.0 - x2 / 42.0 * (1.0 - x2 / 72.0))));
+
+        table[i] = (sin * FIXED_ONE as f64 + 0.5) as i16;
+        i += 1;
+    }
+
+    table
+}
+
+/// `sin(angle)` in `FIXED_ONE` units.
+pub fn sin(angle: Angle) -> i32 {
+    let quarter = angle % QUARTER_TURN;
+    let value = match angle / QUARTER_TURN {
+        0 | 2 => QUARTER_SINE[quarter as usize],
+        _ => QUARTER_SINE[(QUARTER_TURN - quarter) as usize],
+    };
+
+    if angle < HALF_TURN {
+        value as i32
+    } else {
+        -(value as i32)
+    }
+}
+
+/// `cos(angle)` in `FIXED_ONE` units.
+pub fn cos(angle: Angle) -> i32 {
+    sin(angle.wrapping_add(QUARTER_TURN))
+}
+
+/// The angle nearest the direction of `delta`; 0 for no direction.
+pub fn angle_to(delta: Vec2i) -> Angle {
+    if delta == Vec2i::ZERO {
+        return 0;
+    }
+
+    let (dx, dy) = (delta.x as i64, delta.y as i64);
+
+    // The angle whose unit vector lies furthest along (dx, dy), and of
+    // those the table rounds alike, the one least to either side. Patterns
+    // aim once per shot, so trying all 256 is cheap enough.
+    (0..=u8::MAX)
+        .max_by_key(|&angle| {
+            let (cos, sin) = (cos(angle) as i64, sin(angle) as i64);
+
+            (dx * cos + dy * sin, -(dx * sin - dy * cos).abs())
+        })
+        .unwrap_or(0)
+}
+
+/// One bullet a pattern adds.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BulletSpawn {
+    pub pos: Vec2fx,
+    /// Per tick.
+    pub vel: Vec2fx,
+}
+
+/// Bullets one pattern adds at most.
+pub const MAX_PATTERN_BULLETS: usize = 32;
+
+pub type SpawnList = FixedPool<BulletSpawn, MAX_PATTERN_BULLETS>;
+
+/// Speeds are in `FIXED_ONE` units per tick; counts above
+/// `MAX_PATTERN_BULLETS` are cut short.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pattern {
+    /// `count` bullets fanned evenly over `arc`, centred on the target.
+    Spread { count: u8, arc: Angle, speed: i32 },
+    /// `count` bullets evenly around, turned by a random angle each time
+    /// so rings do not line up.
+    Ring { count: u8, speed: i32 },
+    /// `arms` bullets evenly around, turned `turn` further every tick: a
+    /// spiral when fired every few ticks.
+    Spiral { arms: u8, turn: i8, speed: i32 },
+    /// `count` bullets at the target, each up to `jitter` off course and
+    /// up to a quarter slower or faster.
+    AimedBurst {
+        count: u8,
+        jitter: Angle,
+        speed: i32,
+    },
+}
+
+impl Pattern {
+    /// Add the bullets fired from `origin` at `target`, both in pixels,
+    /// on tick `tick`, to `out`.
+    pub fn spawn(
+        &self,
+        origin: Vec2i,
+        target: Vec2i,
+        tick: u32,
+        rng: &mut Rng,
+        out: &mut SpawnList,
+    ) {
+        let aim = angle_to(target - origin);
+        let mut add = |angle: Angle, speed: i32| {
+            let _ = out.spawn(BulletSpawn {
+                pos: origin.to_fixed(),
+                vel: Vec2fx::new(
+                    cos(angle) * speed / FIXED_ONE,
+                    sin(angle) * speed / FIXED_ONE,
+                ),
+            });
+        };
+
+        match *self {
+            Self::Spread { count, arc, speed } => {
+                let count = count as i32;
+                let first = aim as i32 - arc as i32 / 2;
+
+                for i in 0..count {
+                    let offset = if count == 1 {
+                        arc as i32 / 2
+                    } else {
+                        arc as i32 * i / (count - 1)
+                    };
+
+                    add((first + offset) as Angle, speed);
+                }
+            }
+            Self::Ring { count, speed } => {
+                let start = rng.below(256) as i32;
+
+                for i in 0..count as i32 {
+                    add((start + 256 * i / count as i32) as Angle, speed);
+                }
+            }
+            Self::Spiral { arms, turn, speed } => {
+                let start = (tick as i32).wrapping_mul(turn as i32);
+
+                for i in 0..arms as i32 {
+                    add((start + 256 * i / arms as i32) as Angle, speed);
+                }
+            }
+            Self::AimedBurst {
+                count,
+                jitter,
+                speed,
+            } => {
+                for _ in 0..count {
+                    let off = rng.below(2 * jitter as u32 + 1) as i32 - jitter as i32;
+                    let quarter = (speed / 4).max(1);
+                    let speed = speed + rng.below(2 * quarter as u32 + 1) as i32 - quarter;
+
+                    add((aim as i32 + off) as Angle, speed);
+                }
+            }
+        }
+    }
+}
+
+/// The pattern library, by the number enemy scripts `fire`.
+pub const PATTERNS: [Pattern; 4] = [
+    Pattern::Spread {
+        count: 3,
+        arc: 32,
+        speed: FIXED_ONE,
+    },
+    Pattern::Ring {
+        count: 12,
+        speed: FIXED_ONE * 3 / 4,
+    },
+    Pattern::Spiral {
+        arms: 4,
+        turn: 5,
+        speed: FIXED_ONE,
+    },
+    Pattern::AimedBurst {
+        count: 5,
+        jitter: 8,
+        speed: FIXED_ONE * 3 / 2,
+    },
+];
+
+/// Pattern `id` of `PATTERNS`, if there is one.
+pub fn pattern(id: u8) -> Option<&'static Pattern> {
+    PATTERNS.get(id as usize)
+}
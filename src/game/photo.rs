@@ -0,0 +1,141 @@
+//! Photo mode: the paused game without the HUD, to frame and save as a
+//! picture.
+//!
+//! The arrows nudge the view a few pixels, A cycles the filters and Select
+//! the palettes, Start takes the photo and B leaves. `PhotoMode` only draws
+//! the frame and reports the buttons; the binary pauses the game, saves
+//! the PNG (`renderer::png`) and puts its own palette back afterwards.
+
+use crate::game::{GameState, render_world};
+use crate::input::Input;
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, PALETTES, WIDTH};
+
+/// Furthest the view moves from where the game left it, in pixels.
+pub const MAX_NUDGE: i32 = 8;
+
+/// A look applied to the whole photo.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Filter {
+    #[default]
+    None,
+    /// Every shade swapped for its opposite.
+    Invert,
+    /// Black and white: the two dark shades go black, the two light ones
+    /// white.
+    Mono,
+    /// Every other row darkened, like a CRT.
+    Scanlines,
+}
+
+impl Filter {
+    pub const ALL: [Self; 4] = [Self::None, Self::Invert, Self::Mono, Self::Scanlines];
+
+    fn apply(self, framebuffer: &mut FrameBuffer) {
+        match self {
+            Self::None => {}
+            Self::Invert => framebuffer.invert(),
+            Self::Mono => each_pixel(framebuffer, |_, _, color| if color < 2 { 0 } else { 3 }),
+            Self::Scanlines => each_pixel(framebuffer, |_, y, color| match y % 2 {
+                0 => color,
+                _ => color.saturating_sub(1),
+            }),
+        }
+    }
+}
+
+fn each_pixel(framebuffer: &mut FrameBuffer, map: impl Fn(usize, usize, u8) -> u8) {
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let color = map(x, y, framebuffer.get_pixel(x, y));
+            framebuffer.set_pixel(x, y, color);
+        }
+    }
+}
+
+/// What the player asked of photo mode this tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhotoAction {
+    None,
+    /// Save the frame `render` draws.
+    Capture,
+    Leave,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhotoMode {
+    /// Pixels the view is moved by.
+    nudge: (i32, i32),
+    filter: Filter,
+    /// Index into `PALETTES`.
+    palette: usize,
+    /// Buttons held last tick, to see which went down.
+    held: Input,
+}
+
+impl PhotoMode {
+    /// Photo mode starting on `palette`, or the first of `PALETTES` if it
+    /// is none of them.
+    pub fn new(palette: &[u32; 4]) -> Self {
+        Self {
+            nudge: (0, 0),
+            filter: Filter::None,
+            palette: PALETTES
+                .iter()
+                .position(|(_, colors)| colors == palette)
+                .unwrap_or(0),
+            // Whatever opened photo mode is still held.
+            held: Input::from_bits(!0),
+        }
+    }
+
+    /// The palette the photo is taken in.
+    pub fn palette(&self) -> [u32; 4] {
+        PALETTES[self.palette].1
+    }
+
+    /// Run one frame on the buttons held.
+    pub fn update(&mut self, input: Input) -> PhotoAction {
+        let pressed = Input::from_bits(input.bits() & !self.held.bits());
+        let press = |button| pressed.contains(button);
+
+        self.held = input;
+
+        // The view moves; the picture in it moves the other way.
+        let step = |forward, back| press(forward) as i32 - press(back) as i32;
+        let (dx, dy) = self.nudge;
+
+        self.nudge = (
+            (dx + step(Input::LEFT, Input::RIGHT)).clamp(-MAX_NUDGE, MAX_NUDGE),
+            (dy + step(Input::UP, Input::DOWN)).clamp(-MAX_NUDGE, MAX_NUDGE),
+        );
+
+        if press(Input::A) {
+            let next = Filter::ALL
+                .iter()
+                .position(|&f| f == self.filter)
+                .unwrap_or(0)
+                + 1;
+            self.filter = Filter::ALL[next % Filter::ALL.len()];
+        }
+
+        if press(Input::SELECT) {
+            self.palette = (self.palette + 1) % PALETTES.len();
+        }
+
+        if press(Input::B) {
+            PhotoAction::Leave
+        } else if press(Input::START) {
+            PhotoAction::Capture
+        } else {
+            PhotoAction::None
+        }
+    }
+
+    /// Draw `state` as the photo: nudged, filtered and in its palette.
+    pub fn render(&self, state: &GameState, framebuffer: &mut FrameBuffer) {
+        render_world(state, framebuffer);
+        framebuffer.shift(self.nudge.0, self.nudge.1, 0);
+        self.filter.apply(framebuffer);
+        framebuffer.set_palette(self.palette());
+    }
+}
@@ -0,0 +1,31 @@
+use crate::game::entities::PickupKind;
+use crate::math::geom::{Rect, Vec2i};
+
+/// Pixels per tick pickups drift left, with the scenery.
+pub const PICKUP_DRIFT: i32 = 1;
+
+/// Side of a pickup drawn without a sprite, so a ship can catch it.
+pub const PICKUP_BLOCK: i32 = 3;
+
+#[derive(Clone, Copy)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    /// Top-left corner.
+    pub pos: Vec2i,
+}
+
+impl Pickup {
+    pub const fn new(kind: PickupKind, pos: Vec2i) -> Self {
+        Self { kind, pos }
+    }
+
+    /// The sprite's bounds, or a `PICKUP_BLOCK` square.
+    pub fn hitbox(&self) -> Rect {
+        let size = match self.kind.def().sprite {
+            Some(sprite) => Vec2i::new(sprite.sprite().width as i32, sprite.sprite().height as i32),
+            None => Vec2i::new(PICKUP_BLOCK, PICKUP_BLOCK),
+        };
+
+        Rect::at(self.pos, size)
+    }
+}
@@ -1,5 +1,33 @@
+use crate::game::damage::Health;
+use crate::math::geom::{Rect, Vec2i};
+
+pub const PLAYER_WIDTH: i32 = 35;
+pub const PLAYER_HEIGHT: i32 = 16;
+
+/// Hit points a ship is held at. Ships cannot be destroyed yet, so a hit
+/// only counts.
+pub const SHIP_HP: u8 = u8::MAX;
+
+#[derive(Clone, Copy)]
 pub struct Player {
-    pub x: usize,
-    pub y: usize,
+    /// Top-left corner, always on screen.
+    pub pos: Vec2i,
     pub anim_timer: u32,
+    /// Hits land through it like on enemies; its hit points are topped up
+    /// to `SHIP_HP` after each.
+    pub health: Health,
+}
+
+impl Player {
+    pub const fn new(pos: Vec2i) -> Self {
+        Self {
+            pos,
+            anim_timer: 0,
+            health: Health::new(SHIP_HP),
+        }
+    }
+
+    pub fn hitbox(&self) -> Rect {
+        Rect::at(self.pos, Vec2i::new(PLAYER_WIDTH, PLAYER_HEIGHT))
+    }
 }
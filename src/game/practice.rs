@@ -0,0 +1,164 @@
+//! Practice: a stage select over the stages already cleared, each played
+//! from its start or straight from its boss, and a boss rush through all
+//! their bosses in turn.
+//!
+//! The binary finds the stages and which are cleared (`runtime::save`);
+//! `PracticeMenu` shows the list it is handed and reports the pick. The
+//! ship has no weapons yet, so there is no loadout to choose.
+
+use crate::input::Input;
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT, WIDTH};
+use crate::text::{Language, TextId};
+
+/// Top of the first row, under the heading.
+const LIST_Y: usize = 2 * LINE_HEIGHT;
+
+/// Left edge of the rows' text, leaving room for the cursor.
+const TEXT_X: usize = 10;
+
+/// Pixels between rows, on top of the line height.
+const ROW_GAP: usize = 4;
+
+/// A cleared stage, as the menu lists it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PracticeStage<'a> {
+    /// Shown as is; the font has capitals only.
+    pub name: &'a str,
+    /// Whether the stage has a boss to start from.
+    pub boss: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PracticeChoice {
+    /// Play `stages[index]`, from its boss if `boss`.
+    Stage {
+        index: usize,
+        boss: bool,
+    },
+    /// Play every stage's boss, in list order.
+    BossRush,
+    Back,
+}
+
+pub struct PracticeMenu {
+    selected: usize,
+    /// Whether the selected stage starts from its boss; Left and Right
+    /// toggle it.
+    from_boss: bool,
+    /// Buttons held last tick, to see which went down.
+    held: Input,
+}
+
+impl Default for PracticeMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PracticeMenu {
+    pub const fn new() -> Self {
+        Self {
+            selected: 0,
+            from_boss: false,
+            held: Input::empty(),
+        }
+    }
+
+    /// Run one tick on the buttons held. Returns the choice once made.
+    pub fn update(&mut self, input: Input, stages: &[PracticeStage<'_>]) -> Option<PracticeChoice> {
+        let pressed = Input::from_bits(input.bits() & !self.held.bits());
+        let press = |button| pressed.contains(button);
+
+        self.held = input;
+
+        let rows = rows(stages);
+
+        if rows == 0 || press(Input::B) {
+            return Some(PracticeChoice::Back);
+        }
+
+        if press(Input::DOWN) {
+            self.selected = (self.selected + 1) % rows;
+            self.from_boss = false;
+        } else if press(Input::UP) {
+            self.selected = (self.selected + rows - 1) % rows;
+            self.from_boss = false;
+        }
+
+        let stage = stages.get(self.selected);
+
+        if (press(Input::LEFT) || press(Input::RIGHT)) && stage.is_some_and(|stage| stage.boss) {
+            self.from_boss ^= true;
+        }
+
+        if !(press(Input::A) || press(Input::START)) {
+            return None;
+        }
+
+        Some(match stage {
+            Some(_) => PracticeChoice::Stage {
+                index: self.selected,
+                boss: self.from_boss,
+            },
+            None => PracticeChoice::BossRush,
+        })
+    }
+
+    pub fn render(
+        &self,
+        framebuffer: &mut FrameBuffer,
+        language: Language,
+        stages: &[PracticeStage<'_>],
+    ) {
+        let title = language.text(TextId::MenuPractice);
+
+        framebuffer.clear(0);
+        framebuffer.draw_text(
+            (WIDTH - title.chars().count() * FONT_ADVANCE) / 2,
+            LINE_HEIGHT / 2,
+            title,
+            3,
+        );
+
+        let row_height = framebuffer.ui_line_height() + ROW_GAP;
+        let visible = (HEIGHT - LIST_Y) / row_height;
+        // Scroll just enough to keep the cursor on screen.
+        let first = (self.selected + 1).saturating_sub(visible);
+
+        for row in first..rows(stages).min(first + visible) {
+            let y = LIST_Y + (row - first) * row_height;
+            let selected = row == self.selected;
+            let color = if selected { 3 } else { 2 };
+
+            let Some(stage) = stages.get(row) else {
+                let text = language.text(TextId::PracticeBossRush);
+                framebuffer.draw_ui_text(TEXT_X, y, text, color);
+                continue;
+            };
+
+            framebuffer.draw_ui_text(TEXT_X, y, stage.name, color);
+
+            let from = if selected && self.from_boss {
+                TextId::PracticeFromBoss
+            } else {
+                TextId::PracticeFromStart
+            };
+            let value = language.text(from);
+            let width = value.chars().count() * framebuffer.ui_font_advance();
+
+            framebuffer.draw_ui_text(WIDTH - 4 - width, y, value, color);
+        }
+
+        // A block left of the selected row.
+        let y = LIST_Y + (self.selected - first) * row_height;
+        let size = framebuffer.ui_line_height() / 2;
+        let top = y + (framebuffer.ui_line_height() - size) / 2 - 1;
+
+        framebuffer.fill_rect(TEXT_X - size - 2, top, size, size, 3);
+    }
+}
+
+/// The stages, then Boss Rush if any of them has a boss.
+fn rows(stages: &[PracticeStage<'_>]) -> usize {
+    stages.len() + stages.iter().any(|stage| stage.boss) as usize
+}
@@ -0,0 +1,18 @@
+//! Prefabs: entities spawned whole from their definitions in
+//! `assets/entities.toml`.
+//!
+//! `SimState::spawn_prefab` is how update code adds an entity. It builds
+//! the entity from its definition (stats, sprite and with it the hitbox,
+//! behavior and its script's state) instead of a struct literal at the
+//! call site. Bullets are not prefabs: patterns fire them with velocities
+//! of their own (`game::bullet`). Nothing is animated yet: an entity's
+//! sprite is a single frame.
+
+use crate::game::entities::{EnemyKind, PickupKind};
+
+/// What `SimState::spawn_prefab` spawns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrefabId {
+    Enemy(EnemyKind),
+    Pickup(PickupKind),
+}
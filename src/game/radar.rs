@@ -0,0 +1,146 @@
+//! The radar: a small HUD box showing the ships and enemies over more of
+//! the level than fits on screen.
+//!
+//! `Radar::update` reads the entities after every tick and scales their
+//! level positions down into box cells, so drawing is a handful of pixels.
+//! On a level it also shows the spawn markers the camera has not reached,
+//! so what is coming shows up before it enters the screen. Bosses blink.
+
+use crate::game::SimState;
+use crate::game::entities::EnemyKind;
+use crate::game::fixed_pool::FixedPool;
+use crate::math::geom::{Rect, Vec2i};
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+
+/// Level pixels the radar covers from the camera's left edge: the screen
+/// and the three ahead of it.
+pub const RADAR_RANGE: usize = 4 * WIDTH;
+
+/// Inside size of the box, in pixels.
+pub const RADAR_WIDTH: usize = 32;
+pub const RADAR_HEIGHT: usize = 18;
+
+/// Top-left corner of the box's border, in the top-right corner of the
+/// screen, away from the ships.
+const RADAR_X: usize = WIDTH - RADAR_WIDTH - 4;
+const RADAR_Y: usize = 2;
+
+/// Blips shown at most; the rest are left off.
+const MAX_BLIPS: usize = 64;
+
+/// A boss blip shows for this many ticks, then hides for as many.
+const BOSS_BLINK_TICKS: u32 = 8;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct Blip {
+    /// Cell inside the box.
+    x: u8,
+    y: u8,
+    boss: bool,
+}
+
+#[derive(Clone, Copy)]
+pub struct Radar {
+    enemies: FixedPool<Blip, MAX_BLIPS>,
+    ships: FixedPool<Blip, 2>,
+    /// Ticks seen, for the boss blink.
+    ticks: u32,
+}
+
+impl Default for Radar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The level the radar covers, relative to the camera.
+const RANGE: Rect = Rect::new(0, 0, RADAR_RANGE as i32, HEIGHT as i32);
+
+/// The box cell of the level position `pos`, relative to the camera;
+/// `None` outside the radar's range.
+fn cell(pos: Vec2i) -> Option<(u8, u8)> {
+    RANGE.contains(pos).then(|| {
+        (
+            (pos.x as usize * RADAR_WIDTH / RADAR_RANGE) as u8,
+            (pos.y as usize * RADAR_HEIGHT / HEIGHT) as u8,
+        )
+    })
+}
+
+impl Radar {
+    pub const fn new() -> Self {
+        let blip = Blip {
+            x: 0,
+            y: 0,
+            boss: false,
+        };
+
+        Self {
+            enemies: FixedPool::new(blip),
+            ships: FixedPool::new(blip),
+            ticks: 0,
+        }
+    }
+
+    /// Place the blips for `sim` as it is after a tick.
+    pub fn update(&mut self, sim: &SimState) {
+        self.ticks = self.ticks.wrapping_add(1);
+        self.enemies.clear();
+        self.ships.clear();
+
+        let mut add = |pos: Vec2i, boss: bool| {
+            if let Some((x, y)) = cell(pos) {
+                let _ = self.enemies.spawn(Blip { x, y, boss });
+            }
+        };
+
+        for enemy in sim.enemies.as_slice() {
+            add(enemy.pos(), enemy.kind.def().boss);
+        }
+
+        // Enemies are on screen, so their position is already relative to
+        // the camera; markers are at level positions.
+        if let Some(stage) = &sim.stage {
+            for spawn in stage.pending() {
+                let boss = EnemyKind::for_marker(spawn.kind).def().boss;
+                add(spawn.pos() - stage.camera(), boss);
+            }
+        }
+
+        for ship in core::iter::once(&sim.player).chain(&sim.partner) {
+            if let Some((x, y)) = cell(ship.hitbox().center()) {
+                let _ = self.ships.spawn(Blip { x, y, boss: false });
+            }
+        }
+    }
+
+    pub fn render(&self, framebuffer: &mut FrameBuffer) {
+        let (left, top) = (RADAR_X + 1, RADAR_Y + 1);
+        let screen = WIDTH * RADAR_WIDTH / RADAR_RANGE;
+
+        framebuffer.fill_rect(left, top, RADAR_WIDTH, RADAR_HEIGHT, 0);
+        framebuffer.draw_rect(RADAR_X, RADAR_Y, RADAR_WIDTH + 2, RADAR_HEIGHT + 2, 2);
+        // The part of the level on screen.
+        framebuffer.fill_rect(left + screen, top, 1, RADAR_HEIGHT, 1);
+
+        let boss_shown = (self.ticks / BOSS_BLINK_TICKS).is_multiple_of(2);
+
+        for blip in self.enemies.as_slice() {
+            let (x, y) = (left + blip.x as usize, top + blip.y as usize);
+
+            if !blip.boss {
+                framebuffer.set_pixel(x, y, 2);
+            } else if boss_shown {
+                // A 2×2 block, kept inside the box.
+                let x = x.min(left + RADAR_WIDTH - 2);
+                let y = y.min(top + RADAR_HEIGHT - 2);
+
+                framebuffer.fill_rect(x, y, 2, 2, 3);
+            }
+        }
+
+        for ship in self.ships.as_slice() {
+            framebuffer.set_pixel(left + ship.x as usize, top + ship.y as usize, 3);
+        }
+    }
+}
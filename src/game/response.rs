@@ -0,0 +1,171 @@
+//! Collision response: pushing overlapping entities apart, knocking them
+//! back, and moving them among a level's solid tiles.
+//!
+//! The shooter only reports overlaps (`collision`) and pushes nothing
+//! around; these are for levels where entities walk or drift between
+//! walls. `separation` is the shortest push out of an overlap, `knockback`
+//! an impulse away from a hit, and `move_and_slide` moves a hitbox a pixel
+//! at a time through `SolidTiles`, stopping each axis where it meets a wall
+//! so the other slides along it. Level files carry no collision flags, so
+//! `TileCollision` counts the tileset indices from a given one on as solid.
+
+use crate::game::kinematics::Kinematics;
+use crate::game::level::{Level, TILE_SIZE};
+use crate::game::patterns::{angle_to, cos, sin};
+use crate::math::geom::{FIXED_ONE, Rect, Vec2fx, Vec2i};
+
+/// Which tiles of a tile map block movement.
+pub trait SolidTiles {
+    /// Whether the tile at (`x`, `y`), in tiles, is solid. Asked about
+    /// tiles outside the map too.
+    fn is_solid(&self, x: i32, y: i32) -> bool;
+}
+
+/// A level's tiles, solid from tileset index `first_solid` on. Outside the
+/// map is solid, so nothing leaves it.
+#[derive(Debug, Clone, Copy)]
+pub struct TileCollision<'a> {
+    level: Level<'a>,
+    first_solid: u8,
+}
+
+impl<'a> TileCollision<'a> {
+    pub const fn new(level: Level<'a>, first_solid: u8) -> Self {
+        Self { level, first_solid }
+    }
+}
+
+impl SolidTiles for TileCollision<'_> {
+    fn is_solid(&self, x: i32, y: i32) -> bool {
+        match (usize::try_from(x), usize::try_from(y)) {
+            (Ok(x), Ok(y)) if x < self.level.width() && y < self.level.height() => {
+                self.level.tile(x, y) >= self.first_solid
+            }
+            _ => true,
+        }
+    }
+}
+
+/// Whether `hitbox`, in level pixels, covers part of a solid tile.
+pub fn hits_solid(hitbox: &Rect, tiles: &impl SolidTiles) -> bool {
+    if hitbox.is_empty() {
+        return false;
+    }
+
+    let tile = |v: i32| v.div_euclid(TILE_SIZE as i32);
+
+    (tile(hitbox.y)..=tile(hitbox.bottom() - 1))
+        .any(|y| (tile(hitbox.x)..=tile(hitbox.right() - 1)).any(|x| tiles.is_solid(x, y)))
+}
+
+/// How far a `move_and_slide` got.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Slide {
+    /// In pixels.
+    pub moved: Vec2i,
+    /// A wall stopped the horizontal part of the motion.
+    pub blocked_x: bool,
+    /// A wall stopped the vertical part.
+    pub blocked_y: bool,
+}
+
+/// Move `hitbox` by `motion` among `tiles` a pixel at a time, across first
+/// and then down or up. Each axis stops before the first pixel that would
+/// cover a solid tile while the other goes on, so a diagonal move into a
+/// wall slides along it. A hitbox already in a wall moves only by steps
+/// that take it clear.
+pub fn move_and_slide(hitbox: Rect, motion: Vec2i, tiles: &impl SolidTiles) -> Slide {
+    let mut at = hitbox;
+    let mut axis = |step: Vec2i, steps: i32| {
+        for _ in 0..steps {
+            let next = at.offset(step);
+
+            if hits_solid(&next, tiles) {
+                return true;
+            }
+
+            at = next;
+        }
+
+        false
+    };
+
+    let blocked_x = axis(Vec2i::new(motion.x.signum(), 0), motion.x.abs());
+    let blocked_y = axis(Vec2i::new(0, motion.y.signum()), motion.y.abs());
+
+    Slide {
+        moved: at.position() - hitbox.position(),
+        blocked_x,
+        blocked_y,
+    }
+}
+
+/// `Kinematics::integrate` among `tiles` for a body with a hitbox of
+/// `size` at its position: it moves only as far as `move_and_slide` lets
+/// it, and on an axis that meets a wall it stops at the wall and loses its
+/// velocity.
+pub fn integrate_against(body: &mut Kinematics, size: Vec2i, tiles: &impl SolidTiles) -> Slide {
+    let from = body.position();
+
+    body.integrate();
+
+    let slide = move_and_slide(Rect::at(from, size), body.position() - from, tiles);
+    let stopped = from + slide.moved;
+
+    if slide.blocked_x {
+        body.pos.x = stopped.x * FIXED_ONE;
+        body.vel.x = 0;
+    }
+
+    if slide.blocked_y {
+        body.pos.y = stopped.y * FIXED_ONE;
+        body.vel.y = 0;
+    }
+
+    slide
+}
+
+/// The shortest push that moves `a` clear of `b`, along whichever axis
+/// needs less; zero if they do not overlap.
+pub fn separation(a: &Rect, b: &Rect) -> Vec2i {
+    if !a.overlaps(b) {
+        return Vec2i::ZERO;
+    }
+
+    let (left, right) = (b.x - a.right(), b.right() - a.x);
+    let (up, down) = (b.y - a.bottom(), b.bottom() - a.y);
+    let x = if right < -left { right } else { left };
+    let y = if down < -up { down } else { up };
+
+    if x.abs() <= y.abs() {
+        Vec2i::new(x, 0)
+    } else {
+        Vec2i::new(0, y)
+    }
+}
+
+/// Push two overlapping bodies, with hitboxes of `a_size` and `b_size` at
+/// their positions, apart by half the `separation` each; `a` takes the odd
+/// pixel.
+pub fn separate(a: &mut Kinematics, a_size: Vec2i, b: &mut Kinematics, b_size: Vec2i) {
+    let push = separation(
+        &Rect::at(a.position(), a_size),
+        &Rect::at(b.position(), b_size),
+    );
+    let half = Vec2i::new(push.x / 2, push.y / 2);
+
+    a.nudge(push - half);
+    b.nudge(-half);
+}
+
+/// Add `strength` sub-pixels per tick to `body`'s velocity, away from
+/// `from`, or to the right if the body is at `from`. A body with `drag`
+/// loses the impulse over the following ticks; one without keeps it.
+pub fn knockback(body: &mut Kinematics, from: Vec2i, strength: i32) {
+    let angle = angle_to(body.position() - from);
+
+    body.vel += Vec2fx::new(
+        cos(angle) * strength / FIXED_ONE,
+        sin(angle) * strength / FIXED_ONE,
+    );
+}
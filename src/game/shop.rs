@@ -0,0 +1,316 @@
+//! The shop between practice stops: coins picked up in play buy upgrades
+//! that last from run to run.
+//!
+//! Every pickup is worth its `coins` in `assets/entities.toml`, and
+//! destroyed enemies drop them; live runs add what the ships collect to the
+//! save (`runtime::save`), which also keeps the upgrades bought. A normal
+//! run is one stage, a level or the endless wave, so the binary opens
+//! `ShopMenu` only between the stops of a practice run, and hands the
+//! chosen upgrade to `Upgrades::buy`, which refuses what is maxed or too
+//! dear. Upgrades change the simulation, so they are only given to practice
+//! runs, which are not scores: a scored or recorded run plays the same for
+//! everyone. Neither do runs recording a ghost get them (`game::ghost`).
+
+use crate::game::entities::{PICKUP_KINDS, PickupKind};
+use crate::input::Input;
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, LINE_HEIGHT, WIDTH};
+use crate::text::{Language, TextId};
+
+/// Upgrades sold, for tables indexed by `Upgrade::index`.
+pub const UPGRADES: usize = 2;
+
+/// Top of the first row, under the heading and the coins.
+const LIST_Y: usize = 3 * LINE_HEIGHT;
+
+/// Left edge of the rows' text, leaving room for the cursor.
+const TEXT_X: usize = 10;
+
+/// Rows are a line apart, with a gap.
+const ROW_HEIGHT: usize = LINE_HEIGHT + 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upgrade {
+    /// The ship moves an extra pixel every other tick per level.
+    Speed,
+    /// Room for another weapon. The ship has no weapons yet, so it only
+    /// counts.
+    WeaponSlots,
+}
+
+impl Upgrade {
+    pub const ALL: [Self; UPGRADES] = [Self::Speed, Self::WeaponSlots];
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Its key in the save, after `upgrade.`.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Speed => "speed",
+            Self::WeaponSlots => "weapon-slots",
+        }
+    }
+
+    pub const fn text(self) -> TextId {
+        match self {
+            Self::Speed => TextId::ShopSpeed,
+            Self::WeaponSlots => TextId::ShopWeaponSlots,
+        }
+    }
+
+    pub const fn max_level(self) -> u8 {
+        match self {
+            Self::Speed => 2,
+            Self::WeaponSlots => 3,
+        }
+    }
+
+    /// Coins for the level after `level`.
+    pub const fn cost(self, level: u8) -> u32 {
+        let base = match self {
+            Self::Speed => 20,
+            Self::WeaponSlots => 50,
+        };
+
+        base * (level as u32 + 1)
+    }
+}
+
+/// Why `Upgrades::buy` refused.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurchaseError {
+    MaxLevel,
+    /// Short of the cost.
+    NotEnoughCoins {
+        cost: u32,
+    },
+}
+
+/// The level of every upgrade bought.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Upgrades {
+    levels: [u8; UPGRADES],
+}
+
+impl Upgrades {
+    pub const NONE: Self = Self {
+        levels: [0; UPGRADES],
+    };
+
+    pub const fn level(&self, upgrade: Upgrade) -> u8 {
+        self.levels[upgrade.index()]
+    }
+
+    /// Set `upgrade`'s level, held to its `max_level`.
+    pub fn set_level(&mut self, upgrade: Upgrade, level: u8) {
+        self.levels[upgrade.index()] = level.min(upgrade.max_level());
+    }
+
+    pub fn is_none(&self) -> bool {
+        *self == Self::NONE
+    }
+
+    /// The levels as one word for snapshots, a byte each.
+    pub const fn to_word(&self) -> u32 {
+        let mut bytes = [0; 4];
+        let mut i = 0;
+
+        while i < UPGRADES {
+            bytes[i] = self.levels[i];
+            i += 1;
+        }
+
+        u32::from_le_bytes(bytes)
+    }
+
+    /// Read `to_word`'s output, holding each level to its `max_level`.
+    pub fn from_word(word: u32) -> Self {
+        let bytes = word.to_le_bytes();
+        let mut upgrades = Self::NONE;
+
+        for upgrade in Upgrade::ALL {
+            upgrades.set_level(upgrade, bytes[upgrade.index()]);
+        }
+
+        upgrades
+    }
+
+    /// Coins for `upgrade`'s next level; `None` once it is maxed.
+    pub const fn next_cost(&self, upgrade: Upgrade) -> Option<u32> {
+        let level = self.level(upgrade);
+
+        if level >= upgrade.max_level() {
+            None
+        } else {
+            Some(upgrade.cost(level))
+        }
+    }
+
+    /// Raise `upgrade` a level, paying for it out of `coins`.
+    pub fn buy(&mut self, upgrade: Upgrade, coins: &mut u32) -> Result<(), PurchaseError> {
+        let cost = self.next_cost(upgrade).ok_or(PurchaseError::MaxLevel)?;
+
+        if *coins < cost {
+            return Err(PurchaseError::NotEnoughCoins { cost });
+        }
+
+        *coins -= cost;
+        self.levels[upgrade.index()] += 1;
+        Ok(())
+    }
+
+    /// Extra pixels a ship moves per held direction on tick `frame`.
+    pub const fn extra_step(&self, frame: u32) -> i32 {
+        match self.level(Upgrade::Speed) {
+            0 => 0,
+            1 => (frame % 2) as i32,
+            _ => 1,
+        }
+    }
+}
+
+/// Coins for the pickups in `collected`, by `PickupKind::index`.
+pub fn coins_for(collected: &[u8; PICKUP_KINDS]) -> u32 {
+    PickupKind::ALL
+        .iter()
+        .map(|kind| collected[kind.index() as usize] as u32 * kind.def().coins as u32)
+        .sum()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShopChoice {
+    Buy(Upgrade),
+    /// On to the next stage.
+    Continue,
+}
+
+pub struct ShopMenu {
+    /// An upgrade by `Upgrade::index`, or past them Continue.
+    selected: usize,
+    /// Buttons held last tick, to see which went down.
+    held: Input,
+}
+
+impl Default for ShopMenu {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ShopMenu {
+    pub const fn new() -> Self {
+        Self {
+            selected: 0,
+            held: Input::empty(),
+        }
+    }
+
+    /// Run one tick on the buttons held. Returns the choice once made; B
+    /// continues too.
+    pub fn update(&mut self, input: Input) -> Option<ShopChoice> {
+        let pressed = Input::from_bits(input.bits() & !self.held.bits());
+        let press = |button| pressed.contains(button);
+        let rows = UPGRADES + 1;
+
+        self.held = input;
+
+        if press(Input::B) {
+            return Some(ShopChoice::Continue);
+        }
+
+        if press(Input::DOWN) {
+            self.selected = (self.selected + 1) % rows;
+        } else if press(Input::UP) {
+            self.selected = (self.selected + rows - 1) % rows;
+        }
+
+        if !(press(Input::A) || press(Input::START)) {
+            return None;
+        }
+
+        Some(match Upgrade::ALL.get(self.selected) {
+            Some(&upgrade) => ShopChoice::Buy(upgrade),
+            None => ShopChoice::Continue,
+        })
+    }
+
+    /// Draw the coins, then each upgrade with its level and the cost of
+    /// the next; those out of reach are dimmed.
+    pub fn render(
+        &self,
+        framebuffer: &mut FrameBuffer,
+        language: Language,
+        upgrades: &Upgrades,
+        coins: u32,
+    ) {
+        let title = language.text(TextId::ShopTitle);
+
+        framebuffer.clear(0);
+        framebuffer.draw_text(
+            (WIDTH - title.chars().count() * FONT_ADVANCE) / 2,
+            LINE_HEIGHT / 2,
+            title,
+            3,
+        );
+
+        let wallet = language.text(TextId::ShopCoins);
+        let x = TEXT_X + (wallet.chars().count() + 1) * FONT_ADVANCE;
+
+        framebuffer.draw_text(TEXT_X, LINE_HEIGHT * 3 / 2, wallet, 2);
+        framebuffer.draw_u32(x, LINE_HEIGHT * 3 / 2, coins, digits(coins), 3);
+
+        for row in 0..=UPGRADES {
+            let y = LIST_Y + row * ROW_HEIGHT;
+            let selected = row == self.selected;
+
+            let Some(&upgrade) = Upgrade::ALL.get(row) else {
+                let color = if selected { 3 } else { 2 };
+                framebuffer.draw_text(TEXT_X, y, language.text(TextId::ShopContinue), color);
+                continue;
+            };
+
+            let cost = upgrades.next_cost(upgrade);
+            let color = match cost {
+                Some(cost) if cost > coins => 1,
+                _ if selected => 3,
+                _ => 2,
+            };
+            let name = language.text(upgrade.text());
+            let level = upgrades.level(upgrade) as u32;
+            let x = TEXT_X + (name.chars().count() + 1) * FONT_ADVANCE;
+
+            framebuffer.draw_text(TEXT_X, y, name, color);
+            framebuffer.draw_u32(x, y, level, 1, color);
+
+            match cost {
+                Some(cost) => {
+                    let count = digits(cost);
+                    framebuffer.draw_u32(WIDTH - 4 - count * FONT_ADVANCE, y, cost, count, color);
+                }
+                None => {
+                    let maxed = language.text(TextId::ShopMaxed);
+                    let width = maxed.chars().count() * FONT_ADVANCE;
+                    framebuffer.draw_text(WIDTH - 4 - width, y, maxed, color);
+                }
+            }
+        }
+
+        // A block left of the selected row.
+        let y = LIST_Y + self.selected * ROW_HEIGHT;
+        let size = LINE_HEIGHT / 2;
+
+        framebuffer.fill_rect(
+            TEXT_X - size - 2,
+            y + (LINE_HEIGHT - size) / 2 - 1,
+            size,
+            size,
+            3,
+        );
+    }
+}
+
+/// Decimal digits in `value`.
+fn digits(value: u32) -> usize {
+    value.checked_ilog10().unwrap_or(0) as usize + 1
+}
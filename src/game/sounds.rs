@@ -0,0 +1,55 @@
+//! The game's sounds. Each effect is an `SfxDef` in `EFFECTS`, named by the
+//! `SfxId` constant at its index; songs are `.music` assets from the
+//! manifest, checked at compile time.
+
+use crate::audio::music::Music;
+use crate::audio::sfx::{ChannelKind, SfxDef, SfxId, SfxStep};
+use crate::audio::{WAVE_RAM_SIZE, square_period};
+use crate::game::assets::STAGE1_MUSIC;
+
+pub const STAGE1_SONG: Music<'static> = match Music::new(STAGE1_MUSIC) {
+    Some(song) => song,
+    None => panic!("stage1.music is not a music stream of this version"),
+};
+
+/// Effects of this priority or more duck the music, to `DUCK_LEVEL` out
+/// of 255.
+pub const DUCK_PRIORITY: u8 = 5;
+pub const DUCK_LEVEL: u8 = 96;
+
+/// Wave RAM for the wave channel: a triangle, for soft bass.
+pub const TRIANGLE_WAVE: [u8; WAVE_RAM_SIZE] = [
+    0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef, 0xfe, 0xdc, 0xba, 0x98, 0x76, 0x54, 0x32, 0x10,
+];
+
+/// An enemy touched the player.
+pub const HIT: SfxId = SfxId(0);
+/// An enemy entered the screen.
+pub const SPAWN: SfxId = SfxId(1);
+
+pub static EFFECTS: [SfxDef; 2] = [
+    // HIT: a loud noise burst that fades out.
+    SfxDef {
+        kind: ChannelKind::Noise,
+        priority: 10,
+        steps: &[SfxStep {
+            frames: 16,
+            control: 0xf1,
+            tone: 0x52,
+            period: 0,
+            sweep: 0,
+        }],
+    },
+    // SPAWN: a quiet chirp sweeping upwards.
+    SfxDef {
+        kind: ChannelKind::Square,
+        priority: 1,
+        steps: &[SfxStep {
+            frames: 6,
+            control: 0x41,
+            tone: 0x80,
+            period: square_period(660),
+            sweep: 12,
+        }],
+    },
+];
@@ -0,0 +1,143 @@
+//! The speedrun timer: the run's time in ticks, with a split at every
+//! stage cleared.
+//!
+//! Time only passes on ticks the game runs, so pauses and slow frames do
+//! not count and two runs of the same input time the same. It shows as
+//! `M:SS:FF`, minutes, seconds and ticks, in the bottom-left corner. After
+//! a split, the difference to the best time for that segment shows above
+//! it for a while: `-` and bright when ahead, `+` and dim when behind. The
+//! binary names the segments, keeps the best ones in the save file and can
+//! export the splits for LiveSplit (`runtime::splits`).
+
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT};
+use crate::runtime::memory::{ActiveMemory, Arena, MemoryError};
+use crate::runtime::pacing::TICK_RATE;
+
+/// Most splits a run keeps; stages cleared after these are not split.
+pub const MAX_SPLITS: usize = 32;
+
+/// Ticks the difference to the best segment shows after a split.
+const DELTA_TICKS: u32 = 3 * TICK_RATE as u32;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Split {
+    /// Run time at the split.
+    pub ticks: u32,
+    /// Time since the previous split.
+    pub segment: u32,
+    /// The best time for this segment before the run.
+    pub best: Option<u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpeedrunTimer {
+    ticks: u32,
+    splits: [Split; MAX_SPLITS],
+    len: usize,
+    /// Ticks the last split's difference still shows.
+    delta_ticks: u32,
+}
+
+impl Default for SpeedrunTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SpeedrunTimer {
+    pub const fn new() -> Self {
+        Self {
+            ticks: 0,
+            splits: [Split {
+                ticks: 0,
+                segment: 0,
+                best: None,
+            }; MAX_SPLITS],
+            len: 0,
+            delta_ticks: 0,
+        }
+    }
+
+    /// Ticks run so far.
+    pub fn ticks(&self) -> u32 {
+        self.ticks
+    }
+
+    pub fn splits(&self) -> &[Split] {
+        &self.splits[..self.len]
+    }
+
+    /// Count one tick of the game.
+    pub fn tick(&mut self) {
+        self.ticks = self.ticks.saturating_add(1);
+        self.delta_ticks = self.delta_ticks.saturating_sub(1);
+    }
+
+    /// End a segment whose best time so far was `best`. Returns the split,
+    /// or `None` once `MAX_SPLITS` are taken.
+    pub fn split(&mut self, best: Option<u32>) -> Option<Split> {
+        let start = self.splits().last().map_or(0, |split| split.ticks);
+        let split = Split {
+            ticks: self.ticks,
+            segment: self.ticks - start,
+            best,
+        };
+
+        *self.splits.get_mut(self.len)? = split;
+        self.len += 1;
+        self.delta_ticks = DELTA_TICKS;
+        Some(split)
+    }
+
+    /// Draw the time in the bottom-left corner, with the last split's
+    /// difference above it while it shows. The text is formatted in
+    /// `memory`'s frame arena.
+    pub fn render(&self, framebuffer: &mut FrameBuffer, memory: &mut ActiveMemory) {
+        let y = HEIGHT - 1 - LINE_HEIGHT;
+
+        if let Ok(time) = format_time(&mut memory.frame, "", self.ticks) {
+            framebuffer.fill_rect(0, y - 1, time.len() * FONT_ADVANCE + 3, LINE_HEIGHT + 1, 0);
+            framebuffer.draw_text(2, y, time, 3);
+        }
+
+        let Some(split) = self.splits().last() else {
+            return;
+        };
+
+        if self.delta_ticks == 0 {
+            return;
+        }
+
+        let Some(best) = split.best else {
+            return;
+        };
+
+        let (sign, delta, color) = match split.segment.checked_sub(best) {
+            Some(behind) => ("+", behind, 1),
+            None => ("-", best - split.segment, 3),
+        };
+        let y = y - LINE_HEIGHT;
+
+        if let Ok(text) = format_time(&mut memory.frame, sign, delta) {
+            framebuffer.fill_rect(0, y - 1, text.len() * FONT_ADVANCE + 3, LINE_HEIGHT, 0);
+            framebuffer.draw_text(2, y, text, color);
+        }
+    }
+}
+
+/// `ticks` as `M:SS:FF` after `sign`, formatted in `frame`.
+fn format_time<'a, const SIZE: usize>(
+    frame: &'a mut Arena<SIZE>,
+    sign: &str,
+    ticks: u32,
+) -> Result<&'a str, MemoryError> {
+    let seconds = ticks / TICK_RATE as u32;
+
+    crate::fmt_to_arena!(
+        frame,
+        "{sign}{}:{:02}:{:02}",
+        seconds / 60,
+        seconds % 60,
+        ticks % TICK_RATE as u32
+    )
+}
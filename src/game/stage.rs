@@ -0,0 +1,141 @@
+//! Level playback: scrolls a camera across a `Level` and hands out its
+//! spawn markers as the camera's right edge reaches them.
+//!
+//! The markers are copied out of the level, so a `Stage` does not borrow
+//! the level file and can live in `GameState`.
+
+use crate::game::entities::EnemyKind;
+use crate::game::level::{Level, SCROLL_SUBPIXELS, Spawn};
+use crate::math::geom::Vec2i;
+use crate::renderer::framebuffer::WIDTH;
+
+/// Most spawn markers a stage keeps; markers placed after these are
+/// dropped.
+pub const MAX_STAGE_SPAWNS: usize = 128;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Stage {
+    /// Markers sorted by x.
+    spawns: [Spawn; MAX_STAGE_SPAWNS],
+    len: usize,
+    /// Markers handed out so far.
+    next: usize,
+    /// Camera position in 1/`SCROLL_SUBPIXELS` pixels.
+    scroll: u32,
+    /// `Level::scroll_speed`.
+    speed: u16,
+}
+
+impl Stage {
+    pub fn new(level: &Level<'_>) -> Self {
+        let mut spawns = [Spawn {
+            x: 0,
+            y: 0,
+            kind: 0,
+        }; MAX_STAGE_SPAWNS];
+        let len = level.spawn_count().min(MAX_STAGE_SPAWNS);
+
+        for (index, spawn) in spawns[..len].iter_mut().enumerate() {
+            *spawn = level.spawn(index);
+        }
+
+        if level.spawn_count() > MAX_STAGE_SPAWNS {
+            crate::log_warn!(
+                "level has {} spawns; only the first {MAX_STAGE_SPAWNS} are used",
+                level.spawn_count()
+            );
+        }
+
+        spawns[..len].sort_unstable_by_key(|spawn| (spawn.x, spawn.y, spawn.kind));
+
+        Self {
+            spawns,
+            len,
+            next: 0,
+            scroll: 0,
+            speed: level.scroll_speed(),
+        }
+    }
+
+    /// The level pixel at the screen's top-left corner. Subtracting it
+    /// turns a level position into a screen one.
+    pub fn camera(&self) -> Vec2i {
+        Vec2i::new((self.scroll / SCROLL_SUBPIXELS as u32) as i32, 0)
+    }
+
+    /// Markers handed out so far.
+    pub fn spawned(&self) -> usize {
+        self.next
+    }
+
+    /// How far the stage has got, for snapshots: markers handed out and
+    /// the camera's sub-pixel scroll.
+    pub const fn cursor_words(&self) -> [u32; 2] {
+        [self.next as u32, self.scroll]
+    }
+
+    /// Go back to a `cursor_words` of the same level. Returns false,
+    /// leaving the stage as it was, if it has fewer markers.
+    pub fn set_cursor(&mut self, [next, scroll]: [u32; 2]) -> bool {
+        if next as usize > self.len {
+            return false;
+        }
+
+        self.next = next as usize;
+        self.scroll = scroll;
+        true
+    }
+
+    /// The markers not handed out yet, sorted by x.
+    pub fn pending(&self) -> &[Spawn] {
+        &self.spawns[self.next..self.len]
+    }
+
+    /// Whether every marker has been handed out.
+    pub fn is_finished(&self) -> bool {
+        self.next == self.len
+    }
+
+    /// Whether a marker spawns a boss.
+    pub fn has_boss(&self) -> bool {
+        self.boss_index().is_some()
+    }
+
+    /// Move the camera so the first boss's marker is due next tick,
+    /// dropping the markers before it. Returns false, leaving the stage as
+    /// it was, if there is no boss.
+    pub fn skip_to_boss(&mut self) -> bool {
+        let Some(index) = self.boss_index() else {
+            return false;
+        };
+        let x = self.spawns[index].x as u32;
+
+        self.next = index;
+        self.scroll = x.saturating_sub(WIDTH as u32) * SCROLL_SUBPIXELS as u32;
+        true
+    }
+
+    fn boss_index(&self) -> Option<usize> {
+        self.spawns[..self.len]
+            .iter()
+            .position(|spawn| EnemyKind::for_marker(spawn.kind).def().boss)
+    }
+
+    /// Move the camera one tick.
+    pub fn advance(&mut self) {
+        self.scroll = self.scroll.saturating_add(self.speed as u32);
+    }
+
+    /// The next marker the camera's right edge has reached, if any. Call
+    /// until `None` after every `advance`.
+    pub fn next_due(&mut self) -> Option<Spawn> {
+        let spawn = *self.spawns[..self.len].get(self.next)?;
+
+        if (spawn.pos() - self.camera()).x > WIDTH as i32 {
+            return None;
+        }
+
+        self.next += 1;
+        Some(spawn)
+    }
+}
@@ -0,0 +1,134 @@
+//! Lifetime statistics: play time, games, hits taken and enemies
+//! destroyed per kind.
+//!
+//! `Stats::record` takes the `TickEvents` every live tick returns, so the
+//! game itself keeps no counters. The binary keeps the totals in the save
+//! file (`runtime::save`) and shows them from the title's Stats entry.
+
+use crate::game::TickEvents;
+use crate::game::entities::{ENEMY_KINDS, EnemyKind};
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, LINE_HEIGHT, WIDTH};
+use crate::runtime::pacing::TICK_RATE;
+use crate::text::{Language, TextId};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    /// Ticks played, at `TICK_RATE` a second.
+    pub play_ticks: u64,
+    pub games: u32,
+    /// Times a ship was hit.
+    pub hits: u32,
+    /// Enemies destroyed, by `EnemyKind::index`.
+    pub kills: [u32; ENEMY_KINDS],
+}
+
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Stats {
+    pub const fn new() -> Self {
+        Self {
+            play_ticks: 0,
+            games: 0,
+            hits: 0,
+            kills: [0; ENEMY_KINDS],
+        }
+    }
+
+    /// Count a game started.
+    pub fn start_game(&mut self) {
+        self.games = self.games.saturating_add(1);
+    }
+
+    /// Count one tick played and what happened in it.
+    pub fn record(&mut self, events: &TickEvents) {
+        self.play_ticks = self.play_ticks.saturating_add(1);
+        self.hits = self.hits.saturating_add(events.hit as u32);
+
+        for (kills, &destroyed) in self.kills.iter_mut().zip(&events.destroyed) {
+            *kills = kills.saturating_add(destroyed as u32);
+        }
+    }
+}
+
+/// Draw the stats screen: the totals, then kills per enemy kind.
+pub fn render_stats(framebuffer: &mut FrameBuffer, language: Language, stats: &Stats) {
+    let title = language.text(TextId::StatsTitle);
+
+    framebuffer.clear(0);
+    framebuffer.draw_text(
+        (WIDTH - title.chars().count() * FONT_ADVANCE) / 2,
+        LINE_HEIGHT / 2,
+        title,
+        3,
+    );
+
+    let seconds = stats.play_ticks / TICK_RATE;
+    let hours = (seconds / 3600).min(99_999) as u32;
+    let mut y = 2 * LINE_HEIGHT;
+
+    // H:MM:SS, right-aligned.
+    let parts = [
+        (hours, digits(hours)),
+        ((seconds / 60 % 60) as u32, 2),
+        ((seconds % 60) as u32, 2),
+    ];
+    let mut x = WIDTH - 2 - (digits(hours) + 6) * FONT_ADVANCE;
+
+    framebuffer.draw_text(2, y, language.text(TextId::StatsPlayTime), 2);
+
+    for (i, (value, count)) in parts.into_iter().enumerate() {
+        if i > 0 {
+            framebuffer.draw_text(x, y, ":", 3);
+            x += FONT_ADVANCE;
+        }
+
+        framebuffer.draw_u32(x, y, value, count, 3);
+        x += count * FONT_ADVANCE;
+    }
+
+    y += LINE_HEIGHT;
+
+    for (id, value) in [
+        (TextId::StatsGames, stats.games),
+        (TextId::StatsHits, stats.hits),
+    ] {
+        draw_row(framebuffer, y, language.text(id), value);
+        y += LINE_HEIGHT;
+    }
+
+    y += LINE_HEIGHT;
+    framebuffer.draw_text(2, y, language.text(TextId::StatsKills), 3);
+    y += LINE_HEIGHT;
+
+    for kind in EnemyKind::ALL {
+        let name = kind.def().name;
+
+        // Names are lower case in the data file; the font has capitals.
+        for (i, c) in name.chars().enumerate() {
+            let mut bytes = [0; 4];
+            let c = c.to_ascii_uppercase().encode_utf8(&mut bytes);
+
+            framebuffer.draw_text(2 + (i + 1) * FONT_ADVANCE, y, c, 2);
+        }
+
+        draw_row(framebuffer, y, "", stats.kills[kind.index() as usize]);
+        y += LINE_HEIGHT;
+    }
+}
+
+/// `label` on the left of row `y` and `value` right-aligned.
+fn draw_row(framebuffer: &mut FrameBuffer, y: usize, label: &str, value: u32) {
+    let count = digits(value);
+
+    framebuffer.draw_text(2, y, label, 2);
+    framebuffer.draw_u32(WIDTH - 2 - count * FONT_ADVANCE, y, value, count, 3);
+}
+
+/// Decimal digits in `value`.
+fn digits(value: u32) -> usize {
+    value.checked_ilog10().unwrap_or(0) as usize + 1
+}
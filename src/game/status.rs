@@ -0,0 +1,177 @@
+//! Status effects: timed slow, burn and freeze on an entity, up to
+//! `MAX_STACKS` of each.
+//!
+//! A `Hit` may carry a `StatusEffect`; the damage pipeline adds it to the
+//! target's `StatusEffects` as one more stack, lasting as long as the
+//! longer of the effect and what was left. Every tick `StatusEffects::tick`
+//! counts each effect down and runs its kind's hook, which reports what
+//! the effect does this tick: a burn's damage, a slow's or a freeze's share
+//! of the tick the entity moves. The renderer shows what an entity suffers
+//! from its `StatusEffects` alone (`game::draw_world`).
+
+use crate::math::geom::FIXED_ONE;
+
+/// Status kinds, for tables indexed by `StatusKind::index`.
+pub const STATUS_KINDS: usize = 3;
+
+/// Most stacks of one effect an entity carries.
+pub const MAX_STACKS: u8 = 3;
+
+/// Damage each stack of burn deals every `BURN_EVERY` ticks.
+pub const BURN_DAMAGE: u8 = 1;
+pub const BURN_EVERY: u8 = 15;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusKind {
+    /// Halves how far the entity moves, per stack.
+    Slow,
+    /// Damage over time, more per stack.
+    Burn,
+    /// Stops the entity, its script and its homing; more stacks do not
+    /// freeze it harder.
+    Freeze,
+}
+
+impl StatusKind {
+    pub const ALL: [Self; STATUS_KINDS] = [Self::Slow, Self::Burn, Self::Freeze];
+
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// The kind's hook: what `stacks` of it do on a tick with `ticks` left
+    /// after it.
+    fn update(self, stacks: u8, ticks: u8, tick: &mut StatusTick) {
+        match self {
+            Self::Slow => tick.time_scale >>= stacks,
+            Self::Burn => {
+                if ticks.is_multiple_of(BURN_EVERY) {
+                    tick.burn = tick.burn.saturating_add(BURN_DAMAGE * stacks);
+                }
+            }
+            Self::Freeze => tick.time_scale = 0,
+        }
+    }
+}
+
+/// A stack of `kind` for `ticks` ticks, as a hit applies it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    pub ticks: u8,
+}
+
+/// What an entity's effects do on one tick.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatusTick {
+    /// Burn damage due, before resistances.
+    pub burn: u8,
+    /// Share of the tick the entity moves, in 1/`FIXED_ONE`ths: 0 when
+    /// frozen.
+    pub time_scale: i32,
+}
+
+/// The effects on one entity.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StatusEffects {
+    /// Ticks left of each kind, by `StatusKind::index`.
+    ticks: [u8; STATUS_KINDS],
+    stacks: [u8; STATUS_KINDS],
+}
+
+impl StatusEffects {
+    pub const NONE: Self = Self {
+        ticks: [0; STATUS_KINDS],
+        stacks: [0; STATUS_KINDS],
+    };
+
+    /// Add a stack of `effect`.
+    pub fn apply(&mut self, effect: StatusEffect) {
+        if effect.ticks == 0 {
+            return;
+        }
+
+        let i = effect.kind.index();
+
+        self.stacks[i] = (self.stacks[i] + 1).min(MAX_STACKS);
+        self.ticks[i] = self.ticks[i].max(effect.ticks);
+    }
+
+    /// Stacks of `kind`; 0 if it has worn off.
+    pub const fn stacks(&self, kind: StatusKind) -> u8 {
+        self.stacks[kind.index()]
+    }
+
+    pub const fn has(&self, kind: StatusKind) -> bool {
+        self.stacks(kind) > 0
+    }
+
+    /// Share of a tick the entity moves now, as `StatusTick::time_scale`.
+    pub fn time_scale(&self) -> i32 {
+        if self.has(StatusKind::Freeze) {
+            0
+        } else {
+            FIXED_ONE >> self.stacks(StatusKind::Slow)
+        }
+    }
+
+    /// Advance one tick: count every effect down, drop the ones that wore
+    /// off and run the others' hooks. `time_scale` then matches the
+    /// returned one.
+    pub fn tick(&mut self) -> StatusTick {
+        let mut tick = StatusTick {
+            burn: 0,
+            time_scale: FIXED_ONE,
+        };
+
+        for kind in StatusKind::ALL {
+            let i = kind.index();
+
+            if self.stacks[i] == 0 {
+                continue;
+            }
+
+            self.ticks[i] -= 1;
+
+            if self.ticks[i] == 0 {
+                self.stacks[i] = 0;
+            } else {
+                kind.update(self.stacks[i], self.ticks[i], &mut tick);
+            }
+        }
+
+        tick
+    }
+
+    /// As little-endian words, for the state hash and snapshots.
+    pub const fn to_words(&self) -> [u32; 2] {
+        let [slow, burn, freeze] = self.ticks;
+        let [slow_stacks, burn_stacks, freeze_stacks] = self.stacks;
+
+        [
+            u32::from_le_bytes([slow, burn, freeze, slow_stacks]),
+            u32::from_le_bytes([burn_stacks, freeze_stacks, 0, 0]),
+        ]
+    }
+
+    /// Read `to_words`' output, holding stacks to `MAX_STACKS` and
+    /// dropping effects without ticks left.
+    pub fn from_words(words: [u32; 2]) -> Self {
+        let [slow, burn, freeze, slow_stacks] = words[0].to_le_bytes();
+        let [burn_stacks, freeze_stacks, _, _] = words[1].to_le_bytes();
+        let mut effects = Self {
+            ticks: [slow, burn, freeze],
+            stacks: [slow_stacks, burn_stacks, freeze_stacks],
+        };
+
+        for i in 0..STATUS_KINDS {
+            if effects.ticks[i] == 0 {
+                effects.stacks[i] = 0;
+            } else {
+                effects.stacks[i] = effects.stacks[i].clamp(1, MAX_STACKS);
+            }
+        }
+
+        effects
+    }
+}
@@ -0,0 +1,259 @@
+//! The title screen: the logo, a blinking PRESS START and a menu of
+//! Start, Practice, Options, High Scores and Stats.
+//!
+//! `TitleScreen` runs on the same `Input` the game reads, one call to
+//! `update` per tick, and reacts to buttons as they go down. The Options
+//! page toggles the accessibility settings the framebuffer applies, so
+//! changes show on the title right away. Choosing Start wipes the screen
+//! before `update` reports it.
+
+use crate::game::assets::LOGO;
+use crate::input::Input;
+use crate::renderer::effects::{Accessibility, ScreenEffects};
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+use crate::renderer::transition::{Direction, Transition, TransitionKind};
+use crate::text::{Language, TextId};
+
+/// Top of the logo.
+const LOGO_Y: i32 = 24;
+
+/// Top of the first menu row, under the logo.
+const MENU_Y: usize = 72;
+
+/// Top of the first options row.
+const OPTIONS_Y: usize = 56;
+
+/// Pixels between rows, on top of the line height.
+const ROW_GAP: usize = 4;
+
+/// PRESS START shows for this many ticks, then hides for as many.
+const BLINK_TICKS: u32 = 30;
+
+/// Ticks of the wipe after Start is chosen.
+const WIPE_TICKS: u16 = 24;
+
+const MENU: [TextId; 5] = [
+    TextId::MenuStart,
+    TextId::MenuPractice,
+    TextId::MenuOptions,
+    TextId::MenuHighScores,
+    TextId::MenuStats,
+];
+
+/// Rows of the Options page; the last is Back.
+const OPTIONS: [TextId; 5] = [
+    TextId::OptionReduceFlashing,
+    TextId::OptionScreenShake,
+    TextId::OptionLargeText,
+    TextId::OptionShapeMarkers,
+    TextId::MenuBack,
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Page {
+    PressStart,
+    Menu,
+    Options,
+}
+
+/// What the player picked on the title.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleChoice {
+    Start,
+    Practice,
+    HighScores,
+    Stats,
+}
+
+pub struct TitleScreen {
+    page: Page,
+    /// Row of the current page the cursor is on.
+    selected: usize,
+    /// Buttons held last tick, to see which went down.
+    held: Input,
+    ticks: u32,
+    accessibility: Accessibility,
+    /// Whether the High Scores entry can be chosen.
+    high_scores: bool,
+    /// Whether Practice can be chosen: a stage has been cleared.
+    practice: bool,
+    /// Set once Start is chosen; the wipe runs before it is reported.
+    starting: bool,
+    effects: ScreenEffects,
+}
+
+impl TitleScreen {
+    /// A title showing PRESS START, editing `accessibility`. High Scores is
+    /// greyed out unless `high_scores`, and Practice unless `practice`.
+    pub fn new(accessibility: Accessibility, high_scores: bool, practice: bool) -> Self {
+        Self {
+            page: Page::PressStart,
+            selected: 0,
+            held: Input::empty(),
+            ticks: 0,
+            accessibility,
+            high_scores,
+            practice,
+            starting: false,
+            effects: ScreenEffects::new(),
+        }
+    }
+
+    /// The settings as the Options page left them.
+    pub fn accessibility(&self) -> Accessibility {
+        self.accessibility
+    }
+
+    /// Run one tick on the buttons held. Returns the choice once made;
+    /// after Practice, High Scores or Stats the title can carry on where it
+    /// was.
+    pub fn update(&mut self, input: Input) -> Option<TitleChoice> {
+        let pressed = Input::from_bits(input.bits() & !self.held.bits());
+        let press = |button| pressed.contains(button);
+
+        self.held = input;
+        self.ticks = self.ticks.wrapping_add(1);
+        self.effects.tick();
+
+        if self.starting {
+            return (!self.effects.in_transition()).then_some(TitleChoice::Start);
+        }
+
+        let rows = match self.page {
+            Page::PressStart => 0,
+            Page::Menu => MENU.len(),
+            Page::Options => OPTIONS.len(),
+        };
+
+        if press(Input::DOWN) && rows > 0 {
+            self.selected = (self.selected + 1) % rows;
+        } else if press(Input::UP) && rows > 0 {
+            self.selected = (self.selected + rows - 1) % rows;
+        }
+
+        let choose = press(Input::A) || press(Input::START);
+
+        match self.page {
+            Page::PressStart if choose => self.open(Page::Menu),
+            Page::Menu if choose => match MENU[self.selected] {
+                TextId::MenuStart => {
+                    self.starting = true;
+                    self.effects.transition(Transition::new(
+                        TransitionKind::Wipe,
+                        Direction::Out,
+                        WIPE_TICKS,
+                    ));
+                }
+                TextId::MenuOptions => self.open(Page::Options),
+                TextId::MenuStats => return Some(TitleChoice::Stats),
+                TextId::MenuPractice if self.practice => return Some(TitleChoice::Practice),
+                TextId::MenuHighScores if self.high_scores => {
+                    return Some(TitleChoice::HighScores);
+                }
+                _ => {}
+            },
+            Page::Menu if press(Input::B) => self.open(Page::PressStart),
+            Page::Options if press(Input::B) => self.open(Page::Menu),
+            Page::Options if choose => {
+                let accessibility = &mut self.accessibility;
+
+                match self.selected {
+                    0 => accessibility.reduce_flashing ^= true,
+                    1 => accessibility.screen_shake ^= true,
+                    2 => accessibility.large_text ^= true,
+                    3 => accessibility.shape_markers ^= true,
+                    _ => self.open(Page::Menu),
+                }
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    fn open(&mut self, page: Page) {
+        self.page = page;
+        self.selected = 0;
+    }
+
+    pub fn render(&self, framebuffer: &mut FrameBuffer, language: Language) {
+        framebuffer.clear(0);
+        LOGO.draw(framebuffer, (WIDTH - LOGO.pixel_width()) as i32 / 2, LOGO_Y);
+
+        let row_height = framebuffer.ui_line_height() + ROW_GAP;
+
+        match self.page {
+            Page::PressStart => {
+                if (self.ticks / BLINK_TICKS).is_multiple_of(2) {
+                    let text = language.text(TextId::PressStart);
+                    draw_centred(framebuffer, MENU_Y + row_height, text, 3);
+                }
+            }
+            Page::Menu => {
+                // Large text would run off the bottom; it covers the logo
+                // instead.
+                let top = MENU_Y.min(HEIGHT - MENU.len() * row_height);
+
+                for (row, &id) in MENU.iter().enumerate() {
+                    let y = top + row * row_height;
+                    let color = match id {
+                        TextId::MenuHighScores if !self.high_scores => 1,
+                        TextId::MenuPractice if !self.practice => 1,
+                        _ if row == self.selected => 3,
+                        _ => 2,
+                    };
+                    let x = draw_centred(framebuffer, y, language.text(id), color);
+
+                    if row == self.selected {
+                        draw_cursor(framebuffer, x, y);
+                    }
+                }
+            }
+            Page::Options => {
+                let accessibility = self.accessibility;
+                let values = [
+                    accessibility.reduce_flashing,
+                    accessibility.screen_shake,
+                    accessibility.large_text,
+                    accessibility.shape_markers,
+                ];
+
+                for (row, &id) in OPTIONS.iter().enumerate() {
+                    let y = OPTIONS_Y + row * row_height;
+                    let color = if row == self.selected { 3 } else { 2 };
+
+                    if row == self.selected {
+                        draw_cursor(framebuffer, 6, y);
+                    }
+
+                    framebuffer.draw_ui_text(6, y, language.text(id), color);
+
+                    if let Some(&on) = values.get(row) {
+                        let value = language.text(if on { TextId::On } else { TextId::Off });
+                        let width = value.chars().count() * framebuffer.ui_font_advance();
+                        framebuffer.draw_ui_text(WIDTH - 4 - width, y, value, color);
+                    }
+                }
+            }
+        }
+
+        self.effects.apply(framebuffer);
+    }
+}
+
+/// Draw `text` centred on row `y`; returns its left edge.
+fn draw_centred(framebuffer: &mut FrameBuffer, y: usize, text: &str, color: u8) -> usize {
+    let width = text.chars().count() * framebuffer.ui_font_advance();
+    let x = WIDTH.saturating_sub(width) / 2;
+
+    framebuffer.draw_ui_text(x, y, text, color);
+    x
+}
+
+/// A block just left of the row of text starting at (`x`, `y`).
+fn draw_cursor(framebuffer: &mut FrameBuffer, x: usize, y: usize) {
+    let size = framebuffer.ui_line_height() / 2;
+    let top = y + (framebuffer.ui_line_height() - size) / 2 - 1;
+
+    framebuffer.fill_rect(x.saturating_sub(size + 2), top, size, size, 3);
+}
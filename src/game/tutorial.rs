@@ -0,0 +1,141 @@
+//! Tutorial prompts: a hint with the buttons to press, shown the first
+//! time the player meets each mechanic.
+//!
+//! `Tutorial::update` looks at the game after every tick and shows the
+//! first prompt not seen yet whose moment has come. A prompt is seen once
+//! the player presses one of its buttons, or after a few seconds either
+//! way. The binary keeps the seen flags in the save file (`runtime::save`)
+//! and shows prompts in live play only, so replays and golden frames do
+//! not change.
+
+use crate::game::SimState;
+use crate::input::{BUTTON_HEIGHT, Input, button_width, draw_button};
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+use crate::runtime::pacing::TICK_RATE;
+use crate::text::{Language, TextId};
+
+pub const PROMPTS: usize = Prompt::ALL.len();
+
+/// Longest a prompt shows.
+const PROMPT_TICKS: u32 = 4 * TICK_RATE as u32;
+
+/// Shortest a prompt shows, so a button already held does not flash it
+/// away.
+const MIN_PROMPT_TICKS: u32 = TICK_RATE as u32;
+
+/// Pixels between the caps and the text.
+const GAP: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Prompt {
+    /// At the start of the first game.
+    Move,
+    /// When the first enemy comes on screen.
+    Dodge,
+}
+
+impl Prompt {
+    pub const ALL: [Self; 2] = [Self::Move, Self::Dodge];
+
+    /// The prompt's key in the save file.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Move => "move",
+            Self::Dodge => "dodge",
+        }
+    }
+
+    const fn text(self) -> TextId {
+        match self {
+            Self::Move => TextId::TutorialMove,
+            Self::Dodge => TextId::TutorialDodge,
+        }
+    }
+
+    /// The buttons the prompt shows.
+    const fn buttons(self) -> &'static [Input] {
+        match self {
+            Self::Move => &[Input::UP, Input::DOWN, Input::LEFT, Input::RIGHT],
+            Self::Dodge => &[Input::UP, Input::DOWN],
+        }
+    }
+
+    /// Whether the mechanic has come up in `sim`.
+    fn due(self, sim: &SimState) -> bool {
+        match self {
+            Self::Move => true,
+            Self::Dodge => !sim.enemies.is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tutorial {
+    /// By `Prompt` order.
+    seen: [bool; PROMPTS],
+    showing: Option<Prompt>,
+    /// Ticks `showing` has been up.
+    ticks: u32,
+}
+
+impl Tutorial {
+    /// A tutorial that skips the prompts `seen` before.
+    pub const fn new(seen: [bool; PROMPTS]) -> Self {
+        Self {
+            seen,
+            showing: None,
+            ticks: 0,
+        }
+    }
+
+    /// The seen flags, by `Prompt` order.
+    pub fn seen(&self) -> [bool; PROMPTS] {
+        self.seen
+    }
+
+    /// Run one tick, after the game's, on the first player's buttons.
+    pub fn update(&mut self, sim: &SimState, input: Input) {
+        let Some(prompt) = self.showing else {
+            self.showing = Prompt::ALL
+                .into_iter()
+                .find(|&prompt| !self.seen[prompt as usize] && prompt.due(sim));
+            self.ticks = 0;
+            return;
+        };
+
+        self.ticks += 1;
+
+        let pressed = prompt
+            .buttons()
+            .iter()
+            .any(|&button| input.contains(button));
+
+        if self.ticks >= PROMPT_TICKS || (self.ticks >= MIN_PROMPT_TICKS && pressed) {
+            self.seen[prompt as usize] = true;
+            self.showing = None;
+        }
+    }
+
+    /// Draw the prompt showing, if any, centred at the bottom of the
+    /// screen.
+    pub fn render(&self, framebuffer: &mut FrameBuffer, language: Language) {
+        let Some(prompt) = self.showing else {
+            return;
+        };
+
+        let text = language.text(prompt.text());
+        let caps: usize = prompt.buttons().iter().map(|&b| button_width(b) + 1).sum();
+        let width = caps + GAP + text.chars().count() * framebuffer.ui_font_advance();
+        let height = BUTTON_HEIGHT.max(framebuffer.ui_line_height() + 2);
+        let mut x = WIDTH.saturating_sub(width) / 2;
+        let y = HEIGHT - 4 - height;
+
+        framebuffer.fill_rect(x.saturating_sub(2), y - 2, width + 4, height + 4, 0);
+
+        for &button in prompt.buttons() {
+            x += draw_button(framebuffer, x, y, button) + 1;
+        }
+
+        framebuffer.draw_ui_text(x + GAP, y + 2, text, 3);
+    }
+}
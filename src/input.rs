@@ -0,0 +1,370 @@
+use core::fmt;
+#[cfg(feature = "std")]
+use std::fs;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::Path;
+
+use crate::platform::KEYMAP;
+use crate::renderer::font::{Glyph, ICON_DOWN, ICON_LEFT, ICON_RIGHT, ICON_UP};
+use crate::renderer::framebuffer::{
+    FONT_ADVANCE, FONT_HEIGHT, FONT_SPACING, FONT_WIDTH, FrameBuffer, HEIGHT, WIDTH,
+};
+use crate::runtime::pacing::TICK_RATE;
+use crate::text::{Language, TextId};
+
+/// Buttons held during one tick, one bit per button.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Input(u8);
+
+impl Input {
+    pub const UP: Self = Self(1 << 0);
+    pub const DOWN: Self = Self(1 << 1);
+    pub const LEFT: Self = Self(1 << 2);
+    pub const RIGHT: Self = Self(1 << 3);
+    pub const A: Self = Self(1 << 4);
+    pub const B: Self = Self(1 << 5);
+    pub const START: Self = Self(1 << 6);
+    pub const SELECT: Self = Self(1 << 7);
+
+    /// Button names as used in input scripts, in bit order.
+    pub const NAMES: [(&'static str, Self); 8] = [
+        ("UP", Self::UP),
+        ("DOWN", Self::DOWN),
+        ("LEFT", Self::LEFT),
+        ("RIGHT", Self::RIGHT),
+        ("A", Self::A),
+        ("B", Self::B),
+        ("START", Self::START),
+        ("SELECT", Self::SELECT),
+    ];
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// The button called `name` in `NAMES`, in any case.
+    pub fn named(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(candidate, _)| candidate.eq_ignore_ascii_case(name))
+            .map(|(_, button)| *button)
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn contains(self, button: Self) -> bool {
+        self.0 & button.0 == button.0
+    }
+
+    pub fn insert(&mut self, button: Self) {
+        self.0 |= button.0;
+    }
+}
+
+impl fmt::Display for Input {
+    /// Space-separated button names, or `-` when nothing is held.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 == 0 {
+            return f.write_str("-");
+        }
+
+        let mut first = true;
+
+        for (name, button) in Self::NAMES {
+            if self.contains(button) {
+                if !first {
+                    f.write_str(" ")?;
+                }
+
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Rapid fire. While a turbo button is held it is pressed for the first
+/// half of each cycle and released for the rest, starting pressed on the
+/// tick it goes down. Cycles are counted in ticks, so the rate is exact at
+/// the fixed timestep and a recording of the result replays the same.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Turbo {
+    buttons: Input,
+    /// Ticks per press and release.
+    period: u32,
+    /// Ticks each button has been held, in bit order.
+    held: [u32; 8],
+    /// What the last `apply` returned.
+    last: Input,
+}
+
+impl Turbo {
+    /// Fastest rate: pressed one tick, released the next.
+    pub const MAX_HZ: u32 = TICK_RATE as u32 / 2;
+
+    pub const DEFAULT_HZ: u32 = 15;
+
+    /// Turbo on `buttons` at `hz` presses a second, clamped to
+    /// 1..=`MAX_HZ`. Rates that do not divide the tick rate round down.
+    pub fn new(buttons: Input, hz: u32) -> Self {
+        Self {
+            buttons,
+            period: TICK_RATE as u32 / hz.clamp(1, Self::MAX_HZ),
+            held: [0; 8],
+            last: Input::empty(),
+        }
+    }
+
+    pub fn buttons(&self) -> Input {
+        self.buttons
+    }
+
+    /// Presses a second, after rounding.
+    pub fn hz(&self) -> u32 {
+        TICK_RATE as u32 / self.period
+    }
+
+    /// Turn one tick's held buttons into the buttons the game sees.
+    pub fn apply(&mut self, input: Input) -> Input {
+        let mut output = input;
+
+        for (bit, held) in self.held.iter_mut().enumerate() {
+            let button = Input(1 << bit);
+
+            if !self.buttons.contains(button) {
+                continue;
+            }
+
+            if !input.contains(button) {
+                *held = 0;
+                continue;
+            }
+
+            if *held % self.period >= self.period.div_ceil(2) {
+                output.0 &= !button.0;
+            }
+
+            *held += 1;
+        }
+
+        self.last = output;
+        output
+    }
+}
+
+/// Show that turbo is on in the bottom-right corner: dim while no turbo
+/// button is held, then flashing with the presses.
+pub fn render_turbo(framebuffer: &mut FrameBuffer, turbo: &Turbo, language: Language) {
+    let text = language.text(TextId::Turbo);
+
+    let color = if turbo.last.0 & turbo.buttons.0 != 0 {
+        3
+    } else if turbo.held.iter().any(|&held| held > 0) {
+        2
+    } else {
+        1
+    };
+    let width = text.chars().count() * framebuffer.ui_font_advance();
+    let x = WIDTH - 2 - width;
+    let y = HEIGHT - 1 - framebuffer.ui_line_height();
+
+    framebuffer.fill_rect(x - 1, y - 1, width + 1, framebuffer.ui_line_height(), 0);
+    framebuffer.draw_ui_text(x, y, text, color);
+}
+
+/// Height of a key cap drawn by `draw_button`.
+pub const BUTTON_HEIGHT: usize = FONT_HEIGHT + 4;
+
+/// The arrow icon of a direction button.
+fn button_icon(button: Input) -> Option<&'static Glyph> {
+    [
+        (Input::UP, &ICON_UP),
+        (Input::DOWN, &ICON_DOWN),
+        (Input::LEFT, &ICON_LEFT),
+        (Input::RIGHT, &ICON_RIGHT),
+    ]
+    .into_iter()
+    .find_map(|(direction, icon)| (direction == button).then_some(icon))
+}
+
+/// The name of the `KEYMAP` key bound to `button`.
+fn button_label(button: Input) -> &'static str {
+    KEYMAP
+        .iter()
+        .find(|(_, bound)| *bound == button)
+        .map_or("", |(key, _)| key.label())
+}
+
+/// Width of `button`'s key cap as `draw_button` draws it.
+pub fn button_width(button: Input) -> usize {
+    let inner = match button_icon(button) {
+        Some(_) => FONT_WIDTH,
+        None => (button_label(button).len() * FONT_ADVANCE).saturating_sub(FONT_SPACING),
+    };
+
+    inner + 4
+}
+
+/// Draw `button` as a key cap with its top-left corner at (`x`, `y`): an
+/// arrow for the directions, else the name of its `KEYMAP` key. Returns
+/// the cap's width.
+pub fn draw_button(framebuffer: &mut FrameBuffer, x: usize, y: usize, button: Input) -> usize {
+    let width = button_width(button);
+
+    framebuffer.fill_rect(x, y, width, BUTTON_HEIGHT, 2);
+
+    match button_icon(button) {
+        Some(icon) => framebuffer.draw_char(x + 2, y + 2, icon, 0),
+        None => framebuffer.draw_text(x + 2, y + 2, button_label(button), 0),
+    }
+
+    width
+}
+
+/// Input over time, stored as the frames at which the held buttons change.
+///
+/// The text form has one change per line: a frame number followed by the
+/// buttons held from that frame on, or `-` for none. An optional last line
+/// `end <frame>` records how long the recording ran. Blank lines and lines
+/// starting with `#` are ignored.
+///
+/// ```text
+/// # frame buttons
+/// 0 -
+/// 30 UP
+/// 45 UP A
+/// 60 -
+/// end 600
+/// ```
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct InputScript {
+    changes: Vec<(u32, Input)>,
+    end: Option<u32>,
+}
+
+#[cfg(feature = "std")]
+impl InputScript {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the buttons held on `frame`. Frames must be pushed in order;
+    /// a push that does not change the input is ignored.
+    pub fn push(&mut self, frame: u32, input: Input) {
+        debug_assert!(self.changes.last().is_none_or(|&(last, _)| last <= frame));
+
+        if self.changes.is_empty() || self.at(frame) != input {
+            self.changes.push((frame, input));
+        }
+    }
+
+    /// Buttons held on `frame`.
+    pub fn at(&self, frame: u32) -> Input {
+        let next = self.changes.partition_point(|&(start, _)| start <= frame);
+
+        match next {
+            0 => Input::empty(),
+            _ => self.changes[next - 1].1,
+        }
+    }
+
+    /// Frame of the last change, after which the input stays constant.
+    pub fn last_change(&self) -> u32 {
+        self.changes.last().map_or(0, |&(frame, _)| frame)
+    }
+
+    /// Number of frames the recording covers, if known.
+    pub fn end(&self) -> Option<u32> {
+        self.end
+    }
+
+    pub fn set_end(&mut self, frames: u32) {
+        self.end = Some(frames);
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut script = Self::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(end) = line.strip_prefix("end ") {
+                let end = end
+                    .trim()
+                    .parse()
+                    .map_err(|_| format!("line {}: expected a frame number", number + 1))?;
+                script.end = Some(end);
+                continue;
+            }
+
+            let mut words = line.split_whitespace();
+            let frame = words
+                .next()
+                .and_then(|word| word.parse::<u32>().ok())
+                .ok_or_else(|| format!("line {}: expected a frame number", number + 1))?;
+
+            if frame < script.last_change() {
+                return Err(format!("line {}: frames must be in order", number + 1));
+            }
+
+            let mut input = Input::empty();
+
+            for word in words {
+                if word == "-" {
+                    continue;
+                }
+
+                let button = Input::named(word)
+                    .ok_or_else(|| format!("line {}: unknown button `{word}`", number + 1))?;
+
+                input.insert(button);
+            }
+
+            script.changes.push((frame, input));
+        }
+
+        Ok(script)
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+}
+
+#[cfg(feature = "std")]
+impl fmt::Display for InputScript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# frame buttons")?;
+
+        for (frame, input) in &self.changes {
+            writeln!(f, "{frame} {input}")?;
+        }
+
+        if let Some(end) = self.end {
+            writeln!(f, "end {end}")?;
+        }
+
+        Ok(())
+    }
+}
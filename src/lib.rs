@@ -1,4 +1,10 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+pub mod audio;
 pub mod game;
 pub mod input;
+pub mod math;
+pub mod platform;
 pub mod renderer;
 pub mod runtime;
+pub mod text;
@@ -1,100 +1,2096 @@
-use bit_bound::game::{GameState, render, update};
-use bit_bound::renderer::framebuffer::{self, FrameBuffer};
-use bit_bound::runtime::memory::RuntimeMemory;
+use bit_bound::audio::offline::{DEFAULT_SAMPLE_RATE, OfflineAudio};
+use bit_bound::game::damage::DamageTarget;
+use bit_bound::game::ghost::{Ghost, GhostRecorder, render_ghost};
+use bit_bound::game::haptics::Haptics;
+use bit_bound::game::level::Level;
+use bit_bound::game::photo::{PhotoAction, PhotoMode};
+use bit_bound::game::shop::coins_for;
+use bit_bound::game::speedrun::SpeedrunTimer;
+use bit_bound::game::stage::Stage;
+use bit_bound::game::stats::Stats;
+use bit_bound::game::tutorial::Tutorial;
+use bit_bound::game::{ALPHA_ONE, GameState, render, render_interpolated, update_coop};
+use bit_bound::input::{Input, InputScript, Turbo, render_turbo};
+use bit_bound::log_info;
+use bit_bound::platform::minifb::MinifbPlatform;
+#[cfg(feature = "sdl2")]
+use bit_bound::platform::sdl::{SdlOptions, SdlPlatform};
+#[cfg(feature = "terminal")]
+use bit_bound::platform::terminal::TerminalPlatform;
+use bit_bound::platform::threaded::ThreadedPlatform;
+use bit_bound::platform::{Key, Platform, Rumble, WindowState};
+use bit_bound::renderer::effects::Accessibility;
+use bit_bound::renderer::framebuffer::{
+    DirtyCheck, FrameBuffer, PALETTES, dim_palette, palette_named,
+};
+use bit_bound::runtime::Runtime;
+use bit_bound::runtime::config::{CONFIG_FILE, Config};
+use bit_bound::runtime::daily::{DAILY_TICKS, DailyResult, Date};
+use bit_bound::runtime::error::Error;
+use bit_bound::runtime::log::{self, LogLevel};
+use bit_bound::runtime::memory::OvercommitCheck;
+use bit_bound::runtime::netplay::{DEFAULT_DELAY, Lockstep, MAX_DELAY};
+use bit_bound::runtime::pacing::{BACKGROUND_RATE, FramePacer, Pacing, Scene, TickRate};
+use bit_bound::runtime::quality::QualityWatchdog;
+#[cfg(feature = "debug_server")]
+use bit_bound::runtime::remote::DebugServer;
+use bit_bound::runtime::save::{SAVE_FILE, SaveData};
+use bit_bound::runtime::{crash, headless, soak};
+use bit_bound::text::{LANGUAGES, Language};
 
-use std::cell::UnsafeCell;
-use std::time::{Duration, Instant};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use minifb::{Window, WindowOptions};
+/// Fraction of the frame arena a single tick may use before it is reported.
+const FRAME_SOFT_LIMIT_PERCENT: usize = 75;
 
-const FRAME_TIME: Duration = Duration::from_millis(16);
+/// Where practice looks for stages: every `.lvl` file, in name order.
+const LEVELS_DIR: &str = "levels";
 
-struct Global<T> {
-    inner: UnsafeCell<T>,
+/// Where the log is written when a session produced warnings or errors.
+const LOG_FILE: &str = "bitbound.log";
+
+/// Where F9 writes the last frame's profiler zones.
+#[cfg(all(feature = "debug_overlay", feature = "profiler"))]
+const TRACE_FILE: &str = "bitbound-trace.json";
+
+const USAGE: &str = "usage: bit-bound [--headless <frames> [--wav <file>]] [--soak <frames> [--seed <n>]] [--daily [--seed <yyyymmdd>]] [--input <script>] [--record <script>] [--replay <script>] [--ghost <file>] [--record-ghost <file>] [--level <file>] [--backend minifb|sdl2|terminal] [--fullscreen] [--scale <n>] [--window-position <x,y>] [--palette <name>] [--language en|de] [--reduce-flashing] [--no-screen-shake] [--no-rumble] [--large-text] [--shape-markers] [--pacing sleep|spin|vsync|uncapped] [--tick-rate 60|50] [--interpolate] [--run-in-background] [--threaded-present] [--timer] [--splits <file>] [--save-config] [--skip-title] [--host <addr> | --join <addr>] [--input-delay <ticks>] [--turbo <buttons> [--turbo-rate <hz>]] [--leaderboard <url> [--leaderboard-key <key>] [--player <name>] [--scores]] [--debug-server <addr>]";
+
+/// Which `Platform` opens the window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+enum Backend {
+    #[cfg_attr(not(feature = "sdl2"), default)]
+    Minifb,
+    #[cfg(feature = "sdl2")]
+    #[default]
+    Sdl2,
+    #[cfg(feature = "terminal")]
+    Terminal,
+}
+
+#[derive(Default)]
+struct Options {
+    /// Run this many frames without a window, print a summary and exit.
+    headless: Option<u32>,
+    /// Write the headless run's audio to this WAV file.
+    wav: Option<PathBuf>,
+    /// Run this many frames of seeded random input without a window, checking
+    /// runtime invariants.
+    soak: Option<u32>,
+    /// Seed for `--soak`, or the day `--daily` plays.
+    seed: u64,
+    /// Play the daily challenge of this day.
+    daily: Option<Date>,
+    /// Play input from this script instead of the keyboard.
+    input: Option<PathBuf>,
+    /// Save the session's input to this script on exit.
+    record: Option<PathBuf>,
+    /// Open this recording in the replay viewer.
+    replay: Option<PathBuf>,
+    /// Show this ghost beside the player.
+    ghost: Option<PathBuf>,
+    /// Save the run's ship path as a ghost on exit, or after `--headless`.
+    record_ghost: Option<PathBuf>,
+    /// Play this level instead of the endless test wave.
+    level: Option<PathBuf>,
+    /// Window backend; SDL2 when built with the `sdl2` feature.
+    backend: Backend,
+    /// Fill the desktop (SDL2 only).
+    #[cfg(feature = "sdl2")]
+    fullscreen: bool,
+    /// Window size as a multiple of the screen; the backend's default if
+    /// unset.
+    scale: Option<u32>,
+    /// Where the window's top-left corner opens; centred or wherever the
+    /// backend puts it if unset.
+    window_position: Option<(i32, i32)>,
+    /// Colours for the four shades; the framebuffer's default if unset.
+    palette: Option<[u32; 4]>,
+    /// Reduced flashing, screen shake, large text and shape markers.
+    accessibility: Accessibility,
+    /// Language of the game's text.
+    language: Language,
+    /// How the loop waits between frames.
+    pacing: Pacing,
+    /// Ticks a second: 60, or 50 like a PAL console.
+    tick_rate: TickRate,
+    /// Draw positions between the last two ticks; with `vsync` or
+    /// `uncapped` pacing only.
+    interpolate: bool,
+    /// Show the speedrun timer.
+    timer: bool,
+    /// Rumble controllers on hits; on unless turned off.
+    rumble: bool,
+    /// Write the run's splits to this LiveSplit file on exit.
+    splits: Option<PathBuf>,
+    /// Keep playing while the window is not focused instead of pausing.
+    run_in_background: bool,
+    /// Present from a thread of its own.
+    threaded_present: bool,
+    /// Buttons that auto-fire while held.
+    turbo: Input,
+    /// Turbo presses a second; `Turbo::DEFAULT_HZ` if unset.
+    turbo_rate: Option<u32>,
+    /// Wait for a co-op partner on this UDP address.
+    host: Option<String>,
+    /// Join the co-op game hosted at this UDP address.
+    join: Option<String>,
+    /// Ticks of netplay input delay when hosting; `DEFAULT_DELAY` if unset.
+    input_delay: Option<u32>,
+    /// Write `settings` to `CONFIG_FILE` before starting.
+    save_config: bool,
+    /// Start playing without the title screen.
+    skip_title: bool,
+    /// The settings from `CONFIG_FILE` with the command line's applied on
+    /// top.
+    settings: Config,
+    /// Submit live runs to the leaderboard server at this URL.
+    #[cfg(feature = "leaderboard")]
+    leaderboard: Option<String>,
+    /// Key the server checks submissions' signatures with.
+    #[cfg(feature = "leaderboard")]
+    leaderboard_key: String,
+    /// Name submitted with scores.
+    #[cfg(feature = "leaderboard")]
+    player: String,
+    /// Show the leaderboard instead of playing.
+    #[cfg(feature = "leaderboard")]
+    scores: bool,
+    /// Serve the remote debug protocol on this address.
+    #[cfg(feature = "debug_server")]
+    debug_server: Option<String>,
+}
+
+/// Apply a setting `CONFIG_FILE` may hold. Each has a `--<key>` flag taking
+/// the same value, except `fullscreen`, which is `true` or `false` in the
+/// file and a bare flag on the command line.
+fn set_option(options: &mut Options, key: &str, value: &str) -> Result<(), String> {
+    match key {
+        "level" => options.level = Some(value.into()),
+        "backend" => {
+            options.backend = match value {
+                "minifb" => Backend::Minifb,
+                #[cfg(feature = "sdl2")]
+                "sdl2" => Backend::Sdl2,
+                #[cfg(feature = "terminal")]
+                "terminal" => Backend::Terminal,
+                other => return Err(format!("unknown backend `{other}`")),
+            }
+        }
+        #[cfg(feature = "sdl2")]
+        "fullscreen" => {
+            options.fullscreen = value
+                .parse()
+                .map_err(|_| format!("invalid fullscreen `{value}`; use true or false"))?;
+        }
+        "scale" => {
+            let scale = value
+                .parse()
+                .ok()
+                .filter(|scale| (1..=8).contains(scale))
+                .ok_or(format!("invalid scale `{value}`; use 1 to 8"))?;
+            options.scale = Some(scale);
+        }
+        "window-position" => {
+            let position = value
+                .split_once(',')
+                .and_then(|(x, y)| Some((x.trim().parse().ok()?, y.trim().parse().ok()?)))
+                .ok_or(format!("invalid window position `{value}`; use x,y"))?;
+            options.window_position = Some(position);
+        }
+        "palette" => {
+            let palette = palette_named(value).ok_or_else(|| {
+                let names: Vec<_> = PALETTES.iter().map(|(name, _)| *name).collect();
+                format!("unknown palette `{value}`; use {}", names.join(", "))
+            })?;
+            options.palette = Some(palette);
+        }
+        "language" => {
+            options.language = Language::named(value).ok_or_else(|| {
+                let codes: Vec<_> = LANGUAGES.iter().map(|(code, _)| *code).collect();
+                format!("unknown language `{value}`; use {}", codes.join(", "))
+            })?;
+        }
+        "pacing" => {
+            options.pacing = Pacing::parse(value).ok_or(format!("unknown pacing `{value}`"))?;
+        }
+        "tick-rate" => {
+            options.tick_rate = TickRate::parse(value)
+                .ok_or(format!("invalid tick rate `{value}`; use 60 or 50"))?;
+        }
+        "interpolate" => {
+            options.interpolate = value
+                .parse()
+                .map_err(|_| format!("invalid interpolate `{value}`; use true or false"))?;
+        }
+        "run-in-background" => {
+            options.run_in_background = value
+                .parse()
+                .map_err(|_| format!("invalid run-in-background `{value}`; use true or false"))?;
+        }
+        "threaded-present" => {
+            options.threaded_present = value
+                .parse()
+                .map_err(|_| format!("invalid threaded-present `{value}`; use true or false"))?;
+        }
+        "timer" => {
+            options.timer = value
+                .parse()
+                .map_err(|_| format!("invalid timer `{value}`; use true or false"))?;
+        }
+        "rumble" => {
+            options.rumble = value
+                .parse()
+                .map_err(|_| format!("invalid rumble `{value}`; use true or false"))?;
+        }
+        "reduce-flashing" | "screen-shake" | "large-text" | "shape-markers" => {
+            let enabled = value
+                .parse()
+                .map_err(|_| format!("invalid {key} `{value}`; use true or false"))?;
+            let accessibility = &mut options.accessibility;
+
+            match key {
+                "reduce-flashing" => accessibility.reduce_flashing = enabled,
+                "screen-shake" => accessibility.screen_shake = enabled,
+                "large-text" => accessibility.large_text = enabled,
+                _ => accessibility.shape_markers = enabled,
+            }
+        }
+        "turbo" => {
+            options.turbo = Input::empty();
+
+            for name in value.split_whitespace().filter(|name| *name != "-") {
+                let button = Input::named(name).ok_or(format!("unknown button `{name}`"))?;
+                options.turbo.insert(button);
+            }
+        }
+        "turbo-rate" => {
+            let rate = value
+                .parse()
+                .ok()
+                .filter(|rate| (1..=Turbo::MAX_HZ).contains(rate))
+                .ok_or(format!(
+                    "invalid turbo rate `{value}`; use 1 to {}",
+                    Turbo::MAX_HZ
+                ))?;
+            options.turbo_rate = Some(rate);
+        }
+        "input-delay" => {
+            let delay = value
+                .parse()
+                .ok()
+                .filter(|delay| *delay <= MAX_DELAY)
+                .ok_or(format!(
+                    "invalid input delay `{value}`; use 0 to {MAX_DELAY}"
+                ))?;
+            options.input_delay = Some(delay);
+        }
+        #[cfg(feature = "leaderboard")]
+        "leaderboard" => options.leaderboard = Some(value.to_string()),
+        #[cfg(feature = "leaderboard")]
+        "leaderboard-key" => options.leaderboard_key = value.to_string(),
+        #[cfg(feature = "leaderboard")]
+        "player" => {
+            use bit_bound::runtime::leaderboard::RunResult;
+
+            options.player = RunResult::clean_name(value);
+
+            if options.player.is_empty() {
+                return Err(format!("invalid player name `{value}`"));
+            }
+        }
+        _ => return Err(format!("unknown setting `{key}`")),
+    }
+
+    Ok(())
+}
+
+/// Read `CONFIG_FILE`, then the command line, which overrides it.
+fn parse_args() -> Result<Options, String> {
+    let settings =
+        Config::load(Path::new(CONFIG_FILE)).map_err(|err| format!("{CONFIG_FILE}: {err}"))?;
+    let mut options = Options {
+        settings: settings.clone(),
+        rumble: true,
+        ..Options::default()
+    };
+
+    for (key, value) in settings.entries() {
+        set_option(&mut options, key, value).map_err(|err| format!("{CONFIG_FILE}: {err}"))?;
+    }
+
+    let mut args = std::env::args().skip(1);
+    let mut seeded = false;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("`{arg}` needs a value"));
+
+        match arg.as_str() {
+            "--level" | "--backend" | "--scale" | "--window-position" | "--palette"
+            | "--language" | "--pacing" | "--tick-rate" | "--input-delay" | "--turbo"
+            | "--turbo-rate" => {
+                let key = &arg[2..];
+                let value = value()?;
+                set_option(&mut options, key, &value)?;
+                options.settings.set(key, &value);
+            }
+            #[cfg(feature = "sdl2")]
+            "--fullscreen" => {
+                set_option(&mut options, "fullscreen", "true")?;
+                options.settings.set("fullscreen", "true");
+            }
+            #[cfg(feature = "leaderboard")]
+            "--leaderboard" | "--leaderboard-key" | "--player" => {
+                let key = &arg[2..];
+                let value = value()?;
+                set_option(&mut options, key, &value)?;
+                options.settings.set(key, &value);
+            }
+            #[cfg(feature = "leaderboard")]
+            "--scores" => options.scores = true,
+            "--reduce-flashing" | "--large-text" | "--shape-markers" => {
+                set_option(&mut options, &arg[2..], "true")?;
+                options.settings.set(&arg[2..], "true");
+            }
+            "--no-screen-shake" => {
+                set_option(&mut options, "screen-shake", "false")?;
+                options.settings.set("screen-shake", "false");
+            }
+            "--no-rumble" => {
+                set_option(&mut options, "rumble", "false")?;
+                options.settings.set("rumble", "false");
+            }
+            "--interpolate" | "--run-in-background" | "--threaded-present" | "--timer" => {
+                set_option(&mut options, &arg[2..], "true")?;
+                options.settings.set(&arg[2..], "true");
+            }
+            "--save-config" => options.save_config = true,
+            "--skip-title" => options.skip_title = true,
+            "--host" => options.host = Some(value()?),
+            "--join" => options.join = Some(value()?),
+            "--headless" => {
+                let frames = value()?;
+                let frames = frames
+                    .parse()
+                    .map_err(|_| format!("invalid frame count `{frames}`"))?;
+                options.headless = Some(frames);
+            }
+            "--wav" => options.wav = Some(value()?.into()),
+            "--soak" => {
+                let frames = value()?;
+                let frames = frames
+                    .parse()
+                    .map_err(|_| format!("invalid frame count `{frames}`"))?;
+                options.soak = Some(frames);
+            }
+            "--seed" => {
+                let seed = value()?;
+                options.seed = seed.parse().map_err(|_| format!("invalid seed `{seed}`"))?;
+                seeded = true;
+            }
+            "--daily" => options.daily = Some(Date::today()),
+            "--input" => options.input = Some(value()?.into()),
+            "--record" => options.record = Some(value()?.into()),
+            "--splits" => options.splits = Some(value()?.into()),
+            "--replay" => options.replay = Some(value()?.into()),
+            "--ghost" => options.ghost = Some(value()?.into()),
+            "--record-ghost" => options.record_ghost = Some(value()?.into()),
+            #[cfg(feature = "debug_server")]
+            "--debug-server" => options.debug_server = Some(value()?),
+            _ => return Err(format!("unknown argument `{arg}`")),
+        }
+    }
+
+    if options.wav.is_some() && options.headless.is_none() {
+        return Err("`--wav` needs `--headless`".to_string());
+    }
+
+    #[cfg(feature = "sdl2")]
+    if options.fullscreen && options.backend != Backend::Sdl2 {
+        return Err("`--fullscreen` needs `--backend sdl2`".to_string());
+    }
+
+    if options.host.is_some() || options.join.is_some() {
+        if options.host.is_some() && options.join.is_some() {
+            return Err("`--host` and `--join` cannot be combined".to_string());
+        }
+
+        if options.headless.is_some() || options.soak.is_some() || options.input.is_some() {
+            return Err(
+                "netplay needs live input; drop `--headless`, `--soak` and `--input`".to_string(),
+            );
+        }
+
+        // A script holds one seat's input, so it cannot replay a co-op run.
+        if options.record.is_some() {
+            return Err("`--record` saves one player's input; drop it for netplay".to_string());
+        }
+
+        // The replay hash would cover one seat, so the run could not be checked.
+        #[cfg(feature = "leaderboard")]
+        if options.leaderboard.is_some() && !options.scores {
+            println!("netplay runs are not submitted to the leaderboard");
+            options.leaderboard = None;
+        }
+    }
+
+    if options.daily.is_some() {
+        if options.level.is_some()
+            || options.host.is_some()
+            || options.join.is_some()
+            || options.headless.is_some()
+            || options.soak.is_some()
+        {
+            return Err(
+                "`--daily` plays its own wave; drop `--level`, netplay, `--headless` and `--soak`"
+                    .to_string(),
+            );
+        }
+
+        // `--seed` picks another day, to replay its run.
+        if seeded {
+            options.daily = Some(Date::from_seed(options.seed).ok_or(format!(
+                "invalid daily seed `{}`; use yyyymmdd",
+                options.seed
+            ))?);
+        }
+
+        options.seed = options.daily.map_or(0, Date::seed);
+
+        // Everyone plays the day's wave at the same speed.
+        if options.tick_rate != TickRate::Hz60 {
+            println!("the daily challenge runs at 60 Hz");
+            options.tick_rate = TickRate::Hz60;
+        }
+    }
+
+    #[cfg(feature = "leaderboard")]
+    if options.scores && options.leaderboard.is_none() {
+        return Err("`--scores` needs `--leaderboard <url>`".to_string());
+    }
+
+    Ok(options)
+}
+
+/// Whether `--daily` is on and its time is up.
+fn daily_over(options: &Options, state: &GameState) -> bool {
+    options.daily.is_some() && state.sim.frame_counter >= DAILY_TICKS
+}
+
+/// A new game on `options.level`, the daily wave with `--daily`, or the
+/// test wave.
+fn new_game(options: &Options) -> Result<GameState, Error> {
+    if let Some(date) = options.daily {
+        return Ok(GameState::with_seed(date.seed()));
+    }
+
+    match &options.level {
+        Some(path) => read_level(path, GameState::with_level),
+        None => Ok(GameState::new()),
+    }
+}
+
+/// Run `read` on the level file at `path`.
+fn read_level<T>(path: &Path, read: impl FnOnce(&Level<'_>) -> T) -> Result<T, Error> {
+    let data = std::fs::read(path).map_err(|err| Error::load("level", path, err))?;
+    let level = Level::new(&data)
+        .ok_or_else(|| Error::load("level", path, "not a level file of this version"))?;
+
+    Ok(read(&level))
+}
+
+/// The name a stage's clear is saved under: its level file's, without the
+/// extension.
+fn stage_name(path: &Path) -> Option<&str> {
+    path.file_stem()?.to_str()
+}
+
+/// The level files in `LEVELS_DIR`, in name order; none if it is missing.
+fn stage_files() -> Vec<PathBuf> {
+    let Ok(entries) = std::fs::read_dir(LEVELS_DIR) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|extension| extension == "lvl"))
+        .collect();
+
+    files.sort();
+    files
+}
+
+/// A practice stop: a stage, and whether it starts at its boss.
+type PracticeStop = (PathBuf, bool);
+
+/// A new game on a practice stop.
+fn practice_game((path, boss): &PracticeStop) -> Result<GameState, Error> {
+    read_level(path, |level| {
+        let mut state = GameState::with_level(level);
+
+        if *boss && let Some(stage) = &mut state.sim.stage {
+            stage.skip_to_boss();
+        }
+
+        state
+    })
+}
+
+/// The speedrun segment a stop is timed as: the stage's name, with
+/// `.boss` when it starts at the boss.
+fn segment_name((path, boss): &PracticeStop) -> String {
+    let name = stage_name(path).unwrap_or("stage");
+
+    match boss {
+        true => format!("{name}.boss"),
+        false => name.to_string(),
+    }
+}
+
+/// How the player left the title.
+enum TitleExit {
+    Quit,
+    Play,
+    /// Play these stops in order instead of the game.
+    Practice(Vec<PracticeStop>),
+}
+
+/// Open the window of the chosen backend, on a presenter thread with
+/// `threaded_present`. `sound` also opens the audio device.
+fn open_platform(options: &Options, title: &str, sound: bool) -> Result<Box<dyn Platform>, String> {
+    if options.threaded_present {
+        // Only what opening the window reads goes to the presenter thread.
+        let window = Options {
+            backend: options.backend,
+            #[cfg(feature = "sdl2")]
+            fullscreen: options.fullscreen,
+            scale: options.scale,
+            window_position: options.window_position,
+            pacing: options.pacing,
+            ..Options::default()
+        };
+        let title = title.to_string();
+
+        return Ok(Box::new(ThreadedPlatform::spawn(move || {
+            open_platform(&window, &title, sound)
+        })?));
+    }
+
+    match options.backend {
+        Backend::Minifb => {
+            let mut platform =
+                MinifbPlatform::new(title, options.scale.unwrap_or(4), options.window_position)?;
+
+            if sound {
+                platform.open_audio();
+            }
+
+            Ok(Box::new(platform))
+        }
+        #[cfg(feature = "sdl2")]
+        Backend::Sdl2 => {
+            let mut sdl_options = SdlOptions {
+                fullscreen: options.fullscreen,
+                vsync: options.pacing == Pacing::Vsync,
+                position: options.window_position,
+                ..SdlOptions::default()
+            };
+
+            if let Some(scale) = options.scale {
+                sdl_options.scale = scale;
+            }
+
+            Ok(Box::new(SdlPlatform::new(title, sdl_options)?))
+        }
+        #[cfg(feature = "terminal")]
+        Backend::Terminal => Ok(Box::new(TerminalPlatform::new()?)),
+    }
+}
+
+/// The pacer for `options.pacing`, falling back to `Pacing::Spin` if the
+/// backend cannot vsync.
+fn frame_pacer(options: &Options, platform: &dyn Platform) -> FramePacer {
+    if options.pacing == Pacing::Vsync && !platform.vsync() {
+        bit_bound::log_warn!(
+            "the {:?} backend has no vsync; pacing with spin",
+            options.backend
+        );
+        return FramePacer::new(Pacing::Spin, options.tick_rate);
+    }
+
+    FramePacer::new(options.pacing, options.tick_rate)
+}
+
+/// Remember the window's size, place and fullscreen state in `CONFIG_FILE`
+/// for the next run, leaving the file's other settings as they are.
+fn save_window(platform: &dyn Platform) {
+    let Some(WindowState {
+        scale,
+        position: (x, y),
+        fullscreen,
+    }) = platform.window_state()
+    else {
+        return;
+    };
+
+    let path = Path::new(CONFIG_FILE);
+    let mut config = match Config::load(path) {
+        Ok(config) => config,
+        Err(err) => {
+            bit_bound::log_warn!("cannot read {CONFIG_FILE}: {err}");
+            return;
+        }
+    };
+    let before = config.clone();
+
+    config.set("scale", &scale.to_string());
+    config.set("window-position", &format!("{x},{y}"));
+
+    if let Some(fullscreen) = fullscreen {
+        config.set("fullscreen", &fullscreen.to_string());
+    }
+
+    if config != before
+        && let Err(err) = config.save(path)
+    {
+        bit_bound::log_warn!("cannot save {CONFIG_FILE}: {err}");
+    }
+}
+
+/// Apply the display settings: palette and accessibility.
+fn configure_framebuffer(buffer: &mut FrameBuffer, options: &Options) {
+    if let Some(palette) = options.palette {
+        buffer.set_palette(palette);
+    }
+
+    buffer.set_accessibility(options.accessibility);
+    buffer.set_extra_glyphs(options.language.glyphs());
+}
+
+/// Mark the game as paused, while its window is in the background or by
+/// Start. `photo_hint` adds the button that opens photo mode below.
+fn render_pause(buffer: &mut FrameBuffer, language: Language, photo_hint: bool) {
+    use bit_bound::input::{BUTTON_HEIGHT, button_width, draw_button};
+    use bit_bound::renderer::framebuffer::{HEIGHT, WIDTH};
+    use bit_bound::text::TextId;
+
+    let text = language.text(TextId::Paused);
+    let width = text.chars().count() * buffer.ui_font_advance();
+    let x = (WIDTH - width) / 2;
+    let y = (HEIGHT - buffer.ui_line_height()) / 2;
+
+    buffer.fill_rect(x - 2, y - 2, width + 3, buffer.ui_line_height() + 3, 0);
+    buffer.draw_rect(x - 2, y - 2, width + 3, buffer.ui_line_height() + 3, 3);
+    buffer.draw_ui_text(x, y, text, 3);
+
+    if photo_hint {
+        let text = language.text(TextId::Photo);
+        let cap = button_width(Input::SELECT);
+        let width = cap + 3 + text.chars().count() * buffer.ui_font_advance();
+        let x = (WIDTH - width) / 2;
+        let y = y + buffer.ui_line_height() + 6;
+
+        buffer.fill_rect(x - 2, y - 2, width + 4, BUTTON_HEIGHT + 4, 0);
+        draw_button(buffer, x, y, Input::SELECT);
+        buffer.draw_ui_text(x + cap + 3, y + 2, text, 3);
+    }
+}
+
+/// Save `buffer` as `photo-<unix time>.png`, each pixel `scale` wide.
+fn save_photo(buffer: &FrameBuffer, scale: usize) -> std::io::Result<PathBuf> {
+    use bit_bound::renderer::png::write_png_scaled;
+    use std::io::Write;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let path = PathBuf::from(format!("photo-{time}.png"));
+    let mut out = BufWriter::new(File::create(&path)?);
+
+    write_png_scaled(buffer, scale, &mut out)?;
+    out.flush()?;
+    Ok(path)
+}
+
+/// Present `buffer`, dimmed while the pacer asks for it, and tell the pacer
+/// whether the frame changed.
+fn present_paced(
+    platform: &mut dyn Platform,
+    buffer: &mut FrameBuffer,
+    pacer: &mut FramePacer,
+    dirty: &mut DirtyCheck,
+) {
+    let palette = *buffer.palette();
+
+    if pacer.dimmed() {
+        buffer.set_palette(dim_palette(palette));
+    }
+
+    platform.present(buffer);
+    pacer.note_frame(dirty.changed(buffer));
+    buffer.set_palette(palette);
+}
+
+/// Replay viewer. Space pauses, Left/Right step one tick, Up/Down seek a
+/// second, Home/End jump to either end and clicking the timeline seeks
+/// there. Escape quits. Recordings do not say which level they were made
+/// on, so the viewer always plays the test wave.
+fn run_replay(
+    platform: &mut dyn Platform,
+    script: InputScript,
+    mut pacer: FramePacer,
+    options: &Options,
+) -> Result<(), Error> {
+    use bit_bound::renderer::framebuffer::HEIGHT;
+    use bit_bound::runtime::replay::{ReplayPlayer, TIMELINE_HEIGHT};
+
+    let Runtime {
+        memory,
+        framebuffer: buffer,
+    } = Runtime::take().ok_or(Error::RuntimeClaimed)?;
+
+    configure_framebuffer(buffer, options);
+
+    let mut player = ReplayPlayer::new(script);
+    let mut dirty = DirtyCheck::new();
+
+    while platform.is_open() && !platform.is_key_down(Key::Escape) {
+        let ticks = pacer.ticks_due(platform.now_us());
+
+        if platform.is_key_pressed(Key::Space, false) {
+            player.set_paused(!player.is_paused());
+        }
+
+        if platform.is_key_pressed(Key::Right, true) {
+            player.step_forward(memory);
+        } else if platform.is_key_pressed(Key::Left, true) {
+            player.step_back(memory);
+        } else if platform.is_key_pressed(Key::Up, true) {
+            player.seek(player.frame() + 60, memory);
+        } else if platform.is_key_pressed(Key::Down, true) {
+            player.seek(player.frame().saturating_sub(60), memory);
+        } else if platform.is_key_pressed(Key::Home, false) {
+            player.seek(0, memory);
+        } else if platform.is_key_pressed(Key::End, false) {
+            player.seek(player.length(), memory);
+        } else if let Some((x, y)) = platform.pointer_down()
+            && y >= HEIGHT - TIMELINE_HEIGHT
+        {
+            player.seek(player.frame_at_x(x), memory);
+        } else {
+            for _ in 0..ticks {
+                player.advance(memory);
+            }
+        }
+
+        render(player.state(), buffer);
+        player.render_timeline(buffer, options.language);
+        platform.present(buffer);
+        pacer.note_frame(dirty.changed(buffer));
+        pacer.wait(platform);
+    }
+
+    Ok(())
 }
 
-unsafe impl<T> Sync for Global<T> {}
+/// Title screen, until Start or a practice run is chosen. Practice lists
+/// the stages `save` has cleared, High Scores opens the leaderboard viewer
+/// when a server is configured, Stats shows the save's stats.
+fn run_title(
+    platform: &mut dyn Platform,
+    buffer: &mut FrameBuffer,
+    pacer: &mut FramePacer,
+    options: &Options,
+    save: &SaveData,
+) -> TitleExit {
+    use bit_bound::game::title::{TitleChoice, TitleScreen};
+
+    #[cfg(feature = "leaderboard")]
+    let client = leaderboard_client(options);
+
+    #[cfg(feature = "leaderboard")]
+    let high_scores = client.is_some();
+
+    #[cfg(not(feature = "leaderboard"))]
+    let high_scores = false;
+
+    // The daily wave is the run; there is nothing to practise instead.
+    let stages: Vec<(PathBuf, String, bool)> = match options.daily {
+        Some(_) => Vec::new(),
+        None => stage_files()
+            .into_iter()
+            .filter_map(|path| {
+                let name = stage_name(&path)?;
+
+                if !save.is_cleared(name) {
+                    return None;
+                }
+
+                let name = name.to_uppercase();
+                let boss = read_level(&path, |level| Stage::new(level).has_boss()).ok()?;
+                Some((path, name, boss))
+            })
+            .collect(),
+    };
+
+    let mut title = TitleScreen::new(*buffer.accessibility(), high_scores, !stages.is_empty());
+    let mut dirty = DirtyCheck::new();
 
-impl<T> Global<T> {
-    const fn new(value: T) -> Self {
-        Self {
-            inner: UnsafeCell::new(value),
+    pacer.set_scene(Scene::Menu);
+
+    while platform.is_open() {
+        let ticks = pacer.ticks_due(platform.now_us());
+        pacer.note_input(platform.poll_input());
+
+        for _ in 0..ticks {
+            match title.update(platform.poll_input()) {
+                Some(TitleChoice::Start) => return TitleExit::Play,
+                Some(TitleChoice::Practice) => {
+                    let plan = run_practice(platform, buffer, pacer, options.language, &stages);
+
+                    if let Some(plan) = plan {
+                        return TitleExit::Practice(plan);
+                    }
+                }
+                Some(TitleChoice::Stats) => {
+                    show_stats(platform, buffer, pacer, options.language, &save.stats);
+                }
+                #[cfg(feature = "leaderboard")]
+                Some(TitleChoice::HighScores) => {
+                    if let Some(client) = &client {
+                        show_scores(platform, buffer, client, pacer, options.language);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // The Options page edits these; the game keeps what it left.
+        buffer.set_accessibility(title.accessibility());
+        title.render(buffer, options.language);
+        present_paced(platform, buffer, pacer, &mut dirty);
+        pacer.wait(platform);
+    }
+
+    TitleExit::Quit
+}
+
+/// The practice stage select over the cleared stages in `stages`. Returns
+/// the stops to play, or `None` to go back to the title.
+fn run_practice(
+    platform: &mut dyn Platform,
+    buffer: &mut FrameBuffer,
+    pacer: &mut FramePacer,
+    language: Language,
+    stages: &[(PathBuf, String, bool)],
+) -> Option<Vec<PracticeStop>> {
+    use bit_bound::game::practice::{PracticeChoice, PracticeMenu, PracticeStage};
+
+    let list: Vec<PracticeStage<'_>> = stages
+        .iter()
+        .map(|(_, name, boss)| PracticeStage { name, boss: *boss })
+        .collect();
+    let mut menu = PracticeMenu::new();
+    let mut dirty = DirtyCheck::new();
+
+    pacer.set_scene(Scene::Menu);
+
+    while platform.is_open() {
+        let ticks = pacer.ticks_due(platform.now_us());
+        pacer.note_input(platform.poll_input());
+
+        for _ in 0..ticks {
+            match menu.update(platform.poll_input(), &list) {
+                Some(PracticeChoice::Stage { index, boss }) => {
+                    return Some(vec![(stages[index].0.clone(), boss)]);
+                }
+                Some(PracticeChoice::BossRush) => {
+                    let bosses = stages.iter().filter(|(_, _, boss)| *boss);
+                    return Some(bosses.map(|(path, _, _)| (path.clone(), true)).collect());
+                }
+                Some(PracticeChoice::Back) => return None,
+                None => {}
+            }
         }
+
+        menu.render(buffer, language, &list);
+        present_paced(platform, buffer, pacer, &mut dirty);
+        pacer.wait(platform);
     }
 
-    fn get(&self) -> &mut T {
-        unsafe { &mut *self.inner.get() }
+    None
+}
+
+/// The shop between practice stops, until Continue: spends `save`'s
+/// coins on its upgrades. A normal run is a single stage, so this is the
+/// only place it opens. Returns `false` if the window closed.
+fn run_shop(
+    platform: &mut dyn Platform,
+    buffer: &mut FrameBuffer,
+    pacer: &mut FramePacer,
+    language: Language,
+    save: &mut SaveData,
+) -> bool {
+    use bit_bound::game::shop::{ShopChoice, ShopMenu};
+
+    let mut menu = ShopMenu::new();
+    let mut dirty = DirtyCheck::new();
+
+    pacer.set_scene(Scene::Menu);
+
+    while platform.is_open() {
+        let ticks = pacer.ticks_due(platform.now_us());
+        pacer.note_input(platform.poll_input());
+
+        for _ in 0..ticks {
+            match menu.update(platform.poll_input()) {
+                Some(ShopChoice::Buy(upgrade)) => {
+                    // The menu dims what cannot be bought; pressing it
+                    // anyway does nothing.
+                    let _ = save.upgrades.buy(upgrade, &mut save.coins);
+                }
+                Some(ShopChoice::Continue) => return true,
+                None => {}
+            }
+        }
+
+        menu.render(buffer, language, &save.upgrades, save.coins);
+        present_paced(platform, buffer, pacer, &mut dirty);
+        pacer.wait(platform);
+    }
+
+    false
+}
+
+/// The lifetime stats, until Escape.
+fn show_stats(
+    platform: &mut dyn Platform,
+    buffer: &mut FrameBuffer,
+    pacer: &mut FramePacer,
+    language: Language,
+    stats: &Stats,
+) {
+    use bit_bound::game::stats::render_stats;
+
+    let mut dirty = DirtyCheck::new();
+
+    pacer.set_scene(Scene::Menu);
+
+    while platform.is_open() && !platform.is_key_down(Key::Escape) {
+        pacer.ticks_due(platform.now_us());
+        pacer.note_input(platform.poll_input());
+
+        render_stats(buffer, language, stats);
+        present_paced(platform, buffer, pacer, &mut dirty);
+        pacer.wait(platform);
+    }
+}
+
+/// Leaderboard viewer for `--scores`.
+#[cfg(feature = "leaderboard")]
+fn run_scores(
+    platform: &mut dyn Platform,
+    client: &bit_bound::runtime::leaderboard::LeaderboardClient,
+    mut pacer: FramePacer,
+    options: &Options,
+) -> Result<(), Error> {
+    let Runtime {
+        framebuffer: buffer,
+        ..
+    } = Runtime::take().ok_or(Error::RuntimeClaimed)?;
+
+    configure_framebuffer(buffer, options);
+    show_scores(platform, buffer, client, &mut pacer, options.language);
+
+    Ok(())
+}
+
+/// Fetch the top scores once and show them. Up/Down scroll a row,
+/// PageUp/PageDown a screen, Escape leaves.
+#[cfg(feature = "leaderboard")]
+fn show_scores(
+    platform: &mut dyn Platform,
+    buffer: &mut FrameBuffer,
+    client: &bit_bound::runtime::leaderboard::LeaderboardClient,
+    pacer: &mut FramePacer,
+    language: Language,
+) {
+    use bit_bound::runtime::leaderboard::{VISIBLE_ROWS, render_leaderboard};
+    use bit_bound::text::TextId;
+
+    render_leaderboard(buffer, language, &[], 0, Some(TextId::LeaderboardLoading));
+    platform.present(buffer);
+
+    let (entries, status) = match client.top() {
+        Ok(entries) if entries.is_empty() => (entries, Some(TextId::LeaderboardEmpty)),
+        Ok(entries) => (entries, None),
+        Err(err) => {
+            bit_bound::log_warn!("cannot fetch leaderboard: {err}");
+            (Vec::new(), Some(TextId::LeaderboardUnreachable))
+        }
+    };
+
+    let mut scroll: usize = 0;
+    let last = entries.len().saturating_sub(VISIBLE_ROWS);
+    let mut dirty = DirtyCheck::new();
+
+    pacer.set_scene(Scene::Menu);
+
+    while platform.is_open() && !platform.is_key_down(Key::Escape) {
+        pacer.ticks_due(platform.now_us());
+        pacer.note_input(platform.poll_input());
+
+        if platform.is_key_pressed(Key::Down, true) {
+            scroll += 1;
+        } else if platform.is_key_pressed(Key::Up, true) {
+            scroll = scroll.saturating_sub(1);
+        } else if platform.is_key_pressed(Key::PageDown, true) {
+            scroll += VISIBLE_ROWS;
+        } else if platform.is_key_pressed(Key::PageUp, true) {
+            scroll = scroll.saturating_sub(VISIBLE_ROWS);
+        }
+
+        scroll = scroll.min(last);
+
+        render_leaderboard(buffer, language, &entries, scroll, status);
+        present_paced(platform, buffer, pacer, &mut dirty);
+        pacer.wait(platform);
+    }
+}
+
+/// The leaderboard client for `options`, if a server is configured.
+#[cfg(feature = "leaderboard")]
+fn leaderboard_client(
+    options: &Options,
+) -> Option<bit_bound::runtime::leaderboard::LeaderboardClient> {
+    use bit_bound::runtime::leaderboard::{LeaderboardClient, QUEUE_FILE};
+
+    let url = options.leaderboard.as_deref()?;
+    Some(LeaderboardClient::new(
+        url,
+        &options.leaderboard_key,
+        Path::new(QUEUE_FILE),
+    ))
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{err}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(&options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{err}");
+
+            // Scripted and headless runs are tests: nobody is there to
+            // press a button.
+            let watched = options.input.is_none() && options.headless.is_none();
+
+            if watched && err.has_screen() {
+                show_error(&options, &err);
+            }
+
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Show `error` in a window of its own until a button or Escape is
+/// pressed, if a window opens.
+fn show_error(options: &Options, error: &Error) {
+    use bit_bound::runtime::error::render_error;
+
+    let Ok(mut platform) = open_platform(options, "BitBound", false) else {
+        return;
+    };
+    // The game's framebuffer is gone with the run.
+    let mut buffer = Box::new(FrameBuffer::new());
+    let mut pacer = frame_pacer(options, platform.as_ref());
+    let mut dirty = DirtyCheck::new();
+    // Buttons still held from the game do not count.
+    let mut held = platform.poll_input();
+
+    configure_framebuffer(&mut buffer, options);
+    pacer.set_scene(Scene::Menu);
+
+    while platform.is_open() && !platform.is_key_down(Key::Escape) {
+        pacer.ticks_due(platform.now_us());
+
+        let input = platform.poll_input();
+
+        if input.bits() & !held.bits() != 0 {
+            break;
+        }
+
+        held = input;
+        pacer.note_input(input);
+
+        render_error(&mut buffer, options.language, error);
+        present_paced(platform.as_mut(), &mut buffer, &mut pacer, &mut dirty);
+        pacer.wait(platform.as_mut());
     }
 }
 
-static MEMORY: Global<RuntimeMemory> = Global::new(RuntimeMemory::new());
-static FRAMEBUFFER: Global<FrameBuffer> = Global::new(FrameBuffer::new());
+/// Play the run `options` asks for.
+fn run(options: &Options) -> Result<(), Error> {
+    if options.save_config {
+        let path = Path::new(CONFIG_FILE);
 
-fn main() {
-    let memory = MEMORY.get();
-    let buffer = FRAMEBUFFER.get();
+        options
+            .settings
+            .save(path)
+            .map_err(|err| Error::save("", path, err))?;
+        println!("settings saved to {CONFIG_FILE}");
+    }
+
+    if let Some(path) = &options.replay {
+        let script = InputScript::load(path).map_err(|err| Error::load("replay", path, err))?;
+        let mut platform =
+            open_platform(options, "BitBound Replay", false).map_err(Error::Window)?;
+        let pacer = frame_pacer(options, platform.as_ref());
+
+        return run_replay(platform.as_mut(), script, pacer, options);
+    }
+
+    #[cfg(feature = "leaderboard")]
+    if options.scores
+        && let Some(client) = leaderboard_client(options)
+    {
+        let mut platform =
+            open_platform(options, "BitBound Leaderboard", false).map_err(Error::Window)?;
+        let pacer = frame_pacer(options, platform.as_ref());
+
+        return run_scores(platform.as_mut(), &client, pacer, options);
+    }
+
+    let script = match &options.input {
+        Some(path) => {
+            Some(InputScript::load(path).map_err(|err| Error::load("input script", path, err))?)
+        }
+        None => None,
+    };
+
+    let ghost_data = match &options.ghost {
+        Some(path) => std::fs::read(path).map_err(|err| Error::load("ghost", path, err))?,
+        None => Vec::new(),
+    };
+
+    let mut ghost = match &options.ghost {
+        Some(path) => match Ghost::new(&ghost_data) {
+            Some(ghost) => Some(ghost.playback()),
+            None => return Err(Error::load("ghost", path, "not a ghost of this version")),
+        },
+        None => None,
+    };
+
+    let mut state = new_game(options)?;
+
+    state.set_tick_rate(options.tick_rate);
+
+    crash::install(".");
+
+    let Runtime {
+        memory,
+        framebuffer: buffer,
+    } = Runtime::take().ok_or(Error::RuntimeClaimed)?;
+
+    configure_framebuffer(buffer, options);
+
+    if let Some(frames) = options.soak {
+        let report = soak::run(memory, frames, options.seed);
+        println!("{report}");
+
+        if report.violation.is_none() {
+            return Ok(());
+        }
+
+        let path = PathBuf::from(format!("soak-{}.txt", options.seed));
+
+        match report.input.save(&path) {
+            Ok(()) => println!("input saved to {}; replay with --input", path.display()),
+            Err(err) => eprintln!("{}", Error::save("input script", &path, err)),
+        }
+
+        return Err(Error::Soak { seed: options.seed });
+    }
+
+    let mut ghost_recording = options
+        .record_ghost
+        .as_ref()
+        .map(|_| GhostRecorder::new(&state));
+
+    if let Some(frames) = options.headless {
+        let mut audio = options
+            .wav
+            .as_ref()
+            .map(|_| OfflineAudio::new(DEFAULT_SAMPLE_RATE));
+        let summary = headless::run(
+            memory,
+            state,
+            frames,
+            &script.unwrap_or_default(),
+            audio.as_mut(),
+            ghost_recording.as_mut(),
+        );
+        println!("{summary}");
+
+        if let (Some(path), Some(ghost)) = (&options.record_ghost, &ghost_recording) {
+            ghost
+                .save(path)
+                .map_err(|err| Error::save("ghost", path, err))?;
+        }
+
+        if let (Some(path), Some(audio)) = (&options.wav, &audio) {
+            File::create(path)
+                .and_then(|file| audio.write_wav(&mut BufWriter::new(file)))
+                .map_err(|err| Error::save("", path, err))?;
+        }
+
+        return Ok(());
+    }
+
+    let mut recording = InputScript::new();
+
+    // Ticks the player went unhit: the leaderboard and daily score until
+    // the game keeps one of its own.
+    let mut clean_ticks: u32 = 0;
 
     #[cfg(feature = "debug_overlay")]
     let mut last_frame_us = 0; // Used only for debugging
 
-    let mut state = GameState::new();
-    let mut window = Window::new(
-        "BitBound",
-        framebuffer::WIDTH,
-        framebuffer::HEIGHT,
-        WindowOptions {
-            resize: false,
-            scale: minifb::Scale::X4,
-            ..WindowOptions::default()
-        },
-    )
-    .unwrap();
+    #[cfg(feature = "debug_overlay")]
+    let mut memory_history = bit_bound::runtime::debug::MemoryHistory::new();
 
-    window.set_target_fps(60);
+    #[cfg(feature = "debug_overlay")]
+    let mut frame_times = bit_bound::runtime::debug::FrameTimeHistory::new();
+
+    #[cfg(feature = "debug_overlay")]
+    let mut debug_flags = bit_bound::runtime::debug::DebugFlags::empty();
 
-    let mut screen_buffer = vec![0u32; framebuffer::WIDTH * framebuffer::HEIGHT];
+    #[cfg(feature = "debug_overlay")]
+    let mut log_scroll: usize = 0;
+
+    #[cfg(feature = "debug_overlay")]
+    let mut step_control = bit_bound::runtime::debug::StepControl::new();
+
+    #[cfg(feature = "debug_overlay")]
+    let mut sim_speed = bit_bound::runtime::debug::SimSpeed::new();
+
+    #[cfg(feature = "debug_overlay")]
+    let mut sim_hash = bit_bound::runtime::debug::SimHash::new();
+
+    #[cfg(feature = "debug_overlay")]
+    let mut vram_viewer = bit_bound::runtime::vram::VramViewer::new();
+
+    // The frame debugger's recording (Home), taken of the frame after the
+    // one the key went down in.
+    #[cfg(feature = "debug_overlay")]
+    let mut record_frame = false;
+
+    #[cfg(feature = "debug_overlay")]
+    let mut frame_debugger: Option<bit_bound::runtime::framedebug::FrameDebugger> = None;
+
+    // Second screen for the debug panels while the inspector window is
+    // open (F1).
+    #[cfg(feature = "debug_overlay")]
+    let mut inspector: Option<Box<FrameBuffer>> = None;
+
+    #[cfg(feature = "debug_server")]
+    let mut debug_server = match options.debug_server.as_deref().map(DebugServer::bind) {
+        Some(Ok(server)) => {
+            if let Ok(addr) = server.local_addr() {
+                log_info!("debug server listening on {addr}");
+            }
+            Some(server)
+        }
+        Some(Err(err)) => return Err(Error::DebugServer(err.to_string())),
+        None => None,
+    };
+
+    let connected = match (&options.host, &options.join) {
+        (Some(addr), _) => {
+            println!("waiting for a partner on {addr}");
+            Some(Lockstep::host(
+                addr.as_str(),
+                options.input_delay.unwrap_or(DEFAULT_DELAY),
+            ))
+        }
+        (_, Some(addr)) => Some(Lockstep::join(addr.as_str())),
+        _ => None,
+    };
+
+    // The daily challenge is the same game for everyone: no turbo.
+    if options.daily.is_some() && options.turbo != Input::empty() {
+        println!("turbo is off in the daily challenge");
+    }
+
+    // Turbo changes live input only; scripts already hold what the game saw.
+    let turbo_on = options.turbo != Input::empty() && options.daily.is_none();
+    let mut turbo = (turbo_on && script.is_none()).then(|| {
+        Turbo::new(
+            options.turbo,
+            options.turbo_rate.unwrap_or(Turbo::DEFAULT_HZ),
+        )
+    });
+
+    let mut netplay = match connected {
+        Some(Ok(session)) => {
+            log_info!(
+                "netplay: seat {} with {:?}, {} ticks of input delay",
+                session.seat() + 1,
+                session.peer_addr(),
+                session.delay()
+            );
+            state.sim.add_partner();
+            Some(session)
+        }
+        Some(Err(err)) => return Err(Error::Netplay(format!("cannot connect: {err}"))),
+        None => None,
+    };
+
+    // Only live play counts towards the lifetime stats.
+    let live = script.is_none();
+
+    let mut save = SaveData::load(Path::new(SAVE_FILE))
+        .map_err(|err| Error::load("", Path::new(SAVE_FILE), err))?;
+
+    let frame_budget = OvercommitCheck::new(
+        "frame",
+        memory.frame.capacity() * FRAME_SOFT_LIMIT_PERCENT / 100,
+    );
+
+    let mut platform = open_platform(options, "BitBound", true).map_err(Error::Window)?;
+
+    let mut pacer = frame_pacer(options, platform.as_ref());
+
+    // Waiting pacings present right after their one tick, so there is
+    // nothing to draw in between.
+    let interpolate = options.interpolate && !pacer.pacing().waits();
+
+    if options.interpolate && !interpolate {
+        bit_bound::log_warn!("interpolation needs vsync or uncapped pacing; drawing ticks as is");
+    }
+
+    // Frames that keep missing the tick's deadline turn off optional
+    // drawing.
+    let mut quality = QualityWatchdog::new(pacer.rate().tick_us());
+
+    // The state before the newest tick, for interpolation.
+    let mut previous = state.sim;
+    let mut dirty = DirtyCheck::new();
+
+    log_info!(
+        "runtime started: {} KB memory",
+        bit_bound::runtime::memory::ActiveMemory::TOTAL / 1024
+    );
+
+    // Start pauses live games away from netplay, and Select while paused
+    // opens photo mode, which puts the game's palette back on leaving.
+    let pausable = live && netplay.is_none();
+    let mut paused = false;
+    let mut pause_held = Input::empty();
+    let mut photo: Option<(PhotoMode, [u32; 4])> = None;
+
+    // Only live play teaches; scripts already know what to press.
+    let mut tutorial = live.then(|| Tutorial::new(save.tutorial_seen()));
+
+    // Only live play rumbles, like it teaches.
+    let mut haptics = Haptics::new(live && options.rumble);
+
+    // Practice stops still to play after the current one.
+    let mut practice = VecDeque::new();
+
+    // Scripted and netplay runs start playing at once.
+    if !options.skip_title && script.is_none() && netplay.is_none() {
+        match run_title(platform.as_mut(), buffer, &mut pacer, options, &save) {
+            TitleExit::Quit => {
+                save_window(platform.as_ref());
+                return Ok(());
+            }
+            TitleExit::Play => {}
+            TitleExit::Practice(plan) => practice = plan.into(),
+        }
+    }
+
+    // Practice runs are not scores and do not clear stages, so they get
+    // the shop's upgrades; a recorded one plays without them, as its
+    // replay will. So does one recording a ghost, whose format moves the
+    // ship at most a pixel a tick and cannot follow a faster one.
+    let practising = !practice.is_empty();
+    let upgraded = practising && options.record.is_none() && options.record_ghost.is_none();
+
+    if practising && options.record_ghost.is_some() && !save.upgrades.is_none() {
+        println!("upgrades are off while recording a ghost");
+    }
+
+    // The stage being timed, and whether its split is taken.
+    let mut segment = match &options.level {
+        Some(path) => segment_name(&(path.clone(), false)),
+        None => "wave".to_string(),
+    };
+    let mut split_taken = false;
+    let mut timer = (live && (options.timer || options.splits.is_some())).then(SpeedrunTimer::new);
+    let mut segments = Vec::new();
+
+    if let Some(stop) = practice.pop_front() {
+        state = practice_game(&stop)?;
+        state.set_tick_rate(options.tick_rate);
+
+        if upgraded {
+            state.sim.upgrades = save.upgrades;
+        }
+
+        previous = state.sim;
+        segment = segment_name(&stop);
+    }
+
+    if live {
+        save.stats.start_game();
+    }
+
+    state.open_on_player();
 
     loop {
-        let frame_start = Instant::now();
+        let frame_start = platform.now_us();
+
+        #[cfg(feature = "profiler")]
+        bit_bound::runtime::profile::begin_frame();
+
+        log::global().set_frame(state.sim.frame_counter);
+
+        let due = pacer.ticks_due(frame_start);
+
+        // Lockstep cannot pause one side, so netplay keeps running.
+        let background = !options.run_in_background && netplay.is_none() && !platform.has_focus();
+        let mut capture = false;
+
+        if pausable {
+            let buttons = platform.poll_input();
+            let pressed = Input::from_bits(buttons.bits() & !pause_held.bits());
+
+            pause_held = buttons;
+
+            match &mut photo {
+                Some((mode, palette)) => match mode.update(buttons) {
+                    PhotoAction::Capture => capture = true,
+                    PhotoAction::Leave => {
+                        buffer.set_palette(*palette);
+                        photo = None;
+                    }
+                    PhotoAction::None => {}
+                },
+                None if pressed.contains(Input::START) => paused ^= true,
+                None if paused && pressed.contains(Input::SELECT) => {
+                    photo = Some((PhotoMode::new(buffer.palette()), *buffer.palette()));
+                }
+                None => {}
+            }
+
+            pacer.note_input(buttons);
+        }
+
+        // Pause and the background hold still; photo mode moves the view.
+        pacer.set_scene(if (paused && photo.is_none()) || background {
+            Scene::Paused
+        } else {
+            Scene::Play
+        });
+
+        #[cfg(feature = "debug_overlay")]
+        let ticks = if background || paused {
+            0
+        } else if step_control.is_paused() {
+            step_control.should_tick() as u32
+        } else {
+            (0..due).map(|_| sim_speed.advance()).sum()
+        };
+
+        #[cfg(not(feature = "debug_overlay"))]
+        let ticks = if background || paused { 0 } else { due };
+
+        for tick in 0..ticks {
+            if daily_over(options, &state) {
+                break;
+            }
+
+            // The frame arena is reset once per tick; the last tick's usage
+            // is left for the overlay and reset at the end of the frame.
+            if tick > 0 {
+                frame_budget.check(&memory.frame);
+                memory.frame.reset();
+            }
+
+            // Turbo advances on a copy, kept once the tick is known to run.
+            let (input, turbo_after) = match (&script, turbo) {
+                (Some(script), _) => (script.at(state.sim.frame_counter), None),
+                (None, Some(mut turbo)) => (turbo.apply(platform.poll_input()), Some(turbo)),
+                (None, None) => (platform.poll_input(), None),
+            };
+
+            // In netplay, a tick waits for the partner's input; the frame
+            // is drawn again meanwhile.
+            let inputs = match &mut netplay {
+                Some(session) => match session.inputs(state.sim.frame_counter, input) {
+                    Ok(Some(inputs)) => inputs,
+                    Ok(None) => break,
+                    Err(err) => return Err(Error::Netplay(err.to_string())),
+                },
+                None => [input, Input::empty()],
+            };
+
+            if turbo_after.is_some() {
+                turbo = turbo_after;
+            }
+
+            // Always kept: the leaderboard submits its hash.
+            recording.push(state.sim.frame_counter, inputs[0]);
 
-        update(&mut state, memory);
-        render(&state, buffer);
+            crash::checkpoint(&state, memory);
+            previous = state.sim;
+            let events = update_coop(&mut state, inputs, memory);
+
+            if live {
+                save.stats.record(&events);
+                save.coins = save.coins.saturating_add(coins_for(&events.collected));
+            }
+
+            haptics.update(&events);
+
+            if let Some(tutorial) = &mut tutorial {
+                tutorial.update(&state.sim, inputs[0]);
+            }
+
+            if let Some(timer) = &mut timer {
+                timer.tick();
+
+                if !split_taken && state.sim.stage_cleared() {
+                    let best = save.best_segment(&segment);
+
+                    split_taken = true;
+
+                    if let Some(split) = timer.split(best) {
+                        if best.is_none_or(|best| split.segment < best) {
+                            save.set_best_segment(&segment, split.segment);
+                        }
+
+                        segments.push(segment.clone());
+                    }
+                }
+            }
+
+            platform.submit_audio(state.apu.end_frame());
+
+            if let Some(session) = &mut netplay {
+                session.record_hash(state.sim.frame_counter, state.state_hash());
+
+                if let Some(frame) = session.desync() {
+                    bit_bound::log_error!("netplay desync at frame {frame}");
+                    return Err(Error::Desync { frame });
+                }
+            }
+
+            if let Some(ghost) = &mut ghost_recording {
+                ghost.record(&state);
+            }
+
+            // Any damage to the first ship, contact or bullet, spoils the tick.
+            let hit = events
+                .damage
+                .as_slice()
+                .iter()
+                .any(|dealt| dealt.target == DamageTarget::Ship(0));
+
+            if !hit {
+                clean_ticks += 1;
+            }
+
+            #[cfg(feature = "debug_overlay")]
+            sim_hash.record(&state);
+        }
+
+        #[cfg(feature = "debug_overlay")]
+        {
+            buffer.reset_stats();
+
+            if record_frame {
+                buffer.start_recording();
+                record_frame = false;
+            }
+        }
+
+        if let Some((mode, _)) = &photo {
+            // The photo is the whole frame, with nothing drawn over it.
+            mode.render(&state, buffer);
+
+            if capture {
+                let scale = options.scale.unwrap_or(4) as usize;
+
+                match save_photo(buffer, scale) {
+                    Ok(path) => println!("photo saved to {}", path.display()),
+                    Err(err) => eprintln!("cannot save photo: {err}"),
+                }
+            }
+        } else {
+            if interpolate && quality.quality().interpolate() {
+                let progress_us = pacer.tick_progress_us(platform.now_us());
+                let alpha = (progress_us * ALPHA_ONE as u64 / pacer.rate().tick_us()) as u32;
+                render_interpolated(&state, &previous, alpha, buffer);
+            } else {
+                render(&state, buffer);
+            }
+
+            if quality.quality().ghost()
+                && let Some(position) = ghost
+                    .as_mut()
+                    .and_then(|ghost| ghost.position_at(state.sim.frame_counter))
+            {
+                render_ghost(
+                    buffer,
+                    position,
+                    state.tick_rate.design_ticks(state.sim.frame_counter),
+                );
+            }
+
+            if let Some(turbo) = &turbo {
+                render_turbo(buffer, turbo, options.language);
+            }
+
+            if let Some(tutorial) = &tutorial {
+                tutorial.render(buffer, options.language);
+            }
+
+            if let Some(timer) = &timer {
+                timer.render(buffer, memory);
+            }
+
+            if background || paused {
+                render_pause(buffer, options.language, paused);
+            }
+        }
 
         #[cfg(feature = "debug_overlay")]
         {
-            use bit_bound::runtime::debug::{DebugInfo, render_debug_overlay};
+            use bit_bound::runtime::debug::{
+                DebugFlags, DebugInfo, render_collision_grid, render_debug_overlay,
+                render_entity_list, render_frame_graph, render_hitboxes, render_memory_panel,
+                render_pause_indicator, render_speed_indicator,
+            };
+            use bit_bound::runtime::framedebug::FrameDebugger;
+
+            if let Some(recording) = buffer.finish_recording() {
+                frame_debugger = Some(FrameDebugger::new(recording));
+            }
+
+            const TOGGLES: [(Key, DebugFlags); 7] = [
+                (Key::F2, DebugFlags::MEMORY_PANEL),
+                (Key::F3, DebugFlags::FRAME_GRAPH),
+                (Key::F4, DebugFlags::HITBOXES),
+                (Key::F5, DebugFlags::COLLISION_GRID),
+                (Key::F6, DebugFlags::LOG),
+                (Key::F8, DebugFlags::PROFILER),
+                (Key::Tab, DebugFlags::VRAM),
+            ];
+
+            for (key, flag) in TOGGLES {
+                if platform.is_key_pressed(key, false) {
+                    debug_flags.toggle(flag);
+                }
+            }
+
+            // F1 closes the inspector if it is open, and opens it if not.
+            if platform.is_key_pressed(Key::F1, false) && inspector.take().is_none() {
+                let mut panels = Box::new(FrameBuffer::new());
+                panels.set_palette(*buffer.palette());
+
+                if platform.present_inspector(&panels) {
+                    inspector = Some(panels);
+                } else {
+                    bit_bound::log_warn!(
+                        "the {:?} backend cannot open an inspector window",
+                        options.backend
+                    );
+                }
+            }
+
+            #[cfg(feature = "debug_server")]
+            if let Some(server) = &mut debug_server {
+                use bit_bound::runtime::remote::RemoteCommand;
+
+                server.poll(|command| match command {
+                    RemoteCommand::Pause => step_control.set_paused(true),
+                    RemoteCommand::Resume => step_control.set_paused(false),
+                    RemoteCommand::Step(count) => {
+                        step_control.set_paused(true);
+                        step_control.step_by(count);
+                    }
+                    RemoteCommand::SpeedSlower => sim_speed.slower(),
+                    RemoteCommand::SpeedFaster => sim_speed.faster(),
+                    RemoteCommand::SpeedNormal => sim_speed.reset(),
+                    RemoteCommand::Toggle(flag) => debug_flags.toggle(flag),
+                    RemoteCommand::FlushLog => {
+                        let flushed = log::global().flush_to_file(Path::new(LOG_FILE));
+
+                        if let Err(err) = flushed {
+                            bit_bound::log_warn!("log flush failed: {err}");
+                        }
+                    }
+                });
+            }
+
+            if platform.is_key_pressed(Key::F10, false) {
+                step_control.toggle_pause();
+            }
+
+            if platform.is_key_pressed(Key::F11, true) {
+                step_control.step();
+            }
+
+            if platform.is_key_pressed(Key::Minus, false) {
+                sim_speed.slower();
+            }
+
+            if platform.is_key_pressed(Key::Equal, false) {
+                sim_speed.faster();
+            }
+
+            if platform.is_key_pressed(Key::Key0, false) {
+                sim_speed.reset();
+            }
+
+            // Recording pauses the simulation, so the frame stays as
+            // recorded while it is stepped through.
+            if platform.is_key_pressed(Key::Home, false) {
+                record_frame = true;
+                step_control.set_paused(true);
+            }
+
+            if platform.is_key_pressed(Key::End, false) {
+                frame_debugger = None;
+            }
+
+            if let Some(debugger) = &mut frame_debugger {
+                for (key, delta) in [
+                    (Key::Left, -1),
+                    (Key::Right, 1),
+                    (Key::Up, -10),
+                    (Key::Down, 10),
+                ] {
+                    if platform.is_key_pressed(key, true) {
+                        debugger.step(delta);
+                    }
+                }
+            }
+
+            if platform.is_key_pressed(Key::PageUp, true) {
+                log_scroll += 1;
+            }
+
+            if platform.is_key_pressed(Key::PageDown, true) {
+                log_scroll = log_scroll.saturating_sub(1);
+            }
+
+            if platform.is_key_pressed(Key::F7, false) {
+                let flushed = log::global().flush_to_file(Path::new(LOG_FILE));
+
+                if let Err(err) = flushed {
+                    bit_bound::log_warn!("log flush failed: {err}");
+                }
+            }
+
+            #[cfg(feature = "profiler")]
+            if platform.is_key_pressed(Key::F9, false) {
+                match bit_bound::runtime::profile::dump_chrome_trace(Path::new(TRACE_FILE)) {
+                    Ok(()) => log_info!("trace written to {TRACE_FILE}"),
+                    Err(err) => bit_bound::log_warn!("trace dump failed: {err}"),
+                }
+            }
+
+            if let Some(debugger) = &frame_debugger {
+                debugger.render(buffer);
+            } else if debug_flags.contains(DebugFlags::VRAM) {
+                if let Some((x, y)) = platform.pointer_down() {
+                    vram_viewer.select_at(x, y);
+                }
+
+                vram_viewer.render(buffer);
+            } else {
+                if debug_flags.contains(DebugFlags::COLLISION_GRID) {
+                    render_collision_grid(buffer, &state);
+                }
+
+                if debug_flags.contains(DebugFlags::HITBOXES) {
+                    render_hitboxes(buffer, &state);
+                }
+            }
 
             let info = DebugInfo {
                 frame_us: last_frame_us,
                 global_used: memory.global.used() as u32,
                 level_used: memory.level.used() as u32,
                 frame_used: memory.frame.used() as u32,
+                entities: state.sim.enemies.len() as u32,
+                sprites_drawn: buffer.stats().sprites,
+                sprites_culled: buffer.stats().culled,
+                pixels_written: buffer.stats().pixels,
+                collision_pairs: state.stats.collision_pairs,
+                sim_hash: sim_hash.value() as u32,
+                quality: quality.quality(),
             };
 
-            render_debug_overlay(buffer, &info);
+            #[cfg(feature = "debug_server")]
+            if let Some(server) = &mut debug_server {
+                server.send_frame(state.sim.frame_counter, &info, sim_hash.value());
+            }
+
+            bit_bound::runtime::bugreport::capture(state.sim.frame_counter, buffer, &info);
+
+            if platform.is_key_pressed(Key::F12, false) {
+                let dir = Path::new(bit_bound::runtime::bugreport::BUGREPORT_DIR);
+
+                match bit_bound::runtime::bugreport::write(dir, "manual report (F12)") {
+                    Ok(Some(path)) => log_info!("bug report written to {}", path.display()),
+                    Ok(None) => {}
+                    Err(err) => bit_bound::log_warn!("bug report failed: {err}"),
+                }
+            }
+
+            // With the inspector open, the panels go there and leave the
+            // game screen to the game.
+            let panels = match inspector.as_deref_mut() {
+                Some(panels) => {
+                    panels.clear(0);
+                    render_entity_list(panels, &state);
+                    panels
+                }
+                None => &mut *buffer,
+            };
+
+            render_debug_overlay(panels, &info, memory);
+
+            if debug_flags.contains(DebugFlags::MEMORY_PANEL) {
+                render_memory_panel(panels, &memory_history);
+            }
+
+            if debug_flags.contains(DebugFlags::FRAME_GRAPH) {
+                render_frame_graph(panels, &frame_times);
+            }
+
+            if debug_flags.contains(DebugFlags::LOG) {
+                let log = log::global();
+                log_scroll = log_scroll.min(log.len().saturating_sub(1));
+                log::render_log(panels, &log, 8, log_scroll);
+            }
+
+            #[cfg(feature = "profiler")]
+            if debug_flags.contains(DebugFlags::PROFILER) {
+                bit_bound::runtime::profile::render_profile(panels);
+            }
+
+            if let Some(panels) = &inspector
+                && !platform.present_inspector(panels)
+            {
+                inspector = None;
+            }
+
+            if step_control.is_paused() {
+                render_pause_indicator(buffer);
+            }
+
+            render_speed_indicator(buffer, &sim_speed);
         }
 
-        buffer.to_rgba_buffer(&mut screen_buffer);
+        // Paused and background games hold still.
+        let rumble = if paused || background {
+            Rumble::OFF
+        } else {
+            haptics.rumble()
+        };
 
-        window
-            .update_with_buffer(&screen_buffer, framebuffer::WIDTH, framebuffer::HEIGHT)
-            .unwrap();
+        platform.rumble(rumble);
+        present_paced(platform.as_mut(), buffer, &mut pacer, &mut dirty);
 
-        if !window.is_open() {
+        if !platform.is_open() || daily_over(options, &state) {
             break;
         }
 
+        // A cleared practice stage moves on to the next stop through the
+        // shop, and the last ends the run.
+        if practising && state.sim.stage_cleared() {
+            let Some(stop) = practice.pop_front() else {
+                break;
+            };
+
+            if !run_shop(
+                platform.as_mut(),
+                buffer,
+                &mut pacer,
+                options.language,
+                &mut save,
+            ) {
+                break;
+            }
+
+            state = practice_game(&stop)?;
+            state.set_tick_rate(options.tick_rate);
+
+            if upgraded {
+                state.sim.upgrades = save.upgrades;
+            }
+
+            previous = state.sim;
+            state.open_on_player();
+            segment = segment_name(&stop);
+            split_taken = false;
+        }
+
+        #[cfg(feature = "debug_overlay")]
+        memory_history.record(memory);
+
+        frame_budget.check(&memory.frame);
         memory.frame.reset();
 
-        let elapsed = frame_start.elapsed();
+        #[cfg(feature = "profiler")]
+        bit_bound::runtime::profile::end_frame();
+
+        let frame_us = platform.now_us() - frame_start;
+
+        if let Some(level) = quality.record(frame_us) {
+            bit_bound::log_info!("quality set to {} by frame times", level.name());
+        }
 
         #[cfg(feature = "debug_overlay")]
         {
-            last_frame_us = elapsed.as_micros() as u32;
+            last_frame_us = frame_us as u32;
+            frame_times.record(last_frame_us);
         }
 
-        if elapsed < FRAME_TIME {
-            std::thread::sleep(FRAME_TIME - elapsed);
+        // The pacer starts its schedule over once the backlog is dropped, so
+        // play resumes without fast-forwarding.
+        if background {
+            platform.sleep_us(1_000_000 / BACKGROUND_RATE);
+        } else {
+            pacer.wait(platform.as_mut());
         }
     }
+
+    if live {
+        save_window(platform.as_ref());
+    }
+
+    let mut log = log::global();
+
+    if log.worst().is_some_and(|level| level <= LogLevel::Warn) {
+        let _ = log.flush_to_file(Path::new(LOG_FILE));
+    }
+
+    drop(log);
+
+    recording.set_end(state.sim.frame_counter);
+
+    if let Some(path) = &options.record {
+        recording
+            .save(path)
+            .map_err(|err| Error::save("input script", path, err))?;
+    }
+
+    if let (Some(path), Some(ghost)) = (&options.record_ghost, &ghost_recording) {
+        ghost
+            .save(path)
+            .map_err(|err| Error::save("ghost", path, err))?;
+    }
+
+    // A finished daily run keeps its input, so the result can be checked.
+    if let Some(date) = options.daily
+        && daily_over(options, &state)
+    {
+        if live && options.record.is_none() {
+            let path = PathBuf::from(format!("daily-{}.txt", date.seed()));
+
+            recording
+                .save(&path)
+                .map_err(|err| Error::save("input script", &path, err))?;
+
+            println!("input saved to {}", path.display());
+        }
+
+        let result = DailyResult {
+            date,
+            score: clean_ticks,
+            replay_hash: bit_bound::runtime::hash::fnv1a(recording.to_string().as_bytes()),
+        };
+
+        println!("{result}");
+    }
+
+    // The game does not end on a clear, so a level counts as cleared if
+    // it is when the window closes.
+    if live
+        && !practising
+        && state.sim.stage_cleared()
+        && let Some(name) = options.level.as_deref().and_then(stage_name)
+    {
+        save.set_cleared(name);
+    }
+
+    if let Some(tutorial) = &tutorial {
+        save.set_tutorial_seen(tutorial.seen());
+    }
+
+    if let (Some(path), Some(timer)) = (&options.splits, &timer) {
+        use bit_bound::runtime::splits::{NamedSplit, to_lss};
+
+        let splits: Vec<NamedSplit<'_>> = segments
+            .iter()
+            .zip(timer.splits())
+            .map(|(name, &split)| NamedSplit { name, split })
+            .collect();
+        let category = if practising { "Practice" } else { "Any%" };
+
+        std::fs::write(path, to_lss(category, &splits))
+            .map_err(|err| Error::save("splits", path, err))?;
+    }
+
+    if live {
+        let path = Path::new(SAVE_FILE);
+
+        save.save(path).map_err(|err| Error::save("", path, err))?;
+    }
+
+    // Scripted runs are tests, not scores.
+    #[cfg(feature = "leaderboard")]
+    if script.is_none()
+        && !practising
+        && let Some(client) = leaderboard_client(options)
+    {
+        use bit_bound::runtime::leaderboard::{QUEUE_FILE, RunResult};
+
+        let result = RunResult {
+            name: match options.player.as_str() {
+                "" => "PLAYER".to_string(),
+                name => name.to_string(),
+            },
+            score: clean_ticks,
+            seed: options.seed,
+            frames: state.sim.frame_counter,
+            replay_hash: bit_bound::runtime::hash::fnv1a(recording.to_string().as_bytes()),
+        };
+
+        match client.submit(result) {
+            Ok(report) if report.queued > 0 => {
+                println!("{} result(s) queued for the leaderboard", report.queued)
+            }
+            Ok(_) => println!("score submitted"),
+            Err(err) => return Err(Error::save("", Path::new(QUEUE_FILE), err)),
+        }
+    }
+
+    Ok(())
 }
@@ -0,0 +1,191 @@
+//! Screen geometry: points and vectors in whole pixels (`Vec2i`), in
+//! sub-pixels for motion finer than a pixel a tick (`Vec2fx`), and
+//! rectangles (`Rect`).
+//!
+//! All integer, so the simulation stays the same on every target. Screen y
+//! grows downward.
+
+use core::ops::{Add, AddAssign, Neg, Sub, SubAssign};
+
+/// Sub-pixel units in a pixel of a `Vec2fx`.
+pub const FIXED_ONE: i32 = 256;
+
+/// A point or vector in whole pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Vec2i {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Vec2i {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// -1, 0 or 1 on each axis.
+    pub const fn signum(self) -> Self {
+        Self::new(self.x.signum(), self.y.signum())
+    }
+
+    /// Each axis clamped between `min`'s and `max`'s.
+    pub fn clamp(self, min: Self, max: Self) -> Self {
+        Self::new(self.x.clamp(min.x, max.x), self.y.clamp(min.y, max.y))
+    }
+
+    pub const fn to_fixed(self) -> Vec2fx {
+        Vec2fx::new(self.x * FIXED_ONE, self.y * FIXED_ONE)
+    }
+}
+
+/// A point or vector in 1/`FIXED_ONE` pixels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Vec2fx {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Vec2fx {
+    pub const ZERO: Self = Self::new(0, 0);
+
+    pub const fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    /// The whole pixel each axis falls in, rounding toward negative
+    /// infinity.
+    pub const fn to_pixels(self) -> Vec2i {
+        Vec2i::new(self.x.div_euclid(FIXED_ONE), self.y.div_euclid(FIXED_ONE))
+    }
+}
+
+macro_rules! vector_ops {
+    ($vector:ident) => {
+        impl Add for $vector {
+            type Output = Self;
+
+            fn add(self, other: Self) -> Self {
+                Self::new(self.x + other.x, self.y + other.y)
+            }
+        }
+
+        impl Sub for $vector {
+            type Output = Self;
+
+            fn sub(self, other: Self) -> Self {
+                Self::new(self.x - other.x, self.y - other.y)
+            }
+        }
+
+        impl Neg for $vector {
+            type Output = Self;
+
+            fn neg(self) -> Self {
+                Self::new(-self.x, -self.y)
+            }
+        }
+
+        impl AddAssign for $vector {
+            fn add_assign(&mut self, other: Self) {
+                *self = *self + other;
+            }
+        }
+
+        impl SubAssign for $vector {
+            fn sub_assign(&mut self, other: Self) {
+                *self = *self - other;
+            }
+        }
+    };
+}
+
+vector_ops!(Vec2i);
+vector_ops!(Vec2fx);
+
+/// An axis-aligned rectangle in whole pixels: the `w`×`h` pixels from
+/// (`x`, `y`).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+}
+
+impl Rect {
+    pub const fn new(x: i32, y: i32, w: i32, h: i32) -> Self {
+        Self { x, y, w, h }
+    }
+
+    /// A `size.x`×`size.y` rectangle with its top-left at `position`.
+    pub const fn at(position: Vec2i, size: Vec2i) -> Self {
+        Self::new(position.x, position.y, size.x, size.y)
+    }
+
+    pub const fn position(&self) -> Vec2i {
+        Vec2i::new(self.x, self.y)
+    }
+
+    pub const fn size(&self) -> Vec2i {
+        Vec2i::new(self.w, self.h)
+    }
+
+    /// Just past the right edge.
+    pub const fn right(&self) -> i32 {
+        self.x + self.w
+    }
+
+    /// Just past the bottom edge.
+    pub const fn bottom(&self) -> i32 {
+        self.y + self.h
+    }
+
+    /// The middle pixel; of two, the right or lower one.
+    pub const fn center(&self) -> Vec2i {
+        Vec2i::new(self.x + self.w / 2, self.y + self.h / 2)
+    }
+
+    /// Whether it covers no pixel.
+    pub const fn is_empty(&self) -> bool {
+        self.w <= 0 || self.h <= 0
+    }
+
+    /// The rectangle moved by `offset`.
+    pub const fn offset(&self, offset: Vec2i) -> Self {
+        Self::new(self.x + offset.x, self.y + offset.y, self.w, self.h)
+    }
+
+    /// Whether pixel `point` is inside.
+    pub const fn contains(&self, point: Vec2i) -> bool {
+        point.x >= self.x && point.x < self.right() && point.y >= self.y && point.y < self.bottom()
+    }
+
+    /// Whether every pixel of `other` is inside. An empty `other` is
+    /// inside anything.
+    pub const fn contains_rect(&self, other: &Rect) -> bool {
+        other.is_empty()
+            || (other.x >= self.x
+                && other.right() <= self.right()
+                && other.y >= self.y
+                && other.bottom() <= self.bottom())
+    }
+
+    /// Whether the two share a pixel.
+    pub const fn overlaps(&self, other: &Rect) -> bool {
+        self.x < other.right()
+            && other.x < self.right()
+            && self.y < other.bottom()
+            && other.y < self.bottom()
+    }
+
+    /// The pixels the two share, if any.
+    pub fn intersection(&self, other: &Rect) -> Option<Rect> {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = self.right().min(other.right());
+        let bottom = self.bottom().min(other.bottom());
+
+        (x < right && y < bottom).then(|| Rect::new(x, y, right - x, bottom - y))
+    }
+}
@@ -0,0 +1,210 @@
+//! SPI LCD output for embedded targets: pushes the framebuffer to an
+//! ST7789 or ILI9341 panel through `embedded-hal` 1.0, expanding each
+//! 2-bit pixel to RGB565.
+//!
+//! Only the presenter lives here; a board wires it to its own SPI bus,
+//! buttons and timer (see `boards/rp2040`). It needs neither `std` nor an
+//! allocator: pixels go out a row at a time from a stack buffer.
+
+use embedded_hal::delay::DelayNs;
+use embedded_hal::digital::OutputPin;
+use embedded_hal::spi::SpiDevice;
+
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, PALETTE, WIDTH};
+
+const SWRESET: u8 = 0x01;
+const SLPOUT: u8 = 0x11;
+const NORON: u8 = 0x13;
+const INVON: u8 = 0x21;
+const DISPON: u8 = 0x29;
+const CASET: u8 = 0x2a;
+const RASET: u8 = 0x2b;
+const RAMWR: u8 = 0x2c;
+const MADCTL: u8 = 0x36;
+const COLMOD: u8 = 0x3a;
+
+/// `COLMOD` argument for 16 bits per pixel.
+const RGB565: u8 = 0x55;
+
+/// The panel's colour before the first frame: the default darkest shade.
+const BLANK: [u8; 2] = rgb565(PALETTE[0]);
+
+/// `argb` as big-endian RGB565, the order the panels read it in.
+const fn rgb565(argb: u32) -> [u8; 2] {
+    let r = (argb >> 19) & 0x1f;
+    let g = (argb >> 10) & 0x3f;
+    let b = (argb >> 3) & 0x1f;
+
+    (((r << 11) | (g << 5) | b) as u16).to_be_bytes()
+}
+
+/// The panel's size in its chosen orientation, and how to set it up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LcdConfig {
+    pub width: u16,
+    pub height: u16,
+    /// `MADCTL` value: rotation, mirroring and RGB/BGR order.
+    pub madctl: u8,
+    /// Send `INVON`. Most ST7789 modules need it for the colours to come
+    /// out right.
+    pub invert: bool,
+}
+
+impl LcdConfig {
+    /// The square ST7789 module found on most 1.3" boards.
+    pub const ST7789_240X240: Self = Self {
+        width: 240,
+        height: 240,
+        madctl: 0x00,
+        invert: true,
+    };
+
+    /// An ILI9341 module in landscape, BGR.
+    pub const ILI9341_320X240: Self = Self {
+        width: 320,
+        height: 240,
+        madctl: 0x28,
+        invert: false,
+    };
+
+    /// The largest whole scale at which the framebuffer fits the panel.
+    pub fn scale(&self) -> usize {
+        let x = self.width as usize / WIDTH;
+        let y = self.height as usize / HEIGHT;
+
+        x.min(y).max(1)
+    }
+}
+
+/// Why a transfer to the panel failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LcdError<S, P> {
+    Spi(S),
+    /// Driving the data/command pin failed.
+    Pin(P),
+}
+
+/// An ST7789 or ILI9341 on `spi`, with `dc` selecting data (high) or
+/// command (low).
+pub struct SpiLcd<SPI, DC> {
+    spi: SPI,
+    dc: DC,
+    config: LcdConfig,
+}
+
+impl<SPI: SpiDevice, DC: OutputPin> SpiLcd<SPI, DC> {
+    pub fn new(spi: SPI, dc: DC, config: LcdConfig) -> Self {
+        Self { spi, dc, config }
+    }
+
+    /// Wake the panel, select RGB565 and clear it to the darkest shade.
+    /// Takes about 150 ms. Pulse the panel's reset pin first, if wired.
+    pub fn init(
+        &mut self,
+        delay: &mut impl DelayNs,
+    ) -> Result<(), LcdError<SPI::Error, DC::Error>> {
+        self.command(SWRESET, &[])?;
+        delay.delay_ms(150);
+        self.command(SLPOUT, &[])?;
+        delay.delay_ms(10);
+        self.command(COLMOD, &[RGB565])?;
+        self.command(MADCTL, &[self.config.madctl])?;
+
+        if self.config.invert {
+            self.command(INVON, &[])?;
+        }
+
+        self.command(NORON, &[])?;
+
+        let (width, height) = (self.config.width as usize, self.config.height as usize);
+        self.window(0, 0, width, height)?;
+        self.fill(BLANK, width * height)?;
+
+        self.command(DISPON, &[])
+    }
+
+    /// Draw `frame` centred on the panel at `LcdConfig::scale`.
+    pub fn present(&mut self, frame: &FrameBuffer) -> Result<(), LcdError<SPI::Error, DC::Error>> {
+        let scale = self.config.scale();
+        let (width, height) = (WIDTH * scale, HEIGHT * scale);
+        let x = (self.config.width as usize).saturating_sub(width) / 2;
+        let y = (self.config.height as usize).saturating_sub(height) / 2;
+
+        self.window(x, y, width, height)?;
+
+        // One source row holds `WIDTH` pixels; a scaled row goes out in
+        // `scale` buffers' worth, and is repeated `scale` times.
+        let mut row = [0u8; WIDTH * 2];
+        let palette = frame.palette().map(rgb565);
+
+        for py in 0..HEIGHT {
+            for _ in 0..scale {
+                for part in 0..scale {
+                    for (i, pixel) in row.chunks_exact_mut(2).enumerate() {
+                        let px = (part * WIDTH + i) / scale;
+                        pixel.copy_from_slice(&palette[frame.get_pixel(px, py) as usize]);
+                    }
+
+                    self.spi.write(&row).map_err(LcdError::Spi)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Hand the SPI device and pin back.
+    pub fn release(self) -> (SPI, DC) {
+        (self.spi, self.dc)
+    }
+
+    fn command(&mut self, command: u8, args: &[u8]) -> Result<(), LcdError<SPI::Error, DC::Error>> {
+        self.dc.set_low().map_err(LcdError::Pin)?;
+        self.spi.write(&[command]).map_err(LcdError::Spi)?;
+        self.dc.set_high().map_err(LcdError::Pin)?;
+
+        if !args.is_empty() {
+            self.spi.write(args).map_err(LcdError::Spi)?;
+        }
+
+        Ok(())
+    }
+
+    /// Set the `RAMWR` target to a `w`×`h` rectangle and start writing.
+    fn window(
+        &mut self,
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+    ) -> Result<(), LcdError<SPI::Error, DC::Error>> {
+        let [x0, x1, y0, y1] = [x, x + w - 1, y, y + h - 1].map(|v| (v as u16).to_be_bytes());
+
+        self.command(CASET, &[x0[0], x0[1], x1[0], x1[1]])?;
+        self.command(RASET, &[y0[0], y0[1], y1[0], y1[1]])?;
+        self.command(RAMWR, &[])
+    }
+
+    /// Write `count` pixels of `color` to the current window.
+    fn fill(
+        &mut self,
+        color: [u8; 2],
+        count: usize,
+    ) -> Result<(), LcdError<SPI::Error, DC::Error>> {
+        let mut buffer = [0u8; WIDTH * 2];
+
+        for pixel in buffer.chunks_exact_mut(2) {
+            pixel.copy_from_slice(&color);
+        }
+
+        let mut left = count * 2;
+
+        while left > 0 {
+            let n = left.min(buffer.len());
+            self.spi.write(&buffer[..n]).map_err(LcdError::Spi)?;
+            left -= n;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,246 @@
+//! Desktop backend: a minifb window scaled up to 8x, and the default audio
+//! device through cpal when the `audio` feature is on. Debug views can get
+//! a second window of their own (`present_inspector`).
+
+use std::time::{Duration, Instant};
+
+use ::minifb::{KeyRepeat, MouseButton, MouseMode, Scale, Window, WindowOptions};
+
+use crate::audio::RegisterFrame;
+use crate::platform::{Key, Platform, WindowState};
+use crate::renderer::framebuffer::{DirtyCheck, FrameBuffer, HEIGHT, WIDTH};
+
+pub struct MinifbPlatform {
+    window: Window,
+    /// The inspector window, once opened.
+    inspector: Option<Window>,
+    /// Window scale, for the inspector.
+    scale: Scale,
+    /// RGBA copy of the framebuffer handed to minifb.
+    screen: Vec<u32>,
+    /// Whether `screen` is out of date.
+    dirty: DirtyCheck,
+    /// Whether the window was active at the last `present`; minifb only
+    /// answers through `&mut`.
+    focused: bool,
+    start: Instant,
+    #[cfg(feature = "audio")]
+    audio: Option<crate::audio::output::AudioOutput>,
+}
+
+impl MinifbPlatform {
+    /// Open a window titled `title`, `scale` times the screen size: 1, 2,
+    /// 4 or 8, with its top-left corner at `position` if given. minifb
+    /// does not limit the frame rate; the loop's `FramePacer` does.
+    pub fn new(title: &str, scale: u32, position: Option<(i32, i32)>) -> Result<Self, String> {
+        let scale = match scale {
+            1 => Scale::X1,
+            2 => Scale::X2,
+            4 => Scale::X4,
+            8 => Scale::X8,
+            _ => return Err(format!("minifb cannot scale by {scale}; use 1, 2, 4 or 8")),
+        };
+
+        let mut window = Window::new(
+            title,
+            WIDTH,
+            HEIGHT,
+            WindowOptions {
+                resize: false,
+                scale,
+                ..WindowOptions::default()
+            },
+        )
+        .map_err(|err| err.to_string())?;
+
+        window.set_target_fps(0);
+
+        if let Some((x, y)) = position {
+            window.set_position(x as isize, y as isize);
+        }
+
+        Ok(Self {
+            window,
+            inspector: None,
+            scale,
+            screen: vec![0; WIDTH * HEIGHT],
+            dirty: DirtyCheck::new(),
+            focused: true,
+            start: Instant::now(),
+            #[cfg(feature = "audio")]
+            audio: None,
+        })
+    }
+
+    /// Play sound on the default output device. Without one, or without
+    /// the `audio` feature, the game runs silent.
+    pub fn open_audio(&mut self) {
+        #[cfg(feature = "audio")]
+        match crate::audio::output::AudioOutput::open() {
+            Ok(audio) => self.audio = Some(audio),
+            Err(err) => crate::log_warn!("no sound: {err}"),
+        }
+    }
+}
+
+fn minifb_key(key: Key) -> ::minifb::Key {
+    use ::minifb::Key as K;
+
+    match key {
+        Key::Up => K::Up,
+        Key::Down => K::Down,
+        Key::Left => K::Left,
+        Key::Right => K::Right,
+        Key::Z => K::Z,
+        Key::X => K::X,
+        Key::Enter => K::Enter,
+        Key::Backspace => K::Backspace,
+        Key::Escape => K::Escape,
+        Key::Space => K::Space,
+        Key::Tab => K::Tab,
+        Key::Home => K::Home,
+        Key::End => K::End,
+        Key::PageUp => K::PageUp,
+        Key::PageDown => K::PageDown,
+        Key::Minus => K::Minus,
+        Key::Equal => K::Equal,
+        Key::Key0 => K::Key0,
+        Key::F1 => K::F1,
+        Key::F2 => K::F2,
+        Key::F3 => K::F3,
+        Key::F4 => K::F4,
+        Key::F5 => K::F5,
+        Key::F6 => K::F6,
+        Key::F7 => K::F7,
+        Key::F8 => K::F8,
+        Key::F9 => K::F9,
+        Key::F10 => K::F10,
+        Key::F11 => K::F11,
+        Key::F12 => K::F12,
+    }
+}
+
+impl Platform for MinifbPlatform {
+    fn is_open(&self) -> bool {
+        self.window.is_open()
+    }
+
+    fn has_focus(&self) -> bool {
+        self.focused
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.window.is_key_down(minifb_key(key))
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: bool) -> bool {
+        let repeat = if repeat {
+            KeyRepeat::Yes
+        } else {
+            KeyRepeat::No
+        };
+
+        self.window.is_key_pressed(minifb_key(key), repeat)
+    }
+
+    fn pointer_down(&self) -> Option<(usize, usize)> {
+        if !self.window.get_mouse_down(MouseButton::Left) {
+            return None;
+        }
+
+        let (x, y) = self.window.get_mouse_pos(MouseMode::Discard)?;
+        Some((x as usize, y as usize))
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) {
+        if self.dirty.changed(frame) {
+            frame.to_rgba_buffer(&mut self.screen);
+        }
+
+        // Even an unchanged frame is handed over: minifb collects input
+        // events while presenting.
+        if let Err(err) = self.window.update_with_buffer(&self.screen, WIDTH, HEIGHT) {
+            crate::log_warn!("present failed: {err}");
+        }
+
+        self.focused = self.window.is_active();
+    }
+
+    fn window_state(&self) -> Option<WindowState> {
+        let (x, y) = self.window.get_position();
+
+        Some(WindowState {
+            // `new` only opens at these.
+            scale: match self.scale {
+                Scale::X1 => 1,
+                Scale::X2 => 2,
+                Scale::X8 => 8,
+                _ => 4,
+            },
+            position: (x as i32, y as i32),
+            fullscreen: None,
+        })
+    }
+
+    fn present_inspector(&mut self, frame: &FrameBuffer) -> bool {
+        if self.inspector.is_none() {
+            let options = WindowOptions {
+                resize: false,
+                scale: self.scale,
+                ..WindowOptions::default()
+            };
+
+            match Window::new("BitBound Inspector", WIDTH, HEIGHT, options) {
+                Ok(window) => self.inspector = Some(window),
+                Err(err) => {
+                    crate::log_warn!("cannot open inspector: {err}");
+                    return false;
+                }
+            }
+        }
+
+        let Some(inspector) = &mut self.inspector else {
+            return false;
+        };
+
+        if !inspector.is_open() {
+            self.inspector = None;
+            return false;
+        }
+
+        frame.to_rgba_buffer(&mut self.screen);
+        // `screen` now holds the inspector's frame.
+        self.dirty = DirtyCheck::new();
+
+        if let Err(err) = inspector.update_with_buffer(&self.screen, WIDTH, HEIGHT) {
+            crate::log_warn!("inspector present failed: {err}");
+        }
+
+        true
+    }
+
+    #[cfg(feature = "audio")]
+    fn submit_audio(&mut self, frame: RegisterFrame) {
+        if let Some(audio) = &self.audio {
+            audio.submit(frame);
+        }
+    }
+
+    #[cfg(not(feature = "audio"))]
+    fn submit_audio(&mut self, _frame: RegisterFrame) {}
+
+    #[cfg(feature = "audio")]
+    fn audio_synth(&self) -> Option<crate::audio::SharedSynth> {
+        self.audio
+            .as_ref()
+            .map(crate::audio::output::AudioOutput::synth)
+    }
+
+    fn now_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    fn sleep_us(&mut self, us: u64) {
+        std::thread::sleep(Duration::from_micros(us));
+    }
+}
@@ -0,0 +1,254 @@
+//! The host the game runs on: a window to present frames in, keys to read,
+//! somewhere to send sound, and a clock.
+//!
+//! Game code never sees a backend. The main loop and the replay viewer are
+//! written against `Platform`. `minifb::MinifbPlatform` is the default
+//! desktop implementation, and `sdl::SdlPlatform` (feature `sdl2`) adds
+//! fullscreen, vsync and controllers, with rumble. `terminal::TerminalPlatform` (feature
+//! `terminal`) draws in ANSI colour to the terminal. A new backend implements the trait
+//! and picks its own key codes; the game only asks about the keys in `Key`.
+//! See ADR 0021. `threaded::ThreadedPlatform` runs any desktop backend on a
+//! presenter thread of its own (ADR 0025).
+//!
+//! The trait needs no `std`. On embedded targets `lcd::SpiLcd` (feature
+//! `spi_lcd`) presents frames to an SPI panel and the board supplies the
+//! rest.
+
+#[cfg(feature = "spi_lcd")]
+pub mod lcd;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod minifb;
+#[cfg(feature = "sdl2")]
+pub mod sdl;
+#[cfg(feature = "terminal")]
+pub mod terminal;
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub mod threaded;
+#[cfg(all(feature = "std", target_arch = "wasm32"))]
+pub mod web;
+
+use crate::audio::RegisterFrame;
+use crate::input::Input;
+use crate::renderer::framebuffer::FrameBuffer;
+
+/// Every key the game, the replay viewer and the debug tools read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Z,
+    X,
+    Enter,
+    Backspace,
+    Escape,
+    Space,
+    Tab,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Minus,
+    Equal,
+    Key0,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+}
+
+impl Key {
+    pub const ALL: [Self; 30] = [
+        Self::Up,
+        Self::Down,
+        Self::Left,
+        Self::Right,
+        Self::Z,
+        Self::X,
+        Self::Enter,
+        Self::Backspace,
+        Self::Escape,
+        Self::Space,
+        Self::Tab,
+        Self::Home,
+        Self::End,
+        Self::PageUp,
+        Self::PageDown,
+        Self::Minus,
+        Self::Equal,
+        Self::Key0,
+        Self::F1,
+        Self::F2,
+        Self::F3,
+        Self::F4,
+        Self::F5,
+        Self::F6,
+        Self::F7,
+        Self::F8,
+        Self::F9,
+        Self::F10,
+        Self::F11,
+        Self::F12,
+    ];
+
+    /// The key's name as printed on the cap, in the font's characters.
+    pub const fn label(self) -> &'static str {
+        match self {
+            Self::Up => "UP",
+            Self::Down => "DOWN",
+            Self::Left => "LEFT",
+            Self::Right => "RIGHT",
+            Self::Z => "Z",
+            Self::X => "X",
+            Self::Enter => "ENTER",
+            Self::Backspace => "BKSP",
+            Self::Escape => "ESC",
+            Self::Space => "SPACE",
+            Self::Tab => "TAB",
+            Self::Home => "HOME",
+            Self::End => "END",
+            Self::PageUp => "PGUP",
+            Self::PageDown => "PGDN",
+            Self::Minus => "MINUS",
+            Self::Equal => "EQUAL",
+            Self::Key0 => "0",
+            Self::F1 => "F1",
+            Self::F2 => "F2",
+            Self::F3 => "F3",
+            Self::F4 => "F4",
+            Self::F5 => "F5",
+            Self::F6 => "F6",
+            Self::F7 => "F7",
+            Self::F8 => "F8",
+            Self::F9 => "F9",
+            Self::F10 => "F10",
+            Self::F11 => "F11",
+            Self::F12 => "F12",
+        }
+    }
+}
+
+/// Keyboard bindings for each button.
+pub const KEYMAP: [(Key, Input); 8] = [
+    (Key::Up, Input::UP),
+    (Key::Down, Input::DOWN),
+    (Key::Left, Input::LEFT),
+    (Key::Right, Input::RIGHT),
+    (Key::Z, Input::A),
+    (Key::X, Input::B),
+    (Key::Enter, Input::START),
+    (Key::Backspace, Input::SELECT),
+];
+
+/// How hard a controller's rumble motors run, out of `u16::MAX`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rumble {
+    /// The heavy, low-frequency motor.
+    pub low: u16,
+    /// The light, high-frequency motor.
+    pub high: u16,
+}
+
+impl Rumble {
+    pub const OFF: Self = Self { low: 0, high: 0 };
+}
+
+/// A desktop window's size, place and mode, to open it the same way next
+/// run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowState {
+    /// Size as a multiple of the screen; out of fullscreen, for SDL2.
+    pub scale: u32,
+    /// Top-left corner on the desktop, out of fullscreen.
+    pub position: (i32, i32),
+    /// `None` if the backend has no fullscreen.
+    pub fullscreen: Option<bool>,
+}
+
+pub trait Platform {
+    /// `false` once the player has closed the window.
+    fn is_open(&self) -> bool;
+
+    /// Whether the window has keyboard focus, as of the last `present`.
+    /// Backends that cannot tell always say yes.
+    fn has_focus(&self) -> bool {
+        true
+    }
+
+    fn is_key_down(&self, key: Key) -> bool;
+
+    /// Whether `key` went down since the last `present`. With `repeat`,
+    /// holding the key also reports it again at the backend's repeat rate.
+    fn is_key_pressed(&self, key: Key, repeat: bool) -> bool;
+
+    /// The framebuffer pixel under the pointer while its main button is
+    /// held.
+    fn pointer_down(&self) -> Option<(usize, usize)> {
+        None
+    }
+
+    /// The buttons held according to `KEYMAP`.
+    fn poll_input(&self) -> Input {
+        let mut input = Input::empty();
+
+        for (key, button) in KEYMAP {
+            if self.is_key_down(key) {
+                input.insert(button);
+            }
+        }
+
+        input
+    }
+
+    /// Show `frame` and collect the input events for the next one.
+    fn present(&mut self, frame: &FrameBuffer);
+
+    /// Whether `present` waits for the display's refresh, so the loop can
+    /// use `Pacing::Vsync` instead of sleeping.
+    fn vsync(&self) -> bool {
+        false
+    }
+
+    /// The window's state as of the last `present`; `None` if the backend
+    /// has no desktop window.
+    fn window_state(&self) -> Option<WindowState> {
+        None
+    }
+
+    /// Show `frame` in a second window for debug views, opening it on the
+    /// first call. `false` if the backend has no second window, or once the
+    /// user has closed it.
+    fn present_inspector(&mut self, _frame: &FrameBuffer) -> bool {
+        false
+    }
+
+    /// Queue a frame of APU registers; call once per presented frame.
+    /// Backends without sound drop it.
+    fn submit_audio(&mut self, _frame: RegisterFrame) {}
+
+    /// The synth `submit_audio` queues into, so another thread can submit
+    /// to it directly. `None` for backends without sound.
+    #[cfg(feature = "std")]
+    fn audio_synth(&self) -> Option<crate::audio::SharedSynth> {
+        None
+    }
+
+    /// Run every controller's motors at `rumble` until the next call; call
+    /// once per presented frame. Backends without controllers ignore it.
+    fn rumble(&mut self, _rumble: Rumble) {}
+
+    /// Microseconds since an arbitrary, fixed starting point.
+    fn now_us(&self) -> u64;
+
+    /// Block for about `us` microseconds.
+    fn sleep_us(&mut self, us: u64);
+}
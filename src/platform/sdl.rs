@@ -0,0 +1,478 @@
+//! SDL2 backend: a resizable window with integer scaling, optional vsync
+//! and fullscreen (Alt+Enter switches), game controllers and their rumble,
+//! and sound through SDL's audio callback.
+//!
+//! Keys are read by scancode, so the bindings stay in the same place on
+//! every keyboard layout.
+
+use sdl2::audio::{AudioCallback, AudioDevice, AudioSpecDesired};
+use sdl2::controller::{Button, GameController};
+use sdl2::event::{Event, WindowEvent};
+use sdl2::keyboard::{Mod, Scancode};
+use sdl2::mouse::MouseButton;
+use sdl2::pixels::PixelFormatEnum;
+use sdl2::rect::Rect;
+use sdl2::render::{Texture, WindowCanvas};
+use sdl2::video::FullscreenType;
+use sdl2::{EventPump, GameControllerSubsystem, TimerSubsystem};
+
+use std::sync::{Arc, Mutex};
+
+use crate::audio::synth::Synth;
+use crate::audio::{RegisterFrame, SharedSynth};
+use crate::input::Input;
+use crate::platform::{Key, Platform, Rumble, WindowState};
+use crate::renderer::framebuffer::{DirtyCheck, FrameBuffer, HEIGHT, WIDTH};
+
+/// Default window scale.
+const SCALE: u32 = 4;
+
+/// Audio buffer length in samples: about 11 ms at 48 kHz.
+const AUDIO_SAMPLES: u16 = 512;
+
+/// How long a rumble lasts if the game stops calling `rumble`, in ms: a
+/// stalled game does not leave the controllers shaking.
+const RUMBLE_TIMEOUT_MS: u32 = 100;
+
+/// Controller bindings for each button.
+const PAD_MAP: [(Button, Input); 8] = [
+    (Button::DPadUp, Input::UP),
+    (Button::DPadDown, Input::DOWN),
+    (Button::DPadLeft, Input::LEFT),
+    (Button::DPadRight, Input::RIGHT),
+    (Button::A, Input::A),
+    (Button::B, Input::B),
+    (Button::Start, Input::START),
+    (Button::Back, Input::SELECT),
+];
+
+#[derive(Debug, Clone, Copy)]
+pub struct SdlOptions {
+    /// Fill the desktop instead of opening a window.
+    pub fullscreen: bool,
+    /// Wait for the display's refresh in `present`.
+    pub vsync: bool,
+    /// Initial window size as a multiple of the screen. The window can be
+    /// resized afterwards.
+    pub scale: u32,
+    /// Where the window's top-left corner opens; centred if `None`.
+    pub position: Option<(i32, i32)>,
+}
+
+impl Default for SdlOptions {
+    fn default() -> Self {
+        Self {
+            fullscreen: false,
+            vsync: false,
+            scale: SCALE,
+            position: None,
+        }
+    }
+}
+
+struct SynthCallback(SharedSynth);
+
+impl AudioCallback for SynthCallback {
+    type Channel = f32;
+
+    fn callback(&mut self, out: &mut [f32]) {
+        if let Ok(mut synth) = self.0.lock() {
+            synth.render(out);
+        }
+    }
+}
+
+pub struct SdlPlatform {
+    canvas: WindowCanvas,
+    /// Streaming texture the framebuffer is copied into. Created with the
+    /// `unsafe_textures` API and destroyed in `drop`, so it can live beside
+    /// the canvas.
+    texture: Option<Texture>,
+    /// Whether `texture` is out of date.
+    dirty: DirtyCheck,
+    events: EventPump,
+    timer: TimerSubsystem,
+    controller_subsystem: GameControllerSubsystem,
+    controllers: Vec<GameController>,
+    /// The motors as last set.
+    rumble: Rumble,
+    /// Kept open for as long as the window; frames go to `synth`.
+    _audio: Option<AudioDevice<SynthCallback>>,
+    synth: Option<SharedSynth>,
+    vsync: bool,
+    open: bool,
+    focused: bool,
+    /// Keys that went down since the last `present`, and whether the press
+    /// was a key repeat.
+    pressed: Vec<(Scancode, bool)>,
+    /// Framebuffer pixel under the pointer while the left button is held.
+    pointer: Option<(usize, usize)>,
+    /// Where the last frame was drawn in the window.
+    screen: Rect,
+    /// Size and place as of the last time out of fullscreen.
+    window: WindowState,
+}
+
+impl SdlPlatform {
+    pub fn new(title: &str, options: SdlOptions) -> Result<Self, String> {
+        let sdl = sdl2::init()?;
+        let video = sdl.video()?;
+
+        let scale = options.scale.max(1);
+        let mut window = video.window(title, WIDTH as u32 * scale, HEIGHT as u32 * scale);
+        window.resizable();
+
+        match options.position {
+            Some((x, y)) => window.position(x, y),
+            None => window.position_centered(),
+        };
+
+        if options.fullscreen {
+            window.fullscreen_desktop();
+        }
+
+        let window = window.build().map_err(|err| err.to_string())?;
+        let state = WindowState {
+            scale,
+            position: options.position.unwrap_or(window.position()),
+            fullscreen: Some(options.fullscreen),
+        };
+        let mut canvas = window.into_canvas();
+
+        if options.vsync {
+            canvas = canvas.present_vsync();
+        }
+
+        let canvas = canvas.build().map_err(|err| err.to_string())?;
+
+        let texture = canvas
+            .texture_creator()
+            .create_texture_streaming(PixelFormatEnum::ARGB8888, WIDTH as u32, HEIGHT as u32)
+            .map_err(|err| err.to_string())?;
+
+        let (audio, synth) = match open_audio(&sdl) {
+            Ok((audio, synth)) => (Some(audio), Some(synth)),
+            Err(err) => {
+                crate::log_warn!("no sound: {err}");
+                (None, None)
+            }
+        };
+
+        Ok(Self {
+            canvas,
+            texture: Some(texture),
+            dirty: DirtyCheck::new(),
+            events: sdl.event_pump()?,
+            timer: sdl.timer()?,
+            controller_subsystem: sdl.game_controller()?,
+            controllers: Vec::new(),
+            rumble: Rumble::OFF,
+            _audio: audio,
+            synth,
+            vsync: options.vsync,
+            open: true,
+            focused: true,
+            pressed: Vec::new(),
+            pointer: None,
+            screen: Rect::new(0, 0, WIDTH as u32 * scale, HEIGHT as u32 * scale),
+            window: state,
+        })
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::Quit { .. } => self.open = false,
+            Event::Window {
+                win_event: WindowEvent::FocusGained,
+                ..
+            } => self.focused = true,
+            Event::Window {
+                win_event: WindowEvent::FocusLost,
+                ..
+            } => self.focused = false,
+            Event::Window {
+                win_event: WindowEvent::Moved(x, y),
+                ..
+            } if self.window.fullscreen == Some(false) => self.window.position = (x, y),
+            Event::Window {
+                win_event: WindowEvent::SizeChanged(width, height),
+                ..
+            } if self.window.fullscreen == Some(false) => {
+                let scale = (width / WIDTH as i32).min(height / HEIGHT as i32);
+                self.window.scale = scale.clamp(1, 8) as u32;
+            }
+            Event::KeyDown {
+                scancode: Some(Scancode::Return),
+                keymod,
+                repeat: false,
+                ..
+            } if keymod.intersects(Mod::LALTMOD | Mod::RALTMOD) => self.toggle_fullscreen(),
+            Event::KeyDown {
+                scancode: Some(scancode),
+                repeat,
+                ..
+            } => self.pressed.push((scancode, repeat)),
+            Event::MouseButtonDown {
+                mouse_btn: MouseButton::Left,
+                x,
+                y,
+                ..
+            } => self.pointer = self.pointer_pixel(x, y),
+            Event::MouseMotion {
+                mousestate, x, y, ..
+            } if mousestate.left() => self.pointer = self.pointer_pixel(x, y),
+            Event::MouseButtonUp {
+                mouse_btn: MouseButton::Left,
+                ..
+            } => self.pointer = None,
+            Event::ControllerDeviceAdded { which, .. } => {
+                match self.controller_subsystem.open(which) {
+                    Ok(controller) => self.controllers.push(controller),
+                    Err(err) => crate::log_warn!("cannot open controller {which}: {err}"),
+                }
+            }
+            Event::ControllerDeviceRemoved { which, .. } => self
+                .controllers
+                .retain(|controller| controller.instance_id() != which),
+            _ => {}
+        }
+    }
+
+    /// The framebuffer pixel at window position `(x, y)`.
+    fn pointer_pixel(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let scale = (self.screen.width() / WIDTH as u32) as i32;
+        let x = usize::try_from((x - self.screen.x()) / scale).ok()?;
+        let y = usize::try_from((y - self.screen.y()) / scale).ok()?;
+
+        (x < WIDTH && y < HEIGHT).then_some((x, y))
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        let fullscreen = self.window.fullscreen != Some(true);
+        let mode = match fullscreen {
+            true => FullscreenType::Desktop,
+            false => FullscreenType::Off,
+        };
+
+        match self.canvas.window_mut().set_fullscreen(mode) {
+            Ok(()) => self.window.fullscreen = Some(fullscreen),
+            Err(err) => crate::log_warn!("cannot switch fullscreen: {err}"),
+        }
+    }
+
+    /// The largest whole multiple of 160x144 that fits the window, centred.
+    fn screen_rect(&self) -> Rect {
+        let (width, height) = self
+            .canvas
+            .output_size()
+            .unwrap_or((WIDTH as u32, HEIGHT as u32));
+        let scale = (width / WIDTH as u32).min(height / HEIGHT as u32).max(1);
+        let (w, h) = (WIDTH as u32 * scale, HEIGHT as u32 * scale);
+
+        Rect::new(
+            (width as i32 - w as i32) / 2,
+            (height as i32 - h as i32) / 2,
+            w,
+            h,
+        )
+    }
+}
+
+impl Drop for SdlPlatform {
+    fn drop(&mut self) {
+        if let Some(texture) = self.texture.take() {
+            // SAFETY: The canvas, and so the renderer that owns the texture,
+            // is still alive; it is dropped after this.
+            unsafe { texture.destroy() };
+        }
+    }
+}
+
+fn open_audio(sdl: &sdl2::Sdl) -> Result<(AudioDevice<SynthCallback>, SharedSynth), String> {
+    let desired = AudioSpecDesired {
+        freq: Some(48_000),
+        channels: Some(1),
+        samples: Some(AUDIO_SAMPLES),
+    };
+
+    let mut synth = None;
+    let device = sdl.audio()?.open_playback(None, &desired, |spec| {
+        let shared = Arc::new(Mutex::new(Synth::new(spec.freq as u32)));
+        synth = Some(Arc::clone(&shared));
+        SynthCallback(shared)
+    })?;
+    let synth = synth.ok_or("audio device opened without a spec")?;
+
+    device.resume();
+    Ok((device, synth))
+}
+
+fn scancode(key: Key) -> Scancode {
+    match key {
+        Key::Up => Scancode::Up,
+        Key::Down => Scancode::Down,
+        Key::Left => Scancode::Left,
+        Key::Right => Scancode::Right,
+        Key::Z => Scancode::Z,
+        Key::X => Scancode::X,
+        Key::Enter => Scancode::Return,
+        Key::Backspace => Scancode::Backspace,
+        Key::Escape => Scancode::Escape,
+        Key::Space => Scancode::Space,
+        Key::Tab => Scancode::Tab,
+        Key::Home => Scancode::Home,
+        Key::End => Scancode::End,
+        Key::PageUp => Scancode::PageUp,
+        Key::PageDown => Scancode::PageDown,
+        Key::Minus => Scancode::Minus,
+        Key::Equal => Scancode::Equals,
+        Key::Key0 => Scancode::Num0,
+        Key::F1 => Scancode::F1,
+        Key::F2 => Scancode::F2,
+        Key::F3 => Scancode::F3,
+        Key::F4 => Scancode::F4,
+        Key::F5 => Scancode::F5,
+        Key::F6 => Scancode::F6,
+        Key::F7 => Scancode::F7,
+        Key::F8 => Scancode::F8,
+        Key::F9 => Scancode::F9,
+        Key::F10 => Scancode::F10,
+        Key::F11 => Scancode::F11,
+        Key::F12 => Scancode::F12,
+    }
+}
+
+impl Platform for SdlPlatform {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn has_focus(&self) -> bool {
+        self.focused
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        let keyboard = self.events.keyboard_state();
+        let alt = [Scancode::LAlt, Scancode::RAlt]
+            .into_iter()
+            .any(|alt| keyboard.is_scancode_pressed(alt));
+
+        // With Alt, Enter switches fullscreen instead of pressing Start.
+        if key == Key::Enter && alt {
+            return false;
+        }
+
+        keyboard.is_scancode_pressed(scancode(key))
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: bool) -> bool {
+        let wanted = scancode(key);
+
+        self.pressed
+            .iter()
+            .any(|&(scancode, is_repeat)| scancode == wanted && (repeat || !is_repeat))
+    }
+
+    fn pointer_down(&self) -> Option<(usize, usize)> {
+        self.pointer
+    }
+
+    /// Keyboard and every connected controller, combined.
+    fn poll_input(&self) -> Input {
+        let mut input = Input::empty();
+
+        for (key, button) in super::KEYMAP {
+            if self.is_key_down(key) {
+                input.insert(button);
+            }
+        }
+
+        for controller in &self.controllers {
+            for (pad_button, button) in PAD_MAP {
+                if controller.button(pad_button) {
+                    input.insert(button);
+                }
+            }
+        }
+
+        input
+    }
+
+    fn rumble(&mut self, rumble: Rumble) {
+        // Motors at rest stay at rest without being told again.
+        if rumble == Rumble::OFF && self.rumble == Rumble::OFF {
+            return;
+        }
+
+        self.rumble = rumble;
+
+        for controller in &mut self.controllers {
+            // Controllers without motors refuse; there is nothing to do.
+            let _ = controller.set_rumble(rumble.low, rumble.high, RUMBLE_TIMEOUT_MS);
+        }
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) {
+        self.screen = self.screen_rect();
+
+        if let Some(texture) = &mut self.texture {
+            let copied = if self.dirty.changed(frame) {
+                texture.with_lock(None, |pixels, pitch| {
+                    for (y, row) in pixels.chunks_exact_mut(pitch).take(HEIGHT).enumerate() {
+                        for (x, pixel) in row.chunks_exact_mut(4).take(WIDTH).enumerate() {
+                            let color = frame.palette()[frame.get_pixel(x, y) as usize];
+                            pixel.copy_from_slice(&(color | 0xff00_0000).to_ne_bytes());
+                        }
+                    }
+                })
+            } else {
+                Ok(())
+            };
+
+            self.canvas.clear();
+
+            if let Err(err) = copied.and_then(|()| self.canvas.copy(texture, None, self.screen)) {
+                crate::log_warn!("present failed: {err}");
+            }
+
+            self.canvas.present();
+        }
+
+        self.pressed.clear();
+
+        while let Some(event) = self.events.poll_event() {
+            self.handle(event);
+        }
+    }
+
+    fn vsync(&self) -> bool {
+        self.vsync
+    }
+
+    fn window_state(&self) -> Option<WindowState> {
+        Some(self.window)
+    }
+
+    fn submit_audio(&mut self, frame: RegisterFrame) {
+        if let Some(synth) = &self.synth
+            && let Ok(mut synth) = synth.lock()
+        {
+            synth.push(frame);
+        }
+    }
+
+    fn audio_synth(&self) -> Option<SharedSynth> {
+        self.synth.clone()
+    }
+
+    fn now_us(&self) -> u64 {
+        let counter = self.timer.performance_counter() as u128;
+        let frequency = self.timer.performance_frequency() as u128;
+
+        (counter * 1_000_000 / frequency) as u64
+    }
+
+    fn sleep_us(&mut self, us: u64) {
+        std::thread::sleep(std::time::Duration::from_micros(us));
+    }
+}
@@ -0,0 +1,296 @@
+//! Terminal backend: draws the framebuffer with half-block characters in
+//! 256-colour ANSI, two pixels per cell, so the screen takes 160x72 cells.
+//! Works over SSH and on servers without a display.
+//!
+//! Only rows that changed since the last frame are redrawn. Terminals that
+//! support the kitty keyboard protocol report key releases; elsewhere a key
+//! counts as held for a short while after each press or repeat, so holding
+//! a direction works but taps last a few frames. Ctrl+C closes the game.
+
+use std::io::{self, Stdout, Write};
+use std::time::{Duration, Instant};
+
+use crossterm::event::{
+    self, DisableFocusChange, EnableFocusChange, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers, KeyboardEnhancementFlags, PopKeyboardEnhancementFlags,
+    PushKeyboardEnhancementFlags,
+};
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use crossterm::{cursor, execute};
+
+use crate::platform::{Key, Platform};
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+
+/// How long a key stays down after its first press when the terminal does
+/// not report releases; about a typical key repeat delay.
+const HOLD_US: u64 = 300_000;
+
+/// How long a key stays down after a repeat when the terminal does not
+/// report releases.
+const REPEAT_HOLD_US: u64 = 100_000;
+
+/// Cell rows on screen: each holds two pixel rows.
+const ROWS: usize = HEIGHT / 2;
+
+/// The nearest colour in the xterm 6x6x6 cube.
+fn ansi256(argb: u32) -> u8 {
+    fn level(channel: u32) -> u8 {
+        // Cube levels are 0, 95, 135, 175, 215, 255.
+        if channel < 48 {
+            0
+        } else if channel < 115 {
+            1
+        } else {
+            ((channel - 35) / 40) as u8
+        }
+    }
+
+    let r = level((argb >> 16) & 0xff);
+    let g = level((argb >> 8) & 0xff);
+    let b = level(argb & 0xff);
+
+    16 + 36 * r + 6 * g + b
+}
+
+pub struct TerminalPlatform {
+    out: Stdout,
+    start: Instant,
+    open: bool,
+    /// Cleared by a focus-out report; terminals that do not report focus
+    /// stay focused.
+    focused: bool,
+    /// The terminal reports key releases.
+    releases: bool,
+    /// Keys held, and when they stop counting as held if the terminal does
+    /// not report releases.
+    down: Vec<(Key, Option<u64>)>,
+    /// Keys that went down since the last `present`, and whether the press
+    /// was a key repeat.
+    pressed: Vec<(Key, bool)>,
+    /// Palette index of every pixel as last drawn; `u8::MAX` forces a
+    /// redraw.
+    shown: Vec<u8>,
+    /// Escape sequences for the frame being drawn.
+    buffer: Vec<u8>,
+}
+
+impl TerminalPlatform {
+    /// Switch the terminal to raw mode on the alternate screen. The log's
+    /// stderr echo is turned off, as it would draw over the game.
+    pub fn new() -> Result<Self, String> {
+        let mut out = io::stdout();
+
+        terminal::enable_raw_mode().map_err(|err| err.to_string())?;
+        execute!(out, EnterAlternateScreen, cursor::Hide, EnableFocusChange)
+            .map_err(|err| err.to_string())?;
+
+        let releases = terminal::supports_keyboard_enhancement().unwrap_or(false)
+            && execute!(
+                out,
+                PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::REPORT_EVENT_TYPES)
+            )
+            .is_ok();
+
+        crate::runtime::log::global().set_echo(false);
+
+        if let Ok((columns, rows)) = terminal::size()
+            && ((columns as usize) < WIDTH || (rows as usize) < ROWS)
+        {
+            crate::log_warn!("terminal is {columns}x{rows}; {WIDTH}x{ROWS} shows the whole screen");
+        }
+
+        Ok(Self {
+            out,
+            start: Instant::now(),
+            open: true,
+            focused: true,
+            releases,
+            down: Vec::new(),
+            pressed: Vec::new(),
+            shown: vec![u8::MAX; WIDTH * HEIGHT],
+            buffer: Vec::new(),
+        })
+    }
+
+    fn handle(&mut self, event: Event) {
+        match event {
+            Event::FocusGained => self.focused = true,
+            Event::FocusLost => self.focused = false,
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('c'),
+                modifiers,
+                kind: KeyEventKind::Press,
+                ..
+            }) if modifiers.contains(KeyModifiers::CONTROL) => self.open = false,
+            Event::Key(KeyEvent { code, kind, .. }) => {
+                let Some(key) = key(code) else {
+                    return;
+                };
+
+                let now = self.now_us();
+                self.down.retain(|&(down, _)| down != key);
+
+                let until = match kind {
+                    KeyEventKind::Release => return,
+                    _ if self.releases => None,
+                    KeyEventKind::Press => Some(now + HOLD_US),
+                    KeyEventKind::Repeat => Some(now + REPEAT_HOLD_US),
+                };
+
+                self.down.push((key, until));
+                self.pressed.push((key, kind == KeyEventKind::Repeat));
+            }
+            Event::Resize(..) => {
+                self.shown.fill(u8::MAX);
+                self.buffer.extend_from_slice(b"\x1b[2J");
+            }
+            _ => {}
+        }
+    }
+
+    /// Queue the escape sequences that bring the screen up to `frame`.
+    fn draw(&mut self, frame: &FrameBuffer) {
+        let palette = frame.palette().map(ansi256);
+
+        for row in 0..ROWS {
+            let (top, bottom) = (row * 2, row * 2 + 1);
+            let changed = (0..WIDTH).any(|x| {
+                self.shown[top * WIDTH + x] != frame.get_pixel(x, top)
+                    || self.shown[bottom * WIDTH + x] != frame.get_pixel(x, bottom)
+            });
+
+            if !changed {
+                continue;
+            }
+
+            let _ = write!(self.buffer, "\x1b[{};1H", row + 1);
+            let mut colors = None;
+
+            for x in 0..WIDTH {
+                let upper = frame.get_pixel(x, top);
+                let lower = frame.get_pixel(x, bottom);
+                self.shown[top * WIDTH + x] = upper;
+                self.shown[bottom * WIDTH + x] = lower;
+
+                if colors != Some((upper, lower)) {
+                    let _ = write!(
+                        self.buffer,
+                        "\x1b[38;5;{};48;5;{}m",
+                        palette[upper as usize], palette[lower as usize]
+                    );
+                    colors = Some((upper, lower));
+                }
+
+                self.buffer.extend_from_slice("▀".as_bytes());
+            }
+
+            self.buffer.extend_from_slice(b"\x1b[0m");
+        }
+    }
+}
+
+impl Drop for TerminalPlatform {
+    fn drop(&mut self) {
+        if self.releases {
+            let _ = execute!(self.out, PopKeyboardEnhancementFlags);
+        }
+
+        let _ = execute!(
+            self.out,
+            DisableFocusChange,
+            cursor::Show,
+            LeaveAlternateScreen
+        );
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+fn key(code: KeyCode) -> Option<Key> {
+    Some(match code {
+        KeyCode::Up => Key::Up,
+        KeyCode::Down => Key::Down,
+        KeyCode::Left => Key::Left,
+        KeyCode::Right => Key::Right,
+        KeyCode::Char('z' | 'Z') => Key::Z,
+        KeyCode::Char('x' | 'X') => Key::X,
+        KeyCode::Enter => Key::Enter,
+        KeyCode::Backspace => Key::Backspace,
+        KeyCode::Esc => Key::Escape,
+        KeyCode::Char(' ') => Key::Space,
+        KeyCode::Tab => Key::Tab,
+        KeyCode::Home => Key::Home,
+        KeyCode::End => Key::End,
+        KeyCode::PageUp => Key::PageUp,
+        KeyCode::PageDown => Key::PageDown,
+        KeyCode::Char('-') => Key::Minus,
+        KeyCode::Char('=') => Key::Equal,
+        KeyCode::Char('0') => Key::Key0,
+        KeyCode::F(1) => Key::F1,
+        KeyCode::F(2) => Key::F2,
+        KeyCode::F(3) => Key::F3,
+        KeyCode::F(4) => Key::F4,
+        KeyCode::F(5) => Key::F5,
+        KeyCode::F(6) => Key::F6,
+        KeyCode::F(7) => Key::F7,
+        KeyCode::F(8) => Key::F8,
+        KeyCode::F(9) => Key::F9,
+        KeyCode::F(10) => Key::F10,
+        KeyCode::F(11) => Key::F11,
+        KeyCode::F(12) => Key::F12,
+        _ => return None,
+    })
+}
+
+impl Platform for TerminalPlatform {
+    fn is_open(&self) -> bool {
+        self.open
+    }
+
+    fn has_focus(&self) -> bool {
+        self.focused
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        let now = self.now_us();
+
+        self.down
+            .iter()
+            .any(|&(down, until)| down == key && until.is_none_or(|until| now < until))
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: bool) -> bool {
+        self.pressed
+            .iter()
+            .any(|&(pressed, is_repeat)| pressed == key && (repeat || !is_repeat))
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) {
+        self.draw(frame);
+
+        if let Err(err) = self
+            .out
+            .write_all(&self.buffer)
+            .and_then(|()| self.out.flush())
+        {
+            crate::log_warn!("present failed: {err}");
+        }
+
+        self.buffer.clear();
+        self.pressed.clear();
+
+        while event::poll(Duration::ZERO).unwrap_or(false) {
+            match event::read() {
+                Ok(event) => self.handle(event),
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn now_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    fn sleep_us(&mut self, us: u64) {
+        std::thread::sleep(Duration::from_micros(us));
+    }
+}
@@ -0,0 +1,264 @@
+//! Presents frames from a thread of its own, so a slow window system never
+//! holds up the simulation.
+//!
+//! The backend is opened on the presenter thread and stays there: windows
+//! are not `Send`, and their input events arrive on the thread that
+//! presents. The game keeps rendering into its own `FrameBuffer`. `present`
+//! copies it into the back buffer and returns at once; the presenter swaps
+//! the back buffer with its front buffer, converts and shows that, and
+//! publishes the input it collected. A frame the presenter had no time for
+//! is replaced by the next one instead of queueing. Nothing of the runtime
+//! is shared with the presenter but these copies (ADR 0025).
+//!
+//! Sound does not wait for the presenter: `submit_audio` queues straight
+//! into the backend's synth, so a stalled window does not stall the audio.
+
+use std::sync::mpsc;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::audio::{RegisterFrame, SharedSynth};
+use crate::platform::{Key, Platform, WindowState};
+use crate::renderer::framebuffer::FrameBuffer;
+
+/// How long the presenter waits for a new frame before showing the last one
+/// again, so the window keeps answering while the game is stalled.
+const IDLE_PRESENT: Duration = Duration::from_millis(50);
+
+/// Frames the game has presented that the presenter has not picked up.
+struct BackBuffer {
+    frame: FrameBuffer,
+    /// Whether `frame` is newer than the presenter's front buffer.
+    fresh: bool,
+    /// Set when the `ThreadedPlatform` is dropped.
+    quit: bool,
+}
+
+/// What the backend reported after the presenter's last `present`. Keys
+/// are bits by `Key` discriminant.
+#[derive(Debug, Clone, Copy)]
+struct InputState {
+    open: bool,
+    focused: bool,
+    down: u32,
+    /// Keys that went down since the game last took them.
+    pressed: u32,
+    /// `pressed` including key repeats.
+    repeated: u32,
+    pointer: Option<(usize, usize)>,
+    window: Option<WindowState>,
+}
+
+impl InputState {
+    const OPEN: Self = Self {
+        open: true,
+        focused: true,
+        down: 0,
+        pressed: 0,
+        repeated: 0,
+        pointer: None,
+        window: None,
+    };
+}
+
+struct Shared {
+    back: Mutex<BackBuffer>,
+    /// Signalled when `back` has a fresh frame or `quit` is set.
+    ready: Condvar,
+    input: Mutex<InputState>,
+}
+
+/// A lock that outlives a panic on the other thread: both values are valid
+/// after every write.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+const fn bit(key: Key) -> u32 {
+    1 << key as u32
+}
+
+pub struct ThreadedPlatform {
+    shared: Arc<Shared>,
+    /// The input as of the last `present`.
+    input: InputState,
+    presenter: Option<JoinHandle<()>>,
+    /// The backend's synth; `None` without sound.
+    audio: Option<SharedSynth>,
+    start: Instant,
+}
+
+impl ThreadedPlatform {
+    /// Start the presenter thread and open the backend on it with `open`.
+    /// Fails with `open`'s error. The backend's vsync, inspector window,
+    /// controllers and rumble are not available through the thread.
+    pub fn spawn<F>(open: F) -> Result<Self, String>
+    where
+        F: FnOnce() -> Result<Box<dyn Platform>, String> + Send + 'static,
+    {
+        let shared = Arc::new(Shared {
+            back: Mutex::new(BackBuffer {
+                frame: FrameBuffer::new(),
+                fresh: false,
+                quit: false,
+            }),
+            ready: Condvar::new(),
+            input: Mutex::new(InputState::OPEN),
+        });
+        let (opened, result) = mpsc::channel();
+
+        let presenter = {
+            let shared = Arc::clone(&shared);
+
+            thread::Builder::new()
+                .name("presenter".into())
+                .spawn(move || match open() {
+                    Ok(platform) => {
+                        let _ = opened.send(Ok(platform.audio_synth()));
+                        present_loop(platform, &shared);
+                    }
+                    Err(err) => {
+                        let _ = opened.send(Err(err));
+                    }
+                })
+                .map_err(|err| err.to_string())?
+        };
+
+        let audio = result
+            .recv()
+            .map_err(|_| "presenter thread died while opening the window".to_string())??;
+
+        Ok(Self {
+            shared,
+            input: InputState::OPEN,
+            presenter: Some(presenter),
+            audio,
+            start: Instant::now(),
+        })
+    }
+}
+
+fn present_loop(mut platform: Box<dyn Platform>, shared: &Shared) {
+    let mut front = FrameBuffer::new();
+
+    loop {
+        {
+            let (mut back, _) = shared
+                .ready
+                .wait_timeout_while(lock(&shared.back), IDLE_PRESENT, |back| {
+                    !back.fresh && !back.quit
+                })
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+            if back.quit {
+                return;
+            }
+
+            if back.fresh {
+                std::mem::swap(&mut back.frame, &mut front);
+                back.fresh = false;
+            }
+        }
+
+        platform.present(&front);
+
+        let mut down = 0;
+        let mut pressed = 0;
+        let mut repeated = 0;
+
+        for key in Key::ALL {
+            if platform.is_key_down(key) {
+                down |= bit(key);
+            }
+            if platform.is_key_pressed(key, false) {
+                pressed |= bit(key);
+            }
+            if platform.is_key_pressed(key, true) {
+                repeated |= bit(key);
+            }
+        }
+
+        let mut input = lock(&shared.input);
+        input.open = platform.is_open();
+        input.focused = platform.has_focus();
+        input.down = down;
+        input.pressed |= pressed;
+        input.repeated |= repeated;
+        input.pointer = platform.pointer_down();
+        input.window = platform.window_state();
+    }
+}
+
+impl Drop for ThreadedPlatform {
+    fn drop(&mut self) {
+        lock(&self.shared.back).quit = true;
+        self.shared.ready.notify_one();
+
+        if let Some(presenter) = self.presenter.take() {
+            let _ = presenter.join();
+        }
+    }
+}
+
+impl Platform for ThreadedPlatform {
+    fn is_open(&self) -> bool {
+        self.input.open && self.presenter.as_ref().is_some_and(|p| !p.is_finished())
+    }
+
+    fn has_focus(&self) -> bool {
+        self.input.focused
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.input.down & bit(key) != 0
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: bool) -> bool {
+        let pressed = if repeat {
+            self.input.repeated
+        } else {
+            self.input.pressed
+        };
+
+        pressed & bit(key) != 0
+    }
+
+    fn pointer_down(&self) -> Option<(usize, usize)> {
+        self.input.pointer
+    }
+
+    fn window_state(&self) -> Option<WindowState> {
+        self.input.window
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) {
+        {
+            let mut back = lock(&self.shared.back);
+            back.frame.clone_from(frame);
+            back.fresh = true;
+        }
+        self.shared.ready.notify_one();
+
+        let mut input = lock(&self.shared.input);
+        self.input = *input;
+        input.pressed = 0;
+        input.repeated = 0;
+    }
+
+    fn submit_audio(&mut self, frame: RegisterFrame) {
+        if let Some(synth) = &self.audio {
+            lock(synth).push(frame);
+        }
+    }
+
+    fn now_us(&self) -> u64 {
+        self.start.elapsed().as_micros() as u64
+    }
+
+    fn sleep_us(&mut self, us: u64) {
+        thread::sleep(Duration::from_micros(us));
+    }
+}
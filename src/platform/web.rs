@@ -0,0 +1,253 @@
+//! Browser backend for `wasm32`: the framebuffer is drawn to a `<canvas>`
+//! through `ImageData`, keys come from `keydown`/`keyup` listeners on the
+//! window, and `run` drives the game from `requestAnimationFrame`.
+//!
+//! The browser decides when frames happen, so `sleep_us` does nothing and
+//! `run` calls the frame closure once per 1/60 s of elapsed time, however
+//! fast the display refreshes. There is no sound yet: `submit_audio` drops
+//! the registers.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::{Clamped, JsCast};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, ImageData, KeyboardEvent, Window};
+
+use crate::platform::{Key, Platform};
+use crate::renderer::framebuffer::{DirtyCheck, FrameBuffer, HEIGHT, WIDTH};
+
+/// Microseconds per game frame.
+const FRAME_US: f64 = 1_000_000.0 / 60.0;
+
+/// Most frames `run` catches up on after a stall (a background tab, a
+/// debugger), so the game does not fast-forward afterwards.
+const MAX_CATCH_UP: u32 = 4;
+
+#[derive(Default)]
+struct Keys {
+    down: Vec<Key>,
+    /// Keys that went down since the last `present`, and whether the press
+    /// was a key repeat.
+    pressed: Vec<(Key, bool)>,
+}
+
+pub struct WebPlatform {
+    window: Window,
+    context: CanvasRenderingContext2d,
+    /// RGBA pixels for `ImageData`.
+    pixels: Vec<u8>,
+    /// Whether the canvas is out of date.
+    dirty: DirtyCheck,
+    keys: Rc<RefCell<Keys>>,
+    /// The listeners stay registered for as long as the platform lives.
+    _listeners: [Closure<dyn FnMut(KeyboardEvent)>; 2],
+}
+
+/// The key for a `KeyboardEvent.code`, which names the physical key
+/// whatever the layout.
+fn key(code: &str) -> Option<Key> {
+    Some(match code {
+        "ArrowUp" => Key::Up,
+        "ArrowDown" => Key::Down,
+        "ArrowLeft" => Key::Left,
+        "ArrowRight" => Key::Right,
+        "KeyZ" => Key::Z,
+        "KeyX" => Key::X,
+        "Enter" => Key::Enter,
+        "Backspace" => Key::Backspace,
+        "Escape" => Key::Escape,
+        "Space" => Key::Space,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        "Minus" => Key::Minus,
+        "Equal" => Key::Equal,
+        "Digit0" => Key::Key0,
+        "F1" => Key::F1,
+        "F2" => Key::F2,
+        "F3" => Key::F3,
+        "F4" => Key::F4,
+        "F5" => Key::F5,
+        "F6" => Key::F6,
+        "F7" => Key::F7,
+        "F8" => Key::F8,
+        "F9" => Key::F9,
+        "F10" => Key::F10,
+        "F11" => Key::F11,
+        "F12" => Key::F12,
+        _ => return None,
+    })
+}
+
+impl WebPlatform {
+    /// Draw into the `<canvas>` with id `canvas_id`, which is resized to
+    /// 160x144; scale it up with CSS.
+    pub fn new(canvas_id: &str) -> Result<Self, String> {
+        let window = web_sys::window().ok_or("no window")?;
+        let document = window.document().ok_or("no document")?;
+        let canvas: HtmlCanvasElement = document
+            .get_element_by_id(canvas_id)
+            .ok_or(format!("no element `#{canvas_id}`"))?
+            .dyn_into()
+            .map_err(|_| format!("`#{canvas_id}` is not a canvas"))?;
+
+        canvas.set_width(WIDTH as u32);
+        canvas.set_height(HEIGHT as u32);
+
+        let context: CanvasRenderingContext2d = canvas
+            .get_context("2d")
+            .ok()
+            .flatten()
+            .ok_or("no 2d context")?
+            .dyn_into()
+            .map_err(|_| "no 2d context")?;
+
+        let keys = Rc::new(RefCell::new(Keys::default()));
+
+        let on_down = {
+            let keys = Rc::clone(&keys);
+
+            Closure::<dyn FnMut(KeyboardEvent)>::new(move |event: KeyboardEvent| {
+                let Some(key) = key(&event.code()) else {
+                    return;
+                };
+
+                // Keep arrows and Space from scrolling the page.
+                event.prevent_default();
+
+                let mut keys = keys.borrow_mut();
+                keys.pressed.push((key, event.repeat()));
+
+                if !keys.down.contains(&key) {
+                    keys.down.push(key);
+                }
+            })
+        };
+
+        let on_up = {
+            let keys = Rc::clone(&keys);
+
+            Closure::<dyn FnMut(KeyboardEvent)>::new(move |event: KeyboardEvent| {
+                if let Some(key) = key(&event.code()) {
+                    keys.borrow_mut().down.retain(|&down| down != key);
+                }
+            })
+        };
+
+        for (name, listener) in [("keydown", &on_down), ("keyup", &on_up)] {
+            window
+                .add_event_listener_with_callback(name, listener.as_ref().unchecked_ref())
+                .map_err(|_| format!("cannot listen for {name}"))?;
+        }
+
+        Ok(Self {
+            window,
+            context,
+            pixels: vec![0; WIDTH * HEIGHT * 4],
+            dirty: DirtyCheck::new(),
+            keys,
+            _listeners: [on_down, on_up],
+        })
+    }
+}
+
+impl Platform for WebPlatform {
+    fn is_open(&self) -> bool {
+        true
+    }
+
+    fn has_focus(&self) -> bool {
+        self.window
+            .document()
+            .is_none_or(|document| document.has_focus().unwrap_or(true))
+    }
+
+    fn is_key_down(&self, key: Key) -> bool {
+        self.keys.borrow().down.contains(&key)
+    }
+
+    fn is_key_pressed(&self, key: Key, repeat: bool) -> bool {
+        self.keys
+            .borrow()
+            .pressed
+            .iter()
+            .any(|&(pressed, is_repeat)| pressed == key && (repeat || !is_repeat))
+    }
+
+    fn present(&mut self, frame: &FrameBuffer) {
+        self.keys.borrow_mut().pressed.clear();
+
+        // The canvas keeps showing an unchanged frame by itself.
+        if !self.dirty.changed(frame) {
+            return;
+        }
+
+        for (i, pixel) in self.pixels.chunks_exact_mut(4).enumerate() {
+            let color = frame.palette()[frame.get_pixel(i % WIDTH, i / WIDTH) as usize];
+            let [_, r, g, b] = color.to_be_bytes();
+            pixel.copy_from_slice(&[r, g, b, 0xff]);
+        }
+
+        let image = ImageData::new_with_u8_clamped_array_and_sh(
+            Clamped(&self.pixels),
+            WIDTH as u32,
+            HEIGHT as u32,
+        );
+
+        if let Err(err) = image.and_then(|image| self.context.put_image_data(&image, 0.0, 0.0)) {
+            crate::log_warn!("present failed: {err:?}");
+        }
+    }
+
+    fn now_us(&self) -> u64 {
+        self.window
+            .performance()
+            .map_or(0, |performance| (performance.now() * 1000.0) as u64)
+    }
+
+    /// The browser paces frames; see `run`.
+    fn sleep_us(&mut self, _us: u64) {}
+}
+
+/// Call `frame` 60 times a second from `requestAnimationFrame`, for as long
+/// as the page is open. `frame` does one tick: read input, update, render
+/// and present.
+pub fn run(mut platform: WebPlatform, mut frame: impl FnMut(&mut WebPlatform) + 'static) {
+    type Callback = Closure<dyn FnMut(f64)>;
+
+    let callback: Rc<RefCell<Option<Callback>>> = Rc::new(RefCell::new(None));
+    let next = Rc::clone(&callback);
+    let mut last: Option<f64> = None;
+    let mut owed_us = 0.0;
+
+    *callback.borrow_mut() = Some(Closure::new(move |now_ms: f64| {
+        let now_us = now_ms * 1000.0;
+        owed_us += now_us - last.unwrap_or(now_us - FRAME_US);
+        owed_us = owed_us.min(FRAME_US * MAX_CATCH_UP as f64);
+        last = Some(now_us);
+
+        while owed_us >= FRAME_US {
+            owed_us -= FRAME_US;
+            frame(&mut platform);
+        }
+
+        if let Some(callback) = next.borrow().as_ref() {
+            request_frame(&platform.window, callback);
+        }
+    }));
+
+    if let (Some(window), Some(callback)) = (web_sys::window(), callback.borrow().as_ref()) {
+        request_frame(&window, callback);
+    }
+}
+
+fn request_frame(window: &Window, callback: &Closure<dyn FnMut(f64)>) {
+    if window
+        .request_animation_frame(callback.as_ref().unchecked_ref())
+        .is_err()
+    {
+        crate::log_warn!("requestAnimationFrame failed; the game has stopped");
+    }
+}
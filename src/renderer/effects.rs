@@ -0,0 +1,127 @@
+//! Screen effects: the flash and shake that answer a hit, and scene
+//! transitions, applied to the finished frame. They are presentation only
+//! and never reach the simulation.
+//!
+//! Every effect goes through `ScreenEffects::apply`, which enforces the
+//! framebuffer's accessibility settings: with `reduce_flashing` a flash
+//! becomes a steady border instead of an inverted screen, and with
+//! `screen_shake` off the picture stays put. The settings live on the
+//! framebuffer, so they hold for every game state drawn to it, replays
+//! and restored snapshots included.
+
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+use crate::renderer::transition::Transition;
+
+/// Ticks a hit flash lasts.
+const FLASH_TICKS: u8 = 4;
+
+/// Ticks a hit shake lasts.
+const SHAKE_TICKS: u8 = 12;
+
+/// Picture offsets while shaking, one per tick, repeated.
+const SHAKE_OFFSETS: [(i32, i32); 4] = [(2, 0), (-2, 1), (1, -1), (-1, 0)];
+
+/// The accessibility settings group. Effects read `reduce_flashing` and
+/// `screen_shake`, the framebuffer applies `large_text`, and `render`
+/// draws `shape_markers`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Accessibility {
+    /// No full-screen flashes.
+    pub reduce_flashing: bool,
+    pub screen_shake: bool,
+    /// Game UI text at double size.
+    pub large_text: bool,
+    /// Enemies drawn as a shape and co-op ships numbered, so nothing is
+    /// told apart by shade alone.
+    pub shape_markers: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Accessibility {
+    pub const fn new() -> Self {
+        Self {
+            reduce_flashing: false,
+            screen_shake: true,
+            large_text: false,
+            shape_markers: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScreenEffects {
+    /// Ticks of flash left.
+    flash: u8,
+    /// Ticks of shake left.
+    shake: u8,
+    /// The running transition; a finished `Out` one keeps the screen
+    /// covered until the next starts.
+    transition: Option<Transition>,
+}
+
+impl ScreenEffects {
+    pub const fn new() -> Self {
+        Self {
+            flash: 0,
+            shake: 0,
+            transition: None,
+        }
+    }
+
+    pub fn flash(&mut self) {
+        self.flash = FLASH_TICKS;
+    }
+
+    pub fn shake(&mut self) {
+        self.shake = SHAKE_TICKS;
+    }
+
+    /// Start `transition`, replacing any running one.
+    pub fn transition(&mut self, transition: Transition) {
+        self.transition = Some(transition);
+    }
+
+    /// Whether a transition is still moving; a scene waits for this before
+    /// switching after an `Out`.
+    pub fn in_transition(&self) -> bool {
+        self.transition
+            .is_some_and(|transition| !transition.is_done())
+    }
+
+    /// Count the running effects down by one tick.
+    pub fn tick(&mut self) {
+        self.flash = self.flash.saturating_sub(1);
+        self.shake = self.shake.saturating_sub(1);
+
+        if let Some(transition) = &mut self.transition {
+            transition.tick();
+        }
+    }
+
+    /// Apply the running effects to a finished frame.
+    pub fn apply(&self, framebuffer: &mut FrameBuffer) {
+        let accessibility = *framebuffer.accessibility();
+
+        if self.shake > 0 && accessibility.screen_shake {
+            let (dx, dy) = SHAKE_OFFSETS[self.shake as usize % SHAKE_OFFSETS.len()];
+            framebuffer.shift(dx, dy, 0);
+        }
+
+        if self.flash > 0 {
+            if accessibility.reduce_flashing {
+                framebuffer.draw_rect(0, 0, WIDTH, HEIGHT, 3);
+            } else {
+                framebuffer.invert();
+            }
+        }
+
+        if let Some(transition) = &self.transition {
+            transition.apply(framebuffer);
+        }
+    }
+}
@@ -1,3 +1,4 @@
+#[derive(Debug, Clone, Copy)]
 pub struct Glyph {
     pub rows: [u8; 10], // 8 bits per row, using first 5 rows for 3x5 font
 }
@@ -235,6 +236,18 @@ const FONT_SLASH: Glyph = Glyph {
         0b00000000, 0b00000000, 0b00000000,
     ],
 };
+const FONT_PLUS: Glyph = Glyph {
+    rows: [
+        0b00000000, 0b01000000, 0b11100000, 0b01000000, 0b00000000, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000,
+    ],
+};
+const FONT_MINUS: Glyph = Glyph {
+    rows: [
+        0b00000000, 0b00000000, 0b11100000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000,
+    ],
+};
 const FONT_SPACE: Glyph = Glyph {
     rows: [
         0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000, 0b00000000,
@@ -242,6 +255,32 @@ const FONT_SPACE: Glyph = Glyph {
     ],
 };
 
+// 3x5 button icons, drawn on key caps by `input::draw_button`
+pub const ICON_UP: Glyph = Glyph {
+    rows: [
+        0b01000000, 0b11100000, 0b01000000, 0b01000000, 0b01000000, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000,
+    ],
+};
+pub const ICON_DOWN: Glyph = Glyph {
+    rows: [
+        0b01000000, 0b01000000, 0b01000000, 0b11100000, 0b01000000, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000,
+    ],
+};
+pub const ICON_LEFT: Glyph = Glyph {
+    rows: [
+        0b00100000, 0b01100000, 0b11100000, 0b01100000, 0b00100000, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000,
+    ],
+};
+pub const ICON_RIGHT: Glyph = Glyph {
+    rows: [
+        0b10000000, 0b11000000, 0b11100000, 0b11000000, 0b10000000, 0b00000000, 0b00000000,
+        0b00000000, 0b00000000, 0b00000000,
+    ],
+};
+
 pub fn get_glyph(c: char) -> Option<&'static Glyph> {
     match c {
         '0' => Some(&FONT_0),
@@ -282,7 +321,82 @@ pub fn get_glyph(c: char) -> Option<&'static Glyph> {
         'Z' => Some(&FONT_Z),
         ':' => Some(&FONT_COLON),
         '/' => Some(&FONT_SLASH),
+        '+' => Some(&FONT_PLUS),
+        '-' => Some(&FONT_MINUS),
         ' ' => Some(&FONT_SPACE),
         _ => None,
     }
 }
+
+pub const FONT_MAGIC: [u8; 4] = *b"BBFN";
+pub const FONT_VERSION: u8 = 1;
+
+const FONT_HEADER_SIZE: usize = 8;
+
+/// A variable-width bitmap font converted by `spritec --font`.
+///
+/// Glyphs are up to 8 pixels wide and 10 tall, stored as `Glyph` rows plus
+/// a per-glyph advance width. The layout is described in
+/// `docs/sprite_format.md`.
+#[derive(Debug, Clone, Copy)]
+pub struct Font {
+    data: &'static [u8],
+    height: usize,
+    first: u8,
+    count: usize,
+}
+
+impl Font {
+    /// Check the header and size. `None` if `data` is not a font of this
+    /// version.
+    pub const fn new(data: &'static [u8]) -> Option<Self> {
+        if data.len() < FONT_HEADER_SIZE
+            || data[0] != FONT_MAGIC[0]
+            || data[1] != FONT_MAGIC[1]
+            || data[2] != FONT_MAGIC[2]
+            || data[3] != FONT_MAGIC[3]
+            || data[4] != FONT_VERSION
+        {
+            return None;
+        }
+
+        let height = data[5] as usize;
+        let count = data[7] as usize;
+
+        if height == 0 || height > 10 || data.len() < FONT_HEADER_SIZE + count * (1 + height) {
+            return None;
+        }
+
+        Some(Self {
+            data,
+            height,
+            first: data[6],
+            count,
+        })
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The glyph for `c` and its width in pixels. Zero-width entries are
+    /// gaps between the characters of a `--chars` font, so `None`.
+    pub fn glyph(&self, c: char) -> Option<(Glyph, usize)> {
+        let index = (c as u32).checked_sub(self.first as u32)? as usize;
+
+        if index >= self.count {
+            return None;
+        }
+
+        let at = FONT_HEADER_SIZE + index * (1 + self.height);
+
+        if self.data[at] == 0 {
+            return None;
+        }
+
+        let mut glyph = Glyph { rows: [0; 10] };
+        glyph.rows[..self.height].copy_from_slice(&self.data[at + 1..at + 1 + self.height]);
+
+        Some((glyph, self.data[at] as usize))
+    }
+}
@@ -1,9 +1,60 @@
-use crate::renderer::font::{Glyph, get_glyph};
+use crate::renderer::effects::Accessibility;
+use crate::renderer::font::{Font, Glyph, get_glyph};
+use crate::renderer::record::DrawCommand;
+#[cfg(feature = "debug_overlay")]
+use crate::renderer::record::DrawRecording;
 
 pub const PALETTE: [u32; 4] = [
     0xFF0F380F, // Darkest
     0xFF306230, 0xFF8BAC0F, 0xFF9BBC0F, // Lightest
 ];
+
+/// Shade each of a sprite's colour indices is drawn in; index 0 stays
+/// transparent whatever it maps to.
+pub type ColorRemap = [u8; 4];
+
+/// Every index drawn as itself.
+pub const NO_REMAP: ColorRemap = [0, 1, 2, 3];
+
+/// Display palettes by name, darkest first. `dmg` is `PALETTE`.
+pub const PALETTES: [(&str, [u32; 4]); 5] = [
+    ("dmg", PALETTE),
+    ("pocket", [0xFF000000, 0xFF555555, 0xFFAAAAAA, 0xFFFFFFFF]),
+    // Black background with the shades spread as far apart as the display
+    // allows, for low vision.
+    ("contrast", [0xFF000000, 0xFF0050FF, 0xFFFFD000, 0xFFFFFFFF]),
+    // Colour-blind presets: every shade stays distinct under simulated
+    // protanopia, deuteranopia and tritanopia (see `tests/palettes.rs`).
+    // Okabe-Ito blue and orange for red-green deficiencies.
+    ("redgreen", [0xFF000000, 0xFF0072B2, 0xFFE69F00, 0xFFFFFFFF]),
+    // Crimson and teal for blue-yellow deficiency.
+    (
+        "blueyellow",
+        [0xFF000000, 0xFF9E1B32, 0xFF58C9C4, 0xFFFFFFFF],
+    ),
+];
+
+/// The palette called `name` in `PALETTES`.
+pub fn palette_named(name: &str) -> Option<[u32; 4]> {
+    PALETTES
+        .iter()
+        .find(|(candidate, _)| *candidate == name)
+        .map(|&(_, palette)| palette)
+}
+
+/// `palette` at half brightness, for a screen left alone.
+pub const fn dim_palette(palette: [u32; 4]) -> [u32; 4] {
+    let mut dimmed = palette;
+    let mut i = 0;
+
+    while i < dimmed.len() {
+        dimmed[i] = (dimmed[i] & 0xFF000000) | ((dimmed[i] >> 1) & 0x007F7F7F);
+        i += 1;
+    }
+
+    dimmed
+}
+
 pub const WIDTH: usize = 160;
 pub const HEIGHT: usize = 144;
 
@@ -14,30 +65,226 @@ pub const LINE_HEIGHT: usize = 6;
 
 pub const FONT_ADVANCE: usize = FONT_WIDTH + FONT_SPACING;
 
+/// How much larger `draw_ui_text` draws with large text on: each glyph
+/// pixel becomes a 2x2 block, an 8-pixel advance.
+pub const LARGE_TEXT_SCALE: usize = 2;
+
+/// Whether a `w`×`h` rectangle at (`x`, `y`) overlaps the screen.
+pub const fn on_screen(x: i32, y: i32, w: usize, h: usize) -> bool {
+    x < WIDTH as i32 && y < HEIGHT as i32 && x + w as i32 > 0 && y + h as i32 > 0
+}
+
 const PIXELS: usize = WIDTH * HEIGHT;
 const BUFFER_SIZE: usize = PIXELS / 4; // 4 pixels per byte
 
+/// Draw counters since the last `reset_stats()`, collected only when the
+/// debug overlay is compiled in.
+#[cfg(feature = "debug_overlay")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RenderStats {
+    pub sprites: u32,
+    /// Sprites and shapes skipped for being wholly off screen.
+    pub culled: u32,
+    pub pixels: u32,
+}
+
+/// Tells a backend whether a frame differs from the last one it converted,
+/// so a menu or pause screen that redraws the same picture every frame is
+/// not converted again.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DirtyCheck {
+    last: Option<(u64, [u32; 4])>,
+}
+
+impl DirtyCheck {
+    pub const fn new() -> Self {
+        Self { last: None }
+    }
+
+    /// Whether `frame`'s pixels or palette changed since the last call.
+    /// Always true on the first.
+    pub fn changed(&mut self, frame: &FrameBuffer) -> bool {
+        let seen = Some((frame.hash(), frame.palette));
+        let changed = seen != self.last;
+        self.last = seen;
+        changed
+    }
+}
+
+#[derive(Clone)]
 pub struct FrameBuffer {
     buffer: [u8; BUFFER_SIZE],
+    /// ARGB colour of each of the four shades. Only backends read it; the
+    /// game draws in shade indices.
+    palette: [u32; 4],
+    /// Display settings every drawing path honours, like the palette.
+    accessibility: Accessibility,
+    /// Letters the built-in font lacks, such as a language's accented
+    /// ones, for `draw_text` and `draw_ui_text` to fall back to.
+    extra_glyphs: Option<Font>,
+    #[cfg(feature = "debug_overlay")]
+    stats: RenderStats,
+    #[cfg(feature = "debug_overlay")]
+    recording: Option<Box<DrawRecording>>,
+}
+
+impl Default for FrameBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl FrameBuffer {
     pub const fn new() -> Self {
         Self {
             buffer: [0; BUFFER_SIZE],
+            palette: PALETTE,
+            accessibility: Accessibility::new(),
+            extra_glyphs: None,
+            #[cfg(feature = "debug_overlay")]
+            stats: RenderStats {
+                sprites: 0,
+                culled: 0,
+                pixels: 0,
+            },
+            #[cfg(feature = "debug_overlay")]
+            recording: None,
         }
     }
 
-    pub fn clear(&mut self, color: u8) {
-        let packed =
-            (color & 0b11) | ((color & 0b11) << 2) | ((color & 0b11) << 4) | ((color & 0b11) << 6);
+    #[cfg(feature = "debug_overlay")]
+    pub fn stats(&self) -> RenderStats {
+        self.stats
+    }
+
+    #[cfg(feature = "debug_overlay")]
+    pub fn reset_stats(&mut self) {
+        self.stats = RenderStats::default();
+    }
+
+    /// Record every draw call from now until `finish_recording`, starting
+    /// over if a recording is already on.
+    #[cfg(feature = "debug_overlay")]
+    pub fn start_recording(&mut self) {
+        self.recording = Some(Box::new(DrawRecording::new(&self.buffer)));
+    }
+
+    /// Stop recording and return the calls, if a recording was on.
+    #[cfg(feature = "debug_overlay")]
+    pub fn finish_recording(&mut self) -> Option<DrawRecording> {
+        self.recording.take().map(|recording| *recording)
+    }
+
+    /// Overwrite the pixels with ones `raw` returned, such as a recorded
+    /// call's.
+    #[cfg(feature = "debug_overlay")]
+    pub fn load_raw(&mut self, pixels: &[u8]) {
+        self.buffer.copy_from_slice(pixels);
+    }
 
-        for byte in self.buffer.iter_mut() {
-            *byte = packed;
+    /// Run `draw`, the body of a drawing method, and keep it in the
+    /// recording as `command` unless another command is drawing it.
+    #[cfg_attr(not(feature = "debug_overlay"), allow(unused_variables))]
+    #[inline]
+    fn recorded<'a, R>(
+        &mut self,
+        command: impl FnOnce() -> DrawCommand<'a>,
+        draw: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        #[cfg(feature = "debug_overlay")]
+        if let Some(recording) = &mut self.recording {
+            recording.depth += 1;
         }
+
+        let result = draw(self);
+
+        #[cfg(feature = "debug_overlay")]
+        if let Some(recording) = &mut self.recording {
+            recording.depth -= 1;
+
+            if recording.depth == 0 {
+                recording.push(command(), &self.buffer);
+            }
+        }
+
+        result
+    }
+
+    pub const fn palette(&self) -> &[u32; 4] {
+        &self.palette
+    }
+
+    pub fn set_palette(&mut self, palette: [u32; 4]) {
+        self.palette = palette;
+    }
+
+    pub const fn accessibility(&self) -> &Accessibility {
+        &self.accessibility
+    }
+
+    /// Kept across frames; `ScreenEffects` and `draw_ui_text` follow it.
+    pub fn set_accessibility(&mut self, accessibility: Accessibility) {
+        self.accessibility = accessibility;
+    }
+
+    /// Kept across frames; see `text::Language::glyphs`.
+    pub fn set_extra_glyphs(&mut self, glyphs: Option<Font>) {
+        self.extra_glyphs = glyphs;
+    }
+
+    /// The built-in glyph for `c`, else the extra glyphs' one.
+    fn text_glyph(&self, c: char) -> Option<Glyph> {
+        match get_glyph(c) {
+            Some(glyph) => Some(*glyph),
+            None => Some(self.extra_glyphs.as_ref()?.glyph(c)?.0),
+        }
+    }
+
+    /// 1, or `LARGE_TEXT_SCALE` with large text on. Debug tools keep the
+    /// small font, which their layouts are built around.
+    const fn ui_scale(&self) -> usize {
+        if self.accessibility.large_text {
+            LARGE_TEXT_SCALE
+        } else {
+            1
+        }
+    }
+
+    /// Horizontal distance between `draw_ui_text` glyphs.
+    pub const fn ui_font_advance(&self) -> usize {
+        FONT_ADVANCE * self.ui_scale()
+    }
+
+    /// Vertical distance between `draw_ui_text` lines.
+    pub const fn ui_line_height(&self) -> usize {
+        LINE_HEIGHT * self.ui_scale()
+    }
+
+    pub fn clear(&mut self, color: u8) {
+        self.recorded(
+            || DrawCommand::Clear { color },
+            |fb| {
+                let packed = (color & 0b11)
+                    | ((color & 0b11) << 2)
+                    | ((color & 0b11) << 4)
+                    | ((color & 0b11) << 6);
+
+                for byte in fb.buffer.iter_mut() {
+                    *byte = packed;
+                }
+            },
+        );
     }
 
     pub fn set_pixel(&mut self, x: usize, y: usize, color: u8) {
+        self.recorded(
+            || DrawCommand::Pixel { x, y, color },
+            |fb| fb.put_pixel(x, y, color),
+        );
+    }
+
+    /// `set_pixel` as part of another command.
+    fn put_pixel(&mut self, x: usize, y: usize, color: u8) {
         if x >= WIDTH || y >= HEIGHT {
             return;
         }
@@ -51,6 +298,11 @@ impl FrameBuffer {
 
         let byte = &mut self.buffer[byte_index];
         *byte = (*byte & mask) | value;
+
+        #[cfg(feature = "debug_overlay")]
+        {
+            self.stats.pixels += 1;
+        }
     }
 
     #[allow(dead_code)]
@@ -71,55 +323,216 @@ impl FrameBuffer {
         &self.buffer
     }
 
+    /// FNV-1a hash of the packed pixels, for comparing frames.
+    pub fn hash(&self) -> u64 {
+        let mut hash = crate::runtime::hash::Fnv1a::new();
+        hash.write_bytes(&self.buffer);
+        hash.finish()
+    }
+
     pub fn to_rgba_buffer(&self, out: &mut [u32]) {
         for y in 0..HEIGHT {
             for x in 0..WIDTH {
                 let color_index = self.get_pixel(x, y) as usize;
-                out[y * WIDTH + x] = PALETTE[color_index];
+                out[y * WIDTH + x] = self.palette[color_index];
             }
         }
     }
 
+    /// Fill a `w`×`h` rectangle. Pixels outside the screen are skipped.
+    pub fn fill_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: u8) {
+        self.recorded(
+            || DrawCommand::FillRect { x, y, w, h, color },
+            |fb| {
+                for py in y..(y + h).min(HEIGHT) {
+                    for px in x..(x + w).min(WIDTH) {
+                        fb.put_pixel(px, py, color);
+                    }
+                }
+            },
+        );
+    }
+
+    /// Draw a one-pixel `w`×`h` rectangle outline.
+    pub fn draw_rect(&mut self, x: usize, y: usize, w: usize, h: usize, color: u8) {
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        self.recorded(
+            || DrawCommand::Rect { x, y, w, h, color },
+            |fb| {
+                fb.fill_rect(x, y, w, 1, color);
+                fb.fill_rect(x, y + h - 1, w, 1, color);
+                fb.fill_rect(x, y, 1, h, color);
+                fb.fill_rect(x + w - 1, y, 1, h, color);
+            },
+        );
+    }
+
     pub fn draw_char(&mut self, x: usize, y: usize, glyph: &Glyph, color: u8) {
-        for row in 0..FONT_HEIGHT {
-            let bits = glyph.rows[row];
+        self.draw_glyph(x, y, glyph, FONT_WIDTH, FONT_HEIGHT, color);
+    }
 
-            for col in 0..FONT_WIDTH {
-                if (bits >> (7 - col)) & 1 == 1 {
-                    self.set_pixel(x + col, y + row, color);
+    /// Draw the top-left `width` × `height` pixels of `glyph`.
+    pub fn draw_glyph(
+        &mut self,
+        x: usize,
+        y: usize,
+        glyph: &Glyph,
+        width: usize,
+        height: usize,
+        color: u8,
+    ) {
+        self.recorded(
+            || DrawCommand::Glyph { x, y, color },
+            |fb| {
+                for row in 0..height.min(glyph.rows.len()) {
+                    let bits = glyph.rows[row];
+
+                    for col in 0..width.min(8) {
+                        if (bits >> (7 - col)) & 1 == 1 {
+                            fb.put_pixel(x + col, y + row, color);
+                        }
+                    }
                 }
-            }
-        }
+            },
+        );
     }
 
-    pub fn draw_text(&mut self, mut x: usize, y: usize, text: &str, color: u8) {
-        for c in text.chars() {
-            if let Some(glyph) = get_glyph(c) {
-                self.draw_char(x, y, glyph, color);
-            }
+    pub fn draw_text(&mut self, x: usize, y: usize, text: &str, color: u8) {
+        self.recorded(
+            || DrawCommand::Text { x, y, text, color },
+            |fb| {
+                let mut x = x;
 
-            x += FONT_ADVANCE;
-        }
+                for c in text.chars() {
+                    if let Some(glyph) = fb.text_glyph(c) {
+                        fb.draw_char(x, y, &glyph, color);
+                    }
+
+                    x += FONT_ADVANCE;
+                }
+            },
+        );
     }
 
-    pub fn draw_u32(&mut self, mut x: usize, y: usize, value: u32, digits: usize, color: u8) {
-        let mut temp = [0u8; 10];
-        let mut n = value;
+    /// Draw game UI text: `draw_text`, enlarged when large text is on.
+    pub fn draw_ui_text(&mut self, x: usize, y: usize, text: &str, color: u8) {
+        let scale = self.ui_scale();
+
+        self.recorded(
+            || DrawCommand::Text { x, y, text, color },
+            |fb| {
+                let mut x = x;
+
+                for c in text.chars() {
+                    if let Some(glyph) = fb.text_glyph(c) {
+                        for row in 0..FONT_HEIGHT {
+                            for col in 0..FONT_WIDTH {
+                                if (glyph.rows[row] >> (7 - col)) & 1 == 1 {
+                                    let (px, py) = (x + col * scale, y + row * scale);
+                                    fb.fill_rect(px, py, scale, scale, color);
+                                }
+                            }
+                        }
+                    }
+
+                    x += FONT_ADVANCE * scale;
+                }
+            },
+        );
+    }
 
-        for i in (0..digits).rev() {
-            temp[i] = (n % 10) as u8;
-            n /= 10;
-        }
+    /// Swap every shade for its opposite (0 and 3, 1 and 2).
+    pub fn invert(&mut self) {
+        self.recorded(
+            || DrawCommand::Invert,
+            |fb| {
+                for byte in fb.buffer.iter_mut() {
+                    *byte = !*byte;
+                }
+            },
+        );
+    }
 
-        for i in 0..digits {
-            let c = (b'0' + temp[i]) as char;
+    /// Move the picture by (`dx`, `dy`) pixels, filling the uncovered edge
+    /// with `fill`.
+    pub fn shift(&mut self, dx: i32, dy: i32, fill: u8) {
+        self.recorded(
+            || DrawCommand::Shift { dx, dy, fill },
+            |fb| {
+                let source = fb.buffer;
+                let pixel = |x: i32, y: i32| {
+                    if x < 0 || y < 0 || x >= WIDTH as i32 || y >= HEIGHT as i32 {
+                        return fill;
+                    }
+
+                    let index = y as usize * WIDTH + x as usize;
+                    (source[index / 4] >> ((index % 4) * 2)) & 0b11
+                };
+
+                for y in 0..HEIGHT {
+                    for x in 0..WIDTH {
+                        let color = pixel(x as i32 - dx, y as i32 - dy);
+                        fb.put_pixel(x, y, color);
+                    }
+                }
+            },
+        );
+    }
 
-            if let Some(glyph) = get_glyph(c) {
-                self.draw_char(x, y, glyph, color);
-            }
+    /// Draw `text` in a variable-width `font`, one pixel between glyphs.
+    /// Returns the x just past the last glyph.
+    pub fn draw_text_font(
+        &mut self,
+        font: &Font,
+        x: usize,
+        y: usize,
+        text: &str,
+        color: u8,
+    ) -> usize {
+        self.recorded(
+            || DrawCommand::Text { x, y, text, color },
+            |fb| {
+                let mut x = x;
+
+                for c in text.chars() {
+                    if let Some((glyph, width)) = font.glyph(c) {
+                        fb.draw_glyph(x, y, &glyph, width, font.height(), color);
+                        x += width + FONT_SPACING;
+                    }
+                }
 
-            x += FONT_ADVANCE;
-        }
+                x
+            },
+        )
+    }
+
+    pub fn draw_u32(&mut self, x: usize, y: usize, value: u32, digits: usize, color: u8) {
+        self.recorded(
+            || DrawCommand::Number { x, y, value, color },
+            |fb| {
+                let mut temp = [0u8; 10];
+                let mut n = value;
+                let mut x = x;
+
+                for i in (0..digits).rev() {
+                    temp[i] = (n % 10) as u8;
+                    n /= 10;
+                }
+
+                for digit in temp.iter().take(digits) {
+                    let c = (b'0' + digit) as char;
+
+                    if let Some(glyph) = get_glyph(c) {
+                        fb.draw_char(x, y, glyph, color);
+                    }
+
+                    x += FONT_ADVANCE;
+                }
+            },
+        );
     }
 
     /// Decode and render a single 8×8 tile from 2bpp planar data.
@@ -127,12 +540,50 @@ impl FrameBuffer {
     /// `tile_data` must be exactly 16 bytes: 2 bytes per row (low-plane, high-plane),
     /// MSB-first pixel ordering (bit 7 = leftmost pixel).
     pub fn draw_tile(&mut self, x: i32, y: i32, tile_data: &[u8]) {
+        self.draw_tile_flipped(x, y, tile_data, false, false);
+    }
+
+    /// `draw_tile` mirrored left-to-right with `flip_x` and top-to-bottom
+    /// with `flip_y`, for tile maps that store a tile once for its mirror
+    /// images.
+    pub fn draw_tile_flipped(
+        &mut self,
+        x: i32,
+        y: i32,
+        tile_data: &[u8],
+        flip_x: bool,
+        flip_y: bool,
+    ) {
+        self.recorded(
+            || DrawCommand::Tile {
+                x,
+                y,
+                flip_x,
+                flip_y,
+            },
+            |fb| fb.blit_tile(x, y, tile_data, false, (flip_x, flip_y), &NO_REMAP),
+        );
+    }
+
+    /// Like `draw_tile`, with `dither` skipping every other screen pixel in
+    /// a checkerboard, `flip` mirroring along x and y and `remap` picking
+    /// the shades.
+    fn blit_tile(
+        &mut self,
+        x: i32,
+        y: i32,
+        tile_data: &[u8],
+        dither: bool,
+        (flip_x, flip_y): (bool, bool),
+        remap: &ColorRemap,
+    ) {
         for row in 0..8 {
-            let low = tile_data[row * 2];
-            let high = tile_data[row * 2 + 1];
+            let source = if flip_y { 7 - row } else { row };
+            let low = tile_data[source * 2];
+            let high = tile_data[source * 2 + 1];
 
             for col in 0..8 {
-                let bit = 7 - col;
+                let bit = if flip_x { col } else { 7 - col };
                 let index = ((low >> bit) & 1) | (((high >> bit) & 1) << 1);
 
                 // Skip transparent (index 0)
@@ -140,14 +591,18 @@ impl FrameBuffer {
                     continue;
                 }
 
-                let sx = x + col as i32;
+                let sx = x + col;
                 let sy = y + row as i32;
 
                 if sx < 0 || sy < 0 || sx >= WIDTH as i32 || sy >= HEIGHT as i32 {
                     continue;
                 }
 
-                self.set_pixel(sx as usize, sy as usize, index);
+                if dither && (sx + sy) % 2 != 0 {
+                    continue;
+                }
+
+                self.put_pixel(sx as usize, sy as usize, remap[index as usize]);
             }
         }
     }
@@ -157,17 +612,104 @@ impl FrameBuffer {
     /// The sprite's data is a sequence of tiles stored in row-major order
     /// (left-to-right, top-to-bottom). Each tile is 16 bytes of 2bpp planar data.
     pub fn draw_sprite(&mut self, x: i32, y: i32, sprite: &crate::renderer::sprite::Sprite) {
+        self.blit_sprite(x, y, sprite, false, &NO_REMAP);
+    }
+
+    /// Draw `sprite` over every other pixel, so what is behind it shows
+    /// through: the 4-shade screen's stand-in for transparency.
+    pub fn draw_sprite_dithered(
+        &mut self,
+        x: i32,
+        y: i32,
+        sprite: &crate::renderer::sprite::Sprite,
+    ) {
+        self.blit_sprite(x, y, sprite, true, &NO_REMAP);
+    }
+
+    /// Draw `sprite` with its colours swapped through `remap`, as the game
+    /// tints an entity under a status effect.
+    pub fn draw_sprite_remapped(
+        &mut self,
+        x: i32,
+        y: i32,
+        sprite: &crate::renderer::sprite::Sprite,
+        remap: &ColorRemap,
+    ) {
+        self.blit_sprite(x, y, sprite, false, remap);
+    }
+
+    fn blit_sprite(
+        &mut self,
+        x: i32,
+        y: i32,
+        sprite: &crate::renderer::sprite::Sprite,
+        dither: bool,
+        remap: &ColorRemap,
+    ) {
+        let (w, h) = (sprite.tiles_x * 8, sprite.tiles_y * 8);
+
+        self.recorded(
+            || DrawCommand::Sprite {
+                x,
+                y,
+                w,
+                h,
+                dithered: dither,
+                remapped: remap != &NO_REMAP,
+            },
+            |fb| fb.blit_sprite_tiles(x, y, sprite, dither, remap),
+        );
+    }
+
+    fn blit_sprite_tiles(
+        &mut self,
+        x: i32,
+        y: i32,
+        sprite: &crate::renderer::sprite::Sprite,
+        dither: bool,
+        remap: &ColorRemap,
+    ) {
+        if self.cull(x, y, sprite.tiles_x * 8, sprite.tiles_y * 8) {
+            return;
+        }
+
+        #[cfg(feature = "debug_overlay")]
+        {
+            self.stats.sprites += 1;
+        }
+
         for ty in 0..sprite.tiles_y {
             for tx in 0..sprite.tiles_x {
+                let tile_x = x + (tx * 8) as i32;
+                let tile_y = y + (ty * 8) as i32;
+
+                // Tiles hanging off the edge are not decoded either.
+                if !on_screen(tile_x, tile_y, 8, 8) {
+                    continue;
+                }
+
                 let tile_index = ty * sprite.tiles_x + tx;
                 let offset = tile_index * 16;
                 let tile_data = &sprite.data[offset..offset + 16];
 
-                let tile_x = x + (tx * 8) as i32;
-                let tile_y = y + (ty * 8) as i32;
-
-                self.draw_tile(tile_x, tile_y, tile_data);
+                self.blit_tile(tile_x, tile_y, tile_data, dither, (false, false), remap);
             }
         }
     }
+
+    /// Whether a `w`×`h` object at (`x`, `y`) is wholly off screen, in
+    /// which case it is counted as culled and should not be drawn. Sprites
+    /// are culled on their own; shapes drawn pixel by pixel ask first.
+    pub fn cull(&mut self, x: i32, y: i32, w: usize, h: usize) -> bool {
+        if on_screen(x, y, w, h) {
+            return false;
+        }
+
+        #[cfg(feature = "debug_overlay")]
+        {
+            self.stats.culled += 1;
+        }
+
+        true
+    }
 }
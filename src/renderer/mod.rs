@@ -1,3 +1,9 @@
+pub mod effects;
 pub mod font;
 pub mod framebuffer;
+#[cfg(feature = "std")]
+pub mod png;
+pub mod record;
 pub mod sprite;
+pub mod tilemap;
+pub mod transition;
@@ -0,0 +1,110 @@
+//! Minimal PNG encoder for framebuffer screenshots.
+//!
+//! Writes an 8-bit indexed image using the framebuffer palette, with the
+//! pixel data in uncompressed ("stored") deflate blocks. Files are larger
+//! than a real compressor would produce (about 23 KB unscaled), but the encoder has no
+//! dependencies and cannot fail except on I/O.
+
+use std::io::{self, Write};
+
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Largest payload of one stored deflate block.
+const MAX_STORED_BLOCK: usize = 0xffff;
+
+fn crc32(chunks: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+
+    for chunk in chunks {
+        for &byte in *chunk {
+            crc ^= byte as u32;
+
+            for _ in 0..8 {
+                let mask = (crc & 1).wrapping_neg();
+                crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+            }
+        }
+    }
+
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+
+    (b << 16) | a
+}
+
+fn write_chunk(out: &mut impl Write, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    out.write_all(&crc32(&[kind, data]).to_be_bytes())
+}
+
+/// Encode the framebuffer as a PNG.
+pub fn write_png(framebuffer: &FrameBuffer, out: &mut impl Write) -> io::Result<()> {
+    write_png_scaled(framebuffer, 1, out)
+}
+
+/// Encode the framebuffer as a PNG with every pixel drawn as a `scale` ×
+/// `scale` block.
+pub fn write_png_scaled(
+    framebuffer: &FrameBuffer,
+    scale: usize,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    let scale = scale.max(1);
+    let (width, height) = (WIDTH * scale, HEIGHT * scale);
+
+    // Filter type 0 (none) before every row, then one palette index per pixel.
+    let mut pixels = Vec::with_capacity(height * (width + 1));
+
+    for y in 0..height {
+        pixels.push(0);
+
+        for x in 0..width {
+            pixels.push(framebuffer.get_pixel(x / scale, y / scale));
+        }
+    }
+
+    // zlib header (deflate, 32 KB window, no preset dictionary).
+    let mut zlib = vec![0x78, 0x01];
+    let blocks = pixels.chunks(MAX_STORED_BLOCK);
+    let last = blocks.len() - 1;
+
+    for (i, block) in blocks.enumerate() {
+        let len = block.len() as u16;
+        zlib.push((i == last) as u8);
+        zlib.extend_from_slice(&len.to_le_bytes());
+        zlib.extend_from_slice(&(!len).to_le_bytes());
+        zlib.extend_from_slice(block);
+    }
+
+    zlib.extend_from_slice(&adler32(&pixels).to_be_bytes());
+
+    let mut header = [0u8; 13];
+    header[..4].copy_from_slice(&(width as u32).to_be_bytes());
+    header[4..8].copy_from_slice(&(height as u32).to_be_bytes());
+    header[8] = 8; // bit depth
+    header[9] = 3; // indexed color
+
+    let mut palette = [0u8; 4 * 3];
+
+    for (rgb, color) in palette.chunks_exact_mut(3).zip(framebuffer.palette()) {
+        rgb.copy_from_slice(&color.to_be_bytes()[1..]);
+    }
+
+    out.write_all(&SIGNATURE)?;
+    write_chunk(out, b"IHDR", &header)?;
+    write_chunk(out, b"PLTE", &palette)?;
+    write_chunk(out, b"IDAT", &zlib)?;
+    write_chunk(out, b"IEND", &[])
+}
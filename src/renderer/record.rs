@@ -0,0 +1,212 @@
+//! Draw-call recording, for stepping through how a frame was drawn.
+//!
+//! Every drawing method on `FrameBuffer` describes itself as a
+//! `DrawCommand`. With the debug overlay compiled in, a recording started
+//! with `FrameBuffer::start_recording` keeps each command the game issues,
+//! with a copy of the pixels after it, until `finish_recording` hands it
+//! over. Commands a command issues itself, like the glyphs of a text, are
+//! part of the outer one; single pixels set in a row are kept as one call.
+//! Without the overlay, the descriptions are never built.
+
+use core::fmt;
+
+/// A drawing method and its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawCommand<'a> {
+    Clear {
+        color: u8,
+    },
+    Pixel {
+        x: usize,
+        y: usize,
+        color: u8,
+    },
+    FillRect {
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        color: u8,
+    },
+    Rect {
+        x: usize,
+        y: usize,
+        w: usize,
+        h: usize,
+        color: u8,
+    },
+    Glyph {
+        x: usize,
+        y: usize,
+        color: u8,
+    },
+    /// Any of the text methods.
+    Text {
+        x: usize,
+        y: usize,
+        text: &'a str,
+        color: u8,
+    },
+    Number {
+        x: usize,
+        y: usize,
+        value: u32,
+        color: u8,
+    },
+    Invert,
+    Shift {
+        dx: i32,
+        dy: i32,
+        fill: u8,
+    },
+    Tile {
+        x: i32,
+        y: i32,
+        flip_x: bool,
+        flip_y: bool,
+    },
+    Sprite {
+        x: i32,
+        y: i32,
+        w: usize,
+        h: usize,
+        dithered: bool,
+        remapped: bool,
+    },
+}
+
+/// In the built-in font's characters, for the frame debugger.
+impl fmt::Display for DrawCommand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Clear { color } => write!(f, "CLEAR C{color}"),
+            Self::Pixel { x, y, color } => write!(f, "PIXEL {x} {y} C{color}"),
+            Self::FillRect { x, y, w, h, color } => write!(f, "FILL {x} {y} {w}X{h} C{color}"),
+            Self::Rect { x, y, w, h, color } => write!(f, "RECT {x} {y} {w}X{h} C{color}"),
+            Self::Glyph { x, y, color } => write!(f, "GLYPH {x} {y} C{color}"),
+            Self::Text { x, y, text, color } => write!(f, "TEXT {x} {y} C{color} {text}"),
+            Self::Number { x, y, value, color } => write!(f, "NUMBER {x} {y} C{color} {value}"),
+            Self::Invert => write!(f, "INVERT"),
+            Self::Shift { dx, dy, fill } => write!(f, "SHIFT {dx} {dy} C{fill}"),
+            Self::Tile {
+                x,
+                y,
+                flip_x,
+                flip_y,
+            } => {
+                write!(f, "TILE {x} {y}")?;
+
+                match (flip_x, flip_y) {
+                    (false, false) => Ok(()),
+                    (true, false) => write!(f, " FLIP X"),
+                    (false, true) => write!(f, " FLIP Y"),
+                    (true, true) => write!(f, " FLIP XY"),
+                }
+            }
+            Self::Sprite {
+                x,
+                y,
+                w,
+                h,
+                dithered,
+                remapped,
+            } => {
+                write!(f, "SPRITE {x} {y} {w}X{h}")?;
+
+                if dithered {
+                    write!(f, " DITHER")?;
+                }
+
+                if remapped {
+                    write!(f, " REMAP")?;
+                }
+
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Most calls a recording keeps; later ones are only counted.
+#[cfg(feature = "debug_overlay")]
+pub const MAX_DRAW_CALLS: usize = 1024;
+
+#[cfg(feature = "debug_overlay")]
+#[derive(Debug, Clone)]
+pub struct DrawCall {
+    /// The command as `DrawCommand` shows it.
+    pub command: String,
+    /// Calls in a row kept as this one; more than 1 only for pixels.
+    pub repeat: u32,
+    pixel: bool,
+    /// The packed pixels after the call, as `FrameBuffer::raw` returns
+    /// them.
+    pixels: Vec<u8>,
+}
+
+#[cfg(feature = "debug_overlay")]
+impl DrawCall {
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+}
+
+#[cfg(feature = "debug_overlay")]
+#[derive(Debug, Clone)]
+pub struct DrawRecording {
+    /// The pixels before the first call.
+    before: Vec<u8>,
+    calls: Vec<DrawCall>,
+    /// Calls past `MAX_DRAW_CALLS`.
+    dropped: u32,
+    /// Commands under way, so only the outermost is kept.
+    pub(crate) depth: u32,
+}
+
+#[cfg(feature = "debug_overlay")]
+impl DrawRecording {
+    /// A recording of a frame that starts out as `pixels`.
+    pub(crate) fn new(pixels: &[u8]) -> Self {
+        Self {
+            before: pixels.to_vec(),
+            calls: Vec::new(),
+            dropped: 0,
+            depth: 0,
+        }
+    }
+
+    pub fn before(&self) -> &[u8] {
+        &self.before
+    }
+
+    pub fn calls(&self) -> &[DrawCall] {
+        &self.calls
+    }
+
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Keep `command`, which left the frame as `pixels`.
+    pub(crate) fn push(&mut self, command: DrawCommand<'_>, pixels: &[u8]) {
+        let pixel = matches!(command, DrawCommand::Pixel { .. });
+
+        if pixel && let Some(last) = self.calls.last_mut().filter(|last| last.pixel) {
+            last.repeat += 1;
+            last.pixels.copy_from_slice(pixels);
+            return;
+        }
+
+        if self.calls.len() >= MAX_DRAW_CALLS {
+            self.dropped += 1;
+            return;
+        }
+
+        self.calls.push(DrawCall {
+            command: command.to_string(),
+            repeat: 1,
+            pixel,
+            pixels: pixels.to_vec(),
+        });
+    }
+}
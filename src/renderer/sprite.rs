@@ -10,3 +10,66 @@ pub struct Sprite {
     pub tiles_y: usize,
     pub data: &'static [u8],
 }
+
+/// One frame of an `Animation`.
+pub struct AnimationFrame {
+    pub sprite: &'static Sprite,
+    /// How many ticks the frame is shown. Never zero.
+    pub ticks: u32,
+}
+
+/// A looping sequence of sprites, usually generated from `anim` lines in
+/// `assets/assets.manifest`.
+pub struct Animation {
+    frames: &'static [AnimationFrame],
+    total_ticks: u32,
+}
+
+impl Animation {
+    /// In a `const` item, an empty animation or a zero-tick frame is a
+    /// compile error.
+    pub const fn new(frames: &'static [AnimationFrame]) -> Self {
+        assert!(!frames.is_empty(), "an animation needs at least one frame");
+
+        let mut total_ticks = 0;
+        let mut i = 0;
+
+        while i < frames.len() {
+            assert!(
+                frames[i].ticks > 0,
+                "animation frames last at least one tick"
+            );
+            total_ticks += frames[i].ticks;
+            i += 1;
+        }
+
+        Self {
+            frames,
+            total_ticks,
+        }
+    }
+
+    pub const fn frames(&self) -> &'static [AnimationFrame] {
+        self.frames
+    }
+
+    /// Length of one loop, in ticks.
+    pub const fn total_ticks(&self) -> u32 {
+        self.total_ticks
+    }
+
+    /// The sprite to draw `tick` ticks after the animation started.
+    pub fn sprite_at(&self, tick: u32) -> &'static Sprite {
+        let mut remaining = tick % self.total_ticks;
+
+        for frame in self.frames {
+            if remaining < frame.ticks {
+                return frame.sprite;
+            }
+
+            remaining -= frame.ticks;
+        }
+
+        unreachable!("the ticks of the frames add up to total_ticks")
+    }
+}
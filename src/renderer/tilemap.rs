@@ -0,0 +1,58 @@
+/// Map attribute bit: draw the tile mirrored left-to-right.
+pub const ATTR_FLIP_X: u8 = 1 << 5;
+/// Map attribute bit: draw the tile mirrored top-to-bottom.
+pub const ATTR_FLIP_Y: u8 = 1 << 6;
+
+/// A picture made of 8×8 tiles, as `spritec --tilemap` writes it: a
+/// deduplicated tileset and a map of two bytes per cell, the tile index
+/// and its flip attributes (see `docs/sprite_format.md`). Usually
+/// generated from `tilemap` lines in `assets/assets.manifest`, which
+/// checks that every cell names a tile of the set.
+pub struct TileMap {
+    /// Size in tiles.
+    pub width: usize,
+    pub height: usize,
+    /// 16 bytes of 2bpp planar data per tile.
+    pub tiles: &'static [u8],
+    /// `width * height` cells, row-major.
+    pub map: &'static [u8],
+}
+
+impl TileMap {
+    pub const fn pixel_width(&self) -> usize {
+        self.width * 8
+    }
+
+    pub const fn pixel_height(&self) -> usize {
+        self.height * 8
+    }
+
+    /// Draw the map with its top-left corner at (`x`, `y`). Colour 0 is
+    /// transparent, as in sprites, and cells off screen are skipped.
+    pub fn draw(
+        &self,
+        framebuffer: &mut crate::renderer::framebuffer::FrameBuffer,
+        x: i32,
+        y: i32,
+    ) {
+        if framebuffer.cull(x, y, self.pixel_width(), self.pixel_height()) {
+            return;
+        }
+
+        for ty in 0..self.height {
+            for tx in 0..self.width {
+                let cell = (ty * self.width + tx) * 2;
+                let (index, attributes) = (self.map[cell] as usize, self.map[cell + 1]);
+                let tile = &self.tiles[index * 16..index * 16 + 16];
+
+                framebuffer.draw_tile_flipped(
+                    x + tx as i32 * 8,
+                    y + ty as i32 * 8,
+                    tile,
+                    attributes & ATTR_FLIP_X != 0,
+                    attributes & ATTR_FLIP_Y != 0,
+                );
+            }
+        }
+    }
+}
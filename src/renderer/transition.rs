@@ -0,0 +1,140 @@
+//! Scene transitions: a horizontal wipe, an iris around a point and a
+//! checkerboard dissolve of 8×8 tiles.
+//!
+//! A transition covers the finished frame with colour 0, more of it every
+//! tick when going `Out` and less when coming `In`. Like the other screen
+//! effects it is presentation only; `ScreenEffects` ticks it and applies
+//! it last, so nothing drawn later shows through the cover.
+
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+
+/// `cover` of a fully covered screen.
+const COVER_ONE: u32 = 256;
+
+/// Order in which the dissolve covers the tiles of each 4×4 block. At
+/// half-way the covered tiles form a checkerboard.
+const DISSOLVE_ORDER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransitionKind {
+    /// Covers from the left edge to the right.
+    Wipe,
+    /// A circle around (`x`, `y`) closes, or opens, on the picture.
+    Iris { x: i32, y: i32 },
+    /// 8×8 tiles cover in a checkerboard-like order.
+    Dissolve,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// From the picture to a covered screen.
+    Out,
+    /// From a covered screen to the picture.
+    In,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Transition {
+    kind: TransitionKind,
+    direction: Direction,
+    /// Ticks run so far.
+    ticks: u16,
+    length: u16,
+}
+
+impl Transition {
+    /// A transition lasting `length` ticks, at least one.
+    pub const fn new(kind: TransitionKind, direction: Direction, length: u16) -> Self {
+        Self {
+            kind,
+            direction,
+            ticks: 0,
+            length: if length == 0 { 1 } else { length },
+        }
+    }
+
+    pub const fn is_done(&self) -> bool {
+        self.ticks >= self.length
+    }
+
+    pub fn tick(&mut self) {
+        self.ticks = (self.ticks + 1).min(self.length);
+    }
+
+    /// How much of the screen is covered, from 0 to `COVER_ONE`.
+    fn cover(&self) -> u32 {
+        let done = self.ticks as u32 * COVER_ONE / self.length as u32;
+
+        match self.direction {
+            Direction::Out => done,
+            Direction::In => COVER_ONE - done,
+        }
+    }
+
+    /// Cover `framebuffer` as far as the transition has got.
+    pub fn apply(&self, framebuffer: &mut FrameBuffer) {
+        let cover = self.cover();
+
+        if cover == 0 {
+            return;
+        }
+
+        if cover >= COVER_ONE {
+            framebuffer.clear(0);
+            return;
+        }
+
+        match self.kind {
+            TransitionKind::Wipe => {
+                let width = WIDTH * cover as usize / COVER_ONE as usize;
+                // Coming in, the cover leaves by the right edge, so the
+                // wipe keeps its direction.
+                let x = match self.direction {
+                    Direction::Out => 0,
+                    Direction::In => WIDTH - width,
+                };
+
+                framebuffer.fill_rect(x, 0, width, HEIGHT, 0);
+            }
+            TransitionKind::Iris { x, y } => iris(framebuffer, x, y, cover),
+            TransitionKind::Dissolve => {
+                let covered = (cover * 16 / COVER_ONE) as u8;
+
+                for ty in 0..HEIGHT.div_ceil(8) {
+                    for tx in 0..WIDTH.div_ceil(8) {
+                        if DISSOLVE_ORDER[ty % 4][tx % 4] < covered {
+                            framebuffer.fill_rect(tx * 8, ty * 8, 8, 8, 0);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Cover everything outside the circle around (`cx`, `cy`) that shrinks to
+/// nothing as `cover` reaches `COVER_ONE`.
+fn iris(framebuffer: &mut FrameBuffer, cx: i32, cy: i32, cover: u32) {
+    // The farthest corner, so the open iris shows the whole screen.
+    let far_x = cx.max(WIDTH as i32 - cx) as i64;
+    let far_y = cy.max(HEIGHT as i32 - cy) as i64;
+    let full = (far_x * far_x + far_y * far_y).isqrt() + 1;
+    let radius = full * (COVER_ONE - cover) as i64 / COVER_ONE as i64;
+
+    for y in 0..HEIGHT {
+        let dy = y as i64 - cy as i64;
+        let left_over = radius * radius - dy * dy;
+
+        if left_over < 0 {
+            framebuffer.fill_rect(0, y, WIDTH, 1, 0);
+            continue;
+        }
+
+        let half = left_over.isqrt();
+        let left = (cx as i64 - half).clamp(0, WIDTH as i64) as usize;
+        let right = (cx as i64 + half + 1).clamp(0, WIDTH as i64) as usize;
+
+        framebuffer.fill_rect(0, y, left, 1, 0);
+        framebuffer.fill_rect(right, y, WIDTH - right, 1, 0);
+    }
+}
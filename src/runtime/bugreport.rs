@@ -0,0 +1,83 @@
+//! Visual bug reports: a screenshot plus the overlay's numbers.
+//!
+//! The runtime calls `capture()` after every rendered frame. A report holds
+//! the last captured frame, so it shows what the player saw just before the
+//! failure. Reports are written by the panic hook (see `runtime::crash`) and
+//! on demand from a hotkey.
+
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::renderer::framebuffer::FrameBuffer;
+use crate::renderer::png;
+use crate::runtime::debug::DebugInfo;
+
+/// Where reports are written, relative to the working directory.
+pub const BUGREPORT_DIR: &str = "bugreports";
+
+struct Capture {
+    frame: u32,
+    screen: FrameBuffer,
+    info: DebugInfo,
+}
+
+static LAST_CAPTURE: Mutex<Option<Capture>> = Mutex::new(None);
+
+/// Keep a copy of the presented frame and its stats for the next report.
+pub fn capture(frame: u32, screen: &FrameBuffer, info: &DebugInfo) {
+    let mut guard = LAST_CAPTURE
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    match guard.as_mut() {
+        Some(capture) => {
+            capture.frame = frame;
+            capture.screen.clone_from(screen);
+            capture.info = *info;
+        }
+        None => {
+            *guard = Some(Capture {
+                frame,
+                screen: screen.clone(),
+                info: *info,
+            })
+        }
+    }
+}
+
+/// Write `<dir>/bug-<unix time>-<frame>.png` and `.txt` from the last
+/// capture. Returns the PNG path, or `None` if nothing was captured yet or
+/// the capture is locked (a panic inside `capture()` itself).
+pub fn write(dir: &Path, reason: &str) -> io::Result<Option<PathBuf>> {
+    let guard = match LAST_CAPTURE.try_lock() {
+        Ok(guard) => guard,
+        Err(TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+        Err(TryLockError::WouldBlock) => return Ok(None),
+    };
+
+    let Some(capture) = guard.as_ref() else {
+        return Ok(None);
+    };
+
+    let time = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let stem = dir.join(format!("bug-{time}-{}", capture.frame));
+
+    fs::create_dir_all(dir)?;
+
+    let png_path = stem.with_extension("png");
+    let mut out = BufWriter::new(File::create(&png_path)?);
+    png::write_png(&capture.screen, &mut out)?;
+    out.flush()?;
+
+    let mut text = File::create(stem.with_extension("txt"))?;
+    writeln!(text, "reason: {reason}")?;
+    writeln!(text, "frame: {}", capture.frame)?;
+    writeln!(text, "{:#?}", capture.info)?;
+
+    Ok(Some(png_path))
+}
@@ -0,0 +1,102 @@
+//! Startup settings kept between runs: one `key = value` per line, with
+//! `#` comments.
+//!
+//! The file only stores strings; the binary gives them meaning, reading
+//! them like the command-line flags of the same names.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+/// Where the binary looks for its config, relative to the working directory.
+pub const CONFIG_FILE: &str = "bitbound.cfg";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Config {
+    /// In file order, one entry per key.
+    entries: Vec<(String, String)>,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut config = Self::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("line {}: expected `key = value`", number + 1))?;
+            let key = key.trim();
+
+            if key.is_empty() || key.contains(char::is_whitespace) {
+                return Err(format!("line {}: invalid key `{key}`", number + 1));
+            }
+
+            config.set(key, value.trim());
+        }
+
+        Ok(config)
+    }
+
+    /// Read the config at `path`. A missing file is an empty config.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(err) => return Err(err),
+        };
+
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(candidate, _)| candidate == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Set `key`, replacing its value if it already has one.
+    pub fn set(&mut self, key: &str, value: &str) {
+        match self
+            .entries
+            .iter_mut()
+            .find(|(candidate, _)| candidate == key)
+        {
+            Some((_, old)) => *old = value.to_string(),
+            None => self.entries.push((key.to_string(), value.to_string())),
+        }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+impl fmt::Display for Config {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# bit-bound settings; command-line flags override these")?;
+
+        for (key, value) in self.entries() {
+            writeln!(f, "{key} = {value}")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,211 @@
+//! Panic hook that writes a crash report to disk.
+//!
+//! The runtime calls `checkpoint()` at the start of every tick. When a panic
+//! occurs, the hook writes the panic message, the last checkpoint (frame,
+//! arena usage and a `GameState` snapshot) and the newest log lines to
+//! `crash-<unix time>.txt`, then runs the previously installed hook. With the
+//! debug overlay compiled in, it also writes a screenshot bug report.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::panic::{self, PanicHookInfo};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, TryLockError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::game::{GameState, STATE_SNAPSHOT_MAX};
+use crate::runtime::log;
+use crate::runtime::memory::{Arena, RuntimeMemory};
+
+/// Log lines included in a report.
+pub const REPORT_LOG_LINES: usize = 60;
+
+#[derive(Debug, Clone, Copy, Default)]
+struct ArenaUsage {
+    used: usize,
+    peak: usize,
+    capacity: usize,
+}
+
+impl ArenaUsage {
+    fn of<const SIZE: usize>(arena: &Arena<SIZE>) -> Self {
+        Self {
+            used: arena.used(),
+            peak: arena.peak(),
+            capacity: arena.capacity(),
+        }
+    }
+}
+
+/// What the runtime looked like at the start of the crashing tick.
+struct Checkpoint {
+    frame: u32,
+    arenas: [(&'static str, ArenaUsage); 3],
+    state: [u8; STATE_SNAPSHOT_MAX],
+    state_len: usize,
+}
+
+static CHECKPOINT: Mutex<Option<Checkpoint>> = Mutex::new(None);
+
+/// Record the state a crash report will describe. Call once per tick, before
+/// `update()`, so the snapshot plus that tick's input reproduces the crash.
+pub fn checkpoint<const GLOBAL: usize, const LEVEL: usize, const FRAME: usize>(
+    state: &GameState,
+    memory: &RuntimeMemory<GLOBAL, LEVEL, FRAME>,
+) {
+    let mut guard = CHECKPOINT
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let checkpoint = guard.get_or_insert(Checkpoint {
+        frame: 0,
+        arenas: [("", ArenaUsage::default()); 3],
+        state: [0; STATE_SNAPSHOT_MAX],
+        state_len: 0,
+    });
+
+    checkpoint.frame = state.sim.frame_counter;
+    checkpoint.arenas = [
+        ("global", ArenaUsage::of(&memory.global)),
+        ("level", ArenaUsage::of(&memory.level)),
+        ("frame", ArenaUsage::of(&memory.frame)),
+    ];
+
+    let mut out = &mut checkpoint.state[..];
+    checkpoint.state_len = match state.serialize(&mut out) {
+        Ok(()) => STATE_SNAPSHOT_MAX - out.len(),
+        Err(_) => 0,
+    };
+}
+
+/// Install the crash hook. Reports are written to `dir`.
+pub fn install(dir: impl Into<PathBuf>) {
+    let dir = dir.into();
+    let previous = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        let path = dir.join(format!("crash-{}.txt", unix_time()));
+
+        match fs::write(&path, report(info)) {
+            Ok(()) => eprintln!("crash report written to {}", path.display()),
+            Err(err) => eprintln!("cannot write crash report {}: {err}", path.display()),
+        }
+
+        #[cfg(feature = "debug_overlay")]
+        {
+            use crate::runtime::bugreport;
+
+            match bugreport::write(&dir.join(bugreport::BUGREPORT_DIR), &info.to_string()) {
+                Ok(Some(path)) => eprintln!("screenshot written to {}", path.display()),
+                Ok(None) => {}
+                Err(err) => eprintln!("cannot write bug report: {err}"),
+            }
+        }
+
+        previous(info);
+    }));
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+/// Build the report text. Never blocks: locks held by the panicking thread
+/// are reported as unavailable instead.
+fn report(info: &PanicHookInfo<'_>) -> String {
+    let mut out = String::new();
+
+    let _ = writeln!(out, "bit-bound crash report");
+    let _ = writeln!(out, "version: {}", env!("CARGO_PKG_VERSION"));
+    let _ = writeln!(out, "panic: {info}");
+    let _ = writeln!(out);
+
+    match CHECKPOINT.try_lock() {
+        Ok(guard) => write_checkpoint(&mut out, guard.as_ref()),
+        Err(TryLockError::Poisoned(poisoned)) => {
+            write_checkpoint(&mut out, poisoned.into_inner().as_ref())
+        }
+        Err(TryLockError::WouldBlock) => {
+            let _ = writeln!(out, "checkpoint unavailable (locked)");
+        }
+    }
+
+    let _ = writeln!(out);
+    let _ = writeln!(out, "log (newest {REPORT_LOG_LINES} lines):");
+
+    match log::try_global() {
+        Some(log) => {
+            for age in (0..REPORT_LOG_LINES.min(log.len())).rev() {
+                if let Some(line) = log.recent(age) {
+                    let _ = writeln!(out, "  {line}");
+                }
+            }
+        }
+        None => {
+            let _ = writeln!(out, "  unavailable (locked)");
+        }
+    }
+
+    out
+}
+
+fn write_checkpoint(out: &mut String, checkpoint: Option<&Checkpoint>) {
+    let Some(checkpoint) = checkpoint else {
+        let _ = writeln!(out, "no checkpoint recorded");
+        return;
+    };
+
+    let _ = writeln!(out, "frame: {}", checkpoint.frame);
+    let _ = writeln!(out, "arenas (used / peak / capacity, bytes):");
+
+    for (name, usage) in checkpoint.arenas {
+        let _ = writeln!(
+            out,
+            "  {name:<6} {} / {} / {}",
+            usage.used, usage.peak, usage.capacity
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "state snapshot ({} bytes, GameState::deserialize_into):",
+        checkpoint.state_len
+    );
+
+    for chunk in checkpoint.state[..checkpoint.state_len].chunks(32) {
+        let _ = write!(out, "  ");
+
+        for byte in chunk {
+            let _ = write!(out, "{byte:02x}");
+        }
+
+        let _ = writeln!(out);
+    }
+}
+
+/// Read the state snapshot back out of a crash report onto `state`, a
+/// game built for the level that crashed, if any.
+pub fn load_snapshot(report: &Path, state: &mut GameState) -> std::io::Result<()> {
+    let text = fs::read_to_string(report)?;
+    let mut bytes = Vec::new();
+
+    let hex = text
+        .lines()
+        .skip_while(|line| !line.starts_with("state snapshot"))
+        .skip(1)
+        .take_while(|line| line.starts_with("  "))
+        .flat_map(|line| line.trim().as_bytes().chunks(2));
+
+    for pair in hex {
+        let byte = std::str::from_utf8(pair)
+            .ok()
+            .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            .ok_or_else(|| {
+                std::io::Error::new(std::io::ErrorKind::InvalidData, "bad snapshot hex")
+            })?;
+        bytes.push(byte);
+    }
+
+    state.deserialize_into(&mut bytes.as_slice())
+}
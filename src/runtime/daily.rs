@@ -0,0 +1,102 @@
+//! The daily challenge: one seeded wave per calendar day, the same for
+//! everyone, played for a fixed time.
+//!
+//! The seed is the UTC date as a number (`20261016`), so a result names
+//! the wave it was played on. The wave comes from `GameState::with_seed`
+//! and the deterministic `Rng`, so the input script saved with a result
+//! replays it exactly; the result's replay hash is `fnv1a` of that script,
+//! as in leaderboard submissions.
+
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::runtime::pacing::TICK_RATE;
+
+/// Length of a daily run: two minutes.
+pub const DAILY_TICKS: u32 = 2 * 60 * TICK_RATE as u32;
+
+/// A calendar date.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// The date `days` after 1970-01-01.
+    pub const fn from_days(days: i64) -> Self {
+        // Howard Hinnant's `civil_from_days`, on 400-year eras.
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400) as i32 + (month <= 2) as i32;
+
+        Self { year, month, day }
+    }
+
+    /// Today in UTC, by the system clock.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+
+        Self::from_days((seconds / 86_400) as i64)
+    }
+
+    /// The date a `seed` names, to replay another day's wave; `None` if it
+    /// is not a date.
+    pub const fn from_seed(seed: u64) -> Option<Self> {
+        let (year, month, day) = (seed / 10_000, seed / 100 % 100, seed % 100);
+
+        if year > 9999 || month < 1 || month > 12 || day < 1 || day > 31 {
+            return None;
+        }
+
+        Some(Self {
+            year: year as i32,
+            month: month as u32,
+            day: day as u32,
+        })
+    }
+
+    /// The day's wave seed: the date's digits, `YYYYMMDD`.
+    pub const fn seed(self) -> u64 {
+        self.year as u64 * 10_000 + self.month as u64 * 100 + self.day as u64
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// A finished daily run, shared as one line of text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DailyResult {
+    pub date: Date,
+    /// Ticks the first ship went unhit, out of `DAILY_TICKS`.
+    pub score: u32,
+    /// `fnv1a` of the run's input script as saved.
+    pub replay_hash: u64,
+}
+
+impl fmt::Display for DailyResult {
+    /// `BITBOUND DAILY 2026-10-16 seed=20261016 score=6843 replay=<hex>`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "BITBOUND DAILY {} seed={} score={} replay={:016x}",
+            self.date,
+            self.date.seed(),
+            self.score,
+            self.replay_hash
+        )
+    }
+}
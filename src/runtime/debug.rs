@@ -1,4 +1,10 @@
-use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer};
+use crate::game::GameState;
+use crate::game::collision::{CELL_SIZE, GRID_HEIGHT, GRID_WIDTH, SCREEN};
+use crate::math::geom::{Rect, Vec2i};
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT, WIDTH};
+use crate::runtime::hash;
+use crate::runtime::memory::{ActiveMemory, RuntimeMemory};
+use crate::runtime::quality::Quality;
 
 const DEBUG_Y: usize = 1;
 const DEBUG_X_PADDING: usize = 2;
@@ -7,30 +13,54 @@ const COL_FPS: usize = 0;
 const COL_GLOBAL: usize = 7;
 const COL_LEVEL: usize = 12;
 const COL_FRAME: usize = 17;
+const COL_QUALITY: usize = 22;
+
+const DEBUG_Y2: usize = DEBUG_Y + LINE_HEIGHT;
+
+const COL_ENTITIES: usize = 0;
+const COL_SPRITES: usize = 4;
+const COL_PIXELS: usize = 9;
+const COL_COLLISIONS: usize = 16;
+const COL_HASH: usize = 21;
+const COL_CULLED: usize = 31;
 
 fn col_to_x(col: usize) -> usize {
     DEBUG_X_PADDING + col * FONT_ADVANCE
 }
 
+#[derive(Debug, Clone, Copy, Default)]
 pub struct DebugInfo {
     pub frame_us: u32,
     pub global_used: u32,
     pub level_used: u32,
     pub frame_used: u32,
+    pub entities: u32,
+    pub sprites_drawn: u32,
+    /// Sprites and shapes skipped for being off screen.
+    pub sprites_culled: u32,
+    pub pixels_written: u32,
+    pub collision_pairs: u32,
+    /// Low 32 bits of `SimHash::value()`.
+    pub sim_hash: u32,
+    /// Optional drawing the frame-time watchdog left on.
+    pub quality: Quality,
 }
 
-pub fn render_debug_overlay(framebuffer: &mut FrameBuffer, info: &DebugInfo) {
-    let fps = if info.frame_us > 0 {
-        1_000_000 / info.frame_us
-    } else {
-        0
-    };
+/// Draw `info` across the top two rows, formatting the hash in `memory`'s
+/// frame arena.
+pub fn render_debug_overlay(
+    framebuffer: &mut FrameBuffer,
+    info: &DebugInfo,
+    memory: &mut ActiveMemory,
+) {
+    let fps = 1_000_000u32.checked_div(info.frame_us).unwrap_or(0);
 
     // Labels
     framebuffer.draw_text(col_to_x(COL_FPS), DEBUG_Y, "FPS", 2);
     framebuffer.draw_text(col_to_x(COL_GLOBAL), DEBUG_Y, "G", 2);
     framebuffer.draw_text(col_to_x(COL_LEVEL), DEBUG_Y, "L", 2);
     framebuffer.draw_text(col_to_x(COL_FRAME), DEBUG_Y, "F", 2);
+    framebuffer.draw_text(col_to_x(COL_QUALITY), DEBUG_Y, "Q", 2);
 
     // Values
     framebuffer.draw_u32(col_to_x(COL_FPS) + 3 * FONT_ADVANCE, DEBUG_Y, fps, 3, 3);
@@ -55,4 +85,580 @@ pub fn render_debug_overlay(framebuffer: &mut FrameBuffer, info: &DebugInfo) {
         3,
         3,
     );
+
+    // Dim at full quality, bright once the watchdog has cut something.
+    let quality_color = if info.quality == Quality::High { 2 } else { 3 };
+
+    framebuffer.draw_text(
+        col_to_x(COL_QUALITY) + FONT_ADVANCE,
+        DEBUG_Y,
+        info.quality.name(),
+        quality_color,
+    );
+
+    // Second line: entities, sprites, pixels, collision pairs
+    framebuffer.draw_text(col_to_x(COL_ENTITIES), DEBUG_Y2, "E", 2);
+    framebuffer.draw_text(col_to_x(COL_SPRITES), DEBUG_Y2, "S", 2);
+    framebuffer.draw_text(col_to_x(COL_PIXELS), DEBUG_Y2, "P", 2);
+    framebuffer.draw_text(col_to_x(COL_COLLISIONS), DEBUG_Y2, "C", 2);
+
+    framebuffer.draw_u32(
+        col_to_x(COL_ENTITIES) + FONT_ADVANCE,
+        DEBUG_Y2,
+        info.entities,
+        2,
+        3,
+    );
+    framebuffer.draw_u32(
+        col_to_x(COL_SPRITES) + FONT_ADVANCE,
+        DEBUG_Y2,
+        info.sprites_drawn,
+        3,
+        3,
+    );
+    framebuffer.draw_u32(
+        col_to_x(COL_PIXELS) + FONT_ADVANCE,
+        DEBUG_Y2,
+        info.pixels_written,
+        5,
+        3,
+    );
+    framebuffer.draw_u32(
+        col_to_x(COL_COLLISIONS) + FONT_ADVANCE,
+        DEBUG_Y2,
+        info.collision_pairs,
+        3,
+        3,
+    );
+
+    framebuffer.draw_text(col_to_x(COL_CULLED), DEBUG_Y2, "X", 2);
+    framebuffer.draw_u32(
+        col_to_x(COL_CULLED) + FONT_ADVANCE,
+        DEBUG_Y2,
+        info.sprites_culled,
+        3,
+        3,
+    );
+
+    framebuffer.draw_text(col_to_x(COL_HASH), DEBUG_Y2, "H", 2);
+
+    if let Ok(hash) = crate::fmt_to_arena!(memory.frame, "{:08X}", info.sim_hash) {
+        framebuffer.draw_text(col_to_x(COL_HASH) + FONT_ADVANCE, DEBUG_Y2, hash, 3);
+    }
+}
+
+/// Ticks between `SimHash` log lines.
+pub const SIM_HASH_LOG_INTERVAL: u32 = 60;
+
+/// Rolling hash over every tick's `GameState::state_hash()`.
+///
+/// Two runs with the same input must show the same value on every frame; the
+/// first frame where they differ is where determinism broke. The value is
+/// logged every `SIM_HASH_LOG_INTERVAL` ticks so runs can be compared after
+/// the fact.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SimHash {
+    value: u64,
+}
+
+impl SimHash {
+    pub const fn new() -> Self {
+        Self { value: 0 }
+    }
+
+    /// Fold in the state after a tick.
+    pub fn record(&mut self, state: &GameState) {
+        self.value = hash::combine(self.value, state.state_hash());
+
+        if state
+            .sim
+            .frame_counter
+            .is_multiple_of(SIM_HASH_LOG_INTERVAL)
+        {
+            crate::log_debug!("sim hash {:016x}", self.value);
+        }
+    }
+
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+}
+
+/// Number of frames kept by `MemoryHistory`, one pixel column each.
+pub const HISTORY_LEN: usize = 128;
+
+const PANEL_X: usize = 2;
+const PANEL_Y: usize = DEBUG_Y2 + LINE_HEIGHT + 2;
+const GRAPH_X: usize = PANEL_X + 2 * FONT_ADVANCE;
+const GRAPH_HEIGHT: usize = 12;
+const ROW_HEIGHT: usize = GRAPH_HEIGHT + 4;
+
+const ARENA_LABELS: [&str; 3] = ["G", "L", "F"];
+
+/// Per-frame arena usage for the last `HISTORY_LEN` frames.
+pub struct MemoryHistory {
+    samples: [[u32; 3]; HISTORY_LEN],
+    capacity: [u32; 3],
+    peak: [u32; 3],
+    head: usize,
+    len: usize,
+}
+
+impl Default for MemoryHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryHistory {
+    pub const fn new() -> Self {
+        Self {
+            samples: [[0; 3]; HISTORY_LEN],
+            capacity: [0; 3],
+            peak: [0; 3],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    /// Record the current arena usage. Call once per frame, before the
+    /// frame arena is reset.
+    pub fn record<const G: usize, const L: usize, const F: usize>(
+        &mut self,
+        memory: &RuntimeMemory<G, L, F>,
+    ) {
+        self.samples[self.head] = [
+            memory.global.used() as u32,
+            memory.level.used() as u32,
+            memory.frame.used() as u32,
+        ];
+        self.capacity = [G as u32, L as u32, F as u32];
+        self.peak = [
+            memory.global.peak() as u32,
+            memory.level.peak() as u32,
+            memory.frame.peak() as u32,
+        ];
+
+        self.head = (self.head + 1) % HISTORY_LEN;
+        self.len = (self.len + 1).min(HISTORY_LEN);
+    }
+
+    /// Sample `age` frames ago (0 = most recent) for arena `arena`.
+    fn sample(&self, age: usize, arena: usize) -> u32 {
+        let index = (self.head + HISTORY_LEN - 1 - age) % HISTORY_LEN;
+        self.samples[index][arena]
+    }
+}
+
+/// Scale `value` out of `capacity` to a bar height in pixels.
+fn bar_height(value: u32, capacity: u32) -> usize {
+    if capacity == 0 {
+        return 0;
+    }
+
+    ((value as u64 * GRAPH_HEIGHT as u64).div_ceil(capacity as u64) as usize).min(GRAPH_HEIGHT)
+}
+
+/// Draw one bar graph per arena, newest frame on the right, with a line at
+/// the peak and the current usage in percent.
+pub fn render_memory_panel(framebuffer: &mut FrameBuffer, history: &MemoryHistory) {
+    for (arena, label) in ARENA_LABELS.iter().enumerate() {
+        let y = PANEL_Y + arena * ROW_HEIGHT;
+        let capacity = history.capacity[arena];
+
+        framebuffer.fill_rect(
+            PANEL_X,
+            y,
+            GRAPH_X + HISTORY_LEN + 2 - PANEL_X,
+            GRAPH_HEIGHT + 2,
+            0,
+        );
+        framebuffer.draw_text(PANEL_X, y + 1, label, 2);
+        framebuffer.draw_rect(GRAPH_X, y, HISTORY_LEN + 2, GRAPH_HEIGHT + 2, 1);
+
+        let bottom = y + 1 + GRAPH_HEIGHT;
+
+        for age in 0..history.len {
+            let height = bar_height(history.sample(age, arena), capacity);
+            let x = GRAPH_X + HISTORY_LEN - age;
+
+            framebuffer.fill_rect(x, bottom - height, 1, height, 2);
+        }
+
+        let peak = bar_height(history.peak[arena], capacity);
+
+        if peak > 0 {
+            framebuffer.fill_rect(GRAPH_X + 1, bottom - peak, HISTORY_LEN, 1, 3);
+        }
+
+        let current = if history.len > 0 {
+            history.sample(0, arena)
+        } else {
+            0
+        };
+        let percent = (current as u64 * 100)
+            .checked_div(capacity as u64)
+            .unwrap_or(0)
+            .min(99) as u32;
+
+        framebuffer.draw_u32(PANEL_X, y + 1 + LINE_HEIGHT, percent, 2, 2);
+    }
+}
+
+/// Frame budget at 60 FPS, in microseconds.
+pub const FRAME_BUDGET_US: u32 = 16_667;
+
+const FRAME_GRAPH_HEIGHT: usize = 32;
+const FRAME_GRAPH_Y: usize = HEIGHT - FRAME_GRAPH_HEIGHT;
+
+/// Frame time that fills the graph's full height: twice the budget, so the
+/// guide line sits in the middle.
+const FRAME_GRAPH_MAX_US: u32 = 2 * FRAME_BUDGET_US;
+
+/// Frame times for the last `WIDTH` frames, one pixel column each.
+pub struct FrameTimeHistory {
+    samples: [u32; WIDTH],
+    head: usize,
+    len: usize,
+}
+
+impl Default for FrameTimeHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FrameTimeHistory {
+    pub const fn new() -> Self {
+        Self {
+            samples: [0; WIDTH],
+            head: 0,
+            len: 0,
+        }
+    }
+
+    pub fn record(&mut self, frame_us: u32) {
+        self.samples[self.head] = frame_us;
+        self.head = (self.head + 1) % WIDTH;
+        self.len = (self.len + 1).min(WIDTH);
+    }
+
+    /// Sample `age` frames ago (0 = most recent).
+    fn sample(&self, age: usize) -> u32 {
+        self.samples[(self.head + WIDTH - 1 - age) % WIDTH]
+    }
+}
+
+fn frame_bar_height(frame_us: u32) -> usize {
+    (frame_us.min(FRAME_GRAPH_MAX_US) as usize * FRAME_GRAPH_HEIGHT)
+        .div_ceil(FRAME_GRAPH_MAX_US as usize)
+}
+
+/// Plot one column per frame across the bottom of the screen, newest on the
+/// right, with a guide line at the 16.6 ms budget. Frames over budget are
+/// drawn in the brightest color.
+pub fn render_frame_graph(framebuffer: &mut FrameBuffer, history: &FrameTimeHistory) {
+    framebuffer.fill_rect(0, FRAME_GRAPH_Y, WIDTH, FRAME_GRAPH_HEIGHT, 0);
+
+    for age in 0..history.len {
+        let frame_us = history.sample(age);
+        let height = frame_bar_height(frame_us);
+        let color = if frame_us > FRAME_BUDGET_US { 3 } else { 1 };
+
+        framebuffer.fill_rect(WIDTH - 1 - age, HEIGHT - height, 1, height, color);
+    }
+
+    let guide_y = HEIGHT - frame_bar_height(FRAME_BUDGET_US);
+
+    for x in (0..WIDTH).step_by(2) {
+        framebuffer.set_pixel(x, guide_y, 2);
+    }
+}
+
+/// Which optional debug views are switched on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DebugFlags(u8);
+
+impl DebugFlags {
+    pub const MEMORY_PANEL: Self = Self(1 << 0);
+    pub const FRAME_GRAPH: Self = Self(1 << 1);
+    pub const HITBOXES: Self = Self(1 << 2);
+    pub const COLLISION_GRID: Self = Self(1 << 3);
+    pub const LOG: Self = Self(1 << 4);
+    pub const PROFILER: Self = Self(1 << 5);
+    /// `vram::VramViewer` in place of the game.
+    pub const VRAM: Self = Self(1 << 6);
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub fn contains(self, flag: Self) -> bool {
+        self.0 & flag.0 == flag.0
+    }
+
+    pub fn toggle(&mut self, flag: Self) {
+        self.0 ^= flag.0;
+    }
+}
+
+/// Pause and frame-advance state for the main loop.
+///
+/// While paused the loop keeps rendering (so overlays stay live) but only
+/// calls `update()` once per requested step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StepControl {
+    paused: bool,
+    pending_steps: u32,
+}
+
+impl StepControl {
+    pub const fn new() -> Self {
+        Self {
+            paused: false,
+            pending_steps: 0,
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.pending_steps = 0;
+    }
+
+    /// Queue exactly one tick. Pauses first if the simulation is running.
+    pub fn step(&mut self) {
+        if self.paused {
+            self.pending_steps = self.pending_steps.saturating_add(1);
+        } else {
+            self.paused = true;
+        }
+    }
+
+    /// Pause and queue `count` ticks on top of any already queued.
+    pub fn step_by(&mut self, count: u32) {
+        self.paused = true;
+        self.pending_steps = self.pending_steps.saturating_add(count);
+    }
+
+    /// Whether to run `update()` this frame. Consumes one queued step.
+    pub fn should_tick(&mut self) -> bool {
+        if !self.paused {
+            return true;
+        }
+
+        if self.pending_steps > 0 {
+            self.pending_steps -= 1;
+            return true;
+        }
+
+        false
+    }
+}
+
+/// Simulation speeds in quarter ticks per presented frame: 0.25x to 8x.
+const SPEED_QUARTERS: [u32; 6] = [1, 2, 4, 8, 16, 32];
+const NORMAL_SPEED: usize = 2;
+
+/// Simulation-to-presentation ratio, applied through a tick accumulator.
+///
+/// Each presented frame adds the speed (in quarter ticks) to the
+/// accumulator and runs one `update()` per whole tick owed, so 0.25x runs a
+/// tick every fourth frame and 8x runs eight per frame. Counting in frames
+/// rather than wall-clock time keeps runs deterministic at any speed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SimSpeed {
+    index: usize,
+    quarters: u32,
+}
+
+impl Default for SimSpeed {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimSpeed {
+    pub const fn new() -> Self {
+        Self {
+            index: NORMAL_SPEED,
+            quarters: 0,
+        }
+    }
+
+    pub fn faster(&mut self) {
+        self.index = (self.index + 1).min(SPEED_QUARTERS.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn is_normal(&self) -> bool {
+        self.index == NORMAL_SPEED
+    }
+
+    /// Current speed in quarters of normal speed.
+    pub fn quarters(&self) -> u32 {
+        SPEED_QUARTERS[self.index]
+    }
+
+    /// Ticks to run for this presented frame.
+    pub fn advance(&mut self) -> u32 {
+        self.quarters += self.quarters();
+        let ticks = self.quarters / 4;
+        self.quarters %= 4;
+        ticks
+    }
+}
+
+/// Draw the simulation speed (`1/4X` to `8X`) at the right end of the
+/// second overlay line, unless it is normal.
+pub fn render_speed_indicator(framebuffer: &mut FrameBuffer, speed: &SimSpeed) {
+    if speed.is_normal() {
+        return;
+    }
+
+    let text = match speed.quarters() {
+        1 => "1/4X",
+        2 => "1/2X",
+        8 => "2X",
+        16 => "4X",
+        _ => "8X",
+    };
+
+    let x = WIDTH - DEBUG_X_PADDING - text.len() * FONT_ADVANCE;
+
+    framebuffer.fill_rect(
+        x - 1,
+        DEBUG_Y2 - 1,
+        text.len() * FONT_ADVANCE + 1,
+        LINE_HEIGHT,
+        0,
+    );
+    framebuffer.draw_text(x, DEBUG_Y2, text, 3);
+}
+
+/// Draw `PAUSED` in the top-right corner.
+pub fn render_pause_indicator(framebuffer: &mut FrameBuffer) {
+    const TEXT: &str = "PAUSED";
+
+    let x = WIDTH - DEBUG_X_PADDING - TEXT.len() * FONT_ADVANCE;
+
+    framebuffer.fill_rect(
+        x - 1,
+        DEBUG_Y - 1,
+        TEXT.len() * FONT_ADVANCE + 1,
+        LINE_HEIGHT,
+        0,
+    );
+    framebuffer.draw_text(x, DEBUG_Y, TEXT, 3);
+}
+
+/// Outline a hitbox, clipped to the screen.
+fn draw_hitbox(framebuffer: &mut FrameBuffer, hitbox: &Rect, color: u8) {
+    let Some(visible) = hitbox.intersection(&SCREEN) else {
+        return;
+    };
+
+    framebuffer.draw_rect(
+        visible.x as usize,
+        visible.y as usize,
+        visible.w as usize,
+        visible.h as usize,
+        color,
+    );
+}
+
+/// Outline every hitbox. Enemies overlapping the player, and the player
+/// while overlapped, are drawn in the brightest color.
+pub fn render_hitboxes(framebuffer: &mut FrameBuffer, state: &GameState) {
+    let hits = state.sim.collisions.player_hits;
+
+    for (i, enemy) in state.sim.enemies.as_slice().iter().enumerate() {
+        let color = if hits & (1 << i) != 0 { 3 } else { 1 };
+        draw_hitbox(framebuffer, &enemy.hitbox(), color);
+    }
+
+    let color = if hits != 0 { 3 } else { 2 };
+    draw_hitbox(framebuffer, &state.sim.player.hitbox(), color);
+}
+
+/// Draw the broadphase grid as a dot at every cell corner, with occupied
+/// cells marked by their entry count.
+pub fn render_collision_grid(framebuffer: &mut FrameBuffer, state: &GameState) {
+    for cy in 0..GRID_HEIGHT {
+        for cx in 0..GRID_WIDTH {
+            let x = cx * CELL_SIZE;
+            let y = cy * CELL_SIZE;
+
+            framebuffer.set_pixel(x, y, 1);
+
+            let count = state.sim.collisions.grid.occupancy(cx, cy);
+
+            if count > 0 {
+                framebuffer.draw_u32(x + 2, y + 2, count as u32, 1, 2);
+            }
+        }
+    }
+}
+
+const ENTITY_Y: usize = PANEL_Y + ARENA_LABELS.len() * ROW_HEIGHT + 2;
+
+/// Width of one `x:y` entry in the entity list, with its gap.
+const ENTITY_ENTRY_WIDTH: usize = 8 * FONT_ADVANCE;
+
+/// List the player's and every enemy's position between the memory panel
+/// and the frame graph, as many as fit. Enemies hit this tick are drawn in
+/// the brightest color.
+pub fn render_entity_list(framebuffer: &mut FrameBuffer, state: &GameState) {
+    let columns = (WIDTH - PANEL_X) / ENTITY_ENTRY_WIDTH;
+    let rows = (FRAME_GRAPH_Y - ENTITY_Y) / LINE_HEIGHT - 1;
+    let enemies = state.sim.enemies.as_slice();
+
+    framebuffer.draw_text(PANEL_X, ENTITY_Y, "P", 2);
+    draw_position(
+        framebuffer,
+        PANEL_X + FONT_ADVANCE,
+        ENTITY_Y,
+        state.sim.player.pos,
+        2,
+    );
+    framebuffer.draw_text(PANEL_X + ENTITY_ENTRY_WIDTH, ENTITY_Y, "E", 2);
+    framebuffer.draw_u32(
+        PANEL_X + ENTITY_ENTRY_WIDTH + FONT_ADVANCE,
+        ENTITY_Y,
+        enemies.len() as u32,
+        2,
+        2,
+    );
+
+    for (i, enemy) in enemies.iter().take(columns * rows).enumerate() {
+        let x = PANEL_X + (i % columns) * ENTITY_ENTRY_WIDTH;
+        let y = ENTITY_Y + (1 + i / columns) * LINE_HEIGHT;
+        let color = if state.sim.collisions.player_hits & (1 << i) != 0 {
+            3
+        } else {
+            1
+        };
+
+        draw_position(framebuffer, x, y, enemy.pos(), color);
+    }
+}
+
+fn draw_position(framebuffer: &mut FrameBuffer, x: usize, y: usize, position: Vec2i, color: u8) {
+    framebuffer.draw_u32(x, y, position.x as u32, 3, color);
+    framebuffer.draw_text(x + 3 * FONT_ADVANCE, y, ":", color);
+    framebuffer.draw_u32(x + 4 * FONT_ADVANCE, y, position.y as u32, 3, color);
 }
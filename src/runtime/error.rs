@@ -0,0 +1,184 @@
+//! The errors that end a run, and the screen that reports them.
+//!
+//! Whatever the binary can fail at, from opening the window to writing the
+//! save, comes back to `main` as an `Error`. `main` prints it, and when a
+//! player is there to read it and a window still opens, shows it with
+//! `render_error` until a button is pressed instead of closing without a
+//! word. Invariants the code keeps itself still panic, and the crash
+//! handler (`runtime::crash`) reports those.
+
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT, WIDTH};
+use crate::text::{Language, TextId};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Left and right of the message, in pixels.
+const MARGIN: usize = 4;
+
+/// Top of the message, under the heading.
+const MESSAGE_Y: usize = 3 * LINE_HEIGHT;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The window, or the backend behind it, did not open.
+    Window(String),
+    /// A file the run needs could not be read or is not what it should be.
+    /// `what` says what it is for when its name does not.
+    Load {
+        what: &'static str,
+        path: PathBuf,
+        message: String,
+    },
+    /// A file the run produces could not be written.
+    Save {
+        what: &'static str,
+        path: PathBuf,
+        message: String,
+    },
+    DebugServer(String),
+    /// Netplay did not connect, or the partner dropped out.
+    Netplay(String),
+    /// The two netplay games differ from `frame` on.
+    Desync {
+        frame: u32,
+    },
+    /// A soak run broke an invariant; its report says which.
+    Soak {
+        seed: u64,
+    },
+    /// `Runtime::take` was called a second time.
+    RuntimeClaimed,
+}
+
+impl Error {
+    pub fn load(what: &'static str, path: &Path, err: impl fmt::Display) -> Self {
+        Self::Load {
+            what,
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        }
+    }
+
+    pub fn save(what: &'static str, path: &Path, err: impl fmt::Display) -> Self {
+        Self::Save {
+            what,
+            path: path.to_path_buf(),
+            message: err.to_string(),
+        }
+    }
+
+    /// Whether the error is worth a screen: not when the window itself
+    /// failed, nor for a soak run, whose report is the output.
+    pub const fn has_screen(&self) -> bool {
+        !matches!(self, Self::Window(_) | Self::Soak { .. })
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Window(err) => write!(f, "cannot open window: {err}"),
+            Self::Load {
+                what,
+                path,
+                message,
+            } => write_file(f, "load", what, path, message),
+            Self::Save {
+                what,
+                path,
+                message,
+            } => write_file(f, "save", what, path, message),
+            Self::DebugServer(err) => write!(f, "cannot start debug server: {err}"),
+            Self::Netplay(err) => write!(f, "netplay: {err}"),
+            Self::Desync { frame } => {
+                write!(
+                    f,
+                    "netplay: the partner's game differs from frame {frame} on"
+                )
+            }
+            Self::Soak { seed } => write!(f, "soak run {seed} broke an invariant"),
+            Self::RuntimeClaimed => write!(f, "runtime already claimed"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// "cannot `verb` `what` `path`: `message`", without `what` if it is empty.
+fn write_file(
+    f: &mut fmt::Formatter<'_>,
+    verb: &str,
+    what: &str,
+    path: &Path,
+    message: &str,
+) -> fmt::Result {
+    let space = if what.is_empty() { "" } else { " " };
+
+    write!(
+        f,
+        "cannot {verb} {what}{space}{}: {message}",
+        path.display()
+    )
+}
+
+/// Draw `error` under a heading, wrapped to the screen, with how to leave
+/// at the bottom. The font only has capitals, so the message is shown in
+/// them, and what it has no glyph for is left blank.
+pub fn render_error(framebuffer: &mut FrameBuffer, language: Language, error: &Error) {
+    let title = language.text(TextId::ErrorTitle);
+    let dismiss = language.text(TextId::ErrorDismiss);
+    let centered = |text: &str| (WIDTH - text.chars().count() * FONT_ADVANCE) / 2;
+
+    framebuffer.clear(0);
+    framebuffer.draw_text(centered(title), LINE_HEIGHT, title, 3);
+
+    let message = error.to_string().to_uppercase();
+    let bottom = HEIGHT - 3 * LINE_HEIGHT;
+    let columns = (WIDTH - 2 * MARGIN) / FONT_ADVANCE;
+    let mut y = MESSAGE_Y;
+
+    for line in wrap(&message, columns) {
+        if y + LINE_HEIGHT > bottom {
+            break;
+        }
+
+        framebuffer.draw_text(MARGIN, y, &line, 2);
+        y += LINE_HEIGHT;
+    }
+
+    framebuffer.draw_text(centered(dismiss), HEIGHT - 2 * LINE_HEIGHT, dismiss, 3);
+}
+
+/// `text` in lines of at most `columns` characters, broken between words
+/// where it can be and inside one, like a long path, where it cannot.
+fn wrap(text: &str, columns: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word: Vec<char> = word.chars().collect();
+        let used = line.chars().count();
+
+        if used > 0 && used + 1 + word.len() > columns {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        while word.len() > columns {
+            let rest = word.split_off(columns);
+            lines.push(word.into_iter().collect());
+            word = rest;
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.extend(word);
+    }
+
+    if !line.is_empty() {
+        lines.push(line);
+    }
+
+    lines
+}
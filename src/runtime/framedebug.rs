@@ -0,0 +1,110 @@
+//! Frame debugger: one recorded frame, drawn as it stood after any of its
+//! draw calls, for finding calls drawn in the wrong order or clipped.
+//!
+//! The binary records a frame (`renderer::record`) and hands it over;
+//! `FrameDebugger` keeps the call selected and draws the frame as that call
+//! left it, the pixels it changed outlined and a caption with the call at
+//! the bottom. Call 0 is the frame before any call.
+
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT, WIDTH};
+use crate::renderer::record::DrawRecording;
+
+const CAPTION_Y: usize = HEIGHT - 2 * LINE_HEIGHT - 1;
+
+pub struct FrameDebugger {
+    recording: DrawRecording,
+    /// 0 before the first call, else the call's index plus one.
+    selected: usize,
+}
+
+impl FrameDebugger {
+    /// A debugger on `recording`'s last call.
+    pub fn new(recording: DrawRecording) -> Self {
+        let selected = recording.calls().len();
+
+        Self {
+            recording,
+            selected,
+        }
+    }
+
+    /// Move `delta` calls on, or back if negative, stopping at either end.
+    pub fn step(&mut self, delta: isize) {
+        let last = self.recording.calls().len();
+
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+    }
+
+    fn pixels(&self, selected: usize) -> &[u8] {
+        match selected.checked_sub(1) {
+            Some(call) => self.recording.calls()[call].pixels(),
+            None => self.recording.before(),
+        }
+    }
+
+    /// Draw over the whole screen.
+    pub fn render(&self, framebuffer: &mut FrameBuffer) {
+        let after = self.pixels(self.selected);
+
+        framebuffer.load_raw(after);
+
+        if let Some(previous) = self.selected.checked_sub(1)
+            && let Some((x, y, w, h)) = changed_bounds(self.pixels(previous), after)
+        {
+            framebuffer.draw_rect(x.saturating_sub(1), y.saturating_sub(1), w + 2, h + 2, 3);
+        }
+
+        let calls = self.recording.calls();
+
+        framebuffer.fill_rect(0, CAPTION_Y - 1, WIDTH, HEIGHT - CAPTION_Y + 1, 0);
+
+        let mut header = format!("CALL {}/{}", self.selected, calls.len());
+
+        if self.recording.dropped() > 0 {
+            header += &format!(" +{} NOT KEPT", self.recording.dropped());
+        }
+
+        framebuffer.draw_text(2, CAPTION_Y, &header, 2);
+
+        let (command, repeat) = match self.selected.checked_sub(1) {
+            Some(call) => (calls[call].command.as_str(), calls[call].repeat),
+            None => ("BEFORE THE FIRST CALL", 1),
+        };
+        let y = CAPTION_Y + LINE_HEIGHT;
+
+        framebuffer.draw_text(2, y, command, 3);
+
+        if repeat > 1 {
+            let x = 2 + (command.chars().count() + 1) * FONT_ADVANCE;
+            framebuffer.draw_text(x, y, &format!("X{repeat}"), 2);
+        }
+    }
+}
+
+/// The smallest (x, y, w, h) holding every pixel that differs between two
+/// frames' packed pixels, or `None` if none do.
+fn changed_bounds(before: &[u8], after: &[u8]) -> Option<(usize, usize, usize, usize)> {
+    let mut bounds: Option<(usize, usize, usize, usize)> = None;
+
+    for (byte, (&old, &new)) in before.iter().zip(after).enumerate() {
+        let diff = old ^ new;
+
+        for pixel in 0..4 {
+            if (diff >> (pixel * 2)) & 0b11 == 0 {
+                continue;
+            }
+
+            let index = byte * 4 + pixel;
+            let (x, y) = (index % WIDTH, index / WIDTH);
+
+            bounds = Some(match bounds {
+                Some((left, top, right, bottom)) => {
+                    (left.min(x), top.min(y), right.max(x), bottom.max(y))
+                }
+                None => (x, y, x, y),
+            });
+        }
+    }
+
+    bounds.map(|(left, top, right, bottom)| (left, top, right - left + 1, bottom - top + 1))
+}
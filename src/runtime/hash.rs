@@ -0,0 +1,54 @@
+/// 64-bit FNV-1a. Cheap, stable across platforms and builds, and good enough
+/// to tell two simulation states or framebuffers apart. Not for hash tables
+/// fed by untrusted input.
+#[derive(Debug, Clone, Copy)]
+pub struct Fnv1a(u64);
+
+impl Default for Fnv1a {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Fnv1a {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    pub const fn new() -> Self {
+        Self(Self::OFFSET_BASIS)
+    }
+
+    pub const fn write_bytes(&mut self, bytes: &[u8]) {
+        let mut i = 0;
+
+        while i < bytes.len() {
+            self.0 ^= bytes[i] as u64;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+            i += 1;
+        }
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.write_bytes(&value.to_le_bytes());
+    }
+
+    pub const fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
+/// Hash `bytes` in one go. Usable in `const` items, e.g. to hash an asset
+/// name at compile time.
+pub const fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = Fnv1a::new();
+    hash.write_bytes(bytes);
+    hash.finish()
+}
+
+/// Fold `value` into a running hash, so the result depends on every value
+/// and on their order.
+pub fn combine(rolling: u64, value: u64) -> u64 {
+    let mut hash = Fnv1a(rolling);
+    hash.write_bytes(&value.to_le_bytes());
+    hash.finish()
+}
@@ -0,0 +1,79 @@
+//! Runs the simulation without a window, for CI and determinism checks.
+
+use std::fmt;
+use std::time::Instant;
+
+use crate::audio::offline::OfflineAudio;
+use crate::game::ghost::GhostRecorder;
+use crate::game::{GameState, update};
+use crate::input::InputScript;
+use crate::runtime::crash;
+use crate::runtime::memory::ActiveMemory;
+
+/// What a headless run reports when it finishes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HeadlessSummary {
+    pub frames: u32,
+    /// `GameState::state_hash()` after the last frame.
+    pub state_hash: u64,
+    /// Slowest `update()` in microseconds. Varies between runs.
+    pub max_frame_us: u32,
+    pub global_peak: usize,
+    pub level_peak: usize,
+    pub frame_peak: usize,
+}
+
+impl fmt::Display for HeadlessSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "frames: {}", self.frames)?;
+        writeln!(f, "state hash: {:016x}", self.state_hash)?;
+        writeln!(f, "max frame: {} us", self.max_frame_us)?;
+        write!(
+            f,
+            "arena peaks: global {} B, level {} B, frame {} B",
+            self.global_peak, self.level_peak, self.frame_peak
+        )
+    }
+}
+
+/// Run `update()` on `state` for `frames` ticks with input from `script`,
+/// as fast as possible. The frame arena is reset after every tick, like the
+/// windowed loop does. With `audio`, every tick's sound is rendered into it,
+/// and with `ghost`, every tick's ship position.
+pub fn run(
+    memory: &mut ActiveMemory,
+    mut state: GameState,
+    frames: u32,
+    script: &InputScript,
+    mut audio: Option<&mut OfflineAudio>,
+    mut ghost: Option<&mut GhostRecorder>,
+) -> HeadlessSummary {
+    let mut max_frame_us = 0;
+
+    for frame in 0..frames {
+        let start = Instant::now();
+
+        crash::checkpoint(&state, memory);
+        update(&mut state, script.at(frame), memory);
+
+        if let Some(audio) = audio.as_deref_mut() {
+            audio.submit(state.apu.end_frame());
+        }
+
+        if let Some(ghost) = ghost.as_deref_mut() {
+            ghost.record(&state);
+        }
+
+        max_frame_us = max_frame_us.max(start.elapsed().as_micros() as u32);
+        memory.frame.reset();
+    }
+
+    HeadlessSummary {
+        frames,
+        state_hash: state.state_hash(),
+        max_frame_us,
+        global_peak: memory.global.peak(),
+        level_peak: memory.level.peak(),
+        frame_peak: memory.frame.peak(),
+    }
+}
@@ -0,0 +1,280 @@
+//! Online leaderboard client (feature `leaderboard`).
+//!
+//! Results are plain text over HTTP(S). A submission is a `POST` to
+//! `<url>/scores` with one `key=value` per line, signed with HMAC-SHA256
+//! under a key shared with the server:
+//!
+//! ```text
+//! POST /scores
+//! X-Signature: <hex HMAC-SHA256 of the body>
+//!
+//! name=ACE
+//! score=1234
+//! seed=0
+//! frames=3600
+//! replay=00c0ffee00c0ffee
+//! ```
+//!
+//! `GET <url>/top` answers with up to 100 lines of `<name> <score>`, best
+//! first. Results that cannot be sent are appended to a queue file and sent
+//! before the next one.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT, WIDTH};
+use crate::text::{Language, TextId};
+
+/// Where unsent results wait, relative to the working directory.
+pub const QUEUE_FILE: &str = "leaderboard-queue.txt";
+
+/// Entries `top` keeps.
+pub const TOP_LEN: usize = 100;
+
+/// Longest name sent; longer ones are cut.
+pub const MAX_NAME: usize = 12;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One finished run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunResult {
+    pub name: String,
+    pub score: u32,
+    pub seed: u64,
+    pub frames: u32,
+    /// `Fnv1a` of the run's input script as saved, so the server can ask
+    /// for the replay that backs a score.
+    pub replay_hash: u64,
+}
+
+impl RunResult {
+    /// `name` upper-cased, cut to `MAX_NAME`, with anything the screen font
+    /// or the queue file cannot hold dropped.
+    pub fn clean_name(name: &str) -> String {
+        name.chars()
+            .map(|c| c.to_ascii_uppercase())
+            .filter(char::is_ascii_alphanumeric)
+            .take(MAX_NAME)
+            .collect()
+    }
+
+    /// The request body `sign` covers.
+    fn body(&self) -> String {
+        format!(
+            "name={}\nscore={}\nseed={}\nframes={}\nreplay={:016x}\n",
+            self.name, self.score, self.seed, self.frames, self.replay_hash
+        )
+    }
+
+    /// Parse a line written by `Display`.
+    fn parse(line: &str) -> Option<Self> {
+        let mut words = line.split_whitespace();
+        let result = Self {
+            name: words.next()?.to_string(),
+            score: words.next()?.parse().ok()?,
+            seed: words.next()?.parse().ok()?,
+            frames: words.next()?.parse().ok()?,
+            replay_hash: u64::from_str_radix(words.next()?, 16).ok()?,
+        };
+
+        words.next().is_none().then_some(result)
+    }
+}
+
+/// One queue line.
+impl fmt::Display for RunResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {:016x}",
+            self.name, self.score, self.seed, self.frames, self.replay_hash
+        )
+    }
+}
+
+/// A row of the leaderboard.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub name: String,
+    pub score: u32,
+}
+
+/// Hex HMAC-SHA256 of `body` under `key`.
+pub fn sign(key: &[u8], body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(body.as_bytes());
+
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+/// What `submit` did with the queue and the new result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SubmitReport {
+    pub sent: usize,
+    /// Results left in the queue file for next time.
+    pub queued: usize,
+}
+
+/// Why `send` failed.
+enum SendError {
+    /// The server answered with this HTTP status.
+    Rejected(u16),
+    /// No answer.
+    Unreachable(String),
+}
+
+pub struct LeaderboardClient {
+    url: String,
+    key: Vec<u8>,
+    queue: PathBuf,
+    agent: ureq::Agent,
+}
+
+impl LeaderboardClient {
+    /// A client for the server at `url` (without a trailing `/`), queueing
+    /// in `queue`.
+    pub fn new(url: &str, key: &str, queue: &Path) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+            key: key.as_bytes().to_vec(),
+            queue: queue.to_path_buf(),
+            agent: ureq::AgentBuilder::new().timeout(TIMEOUT).build(),
+        }
+    }
+
+    /// Send the queued results, then `result`. Sending stops at the first
+    /// failure and everything unsent is queued, oldest first; a result the
+    /// server rejects is dropped with a warning so it cannot block the
+    /// queue. Only queue file errors are returned.
+    pub fn submit(&self, result: RunResult) -> io::Result<SubmitReport> {
+        let mut pending = self.load_queue()?;
+        pending.push(result);
+
+        let mut sent = 0;
+        let mut index = 0;
+
+        while index < pending.len() {
+            match self.send(&pending[index]) {
+                Ok(()) => sent += 1,
+                Err(SendError::Rejected(status)) => {
+                    crate::log_warn!("leaderboard rejected {}: HTTP {status}", pending[index]);
+                }
+                Err(SendError::Unreachable(err)) => {
+                    crate::log_warn!("leaderboard unreachable: {err}");
+                    break;
+                }
+            }
+
+            index += 1;
+        }
+
+        let unsent = &pending[index..];
+        let text: String = unsent.iter().map(|result| format!("{result}\n")).collect();
+        fs::write(&self.queue, text)?;
+
+        Ok(SubmitReport {
+            sent,
+            queued: unsent.len(),
+        })
+    }
+
+    fn send(&self, result: &RunResult) -> Result<(), SendError> {
+        let body = result.body();
+        let response = self
+            .agent
+            .post(&format!("{}/scores", self.url))
+            .set("Content-Type", "text/plain")
+            .set("X-Signature", &sign(&self.key, &body))
+            .send_string(&body);
+
+        match response {
+            Ok(_) => Ok(()),
+            Err(ureq::Error::Status(status, _)) => Err(SendError::Rejected(status)),
+            Err(err) => Err(SendError::Unreachable(err.to_string())),
+        }
+    }
+
+    /// Unreadable lines are skipped; a missing file is an empty queue.
+    fn load_queue(&self) -> io::Result<Vec<RunResult>> {
+        match fs::read_to_string(&self.queue) {
+            Ok(text) => Ok(text.lines().filter_map(RunResult::parse).collect()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Fetch the best `TOP_LEN` results.
+    pub fn top(&self) -> Result<Vec<Entry>, String> {
+        let text = self
+            .agent
+            .get(&format!("{}/top", self.url))
+            .call()
+            .map_err(|err| err.to_string())?
+            .into_string()
+            .map_err(|err| err.to_string())?;
+
+        Ok(text
+            .lines()
+            .filter_map(|line| {
+                let (name, score) = line.trim().rsplit_once(' ')?;
+
+                Some(Entry {
+                    name: RunResult::clean_name(name),
+                    score: score.parse().ok()?,
+                })
+            })
+            .take(TOP_LEN)
+            .collect())
+    }
+}
+
+/// Rows that fit under the title.
+pub const VISIBLE_ROWS: usize = (HEIGHT - 3 * LINE_HEIGHT) / LINE_HEIGHT;
+
+/// Draw `entries` from row `scroll` on, with their ranks. `status` replaces
+/// the list, e.g. while loading or after an error.
+pub fn render_leaderboard(
+    framebuffer: &mut FrameBuffer,
+    language: Language,
+    entries: &[Entry],
+    scroll: usize,
+    status: Option<TextId>,
+) {
+    let title = language.text(TextId::LeaderboardTitle);
+
+    framebuffer.clear(0);
+    framebuffer.draw_text(
+        (WIDTH - title.chars().count() * FONT_ADVANCE) / 2,
+        LINE_HEIGHT / 2,
+        title,
+        3,
+    );
+
+    let top = 2 * LINE_HEIGHT;
+
+    if let Some(status) = status {
+        framebuffer.draw_text(2, top, language.text(status), 2);
+        return;
+    }
+
+    for (row, entry) in entries.iter().skip(scroll).take(VISIBLE_ROWS).enumerate() {
+        let y = top + row * LINE_HEIGHT;
+        let rank = scroll + row + 1;
+        let color = if rank <= 3 { 3 } else { 2 };
+
+        framebuffer.draw_u32(2, y, rank as u32, 3, 1);
+        framebuffer.draw_text(2 + 4 * FONT_ADVANCE, y, &entry.name, color);
+        framebuffer.draw_u32(WIDTH - 2 - 10 * FONT_ADVANCE, y, entry.score, 10, color);
+    }
+}
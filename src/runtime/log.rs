@@ -0,0 +1,309 @@
+use core::fmt::{self, Write as _};
+#[cfg(feature = "std")]
+use std::fs::OpenOptions;
+#[cfg(feature = "std")]
+use std::io::{self, Write as _};
+#[cfg(feature = "std")]
+use std::path::Path;
+#[cfg(feature = "std")]
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+use crate::renderer::framebuffer::{FrameBuffer, HEIGHT, LINE_HEIGHT, WIDTH};
+
+/// Bytes of text kept per line. Longer messages are truncated.
+pub const LOG_LINE_LEN: usize = 64;
+
+/// Lines kept in the ring buffer. Older lines are overwritten.
+pub const LOG_CAPACITY: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl LogLevel {
+    pub fn tag(self) -> char {
+        match self {
+            LogLevel::Error => 'E',
+            LogLevel::Warn => 'W',
+            LogLevel::Info => 'I',
+            LogLevel::Debug => 'D',
+        }
+    }
+}
+
+/// One log entry with its text stored inline.
+#[derive(Clone, Copy)]
+pub struct LogLine {
+    pub level: LogLevel,
+    pub frame: u32,
+    len: u8,
+    text: [u8; LOG_LINE_LEN],
+}
+
+impl LogLine {
+    const EMPTY: Self = Self {
+        level: LogLevel::Debug,
+        frame: 0,
+        len: 0,
+        text: [0; LOG_LINE_LEN],
+    };
+
+    pub fn text(&self) -> &str {
+        // Only whole UTF-8 sequences are ever copied in.
+        core::str::from_utf8(&self.text[..self.len as usize]).unwrap_or("")
+    }
+}
+
+impl fmt::Display for LogLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:06} {} {}", self.frame, self.level.tag(), self.text())
+    }
+}
+
+/// `fmt::Write` into a `LogLine`, dropping whatever does not fit.
+struct LineWriter<'a>(&'a mut LogLine);
+
+impl fmt::Write for LineWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let line = &mut *self.0;
+        let free = LOG_LINE_LEN - line.len as usize;
+        let mut take = s.len().min(free);
+
+        while !s.is_char_boundary(take) {
+            take -= 1;
+        }
+
+        let start = line.len as usize;
+        line.text[start..start + take].copy_from_slice(&s.as_bytes()[..take]);
+        line.len += take as u8;
+
+        Ok(())
+    }
+}
+
+/// Fixed-capacity ring buffer of log lines.
+pub struct Log {
+    lines: [LogLine; LOG_CAPACITY],
+    /// Total lines ever written; the newest line has sequence `written - 1`.
+    written: u64,
+    /// Sequence number of the first line not yet written to a file.
+    #[cfg(feature = "std")]
+    flushed: u64,
+    frame: u32,
+    min_level: LogLevel,
+    echo: bool,
+    worst: Option<LogLevel>,
+}
+
+impl Default for Log {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Log {
+    pub const fn new() -> Self {
+        Self {
+            lines: [LogLine::EMPTY; LOG_CAPACITY],
+            written: 0,
+            #[cfg(feature = "std")]
+            flushed: 0,
+            frame: 0,
+            min_level: LogLevel::Debug,
+            echo: cfg!(debug_assertions),
+            worst: None,
+        }
+    }
+
+    pub fn write(&mut self, level: LogLevel, args: fmt::Arguments<'_>) {
+        if level > self.min_level {
+            return;
+        }
+
+        let slot = &mut self.lines[(self.written % LOG_CAPACITY as u64) as usize];
+        *slot = LogLine {
+            level,
+            frame: self.frame,
+            ..LogLine::EMPTY
+        };
+        let _ = LineWriter(slot).write_fmt(args);
+
+        #[cfg(feature = "std")]
+        if self.echo {
+            eprintln!("{slot}");
+        }
+
+        self.written += 1;
+        self.worst = Some(self.worst.map_or(level, |worst| worst.min(level)));
+    }
+
+    pub fn set_frame(&mut self, frame: u32) {
+        self.frame = frame;
+    }
+
+    /// Drop lines less severe than `level`.
+    pub fn set_min_level(&mut self, level: LogLevel) {
+        self.min_level = level;
+    }
+
+    /// Mirror every line to stderr. On by default in debug builds.
+    pub fn set_echo(&mut self, echo: bool) {
+        self.echo = echo;
+    }
+
+    /// Most severe level logged so far.
+    pub fn worst(&self) -> Option<LogLevel> {
+        self.worst
+    }
+
+    pub fn len(&self) -> usize {
+        self.written.min(LOG_CAPACITY as u64) as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.written == 0
+    }
+
+    /// Total lines ever written, including those since overwritten.
+    pub fn written(&self) -> u64 {
+        self.written
+    }
+
+    /// Line `age` entries ago (0 = newest).
+    pub fn recent(&self, age: usize) -> Option<&LogLine> {
+        if age >= self.len() {
+            return None;
+        }
+
+        let seq = self.written - 1 - age as u64;
+        Some(&self.lines[(seq % LOG_CAPACITY as u64) as usize])
+    }
+
+    /// Iterate over the retained lines, oldest first.
+    pub fn iter(&self) -> impl Iterator<Item = &LogLine> {
+        (0..self.len()).rev().filter_map(|age| self.recent(age))
+    }
+
+    /// Append every line not yet flushed to the file at `path`.
+    ///
+    /// Lines that were overwritten in the ring buffer before being flushed
+    /// are lost; a marker line records how many.
+    #[cfg(feature = "std")]
+    pub fn flush_to_file(&mut self, path: &Path) -> io::Result<()> {
+        let oldest = self.written - self.len() as u64;
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+        if self.flushed < oldest {
+            writeln!(file, "... {} lines dropped", oldest - self.flushed)?;
+        }
+
+        for seq in self.flushed.max(oldest)..self.written {
+            writeln!(file, "{}", self.lines[(seq % LOG_CAPACITY as u64) as usize])?;
+        }
+
+        self.flushed = self.written;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+static LOG: Mutex<Log> = Mutex::new(Log::new());
+
+/// Lock the process-wide log.
+///
+/// A panic while logging does not disable logging: a poisoned lock is
+/// recovered, since the ring buffer is valid after every write.
+#[cfg(feature = "std")]
+pub fn global() -> MutexGuard<'static, Log> {
+    LOG.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/// Lock the process-wide log unless it is already locked.
+///
+/// For panic hooks: a panic raised while this thread holds the log would
+/// deadlock on `global()`.
+#[cfg(feature = "std")]
+pub fn try_global() -> Option<MutexGuard<'static, Log>> {
+    match LOG.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    }
+}
+
+/// Write to the process-wide log. Use the `log_*!` macros instead.
+#[cfg(feature = "std")]
+pub fn write(level: LogLevel, args: fmt::Arguments<'_>) {
+    global().write(level, args);
+}
+
+/// Without `std` there is no lock for a process-wide log, so the `log_*!`
+/// macros drop their message. A target can keep its own `Log` instead.
+#[cfg(not(feature = "std"))]
+pub fn write(_level: LogLevel, _args: fmt::Arguments<'_>) {}
+
+#[macro_export]
+macro_rules! log_error {
+    ($($arg:tt)*) => {
+        $crate::runtime::log::write($crate::runtime::log::LogLevel::Error, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        $crate::runtime::log::write($crate::runtime::log::LogLevel::Warn, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_info {
+    ($($arg:tt)*) => {
+        $crate::runtime::log::write($crate::runtime::log::LogLevel::Info, format_args!($($arg)*))
+    };
+}
+
+#[macro_export]
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        $crate::runtime::log::write($crate::runtime::log::LogLevel::Debug, format_args!($($arg)*))
+    };
+}
+
+/// Draw `rows` log lines at the bottom of the screen, newest last, skipping
+/// the `scroll` newest lines. Text is upper-cased to fit the font.
+pub fn render_log(framebuffer: &mut FrameBuffer, log: &Log, rows: usize, scroll: usize) {
+    let rows = rows.min(HEIGHT / LINE_HEIGHT - 1);
+    let top = HEIGHT - rows * LINE_HEIGHT;
+
+    framebuffer.fill_rect(0, top - 1, WIDTH, rows * LINE_HEIGHT + 1, 0);
+
+    for row in 0..rows {
+        let Some(line) = log.recent(scroll + rows - 1 - row) else {
+            continue;
+        };
+
+        let y = top + row * LINE_HEIGHT;
+        let color = match line.level {
+            LogLevel::Error | LogLevel::Warn => 3,
+            LogLevel::Info => 2,
+            LogLevel::Debug => 1,
+        };
+
+        let mut upper = [0u8; LOG_LINE_LEN];
+        let text = line.text();
+        upper[..text.len()].copy_from_slice(text.as_bytes());
+        upper[..text.len()].make_ascii_uppercase();
+
+        framebuffer.draw_text(
+            2,
+            y,
+            core::str::from_utf8(&upper[..text.len()]).unwrap_or(""),
+            color,
+        );
+    }
+}
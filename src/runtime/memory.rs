@@ -1,41 +1,279 @@
-use std::mem::{align_of, size_of};
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+/// Largest alignment an arena can serve.
+///
+/// The backing buffer is aligned to this boundary, so offsets computed
+/// relative to the start of the buffer are also correctly aligned in memory.
+pub const MAX_ALIGN: usize = 16;
+
+#[repr(C, align(16))]
+struct AlignedBuffer<const SIZE: usize>([u8; SIZE]);
 
 pub struct Arena<const SIZE: usize> {
-    buffer: [u8; SIZE],
-    offset: usize,
+    buffer: AlignedBuffer<SIZE>,
+    cursor: Cursor,
+    generation: u32,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MemoryError {
     OutOfMemory,
+    /// The handle was issued before the arena was last reset.
+    StaleHandle,
 }
 
-impl<const SIZE: usize> Arena<SIZE> {
-    pub const fn new() -> Self {
+/// An offset-based reference to a value stored in an arena.
+///
+/// Unlike `&mut T`, a handle does not borrow the arena: it can be stored in
+/// level data, copied freely, and resolved later with `Arena::get`. Because it
+/// records an offset rather than an address, it stays meaningful if the arena
+/// contents are written out and restored.
+///
+/// Handles are invalidated by `reset()`. Resolving a stale handle returns
+/// `MemoryError::StaleHandle` instead of reading reused memory.
+pub struct ArenaHandle<T> {
+    offset: usize,
+    generation: u32,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> ArenaHandle<T> {
+    /// Byte offset of the value from the start of the arena.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Encode the handle for storage alongside an arena snapshot.
+    pub fn to_bytes(&self) -> [u8; 8] {
+        let mut bytes = [0; 8];
+        bytes[..4].copy_from_slice(&(self.offset as u32).to_le_bytes());
+        bytes[4..].copy_from_slice(&self.generation.to_le_bytes());
+        bytes
+    }
+
+    /// Decode a handle written by `to_bytes`.
+    ///
+    /// # Safety
+    ///
+    /// The handle must have been issued for a `T` by the arena it will be
+    /// resolved against (or the snapshot that arena was restored from).
+    /// `get` only checks bounds and generation, not the type.
+    pub unsafe fn from_bytes(bytes: [u8; 8]) -> Self {
+        let [o0, o1, o2, o3, g0, g1, g2, g3] = bytes;
+
+        Self {
+            offset: u32::from_le_bytes([o0, o1, o2, o3]) as usize,
+            generation: u32::from_le_bytes([g0, g1, g2, g3]),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T> Clone for ArenaHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for ArenaHandle<T> {}
+
+impl<T> PartialEq for ArenaHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.offset == other.offset && self.generation == other.generation
+    }
+}
+
+impl<T> Eq for ArenaHandle<T> {}
+
+impl<T> core::fmt::Debug for ArenaHandle<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ArenaHandle")
+            .field("offset", &self.offset)
+            .field("generation", &self.generation)
+            .finish()
+    }
+}
+
+const fn align_up(addr: usize, align: usize) -> usize {
+    (addr + align - 1) & !(align - 1)
+}
+
+/// Maximum number of distinct tags tracked per arena between resets.
+/// Allocations under further tags are charged to the last entry.
+pub const MAX_TAGS: usize = 8;
+
+const UNTAGGED: &str = "untagged";
+
+/// Bytes charged to each allocation tag since the last reset.
+struct TagLedger {
+    current: &'static str,
+    entries: [(&'static str, usize); MAX_TAGS],
+    len: usize,
+}
+
+impl TagLedger {
+    const fn new() -> Self {
+        Self {
+            current: UNTAGGED,
+            entries: [("", 0); MAX_TAGS],
+            len: 0,
+        }
+    }
+
+    fn charge(&mut self, bytes: usize) {
+        let tag = self.current;
+        let index = match self.entries[..self.len].iter().position(|(t, _)| *t == tag) {
+            Some(index) => index,
+            None if self.len < MAX_TAGS => {
+                self.entries[self.len] = (tag, 0);
+                self.len += 1;
+                self.len - 1
+            }
+            None => MAX_TAGS - 1,
+        };
+
+        self.entries[index].1 += bytes;
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+/// Bump position plus the high-water marks it has reached.
+struct Cursor {
+    offset: usize,
+    peak: usize,
+    peak_since_reset: usize,
+    tags: TagLedger,
+}
+
+impl Cursor {
+    const fn new() -> Self {
         Self {
-            buffer: [0; SIZE],
             offset: 0,
+            peak: 0,
+            peak_since_reset: 0,
+            tags: TagLedger::new(),
         }
     }
 
-    fn align_up(addr: usize, align: usize) -> usize {
-        (addr + align - 1) & !(align - 1)
+    fn reset(&mut self) {
+        self.offset = 0;
+        self.peak_since_reset = 0;
+        self.tags.clear();
     }
 
-    pub fn alloc<T>(&mut self, value: T) -> Result<&mut T, MemoryError> {
-        let align = align_of::<T>();
-        let size = size_of::<T>();
+    /// Advance past a `size`-byte block at `align` and return the block's start.
+    fn bump(&mut self, capacity: usize, size: usize, align: usize) -> Result<usize, MemoryError> {
+        if align > MAX_ALIGN {
+            return Err(MemoryError::OutOfMemory);
+        }
 
-        let start = Self::align_up(self.offset, align);
-        let end = start + size;
+        let start = align_up(self.offset, align);
+        let end = start.checked_add(size).ok_or(MemoryError::OutOfMemory)?;
 
-        if end > SIZE {
+        if end > capacity {
             return Err(MemoryError::OutOfMemory);
         }
 
+        self.tags.charge(end - self.offset);
         self.offset = end;
+        self.peak = self.peak.max(end);
+        self.peak_since_reset = self.peak_since_reset.max(end);
+        Ok(start)
+    }
+}
 
-        let ptr = self.buffer.as_mut_ptr();
+/// Counts the bytes formatted into it.
+struct ByteCount(usize);
+
+impl fmt::Write for ByteCount {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+/// Copies what is formatted into `bytes`, failing once they are full.
+struct SliceWriter<'a> {
+    bytes: &'a mut [u8],
+    len: usize,
+}
+
+impl fmt::Write for SliceWriter<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let end = self.len + s.len();
+        let dest = self.bytes.get_mut(self.len..end).ok_or(fmt::Error)?;
+
+        dest.copy_from_slice(s.as_bytes());
+        self.len = end;
+        Ok(())
+    }
+}
+
+/// Bytes `args` formats to.
+fn formatted_len(args: fmt::Arguments<'_>) -> usize {
+    let mut count = ByteCount(0);
+
+    fmt::write(&mut count, args).expect("a formatting trait implementation returned an error");
+    count.0
+}
+
+/// Format `args` into `bytes`, sized by `formatted_len`.
+///
+/// Panics, like `format!`, if a `Display` impl fails, or writes more the
+/// second time round.
+fn format_into<'a>(bytes: &'a mut [u8], args: fmt::Arguments<'_>) -> &'a str {
+    let mut writer = SliceWriter { bytes, len: 0 };
+
+    fmt::write(&mut writer, args).expect("a formatting trait implementation returned an error");
+
+    let SliceWriter { bytes, len } = writer;
+
+    // SAFETY: only whole `str`s were copied in.
+    unsafe { core::str::from_utf8_unchecked(&bytes[..len]) }
+}
+
+/// Format into an arena like `format!`, without the heap: the `&str` lives
+/// as long as the allocation, so until the arena is reset.
+///
+/// `fmt_to_arena!(memory.frame, "SCORE {}", score)` takes an `Arena`,
+/// `SubArena` or `Scope` and returns `Result<&str, MemoryError>`. Text
+/// without arguments is returned as is, without allocating.
+#[macro_export]
+macro_rules! fmt_to_arena {
+    ($arena:expr, $($arg:tt)*) => {
+        $arena.alloc_fmt(format_args!($($arg)*))
+    };
+}
+
+impl<const SIZE: usize> Default for Arena<SIZE> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const SIZE: usize> Arena<SIZE> {
+    pub const fn new() -> Self {
+        Self {
+            buffer: AlignedBuffer([0; SIZE]),
+            cursor: Cursor::new(),
+            generation: 0,
+        }
+    }
+
+    fn reserve(&mut self, size: usize, align: usize) -> Result<usize, MemoryError> {
+        self.cursor.bump(SIZE, size, align)
+    }
+
+    pub fn alloc<T>(&mut self, value: T) -> Result<&mut T, MemoryError> {
+        let start = self.reserve(size_of::<T>(), align_of::<T>())?;
+        let ptr = self.buffer.0.as_mut_ptr();
 
         Ok(unsafe {
             let typed_ptr = ptr.add(start) as *mut T;
@@ -45,46 +283,714 @@ impl<const SIZE: usize> Arena<SIZE> {
     }
 
     pub fn alloc_slice<T>(&mut self, count: usize) -> Result<&mut [T], MemoryError> {
-        let align = align_of::<T>();
-        let size = size_of::<T>() * count;
+        let size = size_of::<T>()
+            .checked_mul(count)
+            .ok_or(MemoryError::OutOfMemory)?;
+        let start = self.reserve(size, align_of::<T>())?;
+        let ptr = self.buffer.0.as_mut_ptr();
 
-        let start = Self::align_up(self.offset, align);
-        let end = start + size;
+        Ok(unsafe {
+            let typed_ptr = ptr.add(start) as *mut T;
+            core::slice::from_raw_parts_mut(typed_ptr, count)
+        })
+    }
 
-        if end > SIZE {
-            return Err(MemoryError::OutOfMemory);
+    /// Format `args` into the arena. Use `fmt_to_arena!` instead.
+    pub fn alloc_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<&str, MemoryError> {
+        if let Some(text) = args.as_str() {
+            return Ok(text);
         }
 
-        self.offset = end;
+        let bytes = self.alloc_slice(formatted_len(args))?;
+        Ok(format_into(bytes, args))
+    }
+
+    /// Allocate `value` and return a relocatable handle instead of a borrow.
+    pub fn alloc_handle<T>(&mut self, value: T) -> Result<ArenaHandle<T>, MemoryError> {
+        let start = self.reserve(size_of::<T>(), align_of::<T>())?;
+
+        unsafe {
+            let typed_ptr = self.buffer.0.as_mut_ptr().add(start) as *mut T;
+            typed_ptr.write(value);
+        }
+
+        Ok(ArenaHandle {
+            offset: start,
+            generation: self.generation,
+            _marker: PhantomData,
+        })
+    }
+
+    fn check_handle<T>(&self, handle: ArenaHandle<T>) -> Result<(), MemoryError> {
+        if handle.generation != self.generation
+            || handle.offset + size_of::<T>() > self.cursor.offset
+        {
+            return Err(MemoryError::StaleHandle);
+        }
+
+        Ok(())
+    }
+
+    pub fn get<T>(&self, handle: ArenaHandle<T>) -> Result<&T, MemoryError> {
+        self.check_handle(handle)?;
+
+        Ok(unsafe { &*(self.buffer.0.as_ptr().add(handle.offset) as *const T) })
+    }
+
+    pub fn get_mut<T>(&mut self, handle: ArenaHandle<T>) -> Result<&mut T, MemoryError> {
+        self.check_handle(handle)?;
+
+        Ok(unsafe { &mut *(self.buffer.0.as_mut_ptr().add(handle.offset) as *mut T) })
+    }
+
+    /// Run `f` with a temporary allocation scope.
+    ///
+    /// Everything allocated through the scope is released when `f` returns
+    /// (or unwinds). The closure is generic over the scope lifetime, so no
+    /// reference handed out by the scope can be returned from it.
+    pub fn scope<R>(&mut self, f: impl for<'s> FnOnce(&mut Scope<'s>) -> R) -> R {
+        let base = self.buffer.0.as_mut_ptr();
+        let mut scope = Scope::new(base, &mut self.cursor, SIZE);
+        f(&mut scope)
+    }
+
+    /// Reserve `bytes` and return them as an independent sub-arena.
+    ///
+    /// The sub-arena owns its region exclusively for its lifetime and is
+    /// `Send`, so it can be moved into a worker thread (e.g. via
+    /// `std::thread::scope`) while the main thread keeps using the other
+    /// arenas of `RuntimeMemory`. The reserved bytes stay counted as used
+    /// until this arena is reset.
+    pub fn split_off(&mut self, bytes: usize) -> Result<SubArena<'_>, MemoryError> {
+        let start = self.reserve(bytes, MAX_ALIGN)?;
+
+        Ok(SubArena::new(&mut self.buffer.0[start..start + bytes]))
+    }
+
+    /// Write the used region of the arena to `writer`.
+    ///
+    /// The snapshot records the used byte count and the current generation,
+    /// so handles issued before the snapshot still resolve after
+    /// `deserialize`. Values are copied byte for byte: only data that links
+    /// to other arena data through `ArenaHandle` (never `&T` or raw
+    /// pointers) is meaningful after a restore.
+    #[cfg(feature = "std")]
+    pub fn serialize(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&SNAPSHOT_MAGIC)?;
+        writer.write_all(&(MAX_ALIGN as u32).to_le_bytes())?;
+        writer.write_all(&(self.cursor.offset as u32).to_le_bytes())?;
+        writer.write_all(&self.generation.to_le_bytes())?;
+        writer.write_all(&self.buffer.0[..self.cursor.offset])
+    }
+
+    /// Replace the arena contents with a snapshot written by `serialize`.
+    ///
+    /// Fails with `InvalidData`, leaving the arena untouched, if the header
+    /// is malformed or the snapshot is larger than this arena. If reading the
+    /// payload itself fails, the arena is reset.
+    ///
+    /// # Safety
+    ///
+    /// The snapshot must come from an arena holding the same types at the
+    /// same offsets (in practice: the same build of the game). Existing
+    /// handles reinterpret the restored bytes as their `T`.
+    #[cfg(feature = "std")]
+    pub unsafe fn deserialize(&mut self, reader: &mut impl Read) -> io::Result<()> {
+        let mut header = [0u8; 16];
+        reader.read_exact(&mut header)?;
+
+        let word =
+            |i: usize| u32::from_le_bytes([header[i], header[i + 1], header[i + 2], header[i + 3]]);
+
+        if header[..4] != SNAPSHOT_MAGIC || word(4) as usize != MAX_ALIGN {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "not an arena snapshot",
+            ));
+        }
+
+        let used = word(8) as usize;
 
+        if used > SIZE {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "snapshot larger than arena",
+            ));
+        }
+
+        if let Err(err) = reader.read_exact(&mut self.buffer.0[..used]) {
+            self.reset();
+            return Err(err);
+        }
+
+        self.cursor.reset();
+        self.cursor.offset = used;
+        self.cursor.peak = self.cursor.peak.max(used);
+        self.cursor.peak_since_reset = used;
+        self.generation = word(12);
+
+        Ok(())
+    }
+
+    /// Release every allocation. Outstanding handles become stale.
+    ///
+    /// The peak is kept, so it reports the high-water mark across resets.
+    pub fn reset(&mut self) {
+        self.cursor.reset();
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn used(&self) -> usize {
+        self.cursor.offset
+    }
+
+    pub fn remaining(&self) -> usize {
+        SIZE - self.cursor.offset
+    }
+
+    pub fn capacity(&self) -> usize {
+        SIZE
+    }
+
+    /// Highest `used()` value reached since creation or `reset_peak()`.
+    pub fn peak(&self) -> usize {
+        self.cursor.peak
+    }
+
+    pub fn reset_peak(&mut self) {
+        self.cursor.peak = self.cursor.offset;
+    }
+
+    /// Highest `used()` value reached since the last `reset()`, including
+    /// allocations already released by a scope.
+    pub fn peak_since_reset(&self) -> usize {
+        self.cursor.peak_since_reset
+    }
+
+    /// Charge subsequent allocations to `tag` until the tag changes.
+    ///
+    /// Tags are cleared on `reset()`; allocations made without a tag are
+    /// reported as `"untagged"`.
+    pub fn set_tag(&mut self, tag: &'static str) {
+        self.cursor.tags.current = tag;
+    }
+
+    /// Bytes charged to each tag since the last reset, including alignment
+    /// padding and allocations already released by a scope.
+    pub fn tags(&self) -> &[(&'static str, usize)] {
+        &self.cursor.tags.entries[..self.cursor.tags.len]
+    }
+}
+
+#[cfg(feature = "std")]
+const SNAPSHOT_MAGIC: [u8; 4] = *b"BBAR";
+
+/// A bump allocator over a region borrowed from a parent arena.
+///
+/// Created by `Arena::split_off`. Holds no pointers back into the parent,
+/// so it is `Send` and can be used on another thread.
+pub struct SubArena<'a> {
+    buffer: &'a mut [u8],
+    cursor: Cursor,
+}
+
+impl<'a> SubArena<'a> {
+    fn new(buffer: &'a mut [u8]) -> Self {
+        Self {
+            buffer,
+            cursor: Cursor::new(),
+        }
+    }
+
+    fn reserve(&mut self, size: usize, align: usize) -> Result<usize, MemoryError> {
+        self.cursor.bump(self.buffer.len(), size, align)
+    }
+
+    pub fn alloc<T>(&mut self, value: T) -> Result<&mut T, MemoryError> {
+        let start = self.reserve(size_of::<T>(), align_of::<T>())?;
         let ptr = self.buffer.as_mut_ptr();
 
         Ok(unsafe {
             let typed_ptr = ptr.add(start) as *mut T;
-            std::slice::from_raw_parts_mut(typed_ptr, count)
+            typed_ptr.write(value);
+            &mut *typed_ptr
         })
     }
 
+    pub fn alloc_slice<T>(&mut self, count: usize) -> Result<&mut [T], MemoryError> {
+        let size = size_of::<T>()
+            .checked_mul(count)
+            .ok_or(MemoryError::OutOfMemory)?;
+        let start = self.reserve(size, align_of::<T>())?;
+        let ptr = self.buffer.as_mut_ptr();
+
+        Ok(unsafe {
+            let typed_ptr = ptr.add(start) as *mut T;
+            core::slice::from_raw_parts_mut(typed_ptr, count)
+        })
+    }
+
+    /// Format `args` into the sub-arena. Use `fmt_to_arena!` instead.
+    pub fn alloc_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<&str, MemoryError> {
+        if let Some(text) = args.as_str() {
+            return Ok(text);
+        }
+
+        let bytes = self.alloc_slice(formatted_len(args))?;
+        Ok(format_into(bytes, args))
+    }
+
+    /// Run `f` with a temporary allocation scope. See `Arena::scope`.
+    pub fn scope<R>(&mut self, f: impl for<'s> FnOnce(&mut Scope<'s>) -> R) -> R {
+        let capacity = self.buffer.len();
+        let base = self.buffer.as_mut_ptr();
+        let mut scope = Scope::new(base, &mut self.cursor, capacity);
+        f(&mut scope)
+    }
+
+    /// Carve a further sub-arena out of this one.
+    pub fn split_off(&mut self, bytes: usize) -> Result<SubArena<'_>, MemoryError> {
+        let start = self.reserve(bytes, MAX_ALIGN)?;
+
+        Ok(SubArena::new(&mut self.buffer[start..start + bytes]))
+    }
+
     pub fn reset(&mut self) {
-        self.offset = 0;
+        self.cursor.reset();
     }
 
     pub fn used(&self) -> usize {
-        self.offset
+        self.cursor.offset
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.buffer.len() - self.cursor.offset
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    pub fn peak(&self) -> usize {
+        self.cursor.peak
+    }
+}
+
+/// A temporary allocation region that rewinds its arena when dropped.
+///
+/// Created by `Arena::scope`. Allocations borrow from the scope lifetime
+/// `'a`, so several can be held at once, but none can outlive the scope.
+pub struct Scope<'a> {
+    base: *mut u8,
+    cursor: &'a mut Cursor,
+    capacity: usize,
+    mark: usize,
+    _marker: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> Scope<'a> {
+    fn new(base: *mut u8, cursor: &'a mut Cursor, capacity: usize) -> Self {
+        let mark = cursor.offset;
+
+        Self {
+            base,
+            cursor,
+            capacity,
+            mark,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn alloc<T>(&mut self, value: T) -> Result<&'a mut T, MemoryError> {
+        let start = self
+            .cursor
+            .bump(self.capacity, size_of::<T>(), align_of::<T>())?;
+
+        // Each allocation is a disjoint region of the buffer, so handing out
+        // several `&'a mut` at once does not alias.
+        Ok(unsafe {
+            let typed_ptr = self.base.add(start) as *mut T;
+            typed_ptr.write(value);
+            &mut *typed_ptr
+        })
+    }
+
+    pub fn alloc_slice<T>(&mut self, count: usize) -> Result<&'a mut [T], MemoryError> {
+        let size = size_of::<T>()
+            .checked_mul(count)
+            .ok_or(MemoryError::OutOfMemory)?;
+        let start = self.cursor.bump(self.capacity, size, align_of::<T>())?;
+
+        Ok(unsafe {
+            let typed_ptr = self.base.add(start) as *mut T;
+            core::slice::from_raw_parts_mut(typed_ptr, count)
+        })
+    }
+
+    /// Format `args` into the scope. Use `fmt_to_arena!` instead.
+    pub fn alloc_fmt(&mut self, args: fmt::Arguments<'_>) -> Result<&'a str, MemoryError> {
+        if let Some(text) = args.as_str() {
+            return Ok(text);
+        }
+
+        let bytes = self.alloc_slice(formatted_len(args))?;
+        Ok(format_into(bytes, args))
+    }
+
+    /// Open a nested scope that rewinds to the current position on exit.
+    pub fn scope<R>(&mut self, f: impl for<'s> FnOnce(&mut Scope<'s>) -> R) -> R {
+        let mut scope = Scope::new(self.base, self.cursor, self.capacity);
+        f(&mut scope)
+    }
+
+    /// Charge subsequent allocations to `tag`. See `Arena::set_tag`.
+    pub fn set_tag(&mut self, tag: &'static str) {
+        self.cursor.tags.current = tag;
+    }
+
+    /// Bytes allocated through this scope so far.
+    pub fn used(&self) -> usize {
+        self.cursor.offset - self.mark
     }
 
     pub fn remaining(&self) -> usize {
-        SIZE - self.offset
+        self.capacity - self.cursor.offset
+    }
+}
+
+impl Drop for Scope<'_> {
+    fn drop(&mut self) {
+        self.cursor.offset = self.mark;
+    }
+}
+
+/// A byte range inside a ROM-style asset blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RomRegion {
+    pub offset: usize,
+    pub len: usize,
+}
+
+impl RomRegion {
+    /// Slice this region out of `blob`. In a `const` item an out-of-range
+    /// region is a compile error.
+    pub const fn slice(self, blob: &'static [u8]) -> &'static [u8] {
+        blob.split_at(self.offset).1.split_at(self.len).0
+    }
+}
+
+/// Bump layout of `N` assets packed back to back, evaluated at compile time.
+///
+/// This is the arena's placement rule run in a `const fn`: each asset starts
+/// at the previous end rounded up to `align`. The asset pipeline packs the
+/// blob with the same rule, so the runtime can `include_bytes!` one file and
+/// slice every asset out of it with `const` offsets.
+pub struct RomLayout<const N: usize> {
+    regions: [RomRegion; N],
+    size: usize,
+}
+
+impl<const N: usize> RomLayout<N> {
+    pub const fn new(sizes: [usize; N], align: usize) -> Self {
+        assert!(align.is_power_of_two(), "alignment must be a power of two");
+
+        let mut regions = [RomRegion { offset: 0, len: 0 }; N];
+        let mut offset = 0;
+        let mut i = 0;
+
+        while i < N {
+            offset = align_up(offset, align);
+            regions[i] = RomRegion {
+                offset,
+                len: sizes[i],
+            };
+            offset += sizes[i];
+            i += 1;
+        }
+
+        Self {
+            regions,
+            size: offset,
+        }
+    }
+
+    pub const fn region(&self, index: usize) -> RomRegion {
+        self.regions[index]
+    }
+
+    /// Total size of the laid-out blob in bytes.
+    pub const fn size(&self) -> usize {
+        self.size
+    }
+}
+
+/// What `OvercommitCheck` does when an arena goes over its soft limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OvercommitPolicy {
+    /// Log the report as a warning and continue.
+    Warn,
+    /// Panic with the report.
+    Panic,
+}
+
+/// Soft budget for an arena, checked once per frame before it is reset.
+///
+/// The hard limit is the arena size, where allocation fails outright. The
+/// soft limit sits below it so budget creep shows up during development,
+/// together with the tags responsible, long before the arena runs out.
+#[derive(Debug, Clone, Copy)]
+pub struct OvercommitCheck {
+    pub name: &'static str,
+    pub soft_limit: usize,
+    pub policy: OvercommitPolicy,
+}
+
+impl OvercommitCheck {
+    /// Panics in debug builds and warns in release builds.
+    pub const fn new(name: &'static str, soft_limit: usize) -> Self {
+        Self {
+            name,
+            soft_limit,
+            policy: if cfg!(debug_assertions) {
+                OvercommitPolicy::Panic
+            } else {
+                OvercommitPolicy::Warn
+            },
+        }
+    }
+
+    /// Compare the arena's peak since its last reset against the soft limit.
+    /// Returns `true` if the limit was exceeded.
+    pub fn check<const SIZE: usize>(&self, arena: &Arena<SIZE>) -> bool {
+        let peak = arena.peak_since_reset();
+
+        if peak <= self.soft_limit {
+            return false;
+        }
+
+        let report = Overcommit {
+            name: self.name,
+            peak,
+            soft_limit: self.soft_limit,
+            tags: arena.tags(),
+        };
+
+        match self.policy {
+            OvercommitPolicy::Warn => crate::log_warn!("{report}"),
+            OvercommitPolicy::Panic => panic!("{report}"),
+        }
+
+        true
+    }
+}
+
+struct Overcommit<'a> {
+    name: &'static str,
+    peak: usize,
+    soft_limit: usize,
+    tags: &'a [(&'static str, usize)],
+}
+
+impl core::fmt::Display for Overcommit<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} arena over budget: peaked at {} of {} bytes",
+            self.name, self.peak, self.soft_limit
+        )?;
+
+        for (tag, bytes) in self.tags {
+            write!(f, "\n  {tag}: {bytes}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Stable reference to an object in a `SlotAllocator`.
+///
+/// The generation changes every time a slot is freed, so an id kept after
+/// `free()` resolves to `MemoryError::StaleHandle` instead of aliasing
+/// whatever was allocated into the slot next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SlotId {
+    index: u32,
+    generation: u32,
+}
+
+impl SlotId {
+    pub fn index(&self) -> usize {
+        self.index as usize
+    }
+}
+
+const NO_SLOT: u32 = u32::MAX;
+
+struct Slot<T> {
+    value: Option<T>,
+    generation: u32,
+    next_free: u32,
+}
+
+/// Fixed-capacity allocator for same-sized objects with individual lifetimes.
+///
+/// Arenas free everything at once; this frees one object at a time in O(1)
+/// through an intrusive free list, without moving live objects. Use it for
+/// things like persistent projectiles or audio voices whose lifetimes don't
+/// line up with the global/level/frame phases.
+pub struct SlotAllocator<T, const N: usize> {
+    slots: [Slot<T>; N],
+    free_head: u32,
+    high_water: usize,
+    len: usize,
+}
+
+impl<T, const N: usize> Default for SlotAllocator<T, N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T, const N: usize> SlotAllocator<T, N> {
+    pub const fn new() -> Self {
+        Self {
+            slots: [const {
+                Slot {
+                    value: None,
+                    generation: 0,
+                    next_free: NO_SLOT,
+                }
+            }; N],
+            free_head: NO_SLOT,
+            high_water: 0,
+            len: 0,
+        }
+    }
+
+    pub fn alloc(&mut self, value: T) -> Result<SlotId, MemoryError> {
+        let index = if self.free_head != NO_SLOT {
+            let index = self.free_head as usize;
+            self.free_head = self.slots[index].next_free;
+            index
+        } else if self.high_water < N {
+            self.high_water += 1;
+            self.high_water - 1
+        } else {
+            return Err(MemoryError::OutOfMemory);
+        };
+
+        let slot = &mut self.slots[index];
+        slot.value = Some(value);
+        slot.next_free = NO_SLOT;
+        self.len += 1;
+
+        Ok(SlotId {
+            index: index as u32,
+            generation: slot.generation,
+        })
+    }
+
+    /// Release the object and return it. The id and all copies become stale.
+    pub fn free(&mut self, id: SlotId) -> Result<T, MemoryError> {
+        let slot = self
+            .slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .ok_or(MemoryError::StaleHandle)?;
+        let value = slot.value.take().ok_or(MemoryError::StaleHandle)?;
+
+        slot.generation = slot.generation.wrapping_add(1);
+        slot.next_free = self.free_head;
+        self.free_head = id.index;
+        self.len -= 1;
+
+        Ok(value)
+    }
+
+    pub fn get(&self, id: SlotId) -> Result<&T, MemoryError> {
+        self.slots
+            .get(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_ref())
+            .ok_or(MemoryError::StaleHandle)
+    }
+
+    pub fn get_mut(&mut self, id: SlotId) -> Result<&mut T, MemoryError> {
+        self.slots
+            .get_mut(id.index as usize)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.value.as_mut())
+            .ok_or(MemoryError::StaleHandle)
+    }
+
+    /// Iterate over live objects in slot order.
+    pub fn iter(&self) -> impl Iterator<Item = (SlotId, &T)> {
+        self.slots[..self.high_water]
+            .iter()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let id = SlotId {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+                slot.value.as_ref().map(|value| (id, value))
+            })
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (SlotId, &mut T)> {
+        self.slots[..self.high_water]
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let id = SlotId {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+                slot.value.as_mut().map(|value| (id, value))
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        N
     }
 }
 
-pub struct RuntimeMemory {
-    pub global: Arena<{ 256 * 1024 }>,
-    pub level: Arena<{ 512 * 1024 }>,
-    pub frame: Arena<{ 256 * 1024 }>,
+/// The three arena budgets, in bytes.
+///
+/// Defaults to the 256K/512K/256K split from ADR 0001. Use one of the
+/// `profile` aliases to target a stricter or more relaxed envelope.
+pub struct RuntimeMemory<
+    const GLOBAL: usize = { 256 * 1024 },
+    const LEVEL: usize = { 512 * 1024 },
+    const FRAME: usize = { 256 * 1024 },
+> {
+    pub global: Arena<GLOBAL>,
+    pub level: Arena<LEVEL>,
+    pub frame: Arena<FRAME>,
+}
+
+impl<const GLOBAL: usize, const LEVEL: usize, const FRAME: usize> Default
+    for RuntimeMemory<GLOBAL, LEVEL, FRAME>
+{
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl RuntimeMemory {
+impl<const GLOBAL: usize, const LEVEL: usize, const FRAME: usize>
+    RuntimeMemory<GLOBAL, LEVEL, FRAME>
+{
+    /// Combined size of all three arenas.
+    pub const TOTAL: usize = GLOBAL + LEVEL + FRAME;
+
     pub const fn new() -> Self {
         Self {
             global: Arena::new(),
@@ -93,3 +999,32 @@ impl RuntimeMemory {
         }
     }
 }
+
+/// Named memory budgets.
+pub mod profile {
+    use super::RuntimeMemory;
+
+    /// 64 KB total, roughly the work RAM of a real handheld.
+    pub type Tiny = RuntimeMemory<{ 16 * 1024 }, { 32 * 1024 }, { 16 * 1024 }>;
+
+    /// 1 MB total, the project's reference envelope.
+    pub type Standard = RuntimeMemory;
+
+    /// 4 MB total, for relaxed desktop builds and tooling.
+    pub type Large = RuntimeMemory<{ 1024 * 1024 }, { 2048 * 1024 }, { 1024 * 1024 }>;
+}
+
+#[cfg(all(feature = "profile_tiny", feature = "profile_large"))]
+compile_error!("features `profile_tiny` and `profile_large` are mutually exclusive");
+
+/// The memory profile selected by Cargo features.
+#[cfg(feature = "profile_tiny")]
+pub type ActiveMemory = profile::Tiny;
+
+/// The memory profile selected by Cargo features.
+#[cfg(feature = "profile_large")]
+pub type ActiveMemory = profile::Large;
+
+/// The memory profile selected by Cargo features.
+#[cfg(not(any(feature = "profile_tiny", feature = "profile_large")))]
+pub type ActiveMemory = profile::Standard;
@@ -1,3 +1,67 @@
 #[cfg(feature = "debug_overlay")]
+pub mod bugreport;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod crash;
+#[cfg(feature = "std")]
+pub mod daily;
+#[cfg(feature = "std")]
+pub mod error;
+#[cfg(feature = "debug_overlay")]
+pub mod framedebug;
+#[cfg(feature = "debug_overlay")]
 pub mod debug;
+pub mod hash;
+#[cfg(feature = "std")]
+pub mod headless;
+#[cfg(feature = "leaderboard")]
+pub mod leaderboard;
+pub mod log;
 pub mod memory;
+#[cfg(feature = "std")]
+pub mod netplay;
+pub mod pacing;
+pub mod pack;
+pub mod profile;
+pub mod quality;
+#[cfg(feature = "debug_server")]
+pub mod remote;
+#[cfg(feature = "std")]
+pub mod replay;
+pub mod rng;
+#[cfg(feature = "std")]
+pub mod save;
+#[cfg(feature = "std")]
+pub mod soak;
+#[cfg(feature = "std")]
+pub mod splits;
+pub mod static_cell;
+#[cfg(feature = "debug_overlay")]
+pub mod vram;
+
+use crate::renderer::framebuffer::FrameBuffer;
+use crate::runtime::memory::ActiveMemory;
+use crate::runtime::static_cell::StaticCell;
+
+static MEMORY: StaticCell<ActiveMemory> = StaticCell::new(ActiveMemory::new());
+static FRAMEBUFFER: StaticCell<FrameBuffer> = StaticCell::new(FrameBuffer::new());
+
+/// The engine's owned hardware: the memory arenas and the framebuffer.
+///
+/// Both live in static storage (see ADR 0007) but are reachable only through
+/// this struct, which can be created once per process.
+pub struct Runtime {
+    pub memory: &'static mut ActiveMemory,
+    pub framebuffer: &'static mut FrameBuffer,
+}
+
+impl Runtime {
+    /// Claim the runtime's static storage. Returns `None` on a second call.
+    pub fn take() -> Option<Self> {
+        Some(Self {
+            memory: MEMORY.take()?,
+            framebuffer: FRAMEBUFFER.take()?,
+        })
+    }
+}
@@ -0,0 +1,351 @@
+//! Lockstep two-player netplay over UDP.
+//!
+//! Both peers run the whole simulation. A tick only runs once both players'
+//! input for it is known, so the two simulations see the same input on
+//! every frame and, being deterministic, stay identical. Local input is
+//! scheduled `delay` ticks ahead, which gives it time to reach the peer
+//! before either side needs it; a peer that falls behind stalls the other.
+//!
+//! The host takes the first ship and the guest the second. Every datagram
+//! starts with `BBNP` and a kind byte; all numbers are little-endian:
+//!
+//! | Kind        | Body                                                       |
+//! |-------------|------------------------------------------------------------|
+//! | 0 `HELLO`   | Version (2)                                                |
+//! | 1 `WELCOME` | Version (2), input delay (1)                               |
+//! | 2 `INPUT`   | First frame (4), count (1), inputs (count), hash frame (4), state hash (8) |
+//!
+//! `INPUT` repeats the sender's last `REDUNDANT_INPUTS` inputs, so a lost
+//! datagram is made up for by the next. It also carries the state hash of
+//! the newest frame that is a multiple of `HASH_INTERVAL`; a peer whose own
+//! hash for that frame differs reports a desync.
+
+use std::io::{self, ErrorKind};
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::input::Input;
+
+const MAGIC: [u8; 4] = *b"BBNP";
+const VERSION: u16 = 1;
+
+const HELLO: u8 = 0;
+const WELCOME: u8 = 1;
+const INPUT: u8 = 2;
+
+/// Input delay when none is given, in ticks.
+pub const DEFAULT_DELAY: u32 = 2;
+
+/// Largest input delay; more would overrun the input rings.
+pub const MAX_DELAY: u32 = 30;
+
+/// Inputs sent with every datagram.
+const REDUNDANT_INPUTS: usize = 8;
+
+/// Frames of input kept per player. Neither side can get further ahead of
+/// the other than `MAX_DELAY` plus a datagram's worth.
+const RING: usize = 64;
+
+/// State hashes are compared every this many frames.
+pub const HASH_INTERVAL: u32 = 60;
+
+const LARGEST_DATAGRAM: usize = 5 + 5 + REDUNDANT_INPUTS + 12;
+
+/// How long to wait for the peer, in handshakes and in play.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How often the guest repeats `HELLO`.
+const HELLO_INTERVAL: Duration = Duration::from_millis(100);
+
+/// One peer of a lockstep session.
+pub struct Lockstep {
+    socket: UdpSocket,
+    /// 0 on the host, 1 on the guest: which ship the local input steers.
+    seat: usize,
+    delay: u32,
+    local: [Input; RING],
+    /// Frames before this have local input.
+    local_until: u32,
+    remote: [Input; RING],
+    /// Frames before this have the peer's input.
+    remote_until: u32,
+    /// The newest checked frame and our hash for it.
+    local_hash: Option<(u32, u64)>,
+    remote_hash: Option<(u32, u64)>,
+    desync: Option<u32>,
+    last_heard: Instant,
+}
+
+impl Lockstep {
+    /// Wait on `addr` for a guest to join, then start a session with
+    /// `delay` ticks of input delay.
+    pub fn host(addr: impl ToSocketAddrs, delay: u32) -> io::Result<Self> {
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(TIMEOUT))?;
+
+        let mut buf = [0u8; LARGEST_DATAGRAM];
+
+        let peer = loop {
+            let (len, peer) = match socket.recv_from(&mut buf) {
+                Ok(received) => received,
+                Err(err) if is_timeout(&err) => continue,
+                Err(err) => return Err(err),
+            };
+
+            if let Some(HELLO) = kind(&buf[..len]) {
+                check_version(&buf[..len])?;
+                break peer;
+            }
+        };
+
+        let delay = delay.min(MAX_DELAY);
+
+        socket.connect(peer)?;
+        socket.send(&welcome(delay))?;
+        Self::new(socket, 0, delay)
+    }
+
+    /// Join the host at `addr` and take its input delay.
+    pub fn join(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind((std::net::Ipv4Addr::UNSPECIFIED, 0))?;
+        socket.connect(addr)?;
+        socket.set_read_timeout(Some(HELLO_INTERVAL))?;
+
+        let mut hello = MAGIC.to_vec();
+        hello.push(HELLO);
+        hello.extend_from_slice(&VERSION.to_le_bytes());
+
+        let started = Instant::now();
+        let mut buf = [0u8; LARGEST_DATAGRAM];
+
+        loop {
+            if started.elapsed() > TIMEOUT {
+                return Err(io::Error::new(ErrorKind::TimedOut, "no answer from host"));
+            }
+
+            socket.send(&hello)?;
+
+            let len = match socket.recv(&mut buf) {
+                Ok(len) => len,
+                Err(err) if is_timeout(&err) => continue,
+                Err(err) => return Err(err),
+            };
+
+            if let Some(WELCOME) = kind(&buf[..len])
+                && len >= 8
+            {
+                check_version(&buf[..len])?;
+                return Self::new(socket, 1, (buf[7] as u32).min(MAX_DELAY));
+            }
+        }
+    }
+
+    fn new(socket: UdpSocket, seat: usize, delay: u32) -> io::Result<Self> {
+        socket.set_nonblocking(true)?;
+
+        Ok(Self {
+            socket,
+            seat,
+            delay,
+            // The first `delay` frames run with no buttons held.
+            local: [Input::empty(); RING],
+            local_until: delay,
+            remote: [Input::empty(); RING],
+            remote_until: delay,
+            local_hash: None,
+            remote_hash: None,
+            desync: None,
+            last_heard: Instant::now(),
+        })
+    }
+
+    /// 0 if this peer steers the first ship, 1 for the second.
+    pub fn seat(&self) -> usize {
+        self.seat
+    }
+
+    pub fn delay(&self) -> u32 {
+        self.delay
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.peer_addr()
+    }
+
+    /// Schedule `local` for `frame + delay` if that frame has no input yet,
+    /// exchange input with the peer, and return both players' input for
+    /// `frame` in seat order. `None` means the peer's has not arrived: skip
+    /// the tick and ask again. Errors if the peer has been silent for too
+    /// long.
+    pub fn inputs(&mut self, frame: u32, local: Input) -> io::Result<Option<[Input; 2]>> {
+        if self.local_until <= frame + self.delay {
+            self.local[self.local_until as usize % RING] = local;
+            self.local_until += 1;
+        }
+
+        self.send()?;
+        self.receive()?;
+
+        if self.last_heard.elapsed() > TIMEOUT {
+            return Err(io::Error::new(
+                ErrorKind::TimedOut,
+                "peer stopped answering",
+            ));
+        }
+
+        if frame >= self.remote_until || frame >= self.local_until {
+            return Ok(None);
+        }
+
+        let ours = self.local[frame as usize % RING];
+        let theirs = self.remote[frame as usize % RING];
+
+        Ok(Some(if self.seat == 0 {
+            [ours, theirs]
+        } else {
+            [theirs, ours]
+        }))
+    }
+
+    /// Note the state hash after `frame`; only frames that are multiples of
+    /// `HASH_INTERVAL` are kept and compared.
+    pub fn record_hash(&mut self, frame: u32, hash: u64) {
+        if frame.is_multiple_of(HASH_INTERVAL) {
+            self.local_hash = Some((frame, hash));
+            self.compare_hashes();
+        }
+    }
+
+    /// The first checked frame on which the peers' states differed.
+    pub fn desync(&self) -> Option<u32> {
+        self.desync
+    }
+
+    fn compare_hashes(&mut self) {
+        if let (Some((frame, ours)), Some((their_frame, theirs))) =
+            (self.local_hash, self.remote_hash)
+            && frame == their_frame
+            && ours != theirs
+            && self.desync.is_none()
+        {
+            self.desync = Some(frame);
+        }
+    }
+
+    fn send(&self) -> io::Result<()> {
+        let first = self.local_until.saturating_sub(REDUNDANT_INPUTS as u32);
+        let (hash_frame, hash) = self.local_hash.unwrap_or((0, 0));
+
+        let mut datagram = MAGIC.to_vec();
+        datagram.push(INPUT);
+        datagram.extend_from_slice(&first.to_le_bytes());
+        datagram.push((self.local_until - first) as u8);
+
+        for frame in first..self.local_until {
+            datagram.push(self.local[frame as usize % RING].bits());
+        }
+
+        datagram.extend_from_slice(&hash_frame.to_le_bytes());
+        datagram.extend_from_slice(&hash.to_le_bytes());
+
+        match self.socket.send(&datagram) {
+            Ok(_) => Ok(()),
+            // Nobody listening yet, or the datagram was dropped: the next
+            // one repeats it.
+            Err(err) if err.kind() == ErrorKind::ConnectionRefused || is_timeout(&err) => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn receive(&mut self) -> io::Result<()> {
+        let mut buf = [0u8; LARGEST_DATAGRAM];
+
+        loop {
+            let len = match self.socket.recv(&mut buf) {
+                Ok(len) => len,
+                Err(err) if is_timeout(&err) || err.kind() == ErrorKind::ConnectionRefused => {
+                    return Ok(());
+                }
+                Err(err) => return Err(err),
+            };
+
+            match kind(&buf[..len]) {
+                Some(INPUT) => {
+                    self.last_heard = Instant::now();
+                    self.apply_input(&buf[..len]);
+                }
+                // The guest missed our `WELCOME`.
+                Some(HELLO) if self.seat == 0 => {
+                    let _ = self.socket.send(&welcome(self.delay));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn apply_input(&mut self, datagram: &[u8]) {
+        let Some(header) = datagram.get(5..10) else {
+            return;
+        };
+
+        let first = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+        let count = header[4] as usize;
+
+        let Some(inputs) = datagram.get(10..10 + count) else {
+            return;
+        };
+        let Some(tail) = datagram.get(10 + count..10 + count + 12) else {
+            return;
+        };
+
+        for (frame, &bits) in (first..).zip(inputs) {
+            // Frames already consumed are never overwritten, and ones past
+            // a gap would be.
+            if frame == self.remote_until && frame < self.local_until + RING as u32 / 2 {
+                self.remote[frame as usize % RING] = Input::from_bits(bits);
+                self.remote_until += 1;
+            }
+        }
+
+        let hash_frame = u32::from_le_bytes([tail[0], tail[1], tail[2], tail[3]]);
+        let mut hash = [0u8; 8];
+        hash.copy_from_slice(&tail[4..12]);
+
+        if hash_frame > 0 {
+            self.remote_hash = Some((hash_frame, u64::from_le_bytes(hash)));
+            self.compare_hashes();
+        }
+    }
+}
+
+fn welcome(delay: u32) -> Vec<u8> {
+    let mut datagram = MAGIC.to_vec();
+    datagram.push(WELCOME);
+    datagram.extend_from_slice(&VERSION.to_le_bytes());
+    datagram.push(delay as u8);
+    datagram
+}
+
+/// The kind byte of a bit-bound datagram.
+fn kind(datagram: &[u8]) -> Option<u8> {
+    (datagram.len() >= 5 && datagram[..4] == MAGIC).then(|| datagram[4])
+}
+
+fn check_version(datagram: &[u8]) -> io::Result<()> {
+    let version = datagram
+        .get(5..7)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]));
+
+    if version == Some(VERSION) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "peer runs another netplay version",
+        ))
+    }
+}
+
+fn is_timeout(err: &io::Error) -> bool {
+    matches!(err.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+}
@@ -0,0 +1,365 @@
+//! Frame pacing: how the main loop waits between frames and how many ticks
+//! it runs before presenting.
+//!
+//! The simulation ticks at 60 Hz, or at 50 Hz with `TickRate::Hz50`.
+//! Deadlines are counted from a fixed origin rather than from the end of the previous frame, so rounding never
+//! builds up into drift, and after a stall of more than `MAX_CATCH_UP` ticks
+//! the schedule starts over instead of fast-forwarding.
+//!
+//! `Spin` sizes its busy-wait by how far the OS has been oversleeping, and
+//! stops spinning while the screen stands still.
+//!
+//! Each loop tells the pacer which `Scene` it shows. Menus and the pause
+//! screen are static, so the pacer presents them at 30 Hz, handing out two
+//! ticks a frame to keep their animations on time, and once the buttons
+//! have not changed for `DIM_AFTER_SECONDS` asks for the palette to be
+//! dimmed (`dim_palette`). Backends already skip converting a frame that
+//! did not change (`DirtyCheck`), so a still menu costs little more than
+//! the wait.
+
+use crate::input::Input;
+use crate::platform::Platform;
+
+/// Ticks per second the game is made for. Tick counts in the code, like
+/// animation frames or a song's tempo, are at this rate.
+pub const TICK_RATE: u64 = 60;
+
+/// Microseconds per tick at `TICK_RATE`, rounded down.
+pub const TICK_US: u64 = 1_000_000 / TICK_RATE;
+
+/// How far behind its deadlines the loop may fall before dropping the
+/// backlog.
+pub const MAX_CATCH_UP: u64 = 4;
+
+/// Frames a second the main loop presents while the game is paused in the
+/// background, which keeps the window responsive at little CPU cost.
+pub const BACKGROUND_RATE: u64 = 10;
+
+/// Bounds of how long before the deadline `Spin` stops sleeping and
+/// busy-waits. It starts at the maximum, which covers the oversleep of most
+/// OS schedulers, and follows the oversleep it measures.
+const SPIN_MIN_US: u64 = 250;
+const SPIN_MAX_US: u64 = 2_000;
+
+/// Unchanged frames in a row after which the screen counts as idle, like a
+/// menu or pause screen, and `Spin` only sleeps.
+pub const IDLE_FRAMES: u32 = 30;
+
+/// Ticks per frame in a static scene: 30 frames a second at 60 Hz.
+pub const STATIC_STRIDE: u32 = 2;
+
+/// Seconds a static scene goes without a button change before it is
+/// dimmed.
+pub const DIM_AFTER_SECONDS: u64 = 30;
+
+/// What the main loop is showing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Scene {
+    /// The game running, or anything else that moves on its own.
+    #[default]
+    Play,
+    /// The title and the menus behind it.
+    Menu,
+    Paused,
+}
+
+impl Scene {
+    /// Whether the scene only changes on input or slow animations, so it
+    /// can be presented less often.
+    pub const fn is_static(self) -> bool {
+        matches!(self, Self::Menu | Self::Paused)
+    }
+}
+
+/// How many ticks a second the simulation runs, chosen at startup.
+///
+/// A tick does the same at either rate, so at 50 Hz the game plays a sixth
+/// slower, as on a PAL console, and replays and hashes match across rates.
+/// Animations and music keep their speed by the clock: `design_ticks`
+/// converts for them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TickRate {
+    #[default]
+    Hz60,
+    /// PAL.
+    Hz50,
+}
+
+impl TickRate {
+    /// Names as accepted by `parse`, in declaration order.
+    pub const NAMES: [(&'static str, Self); 2] = [("60", Self::Hz60), ("50", Self::Hz50)];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|&(_, rate)| rate)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|&&(_, rate)| rate == self)
+            .map_or("", |&(name, _)| name)
+    }
+
+    /// Ticks per second.
+    pub const fn hz(self) -> u64 {
+        match self {
+            Self::Hz60 => TICK_RATE,
+            Self::Hz50 => 50,
+        }
+    }
+
+    /// Microseconds per tick, rounded down.
+    pub const fn tick_us(self) -> u64 {
+        1_000_000 / self.hz()
+    }
+
+    /// `ticks` at this rate as the ticks at `TICK_RATE` that take as long.
+    pub const fn design_ticks(self, ticks: u32) -> u32 {
+        (ticks as u64 * TICK_RATE / self.hz()) as u32
+    }
+}
+
+/// How the loop waits for the next frame.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Pacing {
+    /// Sleep until the deadline. Cheapest, but OS sleeps can overshoot by
+    /// a millisecond or more, which shows up as judder.
+    Sleep,
+    /// Sleep most of the way, then spin on the clock. Accurate at the cost
+    /// of up to a couple of milliseconds of CPU per frame, none while the
+    /// screen is idle.
+    #[default]
+    Spin,
+    /// Let the backend's vsync'd `present` block, and run however many
+    /// ticks are due each frame. Needs a backend with vsync.
+    Vsync,
+    /// Never wait: present as often as possible and run the ticks that are
+    /// due. Renderers can place sprites between ticks with `tick_progress_us`.
+    Uncapped,
+}
+
+impl Pacing {
+    /// Names as accepted by `parse`, in declaration order.
+    pub const NAMES: [(&'static str, Self); 4] = [
+        ("sleep", Self::Sleep),
+        ("spin", Self::Spin),
+        ("vsync", Self::Vsync),
+        ("uncapped", Self::Uncapped),
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(candidate, _)| *candidate == name)
+            .map(|&(_, pacing)| pacing)
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES
+            .iter()
+            .find(|&&(_, pacing)| pacing == self)
+            .map_or("", |&(name, _)| name)
+    }
+
+    /// Whether the loop waits out each frame itself, running one tick per
+    /// frame. Otherwise it runs the ticks that came due since the last one.
+    pub fn waits(self) -> bool {
+        matches!(self, Self::Sleep | Self::Spin)
+    }
+}
+
+/// Schedules ticks and waits on a `Platform`'s clock.
+#[derive(Debug, Clone, Copy)]
+pub struct FramePacer {
+    pacing: Pacing,
+    rate: TickRate,
+    /// When tick 0 was due; `None` until the first frame.
+    origin_us: Option<u64>,
+    /// Ticks handed out since `origin_us`.
+    ticks: u64,
+    /// How long before the deadline `Spin` busy-waits.
+    spin_us: u64,
+    /// Frames in a row that `note_frame` saw unchanged.
+    unchanged: u32,
+    scene: Scene,
+    /// Buttons as of the last `note_input`.
+    held: Input,
+    /// Ticks handed out since the buttons or the scene last changed.
+    still_ticks: u64,
+}
+
+impl FramePacer {
+    pub const fn new(pacing: Pacing, rate: TickRate) -> Self {
+        Self {
+            pacing,
+            rate,
+            origin_us: None,
+            ticks: 0,
+            spin_us: SPIN_MAX_US,
+            unchanged: 0,
+            scene: Scene::Play,
+            held: Input::empty(),
+            still_ticks: 0,
+        }
+    }
+
+    pub fn pacing(&self) -> Pacing {
+        self.pacing
+    }
+
+    pub fn rate(&self) -> TickRate {
+        self.rate
+    }
+
+    /// When tick `n` after the origin is due.
+    fn deadline(&self, origin_us: u64, n: u64) -> u64 {
+        origin_us + n * 1_000_000 / self.rate.hz()
+    }
+
+    pub fn scene(&self) -> Scene {
+        self.scene
+    }
+
+    /// Say which scene the loop shows from this frame on.
+    pub fn set_scene(&mut self, scene: Scene) {
+        if scene != self.scene {
+            self.scene = scene;
+            self.still_ticks = 0;
+        }
+    }
+
+    /// Tell the pacer the buttons held this frame, to see the player is
+    /// still there.
+    pub fn note_input(&mut self, input: Input) {
+        if input != self.held {
+            self.held = input;
+            self.still_ticks = 0;
+        }
+    }
+
+    /// Whether the frame should be presented dimmed: a static scene left
+    /// alone for `DIM_AFTER_SECONDS`.
+    pub fn dimmed(&self) -> bool {
+        self.scene.is_static() && self.still_ticks >= DIM_AFTER_SECONDS * self.rate.hz()
+    }
+
+    /// How many ticks to run before presenting the frame that starts at
+    /// `now_us`. For pacings that wait, 1, or `STATIC_STRIDE` in a static
+    /// scene; otherwise the ticks that came due since the last call, which
+    /// may be 0 on a fast display.
+    pub fn ticks_due(&mut self, now_us: u64) -> u32 {
+        let ticks = self.next_ticks(now_us);
+
+        self.still_ticks += ticks as u64;
+        ticks
+    }
+
+    fn next_ticks(&mut self, now_us: u64) -> u32 {
+        let origin_us = *self.origin_us.get_or_insert(now_us);
+        let due = (now_us - origin_us) * self.rate.hz() / 1_000_000 + 1;
+
+        if due > self.ticks + MAX_CATCH_UP {
+            self.origin_us = Some(now_us);
+            self.ticks = 1;
+            return 1;
+        }
+
+        if self.pacing.waits() {
+            let stride = if self.scene.is_static() {
+                STATIC_STRIDE
+            } else {
+                1
+            };
+
+            self.ticks += stride as u64;
+            return stride;
+        }
+
+        let ticks = due.saturating_sub(self.ticks);
+        self.ticks += ticks;
+        ticks as u32
+    }
+
+    /// Microseconds since the last tick handed out was due, at most one
+    /// tick: how far to draw between that tick and the next.
+    pub fn tick_progress_us(&self, now_us: u64) -> u64 {
+        let Some(origin_us) = self.origin_us else {
+            return 0;
+        };
+
+        let last = self.deadline(origin_us, self.ticks.saturating_sub(1));
+        now_us.saturating_sub(last).min(self.rate.tick_us())
+    }
+
+    /// Tell the pacer whether the frame just presented differed from the
+    /// one before (see `DirtyCheck`).
+    pub fn note_frame(&mut self, changed: bool) {
+        self.unchanged = if changed {
+            0
+        } else {
+            self.unchanged.saturating_add(1)
+        };
+    }
+
+    /// Whether the last `IDLE_FRAMES` frames were all the same.
+    pub fn is_idle(&self) -> bool {
+        self.unchanged >= IDLE_FRAMES
+    }
+
+    /// Wait until the next tick is due, if this pacing waits at all. In a
+    /// static scene every pacing waits, until `STATIC_STRIDE` ticks after
+    /// the last one.
+    pub fn wait(&mut self, platform: &mut dyn Platform) {
+        let Some(origin_us) = self.origin_us else {
+            return;
+        };
+
+        let deadline = self.deadline(origin_us, self.ticks);
+
+        if self.scene.is_static() && !self.pacing.waits() {
+            let deadline = self.deadline(origin_us, self.ticks + STATIC_STRIDE as u64 - 1);
+            let now = platform.now_us();
+
+            if now < deadline {
+                platform.sleep_us(deadline - now);
+            }
+
+            return;
+        }
+
+        match self.pacing {
+            Pacing::Spin if !self.is_idle() => {
+                let now = platform.now_us();
+
+                if now + self.spin_us < deadline {
+                    let wake = deadline - self.spin_us;
+                    platform.sleep_us(wake - now);
+
+                    // Spin for half again the worst recent oversleep,
+                    // forgetting old ones by an eighth a frame.
+                    let late = platform.now_us().saturating_sub(wake);
+                    self.spin_us = (late + late / 2)
+                        .max(self.spin_us - self.spin_us / 8)
+                        .clamp(SPIN_MIN_US, SPIN_MAX_US);
+                }
+
+                while platform.now_us() < deadline {
+                    core::hint::spin_loop();
+                }
+            }
+            // An idle screen has nothing moving, so a late wake-up cannot
+            // judder.
+            Pacing::Sleep | Pacing::Spin => {
+                let now = platform.now_us();
+
+                if now < deadline {
+                    platform.sleep_us(deadline - now);
+                }
+            }
+            Pacing::Vsync | Pacing::Uncapped => {}
+        }
+    }
+}
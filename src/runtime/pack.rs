@@ -0,0 +1,110 @@
+//! Reader for asset packs written by `spritec --pack`.
+//!
+//! A pack is one blob holding every converted asset behind a table of
+//! contents sorted by name hash (see `docs/sprite_format.md`). Everything
+//! here is a `const fn`, so a pack can be `include_bytes!`d and its assets
+//! sliced out in `const` items, where a missing asset is a compile error:
+//!
+//! ```ignore
+//! const PACK: AssetPack = AssetPack::new(include_bytes!("../assets/processed/assets.pack"))
+//!     .expect("valid asset pack");
+//! const SHIP: &[u8] = PACK.get("spaceship_0.2bpp").expect("ship sprite in pack");
+//! ```
+
+use crate::runtime::hash::fnv1a;
+use crate::runtime::memory::RomRegion;
+
+pub const PACK_MAGIC: [u8; 4] = *b"BBPK";
+pub const PACK_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 8;
+const ENTRY_SIZE: usize = 16;
+
+const fn read_u16(bytes: &[u8], at: usize) -> u16 {
+    u16::from_le_bytes([bytes[at], bytes[at + 1]])
+}
+
+const fn read_u32(bytes: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes([bytes[at], bytes[at + 1], bytes[at + 2], bytes[at + 3]])
+}
+
+const fn read_u64(bytes: &[u8], at: usize) -> u64 {
+    (read_u32(bytes, at) as u64) | ((read_u32(bytes, at + 4) as u64) << 32)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AssetPack {
+    blob: &'static [u8],
+    count: usize,
+}
+
+impl AssetPack {
+    /// Check the header and table bounds. `None` if `blob` is not a pack of
+    /// this version.
+    pub const fn new(blob: &'static [u8]) -> Option<Self> {
+        if blob.len() < HEADER_SIZE
+            || blob[0] != PACK_MAGIC[0]
+            || blob[1] != PACK_MAGIC[1]
+            || blob[2] != PACK_MAGIC[2]
+            || blob[3] != PACK_MAGIC[3]
+            || read_u16(blob, 4) != PACK_VERSION
+        {
+            return None;
+        }
+
+        let count = read_u16(blob, 6) as usize;
+
+        if blob.len() < HEADER_SIZE + count * ENTRY_SIZE {
+            return None;
+        }
+
+        Some(Self { blob, count })
+    }
+
+    /// Number of assets in the pack.
+    pub const fn len(&self) -> usize {
+        self.count
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.count == 0
+    }
+
+    /// Where the asset called `name` lives in the blob. Names are paths
+    /// relative to spritec's output directory, with `/` separators.
+    pub const fn find(&self, name: &str) -> Option<RomRegion> {
+        let hash = fnv1a(name.as_bytes());
+        let (mut low, mut high) = (0, self.count);
+
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let entry = HEADER_SIZE + mid * ENTRY_SIZE;
+            let entry_hash = read_u64(self.blob, entry);
+
+            if entry_hash < hash {
+                low = mid + 1;
+            } else if entry_hash > hash {
+                high = mid;
+            } else {
+                let offset = read_u32(self.blob, entry + 8) as usize;
+                let len = read_u32(self.blob, entry + 12) as usize;
+
+                if offset + len > self.blob.len() {
+                    return None;
+                }
+
+                return Some(RomRegion { offset, len });
+            }
+        }
+
+        None
+    }
+
+    /// The contents of the asset called `name`.
+    pub const fn get(&self, name: &str) -> Option<&'static [u8]> {
+        match self.find(name) {
+            Some(region) => Some(region.slice(self.blob)),
+            None => None,
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//! Hierarchical frame profiler.
+//!
+//! `profile_scope!("name")` opens a zone that closes at the end of the
+//! enclosing block. Zones nest, and each frame's zones are kept until the
+//! next `end_frame()` so the overlay or a trace dump can inspect them.
+//! Without the `profiler` feature the macro expands to nothing.
+
+/// Open a profiler zone until the end of the enclosing block.
+#[cfg(feature = "profiler")]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_zone = $crate::runtime::profile::ZoneGuard::new($name);
+    };
+}
+
+/// Open a profiler zone until the end of the enclosing block.
+#[cfg(not(feature = "profiler"))]
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {};
+}
+
+#[cfg(feature = "profiler")]
+pub use enabled::*;
+
+#[cfg(feature = "profiler")]
+mod enabled {
+    use std::cell::RefCell;
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+    use std::path::Path;
+    use std::time::Instant;
+
+    /// Zones recorded per frame. Further zones are counted but dropped.
+    pub const MAX_ZONES: usize = 256;
+
+    #[derive(Debug, Clone, Copy)]
+    pub struct Zone {
+        pub name: &'static str,
+        pub depth: u8,
+        pub start_us: u32,
+        pub end_us: u32,
+    }
+
+    impl Zone {
+        const EMPTY: Self = Self {
+            name: "",
+            depth: 0,
+            start_us: 0,
+            end_us: 0,
+        };
+
+        pub fn duration_us(&self) -> u32 {
+            self.end_us.saturating_sub(self.start_us)
+        }
+    }
+
+    /// Zones of one frame, in the order they were opened.
+    #[derive(Clone, Copy)]
+    pub struct FrameProfile {
+        zones: [Zone; MAX_ZONES],
+        len: usize,
+        pub dropped: u32,
+        pub frame_us: u32,
+    }
+
+    impl FrameProfile {
+        const fn new() -> Self {
+            Self {
+                zones: [Zone::EMPTY; MAX_ZONES],
+                len: 0,
+                dropped: 0,
+                frame_us: 0,
+            }
+        }
+
+        pub fn zones(&self) -> &[Zone] {
+            &self.zones[..self.len]
+        }
+    }
+
+    struct Profiler {
+        current: FrameProfile,
+        last: FrameProfile,
+        depth: u8,
+        frame_start: Option<Instant>,
+    }
+
+    thread_local! {
+        static PROFILER: RefCell<Profiler> = const {
+            RefCell::new(Profiler {
+                current: FrameProfile::new(),
+                last: FrameProfile::new(),
+                depth: 0,
+                frame_start: None,
+            })
+        };
+    }
+
+    fn now_us(profiler: &Profiler) -> u32 {
+        profiler
+            .frame_start
+            .map_or(0, |start| start.elapsed().as_micros() as u32)
+    }
+
+    /// Start recording a new frame on this thread.
+    pub fn begin_frame() {
+        PROFILER.with_borrow_mut(|profiler| {
+            profiler.current.len = 0;
+            profiler.current.dropped = 0;
+            profiler.depth = 0;
+            profiler.frame_start = Some(Instant::now());
+        });
+    }
+
+    /// Finish the frame and make it available through `last_frame`.
+    pub fn end_frame() {
+        PROFILER.with_borrow_mut(|profiler| {
+            profiler.current.frame_us = now_us(profiler);
+            profiler.last = profiler.current;
+        });
+    }
+
+    /// Run `f` with the most recently completed frame.
+    pub fn last_frame<R>(f: impl FnOnce(&FrameProfile) -> R) -> R {
+        PROFILER.with_borrow(|profiler| f(&profiler.last))
+    }
+
+    /// Closes its zone when dropped. Created by `profile_scope!`.
+    pub struct ZoneGuard {
+        index: Option<usize>,
+    }
+
+    impl ZoneGuard {
+        pub fn new(name: &'static str) -> Self {
+            let index = PROFILER.with_borrow_mut(|profiler| {
+                let start_us = now_us(profiler);
+                let depth = profiler.depth;
+                profiler.depth = profiler.depth.saturating_add(1);
+
+                let frame = &mut profiler.current;
+
+                if frame.len == MAX_ZONES {
+                    frame.dropped += 1;
+                    return None;
+                }
+
+                frame.zones[frame.len] = Zone {
+                    name,
+                    depth,
+                    start_us,
+                    end_us: start_us,
+                };
+                frame.len += 1;
+                Some(frame.len - 1)
+            });
+
+            Self { index }
+        }
+    }
+
+    impl Drop for ZoneGuard {
+        fn drop(&mut self) {
+            PROFILER.with_borrow_mut(|profiler| {
+                let end_us = now_us(profiler);
+                profiler.depth = profiler.depth.saturating_sub(1);
+
+                if let Some(index) = self.index {
+                    profiler.current.zones[index].end_us = end_us;
+                }
+            });
+        }
+    }
+
+    /// Write the last completed frame as a Chrome tracing JSON file, viewable
+    /// in `chrome://tracing` or Perfetto.
+    pub fn dump_chrome_trace(path: &Path) -> io::Result<()> {
+        let frame = last_frame(|frame| *frame);
+        let mut out = BufWriter::new(File::create(path)?);
+
+        writeln!(out, "[")?;
+
+        for (i, zone) in frame.zones().iter().enumerate() {
+            let separator = if i + 1 < frame.zones().len() { "," } else { "" };
+
+            writeln!(
+                out,
+                r#"  {{"name": "{}", "ph": "X", "ts": {}, "dur": {}, "pid": 0, "tid": 0}}{}"#,
+                escape_json(zone.name),
+                zone.start_us,
+                zone.duration_us(),
+                separator
+            )?;
+        }
+
+        writeln!(out, "]")?;
+        out.flush()
+    }
+
+    /// `text` as the inside of a JSON string: quotes, backslashes and
+    /// control characters escaped, everything else as is.
+    fn escape_json(text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+
+        for c in text.chars() {
+            match c {
+                '"' => out.push_str("\\\""),
+                '\\' => out.push_str("\\\\"),
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+        }
+
+        out
+    }
+
+    /// List the last frame's zones, indented by depth, with their duration
+    /// in microseconds.
+    #[cfg(feature = "debug_overlay")]
+    pub fn render_profile(framebuffer: &mut crate::renderer::framebuffer::FrameBuffer) {
+        use crate::renderer::framebuffer::{FONT_ADVANCE, HEIGHT, LINE_HEIGHT, WIDTH};
+
+        const NAME_CHARS: usize = 12;
+        const ROWS: usize = 8;
+
+        last_frame(|frame| {
+            let rows = frame.zones().len().min(ROWS);
+            let top = HEIGHT - (rows + 1) * LINE_HEIGHT;
+
+            framebuffer.fill_rect(0, top - 1, WIDTH, (rows + 1) * LINE_HEIGHT + 1, 0);
+            framebuffer.draw_text(2, top, "ZONE", 2);
+            framebuffer.draw_u32(WIDTH - 2 - 5 * FONT_ADVANCE, top, frame.frame_us, 5, 2);
+
+            for (row, zone) in frame.zones().iter().take(rows).enumerate() {
+                let y = top + (row + 1) * LINE_HEIGHT;
+                let x = 2 + zone.depth as usize * FONT_ADVANCE;
+
+                let mut name = [b' '; NAME_CHARS];
+                let len = zone.name.len().min(NAME_CHARS);
+                name[..len].copy_from_slice(&zone.name.as_bytes()[..len]);
+                name.make_ascii_uppercase();
+
+                framebuffer.draw_text(x, y, std::str::from_utf8(&name).unwrap_or(""), 3);
+                framebuffer.draw_u32(WIDTH - 2 - 5 * FONT_ADVANCE, y, zone.duration_us(), 5, 3);
+            }
+        });
+    }
+}
@@ -0,0 +1,118 @@
+//! Automatic quality fallback: when frames keep running over budget, the
+//! optional drawing goes until they fit again.
+//!
+//! `QualityWatchdog` is handed each frame's work time, from its start to
+//! just before the pacer waits. After `SLOW_FRAMES` frames in a row over
+//! the budget, one tick at the pacer's rate, it lowers the `Quality` a
+//! level; after `FAST_FRAMES` in a row within three quarters of it, it
+//! raises it a level. Only drawing changes, never the simulation, so
+//! replays and netplay are unaffected. The game has no particles or
+//! parallax layers yet; once it does, they belong to a level too.
+
+/// Frames in a row over budget before the quality drops.
+pub const SLOW_FRAMES: u32 = 30;
+
+/// Frames in a row with time to spare before the quality comes back: five
+/// seconds, so a run of slow frames does not flip it back and forth.
+pub const FAST_FRAMES: u32 = 300;
+
+/// How much optional drawing the binary does, lowest first.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Quality {
+    /// No ghost ship either.
+    Low,
+    /// Ticks drawn as they are, without interpolation.
+    Medium,
+    #[default]
+    High,
+}
+
+impl Quality {
+    /// For the debug overlay and the log.
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Low => "LOW",
+            Self::Medium => "MED",
+            Self::High => "HIGH",
+        }
+    }
+
+    /// Whether frames between ticks are interpolated, where the pacing
+    /// allows it.
+    pub fn interpolate(self) -> bool {
+        self == Self::High
+    }
+
+    /// Whether a `--ghost` run is drawn.
+    pub fn ghost(self) -> bool {
+        self >= Self::Medium
+    }
+
+    const fn lower(self) -> Self {
+        match self {
+            Self::High => Self::Medium,
+            Self::Medium | Self::Low => Self::Low,
+        }
+    }
+
+    const fn higher(self) -> Self {
+        match self {
+            Self::Low => Self::Medium,
+            Self::Medium | Self::High => Self::High,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct QualityWatchdog {
+    budget_us: u64,
+    quality: Quality,
+    /// Frames in a row over budget.
+    slow: u32,
+    /// Frames in a row within three quarters of it.
+    fast: u32,
+}
+
+impl QualityWatchdog {
+    /// A watchdog at `Quality::High` for frames of `budget_us`.
+    pub const fn new(budget_us: u64) -> Self {
+        Self {
+            budget_us,
+            quality: Quality::High,
+            slow: 0,
+            fast: 0,
+        }
+    }
+
+    pub fn quality(&self) -> Quality {
+        self.quality
+    }
+
+    /// Count a frame that took `frame_us`. Returns the new quality when it
+    /// changes.
+    pub fn record(&mut self, frame_us: u64) -> Option<Quality> {
+        let slow = frame_us > self.budget_us;
+        let fast = frame_us * 4 <= self.budget_us * 3;
+
+        self.slow = if slow { self.slow + 1 } else { 0 };
+        self.fast = if fast { self.fast + 1 } else { 0 };
+
+        let quality = if self.slow >= SLOW_FRAMES {
+            self.quality.lower()
+        } else if self.fast >= FAST_FRAMES {
+            self.quality.higher()
+        } else {
+            return None;
+        };
+
+        self.slow = 0;
+        self.fast = 0;
+
+        if quality == self.quality {
+            return None;
+        }
+
+        self.quality = quality;
+        Some(quality)
+    }
+}
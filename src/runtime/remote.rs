@@ -0,0 +1,261 @@
+//! Remote debug server.
+//!
+//! A line-based text protocol over TCP, served from the main loop without
+//! threads: `poll()` accepts clients and reads commands, `send_frame()`
+//! streams one stats line per frame plus any new log lines. Any line-based
+//! client works, e.g. `nc 127.0.0.1 7777`.
+//!
+//! Server to client:
+//!
+//! ```text
+//! stats frame=120 us=812 global=0 level=0 frame_arena=0 entities=4 sprites=1 pixels=560 pairs=0 hash=00c0ffee00c0ffee
+//! log 000120 I runtime started: 1024 KB memory
+//! ok
+//! error unknown command `jump`
+//! ```
+//!
+//! Client to server: see `RemoteCommand::parse`.
+
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::runtime::debug::{DebugFlags, DebugInfo};
+use crate::runtime::log;
+
+/// Longest command line accepted; longer input disconnects the client.
+const MAX_LINE: usize = 256;
+
+/// Most ticks one `step` command queues, a minute of play; larger counts
+/// are clamped.
+const MAX_STEPS: u32 = 3600;
+
+const HELP: &str = "commands: pause, resume, step [n], speed slower|faster|normal, \
+toggle memory|frames|hitboxes|grid|log|profiler|vram, flush-log, help";
+
+/// A command from a remote client, applied by the main loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteCommand {
+    Pause,
+    Resume,
+    /// Pause and run this many ticks, at most `MAX_STEPS`.
+    Step(u32),
+    SpeedSlower,
+    SpeedFaster,
+    SpeedNormal,
+    Toggle(DebugFlags),
+    FlushLog,
+}
+
+impl RemoteCommand {
+    /// Parse one command line. `help` and malformed lines are answered by the
+    /// server and never reach the main loop.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let mut words = line.split_whitespace();
+        let command = words.next().unwrap_or("");
+        let argument = words.next();
+
+        let parsed = match (command, argument) {
+            ("pause", None) => Self::Pause,
+            ("resume", None) => Self::Resume,
+            ("step", None) => Self::Step(1),
+            ("step", Some(count)) => Self::Step(
+                count
+                    .parse::<u32>()
+                    .map_err(|_| format!("invalid step count `{count}`"))?
+                    .min(MAX_STEPS),
+            ),
+            ("speed", Some("slower")) => Self::SpeedSlower,
+            ("speed", Some("faster")) => Self::SpeedFaster,
+            ("speed", Some("normal")) => Self::SpeedNormal,
+            ("toggle", Some(view)) => Self::Toggle(match view {
+                "memory" => DebugFlags::MEMORY_PANEL,
+                "frames" => DebugFlags::FRAME_GRAPH,
+                "hitboxes" => DebugFlags::HITBOXES,
+                "grid" => DebugFlags::COLLISION_GRID,
+                "log" => DebugFlags::LOG,
+                "profiler" => DebugFlags::PROFILER,
+                "vram" => DebugFlags::VRAM,
+                _ => return Err(format!("unknown view `{view}`")),
+            }),
+            ("flush-log", None) => Self::FlushLog,
+            _ => return Err(format!("unknown command `{}`", line.trim())),
+        };
+
+        if words.next().is_some() {
+            return Err(format!("too many arguments to `{command}`"));
+        }
+
+        Ok(parsed)
+    }
+}
+
+struct Client {
+    stream: TcpStream,
+    pending: Vec<u8>,
+    /// Set when the connection failed; the client is dropped on the next poll.
+    closed: bool,
+}
+
+impl Client {
+    fn send(&mut self, line: &str) {
+        if self.closed {
+            return;
+        }
+
+        let sent = self
+            .stream
+            .write_all(line.as_bytes())
+            .and_then(|()| self.stream.write_all(b"\n"));
+
+        // A client that cannot keep up would otherwise receive torn lines.
+        if sent.is_err() {
+            self.closed = true;
+        }
+    }
+}
+
+/// Debug server serving any number of clients from the main loop.
+pub struct DebugServer {
+    listener: TcpListener,
+    clients: Vec<Client>,
+    /// Log sequence number of the next line to stream.
+    log_sent: u64,
+}
+
+impl DebugServer {
+    /// Listen on `addr`. Only log lines written after this call are streamed.
+    pub fn bind(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let listener = TcpListener::bind(addr)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self {
+            listener,
+            clients: Vec::new(),
+            log_sent: log::global().written(),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept new clients and pass every complete command line to `apply`.
+    /// Never blocks.
+    pub fn poll(&mut self, mut apply: impl FnMut(RemoteCommand)) {
+        // Stops at `WouldBlock` (no one waiting). Other accept errors concern
+        // a single connection and are retried next frame.
+        while let Ok((stream, peer)) = self.listener.accept() {
+            if stream.set_nonblocking(true).is_err() {
+                continue;
+            }
+
+            crate::log_info!("debug client connected: {peer}");
+
+            let mut client = Client {
+                stream,
+                pending: Vec::new(),
+                closed: false,
+            };
+            client.send(HELP);
+            self.clients.push(client);
+        }
+
+        for client in &mut self.clients {
+            read_commands(client, &mut apply);
+        }
+
+        self.clients.retain(|client| !client.closed);
+    }
+
+    /// Stream this frame's stats and any log lines written since the last
+    /// call.
+    pub fn send_frame(&mut self, frame: u32, info: &DebugInfo, sim_hash: u64) {
+        if self.clients.is_empty() {
+            self.log_sent = log::global().written();
+            return;
+        }
+
+        let stats = format!(
+            "stats frame={frame} us={} global={} level={} frame_arena={} entities={} \
+             sprites={} culled={} pixels={} pairs={} hash={sim_hash:016x}",
+            info.frame_us,
+            info.global_used,
+            info.level_used,
+            info.frame_used,
+            info.entities,
+            info.sprites_drawn,
+            info.sprites_culled,
+            info.pixels_written,
+            info.collision_pairs,
+        );
+
+        let mut lines = vec![stats];
+
+        {
+            let log = log::global();
+            let written = log.written();
+            let oldest = written - log.len() as u64;
+
+            for seq in self.log_sent.max(oldest)..written {
+                if let Some(line) = log.recent((written - 1 - seq) as usize) {
+                    lines.push(format!("log {line}"));
+                }
+            }
+
+            self.log_sent = written;
+        }
+
+        for client in &mut self.clients {
+            for line in &lines {
+                client.send(line);
+            }
+        }
+    }
+}
+
+fn read_commands(client: &mut Client, apply: &mut impl FnMut(RemoteCommand)) {
+    let mut buffer = [0u8; MAX_LINE];
+
+    loop {
+        match client.stream.read(&mut buffer) {
+            Ok(0) => {
+                client.closed = true;
+                break;
+            }
+            Ok(read) => client.pending.extend_from_slice(&buffer[..read]),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(_) => {
+                client.closed = true;
+                break;
+            }
+        }
+    }
+
+    while let Some(end) = client.pending.iter().position(|&byte| byte == b'\n') {
+        let line: Vec<u8> = client.pending.drain(..=end).collect();
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line == "help" {
+            client.send(HELP);
+            continue;
+        }
+
+        match RemoteCommand::parse(line) {
+            Ok(command) => {
+                apply(command);
+                client.send("ok");
+            }
+            Err(err) => client.send(&format!("error {err}")),
+        }
+    }
+
+    if client.pending.len() > MAX_LINE {
+        client.closed = true;
+    }
+}
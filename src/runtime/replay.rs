@@ -0,0 +1,171 @@
+//! Replay viewer: plays an input recording with seeking, pausing and
+//! stepping.
+//!
+//! Seeking re-simulates from the nearest keyframe at or before the target.
+//! A keyframe is a `GameState` snapshot taken every `KEYFRAME_INTERVAL`
+//! ticks the first time the simulation passes that frame, so seeking back
+//! costs at most `KEYFRAME_INTERVAL` ticks and seeking forward reuses every
+//! keyframe already recorded.
+
+use crate::game::{GameState, STATE_SNAPSHOT_MAX, update};
+use crate::input::InputScript;
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT, WIDTH};
+use crate::runtime::memory::ActiveMemory;
+use crate::text::{Language, TextId};
+
+/// Ticks between keyframes.
+pub const KEYFRAME_INTERVAL: u32 = 60;
+
+/// Height of the timeline strip at the bottom of the screen, in pixels.
+pub const TIMELINE_HEIGHT: usize = LINE_HEIGHT + 4;
+
+const TIMELINE_Y: usize = HEIGHT - TIMELINE_HEIGHT;
+const BAR_Y: usize = TIMELINE_Y + LINE_HEIGHT + 1;
+
+pub struct ReplayPlayer {
+    script: InputScript,
+    length: u32,
+    state: GameState,
+    /// Snapshot of the state before tick `i * KEYFRAME_INTERVAL`.
+    keyframes: Vec<[u8; STATE_SNAPSHOT_MAX]>,
+    paused: bool,
+}
+
+impl ReplayPlayer {
+    /// Prepare to play `script`. Its length is its `end` marker, or one tick
+    /// past its last input change when it has none.
+    pub fn new(script: InputScript) -> Self {
+        let length = script.end().unwrap_or(script.last_change() + 1);
+
+        Self {
+            script,
+            length,
+            state: GameState::new(),
+            keyframes: Vec::new(),
+            paused: false,
+        }
+    }
+
+    pub fn state(&self) -> &GameState {
+        &self.state
+    }
+
+    /// Ticks played so far.
+    pub fn frame(&self) -> u32 {
+        self.state.sim.frame_counter
+    }
+
+    pub fn length(&self) -> u32 {
+        self.length
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Run one tick, unless paused or at the end.
+    pub fn advance(&mut self, memory: &mut ActiveMemory) {
+        if !self.paused {
+            self.tick(memory);
+        }
+    }
+
+    /// Pause and run exactly one tick forward.
+    pub fn step_forward(&mut self, memory: &mut ActiveMemory) {
+        self.paused = true;
+        self.tick(memory);
+    }
+
+    /// Pause and go back one tick.
+    pub fn step_back(&mut self, memory: &mut ActiveMemory) {
+        self.paused = true;
+        self.seek(self.frame().saturating_sub(1), memory);
+    }
+
+    /// Move to `frame` (clamped to the replay length), re-simulating from
+    /// the nearest keyframe when going back or skipping ahead.
+    pub fn seek(&mut self, frame: u32, memory: &mut ActiveMemory) {
+        let frame = frame.min(self.length);
+
+        // Restore the nearest keyframe at or before `frame` when going back,
+        // or when it lies ahead of the current frame. Keyframe 0 is recorded
+        // on the first tick, so going back always finds one.
+        let restore = match self.keyframes.len() as u32 {
+            0 => None,
+            recorded => {
+                let keyframe = (frame / KEYFRAME_INTERVAL).min(recorded - 1);
+                let at = keyframe * KEYFRAME_INTERVAL;
+
+                (frame < self.frame() || at > self.frame()).then_some(keyframe)
+            }
+        };
+
+        if let Some(keyframe) = restore {
+            let snapshot = &self.keyframes[keyframe as usize];
+            self.state
+                .deserialize_into(&mut &snapshot[..])
+                .expect("keyframes are written by GameState::serialize");
+        }
+
+        while self.frame() < frame {
+            self.tick(memory);
+        }
+    }
+
+    fn tick(&mut self, memory: &mut ActiveMemory) {
+        let frame = self.frame();
+
+        if frame >= self.length {
+            return;
+        }
+
+        if frame.is_multiple_of(KEYFRAME_INTERVAL)
+            && (frame / KEYFRAME_INTERVAL) as usize == self.keyframes.len()
+        {
+            let mut snapshot = [0u8; STATE_SNAPSHOT_MAX];
+            self.state
+                .serialize(&mut &mut snapshot[..])
+                .expect("a snapshot fits in STATE_SNAPSHOT_MAX");
+            self.keyframes.push(snapshot);
+        }
+
+        update(&mut self.state, self.script.at(frame), memory);
+        memory.frame.reset();
+    }
+
+    /// Frame under the pixel column `x` of the timeline.
+    pub fn frame_at_x(&self, x: usize) -> u32 {
+        (x.min(WIDTH - 1) as u64 * self.length as u64 / (WIDTH - 1) as u64) as u32
+    }
+
+    /// Draw the timeline strip: the current frame and length, a progress
+    /// bar, and a tick under every recorded keyframe.
+    pub fn render_timeline(&self, framebuffer: &mut FrameBuffer, language: Language) {
+        framebuffer.fill_rect(0, TIMELINE_Y, WIDTH, TIMELINE_HEIGHT, 0);
+
+        framebuffer.draw_u32(2, TIMELINE_Y + 1, self.frame(), 6, 3);
+        framebuffer.draw_text(2 + 6 * FONT_ADVANCE, TIMELINE_Y + 1, "/", 2);
+        framebuffer.draw_u32(2 + 7 * FONT_ADVANCE, TIMELINE_Y + 1, self.length, 6, 2);
+
+        if self.paused {
+            let text = language.text(TextId::Paused);
+            let x = WIDTH - 2 - text.chars().count() * FONT_ADVANCE;
+            framebuffer.draw_text(x, TIMELINE_Y + 1, text, 3);
+        }
+
+        let progress = self.frame() as u64 * (WIDTH - 1) as u64 / self.length.max(1) as u64;
+
+        framebuffer.fill_rect(0, BAR_Y, WIDTH, 2, 1);
+        framebuffer.fill_rect(0, BAR_Y, progress as usize + 1, 2, 3);
+
+        for keyframe in 0..self.keyframes.len() as u32 {
+            let x = (keyframe * KEYFRAME_INTERVAL) as u64 * (WIDTH - 1) as u64
+                / self.length.max(1) as u64;
+            framebuffer.set_pixel(x as usize, BAR_Y + 2, 2);
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! The save file: what the game remembers between runs, as opposed to the
+//! settings in `bitbound.cfg`.
+//!
+//! It uses the config file's `key = value` lines, so it stays readable and
+//! easy to reset by hand. Keys the game does not know are kept and written
+//! back, so a save from a newer version survives a run of an older one.
+//! Kill counts are keyed by enemy name rather than index, so reordering
+//! `assets/entities.toml` does not move them. Cleared stages are keyed by
+//! their level file's name, and unlock them in practice. Tutorial prompts
+//! are keyed by `Prompt::name` once seen. The speedrun timer's best
+//! segments are kept in ticks, keyed by segment name. Shop upgrades are
+//! keyed by `Upgrade::name`.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::game::entities::EnemyKind;
+use crate::game::shop::{Upgrade, Upgrades};
+use crate::game::stats::Stats;
+use crate::game::tutorial::{PROMPTS, Prompt};
+use crate::runtime::config::Config;
+
+/// Where the binary keeps the save, relative to the working directory.
+pub const SAVE_FILE: &str = "bitbound.sav";
+
+const PLAY_TICKS: &str = "play-ticks";
+const GAMES: &str = "games";
+const HITS: &str = "hits";
+const COINS: &str = "coins";
+/// Followed by the enemy's name.
+const KILLS: &str = "kills.";
+/// Followed by the level file's name without its extension.
+const CLEARED: &str = "cleared.";
+/// Followed by the tutorial prompt's name.
+const SEEN: &str = "seen.";
+/// Followed by the speedrun segment's name.
+const BEST: &str = "best.";
+/// Followed by the upgrade's name.
+const UPGRADE: &str = "upgrade.";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SaveData {
+    pub stats: Stats,
+    /// Coins to spend in the shop.
+    pub coins: u32,
+    pub upgrades: Upgrades,
+    /// Every line read, updated from the fields on save.
+    entries: Config,
+}
+
+impl SaveData {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let entries = Config::parse(text)?;
+        let mut stats = Stats::new();
+        let mut coins = 0;
+        let mut upgrades = Upgrades::NONE;
+
+        for (key, value) in entries.entries() {
+            let number = || {
+                value
+                    .parse::<u64>()
+                    .map_err(|_| format!("invalid {key} `{value}`; use a whole number"))
+            };
+            let count = || number().map(|n| n.min(u32::MAX as u64) as u32);
+
+            match key {
+                PLAY_TICKS => stats.play_ticks = number()?,
+                GAMES => stats.games = count()?,
+                HITS => stats.hits = count()?,
+                COINS => coins = count()?,
+                _ => {
+                    if let Some(name) = key.strip_prefix(KILLS)
+                        && let Some(kind) = EnemyKind::ALL
+                            .into_iter()
+                            .find(|kind| kind.def().name == name)
+                    {
+                        stats.kills[kind.index() as usize] = count()?;
+                    } else if let Some(name) = key.strip_prefix(UPGRADE)
+                        && let Some(upgrade) = Upgrade::ALL
+                            .into_iter()
+                            .find(|upgrade| upgrade.name() == name)
+                    {
+                        upgrades.set_level(upgrade, count()?.min(u8::MAX as u32) as u8);
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            stats,
+            coins,
+            upgrades,
+            entries,
+        })
+    }
+
+    /// Whether the stage of level file `stage` has been cleared.
+    pub fn is_cleared(&self, stage: &str) -> bool {
+        self.entries.get(&format!("{CLEARED}{stage}")) == Some("true")
+    }
+
+    /// Remember the stage of level file `stage` as cleared.
+    pub fn set_cleared(&mut self, stage: &str) {
+        self.entries.set(&format!("{CLEARED}{stage}"), "true");
+    }
+
+    /// Which tutorial prompts have been seen, by `Prompt` order.
+    pub fn tutorial_seen(&self) -> [bool; PROMPTS] {
+        Prompt::ALL
+            .map(|prompt| self.entries.get(&format!("{SEEN}{}", prompt.name())) == Some("true"))
+    }
+
+    /// Remember the tutorial prompts `seen`, by `Prompt` order.
+    pub fn set_tutorial_seen(&mut self, seen: [bool; PROMPTS]) {
+        for (prompt, seen) in Prompt::ALL.into_iter().zip(seen) {
+            if seen {
+                self.entries
+                    .set(&format!("{SEEN}{}", prompt.name()), "true");
+            }
+        }
+    }
+
+    /// The best time for speedrun segment `segment`, in ticks.
+    pub fn best_segment(&self, segment: &str) -> Option<u32> {
+        self.entries.get(&format!("{BEST}{segment}"))?.parse().ok()
+    }
+
+    pub fn set_best_segment(&mut self, segment: &str, ticks: u32) {
+        self.entries
+            .set(&format!("{BEST}{segment}"), &ticks.to_string());
+    }
+
+    /// Read the save at `path`. A missing file is a fresh save.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Self::new()),
+            Err(err) => return Err(err),
+        };
+
+        Self::parse(&text).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    pub fn save(&mut self, path: &Path) -> io::Result<()> {
+        let stats = self.stats;
+
+        self.entries.set(PLAY_TICKS, &stats.play_ticks.to_string());
+        self.entries.set(GAMES, &stats.games.to_string());
+        self.entries.set(HITS, &stats.hits.to_string());
+        self.entries.set(COINS, &self.coins.to_string());
+
+        for upgrade in Upgrade::ALL {
+            let level = self.upgrades.level(upgrade);
+            self.entries
+                .set(&format!("{UPGRADE}{}", upgrade.name()), &level.to_string());
+        }
+
+        for kind in EnemyKind::ALL {
+            let kills = stats.kills[kind.index() as usize];
+            self.entries
+                .set(&format!("{KILLS}{}", kind.def().name), &kills.to_string());
+        }
+
+        fs::write(path, self.to_string())
+    }
+}
+
+impl fmt::Display for SaveData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "# bit-bound save; delete to start over")?;
+
+        for (key, value) in self.entries.entries() {
+            writeln!(f, "{key} = {value}")?;
+        }
+
+        Ok(())
+    }
+}
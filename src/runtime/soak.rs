@@ -0,0 +1,219 @@
+//! Long headless runs with random input, checking runtime invariants.
+//!
+//! Input is random but derived from a seed: each input is held for 1 to
+//! `MAX_HOLD_FRAMES` ticks, like a player mashing the pad. The run stops at
+//! the first violated invariant, and the input played so far is returned as
+//! an `InputScript` so the failure can be replayed with `--input`.
+
+use std::fmt;
+use std::time::Instant;
+
+use crate::game::{GameState, update};
+use crate::input::{Input, InputScript};
+use crate::runtime::crash;
+use crate::runtime::memory::ActiveMemory;
+use crate::runtime::rng::Rng;
+
+/// Longest a random input is held, in ticks.
+pub const MAX_HOLD_FRAMES: u32 = 60;
+
+/// `update()` budget for one tick, in microseconds.
+pub const TICK_BUDGET_US: u32 = 16_667;
+
+/// At most one tick in this many (plus one) may exceed `TICK_BUDGET_US`.
+/// Occasional scheduler hiccups are tolerated; a sustained slowdown is not.
+pub const SLOW_TICK_RATIO: u32 = 1000;
+
+/// Ticks between progress reports: one hour of game time.
+pub const REPORT_INTERVAL: u32 = 60 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Violation {
+    /// An arena's peak reached its capacity: the next allocation would fail.
+    OutOfMemory { arena: &'static str, peak: usize },
+    /// The global or level arena grew during the run. Only the frame arena
+    /// may allocate per tick.
+    Leak {
+        arena: &'static str,
+        before: usize,
+        after: usize,
+    },
+    /// The enemy pool filled up, so spawning outpaces despawning.
+    PoolFull { len: usize },
+    /// Too many ticks over `TICK_BUDGET_US`.
+    TooSlow { slow_ticks: u32, max_tick_us: u32 },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfMemory { arena, peak } => {
+                write!(f, "{arena} arena exhausted (peak {peak} B)")
+            }
+            Self::Leak {
+                arena,
+                before,
+                after,
+            } => write!(f, "{arena} arena grew from {before} B to {after} B"),
+            Self::PoolFull { len } => write!(f, "enemy pool full ({len} live)"),
+            Self::TooSlow {
+                slow_ticks,
+                max_tick_us,
+            } => write!(
+                f,
+                "{slow_ticks} ticks over {TICK_BUDGET_US} us (slowest {max_tick_us} us)"
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SoakReport {
+    pub seed: u64,
+    /// Ticks completed, including the failing one.
+    pub frames: u32,
+    pub violation: Option<(u32, Violation)>,
+    pub max_tick_us: u32,
+    pub slow_ticks: u32,
+    pub max_enemies: usize,
+    pub frame_peak: usize,
+    /// Input played so far.
+    pub input: InputScript,
+}
+
+impl fmt::Display for SoakReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "seed: {}", self.seed)?;
+        writeln!(f, "frames: {}", self.frames)?;
+        writeln!(
+            f,
+            "max tick: {} us ({} slow)",
+            self.max_tick_us, self.slow_ticks
+        )?;
+        writeln!(f, "max enemies: {}", self.max_enemies)?;
+        writeln!(f, "frame arena peak: {} B", self.frame_peak)?;
+
+        match &self.violation {
+            Some((frame, violation)) => write!(f, "FAILED at frame {frame}: {violation}"),
+            None => write!(f, "passed"),
+        }
+    }
+}
+
+/// Play `frames` ticks of seeded random input, stopping at the first
+/// violated invariant. Progress is written to stderr every
+/// `REPORT_INTERVAL` ticks.
+pub fn run(memory: &mut ActiveMemory, frames: u32, seed: u64) -> SoakReport {
+    let mut rng = Rng::new(seed);
+    let mut state = GameState::new();
+    let mut input = InputScript::new();
+    let mut held = Input::empty();
+    let mut hold_left = 0;
+
+    let global_before = memory.global.used();
+    let level_before = memory.level.used();
+
+    let mut report = SoakReport {
+        seed,
+        frames: 0,
+        violation: None,
+        max_tick_us: 0,
+        slow_ticks: 0,
+        max_enemies: 0,
+        frame_peak: 0,
+        input: InputScript::new(),
+    };
+
+    for frame in 0..frames {
+        if hold_left == 0 {
+            held = Input::from_bits(rng.next_u32() as u8);
+            hold_left = 1 + rng.below(MAX_HOLD_FRAMES);
+        }
+
+        hold_left -= 1;
+        input.push(frame, held);
+
+        let start = Instant::now();
+
+        crash::checkpoint(&state, memory);
+        update(&mut state, held, memory);
+
+        let tick_us = start.elapsed().as_micros() as u32;
+
+        report.frames = frame + 1;
+        report.max_tick_us = report.max_tick_us.max(tick_us);
+        report.max_enemies = report.max_enemies.max(state.sim.enemies.len());
+        report.frame_peak = memory.frame.peak();
+
+        if tick_us > TICK_BUDGET_US {
+            report.slow_ticks += 1;
+        }
+
+        let violation = check(memory, &state, &report, global_before, level_before);
+        memory.frame.reset();
+
+        if let Some(violation) = violation {
+            report.violation = Some((frame, violation));
+            break;
+        }
+
+        if report.frames.is_multiple_of(REPORT_INTERVAL) {
+            eprintln!(
+                "soak: {} / {frames} frames, max tick {} us, max enemies {}",
+                report.frames, report.max_tick_us, report.max_enemies
+            );
+        }
+    }
+
+    input.set_end(report.frames);
+    report.input = input;
+    report
+}
+
+fn check(
+    memory: &ActiveMemory,
+    state: &GameState,
+    report: &SoakReport,
+    global_before: usize,
+    level_before: usize,
+) -> Option<Violation> {
+    let arenas = [
+        ("global", memory.global.peak(), memory.global.capacity()),
+        ("level", memory.level.peak(), memory.level.capacity()),
+        ("frame", memory.frame.peak(), memory.frame.capacity()),
+    ];
+
+    for (arena, peak, capacity) in arenas {
+        if peak >= capacity {
+            return Some(Violation::OutOfMemory { arena, peak });
+        }
+    }
+
+    for (arena, before, after) in [
+        ("global", global_before, memory.global.used()),
+        ("level", level_before, memory.level.used()),
+    ] {
+        if after > before {
+            return Some(Violation::Leak {
+                arena,
+                before,
+                after,
+            });
+        }
+    }
+
+    if state.sim.enemies.len() == state.sim.enemies.capacity() {
+        return Some(Violation::PoolFull {
+            len: state.sim.enemies.len(),
+        });
+    }
+
+    if report.slow_ticks > 1 + report.frames / SLOW_TICK_RATIO {
+        return Some(Violation::TooSlow {
+            slow_ticks: report.slow_ticks,
+            max_tick_us: report.max_tick_us,
+        });
+    }
+
+    None
+}
@@ -0,0 +1,95 @@
+//! LiveSplit export of a run's splits.
+//!
+//! `to_lss` writes a LiveSplit splits file (`.lss`) with one segment per
+//! split: the run's time at the split as the personal best, and the best
+//! segment time, the save's or this run's. LiveSplit opens it as is, so a
+//! runner can start from the game's own splits. Times are real time; the
+//! game runs at a fixed `TICK_RATE`, so ticks convert exactly.
+
+use std::fmt::Write as _;
+
+use crate::game::speedrun::Split;
+use crate::runtime::pacing::TICK_RATE;
+
+/// A split and the name of its segment.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NamedSplit<'a> {
+    pub name: &'a str,
+    pub split: Split,
+}
+
+/// `ticks` as a LiveSplit time, `HH:MM:SS.fffffff`.
+pub fn lss_time(ticks: u32) -> String {
+    let seconds = ticks as u64 / TICK_RATE;
+    // 100 ns units, LiveSplit's resolution.
+    let fraction = (ticks as u64 % TICK_RATE) * 10_000_000 / TICK_RATE;
+
+    format!(
+        "{:02}:{:02}:{:02}.{fraction:07}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// The splits as a LiveSplit splits file.
+pub fn to_lss(category: &str, splits: &[NamedSplit<'_>]) -> String {
+    let mut out = String::new();
+
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(out, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(out, r#"<Run version="1.7.0">"#);
+    let _ = writeln!(out, "  <GameIcon />");
+    let _ = writeln!(out, "  <GameName>bit-bound</GameName>");
+    let _ = writeln!(out, "  <CategoryName>{}</CategoryName>", escape(category));
+    let _ = writeln!(out, "  <Offset>00:00:00</Offset>");
+    let _ = writeln!(out, "  <AttemptCount>1</AttemptCount>");
+    let _ = writeln!(out, "  <AttemptHistory />");
+    let _ = writeln!(out, "  <Segments>");
+
+    for NamedSplit { name, split } in splits {
+        let best = split
+            .best
+            .map_or(split.segment, |best| best.min(split.segment));
+
+        let _ = writeln!(out, "    <Segment>");
+        let _ = writeln!(out, "      <Name>{}</Name>", escape(name));
+        let _ = writeln!(out, "      <Icon />");
+        let _ = writeln!(out, "      <SplitTimes>");
+        let _ = writeln!(out, r#"        <SplitTime name="Personal Best">"#);
+        let _ = writeln!(
+            out,
+            "          <RealTime>{}</RealTime>",
+            lss_time(split.ticks)
+        );
+        let _ = writeln!(out, "        </SplitTime>");
+        let _ = writeln!(out, "      </SplitTimes>");
+        let _ = writeln!(out, "      <BestSegmentTime>");
+        let _ = writeln!(out, "        <RealTime>{}</RealTime>", lss_time(best));
+        let _ = writeln!(out, "      </BestSegmentTime>");
+        let _ = writeln!(out, "      <SegmentHistory />");
+        let _ = writeln!(out, "    </Segment>");
+    }
+
+    let _ = writeln!(out, "  </Segments>");
+    let _ = writeln!(out, "  <AutoSplitterSettings />");
+    let _ = writeln!(out, "</Run>");
+    out
+}
+
+/// `text` with XML's special characters escaped.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+
+    out
+}
@@ -0,0 +1,48 @@
+use core::cell::UnsafeCell;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+/// Static storage that can be claimed exactly once.
+///
+/// `take()` hands out the only `&'static mut T` that will ever exist for the
+/// cell; every later call returns `None`. Because there is never more than one
+/// mutable reference, the cell can soundly be `Sync` for any `T: Send`.
+pub struct StaticCell<T> {
+    taken: AtomicBool,
+    value: UnsafeCell<T>,
+}
+
+// SAFETY: `take()` gives out at most one `&mut T`, guarded by the atomic flag,
+// so sharing the cell between threads only ever moves `T` to one owner.
+unsafe impl<T: Send> Sync for StaticCell<T> {}
+
+impl<T> StaticCell<T> {
+    pub const fn new(value: T) -> Self {
+        Self {
+            taken: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Claim the cell's contents. Returns `None` if already claimed.
+    #[allow(clippy::mut_from_ref)] // the atomic flag makes the `&mut` unique
+    pub fn take(&'static self) -> Option<&'static mut T> {
+        #[cfg(target_has_atomic = "8")]
+        let taken = self.taken.swap(true, Ordering::AcqRel);
+
+        // Cores without compare-and-swap (Cortex-M0+) can only load and
+        // store, so two claims racing here could both win. Embedded targets
+        // claim once at startup, before a second core or interrupt could.
+        #[cfg(not(target_has_atomic = "8"))]
+        let taken = {
+            let taken = self.taken.load(Ordering::Acquire);
+            self.taken.store(true, Ordering::Release);
+            taken
+        };
+
+        if taken {
+            return None;
+        }
+
+        Some(unsafe { &mut *self.value.get() })
+    }
+}
@@ -0,0 +1,158 @@
+//! VRAM-style asset viewer, like an emulator's tile viewer: the palette,
+//! then every tile of every sprite in the asset manifest on a grid, in
+//! manifest order. Clicking a tile selects it and shows which sprite it
+//! belongs to.
+//!
+//! Tiles are drawn as the game draws them, so shade 0 shows as the
+//! background; a tile that looks empty is either blank or fully
+//! transparent.
+
+use crate::game::assets::SpriteId;
+use crate::renderer::framebuffer::{FONT_ADVANCE, FrameBuffer, HEIGHT, LINE_HEIGHT};
+
+const PALETTE_Y: usize = 1;
+const SWATCH_WIDTH: usize = 6;
+/// A swatch, its hex value and the gap after it.
+const SWATCH_ADVANCE: usize = SWATCH_WIDTH + 2 + 7 * FONT_ADVANCE;
+
+const GRID_X: usize = 2;
+const GRID_Y: usize = PALETTE_Y + LINE_HEIGHT + 2;
+/// A tile and the line after it.
+const CELL: usize = 9;
+const GRID_COLUMNS: usize = 16;
+
+const INFO_Y: usize = HEIGHT - 2 * LINE_HEIGHT;
+/// Tiles beyond this many rows are not shown.
+const GRID_ROWS: usize = (INFO_Y - 1 - GRID_Y) / CELL;
+
+/// Where a tile on the grid comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TileRef {
+    sprite: SpriteId,
+    /// Index within the sprite, row-major.
+    tile: usize,
+}
+
+/// Every tile on the grid in order, with its grid index.
+fn tiles() -> impl Iterator<Item = (usize, TileRef)> {
+    SpriteId::ALL
+        .into_iter()
+        .flat_map(|sprite| {
+            let count = sprite.sprite().tiles_x * sprite.sprite().tiles_y;
+            (0..count).map(move |tile| TileRef { sprite, tile })
+        })
+        .enumerate()
+        .take(GRID_COLUMNS * GRID_ROWS)
+}
+
+fn cell_origin(index: usize) -> (usize, usize) {
+    (
+        GRID_X + (index % GRID_COLUMNS) * CELL,
+        GRID_Y + (index / GRID_COLUMNS) * CELL,
+    )
+}
+
+/// The viewer's selection; drawing needs nothing else.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VramViewer {
+    selected: Option<TileRef>,
+}
+
+impl VramViewer {
+    pub const fn new() -> Self {
+        Self { selected: None }
+    }
+
+    /// Select the tile under framebuffer pixel (`x`, `y`), if there is one.
+    pub fn select_at(&mut self, x: usize, y: usize) {
+        if x < GRID_X || y < GRID_Y {
+            return;
+        }
+
+        let (column, row) = ((x - GRID_X) / CELL, (y - GRID_Y) / CELL);
+
+        if column >= GRID_COLUMNS || row >= GRID_ROWS {
+            return;
+        }
+
+        let index = row * GRID_COLUMNS + column;
+
+        if let Some((_, tile)) = tiles().find(|&(i, _)| i == index) {
+            self.selected = Some(tile);
+        }
+    }
+
+    /// Draw over the whole screen.
+    pub fn render(&self, framebuffer: &mut FrameBuffer) {
+        framebuffer.clear(0);
+
+        let palette = *framebuffer.palette();
+
+        for (shade, argb) in palette.into_iter().enumerate() {
+            let x = GRID_X + shade * SWATCH_ADVANCE;
+
+            framebuffer.fill_rect(x, PALETTE_Y, SWATCH_WIDTH, LINE_HEIGHT - 1, shade as u8);
+            framebuffer.draw_rect(x, PALETTE_Y, SWATCH_WIDTH, LINE_HEIGHT - 1, 1);
+            draw_hex(
+                framebuffer,
+                x + SWATCH_WIDTH + 2,
+                PALETTE_Y,
+                argb & 0xFF_FFFF,
+            );
+        }
+
+        let mut shown = 0;
+
+        for (index, tile) in tiles() {
+            let (x, y) = cell_origin(index);
+            let sprite = tile.sprite.sprite();
+            let offset = tile.tile * 16;
+            let color = match self.selected {
+                Some(selected) if selected == tile => 3,
+                Some(selected) if selected.sprite == tile.sprite => 2,
+                _ => 1,
+            };
+
+            framebuffer.draw_rect(x - 1, y - 1, CELL + 1, CELL + 1, color);
+            framebuffer.draw_tile(x as i32, y as i32, &sprite.data[offset..offset + 16]);
+            shown += 1;
+        }
+
+        let Some(selected) = self.selected else {
+            framebuffer.draw_text(GRID_X, INFO_Y, "CLICK A TILE", 2);
+            framebuffer.draw_u32(GRID_X, INFO_Y + LINE_HEIGHT, shown, 3, 1);
+            framebuffer.draw_text(GRID_X + 4 * FONT_ADVANCE, INFO_Y + LINE_HEIGHT, "TILES", 1);
+            return;
+        };
+
+        let sprite = selected.sprite.sprite();
+        let tiles = sprite.tiles_x * sprite.tiles_y;
+        let mut x = GRID_X;
+
+        framebuffer.draw_text(GRID_X, INFO_Y, selected.sprite.name(), 3);
+
+        for (value, digits, label) in [
+            (selected.tile % sprite.tiles_x, 2, ":"),
+            (selected.tile / sprite.tiles_x, 2, " "),
+            (selected.tile, 3, "/"),
+            (tiles, 3, " "),
+            (sprite.width, 3, "X"),
+            (sprite.height, 3, ""),
+        ] {
+            framebuffer.draw_u32(x, INFO_Y + LINE_HEIGHT, value as u32, digits, 2);
+            x += digits * FONT_ADVANCE;
+            framebuffer.draw_text(x, INFO_Y + LINE_HEIGHT, label, 1);
+            x += label.len() * FONT_ADVANCE;
+        }
+    }
+}
+
+fn draw_hex(framebuffer: &mut FrameBuffer, x: usize, y: usize, rgb: u32) {
+    let mut text = [0u8; 6];
+
+    for (i, digit) in text.iter_mut().enumerate() {
+        *digit = b"0123456789ABCDEF"[(rgb >> (20 - i * 4)) as usize & 0xF];
+    }
+
+    framebuffer.draw_text(x, y, core::str::from_utf8(&text).unwrap_or(""), 2);
+}
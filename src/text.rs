@@ -0,0 +1,233 @@
+//! Game UI text in every language the game ships.
+//!
+//! Each language is a `match` over `TextId`, so a language missing a string
+//! does not compile. The built-in font only has A-Z, 0-9, `:`, `/`, `+`,
+//! `-` and space; a language that needs more letters brings a glyph font,
+//! converted with `spritec --font --chars` and listed in the asset
+//! manifest, that `FrameBuffer` falls back to (see `Language::glyphs`).
+//! Debug tools are not translated.
+
+use crate::game::assets::GLYPHS_DE;
+use crate::renderer::font::Font;
+
+/// A string shown to the player.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TextId {
+    /// The badge shown while turbo is on.
+    Turbo,
+    /// The replay viewer's paused marker.
+    Paused,
+    LeaderboardTitle,
+    LeaderboardLoading,
+    LeaderboardEmpty,
+    LeaderboardUnreachable,
+    /// Blinks on the title until a button is pressed.
+    PressStart,
+    MenuStart,
+    MenuOptions,
+    MenuHighScores,
+    MenuStats,
+    MenuPractice,
+    MenuBack,
+    OptionReduceFlashing,
+    OptionScreenShake,
+    OptionLargeText,
+    OptionShapeMarkers,
+    /// An option's state.
+    On,
+    Off,
+    StatsTitle,
+    StatsPlayTime,
+    StatsGames,
+    StatsHits,
+    StatsKills,
+    /// Where a practice stage starts: its beginning or its boss.
+    PracticeFromStart,
+    PracticeFromBoss,
+    PracticeBossRush,
+    ShopTitle,
+    /// Before the coins to spend.
+    ShopCoins,
+    ShopSpeed,
+    ShopWeaponSlots,
+    /// In place of the cost of an upgrade at its top level.
+    ShopMaxed,
+    ShopContinue,
+    /// Tutorial prompts, after the buttons they show.
+    TutorialMove,
+    TutorialDodge,
+    /// After the button that opens photo mode from the pause.
+    Photo,
+    /// Above the message on the screen a failed run ends on.
+    ErrorTitle,
+    ErrorDismiss,
+}
+
+impl TextId {
+    pub const ALL: [Self; 38] = [
+        Self::Turbo,
+        Self::Paused,
+        Self::LeaderboardTitle,
+        Self::LeaderboardLoading,
+        Self::LeaderboardEmpty,
+        Self::LeaderboardUnreachable,
+        Self::PressStart,
+        Self::MenuStart,
+        Self::MenuOptions,
+        Self::MenuHighScores,
+        Self::MenuStats,
+        Self::MenuPractice,
+        Self::MenuBack,
+        Self::OptionReduceFlashing,
+        Self::OptionScreenShake,
+        Self::OptionLargeText,
+        Self::OptionShapeMarkers,
+        Self::On,
+        Self::Off,
+        Self::StatsTitle,
+        Self::StatsPlayTime,
+        Self::StatsGames,
+        Self::StatsHits,
+        Self::StatsKills,
+        Self::PracticeFromStart,
+        Self::PracticeFromBoss,
+        Self::PracticeBossRush,
+        Self::ShopTitle,
+        Self::ShopCoins,
+        Self::ShopSpeed,
+        Self::ShopWeaponSlots,
+        Self::ShopMaxed,
+        Self::ShopContinue,
+        Self::TutorialMove,
+        Self::TutorialDodge,
+        Self::Photo,
+        Self::ErrorTitle,
+        Self::ErrorDismiss,
+    ];
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+/// Languages by their ISO 639-1 code, the `language` setting's values.
+pub const LANGUAGES: [(&str, Language); 2] = [("en", Language::English), ("de", Language::German)];
+
+const GERMAN_GLYPHS: Option<Font> = Font::new(GLYPHS_DE);
+
+const _: () = assert!(GERMAN_GLYPHS.is_some(), "glyphs_de.font is not a font");
+
+impl Language {
+    /// The language with ISO 639-1 code `code` in `LANGUAGES`.
+    pub fn named(code: &str) -> Option<Self> {
+        LANGUAGES
+            .iter()
+            .find(|(candidate, _)| *candidate == code)
+            .map(|&(_, language)| language)
+    }
+
+    pub const fn text(self, id: TextId) -> &'static str {
+        match self {
+            Self::English => english(id),
+            Self::German => german(id),
+        }
+    }
+
+    /// Glyphs for the letters this language uses beyond the built-in font,
+    /// for `FrameBuffer::set_extra_glyphs`.
+    pub const fn glyphs(self) -> Option<Font> {
+        match self {
+            Self::English => None,
+            Self::German => GERMAN_GLYPHS,
+        }
+    }
+}
+
+const fn english(id: TextId) -> &'static str {
+    match id {
+        TextId::Turbo => "TURBO",
+        TextId::Paused => "PAUSED",
+        TextId::LeaderboardTitle => "LEADERBOARD",
+        TextId::LeaderboardLoading => "LOADING",
+        TextId::LeaderboardEmpty => "NO SCORES YET",
+        TextId::LeaderboardUnreachable => "SERVER UNREACHABLE",
+        TextId::PressStart => "PRESS START",
+        TextId::MenuStart => "START",
+        TextId::MenuOptions => "OPTIONS",
+        TextId::MenuHighScores => "HIGH SCORES",
+        TextId::MenuStats => "STATS",
+        TextId::MenuPractice => "PRACTICE",
+        TextId::MenuBack => "BACK",
+        TextId::OptionReduceFlashing => "REDUCE FLASHING",
+        TextId::OptionScreenShake => "SCREEN SHAKE",
+        TextId::OptionLargeText => "LARGE TEXT",
+        TextId::OptionShapeMarkers => "SHAPE MARKERS",
+        TextId::On => "ON",
+        TextId::Off => "OFF",
+        TextId::StatsTitle => "STATS",
+        TextId::StatsPlayTime => "PLAY TIME",
+        TextId::StatsGames => "GAMES",
+        TextId::StatsHits => "HITS TAKEN",
+        TextId::StatsKills => "ENEMIES DESTROYED",
+        TextId::PracticeFromStart => "START",
+        TextId::PracticeFromBoss => "BOSS",
+        TextId::PracticeBossRush => "BOSS RUSH",
+        TextId::ShopTitle => "SHOP",
+        TextId::ShopCoins => "COINS",
+        TextId::ShopSpeed => "SPEED",
+        TextId::ShopWeaponSlots => "WEAPON SLOTS",
+        TextId::ShopMaxed => "MAX",
+        TextId::ShopContinue => "CONTINUE",
+        TextId::TutorialMove => "MOVE",
+        TextId::TutorialDodge => "DODGE",
+        TextId::Photo => "PHOTO",
+        TextId::ErrorTitle => "ERROR",
+        TextId::ErrorDismiss => "PRESS ANY BUTTON",
+    }
+}
+
+const fn german(id: TextId) -> &'static str {
+    match id {
+        TextId::Turbo => "TURBO",
+        TextId::Paused => "PAUSE",
+        TextId::LeaderboardTitle => "BESTENLISTE",
+        TextId::LeaderboardLoading => "LÄDT",
+        TextId::LeaderboardEmpty => "NOCH KEINE PUNKTE",
+        TextId::LeaderboardUnreachable => "SERVER NICHT ERREICHBAR",
+        TextId::PressStart => "START DRÜCKEN",
+        TextId::MenuStart => "START",
+        TextId::MenuOptions => "OPTIONEN",
+        TextId::MenuHighScores => "BESTENLISTE",
+        TextId::MenuStats => "STATISTIK",
+        TextId::MenuPractice => "TRAINING",
+        TextId::MenuBack => "ZURÜCK",
+        TextId::OptionReduceFlashing => "WENIGER BLITZE",
+        TextId::OptionScreenShake => "WACKELN",
+        TextId::OptionLargeText => "GROSSE SCHRIFT",
+        TextId::OptionShapeMarkers => "FORMMARKER",
+        TextId::On => "AN",
+        TextId::Off => "AUS",
+        TextId::StatsTitle => "STATISTIK",
+        TextId::StatsPlayTime => "SPIELZEIT",
+        TextId::StatsGames => "SPIELE",
+        TextId::StatsHits => "TREFFER KASSIERT",
+        TextId::StatsKills => "GEGNER ZERSTÖRT",
+        TextId::PracticeFromStart => "ANFANG",
+        TextId::PracticeFromBoss => "BOSS",
+        TextId::PracticeBossRush => "ALLE BOSSE",
+        TextId::ShopTitle => "LADEN",
+        TextId::ShopCoins => "MÜNZEN",
+        TextId::ShopSpeed => "TEMPO",
+        TextId::ShopWeaponSlots => "WAFFENPLÄTZE",
+        TextId::ShopMaxed => "MAX",
+        TextId::ShopContinue => "WEITER",
+        TextId::TutorialMove => "STEUERN",
+        TextId::TutorialDodge => "AUSWEICHEN",
+        TextId::Photo => "FOTO",
+        TextId::ErrorTitle => "FEHLER",
+        TextId::ErrorDismiss => "TASTE DRÜCKEN",
+    }
+}
@@ -0,0 +1,191 @@
+//! Golden-frame regression tests.
+//!
+//! Every `tests/golden/<name>.script` is an input script (see `InputScript`).
+//! The game runs it from a fresh `GameState` for `FRAMES` ticks, renders at
+//! every checkpoint and compares the framebuffer hash against
+//! `tests/golden/<name>.golden`. Every roll the game makes, such as crits
+//! and drops, is seeded from the tick count, and the goldens set no daily
+//! wave seed, so the script alone determines the run.
+//!
+//! After an intended visual change, re-bless the goldens with
+//!
+//! ```text
+//! BLESS=1 cargo test --test golden
+//! ```
+//!
+//! which also stores each checkpoint's packed pixels in
+//! `tests/golden/<name>/<frame>.fb`. On a mismatch those are diffed against
+//! the new frame: the test prints the number of changed pixels and their
+//! bounding box, and writes `<name>-<frame>.pgm` images to the test's temp
+//! directory, with changed pixels in white.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bit_bound::game::{GameState, render, update};
+use bit_bound::input::InputScript;
+use bit_bound::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+use bit_bound::runtime::Runtime;
+use bit_bound::runtime::memory::ActiveMemory;
+
+const FRAMES: u32 = 300;
+const CHECKPOINT_INTERVAL: u32 = 60;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn blessing() -> bool {
+    std::env::var_os("BLESS").is_some_and(|value| value != "0")
+}
+
+/// Run `script` from a fresh state, calling `checkpoint` with the rendered
+/// frame every `CHECKPOINT_INTERVAL` ticks.
+fn run_scenario(
+    memory: &mut ActiveMemory,
+    framebuffer: &mut FrameBuffer,
+    script: &InputScript,
+    mut checkpoint: impl FnMut(u32, &FrameBuffer),
+) {
+    let mut state = GameState::new();
+
+    for frame in 1..=FRAMES {
+        update(&mut state, script.at(frame - 1), memory);
+        memory.frame.reset();
+
+        if frame % CHECKPOINT_INTERVAL == 0 {
+            render(&state, framebuffer);
+            checkpoint(frame, framebuffer);
+        }
+    }
+}
+
+fn parse_golden(text: &str) -> Vec<(u32, u64)> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (frame, hash) = line.split_once(' ').expect("golden line is `frame hash`");
+            (
+                frame.parse().expect("golden frame number"),
+                u64::from_str_radix(hash.trim(), 16).expect("golden hash"),
+            )
+        })
+        .collect()
+}
+
+fn pixel(raw: &[u8], x: usize, y: usize) -> u8 {
+    let index = y * WIDTH + x;
+    (raw[index / 4] >> ((index % 4) * 2)) & 0b11
+}
+
+/// Describe how `actual` differs from the blessed pixels in `expected`, and
+/// write a diff image to the test's temp directory.
+fn describe_diff(name: &str, frame: u32, expected: &[u8], actual: &FrameBuffer) -> String {
+    let mut changed = 0;
+    let (mut min_x, mut min_y, mut max_x, mut max_y) = (WIDTH, HEIGHT, 0, 0);
+    let mut image = format!("P5\n{WIDTH} {HEIGHT}\n255\n").into_bytes();
+
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            let now = actual.get_pixel(x, y);
+
+            if pixel(expected, x, y) != now {
+                changed += 1;
+                min_x = min_x.min(x);
+                min_y = min_y.min(y);
+                max_x = max_x.max(x);
+                max_y = max_y.max(y);
+                image.push(255);
+            } else {
+                image.push(now * 40);
+            }
+        }
+    }
+
+    let out = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}-{frame}.pgm"));
+    let written = fs::write(&out, image).is_ok();
+
+    let mut report = format!("{changed} pixels changed within ({min_x},{min_y})-({max_x},{max_y})");
+
+    if written {
+        let _ = write!(report, ", diff image: {}", out.display());
+    }
+
+    report
+}
+
+#[test]
+fn golden_frames() {
+    let Runtime {
+        memory,
+        framebuffer,
+    } = Runtime::take().expect("runtime already claimed");
+
+    let mut scripts: Vec<PathBuf> = fs::read_dir(golden_dir())
+        .expect("tests/golden exists")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "script"))
+        .collect();
+    scripts.sort();
+
+    assert!(!scripts.is_empty(), "no golden scenarios found");
+
+    let mut failures = Vec::new();
+
+    for script_path in &scripts {
+        let name = script_path.file_stem().unwrap().to_str().unwrap();
+        let script = InputScript::load(script_path).expect("valid input script");
+        let golden_path = script_path.with_extension("golden");
+        let frames_dir = golden_dir().join(name);
+
+        if blessing() {
+            let mut golden = String::from("# frame hash\n");
+            fs::create_dir_all(&frames_dir).unwrap();
+
+            run_scenario(memory, framebuffer, &script, |frame, fb| {
+                let _ = writeln!(golden, "{frame} {:016x}", fb.hash());
+                fs::write(frames_dir.join(format!("{frame}.fb")), fb.raw()).unwrap();
+            });
+
+            fs::write(&golden_path, golden).unwrap();
+            continue;
+        }
+
+        let text = fs::read_to_string(&golden_path)
+            .unwrap_or_else(|_| panic!("missing {}; run with BLESS=1", golden_path.display()));
+        let mut expected = parse_golden(&text).into_iter();
+
+        run_scenario(memory, framebuffer, &script, |frame, fb| {
+            let Some((golden_frame, golden_hash)) = expected.next() else {
+                failures.push(format!("{name} frame {frame}: no golden hash"));
+                return;
+            };
+
+            assert_eq!(
+                golden_frame, frame,
+                "{name}: golden checkpoints out of date"
+            );
+
+            if fb.hash() == golden_hash {
+                return;
+            }
+
+            let detail = match fs::read(frames_dir.join(format!("{frame}.fb"))) {
+                Ok(reference) => describe_diff(name, frame, &reference, fb),
+                Err(_) => "no blessed frame to diff against".to_string(),
+            };
+
+            failures.push(format!(
+                "{name} frame {frame}: hash {:016x} != {golden_hash:016x}: {detail}",
+                fb.hash()
+            ));
+        });
+    }
+
+    assert!(
+        failures.is_empty(),
+        "golden frames differ (re-bless with BLESS=1 if intended):\n{}",
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,150 @@
+//! Golden-audio regression tests.
+//!
+//! Plays every `tests/golden/<name>.script` like `tests/golden.rs` does,
+//! rendering each tick's registers with `OfflineAudio`, and compares a hash
+//! of every `CHECKPOINT_INTERVAL` ticks of samples against
+//! `tests/golden/<name>.audio`. A change to the APU, the synth, the sound
+//! effects or the music shows up here.
+//!
+//! After an intended change to the sound, re-bless with
+//!
+//! ```text
+//! BLESS=1 cargo test --test golden_audio
+//! ```
+//!
+//! On a mismatch the whole run is written to `<name>.wav` in the test's
+//! temp directory, to listen to.
+
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use bit_bound::audio::offline::{DEFAULT_SAMPLE_RATE, OfflineAudio, write_wav};
+use bit_bound::game::{GameState, update};
+use bit_bound::input::InputScript;
+use bit_bound::runtime::Runtime;
+use bit_bound::runtime::memory::ActiveMemory;
+
+const FRAMES: u32 = 300;
+const CHECKPOINT_INTERVAL: u32 = 60;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+fn blessing() -> bool {
+    std::env::var_os("BLESS").is_some_and(|value| value != "0")
+}
+
+/// Run `script` from a fresh state. Returns the hash of each checkpoint's
+/// stretch of samples, and every sample of the run.
+fn run_scenario(memory: &mut ActiveMemory, script: &InputScript) -> (Vec<(u32, u64)>, Vec<i16>) {
+    let mut state = GameState::new();
+    let mut audio = OfflineAudio::new(DEFAULT_SAMPLE_RATE);
+    let mut hashes = Vec::new();
+    let mut samples = Vec::new();
+
+    for frame in 1..=FRAMES {
+        update(&mut state, script.at(frame - 1), memory);
+        memory.frame.reset();
+        audio.submit(state.apu.end_frame());
+
+        if frame % CHECKPOINT_INTERVAL == 0 {
+            hashes.push((frame, audio.hash()));
+            samples.extend_from_slice(audio.samples());
+            audio.clear();
+        }
+    }
+
+    (hashes, samples)
+}
+
+fn parse_golden(text: &str) -> Vec<(u32, u64)> {
+    text.lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (frame, hash) = line.split_once(' ').expect("golden line is `frame hash`");
+            (
+                frame.parse().expect("golden frame number"),
+                u64::from_str_radix(hash.trim(), 16).expect("golden hash"),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn golden_audio() {
+    let Runtime { memory, .. } = Runtime::take().expect("runtime already claimed");
+
+    let mut scripts: Vec<PathBuf> = fs::read_dir(golden_dir())
+        .expect("tests/golden exists")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "script"))
+        .collect();
+    scripts.sort();
+
+    assert!(!scripts.is_empty(), "no golden scenarios found");
+
+    let mut failures = Vec::new();
+
+    for script_path in &scripts {
+        let name = script_path.file_stem().unwrap().to_str().unwrap();
+        let script = InputScript::load(script_path).expect("valid input script");
+        let golden_path = script_path.with_extension("audio");
+        let (hashes, samples) = run_scenario(memory, &script);
+
+        if blessing() {
+            let mut golden = String::from("# frame hash\n");
+
+            for (frame, hash) in &hashes {
+                let _ = writeln!(golden, "{frame} {hash:016x}");
+            }
+
+            fs::write(&golden_path, golden).unwrap();
+            continue;
+        }
+
+        let text = fs::read_to_string(&golden_path)
+            .unwrap_or_else(|_| panic!("missing {}; run with BLESS=1", golden_path.display()));
+        let expected = parse_golden(&text);
+
+        if expected == hashes {
+            continue;
+        }
+
+        assert_eq!(
+            expected.len(),
+            hashes.len(),
+            "{name}: golden checkpoints out of date"
+        );
+
+        let differing: Vec<String> = hashes
+            .iter()
+            .zip(&expected)
+            .filter(|(actual, expected)| actual != expected)
+            .map(|((frame, _), _)| frame.to_string())
+            .collect();
+
+        let wav = Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("{name}.wav"));
+        let written = File::create(&wav)
+            .and_then(|file| write_wav(DEFAULT_SAMPLE_RATE, &samples, &mut BufWriter::new(file)));
+
+        let mut failure = format!(
+            "{name}: samples differ in the {CHECKPOINT_INTERVAL} frames before frame {}",
+            differing.join(", ")
+        );
+
+        if written.is_ok() {
+            let _ = write!(failure, "; rendered audio: {}", wav.display());
+        }
+
+        failures.push(failure);
+    }
+
+    assert!(
+        failures.is_empty(),
+        "golden audio differs (re-bless with BLESS=1 if intended):\n{}",
+        failures.join("\n")
+    );
+}
@@ -0,0 +1,89 @@
+//! Colour-blind palette checks.
+//!
+//! Each preset in `COLORBLIND_SAFE` is run through the Machado et al.
+//! (2009) full-severity simulations of protanopia, deuteranopia and
+//! tritanopia. Under every one, each shade has to stay at least
+//! `MIN_CONTRAST` (WCAG contrast ratio) brighter than the one below it, so
+//! the four shades never merge.
+
+use bit_bound::renderer::framebuffer::palette_named;
+
+const COLORBLIND_SAFE: [&str; 2] = ["redgreen", "blueyellow"];
+
+const MIN_CONTRAST: f64 = 1.5;
+
+/// Linear-RGB simulation matrices.
+const SIMULATIONS: [(&str, [[f64; 3]; 3]); 4] = [
+    (
+        "normal",
+        [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]],
+    ),
+    (
+        "protanopia",
+        [
+            [0.152286, 1.052583, -0.204868],
+            [0.114503, 0.786281, 0.099216],
+            [-0.003882, -0.048116, 1.051998],
+        ],
+    ),
+    (
+        "deuteranopia",
+        [
+            [0.367322, 0.860646, -0.227968],
+            [0.280085, 0.672501, 0.047413],
+            [-0.011820, 0.042940, 0.968881],
+        ],
+    ),
+    (
+        "tritanopia",
+        [
+            [1.255528, -0.076749, -0.178779],
+            [-0.078411, 0.930809, 0.147602],
+            [0.004733, 0.691367, 0.303900],
+        ],
+    ),
+];
+
+fn linear(channel: u32) -> f64 {
+    let c = channel as f64 / 255.0;
+
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Relative luminance of `argb` as seen through `matrix`.
+fn luminance(argb: u32, matrix: &[[f64; 3]; 3]) -> f64 {
+    let rgb = [
+        linear((argb >> 16) & 0xFF),
+        linear((argb >> 8) & 0xFF),
+        linear(argb & 0xFF),
+    ];
+    let seen =
+        matrix.map(|row| (row[0] * rgb[0] + row[1] * rgb[1] + row[2] * rgb[2]).clamp(0.0, 1.0));
+
+    0.2126 * seen[0] + 0.7152 * seen[1] + 0.0722 * seen[2]
+}
+
+#[test]
+fn colorblind_presets_keep_shades_apart() {
+    for name in COLORBLIND_SAFE {
+        let palette = palette_named(name).expect("preset is in PALETTES");
+
+        for (vision, matrix) in &SIMULATIONS {
+            for shade in 1..palette.len() {
+                let darker = luminance(palette[shade - 1], matrix);
+                let lighter = luminance(palette[shade], matrix);
+                let contrast = (lighter + 0.05) / (darker + 0.05);
+
+                assert!(
+                    contrast >= MIN_CONTRAST,
+                    "{name} under {vision}: shades {} and {shade} have contrast {contrast:.2}",
+                    shade - 1,
+                );
+            }
+        }
+    }
+}
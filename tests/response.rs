@@ -0,0 +1,85 @@
+//! Collision response checks: sliding along walls and pushing overlaps
+//! apart.
+//!
+//! The walls are a `SolidTiles` of their own, solid from tile column
+//! `WALL` on, so the checks do not depend on any level file.
+
+use bit_bound::game::kinematics::Kinematics;
+use bit_bound::game::level::TILE_SIZE;
+use bit_bound::game::response::{
+    SolidTiles, integrate_against, move_and_slide, separate, separation,
+};
+use bit_bound::math::geom::{FIXED_ONE, Rect, Vec2i};
+
+/// The first solid tile column.
+const WALL: i32 = 4;
+
+struct Wall;
+
+impl SolidTiles for Wall {
+    fn is_solid(&self, x: i32, _y: i32) -> bool {
+        x >= WALL
+    }
+}
+
+/// A 4x4 hitbox whose right edge is 4 pixels short of the wall.
+fn beside_wall() -> Vec2i {
+    Vec2i::new(WALL * TILE_SIZE as i32 - 8, 10)
+}
+
+#[test]
+fn diagonal_move_into_a_wall_slides_along_it() {
+    let hitbox = Rect::at(beside_wall(), Vec2i::new(4, 4));
+    let slide = move_and_slide(hitbox, Vec2i::new(6, 5), &Wall);
+
+    assert_eq!(
+        slide.moved,
+        Vec2i::new(4, 5),
+        "stops at the wall, keeps going down"
+    );
+    assert!(slide.blocked_x, "the wall blocks the horizontal motion");
+    assert!(!slide.blocked_y, "nothing blocks the vertical motion");
+}
+
+#[test]
+fn integrate_against_stops_the_blocked_axis() {
+    let mut body = Kinematics::at(beside_wall());
+    body.set_velocity(Vec2i::new(6, 5));
+
+    let slide = integrate_against(&mut body, Vec2i::new(4, 4), &Wall);
+
+    assert!(slide.blocked_x);
+    assert_eq!(body.position(), beside_wall() + Vec2i::new(4, 5));
+    assert_eq!(body.vel.x, 0, "the blocked axis loses its velocity");
+    assert_eq!(
+        body.vel.y,
+        5 * FIXED_ONE,
+        "the free axis keeps its velocity"
+    );
+}
+
+#[test]
+fn separation_takes_the_shorter_axis() {
+    let a = Rect::new(0, 0, 10, 10);
+
+    assert_eq!(separation(&a, &Rect::new(8, 2, 10, 10)), Vec2i::new(-2, 0));
+    assert_eq!(separation(&a, &Rect::new(2, 7, 10, 10)), Vec2i::new(0, -3));
+    assert_eq!(
+        separation(&a, &Rect::new(10, 0, 10, 10)),
+        Vec2i::ZERO,
+        "touching is not overlapping"
+    );
+}
+
+#[test]
+fn separate_pushes_both_bodies_clear() {
+    let size = Vec2i::new(10, 10);
+    let mut a = Kinematics::at(Vec2i::new(0, 0));
+    let mut b = Kinematics::at(Vec2i::new(8, 2));
+
+    separate(&mut a, size, &mut b, size);
+
+    assert_eq!(a.position(), Vec2i::new(-1, 0));
+    assert_eq!(b.position(), Vec2i::new(9, 2));
+    assert!(!Rect::at(a.position(), size).overlaps(&Rect::at(b.position(), size)));
+}
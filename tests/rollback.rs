@@ -0,0 +1,65 @@
+//! Rollback test: `simulate` must depend on its `SimState` and input alone.
+//!
+//! Runs every `tests/golden/<name>.script`, saving a copy of the state every
+//! `ROLLBACK_INTERVAL` ticks. At each save point the copy is restored and
+//! run forward again with the same input, which has to land on the same
+//! state hash as the first pass.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bit_bound::game::{SimState, simulate};
+use bit_bound::input::InputScript;
+
+const FRAMES: u32 = 300;
+const ROLLBACK_INTERVAL: u32 = 30;
+
+fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden")
+}
+
+#[test]
+fn resimulating_from_a_copy_is_identical() {
+    let mut scripts: Vec<PathBuf> = fs::read_dir(golden_dir())
+        .expect("tests/golden exists")
+        .map(|entry| entry.expect("readable directory entry").path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "script"))
+        .collect();
+    scripts.sort();
+
+    assert!(!scripts.is_empty(), "no scripts in tests/golden");
+
+    for path in scripts {
+        let script = InputScript::load(&path).expect("valid input script");
+        let mut sim = SimState::new();
+        let mut saved = sim;
+        let mut hashes = Vec::new();
+
+        for frame in 0..FRAMES {
+            if frame % ROLLBACK_INTERVAL == 0 {
+                saved = sim;
+                hashes.clear();
+            }
+
+            simulate(&mut sim, script.at(frame));
+            hashes.push(sim.state_hash());
+
+            if (frame + 1) % ROLLBACK_INTERVAL == 0 {
+                let start = frame + 1 - ROLLBACK_INTERVAL;
+                let mut replayed = saved;
+
+                for (offset, expected) in hashes.iter().enumerate() {
+                    simulate(&mut replayed, script.at(start + offset as u32));
+
+                    assert_eq!(
+                        replayed.state_hash(),
+                        *expected,
+                        "{}: tick {} differs after rolling back to {start}",
+                        path.display(),
+                        start + offset as u32 + 1,
+                    );
+                }
+            }
+        }
+    }
+}
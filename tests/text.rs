@@ -0,0 +1,25 @@
+//! String table checks: every string of every language has to be drawable,
+//! each character coming from the built-in font or the language's glyphs.
+
+use bit_bound::renderer::font::get_glyph;
+use bit_bound::text::{LANGUAGES, TextId};
+
+#[test]
+fn every_string_has_glyphs() {
+    for (code, language) in LANGUAGES {
+        let glyphs = language.glyphs();
+
+        for id in TextId::ALL {
+            let text = language.text(id);
+
+            assert!(!text.is_empty(), "{code}: {id:?} is empty");
+
+            for c in text.chars() {
+                let drawable = get_glyph(c).is_some()
+                    || glyphs.as_ref().is_some_and(|font| font.glyph(c).is_some());
+
+                assert!(drawable, "{code}: {id:?} uses `{c}`, which has no glyph");
+            }
+        }
+    }
+}
@@ -0,0 +1,421 @@
+//! Editor state, input handling and drawing.
+//!
+//! The screen is the game's 160×144 framebuffer: a status line at the top,
+//! the level view below it, and two rows of the tileset at the bottom.
+
+use std::fs;
+use std::path::PathBuf;
+
+use bit_bound::game::level::{Spawn, Waypoint};
+use bit_bound::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+use minifb::{Key, KeyRepeat, MouseButton, MouseMode, Window};
+
+use crate::level::LevelData;
+
+const TILE: usize = 8;
+const TILE_BYTES: usize = 16;
+
+const STATUS_HEIGHT: usize = TILE;
+const STRIP_ROWS: usize = 2;
+const STRIP_TOP: usize = HEIGHT - STRIP_ROWS * TILE;
+
+const VIEW_TOP: usize = STATUS_HEIGHT;
+const VIEW_COLUMNS: usize = WIDTH / TILE;
+const VIEW_ROWS: usize = (STRIP_TOP - VIEW_TOP) / TILE;
+
+const STRIP_COLUMNS: usize = WIDTH / TILE;
+
+/// Spawn kinds the editor cycles through. The game decides what each means.
+const SPAWN_KINDS: u8 = 8;
+
+/// A right click removes the marker closest to the cursor within this many
+/// pixels.
+const PICK_RADIUS: i32 = 4;
+
+/// Most spawns, or waypoints, a level file can hold.
+const MAX_MARKERS: usize = u16::MAX as usize;
+
+/// How long a message replaces the status line, in frames.
+const MESSAGE_FRAMES: u32 = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tool {
+    Tiles,
+    Spawns,
+    Waypoints,
+}
+
+pub struct Editor {
+    path: PathBuf,
+    level: LevelData,
+    /// 16-byte 2bpp tiles.
+    tileset: Vec<u8>,
+    tool: Tool,
+    selected: u8,
+    spawn_kind: u8,
+    /// Top-left of the view, in tiles.
+    scroll: (usize, usize),
+    /// First tileset row shown in the strip.
+    strip_row: usize,
+    dirty: bool,
+    /// Escape was pressed once with unsaved changes.
+    quit_armed: bool,
+    message: String,
+    /// Frames left before `message` gives the status line back.
+    message_timer: u32,
+    /// Mouse buttons held last frame, so a click places one marker.
+    held: (bool, bool),
+}
+
+impl Editor {
+    pub fn new(path: PathBuf, level: LevelData, tileset: Vec<u8>) -> Self {
+        Self {
+            path,
+            level,
+            tileset,
+            tool: Tool::Tiles,
+            selected: 0,
+            spawn_kind: 0,
+            scroll: (0, 0),
+            strip_row: 0,
+            dirty: false,
+            quit_armed: false,
+            message: String::new(),
+            message_timer: 0,
+            held: (false, false),
+        }
+    }
+
+    fn tile_count(&self) -> usize {
+        self.tileset.len() / TILE_BYTES
+    }
+
+    /// Handle one frame of input. Returns `false` when the editor should
+    /// close.
+    pub fn update(&mut self, window: &Window) -> bool {
+        if self.message_timer > 0 {
+            self.message_timer -= 1;
+        } else {
+            self.quit_armed = false;
+        }
+
+        if window.is_key_pressed(Key::Escape, KeyRepeat::No) {
+            if !self.dirty || self.quit_armed {
+                return false;
+            }
+
+            self.quit_armed = true;
+            self.show("UNSAVED: ESC TO QUIT");
+        }
+
+        if window.is_key_pressed(Key::S, KeyRepeat::No) {
+            self.save();
+        }
+
+        if window.is_key_pressed(Key::Key1, KeyRepeat::No) {
+            self.tool = Tool::Tiles;
+        } else if window.is_key_pressed(Key::Key2, KeyRepeat::No) {
+            self.tool = Tool::Spawns;
+        } else if window.is_key_pressed(Key::Key3, KeyRepeat::No) {
+            self.tool = Tool::Waypoints;
+        }
+
+        if window.is_key_pressed(Key::K, KeyRepeat::No) {
+            self.spawn_kind = (self.spawn_kind + 1) % SPAWN_KINDS;
+        }
+
+        if window.is_key_pressed(Key::Minus, KeyRepeat::Yes) && self.level.scroll_speed > 0 {
+            self.level.scroll_speed -= 1;
+            self.dirty = true;
+        }
+
+        if window.is_key_pressed(Key::Equal, KeyRepeat::Yes) {
+            self.level.scroll_speed = self.level.scroll_speed.saturating_add(1);
+            self.dirty = true;
+        }
+
+        self.scroll_view(window);
+
+        let left = window.get_mouse_down(MouseButton::Left);
+        let right = window.get_mouse_down(MouseButton::Right);
+        let clicked = (left && !self.held.0, right && !self.held.1);
+        self.held = (left, right);
+
+        if let Some((x, y)) = window.get_mouse_pos(MouseMode::Discard) {
+            self.mouse(x as usize, y as usize, (left, right), clicked);
+        }
+
+        true
+    }
+
+    fn scroll_view(&mut self, window: &Window) {
+        let max_x = self.level.width.saturating_sub(VIEW_COLUMNS);
+        let max_y = self.level.height.saturating_sub(VIEW_ROWS);
+        let (x, y) = &mut self.scroll;
+
+        if window.is_key_pressed(Key::Left, KeyRepeat::Yes) {
+            *x = x.saturating_sub(1);
+        }
+
+        if window.is_key_pressed(Key::Right, KeyRepeat::Yes) {
+            *x = (*x + 1).min(max_x);
+        }
+
+        if window.is_key_pressed(Key::Up, KeyRepeat::Yes) {
+            *y = y.saturating_sub(1);
+        }
+
+        if window.is_key_pressed(Key::Down, KeyRepeat::Yes) {
+            *y = (*y + 1).min(max_y);
+        }
+
+        let strip_rows = self.tile_count().div_ceil(STRIP_COLUMNS);
+
+        if window.is_key_pressed(Key::PageUp, KeyRepeat::Yes) {
+            self.strip_row = self.strip_row.saturating_sub(1);
+        }
+
+        if window.is_key_pressed(Key::PageDown, KeyRepeat::Yes) {
+            self.strip_row = (self.strip_row + 1).min(strip_rows.saturating_sub(STRIP_ROWS));
+        }
+    }
+
+    /// `held` is which buttons are down; `clicked` which went down this frame.
+    fn mouse(&mut self, x: usize, y: usize, held: (bool, bool), clicked: (bool, bool)) {
+        if y >= STRIP_TOP {
+            let index = (self.strip_row + (y - STRIP_TOP) / TILE) * STRIP_COLUMNS + x / TILE;
+
+            if held.0 && index < self.tile_count() {
+                self.selected = index as u8;
+            }
+
+            return;
+        }
+
+        if y < VIEW_TOP {
+            return;
+        }
+
+        // Level pixel under the cursor.
+        let px = self.scroll.0 * TILE + x;
+        let py = self.scroll.1 * TILE + (y - VIEW_TOP);
+
+        if px >= self.level.width * TILE || py >= self.level.height * TILE {
+            return;
+        }
+
+        let changed = match self.tool {
+            Tool::Tiles if held.0 => self.level.set_tile(px / TILE, py / TILE, self.selected),
+            Tool::Tiles if held.1 => self.level.set_tile(px / TILE, py / TILE, 0),
+            Tool::Spawns if clicked.0 && self.level.spawns.len() < MAX_MARKERS => {
+                self.level.spawns.push(Spawn {
+                    x: px as u16,
+                    y: py as u16,
+                    kind: self.spawn_kind,
+                });
+                true
+            }
+            Tool::Spawns if clicked.1 => {
+                let nearest = nearest(
+                    self.level.spawns.iter().map(|spawn| (spawn.x, spawn.y)),
+                    px,
+                    py,
+                );
+                nearest.map(|i| self.level.spawns.remove(i)).is_some()
+            }
+            Tool::Waypoints if clicked.0 && self.level.waypoints.len() < MAX_MARKERS => {
+                self.level.waypoints.push(Waypoint {
+                    x: px as u16,
+                    y: py as u16,
+                });
+                true
+            }
+            Tool::Waypoints if clicked.1 => {
+                let nearest = nearest(
+                    self.level.waypoints.iter().map(|point| (point.x, point.y)),
+                    px,
+                    py,
+                );
+                nearest.map(|i| self.level.waypoints.remove(i)).is_some()
+            }
+            _ => false,
+        };
+
+        if changed {
+            self.dirty = true;
+        }
+    }
+
+    fn show(&mut self, message: &str) {
+        self.message = message.to_string();
+        self.message_timer = MESSAGE_FRAMES;
+    }
+
+    fn save(&mut self) {
+        match fs::write(&self.path, self.level.encode()) {
+            Ok(()) => {
+                self.dirty = false;
+                self.show("SAVED");
+            }
+            Err(err) => {
+                eprintln!("leveled: {}: {err}", self.path.display());
+                self.show("SAVE FAILED");
+            }
+        }
+    }
+
+    pub fn render(&self, fb: &mut FrameBuffer) {
+        fb.clear(0);
+
+        self.render_view(fb);
+
+        // Status line, drawn over anything the view left there.
+        fb.fill_rect(0, 0, WIDTH, STATUS_HEIGHT, 3);
+
+        if self.message_timer > 0 {
+            fb.draw_text(1, 1, &self.message, 0);
+        } else {
+            let tool = match self.tool {
+                Tool::Tiles => format!("TILE {}", self.selected),
+                Tool::Spawns => format!("SPAWN {}", self.spawn_kind),
+                Tool::Waypoints => "PATH".to_string(),
+            };
+
+            let dirty = if self.dirty { " MOD" } else { "" };
+            let status = format!("{tool} SPD {}{dirty}", self.level.scroll_speed);
+
+            fb.draw_text(1, 1, &status, 0);
+        }
+
+        self.render_strip(fb);
+    }
+
+    fn render_view(&self, fb: &mut FrameBuffer) {
+        let (scroll_x, scroll_y) = self.scroll;
+        let columns = VIEW_COLUMNS.min(self.level.width - scroll_x);
+        let rows = VIEW_ROWS.min(self.level.height - scroll_y);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let tile = self.level.tile(scroll_x + column, scroll_y + row) as usize;
+
+                if let Some(data) = self.tileset.chunks_exact(TILE_BYTES).nth(tile) {
+                    fb.draw_tile((column * TILE) as i32, (VIEW_TOP + row * TILE) as i32, data);
+                }
+            }
+        }
+
+        // Outline a level that ends inside the view.
+        if columns < VIEW_COLUMNS || rows < VIEW_ROWS {
+            fb.draw_rect(0, VIEW_TOP, columns * TILE, rows * TILE, 2);
+        }
+
+        let origin = ((scroll_x * TILE) as i32, (scroll_y * TILE) as i32);
+        let to_screen =
+            |x: u16, y: u16| (x as i32 - origin.0, y as i32 - origin.1 + VIEW_TOP as i32);
+
+        let mut previous = None;
+
+        for point in &self.level.waypoints {
+            let (x, y) = to_screen(point.x, point.y);
+
+            if let Some((px, py)) = previous {
+                draw_line(fb, (px, py), (x, y), 2);
+            }
+
+            plot(fb, x, y, 3);
+            previous = Some((x, y));
+        }
+
+        for spawn in &self.level.spawns {
+            let (x, y) = to_screen(spawn.x, spawn.y);
+
+            for (dx, dy) in [
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ] {
+                plot(fb, x + dx, y + dy, 3);
+            }
+
+            if x + 2 >= 0 && y - 2 >= VIEW_TOP as i32 && y + 3 <= STRIP_TOP as i32 {
+                fb.draw_u32((x + 2) as usize, (y - 2) as usize, spawn.kind as u32, 1, 3);
+            }
+        }
+    }
+
+    fn render_strip(&self, fb: &mut FrameBuffer) {
+        fb.fill_rect(0, STRIP_TOP, WIDTH, HEIGHT - STRIP_TOP, 0);
+
+        for row in 0..STRIP_ROWS {
+            for column in 0..STRIP_COLUMNS {
+                let index = (self.strip_row + row) * STRIP_COLUMNS + column;
+
+                let Some(data) = self.tileset.chunks_exact(TILE_BYTES).nth(index) else {
+                    return;
+                };
+
+                let (x, y) = (column * TILE, STRIP_TOP + row * TILE);
+                fb.draw_tile(x as i32, y as i32, data);
+
+                if index == self.selected as usize {
+                    fb.draw_rect(x, y, TILE, TILE, 3);
+                }
+            }
+        }
+    }
+}
+
+/// Index of the point closest to (`x`, `y`) within `PICK_RADIUS`.
+fn nearest(points: impl Iterator<Item = (u16, u16)>, x: usize, y: usize) -> Option<usize> {
+    points
+        .enumerate()
+        .map(|(i, (px, py))| {
+            let dx = (px as i32 - x as i32).abs();
+            let dy = (py as i32 - y as i32).abs();
+            (i, dx.max(dy))
+        })
+        .filter(|&(_, distance)| distance <= PICK_RADIUS)
+        .min_by_key(|&(_, distance)| distance)
+        .map(|(i, _)| i)
+}
+
+/// Set a pixel if it is inside the level view.
+fn plot(fb: &mut FrameBuffer, x: i32, y: i32, color: u8) {
+    if x >= 0 && y >= VIEW_TOP as i32 && y < STRIP_TOP as i32 {
+        fb.set_pixel(x as usize, y as usize, color);
+    }
+}
+
+fn draw_line(fb: &mut FrameBuffer, from: (i32, i32), to: (i32, i32), color: u8) {
+    let (mut x, mut y) = from;
+    let dx = (to.0 - x).abs();
+    let dy = -(to.1 - y).abs();
+    let (sx, sy) = ((to.0 - x).signum(), (to.1 - y).signum());
+    let mut error = dx + dy;
+
+    loop {
+        plot(fb, x, y, color);
+
+        if (x, y) == to {
+            break;
+        }
+
+        let doubled = error * 2;
+
+        if doubled >= dy {
+            error += dy;
+            x += sx;
+        }
+
+        if doubled <= dx {
+            error += dx;
+            y += sy;
+        }
+    }
+}
@@ -0,0 +1,103 @@
+//! The level being edited, and its encoding in the format read by
+//! `bit_bound::game::level::Level`.
+
+use bit_bound::game::level::{
+    HEADER_SIZE, LEVEL_MAGIC, LEVEL_VERSION, Level, SPAWN_SIZE, Spawn, WAYPOINT_SIZE, Waypoint,
+};
+
+/// Editable copy of a level.
+pub struct LevelData {
+    pub width: usize,
+    pub height: usize,
+    /// Camera speed in 1/`SCROLL_SUBPIXELS` pixels per tick.
+    pub scroll_speed: u16,
+    /// Tileset indices, row-major.
+    pub tiles: Vec<u8>,
+    pub spawns: Vec<Spawn>,
+    pub waypoints: Vec<Waypoint>,
+}
+
+impl LevelData {
+    /// An empty level of `width`×`height` tiles, filled with tile 0.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            scroll_speed: 16,
+            tiles: vec![0; width * height],
+            spawns: Vec::new(),
+            waypoints: Vec::new(),
+        }
+    }
+
+    pub fn parse(bytes: &[u8]) -> Result<Self, String> {
+        let level = Level::new(bytes).ok_or("not a level file of this version")?;
+
+        let mut tiles = Vec::with_capacity(level.width() * level.height());
+
+        for y in 0..level.height() {
+            for x in 0..level.width() {
+                tiles.push(level.tile(x, y));
+            }
+        }
+
+        Ok(Self {
+            width: level.width(),
+            height: level.height(),
+            scroll_speed: level.scroll_speed(),
+            tiles,
+            spawns: (0..level.spawn_count()).map(|i| level.spawn(i)).collect(),
+            waypoints: (0..level.waypoint_count())
+                .map(|i| level.waypoint(i))
+                .collect(),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            HEADER_SIZE
+                + self.tiles.len()
+                + self.spawns.len() * SPAWN_SIZE
+                + self.waypoints.len() * WAYPOINT_SIZE,
+        );
+
+        out.extend_from_slice(&LEVEL_MAGIC);
+
+        for value in [
+            LEVEL_VERSION,
+            self.width as u16,
+            self.height as u16,
+            self.scroll_speed,
+            self.spawns.len() as u16,
+            self.waypoints.len() as u16,
+        ] {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+
+        out.extend_from_slice(&self.tiles);
+
+        for spawn in &self.spawns {
+            out.extend_from_slice(&spawn.x.to_le_bytes());
+            out.extend_from_slice(&spawn.y.to_le_bytes());
+            out.extend_from_slice(&[spawn.kind, 0]);
+        }
+
+        for waypoint in &self.waypoints {
+            out.extend_from_slice(&waypoint.x.to_le_bytes());
+            out.extend_from_slice(&waypoint.y.to_le_bytes());
+        }
+
+        out
+    }
+
+    pub fn tile(&self, x: usize, y: usize) -> u8 {
+        self.tiles[y * self.width + x]
+    }
+
+    pub fn set_tile(&mut self, x: usize, y: usize, tile: u8) -> bool {
+        let cell = &mut self.tiles[y * self.width + x];
+        let changed = *cell != tile;
+        *cell = tile;
+        changed
+    }
+}
@@ -0,0 +1,195 @@
+//! `leveled`: a minimal level editor. Paints tiles from a tileset, places
+//! spawn markers and waypoints, sets the scroll speed, and saves the level
+//! in the format `bit_bound::game::level::Level` reads.
+
+mod editor;
+mod level;
+
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use bit_bound::renderer::framebuffer::{FrameBuffer, HEIGHT, WIDTH};
+use minifb::{Window, WindowOptions};
+
+use crate::editor::Editor;
+use crate::level::LevelData;
+
+/// Tileset indices are one byte.
+const MAX_TILES: usize = 256;
+
+/// Size of a new level in tiles: two screens wide, one screen high.
+const DEFAULT_SIZE: (usize, usize) = (40, 18);
+
+const USAGE: &str = "\
+usage: leveled --tiles <file> [--size WxH] <level>
+
+Opens <level>, or creates it when it does not exist.
+
+  --tiles <file>   tileset to paint with: a .tiles file from `spritec --tilemap`
+  --size <WxH>     size of a new level in tiles (default: 40x18)
+  -h, --help       print this help
+
+Controls:
+  1 / 2 / 3        paint tiles / place spawns / place waypoints
+  left click       paint the selected tile, or add a marker
+  right click      erase a tile to tile 0, or remove the nearest marker
+  tileset click    select a tile; PageUp/PageDown scroll the tileset
+  arrows           scroll the view
+  K                next spawn kind
+  - / =            scroll speed down / up (in 1/16 pixel per tick)
+  S                save
+  Escape           quit (twice with unsaved changes)";
+
+struct Options {
+    level: PathBuf,
+    tiles: PathBuf,
+    size: Option<(usize, usize)>,
+}
+
+fn parse_args() -> Result<Option<Options>, String> {
+    let mut args = std::env::args().skip(1);
+    let mut level = None;
+    let mut tiles = None;
+    let mut size = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("`{arg}` needs a value"));
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--tiles" => tiles = Some(PathBuf::from(value()?)),
+            "--size" => {
+                let text = value()?;
+                let parsed = text
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h): &(usize, usize)| {
+                        (1..=u16::MAX as usize).contains(&w) && (1..=u16::MAX as usize).contains(&h)
+                    })
+                    .ok_or(format!("invalid size `{text}`"))?;
+                size = Some(parsed);
+            }
+            _ if arg.starts_with('-') => return Err(format!("unknown argument `{arg}`")),
+            _ if level.is_none() => level = Some(PathBuf::from(arg)),
+            _ => return Err(format!("unexpected argument `{arg}`")),
+        }
+    }
+
+    Ok(Some(Options {
+        level: level.ok_or("missing level file")?,
+        tiles: tiles.ok_or("missing `--tiles`")?,
+        size,
+    }))
+}
+
+fn load_tileset(options: &Options) -> Result<Vec<u8>, String> {
+    let path = &options.tiles;
+    let tiles = fs::read(path).map_err(|err| format!("{}: {err}", path.display()))?;
+
+    if tiles.is_empty() || !tiles.len().is_multiple_of(16) {
+        return Err(format!(
+            "{}: {} bytes is not a whole number of tiles",
+            path.display(),
+            tiles.len()
+        ));
+    }
+
+    if tiles.len() / 16 > MAX_TILES {
+        return Err(format!(
+            "{}: {} tiles, but a level can use at most {MAX_TILES}",
+            path.display(),
+            tiles.len() / 16
+        ));
+    }
+
+    Ok(tiles)
+}
+
+fn load_level(options: &Options) -> Result<LevelData, String> {
+    let path = &options.level;
+
+    match fs::read(path) {
+        Ok(bytes) => {
+            let level =
+                LevelData::parse(&bytes).map_err(|err| format!("{}: {err}", path.display()))?;
+
+            if options
+                .size
+                .is_some_and(|size| size != (level.width, level.height))
+            {
+                eprintln!(
+                    "leveled: {} already exists; ignoring --size",
+                    path.display()
+                );
+            }
+
+            Ok(level)
+        }
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+            let (width, height) = options.size.unwrap_or(DEFAULT_SIZE);
+            Ok(LevelData::new(width, height))
+        }
+        Err(err) => Err(format!("{}: {err}", path.display())),
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("leveled: {err}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let loaded = load_tileset(&options).and_then(|tiles| Ok((tiles, load_level(&options)?)));
+
+    let (tileset, level) = match loaded {
+        Ok(loaded) => loaded,
+        Err(err) => {
+            eprintln!("leveled: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let title = format!("leveled - {}", options.level.display());
+    let mut window = match Window::new(
+        &title,
+        WIDTH,
+        HEIGHT,
+        WindowOptions {
+            resize: false,
+            scale: minifb::Scale::X4,
+            ..WindowOptions::default()
+        },
+    ) {
+        Ok(window) => window,
+        Err(err) => {
+            eprintln!("leveled: cannot open a window: {err}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    window.set_target_fps(60);
+
+    let mut editor = Editor::new(options.level, level, tileset);
+    let mut framebuffer = Box::new(FrameBuffer::new());
+    let mut screen = vec![0u32; WIDTH * HEIGHT];
+
+    while window.is_open() && editor.update(&window) {
+        editor.render(&mut framebuffer);
+        framebuffer.to_rgba_buffer(&mut screen);
+
+        if let Err(err) = window.update_with_buffer(&screen, WIDTH, HEIGHT) {
+            eprintln!("leveled: {err}");
+            return ExitCode::FAILURE;
+        }
+    }
+
+    ExitCode::SUCCESS
+}
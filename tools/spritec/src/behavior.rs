@@ -0,0 +1,234 @@
+//! Enemy behavior: `.script` text compiled into the `.behavior` byte code
+//! run by `game::behavior`. Both are described in
+//! `docs/behavior_format.md`.
+//!
+//! ```text
+//! move -2 0
+//! wait 20
+//! top:
+//! repeat 3
+//!   move 0 1
+//!   wait 8
+//!   fire 0
+//! end
+//! if hp < 2 goto flee
+//! goto top
+//! flee:
+//! move -4 0
+//! ```
+//!
+//! Everything a script can get wrong, like a jump to a missing label or
+//! loops nested deeper than the VM keeps, is reported here with its line.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+pub const BEHAVIOR_MAGIC: &[u8; 4] = b"BBBH";
+pub const BEHAVIOR_VERSION: u16 = 1;
+
+const HEADER_SIZE: usize = 8;
+
+/// `repeat` blocks the VM keeps count of at once.
+pub const MAX_LOOP_DEPTH: usize = 2;
+
+/// Largest program, so every address fits a `u16` past the header.
+const MAX_CODE: usize = u16::MAX as usize - HEADER_SIZE;
+
+const OP_STOP: u8 = 0;
+const OP_MOVE: u8 = 1;
+const OP_WAIT: u8 = 2;
+const OP_FIRE: u8 = 3;
+const OP_JUMP: u8 = 4;
+const OP_REPEAT: u8 = 5;
+const OP_NEXT: u8 = 6;
+const OP_IF_HP_BELOW: u8 = 7;
+
+/// Fastest a script may move an enemy, in pixels per tick on each axis.
+const MAX_SPEED: i64 = 8;
+
+/// A compiled script.
+pub struct Behavior {
+    code: Vec<u8>,
+    instructions: usize,
+}
+
+/// A jump waiting for its label's address.
+struct Fixup {
+    /// Where the address goes in `code`.
+    at: usize,
+    label: String,
+    line: usize,
+}
+
+impl Behavior {
+    /// Parse and compile `.script` text; `path` is only used in errors.
+    pub fn parse(path: &Path, text: &str) -> Result<Self> {
+        let mut code = Vec::new();
+        let mut instructions = 0;
+        let mut labels: HashMap<String, usize> = HashMap::new();
+        let mut fixups = Vec::new();
+        // Body start and line of each open `repeat`.
+        let mut open: Vec<(usize, usize)> = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let error = |message: String| config(path, number, message);
+            let line = line.split('#').next().unwrap_or("").trim();
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(label) = line.strip_suffix(':') {
+                let valid = label.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+                if !valid {
+                    return Err(error(format!("`{label}` is not a label name")));
+                }
+
+                // Jumps leave every open loop, so they may not land in one.
+                if !open.is_empty() {
+                    return Err(error(format!("label `{label}` is inside `repeat`")));
+                }
+
+                if labels.insert(label.to_string(), code.len()).is_some() {
+                    return Err(error(format!("label `{label}` is defined twice")));
+                }
+
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let number_at = |at: usize, min: i64, max: i64| -> Result<i64> {
+                let word = words
+                    .get(at)
+                    .ok_or_else(|| error("missing value".to_string()))?;
+                word.parse()
+                    .ok()
+                    .filter(|value| (min..=max).contains(value))
+                    .ok_or_else(|| error(format!("`{word}` is not a number from {min} to {max}")))
+            };
+            let mut jump = |code: &mut Vec<u8>, label: &str| {
+                fixups.push(Fixup {
+                    at: code.len(),
+                    label: label.to_string(),
+                    line: number,
+                });
+                code.extend_from_slice(&[0, 0]);
+            };
+
+            instructions += 1;
+
+            match words.as_slice() {
+                ["move", _, _] => {
+                    let dx = number_at(1, -MAX_SPEED, MAX_SPEED)? as i8;
+                    let dy = number_at(2, -MAX_SPEED, MAX_SPEED)? as i8;
+                    code.extend_from_slice(&[OP_MOVE, dx as u8, dy as u8]);
+                }
+                ["wait", _] => {
+                    let ticks = number_at(1, 1, u16::MAX as i64)? as u16;
+                    code.push(OP_WAIT);
+                    code.extend_from_slice(&ticks.to_le_bytes());
+                }
+                ["fire", _] => {
+                    let pattern = number_at(1, 0, u8::MAX as i64)? as u8;
+                    code.extend_from_slice(&[OP_FIRE, pattern]);
+                }
+                ["goto", label] => {
+                    code.push(OP_JUMP);
+                    jump(&mut code, label);
+                }
+                ["repeat", _] => {
+                    let count = number_at(1, 2, u8::MAX as i64)? as u8;
+
+                    if open.len() == MAX_LOOP_DEPTH {
+                        return Err(error(format!(
+                            "`repeat` nested more than {MAX_LOOP_DEPTH} deep"
+                        )));
+                    }
+
+                    code.extend_from_slice(&[OP_REPEAT, count]);
+                    open.push((code.len(), number));
+                }
+                ["end"] => {
+                    let (body, _) = open
+                        .pop()
+                        .ok_or_else(|| error("`end` without `repeat`".to_string()))?;
+
+                    code.push(OP_NEXT);
+                    code.extend_from_slice(&(body as u16).to_le_bytes());
+                }
+                ["if", "hp", "<", _, "goto", label] => {
+                    let hp = number_at(3, 1, u8::MAX as i64)? as u8;
+                    code.extend_from_slice(&[OP_IF_HP_BELOW, hp]);
+                    jump(&mut code, label);
+                }
+                ["stop"] => code.push(OP_STOP),
+                _ => return Err(error(format!("unknown instruction `{line}`"))),
+            }
+
+            if code.len() > MAX_CODE {
+                return Err(Error::Limit {
+                    path: path.to_path_buf(),
+                    message: format!("the script is over {MAX_CODE} bytes"),
+                });
+            }
+        }
+
+        if let Some(&(_, line)) = open.last() {
+            return Err(config(path, line, "`repeat` without `end`".to_string()));
+        }
+
+        if instructions == 0 {
+            return Err(Error::Limit {
+                path: path.to_path_buf(),
+                message: "the script has no instructions".to_string(),
+            });
+        }
+
+        for fixup in fixups {
+            let target = *labels
+                .get(&fixup.label)
+                .ok_or_else(|| config(path, fixup.line, format!("no label `{}`", fixup.label)))?;
+
+            code[fixup.at..][..2].copy_from_slice(&(target as u16).to_le_bytes());
+        }
+
+        // Running off the end stops, like `stop`.
+        code.push(OP_STOP);
+
+        Ok(Self { code, instructions })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_SIZE + self.code.len());
+
+        out.extend_from_slice(BEHAVIOR_MAGIC);
+        out.extend_from_slice(&BEHAVIOR_VERSION.to_le_bytes());
+        out.extend_from_slice(&(self.code.len() as u16).to_le_bytes());
+        out.extend_from_slice(&self.code);
+        out
+    }
+
+    pub fn instruction_count(&self) -> usize {
+        self.instructions
+    }
+}
+
+/// `.script` files are UTF-8 text.
+pub fn read_text(path: &Path, bytes: &[u8]) -> Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| Error::Config {
+        path: path.to_path_buf(),
+        message: "not UTF-8 text".to_string(),
+    })
+}
+
+fn config(path: &Path, line: usize, message: String) -> Error {
+    Error::Config {
+        path: path.to_path_buf(),
+        message: format!("line {line}: {message}"),
+    }
+}
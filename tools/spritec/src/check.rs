@@ -0,0 +1,190 @@
+//! `spritec check`: lints sources against project constraints without
+//! writing anything, for pre-commit hooks and CI.
+//!
+//! Every source is checked completely, so one run reports every violation:
+//! each pixel outside the palette (up to `MAX_PIXEL_REPORTS` per file),
+//! sizes that are not tile-aligned (with `--aligned`) or exceed
+//! `--max-size`, whatever the current mode's converter rejects (sheet grids,
+//! tilemap and font limits), and directories whose sources together exceed
+//! `--tile-budget`.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::behavior::{self, Behavior};
+use crate::convert;
+use crate::error::{Error, Result};
+use crate::font;
+use crate::music::{self, Song};
+use crate::palette::Palette;
+use crate::pipeline::{Source, collect_sources};
+use crate::sheet::Sheet;
+use crate::tilemap::Tilemap;
+use crate::{Mode, Options};
+
+/// Unknown-color pixels listed per file; the rest are only counted.
+const MAX_PIXEL_REPORTS: usize = 16;
+
+/// What the project allows, from the command line.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Limits {
+    /// Require sprite and tilemap sizes to be multiples of 8 instead of
+    /// letting the converter pad them.
+    pub aligned: bool,
+    /// Largest source image, in pixels.
+    pub max_size: Option<(usize, usize)>,
+    /// Most tiles the sources of one directory (one level) may add up to.
+    pub tile_budget: Option<usize>,
+}
+
+/// Outcome of a check run.
+pub struct Report {
+    pub files: usize,
+    pub violations: Vec<Error>,
+}
+
+pub fn run(options: &Options) -> Report {
+    let mut report = Report {
+        files: 0,
+        violations: Vec::new(),
+    };
+
+    let palette = match &options.palette {
+        Some(path) => Palette::load(path, options.palette_name.as_deref()),
+        None => Ok(Palette::default()),
+    };
+
+    let sources = palette.and_then(|palette| {
+        collect_sources(&options.input, options.recursive, options.mode)
+            .map(|sources| (palette, sources))
+    });
+
+    let (palette, sources) = match sources {
+        Ok(found) => found,
+        Err(err) => {
+            report.violations.push(err);
+            return report;
+        }
+    };
+
+    // Tiles per directory, relative to the input.
+    let mut levels: BTreeMap<PathBuf, usize> = BTreeMap::new();
+
+    for source in &sources {
+        report.files += 1;
+
+        match check_source(source, &palette, options, &mut report.violations) {
+            Ok(tiles) => {
+                let level = source.stem.parent().unwrap_or(Path::new("")).to_path_buf();
+                *levels.entry(level).or_default() += tiles;
+            }
+            Err(err) => report.violations.push(err),
+        }
+    }
+
+    if let Some(budget) = options.limits.tile_budget {
+        for (level, tiles) in levels {
+            if tiles > budget {
+                let path = if level.as_os_str().is_empty() {
+                    options.input.clone()
+                } else {
+                    options.input.join(level)
+                };
+
+                report.violations.push(Error::Limit {
+                    path,
+                    message: format!("{tiles} tiles, over the budget of {budget}"),
+                });
+            }
+        }
+    }
+
+    report
+}
+
+/// Check one source, pushing what is wrong with it to `violations`. Returns
+/// the number of tiles it adds to its level. `Err` means it could not be
+/// checked at all.
+fn check_source(
+    source: &Source,
+    palette: &Palette,
+    options: &Options,
+    violations: &mut Vec<Error>,
+) -> Result<usize> {
+    let path = &source.path;
+    let bytes = fs::read(path).map_err(|err| Error::io(path, err))?;
+
+    if options.mode == Mode::Music {
+        Song::parse(path, &music::read_text(path, &bytes)?)?;
+        return Ok(0);
+    }
+
+    if options.mode == Mode::Behavior {
+        Behavior::parse(path, &behavior::read_text(path, &bytes)?)?;
+        return Ok(0);
+    }
+
+    let img = convert::decode_rgba(path, &bytes)?;
+    let (width, height) = (img.width() as usize, img.height() as usize);
+    let limit = |message: String| Error::Limit {
+        path: path.clone(),
+        message,
+    };
+
+    let mut unknown = 0;
+
+    let image = convert::to_indexed(&img, palette, |x, y, rgba| {
+        if unknown < MAX_PIXEL_REPORTS {
+            violations.push(Error::Color {
+                path: path.clone(),
+                x,
+                y,
+                rgba,
+            });
+        }
+
+        unknown += 1;
+    });
+
+    if unknown > MAX_PIXEL_REPORTS {
+        violations.push(limit(format!(
+            "{} more pixels have colors outside the palette",
+            unknown - MAX_PIXEL_REPORTS
+        )));
+    }
+
+    // Glyph cells have their own size; everything else is drawn in tiles.
+    if options.limits.aligned
+        && options.mode != Mode::Font
+        && !(width.is_multiple_of(8) && height.is_multiple_of(8))
+    {
+        violations.push(limit(format!(
+            "{width}x{height} is not a multiple of 8 (would be padded to {}x{})",
+            image.width, image.height
+        )));
+    }
+
+    if let Some((max_width, max_height)) = options.limits.max_size
+        && (width > max_width || height > max_height)
+    {
+        violations.push(limit(format!(
+            "{width}x{height} is larger than {max_width}x{max_height}"
+        )));
+    }
+
+    let tiles = match options.mode {
+        Mode::Sprites => match Sheet::load(path)? {
+            Some(sheet) => sheet.encode(path, &image)?.0.len() / 16,
+            None => image.tiles_x() * image.tiles_y(),
+        },
+        Mode::Tilemap => Tilemap::build(path, &image)?.tiles.len(),
+        Mode::Font => {
+            font::encode(path, &image, &options.font)?;
+            0
+        }
+        Mode::Music | Mode::Behavior => unreachable!("text sources are checked above"),
+    };
+
+    Ok(tiles)
+}
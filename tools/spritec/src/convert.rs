@@ -0,0 +1,127 @@
+use std::path::Path;
+
+use image::RgbaImage;
+
+use crate::error::{Error, Result};
+use crate::palette::Palette;
+
+/// A PNG reduced to palette indices, padded to whole 8×8 tiles.
+pub struct IndexedImage {
+    pub width: usize,
+    pub height: usize,
+    /// One index per pixel, `width * height`, row-major.
+    pub pixels: Vec<u8>,
+}
+
+impl IndexedImage {
+    pub fn tiles_x(&self) -> usize {
+        self.width / 8
+    }
+
+    pub fn tiles_y(&self) -> usize {
+        self.height / 8
+    }
+}
+
+/// Decode PNG `bytes` read from `path`.
+pub fn decode_rgba(path: &Path, bytes: &[u8]) -> Result<RgbaImage> {
+    image::load_from_memory(bytes)
+        .map(|img| img.to_rgba8())
+        .map_err(|source| Error::Image {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+/// Map every pixel of `img` through `palette`, padding width and height to
+/// the next multiple of 8 with index 0. Pixels whose color is not in the
+/// palette are passed to `unknown` and become index 0.
+pub fn to_indexed(
+    img: &RgbaImage,
+    palette: &Palette,
+    mut unknown: impl FnMut(u32, u32, [u8; 4]),
+) -> IndexedImage {
+    let (width, height) = img.dimensions();
+    let padded_width = (width as usize).next_multiple_of(8);
+    let padded_height = (height as usize).next_multiple_of(8);
+    let mut pixels = vec![0; padded_width * padded_height];
+
+    for (x, y, px) in img.enumerate_pixels() {
+        let index = palette.index_of(px.0).unwrap_or_else(|| {
+            unknown(x, y, px.0);
+            0
+        });
+
+        pixels[y as usize * padded_width + x as usize] = index;
+    }
+
+    IndexedImage {
+        width: padded_width,
+        height: padded_height,
+        pixels,
+    }
+}
+
+/// Decode PNG `bytes` read from `path` and map it through `palette`. Fails
+/// on the first pixel whose color is not in the palette.
+pub fn decode_indexed(path: &Path, bytes: &[u8], palette: &Palette) -> Result<IndexedImage> {
+    let img = decode_rgba(path, bytes)?;
+    let mut first_unknown = None;
+
+    let image = to_indexed(&img, palette, |x, y, rgba| {
+        first_unknown.get_or_insert(Error::Color {
+            path: path.to_path_buf(),
+            x,
+            y,
+            rgba,
+        });
+    });
+
+    match first_unknown {
+        Some(err) => Err(err),
+        None => Ok(image),
+    }
+}
+
+/// Encode one 8×8 tile at tile coordinates (`tx`, `ty`) as 16 bytes of 2bpp
+/// planar data.
+pub fn encode_tile(image: &IndexedImage, tx: usize, ty: usize) -> [u8; 16] {
+    let mut tile = [0u8; 16];
+
+    for row in 0..8 {
+        let py = ty * 8 + row;
+        let mut low_byte: u8 = 0;
+        let mut high_byte: u8 = 0;
+
+        for col in 0..8 {
+            let index = image.pixels[py * image.width + tx * 8 + col];
+            let bit = 7 - col;
+
+            if index & 1 != 0 {
+                low_byte |= 1 << bit;
+            }
+
+            if index & 2 != 0 {
+                high_byte |= 1 << bit;
+            }
+        }
+
+        tile[row * 2] = low_byte;
+        tile[row * 2 + 1] = high_byte;
+    }
+
+    tile
+}
+
+/// Encode the whole image as 2bpp tiles in row-major tile order.
+pub fn encode_2bpp(image: &IndexedImage) -> Vec<u8> {
+    let mut output = Vec::with_capacity(image.tiles_x() * image.tiles_y() * 16);
+
+    for ty in 0..image.tiles_y() {
+        for tx in 0..image.tiles_x() {
+            output.extend_from_slice(&encode_tile(image, tx, ty));
+        }
+    }
+
+    output
+}
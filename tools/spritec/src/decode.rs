@@ -0,0 +1,284 @@
+//! `spritec decode`: renders converted binaries back to PNG through a
+//! palette, to audit the pipeline's output and spot corrupted data.
+//!
+//! - `<name>.2bpp` becomes `<name>.png`. With a `<name>.anim.json` manifest
+//!   next to it the frames are laid out left to right; otherwise the tiles
+//!   are drawn in rows `--width` pixels wide.
+//! - `<name>.tiles` becomes `<name>.tiles.png`, drawn the same way.
+//! - `<name>.tilemap.json` becomes `<name>.map.png`: the whole map, with
+//!   flipped cells flipped.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use image::RgbaImage;
+use serde::Deserialize;
+
+use crate::Options;
+use crate::convert::IndexedImage;
+use crate::error::{Error, Result};
+use crate::palette::Palette;
+use crate::pipeline::{Source, collect_files};
+use crate::tilemap::{ATTR_FLIP_X, ATTR_FLIP_Y};
+
+/// Width of the tile grid when nothing says how wide the image is.
+pub const DEFAULT_WIDTH: usize = 128;
+
+pub const TILE_BYTES: usize = 16;
+
+#[derive(Deserialize)]
+struct AnimManifest {
+    frame_width: usize,
+    frame_height: usize,
+    frame_count: usize,
+    frame_bytes: usize,
+}
+
+/// The `.tilemap.json` sidecar written by `--tilemap`.
+#[derive(Deserialize)]
+pub struct TilemapManifest {
+    pub width: usize,
+    pub height: usize,
+    pub tiles: String,
+    pub map: String,
+}
+
+/// Outcome of a decode run.
+pub struct Report {
+    pub decoded: usize,
+    pub errors: Vec<Error>,
+}
+
+fn decodable(path: &Path) -> bool {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+
+    name.ends_with(".2bpp") || name.ends_with(".tiles") || name.ends_with(".tilemap.json")
+}
+
+pub fn run(options: &Options) -> Report {
+    let mut report = Report {
+        decoded: 0,
+        errors: Vec::new(),
+    };
+
+    let palette = match &options.palette {
+        Some(path) => Palette::load(path, options.palette_name.as_deref()),
+        None => Ok(Palette::default()),
+    };
+
+    let found = palette.and_then(|palette| {
+        collect_files(&options.input, options.recursive, decodable).map(|files| (palette, files))
+    });
+
+    let (palette, files) = match found {
+        Ok(found) => found,
+        Err(err) => {
+            report.errors.push(err);
+            return report;
+        }
+    };
+
+    for file in &files {
+        match decode_file(file, &palette, options) {
+            Ok(out_path) => {
+                println!("{} -> {}", file.path.display(), out_path.display());
+                report.decoded += 1;
+            }
+            Err(err) => report.errors.push(err),
+        }
+    }
+
+    report
+}
+
+/// Decode one file and write its PNG. Returns the PNG's path.
+fn decode_file(file: &Source, palette: &Palette, options: &Options) -> Result<PathBuf> {
+    let path = &file.path;
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let width = options.decode_width.unwrap_or(DEFAULT_WIDTH);
+
+    let (image, out_name) = if name.ends_with(".tilemap.json") {
+        (decode_tilemap(path)?, file.stem.with_extension("map.png"))
+    } else {
+        let data = fs::read(path).map_err(|err| Error::io(path, err))?;
+        let manifest = path.with_extension("anim.json");
+
+        let image = if name.ends_with(".2bpp") && manifest.exists() {
+            decode_frames(&manifest, &data)?
+        } else {
+            decode_tiles(path, &data, width)?
+        };
+
+        let out_name = if name.ends_with(".tiles") {
+            file.stem.with_extension("tiles.png")
+        } else {
+            file.stem.with_extension("png")
+        };
+
+        (image, out_name)
+    };
+
+    let out_path = options.output.join(out_name);
+    write_png(&out_path, &image, palette)?;
+
+    Ok(out_path)
+}
+
+/// Draw the 16-byte 2bpp `tile` with its top-left corner at pixel
+/// (`left`, `top`).
+fn blit(image: &mut IndexedImage, tile: &[u8], left: usize, top: usize, attributes: u8) {
+    for row in 0..8 {
+        let low = tile[row * 2];
+        let high = tile[row * 2 + 1];
+        let y = if attributes & ATTR_FLIP_Y != 0 {
+            7 - row
+        } else {
+            row
+        };
+
+        for col in 0..8 {
+            let bit = 7 - col;
+            let index = ((low >> bit) & 1) | (((high >> bit) & 1) << 1);
+            let x = if attributes & ATTR_FLIP_X != 0 {
+                7 - col
+            } else {
+                col
+            };
+
+            image.pixels[(top + y) * image.width + left + x] = index;
+        }
+    }
+}
+
+fn blank(width: usize, height: usize) -> IndexedImage {
+    IndexedImage {
+        width,
+        height,
+        pixels: vec![0; width * height],
+    }
+}
+
+fn whole_tiles(path: &Path, data: &[u8]) -> Result<()> {
+    if data.len().is_multiple_of(TILE_BYTES) {
+        return Ok(());
+    }
+
+    Err(Error::Limit {
+        path: path.to_path_buf(),
+        message: format!("{} bytes is not a whole number of tiles", data.len()),
+    })
+}
+
+/// Tiles in row-major order, `width` pixels (rounded up to a tile) per row.
+fn decode_tiles(path: &Path, data: &[u8], width: usize) -> Result<IndexedImage> {
+    whole_tiles(path, data)?;
+
+    let tiles_x = width.div_ceil(8).max(1);
+    let count = data.len() / TILE_BYTES;
+    let mut image = blank(tiles_x * 8, count.div_ceil(tiles_x).max(1) * 8);
+
+    for (i, tile) in data.chunks_exact(TILE_BYTES).enumerate() {
+        blit(&mut image, tile, (i % tiles_x) * 8, (i / tiles_x) * 8, 0);
+    }
+
+    Ok(image)
+}
+
+/// Sheet frames side by side, as described by their `.anim.json`.
+fn decode_frames(manifest_path: &Path, data: &[u8]) -> Result<IndexedImage> {
+    let text = fs::read(manifest_path).map_err(|err| Error::io(manifest_path, err))?;
+    let manifest: AnimManifest = serde_json::from_slice(&text).map_err(|err| Error::Config {
+        path: manifest_path.to_path_buf(),
+        message: err.to_string(),
+    })?;
+
+    let tiles_x = manifest.frame_width / 8;
+    let tiles_y = manifest.frame_height / 8;
+
+    if manifest.frame_bytes != tiles_x * tiles_y * TILE_BYTES
+        || data.len() < manifest.frame_count * manifest.frame_bytes
+    {
+        return Err(Error::Config {
+            path: manifest_path.to_path_buf(),
+            message: "frame layout does not match the tile data".to_string(),
+        });
+    }
+
+    let mut image = blank(
+        manifest.frame_width * manifest.frame_count,
+        manifest.frame_height,
+    );
+
+    for frame in 0..manifest.frame_count {
+        let frame_data = &data[frame * manifest.frame_bytes..][..manifest.frame_bytes];
+
+        for (i, tile) in frame_data.chunks_exact(TILE_BYTES).enumerate() {
+            let left = frame * manifest.frame_width + (i % tiles_x) * 8;
+            blit(&mut image, tile, left, (i / tiles_x) * 8, 0);
+        }
+    }
+
+    Ok(image)
+}
+
+/// The whole map described by a `.tilemap.json` sidecar.
+fn decode_tilemap(manifest_path: &Path) -> Result<IndexedImage> {
+    let config_error = |message: String| Error::Config {
+        path: manifest_path.to_path_buf(),
+        message,
+    };
+
+    let text = fs::read(manifest_path).map_err(|err| Error::io(manifest_path, err))?;
+    let manifest: TilemapManifest =
+        serde_json::from_slice(&text).map_err(|err| config_error(err.to_string()))?;
+
+    let dir = manifest_path.parent().unwrap_or(Path::new(""));
+    let tiles_path = dir.join(&manifest.tiles);
+    let map_path = dir.join(&manifest.map);
+    let tiles = fs::read(&tiles_path).map_err(|err| Error::io(&tiles_path, err))?;
+    let map = fs::read(&map_path).map_err(|err| Error::io(&map_path, err))?;
+
+    whole_tiles(&tiles_path, &tiles)?;
+
+    if map.len() != manifest.width * manifest.height * 2 {
+        return Err(config_error(format!(
+            "{} has {} bytes, expected {} for {}x{} cells",
+            manifest.map,
+            map.len(),
+            manifest.width * manifest.height * 2,
+            manifest.width,
+            manifest.height
+        )));
+    }
+
+    let mut image = blank(manifest.width * 8, manifest.height * 8);
+
+    for (cell, entry) in map.chunks_exact(2).enumerate() {
+        let (index, attributes) = (entry[0] as usize, entry[1]);
+        let tile = tiles
+            .chunks_exact(TILE_BYTES)
+            .nth(index)
+            .ok_or_else(|| config_error(format!("cell {cell} uses missing tile {index}")))?;
+
+        let (x, y) = (cell % manifest.width, cell / manifest.width);
+        blit(&mut image, tile, x * 8, y * 8, attributes);
+    }
+
+    Ok(image)
+}
+
+fn write_png(path: &Path, image: &IndexedImage, palette: &Palette) -> Result<()> {
+    let colors: Vec<[u8; 4]> = (0..4).map(|index| palette.color_of(index)).collect();
+    let rgba = RgbaImage::from_fn(image.width as u32, image.height as u32, |x, y| {
+        image::Rgba(colors[image.pixels[y as usize * image.width + x as usize] as usize])
+    });
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| Error::io(parent, err))?;
+    }
+
+    rgba.save(path).map_err(|source| Error::Image {
+        path: path.to_path_buf(),
+        source,
+    })
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong while converting assets.
+#[derive(Debug)]
+pub enum Error {
+    /// Bad command line. Reported with the usage text.
+    Usage(String),
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Image {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    /// A pixel whose color is not in the palette.
+    Color {
+        path: PathBuf,
+        x: u32,
+        y: u32,
+        rgba: [u8; 4],
+    },
+    /// An asset that does not fit a format limit.
+    Limit {
+        path: PathBuf,
+        message: String,
+    },
+    /// A malformed or inconsistent definition file: a palette, or a sprite
+    /// sheet sidecar.
+    Config {
+        path: PathBuf,
+        message: String,
+    },
+}
+
+impl Error {
+    pub fn io(path: &Path, source: io::Error) -> Self {
+        Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Usage(message) => write!(f, "{message}"),
+            Self::Io { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Image { path, source } => write!(f, "{}: {source}", path.display()),
+            Self::Color { path, x, y, rgba } => write!(
+                f,
+                "{}: pixel ({x}, {y}) has color #{:02x}{:02x}{:02x}{:02x}, which is not in the palette",
+                path.display(),
+                rgba[0],
+                rgba[1],
+                rgba[2],
+                rgba[3]
+            ),
+            Self::Limit { path, message } => write!(f, "{}: {message}", path.display()),
+            Self::Config { path, message } => write!(f, "{}: {message}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+pub type Result<T> = std::result::Result<T, Error>;
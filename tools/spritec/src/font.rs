@@ -0,0 +1,148 @@
+//! Font conversion: slices a PNG of glyphs on a fixed grid into the runtime's
+//! variable-width `Font` format (see `docs/sprite_format.md`).
+
+use std::path::Path;
+
+use crate::convert::IndexedImage;
+use crate::error::{Error, Result};
+
+pub const FONT_MAGIC: &[u8; 4] = b"BBFN";
+pub const FONT_VERSION: u8 = 1;
+
+/// Largest glyph cell the runtime's `Glyph` rows can hold.
+pub const MAX_GLYPH_WIDTH: usize = 8;
+pub const MAX_GLYPH_HEIGHT: usize = 10;
+
+/// How the glyph grid is laid out.
+#[derive(Debug, Clone)]
+pub struct FontLayout {
+    pub cell_width: usize,
+    pub cell_height: usize,
+    /// Character of the top-left cell; the rest follow in code point order,
+    /// left to right, then top to bottom.
+    pub first: u8,
+    /// The character of each cell in that order instead, for a set that is
+    /// not one run of codes, such as the accented letters of a language.
+    pub chars: Option<Vec<char>>,
+}
+
+impl FontLayout {
+    /// Parse a `--glyph-size` value such as `4x6`.
+    pub fn parse_size(text: &str) -> Option<(usize, usize)> {
+        let (width, height) = text.split_once('x')?;
+        let (width, height) = (width.parse().ok()?, height.parse().ok()?);
+
+        ((1..=MAX_GLYPH_WIDTH).contains(&width) && (1..=MAX_GLYPH_HEIGHT).contains(&height))
+            .then_some((width, height))
+    }
+}
+
+/// Encode every glyph in `image`. A glyph's width runs to its rightmost inked
+/// column; an empty cell (a space) is half the cell wide. Trailing empty
+/// cells are dropped. With `layout.chars`, codes between the listed
+/// characters get zero-width placeholders. Returns the font bytes and the
+/// glyph count.
+pub fn encode(path: &Path, image: &IndexedImage, layout: &FontLayout) -> Result<(Vec<u8>, usize)> {
+    let columns = image.width / layout.cell_width;
+    let rows = image.height / layout.cell_height;
+    let mut glyphs: Vec<(u8, Vec<u8>)> = Vec::with_capacity(columns * rows);
+
+    for cell in 0..columns * rows {
+        let left = (cell % columns) * layout.cell_width;
+        let top = (cell / columns) * layout.cell_height;
+        let mut bits = Vec::with_capacity(layout.cell_height);
+        let mut width = 0;
+
+        for y in top..top + layout.cell_height {
+            let mut row = 0u8;
+
+            for col in 0..layout.cell_width {
+                if image.pixels[y * image.width + left + col] != 0 {
+                    row |= 0x80 >> col;
+                    width = width.max(col + 1);
+                }
+            }
+
+            bits.push(row);
+        }
+
+        glyphs.push((width as u8, bits));
+    }
+
+    let space = layout.cell_width.div_ceil(2) as u8;
+    let limit = |message: String| Error::Limit {
+        path: path.to_path_buf(),
+        message,
+    };
+
+    let first = match &layout.chars {
+        Some(chars) => {
+            if chars.len() > glyphs.len() {
+                return Err(limit(format!(
+                    "{} characters listed for {} glyph cells",
+                    chars.len(),
+                    glyphs.len()
+                )));
+            }
+
+            if let Some(&c) = chars.iter().find(|&&c| c as u32 > 0xFF) {
+                return Err(limit(format!(
+                    "`{c}` is past U+00FF, the last code a font can hold"
+                )));
+            }
+
+            let first = chars.iter().map(|&c| c as u8).min().unwrap_or(b' ');
+            let last = chars.iter().map(|&c| c as u8).max().unwrap_or(b' ');
+            let mut table = vec![(0, vec![0; layout.cell_height]); (last - first) as usize + 1];
+
+            for (&c, (width, bits)) in chars.iter().zip(glyphs) {
+                let slot = &mut table[(c as u8 - first) as usize];
+
+                if slot.0 != 0 {
+                    return Err(limit(format!("`{c}` is listed twice")));
+                }
+
+                *slot = (if width == 0 { space } else { width }, bits);
+            }
+
+            glyphs = table;
+            first
+        }
+        None => {
+            while glyphs.last().is_some_and(|(width, _)| *width == 0) {
+                glyphs.pop();
+            }
+
+            for (width, _) in &mut glyphs {
+                if *width == 0 {
+                    *width = space;
+                }
+            }
+
+            layout.first
+        }
+    };
+
+    if first as usize + glyphs.len() > 256 {
+        return Err(limit(format!(
+            "{} glyphs from character {first} run past 255",
+            glyphs.len()
+        )));
+    }
+
+    let mut font = Vec::with_capacity(8 + glyphs.len() * (1 + layout.cell_height));
+    font.extend_from_slice(FONT_MAGIC);
+    font.extend_from_slice(&[
+        FONT_VERSION,
+        layout.cell_height as u8,
+        first,
+        glyphs.len() as u8,
+    ]);
+
+    for (width, bits) in &glyphs {
+        font.push(*width);
+        font.extend_from_slice(bits);
+    }
+
+    Ok((font, glyphs.len()))
+}
@@ -1,99 +1,463 @@
-use std::fs::{self, File};
-use std::io::Write;
-use std::path::{Path, PathBuf};
+mod behavior;
+mod check;
+mod convert;
+mod decode;
+mod error;
+mod font;
+mod music;
+mod pack;
+mod palette;
+mod pipeline;
+mod report;
+mod sheet;
+mod tilemap;
+mod unused;
 
-fn main() {
-    let raw_dir = Path::new("assets/raw");
-    let processed_dir = Path::new("assets/processed");
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::thread;
+use std::time::{Duration, Instant};
 
-    if !processed_dir.exists() {
-        fs::create_dir_all(processed_dir).expect("Failed to create assets/processed directory");
+use check::Limits;
+use error::{Error, Result};
+use font::FontLayout;
+use pipeline::Pipeline;
+use report::Budgets;
+
+/// Alignment of each asset inside the packed ROM blob. Must match the
+/// `RomLayout` used by the runtime.
+const ROM_ALIGN: usize = 16;
+
+/// Name of the packed blob inside the output directory.
+const ROM_NAME: &str = "sprites.rom";
+
+/// How often `--watch` rescans the input.
+const WATCH_INTERVAL: Duration = Duration::from_millis(250);
+
+const USAGE: &str = "\
+usage: spritec [options] [input]
+       spritec check [options] [input]
+       spritec decode [options] <input>
+       spritec report [options] [input]
+       spritec unused [options] [input]
+
+Converts PNGs (or songs and behavior scripts). `check` only reports what violates the limits below; `decode`
+renders .2bpp, .tiles and .tilemap.json outputs back to PNG; `report` lists the sizes of
+converted tiles, maps, music, fonts and behaviors (default input: the output directory) and fails
+when one is over its budget; `unused` lists converted assets the game never references and
+shows how often each tile is used by the maps and levels in the same directory.
+
+  input                 source file or directory of sources (default: assets/raw)
+  -o, --output <dir>    output directory (default: assets/processed)
+  --format <format>     2bpp, rom or all (default: all)
+  --tilemap             export each PNG as a tileset plus tile map
+  --font                convert each PNG of glyphs into a variable-width font
+  --glyph-size <WxH>    font glyph cell size, at most 8x10 (default: 8x8)
+  --first-char <c>      character of the first glyph cell (default: space)
+  --chars <text>        the character of each glyph cell, in order, instead of a run
+                        from --first-char; at most U+00FF
+  --music               compile each .song tracker file into a .music stream
+  --behavior            compile each .script enemy behavior into .behavior byte code
+  --pack <file>         also bundle every output into one asset pack
+  --palette <file>      palette definitions (JSON) to map colors with
+  --palette-name <name> which palette in the file to use
+  -r, --recursive       also convert PNGs in subdirectories
+  -f, --force           rebuild outputs that are already up to date
+  -w, --watch           keep running, reconverting sources as they change
+  --aligned             check: require sizes to be multiples of 8
+  --max-size <WxH>      check: largest allowed image
+  --tile-budget <n>     check: most tiles the PNGs of one directory may use
+  --width <px>          decode: row width for tile data without a manifest
+                        (default: 128)
+  --budget <cat>=<n>    report: byte budget for tiles, maps, music, fonts, behaviors
+                        or total;
+                        n may end in K (repeatable)
+  --manifest <file>     unused: asset manifest (default: assets/assets.manifest)
+  --src <dir>           unused: Rust sources to look for references in (default: src;
+                        repeatable)
+  --entities <file>     unused: entity table whose sprite and script values count as
+                        references (default: assets/entities.toml)
+  --tiles <file>        unused: tileset the .lvl levels paint with
+  -h, --help            print this help";
+
+/// What to do with the sources.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Command {
+    Convert,
+    /// Report constraint violations without writing anything.
+    Check,
+    /// Render converted binaries back to PNG.
+    Decode,
+    /// Print output sizes against budgets.
+    Report,
+    /// List unreferenced assets and unused tiles.
+    Unused,
+}
+
+/// What each source is converted into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    /// A `.2bpp` sprite, also packed into `sprites.rom`.
+    Sprites,
+    /// A deduplicated `.tiles` set, a `.map` and a `.tilemap.json` sidecar.
+    Tilemap,
+    /// A `.font` glyph table.
+    Font,
+    /// A `.music` stream, from a `.song` rather than a PNG.
+    Music,
+    /// `.behavior` byte code, from a `.script`.
+    Behavior,
+}
+
+impl Mode {
+    /// Extension of the source files this mode converts.
+    fn source_extension(self) -> &'static str {
+        match self {
+            Mode::Music => "song",
+            Mode::Behavior => "script",
+            _ => "png",
+        }
+    }
+}
+
+/// Which sprite outputs to write.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// One `.2bpp` file per sprite.
+    Tiles,
+    /// Only the packed `sprites.rom`.
+    Rom,
+    All,
+}
+
+impl Format {
+    fn tiles(self) -> bool {
+        matches!(self, Self::Tiles | Self::All)
+    }
+
+    fn rom(self) -> bool {
+        matches!(self, Self::Rom | Self::All)
+    }
+}
+
+struct Options {
+    command: Command,
+    input: PathBuf,
+    output: PathBuf,
+    mode: Mode,
+    format: Format,
+    pack: Option<PathBuf>,
+    font: FontLayout,
+    palette: Option<PathBuf>,
+    palette_name: Option<String>,
+    recursive: bool,
+    force: bool,
+    watch: bool,
+    limits: Limits,
+    decode_width: Option<usize>,
+    budgets: Budgets,
+    manifest: PathBuf,
+    sources: Vec<PathBuf>,
+    entities: PathBuf,
+    level_tiles: Option<PathBuf>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            command: Command::Convert,
+            input: PathBuf::from("assets/raw"),
+            output: PathBuf::from("assets/processed"),
+            mode: Mode::Sprites,
+            format: Format::All,
+            pack: None,
+            font: FontLayout {
+                cell_width: 8,
+                cell_height: 8,
+                first: b' ',
+                chars: None,
+            },
+            palette: None,
+            palette_name: None,
+            recursive: false,
+            force: false,
+            watch: false,
+            limits: Limits::default(),
+            decode_width: None,
+            budgets: Budgets::default(),
+            manifest: PathBuf::from("assets/assets.manifest"),
+            sources: Vec::new(),
+            entities: PathBuf::from("assets/entities.toml"),
+            level_tiles: None,
+        }
     }
+}
 
-    let entries = fs::read_dir(raw_dir).expect("Failed to read assets/raw");
+/// Parse the command line. `Ok(None)` means help was requested.
+fn parse_args() -> Result<Option<Options>> {
+    let mut options = Options::default();
+    let mut input = None;
+    let mut args = std::env::args().skip(1).peekable();
 
-    for entry in entries {
-        let entry = entry.expect("Failed to read directory entry");
-        let path = entry.path();
+    if args.next_if(|arg| arg == "check").is_some() {
+        options.command = Command::Check;
+    } else if args.next_if(|arg| arg == "decode").is_some() {
+        options.command = Command::Decode;
+    } else if args.next_if(|arg| arg == "report").is_some() {
+        options.command = Command::Report;
+    } else if args.next_if(|arg| arg == "unused").is_some() {
+        options.command = Command::Unused;
+    }
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| Error::Usage(format!("`{arg}` needs a value")))
+        };
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "-o" | "--output" => options.output = value()?.into(),
+            "--format" => {
+                options.format = match value()?.as_str() {
+                    "2bpp" => Format::Tiles,
+                    "rom" => Format::Rom,
+                    "all" => Format::All,
+                    other => return Err(Error::Usage(format!("unknown format `{other}`"))),
+                }
+            }
+            "--tilemap" => options.mode = Mode::Tilemap,
+            "--font" => options.mode = Mode::Font,
+            "--music" => options.mode = Mode::Music,
+            "--behavior" => options.mode = Mode::Behavior,
+            "--glyph-size" => {
+                let size = value()?;
+                (options.font.cell_width, options.font.cell_height) = FontLayout::parse_size(&size)
+                    .ok_or_else(|| Error::Usage(format!("invalid glyph size `{size}`")))?;
+            }
+            "--first-char" => {
+                let first = value()?;
+                options.font.first = match first.as_bytes() {
+                    &[byte] if byte.is_ascii() => byte,
+                    _ => {
+                        return Err(Error::Usage(format!(
+                            "`--first-char` must be one ASCII character, not `{first}`"
+                        )));
+                    }
+                };
+            }
+            "--chars" => {
+                let chars: Vec<char> = value()?.chars().collect();
+
+                if chars.is_empty() {
+                    return Err(Error::Usage(
+                        "`--chars` needs at least one character".into(),
+                    ));
+                }
+
+                options.font.chars = Some(chars);
+            }
+            "--pack" => options.pack = Some(value()?.into()),
+            "--palette" => options.palette = Some(value()?.into()),
+            "--palette-name" => options.palette_name = Some(value()?),
+            "-r" | "--recursive" => options.recursive = true,
+            "-f" | "--force" => options.force = true,
+            "-w" | "--watch" => options.watch = true,
+            "--aligned" => options.limits.aligned = true,
+            "--max-size" => {
+                let size = value()?;
+                let parsed = size
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)));
+                options.limits.max_size =
+                    Some(parsed.ok_or_else(|| Error::Usage(format!("invalid size `{size}`")))?);
+            }
+            "--width" => {
+                let width = value()?;
+                options.decode_width = Some(
+                    width
+                        .parse()
+                        .ok()
+                        .filter(|&width| width > 0)
+                        .ok_or_else(|| Error::Usage(format!("invalid width `{width}`")))?,
+                );
+            }
+            "--tile-budget" => {
+                let budget = value()?;
+                options.limits.tile_budget = Some(
+                    budget
+                        .parse()
+                        .map_err(|_| Error::Usage(format!("invalid tile budget `{budget}`")))?,
+                );
+            }
+            "--budget" => {
+                let budget = value()?;
+                options
+                    .budgets
+                    .set(&budget)
+                    .ok_or_else(|| Error::Usage(format!("invalid budget `{budget}`")))?;
+            }
+            "--manifest" => options.manifest = value()?.into(),
+            "--src" => options.sources.push(value()?.into()),
+            "--entities" => options.entities = value()?.into(),
+            "--tiles" => options.level_tiles = Some(value()?.into()),
+            _ if arg.starts_with('-') => {
+                return Err(Error::Usage(format!("unknown option `{arg}`")));
+            }
+            _ if input.is_none() => input = Some(PathBuf::from(arg)),
+            _ => return Err(Error::Usage(format!("unexpected argument `{arg}`"))),
+        }
+    }
 
-        if path.extension().map_or(false, |ext| ext == "png") {
-            process_sprite(&path, processed_dir);
+    match input {
+        Some(input) => options.input = input,
+        None if options.command == Command::Decode => {
+            return Err(Error::Usage("`decode` needs an input".to_string()));
+        }
+        None if matches!(options.command, Command::Report | Command::Unused) => {
+            options.input = options.output.clone();
         }
+        None => {}
     }
+
+    if options.sources.is_empty() {
+        options.sources.push(PathBuf::from("src"));
+    }
+
+    if options.palette_name.is_some() && options.palette.is_none() {
+        return Err(Error::Usage(
+            "`--palette-name` needs `--palette`".to_string(),
+        ));
+    }
+
+    Ok(Some(options))
 }
 
-fn process_sprite(png_path: &Path, output_dir: &Path) {
-    let file_stem = png_path.file_stem().unwrap().to_str().unwrap();
-    let mut out_path = PathBuf::from(output_dir);
-    out_path.push(format!("{}.2bpp", file_stem));
+fn main() -> ExitCode {
+    let options = match parse_args() {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("spritec: {err}\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    if options.command == Command::Check {
+        let report = check::run(&options);
+
+        for err in &report.violations {
+            eprintln!("spritec: {err}");
+        }
+
+        println!(
+            "{} files checked, {} violations",
+            report.files,
+            report.violations.len()
+        );
 
-    println!("Processing: {:?} -> {:?}", png_path, out_path);
+        return if report.violations.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
+    }
 
-    let img = image::open(png_path)
-        .expect("Failed to open image")
-        .to_rgba8();
+    if options.command == Command::Decode {
+        let report = decode::run(&options);
 
-    let (width, height) = img.dimensions();
-    let pixels = img.into_raw();
+        for err in &report.errors {
+            eprintln!("spritec: {err}");
+        }
 
-    // Map RGBA pixels to 2-bit palette indices
-    let mut indexed: Vec<u8> = Vec::with_capacity((width * height) as usize);
+        println!("{} decoded, {} failed", report.decoded, report.errors.len());
 
-    for px in pixels.chunks_exact(4) {
-        let value = match px {
-            [0, 0, 0, 0] => 0,        // transparent
-            [15, 56, 15, 255] => 1,   // darkest green
-            [48, 98, 48, 255] => 2,   // mid green
-            [139, 172, 15, 255] => 3, // lightest green
-            other => panic!("Unexpected color in sprite {:?}: {:?}", png_path, other),
+        return if report.errors.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
         };
+    }
+
+    if options.command == Command::Report {
+        let report = report::run(&options);
 
-        indexed.push(value);
+        println!("{report}");
+
+        for err in &report.errors {
+            eprintln!("spritec: {err}");
+        }
+
+        return if report.errors.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
     }
 
-    // Pad width/height to next multiple of 8
-    let padded_width = (width as usize + 7) & !7;
-    let padded_height = (height as usize + 7) & !7;
+    if options.command == Command::Unused {
+        let report = unused::run(&options);
 
-    let mut padded: Vec<u8> = vec![0; padded_width * padded_height];
+        println!("{report}");
 
-    for y in 0..height as usize {
-        for x in 0..width as usize {
-            padded[y * padded_width + x] = indexed[y * width as usize + x];
+        for err in &report.errors {
+            eprintln!("spritec: {err}");
         }
+
+        return if report.errors.is_empty() {
+            ExitCode::SUCCESS
+        } else {
+            ExitCode::FAILURE
+        };
     }
 
-    // Convert to 2bpp planar
-    let tiles_x = padded_width / 8;
-    let tiles_y = padded_height / 8;
-    let mut output: Vec<u8> = Vec::with_capacity(tiles_x * tiles_y * 16);
+    let mut pipeline = Pipeline::new(&options);
 
-    for ty in 0..tiles_y {
-        for tx in 0..tiles_x {
-            for row in 0..8 {
-                let py = ty * 8 + row;
-                let mut low_byte: u8 = 0;
-                let mut high_byte: u8 = 0;
+    if options.watch {
+        watch(&mut pipeline, &options);
+    }
 
-                for col in 0..8 {
-                    let px = tx * 8 + col;
-                    let index = padded[py * padded_width + px];
+    let summary = pipeline.run();
 
-                    let bit = 7 - col;
-                    if index & 1 != 0 {
-                        low_byte |= 1 << bit;
-                    }
-                    if index & 2 != 0 {
-                        high_byte |= 1 << bit;
-                    }
-                }
+    for err in &summary.errors {
+        eprintln!("spritec: {err}");
+    }
 
-                output.push(low_byte);
-                output.push(high_byte);
+    println!("{summary}");
+
+    if summary.errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// Rerun the pipeline every `WATCH_INTERVAL` until the process is killed,
+/// printing a summary whenever a run did anything. Errors are reported and
+/// retried on the next change, so a half-saved PNG does not end the watch.
+fn watch(pipeline: &mut Pipeline, options: &Options) -> ! {
+    println!("watching {} (ctrl-c to stop)", options.input.display());
+
+    let mut last_errors = Vec::new();
+
+    loop {
+        let started = Instant::now();
+        let summary = pipeline.run();
+        let errors: Vec<String> = summary.errors.iter().map(|err| err.to_string()).collect();
+
+        // A broken file stays broken until it is saved again; say so once.
+        if summary.converted > 0 || errors != last_errors {
+            for err in &errors {
+                eprintln!("spritec: {err}");
             }
+
+            println!("{summary} in {} ms", started.elapsed().as_millis());
         }
-    }
 
-    let mut file = File::create(out_path).expect("Failed to create .2bpp file");
-    file.write_all(&output).expect("Failed to write .2bpp file");
+        last_errors = errors;
+        thread::sleep(WATCH_INTERVAL);
+    }
 }
@@ -0,0 +1,552 @@
+//! Music: tracker-style `.song` text compiled into the `.music` byte stream
+//! played by the audio driver. Both are described in
+//! `docs/music_format.md`.
+//!
+//! ```text
+//! tempo 6
+//! loop 0
+//! instrument 0 volume 12 fade 2 duty 2
+//! pattern 0
+//! C-4 00 ... | E-4 00 ... | C-3 00 ... | --- .. ...
+//! --- .. ... | --- .. ... | --- .. T04 | C-5 00 ...
+//! order 0 0
+//! layer 1 0 1
+//! ```
+//!
+//! Rows have one cell per channel (square 1, square 2, wave, noise): a note,
+//! an instrument in hex and an effect. Everything is checked against what
+//! the hardware-style channels can play. A `layer` plays a variant of a
+//! pattern once the game's danger reaches its level.
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+pub const MUSIC_MAGIC: &[u8; 4] = b"BBMU";
+pub const MUSIC_VERSION: u16 = 2;
+
+/// Square 1, square 2, wave and noise.
+pub const CHANNELS: usize = 4;
+
+pub const MAX_ROWS: usize = 64;
+pub const MAX_PATTERNS: usize = 255;
+/// One less than 256 so `NO_LOOP` cannot be an order index.
+pub const MAX_ORDERS: usize = 255;
+pub const MAX_INSTRUMENTS: usize = 16;
+pub const MAX_LAYERS: usize = 255;
+/// Highest danger level the game reports.
+pub const MAX_DANGER: usize = 3;
+
+/// Lowest note, C-2, is note 1; highest is B-7.
+const LOWEST_OCTAVE: u8 = 2;
+const HIGHEST_OCTAVE: u8 = 7;
+
+const NOTE_NONE: u8 = 0;
+const NOTE_OFF: u8 = 0xff;
+const NO_INSTRUMENT: u8 = 0xff;
+const NO_DUTY: u8 = 0xff;
+const NO_LOOP: u8 = 0xff;
+
+const SQUARE_CHANNELS: [bool; CHANNELS] = [true, true, false, false];
+
+#[derive(Debug, Clone, Copy)]
+struct Instrument {
+    /// Starting volume, 0-15.
+    volume: u8,
+    /// Ticks per volume step down; 0 holds the volume.
+    fade: u8,
+    /// Square duty cycle, 0-3 (12.5%, 25%, 50%, 75%).
+    duty: Option<u8>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct Cell {
+    note: u8,
+    instrument: Option<u8>,
+    effect: Option<(u8, u8)>,
+}
+
+struct Pattern {
+    /// Line of the `pattern` directive, for errors.
+    line: usize,
+    rows: Vec<([Cell; CHANNELS], usize)>,
+}
+
+/// `.song` files are UTF-8 text.
+pub fn read_text(path: &Path, bytes: &[u8]) -> Result<String> {
+    String::from_utf8(bytes.to_vec()).map_err(|_| Error::Config {
+        path: path.to_path_buf(),
+        message: "not UTF-8 text".to_string(),
+    })
+}
+
+/// A parsed song.
+pub struct Song {
+    tempo: u8,
+    loop_order: Option<u8>,
+    instruments: Vec<Option<Instrument>>,
+    patterns: Vec<Option<Pattern>>,
+    orders: Vec<u8>,
+    /// Danger level, pattern and the variant it plays as, with the line.
+    layers: Vec<([u8; 3], usize)>,
+}
+
+impl Song {
+    /// Parse and validate `.song` text; `path` is only used in errors.
+    pub fn parse(path: &Path, text: &str) -> Result<Self> {
+        let mut song = Self {
+            tempo: 6,
+            loop_order: None,
+            instruments: Vec::new(),
+            patterns: Vec::new(),
+            orders: Vec::new(),
+            layers: Vec::new(),
+        };
+
+        let mut current: Option<usize> = None;
+
+        for (number, line) in text.lines().enumerate() {
+            let number = number + 1;
+            let error = |message: String| config(path, number, message);
+            let line = line.trim();
+
+            // Only whole lines are comments: `#` is also a sharp.
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.contains('|') {
+                let index = current.ok_or_else(|| error("row outside a pattern".to_string()))?;
+                let pattern = song.patterns[index]
+                    .as_mut()
+                    .expect("current pattern exists");
+
+                if pattern.rows.len() == MAX_ROWS {
+                    return Err(error(format!("pattern has more than {MAX_ROWS} rows")));
+                }
+
+                pattern.rows.push((parse_row(line).map_err(error)?, number));
+                continue;
+            }
+
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let number_at = |at: usize, max: usize| -> Result<usize> {
+                let word = words
+                    .get(at)
+                    .ok_or_else(|| error("missing value".to_string()))?;
+                word.parse()
+                    .ok()
+                    .filter(|&value| value <= max)
+                    .ok_or_else(|| error(format!("`{word}` is not a number up to {max}")))
+            };
+
+            current = None;
+
+            match words[0] {
+                "tempo" if words.len() == 2 => {
+                    song.tempo = number_at(1, u8::MAX as usize)? as u8;
+
+                    if song.tempo == 0 {
+                        return Err(error("tempo must be at least 1 tick per row".to_string()));
+                    }
+                }
+                "loop" if words.len() == 2 => {
+                    song.loop_order = Some(number_at(1, MAX_ORDERS - 1)? as u8);
+                }
+                "instrument" => {
+                    let id = number_at(1, MAX_INSTRUMENTS - 1)?;
+                    let instrument = parse_instrument(&words[2..]).map_err(error)?;
+                    let slot = slot(&mut song.instruments, id);
+
+                    if slot.replace(instrument).is_some() {
+                        return Err(error(format!("instrument {id} is defined twice")));
+                    }
+                }
+                "pattern" if words.len() == 2 => {
+                    let id = number_at(1, MAX_PATTERNS - 1)?;
+                    let pattern = Pattern {
+                        line: number,
+                        rows: Vec::new(),
+                    };
+
+                    if slot(&mut song.patterns, id).replace(pattern).is_some() {
+                        return Err(error(format!("pattern {id} is defined twice")));
+                    }
+
+                    current = Some(id);
+                }
+                "order" if words.len() > 1 => {
+                    for at in 1..words.len() {
+                        if song.orders.len() == MAX_ORDERS {
+                            return Err(error(format!("more than {MAX_ORDERS} orders")));
+                        }
+
+                        song.orders.push(number_at(at, MAX_PATTERNS - 1)? as u8);
+                    }
+                }
+                "layer" if words.len() == 4 => {
+                    if song.layers.len() == MAX_LAYERS {
+                        return Err(error(format!("more than {MAX_LAYERS} layers")));
+                    }
+
+                    let danger = number_at(1, MAX_DANGER)? as u8;
+                    let pattern = number_at(2, MAX_PATTERNS - 1)? as u8;
+                    let variant = number_at(3, MAX_PATTERNS - 1)? as u8;
+
+                    if danger == 0 {
+                        return Err(error("layer danger must be at least 1".to_string()));
+                    }
+
+                    if song
+                        .layers
+                        .iter()
+                        .any(|([level, base, _], _)| (*level, *base) == (danger, pattern))
+                    {
+                        return Err(error(format!(
+                            "pattern {pattern} has two layers for danger {danger}"
+                        )));
+                    }
+
+                    song.layers.push(([danger, pattern, variant], number));
+                }
+                _ => return Err(error(format!("unknown directive `{line}`"))),
+            }
+        }
+
+        song.validate(path)?;
+
+        Ok(song)
+    }
+
+    /// Cross-references that can only be checked once everything is read.
+    fn validate(&self, path: &Path) -> Result<()> {
+        let limit = |message: String| Error::Limit {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        if self.orders.is_empty() {
+            return Err(limit("the song has no `order`".to_string()));
+        }
+
+        if let Some(id) = self.instruments.iter().position(Option::is_none) {
+            return Err(limit(format!("instrument {id} is not defined")));
+        }
+
+        if let Some(id) = self.patterns.iter().position(Option::is_none) {
+            return Err(limit(format!("pattern {id} is not defined")));
+        }
+
+        if let Some(&id) = self
+            .orders
+            .iter()
+            .find(|&&id| id as usize >= self.patterns.len())
+        {
+            return Err(limit(format!(
+                "order plays pattern {id}, which is not defined"
+            )));
+        }
+
+        if let Some(order) = self.loop_order
+            && order as usize >= self.orders.len()
+        {
+            return Err(limit(format!(
+                "loop goes to order {order}, but there are {} orders",
+                self.orders.len()
+            )));
+        }
+
+        for &([_, base, variant], line) in &self.layers {
+            let rows = |id: u8| {
+                self.patterns
+                    .get(id as usize)
+                    .and_then(Option::as_ref)
+                    .map(|pattern| pattern.rows.len())
+                    .ok_or_else(|| config(path, line, format!("pattern {id} is not defined")))
+            };
+
+            if rows(base)? != rows(variant)? {
+                return Err(config(
+                    path,
+                    line,
+                    format!("pattern {variant} has a different row count from pattern {base}"),
+                ));
+            }
+        }
+
+        for pattern in self.patterns.iter().flatten() {
+            if pattern.rows.is_empty() {
+                return Err(config(
+                    path,
+                    pattern.line,
+                    "pattern has no rows".to_string(),
+                ));
+            }
+
+            for (cells, line) in &pattern.rows {
+                for (channel, cell) in cells.iter().enumerate() {
+                    self.validate_cell(channel, cell)
+                        .map_err(|message| config(path, *line, message))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_cell(&self, channel: usize, cell: &Cell) -> std::result::Result<(), String> {
+        if let Some(id) = cell.instrument {
+            let instrument = self
+                .instruments
+                .get(id as usize)
+                .copied()
+                .flatten()
+                .ok_or_else(|| format!("instrument {id:02X} is not defined"))?;
+
+            if instrument.duty.is_some() && !SQUARE_CHANNELS[channel] {
+                return Err(format!(
+                    "channel {} cannot play instrument {id:02X}: only square channels have a duty cycle",
+                    channel + 1
+                ));
+            }
+        }
+
+        match cell.effect {
+            Some((b'T', 0)) => Err("tempo must be at least 1 tick per row".to_string()),
+            Some((b'V', volume)) if volume > 15 => Err(format!("volume {volume:02X} is over 0F")),
+            Some((b'B', order)) if order as usize >= self.orders.len() => Err(format!(
+                "jump to order {order:02X}, but there are {} orders",
+                self.orders.len()
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Encode the song. `path` is only used in errors.
+    pub fn encode(&self, path: &Path) -> Result<Vec<u8>> {
+        let mut out = Vec::new();
+
+        out.extend_from_slice(MUSIC_MAGIC);
+        out.extend_from_slice(&MUSIC_VERSION.to_le_bytes());
+        out.extend_from_slice(&[
+            self.tempo,
+            self.loop_order.unwrap_or(NO_LOOP),
+            self.instruments.len() as u8,
+            self.patterns.len() as u8,
+            self.orders.len() as u8,
+            self.layers.len() as u8,
+        ]);
+
+        for instrument in self.instruments.iter().flatten() {
+            out.extend_from_slice(&[
+                instrument.volume,
+                instrument.fade,
+                instrument.duty.unwrap_or(NO_DUTY),
+                0,
+            ]);
+        }
+
+        out.extend_from_slice(&self.orders);
+
+        for (layer, _) in &self.layers {
+            out.extend_from_slice(layer);
+        }
+
+        let table = out.len();
+        out.resize(table + self.patterns.len() * 2, 0);
+
+        for (id, pattern) in self.patterns.iter().flatten().enumerate() {
+            let offset = u16::try_from(out.len()).map_err(|_| Error::Limit {
+                path: path.to_path_buf(),
+                message: format!("pattern {id} starts past 64 KiB"),
+            })?;
+
+            out[table + id * 2..][..2].copy_from_slice(&offset.to_le_bytes());
+            out.push(pattern.rows.len() as u8);
+
+            for (cells, _) in &pattern.rows {
+                let mask = cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, cell)| !cell.is_empty())
+                    .fold(0u8, |mask, (channel, _)| mask | 1 << channel);
+
+                out.push(mask);
+
+                for cell in cells.iter().filter(|cell| !cell.is_empty()) {
+                    let (effect, param) = cell.effect.unwrap_or((0, 0));
+                    out.extend_from_slice(&[
+                        cell.note,
+                        cell.instrument.unwrap_or(NO_INSTRUMENT),
+                        effect,
+                        param,
+                    ]);
+                }
+            }
+        }
+
+        Ok(out)
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn order_count(&self) -> usize {
+        self.orders.len()
+    }
+}
+
+impl Cell {
+    fn is_empty(&self) -> bool {
+        self.note == NOTE_NONE && self.instrument.is_none() && self.effect.is_none()
+    }
+}
+
+fn config(path: &Path, line: usize, message: String) -> Error {
+    Error::Config {
+        path: path.to_path_buf(),
+        message: format!("line {line}: {message}"),
+    }
+}
+
+/// The slot for `id`, growing `items` with empty slots as needed.
+fn slot<T>(items: &mut Vec<Option<T>>, id: usize) -> &mut Option<T> {
+    if items.len() <= id {
+        items.resize_with(id + 1, || None);
+    }
+
+    &mut items[id]
+}
+
+fn parse_instrument(words: &[&str]) -> std::result::Result<Instrument, String> {
+    let mut instrument = Instrument {
+        volume: 15,
+        fade: 0,
+        duty: None,
+    };
+
+    for pair in words.chunks(2) {
+        let [key, value] = pair else {
+            return Err(format!("`{}` needs a value", pair[0]));
+        };
+
+        let (field, max) = match *key {
+            "volume" => (&mut instrument.volume, 15),
+            "fade" => (&mut instrument.fade, 7),
+            "duty" => (instrument.duty.insert(0), 3),
+            _ => return Err(format!("unknown instrument setting `{key}`")),
+        };
+
+        *field = value
+            .parse()
+            .ok()
+            .filter(|&value| value <= max)
+            .ok_or_else(|| format!("{key} `{value}` is not a number up to {max}"))?;
+    }
+
+    Ok(instrument)
+}
+
+fn parse_row(line: &str) -> std::result::Result<[Cell; CHANNELS], String> {
+    let columns: Vec<&str> = line.split('|').collect();
+
+    if columns.len() != CHANNELS {
+        return Err(format!(
+            "row has {} channels, but there are {CHANNELS}",
+            columns.len()
+        ));
+    }
+
+    let mut cells = [Cell::default(); CHANNELS];
+
+    for (cell, column) in cells.iter_mut().zip(columns) {
+        *cell = parse_cell(column.trim())?;
+    }
+
+    Ok(cells)
+}
+
+/// `C#4 01 V0C`: note, instrument and effect, each of which may be blank
+/// (`---`, `..`, `...`). `===` stops the note.
+fn parse_cell(text: &str) -> std::result::Result<Cell, String> {
+    let [note, instrument, effect] = text.split_whitespace().collect::<Vec<_>>()[..] else {
+        return Err(format!("`{text}` is not a `note instrument effect` cell"));
+    };
+
+    let note = match note {
+        "---" => NOTE_NONE,
+        "===" => NOTE_OFF,
+        _ => parse_note(note).ok_or_else(|| {
+            format!(
+                "`{note}` is not a note from C-{LOWEST_OCTAVE} to B-{HIGHEST_OCTAVE}, `---` or `===`"
+            )
+        })?,
+    };
+
+    let instrument = match instrument {
+        ".." => None,
+        _ => Some(
+            u8::from_str_radix(instrument, 16)
+                .ok()
+                .filter(|_| instrument.len() == 2)
+                .ok_or_else(|| format!("`{instrument}` is not a hex instrument or `..`"))?,
+        ),
+    };
+
+    let effect = match effect.as_bytes() {
+        b"..." => None,
+        [kind @ (b'T' | b'V' | b'B'), param @ ..] if param.len() == 2 => {
+            let param = std::str::from_utf8(param)
+                .ok()
+                .and_then(|param| u8::from_str_radix(param, 16).ok())
+                .ok_or_else(|| format!("`{effect}` has a bad hex parameter"))?;
+
+            Some((*kind, param))
+        }
+        _ => {
+            return Err(format!(
+                "`{effect}` is not an effect (Txx tempo, Vxx volume, Bxx jump) or `...`"
+            ));
+        }
+    };
+
+    Ok(Cell {
+        note,
+        instrument,
+        effect,
+    })
+}
+
+/// `C-4` or `C#4` as a note number: 1 is C-2.
+fn parse_note(text: &str) -> Option<u8> {
+    let &[name, accidental, octave] = text.as_bytes() else {
+        return None;
+    };
+
+    let base = match name {
+        b'C' => 0,
+        b'D' => 2,
+        b'E' => 4,
+        b'F' => 5,
+        b'G' => 7,
+        b'A' => 9,
+        b'B' => 11,
+        _ => return None,
+    };
+
+    let semitone = match accidental {
+        b'-' => base,
+        // E# and B# are spelled F and C.
+        b'#' if !matches!(name, b'E' | b'B') => base + 1,
+        _ => return None,
+    };
+
+    let octave = octave.checked_sub(b'0')?;
+
+    if !(LOWEST_OCTAVE..=HIGHEST_OCTAVE).contains(&octave) {
+        return None;
+    }
+
+    Some((octave - LOWEST_OCTAVE) * 12 + semitone + 1)
+}
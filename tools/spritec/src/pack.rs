@@ -0,0 +1,97 @@
+//! Asset packs: every converted file bundled into one blob behind a table of
+//! contents. The layout is described in `docs/sprite_format.md` and read by
+//! the runtime's `AssetPack`.
+
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+pub const PACK_MAGIC: &[u8; 4] = b"BBPK";
+pub const PACK_VERSION: u16 = 1;
+
+/// Alignment of every asset's data inside the pack.
+pub const PACK_ALIGN: usize = 16;
+
+const HEADER_SIZE: usize = 8;
+const ENTRY_SIZE: usize = 16;
+
+/// 64-bit FNV-1a, the same hash the runtime uses for asset names.
+pub fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Collects assets in the order they are added.
+#[derive(Default)]
+pub struct PackBuilder {
+    assets: Vec<(String, Vec<u8>)>,
+}
+
+impl PackBuilder {
+    /// Add the output at `path`, relative to the output directory. Its name
+    /// in the pack uses `/` separators on every platform.
+    pub fn add(&mut self, path: &Path, bytes: &[u8]) {
+        let name = path
+            .components()
+            .map(|part| part.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+
+        self.assets.push((name, bytes.to_vec()));
+    }
+
+    /// Lay out the pack. Returns its bytes and the number of assets;
+    /// `path` is only used in errors.
+    pub fn finish(&self, path: &Path) -> Result<(Vec<u8>, usize)> {
+        let limit = |message: String| Error::Limit {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let count = u16::try_from(self.assets.len())
+            .map_err(|_| limit(format!("{} assets is over 65535", self.assets.len())))?;
+
+        let mut entries: Vec<(u64, &str, &[u8])> = self
+            .assets
+            .iter()
+            .map(|(name, bytes)| (fnv1a(name.as_bytes()), name.as_str(), bytes.as_slice()))
+            .collect();
+
+        // Sorted by hash so the runtime can binary search the table.
+        entries.sort_by_key(|&(hash, ..)| hash);
+
+        if let Some(pair) = entries.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(limit(format!(
+                "`{}` and `{}` have the same name hash",
+                pair[0].1, pair[1].1
+            )));
+        }
+
+        let table_end = HEADER_SIZE + entries.len() * ENTRY_SIZE;
+        let mut data_offset = table_end.next_multiple_of(PACK_ALIGN);
+        let mut pack = Vec::with_capacity(data_offset);
+
+        pack.extend_from_slice(PACK_MAGIC);
+        pack.extend_from_slice(&PACK_VERSION.to_le_bytes());
+        pack.extend_from_slice(&count.to_le_bytes());
+
+        for &(hash, name, bytes) in &entries {
+            let offset = u32::try_from(data_offset)
+                .map_err(|_| limit(format!("`{name}` starts past 4 GiB")))?;
+
+            pack.extend_from_slice(&hash.to_le_bytes());
+            pack.extend_from_slice(&offset.to_le_bytes());
+            pack.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+
+            data_offset = (data_offset + bytes.len()).next_multiple_of(PACK_ALIGN);
+        }
+
+        for &(_, _, bytes) in &entries {
+            pack.resize(pack.len().next_multiple_of(PACK_ALIGN), 0);
+            pack.extend_from_slice(bytes);
+        }
+
+        Ok((pack, entries.len()))
+    }
+}
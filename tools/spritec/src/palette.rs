@@ -0,0 +1,118 @@
+//! Palette definitions: which source colors map to which 2-bit index.
+//!
+//! A palette file holds any number of named palettes, each mapping
+//! `#rrggbb` (opaque) or `#rrggbbaa` colors to an index:
+//!
+//! ```json
+//! {
+//!   "dmg": { "#0f380f": 1, "#306230": 2, "#8bac0f": 3 },
+//!   "gray": { "#000000": 1, "#555555": 2, "#aaaaaa": 3 }
+//! }
+//! ```
+//!
+//! Fully transparent pixels are always index 0, whatever their RGB.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::error::{Error, Result};
+
+/// Maps source colors to 2-bit palette indices.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Palette {
+    entries: Vec<([u8; 4], u8)>,
+}
+
+impl Default for Palette {
+    /// The DMG greens, matching the `dmg` entry of `assets/palettes.json`.
+    fn default() -> Self {
+        Self {
+            entries: vec![
+                ([15, 56, 15, 255], 1),
+                ([48, 98, 48, 255], 2),
+                ([139, 172, 15, 255], 3),
+            ],
+        }
+    }
+}
+
+impl Palette {
+    /// Index for an RGBA color.
+    pub fn index_of(&self, rgba: [u8; 4]) -> Option<u8> {
+        if rgba[3] == 0 {
+            return Some(0);
+        }
+
+        self.entries
+            .iter()
+            .find(|(color, _)| *color == rgba)
+            .map(|&(_, index)| index)
+    }
+
+    /// The color to draw `index` with: the first color mapped to it, or
+    /// transparent when none is (always the case for index 0 in the
+    /// built-in palette).
+    pub fn color_of(&self, index: u8) -> [u8; 4] {
+        self.entries
+            .iter()
+            .find(|&&(_, entry)| entry == index)
+            .map_or([0, 0, 0, 0], |&(color, _)| color)
+    }
+
+    /// Load palette `name` from a palette file. Without a name the file must
+    /// hold exactly one palette.
+    pub fn load(path: &Path, name: Option<&str>) -> Result<Self> {
+        let error = |message: String| Error::Config {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let text = fs::read_to_string(path).map_err(|err| Error::io(path, err))?;
+        let palettes: BTreeMap<String, BTreeMap<String, u8>> =
+            serde_json::from_str(&text).map_err(|err| error(err.to_string()))?;
+        let names = || palettes.keys().cloned().collect::<Vec<_>>().join(", ");
+
+        let colors = match name {
+            Some(name) => palettes
+                .get(name)
+                .ok_or_else(|| error(format!("no palette `{name}` (have: {})", names())))?,
+            None if palettes.len() == 1 => palettes.values().next().unwrap(),
+            None => {
+                return Err(error(format!(
+                    "several palettes defined, pick one with --palette-name ({})",
+                    names()
+                )));
+            }
+        };
+
+        let mut entries = Vec::with_capacity(colors.len());
+
+        for (color, &index) in colors {
+            let rgba =
+                parse_color(color).ok_or_else(|| error(format!("invalid color `{color}`")))?;
+
+            if index > 3 {
+                return Err(error(format!("{color}: index {index} is not 0-3")));
+            }
+
+            entries.push((rgba, index));
+        }
+
+        Ok(Self { entries })
+    }
+}
+
+/// Parse `#rrggbb` (opaque) or `#rrggbbaa`.
+pub fn parse_color(text: &str) -> Option<[u8; 4]> {
+    let hex = text.strip_prefix('#')?;
+
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+
+    Some([channel(0)?, channel(1)?, channel(2)?, alpha])
+}
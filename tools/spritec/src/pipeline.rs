@@ -0,0 +1,494 @@
+//! Converts a set of sources, remembering what it built so a rerun only
+//! reconverts what changed. One-shot runs start with an empty cache and fall
+//! back to comparing against the output files; `--watch` keeps one pipeline
+//! alive and reruns it on every poll.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use crate::behavior::{self, Behavior};
+use crate::convert;
+use crate::error::{Error, Result};
+use crate::font;
+use crate::music::{self, Song};
+use crate::pack::{PackBuilder, fnv1a};
+use crate::palette::Palette;
+use crate::sheet::{self, Sheet};
+use crate::tilemap::Tilemap;
+use crate::{Mode, Options, ROM_ALIGN, ROM_NAME};
+
+/// A source PNG and where its outputs go, relative to the output directory.
+pub struct Source {
+    pub path: PathBuf,
+    /// Output path without extension, relative to the output directory.
+    pub stem: PathBuf,
+}
+
+/// One file a source converts into.
+pub struct Output {
+    /// Relative to the output directory; also the asset's name in a pack.
+    pub path: PathBuf,
+    pub bytes: Vec<u8>,
+}
+
+/// What the last successful build of a source looked like.
+struct Cached {
+    modified: Option<SystemTime>,
+    hash: u64,
+    outputs: Vec<Output>,
+}
+
+/// Counts from one run.
+#[derive(Debug, Default)]
+pub struct Summary {
+    pub converted: usize,
+    pub unchanged: usize,
+    pub errors: Vec<Error>,
+}
+
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} converted, {} unchanged, {} failed",
+            self.converted,
+            self.unchanged,
+            self.errors.len()
+        )
+    }
+}
+
+pub struct Pipeline<'a> {
+    options: &'a Options,
+    palette: Palette,
+    palette_modified: Option<SystemTime>,
+    cache: HashMap<PathBuf, Cached>,
+}
+
+impl<'a> Pipeline<'a> {
+    pub fn new(options: &'a Options) -> Self {
+        Self {
+            options,
+            palette: Palette::default(),
+            palette_modified: None,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Convert every source that changed since the last run, then rewrite
+    /// the ROM (sprite mode) and the pack (`--pack`) if their contents
+    /// changed.
+    pub fn run(&mut self) -> Summary {
+        let mut summary = Summary::default();
+
+        if let Err(err) = self.reload_palette() {
+            summary.errors.push(err);
+            return summary;
+        }
+
+        let sources = match collect_sources(
+            &self.options.input,
+            self.options.recursive,
+            self.options.mode,
+        ) {
+            Ok(sources) => sources,
+            Err(err) => {
+                summary.errors.push(err);
+                return summary;
+            }
+        };
+
+        // Forget sources that were deleted or renamed.
+        self.cache
+            .retain(|path, _| sources.iter().any(|source| &source.path == path));
+
+        let sprites = self.options.mode == Mode::Sprites;
+        let mut rom: Vec<u8> = Vec::new();
+        let mut pack = PackBuilder::default();
+
+        for source in &sources {
+            match self.build_source(source) {
+                Ok((outputs, converted)) => {
+                    if converted {
+                        summary.converted += 1;
+                    } else {
+                        summary.unchanged += 1;
+                    }
+
+                    if sprites {
+                        rom.resize(rom.len().next_multiple_of(ROM_ALIGN), 0);
+                        rom.extend_from_slice(&outputs[0].bytes);
+                    }
+
+                    for output in outputs {
+                        pack.add(&output.path, &output.bytes);
+                    }
+                }
+                Err(err) => summary.errors.push(err),
+            }
+        }
+
+        // A ROM or pack with an asset missing would be silently incomplete.
+        if !summary.errors.is_empty() {
+            return summary;
+        }
+
+        if sprites && self.options.format.rom() {
+            let rom_path = self.options.output.join(ROM_NAME);
+            let description = format!("{} sprites", sources.len());
+
+            if let Err(err) = self.write_bundle(&rom_path, &rom, &description) {
+                summary.errors.push(err);
+            }
+        }
+
+        if let Some(pack_path) = &self.options.pack {
+            let written = pack.finish(pack_path).and_then(|(bytes, count)| {
+                self.write_bundle(pack_path, &bytes, &format!("{count} assets"))
+            });
+
+            if let Err(err) = written {
+                summary.errors.push(err);
+            }
+        }
+
+        summary
+    }
+
+    /// Write a ROM or pack unless the file already holds exactly `bytes`.
+    fn write_bundle(&self, path: &Path, bytes: &[u8], description: &str) -> Result<()> {
+        let unchanged = fs::read(path).is_ok_and(|old| old == bytes);
+
+        if self.options.force || !unchanged {
+            println!("packing {description} -> {}", path.display());
+            write_file(path, bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Load the palette file if it changed. A new palette invalidates every
+    /// cached conversion.
+    fn reload_palette(&mut self) -> Result<()> {
+        let Some(path) = &self.options.palette else {
+            return Ok(());
+        };
+
+        let modified = modified(path);
+
+        if modified.is_some() && modified == self.palette_modified {
+            return Ok(());
+        }
+
+        self.palette = Palette::load(path, self.options.palette_name.as_deref())?;
+        self.palette_modified = modified;
+        self.cache.clear();
+
+        Ok(())
+    }
+
+    /// The files `source` converts into, relative to the output directory.
+    /// The first is its ROM share in sprite mode.
+    fn output_paths(&self, source: &Source, is_sheet: bool) -> Vec<PathBuf> {
+        let stem = &source.stem;
+
+        match self.options.mode {
+            Mode::Sprites if is_sheet => {
+                vec![
+                    stem.with_extension("2bpp"),
+                    stem.with_extension("anim.json"),
+                ]
+            }
+            Mode::Sprites => vec![stem.with_extension("2bpp")],
+            Mode::Tilemap => vec![
+                stem.with_extension("map"),
+                stem.with_extension("tiles"),
+                stem.with_extension("tilemap.json"),
+            ],
+            Mode::Font => vec![stem.with_extension("font")],
+            Mode::Music => vec![stem.with_extension("music")],
+            Mode::Behavior => vec![stem.with_extension("behavior")],
+        }
+    }
+
+    /// Whether `output` is written to disk. `--format rom` keeps sprite tile
+    /// data out of the output directory, but not manifests.
+    fn writes(&self, output: &Path) -> bool {
+        self.options.mode != Mode::Sprites
+            || self.options.format.tiles()
+            || output.extension().is_none_or(|ext| ext != "2bpp")
+    }
+
+    /// Convert one source, or reuse its previous build. Returns its outputs
+    /// and whether it was converted this run.
+    fn build_source(&mut self, source: &Source) -> Result<(&[Output], bool)> {
+        let options = self.options;
+
+        // A sprite sheet's sidecar is part of its source.
+        let sidecar = sheet::sidecar_path(&source.path);
+        let modified = modified(&source.path).max(modified(&sidecar));
+
+        // An untouched file needs neither reading nor hashing.
+        let untouched = modified.is_some()
+            && self
+                .cache
+                .get(&source.path)
+                .is_some_and(|cached| cached.modified == modified);
+
+        if untouched {
+            return Ok((&self.cache[&source.path].outputs, false));
+        }
+
+        let bytes = fs::read(&source.path).map_err(|err| Error::io(&source.path, err))?;
+        let sidecar_bytes = fs::read(&sidecar).ok();
+        let mut hash = fnv1a(&bytes);
+
+        if let Some(sidecar) = &sidecar_bytes {
+            hash ^= fnv1a(sidecar).rotate_left(1);
+        }
+
+        // Touched but identical, e.g. re-saved by an editor.
+        let identical = match self.cache.get_mut(&source.path) {
+            Some(cached) if cached.hash == hash => {
+                cached.modified = modified;
+                true
+            }
+            _ => false,
+        };
+
+        if identical {
+            return Ok((&self.cache[&source.path].outputs, false));
+        }
+
+        let paths = self.output_paths(source, sidecar_bytes.is_some());
+        let mut inputs = vec![source.path.as_path()];
+        inputs.extend(options.palette.as_deref());
+
+        if sidecar_bytes.is_some() {
+            inputs.push(&sidecar);
+        }
+
+        let reuse_outputs = !options.force
+            && !self.cache.contains_key(&source.path)
+            && paths
+                .iter()
+                .all(|path| self.writes(path) && up_to_date(&options.output.join(path), &inputs));
+
+        let (outputs, converted) = if reuse_outputs {
+            let outputs = paths
+                .into_iter()
+                .map(|path| {
+                    let full = options.output.join(&path);
+                    let bytes = fs::read(&full).map_err(|err| Error::io(&full, err))?;
+                    Ok(Output { path, bytes })
+                })
+                .collect::<Result<Vec<_>>>()?;
+
+            (outputs, false)
+        } else {
+            let outputs = self.convert(source, &bytes, &paths)?;
+
+            for output in &outputs {
+                if self.writes(&output.path) {
+                    write_file(&options.output.join(&output.path), &output.bytes)?;
+                }
+            }
+
+            (outputs, true)
+        };
+
+        let cached = self
+            .cache
+            .entry(source.path.clone())
+            .insert_entry(Cached {
+                modified,
+                hash,
+                outputs,
+            })
+            .into_mut();
+
+        Ok((&cached.outputs, converted))
+    }
+
+    /// Convert `bytes`, the contents of `source`, into the files at `paths`.
+    fn convert(&self, source: &Source, bytes: &[u8], paths: &[PathBuf]) -> Result<Vec<Output>> {
+        let out_path = self.options.output.join(&paths[0]);
+
+        if self.options.mode == Mode::Music {
+            let song = Song::parse(&source.path, &music::read_text(&source.path, bytes)?)?;
+
+            println!(
+                "{} -> {} ({} patterns, {} orders)",
+                source.path.display(),
+                out_path.display(),
+                song.pattern_count(),
+                song.order_count()
+            );
+
+            return Ok(vec![Output {
+                path: paths[0].clone(),
+                bytes: song.encode(&source.path)?,
+            }]);
+        }
+
+        if self.options.mode == Mode::Behavior {
+            let script = Behavior::parse(&source.path, &behavior::read_text(&source.path, bytes)?)?;
+
+            println!(
+                "{} -> {} ({} instructions)",
+                source.path.display(),
+                out_path.display(),
+                script.instruction_count()
+            );
+
+            return Ok(vec![Output {
+                path: paths[0].clone(),
+                bytes: script.encode(),
+            }]);
+        }
+
+        let image = convert::decode_indexed(&source.path, bytes, &self.palette)?;
+
+        match self.options.mode {
+            Mode::Sprites => {
+                let Some(sheet) = Sheet::load(&source.path)? else {
+                    println!("{} -> {}", source.path.display(), out_path.display());
+
+                    return Ok(vec![Output {
+                        path: paths[0].clone(),
+                        bytes: convert::encode_2bpp(&image),
+                    }]);
+                };
+
+                let (tiles, manifest) = sheet.encode(&source.path, &image)?;
+                let data = paths[0].file_name().unwrap_or_default().to_string_lossy();
+
+                println!(
+                    "{} -> {} ({} frames)",
+                    source.path.display(),
+                    out_path.display(),
+                    manifest.frame_count
+                );
+
+                Ok(vec![
+                    Output {
+                        path: paths[0].clone(),
+                        bytes: tiles,
+                    },
+                    Output {
+                        path: paths[1].clone(),
+                        bytes: manifest.to_json(&data),
+                    },
+                ])
+            }
+            Mode::Tilemap => {
+                let tilemap = Tilemap::build(&source.path, &image)?;
+
+                println!(
+                    "{} -> {} ({}x{} cells, {} tiles)",
+                    source.path.display(),
+                    out_path.display(),
+                    tilemap.width,
+                    tilemap.height,
+                    tilemap.tiles.len()
+                );
+
+                Ok(tilemap.outputs(paths))
+            }
+            Mode::Font => {
+                let (bytes, glyphs) = font::encode(&source.path, &image, &self.options.font)?;
+
+                println!(
+                    "{} -> {} ({glyphs} glyphs)",
+                    source.path.display(),
+                    out_path.display()
+                );
+
+                Ok(vec![Output {
+                    path: paths[0].clone(),
+                    bytes,
+                }])
+            }
+            Mode::Music | Mode::Behavior => unreachable!("text sources are compiled above"),
+        }
+    }
+}
+
+/// Collect the sources of `mode` under `input`, sorted by relative path so
+/// the ROM layout is stable across platforms.
+pub fn collect_sources(input: &Path, recursive: bool, mode: Mode) -> Result<Vec<Source>> {
+    let extension = mode.source_extension();
+
+    collect_files(input, recursive, |path| {
+        path.extension().is_some_and(|ext| ext == extension)
+    })
+}
+
+/// Collect `input` itself if it is a file, or the files under it that
+/// `accept` picks, sorted by relative path.
+pub fn collect_files(
+    input: &Path,
+    recursive: bool,
+    accept: impl Fn(&Path) -> bool,
+) -> Result<Vec<Source>> {
+    let metadata = fs::metadata(input).map_err(|err| Error::io(input, err))?;
+
+    if metadata.is_file() {
+        let stem = input.file_stem().map(PathBuf::from).unwrap_or_default();
+
+        return Ok(vec![Source {
+            path: input.to_path_buf(),
+            stem,
+        }]);
+    }
+
+    let mut sources = Vec::new();
+    let mut pending = vec![input.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir).map_err(|err| Error::io(&dir, err))?;
+
+        for entry in entries {
+            let path = entry.map_err(|err| Error::io(&dir, err))?.path();
+
+            if path.is_dir() {
+                if recursive {
+                    pending.push(path);
+                }
+            } else if accept(&path) {
+                let stem = path.strip_prefix(input).unwrap_or(&path).with_extension("");
+                sources.push(Source { path, stem });
+            }
+        }
+    }
+
+    sources.sort_by(|a, b| a.stem.cmp(&b.stem));
+
+    Ok(sources)
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// True if `output` exists and is newer than every one of `inputs`.
+fn up_to_date(output: &Path, inputs: &[&Path]) -> bool {
+    let Some(built) = modified(output) else {
+        return false;
+    };
+
+    inputs
+        .iter()
+        .all(|input| modified(input).is_some_and(|time| time <= built))
+}
+
+pub(crate) fn write_file(path: &Path, bytes: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|err| Error::io(parent, err))?;
+    }
+
+    fs::write(path, bytes).map_err(|err| Error::io(path, err))
+}
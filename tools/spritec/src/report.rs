@@ -0,0 +1,246 @@
+//! `spritec report`: sizes of the converted assets, per asset and per
+//! category, against byte budgets. Anything over budget is an error, so a
+//! build script or CI step running `spritec report` fails when the ROM data
+//! outgrows what the target can hold.
+//!
+//! Bundles (`sprites.rom`, asset packs) and JSON sidecars are not counted:
+//! they repeat or describe data that is already counted.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Options;
+use crate::error::{Error, Result};
+use crate::pipeline::collect_files;
+
+/// What kind of data an output file holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Category {
+    /// `.2bpp` sprites and `.tiles` tilesets.
+    Tiles,
+    /// `.map` tile maps and `.lvl` levels.
+    Maps,
+    /// `.music` streams.
+    Music,
+    /// `.font` glyph tables.
+    Fonts,
+    /// `.behavior` byte code.
+    Behaviors,
+}
+
+impl Category {
+    pub const ALL: [Self; 5] = [
+        Self::Tiles,
+        Self::Maps,
+        Self::Music,
+        Self::Fonts,
+        Self::Behaviors,
+    ];
+
+    pub fn of(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "2bpp" | "tiles" => Some(Self::Tiles),
+            "map" | "lvl" => Some(Self::Maps),
+            "music" => Some(Self::Music),
+            "font" => Some(Self::Fonts),
+            "behavior" => Some(Self::Behaviors),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Tiles => "tiles",
+            Self::Maps => "maps",
+            Self::Music => "music",
+            Self::Fonts => "fonts",
+            Self::Behaviors => "behaviors",
+        }
+    }
+}
+
+/// Byte budgets per category and for everything together. `None` is
+/// unlimited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Budgets {
+    categories: [Option<usize>; Category::ALL.len()],
+    total: Option<usize>,
+}
+
+impl Budgets {
+    /// Set one budget from `<category>=<bytes>`, where the category can also
+    /// be `total` and the size can end in `K` for KiB. `None` if malformed.
+    pub fn set(&mut self, spec: &str) -> Option<()> {
+        let (name, size) = spec.split_once('=')?;
+
+        let bytes = match size.strip_suffix(['K', 'k']) {
+            Some(kib) => kib.parse::<usize>().ok()?.checked_mul(1024)?,
+            None => size.parse().ok()?,
+        };
+
+        let slot = match name {
+            "total" => &mut self.total,
+            _ => {
+                let category = Category::ALL.into_iter().find(|c| c.name() == name)?;
+                &mut self.categories[category as usize]
+            }
+        };
+
+        *slot = Some(bytes);
+        Some(())
+    }
+
+    fn of(&self, category: Category) -> Option<usize> {
+        self.categories[category as usize]
+    }
+}
+
+struct Asset {
+    category: Category,
+    /// Path relative to the reported directory.
+    name: PathBuf,
+    bytes: usize,
+}
+
+/// Outcome of a report run. `Display` prints the size table.
+pub struct Report {
+    assets: Vec<Asset>,
+    budgets: Budgets,
+    /// Unreadable files and exceeded budgets.
+    pub errors: Vec<Error>,
+}
+
+impl Report {
+    fn total(&self, category: Option<Category>) -> usize {
+        self.assets
+            .iter()
+            .filter(|asset| category.is_none_or(|category| asset.category == category))
+            .map(|asset| asset.bytes)
+            .sum()
+    }
+}
+
+pub fn run(options: &Options) -> Report {
+    let mut report = Report {
+        assets: Vec::new(),
+        budgets: options.budgets,
+        errors: Vec::new(),
+    };
+
+    let files = match collect_files(&options.input, options.recursive, |path| {
+        Category::of(path).is_some()
+    }) {
+        Ok(files) => files,
+        Err(err) => {
+            report.errors.push(err);
+            return report;
+        }
+    };
+
+    for file in &files {
+        match measure(&options.input, &file.path) {
+            Ok(asset) => report.assets.push(asset),
+            Err(err) => report.errors.push(err),
+        }
+    }
+
+    report
+        .assets
+        .sort_by(|a, b| (a.category, &a.name).cmp(&(b.category, &b.name)));
+
+    let over = |what: &str, used: usize, budget: Option<usize>| {
+        budget
+            .filter(|&budget| used > budget)
+            .map(|budget| Error::Limit {
+                path: options.input.clone(),
+                message: format!("{what}: {used} bytes, over the budget of {budget}"),
+            })
+    };
+
+    for category in Category::ALL {
+        let used = report.total(Some(category));
+
+        if let Some(err) = over(category.name(), used, report.budgets.of(category)) {
+            report.errors.push(err);
+        }
+    }
+
+    if let Some(err) = over("all assets", report.total(None), report.budgets.total) {
+        report.errors.push(err);
+    }
+
+    report
+}
+
+fn measure(input: &Path, path: &Path) -> Result<Asset> {
+    let metadata = fs::metadata(path).map_err(|err| Error::io(path, err))?;
+
+    let name = match path.strip_prefix(input) {
+        Ok(name) if !name.as_os_str().is_empty() => name,
+        _ => path,
+    };
+
+    Ok(Asset {
+        category: Category::of(path).expect("collected files have a category"),
+        name: name.to_path_buf(),
+        bytes: metadata.len() as usize,
+    })
+}
+
+/// `320` or `320 / 8192 (3%)`, flagged when over.
+fn usage(used: usize, budget: Option<usize>) -> String {
+    match budget {
+        Some(budget) => {
+            let percent = (used * 100).checked_div(budget).unwrap_or(100);
+            let flag = if used > budget { "  OVER" } else { "" };
+            format!("{used:>6} / {budget} ({percent}%){flag}")
+        }
+        None => format!("{used:>6}"),
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NAME_WIDTH: usize = 32;
+
+        for category in Category::ALL {
+            let budget = self.budgets.of(category);
+            let mut assets = self
+                .assets
+                .iter()
+                .filter(|asset| asset.category == category)
+                .peekable();
+
+            if assets.peek().is_none() && budget.is_none() {
+                continue;
+            }
+
+            writeln!(f, "{}", category.name())?;
+
+            for asset in assets {
+                writeln!(
+                    f,
+                    "  {:<NAME_WIDTH$} {:>6}",
+                    asset.name.display(),
+                    asset.bytes
+                )?;
+            }
+
+            writeln!(
+                f,
+                "  {:<NAME_WIDTH$} {}",
+                "total",
+                usage(self.total(Some(category)), budget)
+            )?;
+        }
+
+        write!(
+            f,
+            "{:<w$} {}",
+            "total",
+            usage(self.total(None), self.budgets.total),
+            w = NAME_WIDTH + 2
+        )
+    }
+}
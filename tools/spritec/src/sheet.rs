@@ -0,0 +1,170 @@
+//! Sprite sheets: a PNG of equally sized frames on a grid, described by a
+//! `<name>.sheet.json` sidecar next to it:
+//!
+//! ```json
+//! { "frame_width": 16, "frame_height": 16, "frames": 6, "durations": [8, 8, 8, 8, 8, 16] }
+//! ```
+//!
+//! `frames` defaults to every cell of the grid; `durations` (in ticks) may
+//! list one value per frame or a single value for all of them. Frames are
+//! read left to right, then top to bottom, and written back to back into the
+//! sheet's `.2bpp` together with a `<name>.anim.json` manifest.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::convert::{self, IndexedImage};
+use crate::error::{Error, Result};
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Sheet {
+    frame_width: usize,
+    frame_height: usize,
+    frames: Option<usize>,
+    #[serde(default = "default_durations")]
+    durations: Vec<u32>,
+}
+
+fn default_durations() -> Vec<u32> {
+    vec![1]
+}
+
+/// Where the sidecar for `png` lives.
+pub fn sidecar_path(png: &Path) -> PathBuf {
+    png.with_extension("sheet.json")
+}
+
+impl Sheet {
+    /// Parse a sidecar's contents; `path` is only used in errors.
+    pub fn parse(path: &Path, text: &[u8]) -> Result<Self> {
+        let error = |message: String| Error::Config {
+            path: path.to_path_buf(),
+            message,
+        };
+
+        let sheet: Self = serde_json::from_slice(text).map_err(|err| error(err.to_string()))?;
+
+        if sheet.frame_width == 0
+            || sheet.frame_height == 0
+            || !sheet.frame_width.is_multiple_of(8)
+            || !sheet.frame_height.is_multiple_of(8)
+        {
+            return Err(error(format!(
+                "frame size {}x{} is not a non-zero multiple of 8",
+                sheet.frame_width, sheet.frame_height
+            )));
+        }
+
+        if sheet.durations.is_empty() {
+            return Err(error("`durations` is empty".to_string()));
+        }
+
+        Ok(sheet)
+    }
+
+    /// Read the sidecar for `png`, if it has one.
+    pub fn load(png: &Path) -> Result<Option<Self>> {
+        let path = sidecar_path(png);
+
+        match fs::read(&path) {
+            Ok(text) => Self::parse(&path, &text).map(Some),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(Error::io(&path, err)),
+        }
+    }
+
+    /// Slice `image` into frames and encode them back to back. Returns the
+    /// tile data and the manifest to write next to it.
+    pub fn encode(&self, path: &Path, image: &IndexedImage) -> Result<(Vec<u8>, Manifest)> {
+        let error = |message: String| Error::Config {
+            path: sidecar_path(path),
+            message,
+        };
+
+        let columns = image.width / self.frame_width;
+        let rows = image.height / self.frame_height;
+
+        if columns * self.frame_width != image.width || rows * self.frame_height != image.height {
+            return Err(error(format!(
+                "{}x{} image is not a whole grid of {}x{} frames",
+                image.width, image.height, self.frame_width, self.frame_height
+            )));
+        }
+
+        let frame_count = self.frames.unwrap_or(columns * rows);
+
+        if frame_count == 0 || frame_count > columns * rows {
+            return Err(error(format!(
+                "{frame_count} frames do not fit a {columns}x{rows} grid"
+            )));
+        }
+
+        let durations = match self.durations.len() {
+            1 => vec![self.durations[0]; frame_count],
+            len if len == frame_count => self.durations.clone(),
+            len => {
+                return Err(error(format!("{len} durations for {frame_count} frames")));
+            }
+        };
+
+        let tiles_x = self.frame_width / 8;
+        let tiles_y = self.frame_height / 8;
+        let mut data = Vec::with_capacity(frame_count * tiles_x * tiles_y * 16);
+
+        for frame in 0..frame_count {
+            let (column, row) = (frame % columns, frame / columns);
+
+            for ty in 0..tiles_y {
+                for tx in 0..tiles_x {
+                    data.extend_from_slice(&convert::encode_tile(
+                        image,
+                        column * tiles_x + tx,
+                        row * tiles_y + ty,
+                    ));
+                }
+            }
+        }
+
+        let manifest = Manifest {
+            frame_width: self.frame_width,
+            frame_height: self.frame_height,
+            frame_count,
+            frame_bytes: tiles_x * tiles_y * 16,
+            durations,
+        };
+
+        Ok((data, manifest))
+    }
+}
+
+/// Describes a converted sheet for the runtime's animation tables.
+pub struct Manifest {
+    pub frame_width: usize,
+    pub frame_height: usize,
+    pub frame_count: usize,
+    /// Bytes per frame in the `.2bpp` data; frame `i` starts at
+    /// `i * frame_bytes`.
+    pub frame_bytes: usize,
+    pub durations: Vec<u32>,
+}
+
+impl Manifest {
+    /// The manifest as `.anim.json` contents, naming `data` as the file
+    /// holding the frames.
+    pub fn to_json(&self, data: &str) -> Vec<u8> {
+        let manifest = serde_json::json!({
+            "version": 1,
+            "data": data,
+            "frame_width": self.frame_width,
+            "frame_height": self.frame_height,
+            "frame_count": self.frame_count,
+            "frame_bytes": self.frame_bytes,
+            "durations": self.durations,
+        });
+
+        format!("{manifest:#}\n").into_bytes()
+    }
+}
@@ -0,0 +1,138 @@
+//! Tilemap export: slices a large image into a deduplicated tileset and a
+//! map of tile indices with flip attributes. See `docs/sprite_format.md`.
+
+use std::path::{Path, PathBuf};
+
+use crate::convert::{self, IndexedImage};
+use crate::error::{Error, Result};
+use crate::pipeline::Output;
+
+/// Most distinct tiles a tileset can hold; map cells store an 8-bit index.
+pub const MAX_TILES: usize = 256;
+
+/// Map attribute bit: draw the tile mirrored left-to-right.
+pub const ATTR_FLIP_X: u8 = 1 << 5;
+/// Map attribute bit: draw the tile mirrored top-to-bottom.
+pub const ATTR_FLIP_Y: u8 = 1 << 6;
+
+pub struct Tilemap {
+    /// Width and height in tiles.
+    pub width: usize,
+    pub height: usize,
+    pub tiles: Vec<[u8; 16]>,
+    /// `(tile index, attributes)` per cell, row-major.
+    pub cells: Vec<(u8, u8)>,
+}
+
+fn flip_x(tile: &[u8; 16]) -> [u8; 16] {
+    tile.map(u8::reverse_bits)
+}
+
+fn flip_y(tile: &[u8; 16]) -> [u8; 16] {
+    let mut flipped = [0; 16];
+
+    for row in 0..8 {
+        flipped[row * 2..row * 2 + 2].copy_from_slice(&tile[(7 - row) * 2..(7 - row) * 2 + 2]);
+    }
+
+    flipped
+}
+
+impl Tilemap {
+    /// Slice `image` into tiles, reusing an existing tile when a cell matches
+    /// it directly or mirrored. Fails if more than `MAX_TILES` distinct tiles
+    /// remain.
+    pub fn build(path: &Path, image: &IndexedImage) -> Result<Self> {
+        let mut tiles: Vec<[u8; 16]> = Vec::new();
+        let mut cells = Vec::with_capacity(image.tiles_x() * image.tiles_y());
+
+        for ty in 0..image.tiles_y() {
+            for tx in 0..image.tiles_x() {
+                let tile = convert::encode_tile(image, tx, ty);
+                let variants = [
+                    (tile, 0),
+                    (flip_x(&tile), ATTR_FLIP_X),
+                    (flip_y(&tile), ATTR_FLIP_Y),
+                    (flip_y(&flip_x(&tile)), ATTR_FLIP_X | ATTR_FLIP_Y),
+                ];
+
+                let found = variants.iter().find_map(|(variant, attributes)| {
+                    tiles
+                        .iter()
+                        .position(|existing| existing == variant)
+                        .map(|index| (index, *attributes))
+                });
+
+                let (index, attributes) = match found {
+                    Some(found) => found,
+                    None => {
+                        tiles.push(tile);
+                        (tiles.len() - 1, 0)
+                    }
+                };
+
+                if index >= MAX_TILES {
+                    return Err(Error::Limit {
+                        path: path.to_path_buf(),
+                        message: format!(
+                            "tile ({tx}, {ty}) needs more than {MAX_TILES} distinct tiles"
+                        ),
+                    });
+                }
+
+                cells.push((index as u8, attributes));
+            }
+        }
+
+        Ok(Self {
+            width: image.tiles_x(),
+            height: image.tiles_y(),
+            tiles,
+            cells,
+        })
+    }
+
+    /// The `.map`, `.tiles` and `.tilemap.json` sidecar outputs, at
+    /// `paths` in that order.
+    pub fn outputs(&self, paths: &[PathBuf]) -> Vec<Output> {
+        let [map_path, tiles_path, meta_path] = paths else {
+            unreachable!("a tilemap has three outputs");
+        };
+
+        let file_name = |path: &Path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+
+        let map = self
+            .cells
+            .iter()
+            .flat_map(|&(index, attributes)| [index, attributes])
+            .collect();
+
+        let meta = serde_json::json!({
+            "version": 1,
+            "width": self.width,
+            "height": self.height,
+            "tile_count": self.tiles.len(),
+            "tiles": file_name(tiles_path),
+            "map": file_name(map_path),
+        });
+
+        vec![
+            Output {
+                path: map_path.clone(),
+                bytes: map,
+            },
+            Output {
+                path: tiles_path.clone(),
+                bytes: self.tiles.as_flattened().to_vec(),
+            },
+            Output {
+                path: meta_path.clone(),
+                bytes: format!("{meta:#}\n").into_bytes(),
+            },
+        ]
+    }
+}
@@ -0,0 +1,398 @@
+//! `spritec unused`: finds dead data to prune.
+//!
+//! - Manifest entries whose generated constant no source file mentions. A
+//!   sprite only used as a frame of a referenced animation counts as used,
+//!   and so does one the entity table names as a `sprite` or `script`.
+//! - Converted files in the output directory that the manifest does not
+//!   list, so the game does not embed them.
+//! - Per tileset, how often each tile is used by the maps and levels that
+//!   draw with it, as a heatmap, and which tiles are never used. A
+//!   `.tilemap.json` names its own tileset; `.lvl` levels use `--tiles`.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::Options;
+use crate::decode::{TILE_BYTES, TilemapManifest};
+use crate::error::{Error, Result};
+use crate::pipeline::collect_files;
+use crate::report::Category;
+
+/// Tiles per heatmap row.
+const HEATMAP_WIDTH: usize = 16;
+
+/// Heatmap cells from least to most used, relative to the busiest tile.
+const SHADES: &[u8] = b".:+*#@";
+
+/// Level header fields this needs; see `docs/level_format.md`.
+const LEVEL_MAGIC: &[u8; 4] = b"BBLV";
+const LEVEL_HEADER_SIZE: usize = 16;
+
+/// One line of `assets/assets.manifest`.
+struct Entry {
+    name: String,
+    /// Converted files it includes; none for animations.
+    files: Vec<String>,
+    /// Sprites an animation plays.
+    frames: Vec<String>,
+}
+
+struct TileUsage {
+    /// Uses of each tile.
+    counts: Vec<usize>,
+    /// Maps and levels counted.
+    maps: usize,
+}
+
+/// Outcome of an unused-data run. `Display` prints the findings.
+pub struct Report {
+    /// `what: why` for each unreferenced asset.
+    unreferenced: Vec<String>,
+    tilesets: BTreeMap<PathBuf, TileUsage>,
+    /// Levels found without `--tiles` to count them against.
+    skipped_levels: usize,
+    pub errors: Vec<Error>,
+}
+
+impl Report {
+    pub fn unused_tiles(&self) -> usize {
+        self.tilesets
+            .values()
+            .flat_map(|usage| &usage.counts)
+            .filter(|&&count| count == 0)
+            .count()
+    }
+}
+
+pub fn run(options: &Options) -> Report {
+    let mut report = Report {
+        unreferenced: Vec::new(),
+        tilesets: BTreeMap::new(),
+        skipped_levels: 0,
+        errors: Vec::new(),
+    };
+
+    if let Err(err) = find_unreferenced(options, &mut report) {
+        report.errors.push(err);
+    }
+
+    let maps = collect_files(&options.input, options.recursive, |path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        name.ends_with(".tilemap.json") || name.ends_with(".lvl")
+    });
+
+    let maps = match maps {
+        Ok(maps) => maps,
+        Err(err) => {
+            report.errors.push(err);
+            return report;
+        }
+    };
+
+    for map in &maps {
+        let counted = if map.path.extension().is_some_and(|ext| ext == "lvl") {
+            match &options.level_tiles {
+                Some(tiles) => count_level(&map.path, tiles, &mut report),
+                None => {
+                    report.skipped_levels += 1;
+                    Ok(())
+                }
+            }
+        } else {
+            count_tilemap(&map.path, &mut report)
+        };
+
+        if let Err(err) = counted {
+            report.errors.push(err);
+        }
+    }
+
+    report
+}
+
+fn find_unreferenced(options: &Options, report: &mut Report) -> Result<()> {
+    let entries = read_manifest(&options.manifest)?;
+    let mut identifiers = source_identifiers(&options.sources)?;
+    identifiers.extend(entity_references(&options.entities)?);
+
+    let mut used: HashSet<&str> = entries
+        .iter()
+        .map(|entry| entry.name.as_str())
+        .filter(|name| identifiers.contains(*name))
+        .collect();
+
+    for entry in &entries {
+        if used.contains(entry.name.as_str()) {
+            used.extend(entry.frames.iter().map(String::as_str));
+        }
+    }
+
+    for entry in entries
+        .iter()
+        .filter(|entry| !used.contains(entry.name.as_str()))
+    {
+        let what = match entry.files.as_slice() {
+            [] => entry.name.clone(),
+            files => format!("{} ({})", entry.name, files.join(", ")),
+        };
+
+        report
+            .unreferenced
+            .push(format!("{what}: not used in the sources"));
+    }
+
+    let listed: HashSet<&str> = entries
+        .iter()
+        .flat_map(|entry| entry.files.iter().map(String::as_str))
+        .collect();
+    let converted = collect_files(&options.input, options.recursive, |path| {
+        Category::of(path).is_some()
+    })?;
+
+    for file in converted {
+        let name = file.path.strip_prefix(&options.input).unwrap_or(&file.path);
+        let name = name.to_string_lossy().replace('\\', "/");
+
+        if !listed.contains(name.as_str()) {
+            report
+                .unreferenced
+                .push(format!("{name}: not in the manifest"));
+        }
+    }
+
+    Ok(())
+}
+
+/// The entries of the asset manifest. Only the names and files are read;
+/// `build.rs` validates the rest.
+fn read_manifest(path: &Path) -> Result<Vec<Entry>> {
+    let text = fs::read_to_string(path).map_err(|err| Error::io(path, err))?;
+    let mut entries = Vec::new();
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default();
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        let entry = match words[..] {
+            [] => continue,
+            ["sprite", name, file, _] | ["data", name, file] => Entry {
+                name: name.to_string(),
+                files: vec![file.to_string()],
+                frames: Vec::new(),
+            },
+            ["tilemap", name, stem, _] => Entry {
+                name: name.to_string(),
+                files: vec![format!("{stem}.tiles"), format!("{stem}.map")],
+                frames: Vec::new(),
+            },
+            ["anim", name, ref frames @ ..] => Entry {
+                name: name.to_string(),
+                files: Vec::new(),
+                frames: frames
+                    .iter()
+                    .map(|frame| frame.split(':').next().unwrap_or_default().to_string())
+                    .collect(),
+            },
+            _ => {
+                return Err(Error::Config {
+                    path: path.to_path_buf(),
+                    message: format!("line {}: unknown entry `{}`", number + 1, line.trim()),
+                });
+            }
+        };
+
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Every identifier-like word in the `.rs` files under `sources`.
+fn source_identifiers(sources: &[PathBuf]) -> Result<HashSet<String>> {
+    let mut identifiers = HashSet::new();
+
+    for source in sources {
+        let files = collect_files(source, true, |path| {
+            path.extension().is_some_and(|ext| ext == "rs")
+        })?;
+
+        for file in files {
+            let text = fs::read_to_string(&file.path).map_err(|err| Error::io(&file.path, err))?;
+
+            identifiers.extend(
+                text.split(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .filter(|word| !word.is_empty())
+                    .map(str::to_string),
+            );
+        }
+    }
+
+    Ok(identifiers)
+}
+
+/// The `sprite` and `script` values of the entity table, which `build.rs`
+/// turns into references to the constants they name. A missing table has
+/// none.
+fn entity_references(path: &Path) -> Result<Vec<String>> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(Error::io(path, err)),
+    };
+
+    let references = text
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .filter_map(|line| line.split_once('='))
+        .filter(|(key, _)| matches!(key.trim(), "sprite" | "script"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+        .collect();
+
+    Ok(references)
+}
+
+/// Count the tiles a `.tilemap.json` map uses from its own tileset.
+fn count_tilemap(manifest_path: &Path, report: &mut Report) -> Result<()> {
+    let text = fs::read(manifest_path).map_err(|err| Error::io(manifest_path, err))?;
+    let manifest: TilemapManifest = serde_json::from_slice(&text).map_err(|err| Error::Config {
+        path: manifest_path.to_path_buf(),
+        message: err.to_string(),
+    })?;
+
+    let dir = manifest_path.parent().unwrap_or(Path::new(""));
+    let map_path = dir.join(&manifest.map);
+    let map = fs::read(&map_path).map_err(|err| Error::io(&map_path, err))?;
+
+    // Cells are (tile index, attributes) pairs.
+    let cells = map.chunks_exact(2).map(|cell| cell[0]);
+
+    count(report, &dir.join(&manifest.tiles), manifest_path, cells)
+}
+
+/// Count the tiles a `.lvl` level paints from `tiles`.
+fn count_level(path: &Path, tiles: &Path, report: &mut Report) -> Result<()> {
+    let bytes = fs::read(path).map_err(|err| Error::io(path, err))?;
+    let field = |offset: usize| u16::from_le_bytes([bytes[offset], bytes[offset + 1]]) as usize;
+
+    if bytes.len() < LEVEL_HEADER_SIZE || &bytes[..4] != LEVEL_MAGIC {
+        return Err(Error::Config {
+            path: path.to_path_buf(),
+            message: "not a level file".to_string(),
+        });
+    }
+
+    let cells = field(6) * field(8);
+    let map = bytes
+        .get(LEVEL_HEADER_SIZE..LEVEL_HEADER_SIZE + cells)
+        .ok_or_else(|| Error::Config {
+            path: path.to_path_buf(),
+            message: "tile map is cut short".to_string(),
+        })?;
+
+    count(report, tiles, path, map.iter().copied())
+}
+
+fn count(
+    report: &mut Report,
+    tiles_path: &Path,
+    map_path: &Path,
+    cells: impl Iterator<Item = u8>,
+) -> Result<()> {
+    let usage = match report.tilesets.get_mut(tiles_path) {
+        Some(usage) => usage,
+        None => {
+            let tiles = fs::metadata(tiles_path).map_err(|err| Error::io(tiles_path, err))?;
+
+            report
+                .tilesets
+                .entry(tiles_path.to_path_buf())
+                .or_insert(TileUsage {
+                    counts: vec![0; tiles.len() as usize / TILE_BYTES],
+                    maps: 0,
+                })
+        }
+    };
+
+    usage.maps += 1;
+
+    let tile_count = usage.counts.len();
+
+    for tile in cells {
+        let count = usage
+            .counts
+            .get_mut(tile as usize)
+            .ok_or_else(|| Error::Limit {
+                path: map_path.to_path_buf(),
+                message: format!(
+                    "uses tile {tile}, but {} has {tile_count} tiles",
+                    tiles_path.display()
+                ),
+            })?;
+
+        *count += 1;
+    }
+
+    Ok(())
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.unreferenced.is_empty() {
+            writeln!(f, "unreferenced assets")?;
+
+            for line in &self.unreferenced {
+                writeln!(f, "  {line}")?;
+            }
+        }
+
+        for (path, usage) in &self.tilesets {
+            let max = usage.counts.iter().copied().max().unwrap_or(0);
+
+            writeln!(
+                f,
+                "tile usage of {} ({} maps, busiest tile used {max} times)",
+                path.display(),
+                usage.maps
+            )?;
+            writeln!(f, "     0123456789ABCDEF")?;
+
+            for (row, counts) in usage.counts.chunks(HEATMAP_WIDTH).enumerate() {
+                let cells: String = counts
+                    .iter()
+                    .map(|&count| match count {
+                        0 => '-',
+                        _ => SHADES[(count * SHADES.len() - 1) / max] as char,
+                    })
+                    .collect();
+
+                writeln!(f, "  {:02X} {cells}", row * HEATMAP_WIDTH)?;
+            }
+
+            let unused: Vec<String> = (0..usage.counts.len())
+                .filter(|&tile| usage.counts[tile] == 0)
+                .map(|tile| format!("{tile:02X}"))
+                .collect();
+
+            if !unused.is_empty() {
+                writeln!(f, "  unused: {}", unused.join(" "))?;
+            }
+        }
+
+        if self.skipped_levels > 0 {
+            writeln!(
+                f,
+                "{} levels skipped: pass `--tiles` with the tileset they paint with",
+                self.skipped_levels
+            )?;
+        }
+
+        write!(
+            f,
+            "{} unreferenced assets, {} unused tiles",
+            self.unreferenced.len(),
+            self.unused_tiles()
+        )
+    }
+}
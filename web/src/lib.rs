@@ -0,0 +1,45 @@
+//! Browser build of the game. Compiles to nothing except on `wasm32`.
+//!
+//! ```text
+//! cargo build -p bit-bound-web --target wasm32-unknown-unknown --release
+//! wasm-bindgen --target web --out-dir web/pkg \
+//!     target/wasm32-unknown-unknown/release/bit_bound_web.wasm
+//! ```
+//!
+//! then serve `web/` and open `index.html`.
+
+#![cfg(target_arch = "wasm32")]
+
+use bit_bound::game::{GameState, render, update};
+use bit_bound::platform::Platform;
+use bit_bound::platform::web::{self, WebPlatform};
+use bit_bound::runtime::Runtime;
+use wasm_bindgen::prelude::*;
+
+/// Id of the `<canvas>` in `index.html`.
+const CANVAS_ID: &str = "screen";
+
+#[wasm_bindgen(start)]
+pub fn start() -> Result<(), JsValue> {
+    let Runtime {
+        memory,
+        framebuffer,
+    } = Runtime::take().ok_or("runtime already claimed")?;
+
+    let platform = WebPlatform::new(CANVAS_ID)?;
+    let mut state = GameState::new();
+
+    web::run(platform, move |platform| {
+        // Paused while the page is in the background; browsers already
+        // slow animation frames down there.
+        if platform.has_focus() {
+            update(&mut state, platform.poll_input(), memory);
+            memory.frame.reset();
+        }
+
+        render(&state, framebuffer);
+        platform.present(framebuffer);
+    });
+
+    Ok(())
+}